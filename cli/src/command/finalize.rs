@@ -15,13 +15,17 @@
 //! Standard Exonum CLI command used to combine a private and all the public parts of the
 //! node configuration in a single file.
 
-use exonum::{blockchain::ConsensusConfig, crypto::PublicKey};
+use exonum::{blockchain::ConsensusConfig, crypto::PublicKey, helpers::ValidateInput};
 use exonum_node::{ConnectInfo, ConnectListConfig, NodeApiConfig};
 use failure::{bail, ensure, format_err, Error};
 use serde_derive::{Deserialize, Serialize};
 use structopt::StructOpt;
 
-use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    path::PathBuf,
+};
 
 use crate::{
     command::{ExonumCommand, StandardResult},
@@ -69,10 +73,12 @@ impl Finalize {
     fn validate_configs(configs: Vec<NodePublicConfig>) -> Result<ValidatedConfigs, Error> {
         let mut config_iter = configs.into_iter();
         let mut public_configs = BTreeMap::new();
+        let mut addresses = BTreeSet::new();
         let first = config_iter
             .next()
             .ok_or_else(|| format_err!("Expected at least one config in <public-configs>"))?;
         let consensus_key = Self::get_consensus_key(&first)?;
+        addresses.insert(Self::get_address(&first)?);
         public_configs.insert(consensus_key, first.clone());
 
         for config in config_iter {
@@ -93,6 +99,13 @@ impl Finalize {
                 config.general
             );
 
+            let address = Self::get_address(&config)?;
+            ensure!(
+                addresses.insert(address.clone()),
+                "Found duplicated peer addresses in <public-configs>: {}",
+                address
+            );
+
             let consensus_key = Self::get_consensus_key(&config)?;
             if public_configs.insert(consensus_key, config).is_some() {
                 bail!(
@@ -114,6 +127,13 @@ impl Finalize {
             .consensus_key)
     }
 
+    fn get_address(config: &NodePublicConfig) -> Result<String, failure::Error> {
+        config
+            .address
+            .clone()
+            .ok_or_else(|| format_err!("Expected peer address in public config: {:#?}", config))
+    }
+
     fn create_connect_list_config(
         public_configs: &[NodePublicConfig],
         private_config: &NodePrivateConfig,
@@ -126,10 +146,15 @@ impl Finalize {
             .map(|config| ConnectInfo {
                 public_key: Self::get_consensus_key(config).unwrap(),
                 address: config.address.clone().unwrap(),
+                network_key: None,
+                consensus_key: None,
             })
             .collect();
 
-        ConnectListConfig { peers }
+        ConnectListConfig {
+            peers,
+            dns_seeds: Vec::new(),
+        }
     }
 }
 
@@ -166,6 +191,9 @@ impl ExonumCommand for Finalize {
                 .collect(),
             ..common.consensus
         };
+        consensus
+            .validate()
+            .map_err(|e| format_err!("Assembled consensus configuration is invalid: {}", e))?;
 
         let connect_list = Self::create_connect_list_config(&public_configs, &private_config);
 
@@ -186,6 +214,8 @@ impl ExonumCommand for Finalize {
             thread_pool_size: private_config.thread_pool_size,
             connect_list,
             keys: private_config.keys,
+            network_key_path: private_config.network_key_path,
+            network_keys: private_config.network_keys,
         };
         let public_config = NodePublicConfig {
             consensus,
@@ -74,10 +74,11 @@ pub struct GenerateConfig {
     pub no_password: bool,
     /// Passphrase entry method for master key.
     ///
-    /// Possible values are: `stdin`, `env{:ENV_VAR_NAME}`, `pass:PASSWORD`.
+    /// Possible values are: `stdin`, `env{:ENV_VAR_NAME}`, `pass:PASSWORD`, `cmd:COMMAND`.
     /// Default Value is `stdin`.
     /// If `ENV_VAR_NAME` is not specified `$EXONUM_MASTER_PASS` is used
-    /// by default.
+    /// by default. `COMMAND` is run to obtain the passphrase from its standard output
+    /// (e.g., to fetch it from a key management service).
     #[structopt(long)]
     pub master_key_pass: Option<PassInputMethod>,
     /// Path to the master key file. If empty, file will be placed to <output_dir>.
@@ -164,6 +165,8 @@ impl ExonumCommand for GenerateConfig {
             thread_pool_size: Default::default(),
             connect_list: Default::default(),
             keys,
+            network_key_path: None,
+            network_keys: None,
         };
 
         save_config_file(&private_config, &private_config_path)?;
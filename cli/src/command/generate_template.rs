@@ -14,6 +14,7 @@
 
 //! Standard Exonum CLI command used to generate common configuration file.
 
+use exonum::blockchain::ConsensusPreset;
 use exonum_supervisor::mode::Mode as SupervisorMode;
 use failure::Error;
 use serde_derive::{Deserialize, Serialize};
@@ -38,12 +39,18 @@ pub struct GenerateTemplate {
     /// Supervisor service mode. Possible options are "simple" and "decentralized".
     #[structopt(long)]
     pub supervisor_mode: SupervisorMode,
+    /// Preset of consensus timeouts. Possible options are "lan" (default), "wan"
+    /// and "geo_distributed".
+    #[structopt(long)]
+    pub consensus_preset: Option<ConsensusPreset>,
 }
 
 impl ExonumCommand for GenerateTemplate {
     fn execute(self) -> Result<StandardResult, Error> {
         let config = NodePublicConfig {
-            consensus: Default::default(),
+            consensus: self
+                .consensus_preset
+                .map_or_else(Default::default, ConsensusPreset::consensus_config),
             general: GeneralConfig {
                 validators_count: self.validators_count,
                 supervisor_mode: self.supervisor_mode,
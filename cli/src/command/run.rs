@@ -59,10 +59,11 @@ pub struct Run {
     pub private_api_address: Option<SocketAddr>,
     /// Passphrase entry method for master key.
     ///
-    /// Possible values are: `stdin`, `env{:ENV_VAR_NAME}`, `pass:PASSWORD`.
+    /// Possible values are: `stdin`, `env{:ENV_VAR_NAME}`, `pass:PASSWORD`, `cmd:COMMAND`.
     /// Default Value is `stdin`.
     /// If `ENV_VAR_NAME` is not specified `$EXONUM_MASTER_PASS` is used
-    /// by default.
+    /// by default. `COMMAND` is run to obtain the passphrase from its standard output
+    /// (e.g., to fetch it from a key management service).
     #[structopt(long)]
     pub master_key_pass: Option<PassInputMethod>,
 }
@@ -20,7 +20,7 @@ use failure::{Error, ResultExt};
 use serde_derive::{Deserialize, Serialize};
 use structopt::StructOpt;
 
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{env, fs, path::PathBuf, str::FromStr};
 
 use crate::command::{
     finalize::Finalize,
@@ -30,17 +30,37 @@ use crate::command::{
     ExonumCommand, StandardResult,
 };
 
+/// Name of the directory within the system temporary directory used to store artifacts
+/// if the artifacts directory is not specified.
+const DEFAULT_ARTIFACTS_DIR: &str = "exonum-run-dev";
+/// Name of the node configuration file.
+const NODE_CONFIG_FILE_NAME: &str = "node.toml";
+
 /// Run application in development mode (generate configuration and db files automatically).
 #[derive(StructOpt, Debug, Serialize, Deserialize)]
 pub struct RunDev {
     /// The path where configuration and db files will be generated.
+    ///
+    /// If not specified, the `exonum-run-dev` directory within the system temporary
+    /// directory is used.
     #[structopt(long, short = "a")]
-    pub artifacts_dir: PathBuf,
+    pub artifacts_dir: Option<PathBuf>,
+    /// Keep the blockchain state and the node configuration from the previous run.
+    ///
+    /// By default, the state is wiped and a new blockchain is started on each run.
+    #[structopt(long)]
+    pub keep_state: bool,
 }
 
 impl RunDev {
+    fn artifacts_dir(&self) -> PathBuf {
+        self.artifacts_dir
+            .clone()
+            .unwrap_or_else(|| env::temp_dir().join(DEFAULT_ARTIFACTS_DIR))
+    }
+
     fn artifact_path(&self, artifact_name: &str) -> PathBuf {
-        let mut path = self.artifacts_dir.clone();
+        let mut path = self.artifacts_dir();
         path.push(artifact_name);
         path
     }
@@ -48,29 +68,26 @@ impl RunDev {
     fn cleanup(&self) -> Result<(), Error> {
         let database_dir = self.artifact_path("db");
         if database_dir.exists() {
-            fs::remove_dir_all(self.artifacts_dir.clone())
+            fs::remove_dir_all(self.artifacts_dir())
                 .context("Expected DATABASE_PATH folder being removable.")?;
         }
         Ok(())
     }
-}
-
-impl ExonumCommand for RunDev {
-    fn execute(self) -> Result<StandardResult, Error> {
-        self.cleanup()?;
 
+    fn generate_node_config(&self) -> Result<(), Error> {
         let common_config = self.artifact_path("template.toml");
 
         let generate_template = GenerateTemplate {
             common_config: common_config.clone(),
             validators_count: 1,
             supervisor_mode: SupervisorMode::Simple,
+            consensus_preset: None,
         };
         generate_template.execute()?;
 
         let generate_config = GenerateConfig {
             common_config: common_config.clone(),
-            output_dir: self.artifacts_dir.clone(),
+            output_dir: self.artifacts_dir(),
             peer_address: "127.0.0.1:6200".parse().unwrap(),
             listen_address: None,
             no_password: true,
@@ -79,11 +96,9 @@ impl ExonumCommand for RunDev {
         };
         generate_config.execute()?;
 
-        let node_config_file_name = "node.toml";
-
         let finalize = Finalize {
             private_config_path: self.artifact_path(PRIVATE_CONFIG_FILE_NAME),
-            output_config_path: self.artifact_path(node_config_file_name),
+            output_config_path: self.artifact_path(NODE_CONFIG_FILE_NAME),
             public_configs: vec![self.artifact_path(PUBLIC_CONFIG_FILE_NAME)],
             public_api_address: Some("127.0.0.1:8080".parse().unwrap()),
             private_api_address: Some("127.0.0.1:8081".parse().unwrap()),
@@ -91,9 +106,20 @@ impl ExonumCommand for RunDev {
             private_allow_origin: Some("http://127.0.0.1:8081, http://localhost:8081".to_string()),
         };
         finalize.execute()?;
+        Ok(())
+    }
+}
+
+impl ExonumCommand for RunDev {
+    fn execute(self) -> Result<StandardResult, Error> {
+        let node_config = self.artifact_path(NODE_CONFIG_FILE_NAME);
+        if !self.keep_state || !node_config.exists() {
+            self.cleanup()?;
+            self.generate_node_config()?;
+        }
 
         let run = Run {
-            node_config: self.artifact_path(node_config_file_name),
+            node_config,
             db_path: self.artifact_path("db"),
             public_api_address: None,
             private_api_address: None,
@@ -16,8 +16,9 @@
 
 use exonum::{
     blockchain::{ConsensusConfig, ValidatorKeys},
+    crypto::KeyPair,
     exonum_merkledb::DbOptions,
-    keys::{read_keys_from_file, Keys},
+    keys::{read_keys_from_file, read_network_keys, Keys},
 };
 use exonum_node::{
     ConnectListConfig, MemoryPoolConfig, NetworkConfiguration, NodeApiConfig,
@@ -82,6 +83,15 @@ pub struct NodePrivateConfig {
     /// Validator keys.
     #[serde(skip)]
     pub keys: Keys,
+    /// Path to the file with the key pair used to authenticate the node in connections
+    /// with peers. If not set, the consensus key pair is used for this purpose.
+    ///
+    /// The file is created next to the node configuration once the network key is rotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_key_path: Option<PathBuf>,
+    /// Network key pair read from `network_key_path`.
+    #[serde(skip)]
+    pub network_keys: Option<KeyPair>,
 }
 
 /// Configuration for the `Node`.
@@ -110,7 +120,7 @@ impl Into<CoreNodeConfig> for NodeConfig {
 }
 
 impl NodeConfig {
-    /// Read validator keys from the encrypted file.
+    /// Read validator keys from the encrypted file and the network keys, if any.
     pub fn read_secret_keys(
         &mut self,
         config_file_path: impl AsRef<Path>,
@@ -126,9 +136,14 @@ impl NodeConfig {
 
         let keys = read_keys_from_file(&master_key_path, master_key_passphrase)
             .expect("Could not read master_key_path from file");
+        let network_keys = self.private_config.network_key_path.as_ref().map(|path| {
+            read_network_keys(config_folder.join(path))
+                .expect("Could not read network_key_path from file")
+        });
 
         self.private_config = NodePrivateConfig {
             keys,
+            network_keys,
             ..self.private_config.clone()
         };
     }
@@ -14,11 +14,16 @@
 
 //! Updating node configuration on the fly.
 
+use exonum::{crypto::KeyPair, keys::save_network_keys};
 use exonum_node::{ConfigManager, ConnectListConfig};
 use failure;
 use log::error;
 
-use std::{path::Path, sync::mpsc, thread};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
 
 use crate::{
     config::NodeConfig,
@@ -31,9 +36,18 @@ pub struct DefaultConfigManager {
     tx: mpsc::Sender<UpdateRequest>,
 }
 
+/// Name of the file with the network key pair, which is created next to the node
+/// configuration if the configuration does not specify `network_key_path`.
+const NETWORK_KEY_FILE: &str = "network.key.toml";
+
 /// Messages for ConfigManager.
 #[derive(Debug)]
-pub struct UpdateRequest(ConnectListConfig);
+pub enum UpdateRequest {
+    /// Update the connect list.
+    ConnectList(ConnectListConfig),
+    /// Save the rotated network key pair.
+    NetworkKeys(KeyPair),
+}
 
 impl DefaultConfigManager {
     /// Creates a new `ConfigManager` instance for the given path.
@@ -43,8 +57,13 @@ impl DefaultConfigManager {
     {
         let (tx, rx) = mpsc::channel();
         thread::spawn(move || {
-            for UpdateRequest(connect_list) in rx {
-                let res = Self::update_connect_list(connect_list, &path);
+            for request in rx {
+                let res = match request {
+                    UpdateRequest::ConnectList(connect_list) => {
+                        Self::update_connect_list(connect_list, &path)
+                    }
+                    UpdateRequest::NetworkKeys(keys) => Self::update_network_keys(&keys, &path),
+                };
 
                 if let Err(ref error) = res {
                     error!("Unable to update config: {}", error);
@@ -71,31 +90,66 @@ impl DefaultConfigManager {
 
         Ok(())
     }
+
+    // Saves the network key pair on file system synchronously and refers to the key file
+    // in the node configuration.
+    // This method is public only for testing and should not be used explicitly.
+    #[doc(hidden)]
+    pub fn update_network_keys<P>(keys: &KeyPair, path: &P) -> Result<(), failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut current_config: NodeConfig = load_config_file(path)?;
+        let config_folder = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+        let network_key_path = current_config
+            .private_config
+            .network_key_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(NETWORK_KEY_FILE));
+        save_network_keys(config_folder.join(&network_key_path), keys)?;
+
+        if current_config.private_config.network_key_path.is_none() {
+            current_config.private_config.network_key_path = Some(network_key_path);
+            save_config_file(&current_config, path)?;
+        }
+        Ok(())
+    }
 }
 
 impl ConfigManager for DefaultConfigManager {
     /// Stores updated connect list at file system.
     fn store_connect_list(&mut self, connect_list: ConnectListConfig) {
         self.tx
-            .send(UpdateRequest(connect_list))
+            .send(UpdateRequest::ConnectList(connect_list))
+            .expect("Can't message to ConfigManager thread");
+    }
+
+    /// Stores rotated network keys at file system.
+    fn store_network_keys(&mut self, keys: KeyPair) {
+        self.tx
+            .send(UpdateRequest::NetworkKeys(keys))
             .expect("Can't message to ConfigManager thread");
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use exonum::crypto::gen_keypair;
+    use exonum::{
+        crypto::{gen_keypair, KeyPair},
+        keys::read_network_keys,
+    };
     use exonum_node::{ConnectInfo, ConnectListConfig};
     use exonum_supervisor::mode::Mode;
     use tempfile::tempdir;
 
-    use super::DefaultConfigManager;
+    use std::path::PathBuf;
+
+    use super::{DefaultConfigManager, NETWORK_KEY_FILE};
     use crate::config::{GeneralConfig, NodeConfig, NodePrivateConfig, NodePublicConfig};
     use crate::io::{load_config_file, save_config_file};
 
-    #[test]
-    fn test_update_config() {
-        let config = NodeConfig {
+    fn node_config() -> NodeConfig {
+        NodeConfig {
             private_config: NodePrivateConfig {
                 listen_address: "127.0.0.1:5400".parse().unwrap(),
                 external_address: "127.0.0.1:5400".to_string(),
@@ -107,6 +161,8 @@ mod tests {
                 thread_pool_size: None,
                 connect_list: Default::default(),
                 keys: Default::default(),
+                network_key_path: None,
+                network_keys: None,
             },
             public_config: NodePublicConfig {
                 consensus: Default::default(),
@@ -117,18 +173,27 @@ mod tests {
                 validator_keys: None,
                 address: None,
             },
-        };
+        }
+    }
+
+    #[test]
+    fn test_update_config() {
         let tmp_dir = tempdir().unwrap();
         let config_path = tmp_dir.path().join("node.toml");
-        save_config_file(&config, &config_path).unwrap();
+        save_config_file(&node_config(), &config_path).unwrap();
 
         // Test config update.
         let peer = ConnectInfo {
             address: "0.0.0.1:8080".to_owned(),
             public_key: gen_keypair().0,
+            network_key: None,
+            consensus_key: None,
         };
 
-        let connect_list = ConnectListConfig { peers: vec![peer] };
+        let connect_list = ConnectListConfig {
+            peers: vec![peer],
+            dns_seeds: Vec::new(),
+        };
 
         DefaultConfigManager::update_connect_list(connect_list.clone(), &config_path)
             .expect("Unable to update connect list");
@@ -137,4 +202,28 @@ mod tests {
         let new_connect_list = config.private_config.connect_list;
         assert_eq!(new_connect_list.peers, connect_list.peers);
     }
+
+    #[test]
+    fn test_update_network_keys() {
+        let tmp_dir = tempdir().unwrap();
+        let config_path = tmp_dir.path().join("node.toml");
+        save_config_file(&node_config(), &config_path).unwrap();
+
+        let keys = KeyPair::from(gen_keypair());
+        DefaultConfigManager::update_network_keys(&keys, &config_path)
+            .expect("Unable to update network keys");
+        let config: NodeConfig = load_config_file(&config_path).unwrap();
+        assert_eq!(
+            config.private_config.network_key_path,
+            Some(PathBuf::from(NETWORK_KEY_FILE))
+        );
+        let key_path = tmp_dir.path().join(NETWORK_KEY_FILE);
+        assert_eq!(read_network_keys(&key_path).unwrap(), keys);
+
+        // The key file is overwritten on subsequent rotations.
+        let new_keys = KeyPair::from(gen_keypair());
+        DefaultConfigManager::update_network_keys(&new_keys, &config_path)
+            .expect("Unable to update network keys");
+        assert_eq!(read_network_keys(&key_path).unwrap(), new_keys);
+    }
 }
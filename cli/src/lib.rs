@@ -173,12 +173,16 @@ impl NodeBuilder {
             let node_config_path = run_config.node_config_path.to_string_lossy().to_string();
             let config_manager = DefaultConfigManager::new(node_config_path);
             let rust_runtime = self.rust_runtime;
+            let network_keys = run_config.node_config.private_config.network_keys.clone();
 
             let mut node_builder =
                 CoreNodeBuilder::new(database, run_config.node_config.into(), genesis_config)
                     .with_config_manager(config_manager)
                     .with_plugin(SystemApiPlugin)
                     .with_runtime_fn(|channel| rust_runtime.build(channel.endpoints_sender()));
+            if let Some(network_keys) = network_keys {
+                node_builder = node_builder.with_network_keys(network_keys);
+            }
             for runtime in self.external_runtimes {
                 node_builder = node_builder.with_runtime(runtime);
             }
@@ -195,6 +199,14 @@ impl NodeBuilder {
             .general
             .supervisor_mode
             .clone();
-        Supervisor::builtin_instance(SupervisorConfig { mode })
+        Supervisor::builtin_instance(SupervisorConfig {
+            mode,
+            majority_count: None,
+            min_activation_delay: 0,
+            consensus_limits: None,
+            max_removed_validators_percent: None,
+            vote_weights: Vec::new(),
+            time_service: None,
+        })
     }
 }
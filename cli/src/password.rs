@@ -14,12 +14,12 @@
 
 //! This module contains utilities for passphrase entry.
 
-use failure::{bail, Error, ResultExt};
+use failure::{bail, ensure, format_err, Error, ResultExt};
 use rpassword::read_password_from_tty;
 use serde_derive::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
-use std::{env, str::FromStr};
+use std::{env, process::Command, str::FromStr};
 
 /// Default name of the environment variable with a master key passphrase.
 pub const DEFAULT_MASTER_PASS_ENV_VAR: &str = "EXONUM_MASTER_PASS";
@@ -70,6 +70,11 @@ pub enum PassInputMethod {
     EnvVariable(Option<String>),
     /// Passphrase is passed as a command line parameter.
     CmdLineParameter(Passphrase),
+    /// Get passphrase from the standard output of the given command, e.g.,
+    /// a client of the key management service. Trailing line breaks are ignored.
+    ///
+    /// The command is split into the program name and arguments by whitespace.
+    Command(String),
 }
 
 impl Default for PassInputMethod {
@@ -113,6 +118,7 @@ impl PassInputMethod {
                 Ok(Passphrase(passphrase))
             }
             PassInputMethod::CmdLineParameter(pass) => Ok(pass),
+            PassInputMethod::Command(command) => passphrase_from_command(&command),
         }
     }
 }
@@ -134,6 +140,12 @@ impl FromStr for PassInputMethod {
             return Ok(PassInputMethod::EnvVariable(env_var));
         }
 
+        if s.starts_with("cmd:") {
+            let command = s["cmd:".len()..].trim();
+            ensure!(!command.is_empty(), "Passphrase command is empty");
+            return Ok(PassInputMethod::Command(command.to_owned()));
+        }
+
         if s.starts_with("pass") {
             let pass = s.split(':').nth(1).unwrap_or_default();
             return Ok(PassInputMethod::CmdLineParameter(Passphrase(
@@ -145,6 +157,30 @@ impl FromStr for PassInputMethod {
     }
 }
 
+/// Runs the command and returns its standard output as a passphrase.
+fn passphrase_from_command(command: &str) -> Result<Passphrase, Error> {
+    let mut words = command.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| format_err!("Passphrase command is empty"))?;
+    let output = Command::new(program)
+        .args(words)
+        .output()
+        .with_context(|e| format!("Failed to run passphrase command `{}`: {}", program, e))?;
+    ensure!(
+        output.status.success(),
+        "Passphrase command `{}` failed with {}",
+        program,
+        output.status
+    );
+
+    let mut passphrase = String::from_utf8(output.stdout)
+        .map_err(|_| format_err!("Passphrase command output is not valid UTF-8"))?;
+    let len = passphrase.trim_end_matches(&['\r', '\n'][..]).len();
+    passphrase.truncate(len);
+    Ok(Passphrase(passphrase))
+}
+
 /// Prompt user for a passphrase. The user must enter the passphrase twice.
 /// Passphrase must not be empty.
 fn prompt_passphrase(prompt: &str) -> Result<Passphrase, Error> {
@@ -169,7 +205,7 @@ fn prompt_passphrase(prompt: &str) -> Result<Passphrase, Error> {
 mod tests {
     use std::str::FromStr;
 
-    use super::{PassInputMethod, Passphrase};
+    use super::{PassInputMethod, Passphrase, PassphraseUsage};
 
     #[test]
     fn test_pass_input_method_parse() {
@@ -190,6 +226,10 @@ mod tests {
                 "pass:PASS",
                 PassInputMethod::CmdLineParameter(Passphrase("PASS".to_owned())),
             ),
+            (
+                "cmd:kms-client decrypt node.key",
+                PassInputMethod::Command("kms-client decrypt node.key".to_owned()),
+            ),
         ];
 
         for (inp, out) in correct_cases {
@@ -198,4 +238,24 @@ mod tests {
             assert_eq!(method.unwrap(), out)
         }
     }
+
+    #[test]
+    fn test_pass_input_method_parse_empty_command() {
+        let err = <PassInputMethod as FromStr>::from_str("cmd: ").unwrap_err();
+        assert!(err.to_string().contains("Passphrase command is empty"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_passphrase_from_command() {
+        let method = PassInputMethod::Command("echo secret".to_owned());
+        let passphrase = method.get_passphrase(PassphraseUsage::Using).unwrap();
+        assert_eq!(passphrase, Passphrase("secret".to_owned()));
+
+        let method = PassInputMethod::Command("false".to_owned());
+        let err = method.get_passphrase(PassphraseUsage::Using).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Passphrase command `false` failed"));
+    }
 }
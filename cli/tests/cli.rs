@@ -14,13 +14,16 @@
 
 // This is a regression test for exonum configuration.
 
-use exonum::{blockchain::ValidatorKeys, crypto::gen_keypair};
+use exonum::{
+    blockchain::{ConsensusPreset, ValidatorKeys},
+    crypto::gen_keypair,
+};
 use exonum_cli::{
     command::{
         finalize::Finalize, generate_config::GenerateConfig, generate_template::GenerateTemplate,
         run::Run, Command, ExonumCommand, StandardResult,
     },
-    config::{GeneralConfig, NodePrivateConfig, NodePublicConfig},
+    config::{GeneralConfig, NodeConfig, NodePrivateConfig, NodePublicConfig},
     io::{load_config_file, save_config_file},
     password::DEFAULT_MASTER_PASS_ENV_VAR,
 };
@@ -247,6 +250,25 @@ fn test_generate_template_simple_supervisor() {
     assert_config_files_eq(&output_template_file, env.expected_template_file());
 }
 
+#[test]
+fn test_generate_template_consensus_preset() {
+    let env = ConfigSpec::new_without_pass();
+    let output_template_file = env.output_template_file();
+    env.command("generate-template")
+        .with_arg(&output_template_file)
+        .with_named_arg("--validators-count", env.validators_count.to_string())
+        .with_named_arg("--supervisor-mode", "simple")
+        .with_named_arg("--consensus-preset", "geo_distributed")
+        .run()
+        .unwrap();
+
+    let template: NodePublicConfig = load_config_file(&output_template_file).unwrap();
+    assert_eq!(
+        template.consensus,
+        ConsensusPreset::GeoDistributed.consensus_config()
+    );
+}
+
 #[test]
 fn test_generate_config_key_files() {
     let env = ConfigSpec::new_without_pass();
@@ -440,6 +462,41 @@ fn test_full_workflow() {
     }
 }
 
+#[test]
+fn test_finalize_duplicated_addresses() {
+    let env = ConfigSpec::new("", 2);
+
+    let output_template_file = env.output_template_file();
+    env.command("generate-template")
+        .with_arg(&output_template_file)
+        .with_named_arg("--validators-count", env.validators_count.to_string())
+        .with_named_arg("--supervisor-mode", "simple")
+        .run()
+        .unwrap();
+
+    for i in 0..env.validators_count {
+        env.command("generate-config")
+            .with_arg(&output_template_file)
+            .with_arg(&env.output_node_config_dir(i))
+            .with_named_arg("-a", "0.0.0.0:8000")
+            .with_arg("--no-password")
+            .run()
+            .unwrap();
+    }
+
+    let err = env
+        .command("finalize")
+        .with_arg(env.output_private_config(0))
+        .with_arg(env.output_node_config(0))
+        .with_arg("--public-configs")
+        .with_args(env.output_pub_configs())
+        .run()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Found duplicated peer addresses in <public-configs>: 0.0.0.0:8000"));
+}
+
 #[test]
 fn test_run_dev() {
     let env = ConfigSpec::new_without_pass();
@@ -461,6 +518,36 @@ fn test_run_dev() {
     assert!(!old_db_file.exists());
 }
 
+#[test]
+fn test_run_dev_keep_state() {
+    let env = ConfigSpec::new_without_pass();
+
+    let artifacts_dir = env.output_dir().join("artifacts");
+    let feedback = env
+        .command("run-dev")
+        .with_named_arg("-a", &artifacts_dir)
+        .run();
+    assert!(is_run_node_config(feedback.unwrap()));
+    let node_config = artifacts_dir.join("node.toml");
+    let config_before: NodeConfig = load_config_file(&node_config).unwrap();
+
+    // Mocks existence of DB files that are supposed to be kept.
+    let db_dir = artifacts_dir.join("db");
+    fs::create_dir_all(&db_dir).unwrap();
+    let db_file = db_dir.join("content.foo");
+    touch(&db_file);
+
+    let feedback = env
+        .command("run-dev")
+        .with_named_arg("-a", &artifacts_dir)
+        .with_arg("--keep-state")
+        .run();
+    assert!(is_run_node_config(feedback.unwrap()));
+    assert!(db_file.exists());
+    let config_after: NodeConfig = load_config_file(&node_config).unwrap();
+    assert_eq!(config_before, config_after);
+}
+
 #[test]
 fn test_clear_cache() {
     let env = ConfigSpec::new_without_pass();
@@ -499,6 +586,8 @@ fn different_supervisor_modes_in_public_configs() -> Result<(), failure::Error>
         thread_pool_size: None,
         connect_list: Default::default(),
         keys: Default::default(),
+        network_key_path: None,
+        network_keys: None,
     };
 
     let testnet_dir = tempfile::tempdir()?;
@@ -551,6 +640,7 @@ fn run_node_with_supervisor(supervisor_mode: &SupervisorMode) -> Result<(), fail
         common_config: common_config_path.clone(),
         validators_count: 1,
         supervisor_mode: supervisor_mode.clone(),
+        consensus_preset: None,
     };
     generate_template.execute()?;
 
@@ -27,6 +27,8 @@ use actix_web::{
 use failure::{ensure, format_err, Error};
 use futures::{future::Either, sync::mpsc, Future, IntoFuture, Stream};
 use serde::{de::DeserializeOwned, Serialize};
+use tracing::info_span;
+use tracing_futures::Instrument;
 
 use std::{
     fmt,
@@ -251,15 +253,18 @@ where
         let handler = f.inner.handler;
         let actuality = f.inner.actuality;
         let mutability = f.mutability;
+        let name = f.name.clone();
         let index = move |request: HttpRequest| -> FutureResponse {
             let handler = handler.clone();
             let actuality = actuality.clone();
+            let span = info_span!("api_request", endpoint = name.as_str());
             extract_query(request, mutability)
                 .and_then(move |query| {
                     handler(query)
                         .map(|value| json_response(actuality, value))
                         .map_err(From::from)
                 })
+                .instrument(span)
                 .responder()
         };
 
@@ -19,7 +19,13 @@ use actix_net::server::Server;
 use actix_web::server::{HttpServer, StopServer};
 use futures::{sync::mpsc, Future};
 
-use std::{collections::HashMap, fmt, io, net::SocketAddr, time::Duration};
+use std::{
+    collections::HashMap,
+    fmt, io,
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+    time::Duration,
+};
 
 use crate::{backends::actix::create_app, AllowOrigin, ApiAccess, ApiAggregator, ApiBuilder};
 
@@ -30,6 +36,9 @@ pub struct WebServerConfig {
     pub listen_address: SocketAddr,
     /// Optional CORS settings.
     pub allow_origin: Option<AllowOrigin>,
+    /// Listener bound to `listen_address` beforehand, which is used by the server
+    /// instead of binding to the address.
+    listener: Option<Arc<TcpListener>>,
 }
 
 impl WebServerConfig {
@@ -38,8 +47,21 @@ impl WebServerConfig {
         Self {
             listen_address,
             allow_origin: None,
+            listener: None,
         }
     }
+
+    /// Creates a web server configuration for an already bound listener. The listener
+    /// is retained by the configuration, so the address remains reserved for the server
+    /// even while it is restarted. This allows to use listeners bound to port 0,
+    /// with the port assigned by the OS.
+    pub fn from_listener(listener: TcpListener) -> io::Result<Self> {
+        Ok(Self {
+            listen_address: listener.local_addr()?,
+            allow_origin: None,
+            listener: Some(Arc::new(listener)),
+        })
+    }
 }
 
 /// Configuration parameters for `ApiManager`.
@@ -109,12 +131,19 @@ impl ApiManager {
         let listen_address = server_config.listen_address;
         log::info!("Starting {} web api on {}", access, listen_address);
 
+        let listener = match server_config.listener {
+            Some(ref listener) => Some(listener.try_clone()?),
+            None => None,
+        };
         let mut aggregator = self.config.api_aggregator.clone();
         aggregator.extend(self.variable_endpoints.clone());
-        HttpServer::new(move || create_app(&aggregator, access, &server_config))
-            .disable_signals()
-            .bind(listen_address)
-            .map(HttpServer::start)
+        let server = HttpServer::new(move || create_app(&aggregator, access, &server_config))
+            .disable_signals();
+        let server = match listener {
+            Some(listener) => server.listen(listener),
+            None => server.bind(listen_address)?,
+        };
+        Ok(server.start())
     }
 
     fn initiate_restart(&mut self, manager: Addr<Self>) {
@@ -204,4 +233,124 @@ impl StreamHandler<UpdateEndpoints, ()> for ApiManager {
         self.variable_endpoints = msg.endpoints;
         self.initiate_restart(ctx.address());
     }
+
+    // The default implementation stops the actor once the stream is finished. The servers
+    // should keep running with the latest endpoints even if no more updates are expected.
+    fn finished(&mut self, _ctx: &mut Context<Self>) {
+        log::debug!("Stream of endpoint updates is finished");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Sink;
+
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        thread,
+        time::Instant,
+    };
+
+    use super::*;
+    use crate::backends::actix::SystemRuntime;
+
+    fn api_builder(response: &'static str) -> ApiBuilder {
+        let mut builder = ApiBuilder::new();
+        builder
+            .public_scope()
+            .endpoint("v1/ping", move |_query: ()| -> crate::Result<_> {
+                Ok(response)
+            });
+        builder
+    }
+
+    /// Starts the manager with a public server listening on a free port. Returns the runtime
+    /// and the address of the server.
+    fn start_api_manager(
+        endpoints_rx: mpsc::Receiver<UpdateEndpoints>,
+    ) -> (SystemRuntime, SocketAddr) {
+        let mut api_aggregator = ApiAggregator::new();
+        api_aggregator.insert("static", api_builder("static"));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Cannot find a free port");
+        let server_config =
+            WebServerConfig::from_listener(listener).expect("Cannot get address of the listener");
+        let address = server_config.listen_address;
+        let config = ApiManagerConfig {
+            servers: vec![(ApiAccess::Public, server_config)]
+                .into_iter()
+                .collect(),
+            api_aggregator,
+            server_restart_retry_timeout: 100,
+            server_restart_max_retries: 50,
+        };
+        let runtime = SystemRuntime::start(ApiManager::new(config, endpoints_rx)).unwrap();
+        (runtime, address)
+    }
+
+    fn get(address: SocketAddr, path: &str) -> Option<String> {
+        let mut stream = TcpStream::connect(address).ok()?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            path, address
+        )
+        .ok()?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok()?;
+        Some(response)
+    }
+
+    fn wait_for_response(address: SocketAddr, path: &str, expected_body: &str) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            if let Some(response) = get(address, path) {
+                if response.starts_with("HTTP/1.1 200") && response.ends_with(expected_body) {
+                    return;
+                }
+            }
+            assert!(
+                Instant::now() < deadline,
+                "Endpoint {} did not respond with {}",
+                path,
+                expected_body
+            );
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn api_restart_after_startup() {
+        let (endpoints_tx, endpoints_rx) = mpsc::channel(4);
+        let (runtime, address) = start_api_manager(endpoints_rx);
+        wait_for_response(address, "/api/static/v1/ping", "\"static\"");
+
+        // Variable endpoints are added by restarting the server.
+        let endpoints = vec![("dynamic".to_owned(), api_builder("dynamic"))];
+        let endpoints_tx = endpoints_tx
+            .send(UpdateEndpoints { endpoints })
+            .wait()
+            .unwrap();
+        wait_for_response(address, "/api/dynamic/v1/ping", "\"dynamic\"");
+        wait_for_response(address, "/api/static/v1/ping", "\"static\"");
+
+        // The server keeps running after the stream of updates is finished.
+        drop(endpoints_tx);
+        thread::sleep(Duration::from_millis(500));
+        wait_for_response(address, "/api/dynamic/v1/ping", "\"dynamic\"");
+        wait_for_response(address, "/api/static/v1/ping", "\"static\"");
+
+        runtime.stop().unwrap();
+    }
+
+    #[test]
+    fn api_without_endpoint_updates() {
+        // The stream of updates is finished right away, as in the bootstrap mode of the node.
+        let (runtime, address) = start_api_manager(mpsc::channel(0).1);
+        wait_for_response(address, "/api/static/v1/ping", "\"static\"");
+        thread::sleep(Duration::from_millis(500));
+        wait_for_response(address, "/api/static/v1/ping", "\"static\"");
+
+        runtime.stop().unwrap();
+    }
 }
@@ -105,6 +105,19 @@ pub fn verify(sig: &Signature, data: &[u8], pub_key: &PublicKey) -> bool {
     ed25519::verify_detached(sig, data, pub_key)
 }
 
+/// Verifies a batch of detached signatures.
+///
+/// `libsodium` does not provide batch verification of Ed25519 signatures, and batch
+/// verifiers of other libraries differ from `libsodium` in the edge cases (non-canonical
+/// scalars, points of small order, cofactored verification equation). Since signatures
+/// checked in a batch and one by one must be accepted under the same rules, the batch
+/// is verified with `verify`; the loop stops at the first invalid signature.
+pub fn verify_batch<'a>(
+    mut items: impl Iterator<Item = (&'a [u8], &'a Signature, &'a PublicKey)>,
+) -> bool {
+    items.all(|(data, sig, pub_key)| verify(sig, data, pub_key))
+}
+
 /// Calculates hash of a bytes slice.
 pub fn hash(data: &[u8]) -> Hash {
     sha256::hash(data)
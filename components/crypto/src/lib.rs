@@ -145,6 +145,43 @@ pub fn verify(sig: &Signature, data: &[u8], pubkey: &PublicKey) -> bool {
     crypto_impl::verify(&sig.0, data, &pubkey.0)
 }
 
+/// Verifies a batch of signatures at once. Each item of the batch consists of the signed data,
+/// the signature and the public key of the signer. Returns `true` only if every signature
+/// in the batch is valid.
+///
+/// The method is intended for the cases when many signatures are checked together
+/// (e.g., precommits of a block). A signature is accepted in a batch if and only if
+/// it is accepted by [`verify`], so both functions can be used on consensus-critical paths
+/// interchangeably. If the batch is rejected, there is no information which signature
+/// is invalid; use [`verify`] to find it out.
+///
+/// [`verify`]: fn.verify.html
+///
+/// # Examples
+///
+/// ```
+/// # exonum_crypto::init();
+/// let (public_key, secret_key) = exonum_crypto::gen_keypair();
+/// let data: Vec<&[u8]> = vec![&[1, 2, 3], &[4, 5]];
+/// let signatures: Vec<_> = data
+///     .iter()
+///     .map(|data| exonum_crypto::sign(data, &secret_key))
+///     .collect();
+/// let batch: Vec<_> = data
+///     .iter()
+///     .zip(&signatures)
+///     .map(|(data, signature)| (*data, signature, &public_key))
+///     .collect();
+/// assert!(exonum_crypto::verify_batch(&batch));
+/// ```
+pub fn verify_batch(items: &[(&[u8], &Signature, &PublicKey)]) -> bool {
+    crypto_impl::verify_batch(
+        items
+            .iter()
+            .map(|&(data, sig, pubkey)| (data, &sig.0, &pubkey.0)),
+    )
+}
+
 /// Calculates a hash of a bytes slice.
 ///
 /// Type of a hash depends on a chosen crypto backend (via `...-crypto` cargo feature).
@@ -531,6 +568,93 @@ mod tests {
         assert_eq!(s, sh);
     }
 
+    #[test]
+    fn verify_signature_batch() {
+        let keys: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let data: Vec<Vec<u8>> = (0..4_u8).map(|i| vec![i; 10]).collect();
+        let data: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+        let signatures: Vec<_> = keys
+            .iter()
+            .zip(&data)
+            .map(|((_, secret_key), data)| sign(data, secret_key))
+            .collect();
+        let mut batch: Vec<_> = data
+            .iter()
+            .zip(&signatures)
+            .zip(&keys)
+            .map(|((data, signature), (public_key, _))| (*data, signature, public_key))
+            .collect();
+
+        assert!(verify_batch(&batch));
+        assert!(verify_batch(&[]));
+
+        let signature = batch[1].1;
+        batch[1].1 = batch[2].1;
+        batch[2].1 = signature;
+        assert!(!verify_batch(&batch));
+    }
+
+    /// Adds the order of the Ed25519 base point to the `S` part of the signature,
+    /// which yields a signature with the non-canonical encoding of the same scalar.
+    fn make_non_canonical(signature: &Signature) -> Signature {
+        const GROUP_ORDER: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+        ];
+
+        let mut bytes = signature.as_ref().to_vec();
+        let mut carry = 0_u16;
+        for (byte, order_byte) in bytes[32..].iter_mut().zip(&GROUP_ORDER) {
+            let sum = u16::from(*byte) + u16::from(*order_byte) + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        Signature::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn verify_batch_rejects_non_canonical_signatures() {
+        let (public_key, secret_key) = gen_keypair();
+        let data: &[u8] = &[1, 2, 3];
+        let signature = sign(data, &secret_key);
+        let non_canonical = make_non_canonical(&signature);
+
+        assert!(verify(&signature, data, &public_key));
+        assert!(!verify(&non_canonical, data, &public_key));
+        assert!(!verify_batch(&[(data, &non_canonical, &public_key)]));
+        assert!(!verify_batch(&[
+            (data, &signature, &public_key),
+            (data, &non_canonical, &public_key),
+        ]));
+    }
+
+    #[test]
+    fn verify_batch_rejects_small_order_points() {
+        // Encoding of the neutral element of the curve, which has order 1.
+        let mut identity = [0_u8; 32];
+        identity[0] = 1;
+        let public_key = PublicKey::new(identity);
+        // `R` is the neutral element and `S` is zero, so the cofactorless equation
+        // `S * B = R + k * A` holds for the small-order key for any message.
+        let mut signature = [0_u8; 64];
+        signature[..32].copy_from_slice(&identity);
+        let signature = Signature::new(signature);
+        let data: &[u8] = &[1, 2, 3];
+
+        assert!(!verify(&signature, data, &public_key));
+        assert!(!verify_batch(&[(data, &signature, &public_key)]));
+
+        let (valid_key, secret_key) = gen_keypair();
+        let valid_signature = sign(data, &secret_key);
+        assert!(!verify_batch(&[
+            (data, &valid_signature, &valid_key),
+            (data, &signature, &public_key),
+        ]));
+        // Small-order `R` with a valid key is rejected as well.
+        assert!(!verify_batch(&[(data, &signature, &valid_key)]));
+        assert!(!verify(&signature, data, &valid_key));
+    }
+
     #[test]
     fn serialize_deserialize_hash() {
         assert_serialize_deserialize(&Hash::new([207; HASH_SIZE]));
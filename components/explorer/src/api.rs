@@ -28,7 +28,7 @@ use exonum::{
 };
 use serde_derive::{Deserialize, Serialize};
 
-use std::ops::Range;
+use std::{error::Error as StdError, fmt, ops::Range};
 
 use crate::median_precommits_time;
 
@@ -134,6 +134,39 @@ pub struct BlocksQuery {
     pub add_precommits: bool,
 }
 
+/// Error of selecting blocks with a [`BlocksQuery`](struct.BlocksQuery.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlocksQueryError {
+    /// The number of requested blocks exceeds `MAX_BLOCKS_PER_REQUEST`.
+    TooManyBlocks,
+    /// The requested latest height is greater than the current blockchain height.
+    HeightOutOfRange {
+        /// Requested latest height.
+        latest: Height,
+        /// Current blockchain height.
+        height: Height,
+    },
+}
+
+impl fmt::Display for BlocksQueryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlocksQueryError::TooManyBlocks => write!(
+                formatter,
+                "Max block count per request exceeded ({})",
+                MAX_BLOCKS_PER_REQUEST
+            ),
+            BlocksQueryError::HeightOutOfRange { latest, height } => write!(
+                formatter,
+                "Requested latest height {} is greater than the current blockchain height {}",
+                latest, height
+            ),
+        }
+    }
+}
+
+impl StdError for BlocksQueryError {}
+
 /// Block query parameters.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct BlockQuery {
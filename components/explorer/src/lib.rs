@@ -732,6 +732,78 @@ impl<'a> BlockchainExplorer<'a> {
             back: max(ptr, end_height(heights.end_bound(), max_height)),
         }
     }
+
+    /// Returns blocks selected by the query in the same format as the explorer HTTP API does.
+    /// Blocks are returned in the reverse order, starting from the latest one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `count` exceeds [`MAX_BLOCKS_PER_REQUEST`] or `latest` exceeds
+    /// the height of the blockchain.
+    ///
+    /// [`MAX_BLOCKS_PER_REQUEST`]: api/constant.MAX_BLOCKS_PER_REQUEST.html
+    pub fn blocks_range(
+        &self,
+        query: &api::BlocksQuery,
+    ) -> Result<api::BlocksRange, api::BlocksQueryError> {
+        if query.count > api::MAX_BLOCKS_PER_REQUEST {
+            return Err(api::BlocksQueryError::TooManyBlocks);
+        }
+        if let Some(latest) = query.latest {
+            if latest > self.height() {
+                return Err(api::BlocksQueryError::HeightOutOfRange {
+                    latest,
+                    height: self.height(),
+                });
+            }
+        }
+
+        let (upper, upper_bound) = if let Some(upper) = query.latest {
+            (upper, Bound::Included(upper))
+        } else {
+            (self.height(), Bound::Unbounded)
+        };
+        let lower_bound = if let Some(lower) = query.earliest {
+            Bound::Included(lower)
+        } else {
+            Bound::Unbounded
+        };
+
+        let blocks: Vec<_> = self
+            .blocks((lower_bound, upper_bound))
+            .rev()
+            .filter(|block| !query.skip_empty_blocks || !block.is_empty())
+            .take(query.count)
+            .map(|block| api::BlockInfo {
+                txs: None,
+
+                time: if query.add_blocks_time {
+                    Some(median_precommits_time(&block.precommits()))
+                } else {
+                    None
+                },
+
+                precommits: if query.add_precommits {
+                    Some(block.precommits().to_vec())
+                } else {
+                    None
+                },
+
+                block: block.into_header(),
+            })
+            .collect();
+
+        let height = if blocks.len() < query.count {
+            query.earliest.unwrap_or(Height(0))
+        } else {
+            blocks.last().map_or(Height(0), |info| info.block.height)
+        };
+
+        Ok(api::BlocksRange {
+            range: height..upper.next(),
+            blocks,
+        })
+    }
 }
 
 /// Iterator over blocks in the blockchain.
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use self::signer::{SignatureFuture, Signer};
+
 use exonum_crypto::{gen_keypair_from_seed, KeyPair, PublicKey, SecretKey, Seed, SEED_LENGTH};
 use failure::format_err;
 use pwbox::{sodium::Sodium, ErasedPwBox, Eraser, SensitiveData, Suite};
@@ -27,6 +29,8 @@ use std::{
     path::Path,
 };
 
+mod signer;
+
 #[cfg(unix)]
 #[cfg_attr(feature = "cargo-clippy", allow(clippy::verbose_bit_mask))]
 fn validate_file_mode(mode: u32) -> Result<(), Error> {
@@ -171,6 +175,51 @@ fn generate_keys_from_master_password(tree: SecretTree) -> Option<Keys> {
     ))
 }
 
+/// Saves the key pair used to authenticate the node in connections with peers to a TOML file.
+///
+/// Unlike the master key, the network key pair is stored unencrypted, so that the node
+/// is able to update it at runtime. The file is created with the same permissions
+/// as the master key file.
+pub fn save_network_keys<P: AsRef<Path>>(path: P, keys: &KeyPair) -> Result<(), failure::Error> {
+    let file_content = toml::to_string_pretty(&NetworkKeys::from(keys))?;
+    let mut open_options = OpenOptions::new();
+    open_options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    open_options.mode(0o_600);
+    let mut file = open_options.open(path.as_ref())?;
+    file.write_all(file_content.as_bytes())?;
+
+    Ok(())
+}
+
+/// Reads the key pair used to authenticate the node in connections with peers from file.
+pub fn read_network_keys<P: AsRef<Path>>(path: P) -> Result<KeyPair, failure::Error> {
+    let mut key_file = File::open(path)?;
+
+    #[cfg(unix)]
+    validate_file_mode(key_file.metadata()?.mode())?;
+
+    let mut file_content = vec![];
+    key_file.read_to_end(&mut file_content)?;
+    let keys: NetworkKeys = toml::from_slice(file_content.as_slice())?;
+    Ok(KeyPair::from_keys(keys.public_key, keys.secret_key))
+}
+
+#[derive(Serialize, Deserialize)]
+struct NetworkKeys {
+    public_key: PublicKey,
+    secret_key: SecretKey,
+}
+
+impl From<&KeyPair> for NetworkKeys {
+    fn from(keys: &KeyPair) -> Self {
+        Self {
+            public_key: keys.public_key(),
+            secret_key: keys.secret_key().to_owned(),
+        }
+    }
+}
+
 /// Reads encrypted master key from file and generate validator keys from it.
 pub fn read_keys_from_file<P: AsRef<Path>, W: AsRef<[u8]>>(
     path: P,
@@ -207,6 +256,20 @@ mod tests {
         assert_eq!(pk1, pk2);
     }
 
+    #[test]
+    fn test_save_and_read_network_keys() {
+        let dir = TempDir::new("test_utils").expect("Couldn't create TempDir");
+        let file_path = dir.path().join("network_key.toml");
+        let keys = KeyPair::from(exonum_crypto::gen_keypair());
+        save_network_keys(&file_path, &keys).unwrap();
+        assert_eq!(read_network_keys(&file_path).unwrap(), keys);
+
+        // The file is overwritten when the key pair is rotated.
+        let new_keys = KeyPair::from(exonum_crypto::gen_keypair());
+        save_network_keys(&file_path, &new_keys).unwrap();
+        assert_eq!(read_network_keys(&file_path).unwrap(), new_keys);
+    }
+
     #[test]
     fn encrypt_decrypt() {
         let pass_phrase = b"passphrase";
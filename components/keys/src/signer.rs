@@ -0,0 +1,61 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Abstraction of entities signing messages on behalf of a key.
+
+use exonum_crypto::{self as crypto, KeyPair, PublicKey, Signature};
+use futures::{future, Future};
+
+use std::fmt;
+
+/// Future resolving to the signature produced by a [`Signer`].
+///
+/// [`Signer`]: trait.Signer.html
+pub type SignatureFuture = Box<dyn Future<Item = Signature, Error = failure::Error> + Send>;
+
+/// Entity able to sign data with a certain key.
+///
+/// The local implementation is provided for [`KeyPair`]. Other implementations
+/// may delegate signing to an external party (e.g., a remote signer daemon or an HSM),
+/// so that the secret key is not kept in the memory of the process. Such implementations
+/// are not provided by this crate, since they depend on the protocol of the external party;
+/// they are expected to be implemented in separate crates.
+///
+/// The node uses signers only for consensus messages (see `NodeBuilder::with_consensus_signer`
+/// in `exonum-node`). Service keys and the key pair identifying the node among peers
+/// are kept by the node.
+///
+/// Signing is asynchronous, so that the party requesting a signature is not blocked
+/// while the signer communicates with an external party. Futures returned by the signer
+/// should not block the thread they are polled on.
+///
+/// [`KeyPair`]: https://docs.rs/exonum-crypto/latest/exonum_crypto/struct.KeyPair.html
+pub trait Signer: Send + Sync + fmt::Debug {
+    /// Returns the public key corresponding to the signing key.
+    fn public_key(&self) -> PublicKey;
+
+    /// Signs `data`. The returned future resolves to an error if the signature
+    /// could not be obtained, e.g., because the remote signer is unreachable.
+    fn sign(&self, data: &[u8]) -> SignatureFuture;
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> PublicKey {
+        KeyPair::public_key(self)
+    }
+
+    fn sign(&self, data: &[u8]) -> SignatureFuture {
+        Box::new(future::ok(crypto::sign(data, self.secret_key())))
+    }
+}
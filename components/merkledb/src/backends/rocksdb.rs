@@ -21,7 +21,14 @@ use rocksdb::{
     self, checkpoint::Checkpoint, ColumnFamily, DBIterator, Options as RocksDbOptions, WriteBatch,
 };
 use smallvec::SmallVec;
-use std::{fmt, iter::Peekable, mem, path::Path, sync::Arc};
+use std::{
+    fmt, fs, io,
+    iter::Peekable,
+    mem,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use crate::{
     db::{check_database, Change},
@@ -32,6 +39,10 @@ use crate::{
 /// in a column family.
 const ID_SIZE: usize = mem::size_of::<u64>();
 
+/// Interval between updates of the size of the database on disk. Calculating the size
+/// requires traversing the database directory, so the size is cached in the meantime.
+const SIZE_ON_DISK_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
 /// Database implementation on top of [`RocksDB`](https://rocksdb.org)
 /// backend.
 ///
@@ -41,6 +52,7 @@ const ID_SIZE: usize = mem::size_of::<u64>();
 pub struct RocksDB {
     db: Arc<ShardedLock<rocksdb::DB>>,
     options: DbOptions,
+    size_on_disk: DirSizeCache,
 }
 
 impl From<DbOptions> for RocksDbOptions {
@@ -92,6 +104,7 @@ impl RocksDB {
         let mut db = Self {
             db: Arc::new(ShardedLock::new(inner)),
             options: *options,
+            size_on_disk: DirSizeCache::new(SIZE_ON_DISK_UPDATE_INTERVAL),
         };
         check_database(&mut db)?;
         Ok(db)
@@ -109,16 +122,35 @@ impl RocksDB {
         Ok(())
     }
 
-    fn cf_exists(&self, cf_name: &str) -> bool {
-        self.get_lock_guard().cf_handle(cf_name).is_some()
-    }
+    /// Creates all column families from `cf_names` that are not present in the database.
+    ///
+    /// The database write lock is acquired at most once per call, and only if some column
+    /// families are actually missing. Since the write lock blocks all readers (including
+    /// snapshots used by API handlers), it must be held for as short a time as possible.
+    fn create_missing_cfs<'a>(
+        &self,
+        cf_names: impl IntoIterator<Item = &'a str>,
+    ) -> crate::Result<()> {
+        let missing_cfs: Vec<_> = {
+            let db_reader = self.get_lock_guard();
+            cf_names
+                .into_iter()
+                .filter(|name| db_reader.cf_handle(name).is_none())
+                .collect()
+        };
+        if missing_cfs.is_empty() {
+            return Ok(());
+        }
 
-    fn create_cf(&self, cf_name: &str) -> crate::Result<()> {
-        self.db
-            .write()
-            .expect("Couldn't get write lock to DB")
-            .create_cf(cf_name, &self.options.into())
-            .map_err(Into::into)
+        let mut db_writer = self.db.write().expect("Couldn't get write lock to DB");
+        let cf_options: RocksDbOptions = self.options.into();
+        for cf_name in missing_cfs {
+            // The column family could have been created by a concurrent merge.
+            if db_writer.cf_handle(cf_name).is_none() {
+                db_writer.create_cf(cf_name, &cf_options)?;
+            }
+        }
+        Ok(())
     }
 
     pub(super) fn get_lock_guard(&self) -> ShardedLockReadGuard<rocksdb::DB> {
@@ -126,13 +158,14 @@ impl RocksDB {
     }
 
     fn do_merge(&self, patch: Patch, w_opts: &RocksDBWriteOptions) -> crate::Result<()> {
-        let mut batch = WriteBatch::default();
-        for (resolved, changes) in patch.into_changes() {
-            if !self.cf_exists(&resolved.name) {
-                self.create_cf(&resolved.name)?;
-            }
+        let changes = patch.into_changes();
+        // Column families are created before the batch is assembled, so that the write lock
+        // is never requested while the batch is being built or written.
+        self.create_missing_cfs(changes.keys().map(|resolved| resolved.name.as_str()))?;
 
-            let db_reader = self.get_lock_guard();
+        let db_reader = self.get_lock_guard();
+        let mut batch = WriteBatch::default();
+        for (resolved, changes) in changes {
             let cf = db_reader.cf_handle(&resolved.name).unwrap();
 
             if changes.is_cleared() {
@@ -167,9 +200,7 @@ impl RocksDB {
             }
         }
 
-        self.get_lock_guard()
-            .write_opt(batch, w_opts)
-            .map_err(Into::into)
+        db_reader.write_opt(batch, w_opts).map_err(Into::into)
     }
 
     /// Removes all keys with a specified prefix from a column family.
@@ -242,6 +273,59 @@ impl Database for RocksDB {
         w_opts.set_sync(true);
         self.do_merge(patch, &w_opts)
     }
+
+    fn size_on_disk(&self) -> Option<u64> {
+        self.size_on_disk.get(self.get_lock_guard().path()).ok()
+    }
+}
+
+/// Size of a directory on disk, which is recalculated at most once per the update interval.
+struct DirSizeCache {
+    update_interval: Duration,
+    cached: Mutex<Option<(Instant, u64)>>,
+}
+
+impl DirSizeCache {
+    fn new(update_interval: Duration) -> Self {
+        Self {
+            update_interval,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cached size of the directory, or recalculates it if the cached value
+    /// is outdated. The lock is held during the calculation, so that concurrent callers
+    /// do not traverse the directory simultaneously.
+    fn get(&self, path: &Path) -> io::Result<u64> {
+        let mut cached = self
+            .cached
+            .lock()
+            .expect("Cannot lock directory size cache");
+        if let Some((updated_at, size)) = *cached {
+            if updated_at.elapsed() < self.update_interval {
+                return Ok(size);
+            }
+        }
+
+        let size = dir_size(path)?;
+        *cached = Some((Instant::now(), size));
+        Ok(size)
+    }
+}
+
+/// Calculates the total size of files in the directory, including nested directories.
+fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
 }
 
 impl Snapshot for RocksDBSnapshot {
@@ -321,3 +405,71 @@ impl fmt::Debug for RocksDBSnapshot {
         f.debug_struct("RocksDBSnapshot").finish()
     }
 }
+
+#[test]
+fn dir_size_cache() {
+    use tempfile::TempDir;
+
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("first"), vec![0_u8; 100]).unwrap();
+    fs::create_dir(dir.path().join("nested")).unwrap();
+    fs::write(dir.path().join("nested").join("second"), vec![0_u8; 50]).unwrap();
+
+    let cache = DirSizeCache::new(Duration::from_secs(3_600));
+    let uncached = DirSizeCache::new(Duration::from_secs(0));
+    assert_eq!(cache.get(dir.path()).unwrap(), 150);
+    assert_eq!(uncached.get(dir.path()).unwrap(), 150);
+
+    // The cached size is not updated until the update interval passes.
+    fs::write(dir.path().join("third"), vec![0_u8; 10]).unwrap();
+    assert_eq!(cache.get(dir.path()).unwrap(), 150);
+    assert_eq!(uncached.get(dir.path()).unwrap(), 160);
+}
+
+#[test]
+fn snapshots_are_consistent_during_concurrent_merges() {
+    use std::thread;
+    use tempfile::TempDir;
+
+    use crate::access::AccessExt;
+
+    const MERGES: u32 = 50;
+    const READERS: usize = 4;
+
+    let dir = TempDir::new().unwrap();
+    let db = Arc::new(RocksDB::open(dir.path(), &DbOptions::default()).unwrap());
+    let fork = db.fork();
+    fork.get_list("counter").push(0_u32);
+    db.merge(fork.into_patch()).unwrap();
+
+    let readers: Vec<_> = (0..READERS)
+        .map(|_| {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                let mut last_seen = 0;
+                while last_seen < MERGES {
+                    let snapshot = db.snapshot();
+                    let list = snapshot.get_list::<_, u32>("counter");
+                    let len = list.len() as u32;
+                    // Snapshots must always observe a consistent, monotonically growing state.
+                    assert!(len > last_seen);
+                    assert_eq!(list.last(), Some(len - 1));
+                    last_seen = len - 1;
+                }
+            })
+        })
+        .collect();
+
+    for i in 1..=MERGES {
+        let fork = db.fork();
+        fork.get_list("counter").push(i);
+        // Each merge also creates a new column family, which requires exclusive access
+        // to the database.
+        fork.get_entry(format!("entry_{}", i).as_str()).set(i);
+        db.merge(fork.into_patch()).unwrap();
+    }
+
+    for reader in readers {
+        reader.join().unwrap();
+    }
+}
@@ -115,6 +115,10 @@ impl Database for TemporaryDB {
     fn merge_sync(&self, patch: Patch) -> Result<()> {
         self.inner.merge_sync(patch)
     }
+
+    fn size_on_disk(&self) -> Option<u64> {
+        self.inner.size_on_disk()
+    }
 }
 
 impl Snapshot for TemporarySnapshot {
@@ -187,3 +191,21 @@ fn check_if_snapshot_is_still_valid() {
     let index = snapshot.get_list("index");
     assert_eq!(index.get(0), Some(1));
 }
+
+#[test]
+fn size_on_disk() {
+    use crate::access::AccessExt;
+
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    fork.get_list("index").extend(vec![vec![0_u8; 1_024]; 100]);
+    db.merge_sync(fork.into_patch()).unwrap();
+    let size = db.size_on_disk().unwrap();
+    assert!(size > 0);
+
+    // The size is cached, so it is not recalculated after each merge.
+    let fork = db.fork();
+    fork.get_list("other_index").extend(vec![vec![0_u8; 1_024]; 100]);
+    db.merge_sync(fork.into_patch()).unwrap();
+    assert_eq!(db.size_on_disk().unwrap(), size);
+}
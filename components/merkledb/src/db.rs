@@ -514,6 +514,15 @@ pub trait Database: Send + Sync + 'static {
     /// will be returned. In case of an error, the method guarantees no changes are applied to
     /// the database.
     fn merge_sync(&self, patch: Patch) -> Result<()>;
+
+    /// Returns the size of the database files on disk in bytes, or `None` if the database
+    /// is not stored on disk or its size cannot be determined.
+    ///
+    /// Implementations may cache the size, since calculating it can be expensive. For example,
+    /// `RocksDB` recalculates the size at most once per 10 seconds.
+    fn size_on_disk(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// Extension trait for `Database`.
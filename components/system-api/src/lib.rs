@@ -76,7 +76,8 @@ fn system_api(
     shared_api_state: SharedNodeState,
 ) -> ApiBuilder {
     let mut builder = ApiBuilder::new();
-    PrivateSystemApi::new(sender, shared_api_state.clone()).wire(builder.private_scope());
+    PrivateSystemApi::new(blockchain.clone(), sender, shared_api_state.clone())
+        .wire(builder.private_scope());
     SystemApi::new(blockchain, shared_api_state).wire(builder.public_scope());
     builder
 }
@@ -17,13 +17,18 @@
 //! Private API includes requests that are available only to the blockchain
 //! administrators, e.g. shutting down the node.
 
-use exonum::{blockchain::ApiSender, crypto::PublicKey, runtime::InstanceId};
+use exonum::{
+    blockchain::{ApiSender, Blockchain, Schema},
+    crypto::{gen_keypair, KeyPair, PublicKey},
+    helpers::{log_filters, set_log_filters, validate_log_filters},
+    runtime::InstanceId,
+};
 use exonum_api::{ApiBackend, ApiScope, Error as ApiError, FutureResult};
-use exonum_node::{ConnectInfo, ExternalMessage, SharedNodeState};
+use exonum_node::{CacheUsage, ConnectInfo, ExternalMessage, PeerConnection, SharedNodeState};
 use futures::Future;
 use serde_derive::{Deserialize, Serialize};
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
 
 /// Short information about the service.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -55,6 +60,21 @@ impl Default for NodeInfo {
     }
 }
 
+/// Usage of the node resources.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResourceUsage {
+    /// Size of the database on disk in bytes, or `None` if it cannot be determined.
+    pub database_size: Option<u64>,
+    /// Number of transactions in the persistent pool of unconfirmed transactions.
+    pub tx_pool_len: u64,
+    /// Usage of the in-memory caches of the node.
+    pub caches: CacheUsage,
+    /// Number of incoming connections with peers.
+    pub incoming_connections: usize,
+    /// Number of outgoing connections with peers.
+    pub outgoing_connections: usize,
+}
+
 #[derive(Serialize, Deserialize, Default)]
 struct OutgoingConnection {
     public_key: Option<PublicKey>,
@@ -64,6 +84,17 @@ struct OutgoingConnection {
 struct PeersInfo {
     incoming_connections: Vec<ConnectInfo>,
     outgoing_connections: HashMap<SocketAddr, OutgoingConnection>,
+    /// Connection states of the peers from the connect list.
+    connect_list: Vec<PeerConnection>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct RemovePeerQuery {
+    public_key: PublicKey,
+    /// If set, the connection with the peer is dropped, but the peer is not removed
+    /// from the connect list.
+    #[serde(default)]
+    keep_in_connect_list: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -71,9 +102,28 @@ struct ConsensusEnabledQuery {
     enabled: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct LogFiltersQuery {
+    filters: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct BanPeerQuery {
+    public_key: PublicKey,
+    /// Ban duration in seconds. If not specified, the ban is permanent.
+    #[serde(default)]
+    duration_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct UnbanPeerQuery {
+    public_key: PublicKey,
+}
+
 /// Private system API.
 #[derive(Debug)]
 pub(super) struct SystemApi {
+    blockchain: Blockchain,
     info: NodeInfo,
     shared_api_state: SharedNodeState,
     sender: ApiSender<ExternalMessage>,
@@ -81,8 +131,13 @@ pub(super) struct SystemApi {
 
 impl SystemApi {
     /// Create a new `private::SystemApi` instance.
-    pub fn new(sender: ApiSender<ExternalMessage>, shared_api_state: SharedNodeState) -> Self {
+    pub fn new(
+        blockchain: Blockchain,
+        sender: ApiSender<ExternalMessage>,
+        shared_api_state: SharedNodeState,
+    ) -> Self {
         Self {
+            blockchain,
             sender,
             info: NodeInfo::new(),
             shared_api_state,
@@ -93,9 +148,18 @@ impl SystemApi {
     pub fn wire(self, api_scope: &mut ApiScope) -> &mut ApiScope {
         self.handle_peers_info("v1/peers", api_scope)
             .handle_peer_add("v1/peers", api_scope)
+            .handle_peer_remove("v1/peers/remove", api_scope)
+            .handle_banned_peers("v1/bans", api_scope)
+            .handle_ban_peer("v1/bans", api_scope)
+            .handle_unban_peer("v1/bans/remove", api_scope)
             .handle_network_info("v1/network", api_scope)
+            .handle_rotate_network_key("v1/network/rotate_key", api_scope)
             .handle_is_consensus_enabled("v1/consensus_enabled", api_scope)
             .handle_set_consensus_enabled("v1/consensus_enabled", api_scope)
+            .handle_consensus_metrics("v1/consensus_metrics", api_scope)
+            .handle_resource_usage("v1/resources", api_scope)
+            .handle_log_filters("v1/log_filters", api_scope)
+            .handle_set_log_filters("v1/log_filters", api_scope)
             .handle_shutdown("v1/shutdown", api_scope);
         api_scope
     }
@@ -117,6 +181,7 @@ impl SystemApi {
             Ok(PeersInfo {
                 incoming_connections: shared_api_state.incoming_connections(),
                 outgoing_connections,
+                connect_list: shared_api_state.peer_connections(),
             })
         });
         self
@@ -133,12 +198,69 @@ impl SystemApi {
         self
     }
 
+    fn handle_peer_remove(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let sender = self.sender.clone();
+        api_scope.endpoint_mut(name, move |query: RemovePeerQuery| -> FutureResult<()> {
+            let handler = sender
+                .send_message(ExternalMessage::PeerRemove(
+                    query.public_key,
+                    query.keep_in_connect_list,
+                ))
+                .map_err(|e| ApiError::InternalError(e.into()));
+            Box::new(handler)
+        });
+        self
+    }
+
+    fn handle_banned_peers(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let shared_api_state = self.shared_api_state.clone();
+        api_scope.endpoint(name, move |_query: ()| Ok(shared_api_state.banned_peers()));
+        self
+    }
+
+    fn handle_ban_peer(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let sender = self.sender.clone();
+        api_scope.endpoint_mut(name, move |query: BanPeerQuery| -> FutureResult<()> {
+            let duration = query.duration_secs.map(Duration::from_secs);
+            let handler = sender
+                .send_message(ExternalMessage::BanPeer(query.public_key, duration))
+                .map_err(|e| ApiError::InternalError(e.into()));
+            Box::new(handler)
+        });
+        self
+    }
+
+    fn handle_unban_peer(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let sender = self.sender.clone();
+        api_scope.endpoint_mut(name, move |query: UnbanPeerQuery| -> FutureResult<()> {
+            let handler = sender
+                .send_message(ExternalMessage::UnbanPeer(query.public_key))
+                .map_err(|e| ApiError::InternalError(e.into()));
+            Box::new(handler)
+        });
+        self
+    }
+
     fn handle_network_info(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
         let info = self.info.clone();
         api_scope.endpoint(name, move |_query: ()| Ok(info.clone()));
         self
     }
 
+    fn handle_rotate_network_key(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let sender = self.sender.clone();
+        api_scope.endpoint_mut(name, move |_query: ()| -> FutureResult<PublicKey> {
+            let keypair = KeyPair::from(gen_keypair());
+            let public_key = keypair.public_key();
+            let handler = sender
+                .send_message(ExternalMessage::RotateNetworkKey(keypair))
+                .map(move |()| public_key)
+                .map_err(|e| ApiError::InternalError(e.into()));
+            Box::new(handler)
+        });
+        self
+    }
+
     fn handle_is_consensus_enabled(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
         let shared_api_state = self.shared_api_state.clone();
         api_scope.endpoint(name, move |_query: ()| Ok(shared_api_state.is_enabled()));
@@ -159,6 +281,46 @@ impl SystemApi {
         self
     }
 
+    fn handle_consensus_metrics(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let shared_api_state = self.shared_api_state.clone();
+        api_scope.endpoint(name, move |_query: ()| {
+            Ok(shared_api_state.consensus_metrics())
+        });
+        self
+    }
+
+    fn handle_resource_usage(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let blockchain = self.blockchain.clone();
+        let shared_api_state = self.shared_api_state.clone();
+        api_scope.endpoint(name, move |_query: ()| {
+            let snapshot = blockchain.snapshot();
+            Ok(ResourceUsage {
+                database_size: blockchain.database_size(),
+                tx_pool_len: Schema::new(&snapshot).transactions_pool_len(),
+                caches: shared_api_state.cache_usage(),
+                incoming_connections: shared_api_state.incoming_connections().len(),
+                outgoing_connections: shared_api_state.outgoing_connections().len(),
+            })
+        });
+        self
+    }
+
+    fn handle_log_filters(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        api_scope.endpoint(name, |_query: ()| {
+            log_filters().map_err(ApiError::InternalError)
+        });
+        self
+    }
+
+    fn handle_set_log_filters(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        api_scope.endpoint_mut(name, |query: LogFiltersQuery| {
+            validate_log_filters(&query.filters)
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            set_log_filters(&query.filters).map_err(ApiError::InternalError)
+        });
+        self
+    }
+
     fn handle_shutdown(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
         // These backend-dependent uses are needed to provide realization of the support of empty
         // request which is not easy in the generic approach, so it will be harder to misuse
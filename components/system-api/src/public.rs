@@ -19,12 +19,12 @@
 
 use exonum::{
     blockchain::{Blockchain, Schema},
-    helpers::user_agent,
+    helpers::{user_agent, Height, Round},
     merkledb::access::AsReadonly,
     runtime::{ArtifactId, DispatcherSchema, InstanceState, SnapshotExt},
 };
 use exonum_api::ApiScope;
-use exonum_node::SharedNodeState;
+use exonum_node::{SharedNodeState, ValidatorLiveness};
 use serde_derive::{Deserialize, Serialize};
 
 /// Information about the current state of the node memory pool.
@@ -60,6 +60,24 @@ pub struct HealthCheckInfo {
     pub connected_peers: usize,
 }
 
+/// Information about the consensus state of the node.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ConsensusStatusInfo {
+    /// Height of the block the node is currently agreeing upon.
+    pub height: Height,
+    /// Current consensus round of the node, or `None` if the node has not reported
+    /// its state yet.
+    pub round: Option<Round>,
+    /// Whether the node is the leader of the current round.
+    pub is_leader: bool,
+    /// Whether consensus is enabled on the node.
+    pub is_enabled: bool,
+    /// Consensus status.
+    pub consensus_status: ConsensusStatus,
+    /// Liveness of the validators as seen by the node.
+    pub validators: Vec<ValidatorLiveness>,
+}
+
 /// Services info response.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct DispatcherInfo {
@@ -125,6 +143,23 @@ impl SystemApi {
         self_
     }
 
+    fn handle_consensus_status_info(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(name, move |_query: ()| {
+            let snapshot = self.blockchain.snapshot();
+            let metrics = self.node_state.consensus_metrics();
+            Ok(ConsensusStatusInfo {
+                height: Schema::new(&snapshot).next_height(),
+                round: metrics.map(|metrics| metrics.round),
+                is_leader: self.node_state.is_leader(),
+                is_enabled: self.node_state.is_enabled(),
+                consensus_status: self.get_consensus_status(),
+                validators: self.node_state.validators_liveness(),
+            })
+        });
+        self_
+    }
+
     fn handle_list_services_info(self, name: &'static str, api_scope: &mut ApiScope) -> Self {
         let self_ = self.clone();
         api_scope.endpoint(name, move |_query: ()| {
@@ -158,6 +193,7 @@ impl SystemApi {
     pub fn wire(self, api_scope: &mut ApiScope) -> &mut ApiScope {
         self.handle_stats_info("v1/stats", api_scope)
             .handle_healthcheck_info("v1/healthcheck", api_scope)
+            .handle_consensus_status_info("v1/consensus_status", api_scope)
             .handle_user_agent_info("v1/user_agent", api_scope)
             .handle_list_services_info("v1/services", api_scope);
         api_scope
@@ -12,14 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use exonum::helpers::user_agent;
-use exonum_node::ExternalMessage;
+use exonum::{
+    crypto::{gen_keypair, PublicKey},
+    helpers::{init_logger, user_agent, Height},
+};
+use exonum_api::Error as ApiError;
+use exonum_node::{BannedPeer, CacheUsage, ConsensusMetrics, ExternalMessage, PeerConnection};
 use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
 use pretty_assertions::assert_eq;
+use serde_derive::{Deserialize, Serialize};
+
+use std::{collections::HashMap, time::Duration};
 
 use exonum_system_api::{
-    private::NodeInfo,
-    public::{ConsensusStatus, HealthCheckInfo, StatsInfo},
+    private::{NodeInfo, ResourceUsage},
+    public::{ConsensusStatus, ConsensusStatusInfo, HealthCheckInfo, StatsInfo},
     SystemApiPlugin,
 };
 
@@ -48,6 +55,28 @@ fn healthcheck() {
     assert_eq!(info, expected);
 }
 
+#[test]
+fn consensus_status() {
+    let mut testkit = create_testkit();
+    testkit.create_blocks_until(Height(3));
+    let api = testkit.api();
+
+    let info: ConsensusStatusInfo = api
+        .public(ApiKind::System)
+        .get("v1/consensus_status")
+        .unwrap();
+    // The testkit does not update `SharedNodeState`, thus only the height is known.
+    let expected = ConsensusStatusInfo {
+        height: Height(4),
+        round: None,
+        is_leader: false,
+        is_enabled: true,
+        consensus_status: ConsensusStatus::Enabled,
+        validators: vec![],
+    };
+    assert_eq!(info, expected);
+}
+
 #[test]
 fn stats() {
     let mut testkit = create_testkit();
@@ -78,6 +107,22 @@ fn network() {
     assert!(info.core_version.is_some());
 }
 
+#[test]
+fn rotate_network_key() {
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+    let public_key: PublicKey = api
+        .private(ApiKind::System)
+        .post("v1/network/rotate_key")
+        .unwrap();
+
+    let control_messages = testkit.poll_control_messages();
+    match control_messages.as_slice() {
+        [ExternalMessage::RotateNetworkKey(keypair)] if keypair.public_key() == public_key => {}
+        _ => panic!("Unexpected control messages: {:?}", control_messages),
+    }
+}
+
 #[test]
 fn shutdown() {
     let mut testkit = create_testkit();
@@ -91,3 +136,154 @@ fn shutdown() {
         _ => panic!("Unexpected control messages: {:?}", control_messages),
     }
 }
+
+#[test]
+fn log_filters() {
+    // The logger may be already installed by another test.
+    let _ = init_logger();
+
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+    let mut query = HashMap::new();
+    query.insert("filters", "info,exonum_node=trace");
+    api.private(ApiKind::System)
+        .query(&query)
+        .post::<()>("v1/log_filters")
+        .unwrap();
+
+    let filters: String = api.private(ApiKind::System).get("v1/log_filters").unwrap();
+    assert_eq!(filters, "info,exonum_node=trace");
+
+    // Malformed directives are rejected, and the current directives are retained.
+    query.insert("filters", "info,exonum_node=loud");
+    let err = api
+        .private(ApiKind::System)
+        .query(&query)
+        .post::<()>("v1/log_filters")
+        .unwrap_err();
+    match err {
+        ApiError::BadRequest(msg) => assert!(msg.contains("Invalid log level `loud`")),
+        other => panic!("Unexpected error: {:?}", other),
+    }
+    let filters: String = api.private(ApiKind::System).get("v1/log_filters").unwrap();
+    assert_eq!(filters, "info,exonum_node=trace");
+}
+
+#[derive(Serialize)]
+struct BanPeerQuery {
+    public_key: PublicKey,
+    duration_secs: Option<u64>,
+}
+
+#[test]
+fn consensus_metrics() {
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+    // The testkit does not update `SharedNodeState`, thus the metrics are not reported.
+    let metrics: Option<ConsensusMetrics> = api
+        .private(ApiKind::System)
+        .get("v1/consensus_metrics")
+        .unwrap();
+    assert_eq!(metrics, None);
+}
+
+#[test]
+fn peer_bans() {
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+    let banned_peers: Vec<BannedPeer> = api.private(ApiKind::System).get("v1/bans").unwrap();
+    assert!(banned_peers.is_empty());
+
+    let public_key = gen_keypair().0;
+    let query = BanPeerQuery {
+        public_key,
+        duration_secs: Some(60),
+    };
+    api.private(ApiKind::System)
+        .query(&query)
+        .post::<()>("v1/bans")
+        .unwrap();
+    let mut query = HashMap::new();
+    query.insert("public_key", public_key);
+    api.private(ApiKind::System)
+        .query(&query)
+        .post::<()>("v1/bans/remove")
+        .unwrap();
+
+    let control_messages = testkit.poll_control_messages();
+    match control_messages.as_slice() {
+        [ExternalMessage::BanPeer(banned_key, Some(duration)), ExternalMessage::UnbanPeer(unbanned_key)]
+            if *banned_key == public_key
+                && *duration == Duration::from_secs(60)
+                && *unbanned_key == public_key => {}
+        _ => panic!("Unexpected control messages: {:?}", control_messages),
+    }
+}
+
+#[test]
+fn peer_remove() {
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+
+    let public_key = gen_keypair().0;
+    let mut query = HashMap::new();
+    query.insert("public_key", public_key);
+    api.private(ApiKind::System)
+        .query(&query)
+        .post::<()>("v1/peers/remove")
+        .unwrap();
+
+    let control_messages = testkit.poll_control_messages();
+    match control_messages.as_slice() {
+        [ExternalMessage::PeerRemove(removed_key, false)] if *removed_key == public_key => {}
+        _ => panic!("Unexpected control messages: {:?}", control_messages),
+    }
+}
+
+#[test]
+fn resource_usage() {
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+    let usage: ResourceUsage = api.private(ApiKind::System).get("v1/resources").unwrap();
+    assert!(usage.database_size.unwrap() > 0);
+    assert_eq!(usage.tx_pool_len, 0);
+    // The testkit does not update `SharedNodeState`, thus the caches are reported empty.
+    assert_eq!(usage.caches, CacheUsage::default());
+    assert_eq!(usage.incoming_connections, 0);
+    assert_eq!(usage.outgoing_connections, 0);
+}
+
+#[test]
+fn peer_disconnect() {
+    #[derive(Serialize)]
+    struct RemovePeerQuery {
+        public_key: PublicKey,
+        keep_in_connect_list: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct PeersInfo {
+        connect_list: Vec<PeerConnection>,
+    }
+
+    let mut testkit = create_testkit();
+    let api = testkit.api();
+    // The testkit does not update `SharedNodeState`, thus no connections are reported.
+    let peers: PeersInfo = api.private(ApiKind::System).get("v1/peers").unwrap();
+    assert!(peers.connect_list.is_empty());
+
+    let public_key = gen_keypair().0;
+    api.private(ApiKind::System)
+        .query(&RemovePeerQuery {
+            public_key,
+            keep_in_connect_list: true,
+        })
+        .post::<()>("v1/peers/remove")
+        .unwrap();
+
+    let control_messages = testkit.poll_control_messages();
+    match control_messages.as_slice() {
+        [ExternalMessage::PeerRemove(key, true)] if *key == public_key => {}
+        _ => panic!("Unexpected control messages: {:?}", control_messages),
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Light client for the cryptocurrency service.
+//!
+//! The client queries a wallet from a node and verifies the response locally,
+//! trusting only the consensus keys of the validators:
+//!
+//! 1. The block proof is checked to be authorized by a supermajority of the validators.
+//! 2. The aggregation proof is checked to tie the hash of the wallets table
+//!    to the state hash of the block.
+//! 3. The map proof is checked to tie the wallet to the hash of the wallets table.
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run --example light_client -- \
+//!     <node_url> <service_name> <wallet_key> <validator_consensus_key>...
+//! ```
+//!
+//! Validator consensus keys must be specified in the order of validator IDs,
+//! e.g., as in the `consensus_key` fields of the consensus configuration.
+
+use exonum::{blockchain::IndexProof, crypto::PublicKey};
+use exonum_cryptocurrency_advanced::{
+    api::{WalletInfo, WalletQuery},
+    wallet::Wallet,
+};
+use failure::{bail, ensure, format_err, Error};
+use hex::FromHex;
+
+use std::env;
+
+/// Name of the wallets table within the service.
+const WALLETS_TABLE: &str = "wallets";
+
+fn parse_key(key: &str) -> Result<PublicKey, Error> {
+    PublicKey::from_hex(key).map_err(|e| format_err!("Invalid public key {}: {}", key, e))
+}
+
+/// Requests the wallet information from the node. The response is not trusted.
+fn fetch_wallet_info(
+    node_url: &str,
+    service_name: &str,
+    pub_key: PublicKey,
+) -> Result<WalletInfo, Error> {
+    let url = format!(
+        "{}/api/services/{}/v1/wallets/info",
+        node_url.trim_end_matches('/'),
+        service_name
+    );
+    let mut response = reqwest::Client::new()
+        .get(&url)
+        .query(&WalletQuery { pub_key })
+        .send()?
+        .error_for_status()?;
+    Ok(response.json()?)
+}
+
+/// Verifies the wallet information against the validator keys. Returns the wallet together
+/// with the height of the block it is proven for; the wallet is `None` if the node
+/// has proven that the wallet does not exist.
+fn verify_wallet_info(
+    info: WalletInfo,
+    service_name: &str,
+    pub_key: &PublicKey,
+    validator_keys: &[PublicKey],
+) -> Result<(Option<Wallet>, u64), Error> {
+    let height = info.block_proof.block.height.0;
+    let index_proof = IndexProof {
+        block_proof: info.block_proof,
+        index_proof: info.wallet_proof.to_table,
+    };
+    // Checks the block proof and the aggregation proof.
+    let (index_name, index_hash) = index_proof.verify(validator_keys)?;
+    let expected_name = format!("{}.{}", service_name, WALLETS_TABLE);
+    ensure!(
+        index_name == expected_name,
+        "Proof is given for table `{}`, expected `{}`",
+        index_name,
+        expected_name
+    );
+
+    let wallet_proof = info.wallet_proof.to_wallet.check_against_hash(index_hash)?;
+    let wallet = wallet_proof
+        .all_entries()
+        .find(|(key, _)| *key == pub_key)
+        .map(|(_, wallet)| wallet.cloned());
+    match wallet {
+        Some(wallet) => Ok((wallet, height)),
+        None => bail!("Proof does not mention the requested wallet"),
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<_> = env::args().skip(1).collect();
+    if args.len() < 4 {
+        bail!("Usage: light_client <node_url> <service_name> <wallet_key> <validator_key>...");
+    }
+    let (node_url, service_name) = (&args[0], &args[1]);
+    let pub_key = parse_key(&args[2])?;
+    let validator_keys = args[3..]
+        .iter()
+        .map(|key| parse_key(key))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let info = fetch_wallet_info(node_url, service_name, pub_key)?;
+    let (wallet, height) = verify_wallet_info(info, service_name, &pub_key, &validator_keys)?;
+    match wallet {
+        Some(wallet) => println!(
+            "Verified at height {}: wallet {} ({}) has balance {}",
+            height,
+            pub_key.to_hex(),
+            wallet.name,
+            wallet.balance
+        ),
+        None => println!(
+            "Verified at height {}: wallet {} does not exist",
+            height,
+            pub_key.to_hex()
+        ),
+    }
+    Ok(())
+}
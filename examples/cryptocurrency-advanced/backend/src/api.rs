@@ -32,6 +32,22 @@ pub struct WalletQuery {
     pub pub_key: PublicKey,
 }
 
+/// Maximum number of transactions in a page of the wallet history.
+pub const MAX_HISTORY_PAGE_SIZE: u64 = 100;
+
+/// Describes the query parameters for the `wallet_history` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct WalletHistoryQuery {
+    /// Public key of the queried wallet.
+    pub pub_key: PublicKey,
+    /// Position of the first transaction of the page in the wallet history.
+    #[serde(default)]
+    pub start: u64,
+    /// Maximum number of transactions in the page. Cannot exceed `MAX_HISTORY_PAGE_SIZE`,
+    /// which is also used as the default value.
+    pub count: Option<u64>,
+}
+
 /// Proof of existence for specific wallet.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletProof {
@@ -61,12 +77,31 @@ pub struct WalletInfo {
     pub wallet_history: Option<WalletHistory>,
 }
 
+/// Page of the wallet history.
+///
+/// The page is verified in the following order: `block_proof` proves the state hash,
+/// `wallet_proof` proves the wallet against this hash, and the range proof in `history`
+/// is checked against the `history_hash` of the proved wallet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletHistoryPage {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate wallet.
+    pub wallet_proof: WalletProof,
+    /// Transactions of the page together with their range proof.
+    /// `None` if the wallet does not exist.
+    pub history: Option<WalletHistory>,
+    /// Position of the first transaction of the next page, or `None` if the page is the last.
+    pub next_start: Option<u64>,
+}
+
 /// Public service API description.
 #[derive(Debug, Clone, Copy)]
 pub struct PublicApi;
 
 impl PublicApi {
-    /// Endpoint for getting a single wallet.
+    /// Endpoint for getting a single wallet together with its full history.
+    /// Use `wallet_history` to fetch long histories in pages.
     pub fn wallet_info(
         self,
         state: &ServiceApiState<'_>,
@@ -109,13 +144,79 @@ impl PublicApi {
         })
     }
 
-    /// Wires the above endpoint to public scope of the given `ServiceApiBuilder`.
+    /// Endpoint for getting a page of the wallet history.
+    pub fn wallet_history(
+        self,
+        state: &ServiceApiState<'_>,
+        query: WalletHistoryQuery,
+    ) -> api::Result<WalletHistoryPage> {
+        let count = query.count.unwrap_or(MAX_HISTORY_PAGE_SIZE);
+        if count == 0 || count > MAX_HISTORY_PAGE_SIZE {
+            return Err(api::Error::BadRequest(format!(
+                "Page size should be positive and not exceed {}",
+                MAX_HISTORY_PAGE_SIZE
+            )));
+        }
+
+        let IndexProof {
+            block_proof,
+            index_proof,
+        } = state.data().proof_for_service_index("wallets").unwrap();
+
+        let currency_schema = SchemaImpl::new(state.service_data());
+        let pub_key = query.pub_key;
+        let wallet_proof = WalletProof {
+            to_table: index_proof,
+            to_wallet: currency_schema.public.wallets.get_proof(pub_key),
+        };
+        let wallet = currency_schema.public.wallets.get(&pub_key);
+
+        let mut next_start = None;
+        let history = wallet.map(|_| {
+            let history = currency_schema.wallet_history.get(&pub_key);
+            let end = query.start.saturating_add(count).min(history.len());
+            if end < history.len() {
+                next_start = Some(end);
+            }
+            let proof = history.get_range_proof(query.start..query.start.max(end));
+
+            let transactions = state.data().for_core().transactions();
+            let transactions = (query.start..end)
+                .map(|i| {
+                    let tx_hash = history.get(i).unwrap();
+                    transactions.get(&tx_hash).unwrap()
+                })
+                .collect();
+
+            WalletHistory {
+                proof,
+                transactions,
+            }
+        });
+
+        Ok(WalletHistoryPage {
+            block_proof,
+            wallet_proof,
+            history,
+            next_start,
+        })
+    }
+
+    /// Wires the above endpoints to public scope of the given `ServiceApiBuilder`.
     pub fn wire(self, builder: &mut ServiceApiBuilder) {
-        builder.public_scope().endpoint(
-            "v1/wallets/info",
-            move |state: &ServiceApiState<'_>, query: WalletQuery| {
-                self.wallet_info(state, query.pub_key)
-            },
-        );
+        builder
+            .public_scope()
+            .endpoint(
+                "v1/wallets/info",
+                move |state: &ServiceApiState<'_>, query: WalletQuery| {
+                    self.wallet_info(state, query.pub_key)
+                },
+            )
+            .endpoint(
+                "v1/wallets/history",
+                move |state: &ServiceApiState<'_>, query: WalletHistoryQuery| {
+                    self.wallet_history(state, query)
+                },
+            );
     }
 }
@@ -0,0 +1,39 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Escrowed funds released by an arbiter.
+
+use exonum::crypto::PublicKey;
+use exonum_derive::{BinaryValue, ObjectHash};
+use exonum_proto::ProtobufConvert;
+
+use super::proto;
+
+/// Currency locked by the payer until the arbiter releases it to the payee.
+/// If the funds are not released before the timeout, they are refunded to the payer
+/// automatically.
+#[derive(Clone, Debug, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Escrow", serde_pb_convert)]
+pub struct Escrow {
+    /// `PublicKey` of payer's wallet.
+    pub payer: PublicKey,
+    /// `PublicKey` of payee's wallet.
+    pub payee: PublicKey,
+    /// `PublicKey` of the arbiter allowed to release the funds.
+    pub arbiter: PublicKey,
+    /// Amount of locked currency.
+    pub amount: u64,
+    /// Height at which the funds are refunded to the payer unless released before.
+    pub timeout_height: u64,
+}
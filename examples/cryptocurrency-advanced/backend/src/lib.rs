@@ -20,34 +20,73 @@
 #[macro_use]
 extern crate serde_derive; // Required for Protobuf.
 
-pub use crate::{schema::Schema, transactions::CryptocurrencyInterface};
+pub use crate::{
+    schema::{Config, Schema},
+    transactions::{CryptocurrencyInterface, CryptocurrencyInterfaceMut},
+};
 
 pub mod api;
+pub mod escrow;
+pub mod multisig;
 pub mod proto;
 pub mod schema;
+pub mod swap;
 pub mod transactions;
 pub mod wallet;
 
-use exonum::runtime::ExecutionError;
+use exonum::{
+    merkledb::BinaryValue,
+    runtime::{CommonError, ExecutionError},
+};
 use exonum_derive::{ServiceDispatcher, ServiceFactory};
 use exonum_rust_runtime::{api::ServiceApiBuilder, CallContext, Service};
+use exonum_supervisor::Configure;
 
-use crate::{api::PublicApi as CryptocurrencyApi, schema::SchemaImpl};
+use crate::{api::PublicApi as CryptocurrencyApi, schema::SchemaImpl, transactions::Error};
 
-/// Initial balance of the wallet.
+/// Initial balance of the wallet used if the service configuration does not specify otherwise.
 pub const INITIAL_BALANCE: u64 = 100;
 
 /// Cryptocurrency service implementation.
 #[derive(Debug, ServiceDispatcher, ServiceFactory)]
-#[service_dispatcher(implements("CryptocurrencyInterface"))]
+#[service_dispatcher(implements("CryptocurrencyInterface", raw = "Configure<Params = Config>"))]
 #[service_factory(proto_sources = "proto")]
 pub struct CryptocurrencyService;
 
 impl Service for CryptocurrencyService {
-    fn initialize(&self, context: CallContext<'_>, _params: Vec<u8>) -> Result<(), ExecutionError> {
+    fn initialize(&self, context: CallContext<'_>, params: Vec<u8>) -> Result<(), ExecutionError> {
+        let config = if params.is_empty() {
+            Config::default()
+        } else {
+            Config::from_bytes(params.into()).map_err(CommonError::malformed_arguments)?
+        };
+        if !config.is_valid() {
+            return Err(Error::InvalidConfig.into());
+        }
         // Initialize indexes. Not doing this may lead to errors in HTTP API, since it relies on
         // `wallets` indexes being initialized for returning corresponding proofs.
-        SchemaImpl::new(context.service_data());
+        let mut schema = SchemaImpl::new(context.service_data());
+        schema.public.config.set(config);
+        Ok(())
+    }
+
+    fn before_transactions(&self, context: CallContext<'_>) -> Result<(), ExecutionError> {
+        // Refund escrows which have not been released before their timeout.
+        let height = context.data().for_core().next_height().0;
+        SchemaImpl::new(context.service_data()).refund_expired_escrows(height)
+    }
+
+    fn after_transactions(&self, context: CallContext<'_>) -> Result<(), ExecutionError> {
+        // Transfer fees collected in the block to the wallets of the validators.
+        let validator_keys: Vec<_> = context
+            .data()
+            .for_core()
+            .consensus_config()
+            .validator_keys
+            .iter()
+            .map(|keys| keys.service_key)
+            .collect();
+        SchemaImpl::new(context.service_data()).distribute_fees(&validator_keys);
         Ok(())
     }
 
@@ -55,3 +94,41 @@ impl Service for CryptocurrencyService {
         CryptocurrencyApi.wire(builder);
     }
 }
+
+impl Configure for CryptocurrencyService {
+    type Params = Config;
+
+    fn verify_config(
+        &self,
+        context: CallContext<'_>,
+        params: Self::Params,
+    ) -> Result<(), ExecutionError> {
+        context
+            .caller()
+            .as_supervisor()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        // The currency which is already issued cannot be revoked.
+        let schema = SchemaImpl::new(context.service_data());
+        if params.is_valid() && params.allows_supply(schema.total_supply()) {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig.into())
+        }
+    }
+
+    fn apply_config(
+        &self,
+        context: CallContext<'_>,
+        params: Self::Params,
+    ) -> Result<(), ExecutionError> {
+        context
+            .caller()
+            .as_supervisor()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        schema.public.config.set(params);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Multisignature wallets.
+
+use exonum::crypto::PublicKey;
+use exonum_derive::{BinaryValue, ObjectHash};
+use exonum_proto::ProtobufConvert;
+
+use super::proto;
+
+/// Owners of a multisignature wallet stored in the database.
+#[derive(Clone, Debug, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::MultisigConfig", serde_pb_convert)]
+pub struct MultisigConfig {
+    /// Keys of the wallet owners.
+    pub owners: Vec<PublicKey>,
+    /// Number of owners required to approve a transfer from the wallet.
+    pub quorum: u32,
+}
+
+impl MultisigConfig {
+    /// Checks whether the key belongs to one of the wallet owners.
+    pub fn is_owner(&self, key: &PublicKey) -> bool {
+        self.owners.contains(key)
+    }
+}
+
+/// Transfer from a multisignature wallet waiting for approvals of the wallet owners.
+#[derive(Clone, Debug, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::PendingTransfer", serde_pb_convert)]
+pub struct PendingTransfer {
+    /// `PublicKey` of the multisignature wallet.
+    pub from: PublicKey,
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of currency to transfer.
+    pub amount: u64,
+    /// Owners who have approved the transfer.
+    pub approvals: Vec<PublicKey>,
+}
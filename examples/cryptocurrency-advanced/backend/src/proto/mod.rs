@@ -17,7 +17,12 @@
 // For protobuf generated files.
 #![allow(bare_trait_objects)]
 
-pub use self::service::{CreateWallet, Issue, Transfer, Wallet};
+pub use self::service::{
+    Approve, ApproveTransfer, ClaimFunds, ClaimSwap, CreateEscrow, CreateMultisigWallet,
+    CreateWallet, Escrow, FreezeWallet, HashLock, Issue, LockFunds, MultisigConfig,
+    PendingTransfer, ProposeTransfer, RefundFunds, ReleaseEscrow, Transfer, TransferBatch,
+    TransferBatchItem, TransferFrom, UnfreezeWallet, Wallet,
+};
 
 include!(concat!(env!("OUT_DIR"), "/protobuf_mod.rs"));
 
@@ -14,14 +14,145 @@
 
 //! Cryptocurrency database schema.
 
-use exonum::crypto::{Hash, PublicKey};
+use exonum::crypto::{self, Hash, PublicKey, PUBLIC_KEY_LENGTH};
 use exonum::merkledb::{
     access::{Access, FromAccess, RawAccessMut},
-    Group, ObjectHash, ProofListIndex, RawProofMapIndex,
+    BinaryKey, Group, KeySetIndex, ObjectHash, ProofEntry, ProofListIndex, ProofMapIndex,
+    RawProofMapIndex,
 };
-use exonum_derive::{FromAccess, RequireArtifact};
+use exonum::runtime::ExecutionError;
+use exonum_derive::{BinaryValue, FromAccess, ObjectHash, RequireArtifact};
+use exonum_proto::ProtobufConvert;
 
-use crate::{wallet::Wallet, INITIAL_BALANCE};
+use crate::{
+    escrow::Escrow,
+    multisig::{MultisigConfig, PendingTransfer},
+    proto,
+    swap::HashLock,
+    transactions::Error,
+    wallet::Wallet,
+    INITIAL_BALANCE,
+};
+
+/// Configuration of the cryptocurrency service passed to the service constructor
+/// and to the `Configure` interface. If the constructor parameters are empty,
+/// the default configuration is used.
+#[derive(Clone, Debug, PartialEq)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Config", serde_pb_convert)]
+pub struct Config {
+    /// Fee charged from the sender for each transfer. Collected fees are distributed
+    /// among the wallets of the validators at the end of each block.
+    pub transfer_fee: u64,
+    /// Key allowed to freeze and unfreeze wallets. If not set, wallets cannot be frozen.
+    #[protobuf_convert(with = "self::pb_optional_public_key")]
+    pub admin_key: Option<PublicKey>,
+    /// Balance of newly created wallets. The balance is counted towards the total supply
+    /// of the currency. The balance must be positive; since Protobuf does not distinguish
+    /// an omitted value from zero, configurations with zero balance are rejected.
+    pub initial_balance: u64,
+    /// Key allowed to issue currency. If not set, any wallet owner can issue currency.
+    #[protobuf_convert(with = "self::pb_optional_public_key")]
+    pub issuer_key: Option<PublicKey>,
+    /// Maximum total supply of the currency. Zero, which is also the value
+    /// of an omitted field, means that the supply is not limited.
+    pub max_supply: u64,
+}
+
+/// The default configuration has no fees, no administrator and no issuer; wallets
+/// are created with `INITIAL_BALANCE`, and the supply is not limited.
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            transfer_fee: 0,
+            admin_key: None,
+            initial_balance: INITIAL_BALANCE,
+            issuer_key: None,
+            max_supply: 0,
+        }
+    }
+}
+
+impl Config {
+    /// Checks whether the configuration can be used by the service.
+    pub fn is_valid(&self) -> bool {
+        self.initial_balance > 0
+    }
+
+    /// Checks whether the given total supply of the currency is allowed by the configuration.
+    pub fn allows_supply(&self, total_supply: u64) -> bool {
+        self.max_supply == 0 || total_supply <= self.max_supply
+    }
+}
+
+/// Key of the allowances map combining the keys of the wallet owner and the spender.
+///
+/// Keys are serialized as the owner key followed by the spender key, so that
+/// the allowances given by the same owner are adjacent in the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AllowanceKey {
+    /// Key of the wallet owner.
+    pub owner: PublicKey,
+    /// Key of the spender allowed to transfer currency from the owner's wallet.
+    pub spender: PublicKey,
+}
+
+impl AllowanceKey {
+    /// Creates a new key.
+    pub fn new(owner: PublicKey, spender: PublicKey) -> Self {
+        Self { owner, spender }
+    }
+}
+
+impl BinaryKey for AllowanceKey {
+    fn size(&self) -> usize {
+        PUBLIC_KEY_LENGTH * 2
+    }
+
+    fn write(&self, buffer: &mut [u8]) -> usize {
+        self.owner.write(&mut buffer[..PUBLIC_KEY_LENGTH]);
+        self.spender.write(&mut buffer[PUBLIC_KEY_LENGTH..]);
+        self.size()
+    }
+
+    fn read(buffer: &[u8]) -> Self {
+        Self {
+            owner: PublicKey::read(&buffer[..PUBLIC_KEY_LENGTH]),
+            spender: PublicKey::read(&buffer[PUBLIC_KEY_LENGTH..]),
+        }
+    }
+}
+
+impl ObjectHash for AllowanceKey {
+    fn object_hash(&self) -> Hash {
+        let mut buffer = [0; PUBLIC_KEY_LENGTH * 2];
+        self.write(&mut buffer);
+        crypto::hash(&buffer)
+    }
+}
+
+mod pb_optional_public_key {
+    use exonum::crypto::proto::types::PublicKey as PbPublicKey;
+    use exonum_proto::ProtobufConvert;
+
+    use super::PublicKey;
+
+    pub fn from_pb(pb: PbPublicKey) -> Result<Option<PublicKey>, failure::Error> {
+        if pb.get_data().is_empty() {
+            Ok(None)
+        } else {
+            PublicKey::from_pb(pb).map(Some)
+        }
+    }
+
+    pub fn to_pb(value: &Option<PublicKey>) -> PbPublicKey {
+        if let Some(key) = value {
+            key.to_pb()
+        } else {
+            PbPublicKey::new()
+        }
+    }
+}
 
 /// Database schema for the cryptocurrency.
 ///
@@ -33,6 +164,12 @@ pub(crate) struct SchemaImpl<T: Access> {
     pub public: Schema<T>,
     /// History for specific wallets.
     pub wallet_history: Group<T, PublicKey, ProofListIndex<T::Base, Hash>>,
+    /// Amounts of currency the spenders are allowed to transfer from the owners' wallets.
+    /// Unlike index groups, a single map with composite keys is aggregated into
+    /// the blockchain state hash.
+    pub allowances: ProofMapIndex<T::Base, AllowanceKey, u64>,
+    /// Escrows scheduled for refund, grouped by the timeout height.
+    pub escrow_timeouts: Group<T, u64, KeySetIndex<T::Base, Hash>>,
 }
 
 /// Public part of the cryptocurrency schema.
@@ -40,12 +177,50 @@ pub(crate) struct SchemaImpl<T: Access> {
 pub struct Schema<T: Access> {
     /// Map of wallet keys to information about the corresponding account.
     pub wallets: RawProofMapIndex<T::Base, PublicKey, Wallet>,
+    /// Map of multisignature wallet keys to the owners of the corresponding wallet.
+    pub multisig_wallets: RawProofMapIndex<T::Base, PublicKey, MultisigConfig>,
+    /// Transfers from multisignature wallets waiting for approvals, keyed by the hash
+    /// of the proposing transaction.
+    pub pending_transfers: RawProofMapIndex<T::Base, Hash, PendingTransfer>,
+    /// Currency locked for atomic swaps, keyed by the hash of the locking transaction.
+    pub hash_locks: RawProofMapIndex<T::Base, Hash, HashLock>,
+    /// Currency locked in escrows, keyed by the hash of the creating transaction.
+    pub escrows: RawProofMapIndex<T::Base, Hash, Escrow>,
+    /// Service configuration.
+    pub config: ProofEntry<T::Base, Config>,
+    /// Fees collected from transfers, which are not yet distributed among validators.
+    pub fee_pool: ProofEntry<T::Base, u64>,
+    /// Total amount of the issued currency, including the initial balances of wallets.
+    pub total_supply: ProofEntry<T::Base, u64>,
 }
 
 impl<T: Access> SchemaImpl<T> {
     pub fn new(access: T) -> Self {
         Self::from_root(access).unwrap()
     }
+
+    /// Returns the service configuration.
+    pub(crate) fn config(&self) -> Config {
+        self.public.config.get().unwrap_or_default()
+    }
+
+    /// Returns the amount of currency the spender is allowed to transfer from
+    /// the owner's wallet.
+    pub(crate) fn allowance(&self, owner: &PublicKey, spender: &PublicKey) -> u64 {
+        self.allowances
+            .get(&AllowanceKey::new(*owner, *spender))
+            .unwrap_or_default()
+    }
+
+    /// Returns the total amount of the issued currency.
+    pub(crate) fn total_supply(&self) -> u64 {
+        self.public.total_supply.get().unwrap_or_default()
+    }
+
+    /// Returns the fee charged for each transfer.
+    pub(crate) fn transfer_fee(&self) -> u64 {
+        self.config().transfer_fee
+    }
 }
 
 impl<T> SchemaImpl<T>
@@ -71,30 +246,197 @@ where
         self.public.wallets.put(&wallet_key, wallet);
     }
 
-    /// Decrease balance of the wallet and append new record to its history.
+    /// Decrease balance of the wallet by `amount` and `fee`, add the fee to the fee pool
+    /// and append new record to the wallet history.
     ///
     /// Panics if there is no wallet with given public key.
     pub(crate) fn decrease_wallet_balance(
         &mut self,
         wallet: Wallet,
         amount: u64,
+        fee: u64,
         transaction: Hash,
     ) {
         let mut history = self.wallet_history.get(&wallet.pub_key);
         history.push(transaction);
         let history_hash = history.object_hash();
         let balance = wallet.balance;
-        let wallet = wallet.set_balance(balance - amount, &history_hash);
+        let wallet = wallet.set_balance(balance - amount - fee, &history_hash);
         let wallet_key = wallet.pub_key;
         self.public.wallets.put(&wallet_key, wallet);
+
+        if fee > 0 {
+            let fee_pool = self.public.fee_pool.get().unwrap_or_default();
+            self.public.fee_pool.set(fee_pool + fee);
+        }
+    }
+
+    /// Distributes the fee pool equally among the existing wallets with the given keys.
+    /// The remainder of the division stays in the pool until the next distribution.
+    ///
+    /// Fee rewards are not caused by any transaction, hence they are not recorded
+    /// in the wallet histories.
+    pub(crate) fn distribute_fees(&mut self, keys: &[PublicKey]) {
+        let fee_pool = self.public.fee_pool.get().unwrap_or_default();
+        let wallets: Vec<_> = keys
+            .iter()
+            .filter_map(|key| self.public.wallets.get(key))
+            .collect();
+        if wallets.is_empty() {
+            return;
+        }
+
+        let share = fee_pool / wallets.len() as u64;
+        if share == 0 {
+            return;
+        }
+        for wallet in &wallets {
+            let wallet = Wallet {
+                balance: wallet.balance + share,
+                ..wallet.clone()
+            };
+            self.public.wallets.put(&wallet.pub_key, wallet);
+        }
+        self.public
+            .fee_pool
+            .set(fee_pool - share * wallets.len() as u64);
+    }
+
+    /// Stores the pending transfer with updated approvals, or executes it if it is
+    /// approved by the required number of the wallet owners. The transfer is identified
+    /// by the hash of the proposing transaction, which is also recorded in the wallet
+    /// histories once the transfer is executed.
+    pub(crate) fn approve_transfer(
+        &mut self,
+        transfer_id: Hash,
+        transfer: PendingTransfer,
+        config: &MultisigConfig,
+    ) -> Result<(), ExecutionError> {
+        if transfer.approvals.len() < config.quorum as usize {
+            self.public.pending_transfers.put(&transfer_id, transfer);
+            return Ok(());
+        }
+
+        let sender = self
+            .public
+            .wallets
+            .get(&transfer.from)
+            .ok_or(Error::SenderNotFound)?;
+        let receiver = self
+            .public
+            .wallets
+            .get(&transfer.to)
+            .ok_or(Error::ReceiverNotFound)?;
+        if sender.frozen || receiver.frozen {
+            return Err(Error::WalletFrozen.into());
+        }
+        let fee = self.transfer_fee();
+        if !sender.has_funds(transfer.amount, fee) {
+            return Err(Error::InsufficientCurrencyAmount.into());
+        }
+        self.decrease_wallet_balance(sender, transfer.amount, fee, transfer_id);
+        self.increase_wallet_balance(receiver, transfer.amount, transfer_id);
+        self.public.pending_transfers.remove(&transfer_id);
+        Ok(())
+    }
+
+    /// Sets the amount of currency the spender is allowed to transfer from the owner's wallet.
+    pub(crate) fn set_allowance(&mut self, owner: &PublicKey, spender: &PublicKey, amount: u64) {
+        let key = AllowanceKey::new(*owner, *spender);
+        if amount == 0 {
+            self.allowances.remove(&key);
+        } else {
+            self.allowances.put(&key, amount);
+        }
+    }
+
+    /// Credits the locked funds to the wallet and removes the lock. The lock is recorded
+    /// in the wallet history by the hash of the locking transaction.
+    pub(crate) fn release_lock(
+        &mut self,
+        lock_id: Hash,
+        key: &PublicKey,
+        amount: u64,
+    ) -> Result<(), ExecutionError> {
+        let wallet = self
+            .public
+            .wallets
+            .get(key)
+            .ok_or(Error::ReceiverNotFound)?;
+        self.increase_wallet_balance(wallet, amount, lock_id);
+        self.public.hash_locks.remove(&lock_id);
+        Ok(())
     }
 
-    /// Create new wallet and append first record to its history.
-    pub(crate) fn create_wallet(&mut self, key: &PublicKey, name: &str, transaction: Hash) {
+    /// Stores the escrow and schedules its refund at the timeout height.
+    pub(crate) fn create_escrow(&mut self, escrow_id: Hash, escrow: Escrow) {
+        self.escrow_timeouts
+            .get(&escrow.timeout_height)
+            .insert(escrow_id);
+        self.public.escrows.put(&escrow_id, escrow);
+    }
+
+    /// Credits the escrowed funds to the wallet and removes the escrow. The escrow is
+    /// recorded in the wallet history by the hash of the creating transaction.
+    pub(crate) fn release_escrow(
+        &mut self,
+        escrow_id: Hash,
+        escrow: &Escrow,
+        key: &PublicKey,
+    ) -> Result<(), ExecutionError> {
+        let wallet = self
+            .public
+            .wallets
+            .get(key)
+            .ok_or(Error::ReceiverNotFound)?;
+        self.increase_wallet_balance(wallet, escrow.amount, escrow_id);
+        self.public.escrows.remove(&escrow_id);
+        self.escrow_timeouts
+            .get(&escrow.timeout_height)
+            .remove(&escrow_id);
+        Ok(())
+    }
+
+    /// Refunds the escrows with the given timeout height to their payers.
+    pub(crate) fn refund_expired_escrows(&mut self, height: u64) -> Result<(), ExecutionError> {
+        let expired: Vec<_> = self.escrow_timeouts.get(&height).iter().collect();
+        for escrow_id in expired {
+            let escrow = self.public.escrows.get(&escrow_id).unwrap();
+            self.release_escrow(escrow_id, &escrow, &escrow.payer)?;
+        }
+        Ok(())
+    }
+
+    /// Increases the total supply of the currency by `amount`, unless this exceeds
+    /// the maximum supply specified in the service configuration.
+    pub(crate) fn increase_supply(&mut self, amount: u64) -> Result<(), ExecutionError> {
+        let total_supply = self
+            .total_supply()
+            .checked_add(amount)
+            .ok_or(Error::SupplyExceeded)?;
+        if !self.config().allows_supply(total_supply) {
+            return Err(Error::SupplyExceeded.into());
+        }
+        self.public.total_supply.set(total_supply);
+        Ok(())
+    }
+
+    /// Create new wallet with the initial balance specified in the service configuration
+    /// and append first record to its history.
+    pub(crate) fn create_wallet(
+        &mut self,
+        key: &PublicKey,
+        name: &str,
+        transaction: Hash,
+    ) -> Result<(), ExecutionError> {
+        let balance = self.config().initial_balance;
+        self.increase_supply(balance)?;
+
         let mut history = self.wallet_history.get(key);
         history.push(transaction);
         let history_hash = history.object_hash();
-        let wallet = Wallet::new(key, name, INITIAL_BALANCE, history.len(), &history_hash);
+        let wallet = Wallet::new(key, name, balance, history.len(), &history_hash);
         self.public.wallets.put(key, wallet);
+        Ok(())
     }
 }
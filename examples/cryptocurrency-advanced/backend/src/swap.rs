@@ -0,0 +1,38 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hash-time-locked funds used for atomic swaps.
+
+use exonum::crypto::{Hash, PublicKey};
+use exonum_derive::{BinaryValue, ObjectHash};
+use exonum_proto::ProtobufConvert;
+
+use super::proto;
+
+/// Currency locked by the sender until the receiver reveals the secret matching
+/// the hash lock, or the timeout expires.
+#[derive(Clone, Debug, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::HashLock", serde_pb_convert)]
+pub struct HashLock {
+    /// `PublicKey` of sender's wallet.
+    pub from: PublicKey,
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of locked currency.
+    pub amount: u64,
+    /// Hash of the secret required to claim the funds.
+    pub hash_lock: Hash,
+    /// Height starting from which the funds can no longer be claimed and can be refunded.
+    pub timeout_height: u64,
+}
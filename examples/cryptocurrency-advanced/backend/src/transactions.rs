@@ -15,14 +15,21 @@
 //! Cryptocurrency transactions.
 
 use exonum::{
-    crypto::PublicKey,
-    runtime::{CommonError, ExecutionError},
+    crypto::{self, Hash, PublicKey},
+    runtime::{CommonError, ExecutionError, ExecutionFail, InstanceId},
 };
 use exonum_derive::{exonum_interface, interface_method, BinaryValue, ExecutionFail, ObjectHash};
 use exonum_proto::ProtobufConvert;
 use exonum_rust_runtime::CallContext;
 
-use super::{proto, schema::SchemaImpl, CryptocurrencyService};
+use super::{
+    escrow::Escrow,
+    multisig::{MultisigConfig, PendingTransfer},
+    proto,
+    schema::SchemaImpl,
+    swap::HashLock,
+    CryptocurrencyService,
+};
 
 /// Error codes emitted by wallet transactions during execution.
 #[derive(Debug, ExecutionFail)]
@@ -47,6 +54,83 @@ pub enum Error {
     ///
     /// Can be emitted by 'Transfer`.
     SenderSameAsReceiver = 4,
+    /// Owners or quorum of the multisignature wallet are invalid.
+    ///
+    /// Can be emitted by `CreateMultisigWallet`.
+    InvalidMultisigConfig = 5,
+    /// Wallet is not a multisignature wallet.
+    ///
+    /// Can be emitted by `ProposeTransfer`.
+    NotMultisigWallet = 6,
+    /// Transaction author is not an owner of the multisignature wallet.
+    ///
+    /// Can be emitted by `ProposeTransfer` or `ApproveTransfer`.
+    NotWalletOwner = 7,
+    /// Pending transfer doesn't exist.
+    ///
+    /// Can be emitted by `ApproveTransfer`.
+    TransferNotFound = 8,
+    /// Transfer is already approved by the transaction author.
+    ///
+    /// Can be emitted by `ApproveTransfer`.
+    TransferAlreadyApproved = 9,
+    /// Wallet is frozen by the administrator.
+    ///
+    /// Can be emitted by `Transfer`, `ProposeTransfer` or `ApproveTransfer`.
+    WalletFrozen = 10,
+    /// Transaction author is not the administrator of the service.
+    ///
+    /// Can be emitted by `FreezeWallet` or `UnfreezeWallet`.
+    NotAdmin = 11,
+    /// Wallet doesn't exist.
+    ///
+    /// Can be emitted by `FreezeWallet` or `UnfreezeWallet`.
+    WalletNotFound = 12,
+    /// Amount exceeds the allowance given to the transaction author.
+    ///
+    /// Can be emitted by `TransferFrom`.
+    InsufficientAllowance = 13,
+    /// Locked funds don't exist.
+    ///
+    /// Can be emitted by `ClaimFunds`, `RefundFunds` or `ClaimSwap`.
+    LockNotFound = 14,
+    /// Secret doesn't match the hash lock.
+    ///
+    /// Can be emitted by `ClaimFunds` or `ClaimSwap`.
+    InvalidSecret = 15,
+    /// Timeout of the locked funds has expired.
+    ///
+    /// Can be emitted by `LockFunds`, `ClaimFunds`, `ClaimSwap` or `CreateEscrow`.
+    LockExpired = 16,
+    /// Timeout of the locked funds hasn't expired yet.
+    ///
+    /// Can be emitted by `RefundFunds`.
+    LockNotExpired = 17,
+    /// Escrow doesn't exist.
+    ///
+    /// Can be emitted by `ReleaseEscrow`.
+    EscrowNotFound = 18,
+    /// Transaction author is not the arbiter of the escrow.
+    ///
+    /// Can be emitted by `ReleaseEscrow`.
+    NotArbiter = 19,
+    /// Batch doesn't contain any transfers.
+    ///
+    /// Can be emitted by `TransferBatch`.
+    EmptyBatch = 20,
+    /// Total supply of the currency would exceed the maximum supply.
+    ///
+    /// Can be emitted by `Issue`, `CreateWallet` or `CreateMultisigWallet`.
+    SupplyExceeded = 21,
+    /// Transaction author is not the issuer specified in the service configuration.
+    ///
+    /// Can be emitted by `Issue`.
+    NotIssuer = 22,
+    /// Service configuration is invalid: either the initial balance is zero,
+    /// or the maximum supply is less than the current supply.
+    ///
+    /// Can be emitted during the service initialization or the configuration change.
+    InvalidConfig = 23,
 }
 
 /// Transfer `amount` of the currency from one wallet to another.
@@ -64,7 +148,36 @@ pub struct Transfer {
     pub seed: u64,
 }
 
-/// Issue `amount` of the currency to the `wallet`.
+/// Single transfer within the `TransferBatch` transaction.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::TransferBatchItem", serde_pb_convert)]
+pub struct TransferBatchItem {
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of currency to transfer.
+    pub amount: u64,
+}
+
+/// Transfer the currency from the author's wallet to several wallets. The transfers
+/// are applied atomically: if any of them is invalid, none of them takes effect, and
+/// the execution error describes the position of the invalid transfer in the batch.
+///
+/// The transfer fee is charged for each transfer in the batch.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::TransferBatch", serde_pb_convert)]
+pub struct TransferBatch {
+    /// Transfers to perform.
+    pub transfers: Vec<TransferBatchItem>,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Issue `amount` of the currency to the `wallet`. If the issuer is specified
+/// in the service configuration, only the issuer can issue currency.
 #[derive(Clone, Debug)]
 #[derive(Serialize, Deserialize)]
 #[derive(ProtobufConvert, BinaryValue, ObjectHash)]
@@ -95,6 +208,199 @@ impl CreateWallet {
     }
 }
 
+/// Create a wallet owned by several keys. The key of the new wallet is derived from
+/// the hash of this transaction; transfers from the wallet require approvals of `quorum`
+/// owners.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::CreateMultisigWallet", serde_pb_convert)]
+pub struct CreateMultisigWallet {
+    /// Name of the new wallet.
+    pub name: String,
+    /// Keys of the wallet owners.
+    pub owners: Vec<PublicKey>,
+    /// Number of owners required to approve a transfer from the wallet.
+    pub quorum: u32,
+}
+
+/// Propose a transfer of `amount` of the currency from a multisignature wallet.
+/// The proposal counts as an approval of the transaction author.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ProposeTransfer", serde_pb_convert)]
+pub struct ProposeTransfer {
+    /// `PublicKey` of the multisignature wallet.
+    pub from: PublicKey,
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of currency to transfer.
+    pub amount: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Approve a pending transfer from a multisignature wallet. The transfer is executed
+/// as soon as it is approved by the required number of wallet owners. If the balance
+/// of the wallet is insufficient at this moment, the approval fails and the transfer
+/// remains pending.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ApproveTransfer", serde_pb_convert)]
+pub struct ApproveTransfer {
+    /// Hash of the `ProposeTransfer` transaction.
+    pub transfer_id: Hash,
+}
+
+impl CreateMultisigWallet {
+    /// Returns the key of the wallet created by the transaction with the given hash.
+    pub fn wallet_key(tx_hash: &Hash) -> PublicKey {
+        PublicKey::from_slice(tx_hash.as_ref()).expect("Hash and public key have the same size")
+    }
+}
+
+/// Allow the `spender` to transfer up to `amount` of the currency from the wallet
+/// of the transaction author. The new allowance replaces the previous one;
+/// zero amount revokes the allowance.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Approve", serde_pb_convert)]
+pub struct Approve {
+    /// `PublicKey` of the spender's wallet.
+    pub spender: PublicKey,
+    /// Maximum amount of currency the spender is allowed to transfer.
+    pub amount: u64,
+}
+
+/// Transfer `amount` of the currency from the `owner`'s wallet to another wallet
+/// using the allowance given to the transaction author. The transfer fee is paid
+/// by the owner.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::TransferFrom", serde_pb_convert)]
+pub struct TransferFrom {
+    /// `PublicKey` of the wallet to transfer currency from.
+    pub owner: PublicKey,
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of currency to transfer.
+    pub amount: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Lock `amount` of the currency of the transaction author for the receiver.
+/// The receiver can claim the funds by revealing the secret matching `hash_lock`
+/// before `timeout_height`; afterwards, the funds can be refunded to the author.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::LockFunds", serde_pb_convert)]
+pub struct LockFunds {
+    /// `PublicKey` of receiver's wallet.
+    pub to: PublicKey,
+    /// Amount of currency to lock.
+    pub amount: u64,
+    /// Hash of the secret required to claim the funds.
+    pub hash_lock: Hash,
+    /// Height starting from which the funds can no longer be claimed and can be refunded.
+    pub timeout_height: u64,
+}
+
+/// Claim locked funds by revealing the secret. The funds are always credited to
+/// the receiver specified in the lock, so the claim can be submitted by anyone
+/// knowing the secret.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ClaimFunds", serde_pb_convert)]
+pub struct ClaimFunds {
+    /// Hash of the `LockFunds` transaction.
+    pub lock_id: Hash,
+    /// Secret matching the hash lock.
+    pub secret: Vec<u8>,
+}
+
+/// Return expired locked funds to the sender. The funds are always credited to
+/// the sender specified in the lock, so the refund can be submitted by anyone.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::RefundFunds", serde_pb_convert)]
+pub struct RefundFunds {
+    /// Hash of the `LockFunds` transaction.
+    pub lock_id: Hash,
+}
+
+/// Claim locked funds in this service instance and in the counterpart cryptocurrency
+/// instance with the same secret, completing an atomic swap in a single transaction.
+/// The counterpart funds are claimed via a call to the counterpart instance; if either
+/// of the claims fails, neither of them takes effect.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ClaimSwap", serde_pb_convert)]
+pub struct ClaimSwap {
+    /// Hash of the `LockFunds` transaction in this instance.
+    pub lock_id: Hash,
+    /// Secret matching the hash locks.
+    pub secret: Vec<u8>,
+    /// Identifier of the counterpart cryptocurrency instance.
+    pub counterpart_instance: InstanceId,
+    /// Hash of the `LockFunds` transaction in the counterpart instance.
+    pub counterpart_lock_id: Hash,
+}
+
+/// Lock `amount` of the currency of the transaction author for the payee. The funds
+/// are credited to the payee once the arbiter releases them; if this does not happen
+/// before `timeout_height`, the funds are refunded to the author automatically.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::CreateEscrow", serde_pb_convert)]
+pub struct CreateEscrow {
+    /// `PublicKey` of payee's wallet.
+    pub payee: PublicKey,
+    /// `PublicKey` of the arbiter allowed to release the funds.
+    pub arbiter: PublicKey,
+    /// Amount of currency to lock.
+    pub amount: u64,
+    /// Height at which the funds are refunded to the author unless released before.
+    pub timeout_height: u64,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+/// Release escrowed funds to the payee. Can only be performed by the arbiter
+/// specified in the escrow.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ReleaseEscrow", serde_pb_convert)]
+pub struct ReleaseEscrow {
+    /// Hash of the `CreateEscrow` transaction.
+    pub escrow_id: Hash,
+}
+
+/// Freeze the wallet, forbidding transfers from and to it. Can only be performed
+/// by the administrator specified in the service configuration.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::FreezeWallet", serde_pb_convert)]
+pub struct FreezeWallet {
+    /// `PublicKey` of the wallet.
+    pub pub_key: PublicKey,
+}
+
+/// Unfreeze the previously frozen wallet. Can only be performed by the administrator
+/// specified in the service configuration.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::UnfreezeWallet", serde_pb_convert)]
+pub struct UnfreezeWallet {
+    /// `PublicKey` of the wallet.
+    pub pub_key: PublicKey,
+}
+
 /// Cryptocurrency service transactions.
 #[exonum_interface]
 pub trait CryptocurrencyInterface<Ctx> {
@@ -110,6 +416,48 @@ pub trait CryptocurrencyInterface<Ctx> {
     /// Creates wallet with the given `name`.
     #[interface_method(id = 2)]
     fn create_wallet(&self, ctx: Ctx, arg: CreateWallet) -> Self::Output;
+    /// Creates wallet owned by several keys.
+    #[interface_method(id = 3)]
+    fn create_multisig_wallet(&self, ctx: Ctx, arg: CreateMultisigWallet) -> Self::Output;
+    /// Proposes a transfer from a multisignature wallet.
+    #[interface_method(id = 4)]
+    fn propose_transfer(&self, ctx: Ctx, arg: ProposeTransfer) -> Self::Output;
+    /// Approves a pending transfer from a multisignature wallet.
+    #[interface_method(id = 5)]
+    fn approve_transfer(&self, ctx: Ctx, arg: ApproveTransfer) -> Self::Output;
+    /// Freezes the wallet.
+    #[interface_method(id = 6)]
+    fn freeze_wallet(&self, ctx: Ctx, arg: FreezeWallet) -> Self::Output;
+    /// Unfreezes the wallet.
+    #[interface_method(id = 7)]
+    fn unfreeze_wallet(&self, ctx: Ctx, arg: UnfreezeWallet) -> Self::Output;
+    /// Allows another wallet to spend currency from the author's wallet.
+    #[interface_method(id = 8)]
+    fn approve(&self, ctx: Ctx, arg: Approve) -> Self::Output;
+    /// Transfers currency from another wallet using the allowance.
+    #[interface_method(id = 9)]
+    fn transfer_from(&self, ctx: Ctx, arg: TransferFrom) -> Self::Output;
+    /// Locks currency until the secret is revealed or the timeout expires.
+    #[interface_method(id = 10)]
+    fn lock_funds(&self, ctx: Ctx, arg: LockFunds) -> Self::Output;
+    /// Claims locked currency by revealing the secret.
+    #[interface_method(id = 11)]
+    fn claim_funds(&self, ctx: Ctx, arg: ClaimFunds) -> Self::Output;
+    /// Returns expired locked currency to the sender.
+    #[interface_method(id = 12)]
+    fn refund_funds(&self, ctx: Ctx, arg: RefundFunds) -> Self::Output;
+    /// Claims locked currency both in this and the counterpart instance.
+    #[interface_method(id = 13)]
+    fn claim_swap(&self, ctx: Ctx, arg: ClaimSwap) -> Self::Output;
+    /// Locks currency until the arbiter releases it or the timeout expires.
+    #[interface_method(id = 14)]
+    fn create_escrow(&self, ctx: Ctx, arg: CreateEscrow) -> Self::Output;
+    /// Releases escrowed currency to the payee.
+    #[interface_method(id = 15)]
+    fn release_escrow(&self, ctx: Ctx, arg: ReleaseEscrow) -> Self::Output;
+    /// Transfers currency to several wallets atomically.
+    #[interface_method(id = 16)]
+    fn transfer_batch(&self, ctx: Ctx, arg: TransferBatch) -> Self::Output;
 }
 
 impl CryptocurrencyInterface<CallContext<'_>> for CryptocurrencyService {
@@ -139,10 +487,14 @@ impl CryptocurrencyInterface<CallContext<'_>> for CryptocurrencyService {
             .wallets
             .get(&to)
             .ok_or(Error::ReceiverNotFound)?;
-        if sender.balance < amount {
+        if sender.frozen || receiver.frozen {
+            return Err(Error::WalletFrozen.into());
+        }
+        let fee = schema.transfer_fee();
+        if !sender.has_funds(amount, fee) {
             Err(Error::InsufficientCurrencyAmount.into())
         } else {
-            schema.decrease_wallet_balance(sender, amount, tx_hash);
+            schema.decrease_wallet_balance(sender, amount, fee, tx_hash);
             schema.increase_wallet_balance(receiver, amount, tx_hash);
             Ok(())
         }
@@ -155,8 +507,14 @@ impl CryptocurrencyInterface<CallContext<'_>> for CryptocurrencyService {
             .ok_or(CommonError::UnauthorizedCaller)?;
 
         let mut schema = SchemaImpl::new(context.service_data());
+        if let Some(issuer_key) = schema.config().issuer_key {
+            if from != issuer_key {
+                return Err(Error::NotIssuer.into());
+            }
+        }
         if let Some(wallet) = schema.public.wallets.get(&from) {
             let amount = arg.amount;
+            schema.increase_supply(amount)?;
             schema.increase_wallet_balance(wallet, amount, tx_hash);
             Ok(())
         } else {
@@ -173,10 +531,403 @@ impl CryptocurrencyInterface<CallContext<'_>> for CryptocurrencyService {
         let mut schema = SchemaImpl::new(context.service_data());
         if schema.public.wallets.get(&from).is_none() {
             let name = &arg.name;
-            schema.create_wallet(&from, name, tx_hash);
-            Ok(())
+            schema.create_wallet(&from, name, tx_hash)
         } else {
             Err(Error::WalletAlreadyExists.into())
         }
     }
+
+    fn create_multisig_wallet(
+        &self,
+        context: CallContext<'_>,
+        arg: CreateMultisigWallet,
+    ) -> Self::Output {
+        let (tx_hash, _) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut owners = arg.owners.clone();
+        owners.sort();
+        owners.dedup();
+        if owners.len() != arg.owners.len() || arg.quorum == 0 || arg.quorum as usize > owners.len()
+        {
+            return Err(Error::InvalidMultisigConfig.into());
+        }
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let key = CreateMultisigWallet::wallet_key(&tx_hash);
+        if schema.public.wallets.contains(&key) {
+            return Err(Error::WalletAlreadyExists.into());
+        }
+        schema.create_wallet(&key, &arg.name, tx_hash)?;
+        let config = MultisigConfig {
+            owners: arg.owners,
+            quorum: arg.quorum,
+        };
+        schema.public.multisig_wallets.put(&key, config);
+        Ok(())
+    }
+
+    fn propose_transfer(&self, context: CallContext<'_>, arg: ProposeTransfer) -> Self::Output {
+        let (tx_hash, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        if arg.from == arg.to {
+            return Err(Error::SenderSameAsReceiver.into());
+        }
+        if !schema.public.wallets.contains(&arg.from) {
+            return Err(Error::SenderNotFound.into());
+        }
+        if !schema.public.wallets.contains(&arg.to) {
+            return Err(Error::ReceiverNotFound.into());
+        }
+        let config = schema
+            .public
+            .multisig_wallets
+            .get(&arg.from)
+            .ok_or(Error::NotMultisigWallet)?;
+        if !config.is_owner(&author) {
+            return Err(Error::NotWalletOwner.into());
+        }
+
+        let transfer = PendingTransfer {
+            from: arg.from,
+            to: arg.to,
+            amount: arg.amount,
+            approvals: vec![author],
+        };
+        schema.approve_transfer(tx_hash, transfer, &config)
+    }
+
+    fn approve_transfer(&self, context: CallContext<'_>, arg: ApproveTransfer) -> Self::Output {
+        let (_, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let mut transfer = schema
+            .public
+            .pending_transfers
+            .get(&arg.transfer_id)
+            .ok_or(Error::TransferNotFound)?;
+        let config = schema
+            .public
+            .multisig_wallets
+            .get(&transfer.from)
+            .ok_or(Error::NotMultisigWallet)?;
+        if !config.is_owner(&author) {
+            return Err(Error::NotWalletOwner.into());
+        }
+        if transfer.approvals.contains(&author) {
+            return Err(Error::TransferAlreadyApproved.into());
+        }
+
+        transfer.approvals.push(author);
+        schema.approve_transfer(arg.transfer_id, transfer, &config)
+    }
+
+    fn freeze_wallet(&self, context: CallContext<'_>, arg: FreezeWallet) -> Self::Output {
+        set_wallet_frozen(context, arg.pub_key, true)
+    }
+
+    fn unfreeze_wallet(&self, context: CallContext<'_>, arg: UnfreezeWallet) -> Self::Output {
+        set_wallet_frozen(context, arg.pub_key, false)
+    }
+
+    fn approve(&self, context: CallContext<'_>, arg: Approve) -> Self::Output {
+        let (_, owner) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        if !schema.public.wallets.contains(&owner) {
+            return Err(Error::SenderNotFound.into());
+        }
+        if !schema.public.wallets.contains(&arg.spender) {
+            return Err(Error::ReceiverNotFound.into());
+        }
+        schema.set_allowance(&owner, &arg.spender, arg.amount);
+        Ok(())
+    }
+
+    fn transfer_from(&self, context: CallContext<'_>, arg: TransferFrom) -> Self::Output {
+        let (tx_hash, spender) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        if arg.owner == arg.to {
+            return Err(Error::SenderSameAsReceiver.into());
+        }
+        let allowance = schema.allowance(&arg.owner, &spender);
+        if allowance < arg.amount {
+            return Err(Error::InsufficientAllowance.into());
+        }
+
+        let sender = schema
+            .public
+            .wallets
+            .get(&arg.owner)
+            .ok_or(Error::SenderNotFound)?;
+        let receiver = schema
+            .public
+            .wallets
+            .get(&arg.to)
+            .ok_or(Error::ReceiverNotFound)?;
+        if sender.frozen || receiver.frozen {
+            return Err(Error::WalletFrozen.into());
+        }
+        let fee = schema.transfer_fee();
+        if !sender.has_funds(arg.amount, fee) {
+            return Err(Error::InsufficientCurrencyAmount.into());
+        }
+
+        schema.set_allowance(&arg.owner, &spender, allowance - arg.amount);
+        schema.decrease_wallet_balance(sender, arg.amount, fee, tx_hash);
+        schema.increase_wallet_balance(receiver, arg.amount, tx_hash);
+        Ok(())
+    }
+
+    fn lock_funds(&self, context: CallContext<'_>, arg: LockFunds) -> Self::Output {
+        let (tx_hash, from) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let height = context.data().for_core().next_height().0;
+        let mut schema = SchemaImpl::new(context.service_data());
+        if from == arg.to {
+            return Err(Error::SenderSameAsReceiver.into());
+        }
+        if arg.timeout_height <= height {
+            return Err(Error::LockExpired.into());
+        }
+
+        let sender = schema
+            .public
+            .wallets
+            .get(&from)
+            .ok_or(Error::SenderNotFound)?;
+        let receiver = schema
+            .public
+            .wallets
+            .get(&arg.to)
+            .ok_or(Error::ReceiverNotFound)?;
+        if sender.frozen || receiver.frozen {
+            return Err(Error::WalletFrozen.into());
+        }
+        if !sender.has_funds(arg.amount, 0) {
+            return Err(Error::InsufficientCurrencyAmount.into());
+        }
+
+        schema.decrease_wallet_balance(sender, arg.amount, 0, tx_hash);
+        let lock = HashLock {
+            from,
+            to: arg.to,
+            amount: arg.amount,
+            hash_lock: arg.hash_lock,
+            timeout_height: arg.timeout_height,
+        };
+        schema.public.hash_locks.put(&tx_hash, lock);
+        Ok(())
+    }
+
+    fn claim_funds(&self, context: CallContext<'_>, arg: ClaimFunds) -> Self::Output {
+        claim_locked_funds(&context, arg)
+    }
+
+    fn refund_funds(&self, context: CallContext<'_>, arg: RefundFunds) -> Self::Output {
+        let height = context.data().for_core().next_height().0;
+        let mut schema = SchemaImpl::new(context.service_data());
+        let lock = schema
+            .public
+            .hash_locks
+            .get(&arg.lock_id)
+            .ok_or(Error::LockNotFound)?;
+        if height < lock.timeout_height {
+            return Err(Error::LockNotExpired.into());
+        }
+        schema.release_lock(arg.lock_id, &lock.from, lock.amount)
+    }
+
+    fn claim_swap(&self, mut context: CallContext<'_>, arg: ClaimSwap) -> Self::Output {
+        let claim = ClaimFunds {
+            lock_id: arg.lock_id,
+            secret: arg.secret.clone(),
+        };
+        claim_locked_funds(&context, claim)?;
+
+        let counterpart_claim = ClaimFunds {
+            lock_id: arg.counterpart_lock_id,
+            secret: arg.secret,
+        };
+        context.claim_funds(arg.counterpart_instance, counterpart_claim)
+    }
+
+    fn create_escrow(&self, context: CallContext<'_>, arg: CreateEscrow) -> Self::Output {
+        let (tx_hash, payer) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let height = context.data().for_core().next_height().0;
+        let mut schema = SchemaImpl::new(context.service_data());
+        if payer == arg.payee {
+            return Err(Error::SenderSameAsReceiver.into());
+        }
+        if arg.timeout_height <= height {
+            return Err(Error::LockExpired.into());
+        }
+
+        let sender = schema
+            .public
+            .wallets
+            .get(&payer)
+            .ok_or(Error::SenderNotFound)?;
+        let receiver = schema
+            .public
+            .wallets
+            .get(&arg.payee)
+            .ok_or(Error::ReceiverNotFound)?;
+        if sender.frozen || receiver.frozen {
+            return Err(Error::WalletFrozen.into());
+        }
+        if !sender.has_funds(arg.amount, 0) {
+            return Err(Error::InsufficientCurrencyAmount.into());
+        }
+
+        schema.decrease_wallet_balance(sender, arg.amount, 0, tx_hash);
+        let escrow = Escrow {
+            payer,
+            payee: arg.payee,
+            arbiter: arg.arbiter,
+            amount: arg.amount,
+            timeout_height: arg.timeout_height,
+        };
+        schema.create_escrow(tx_hash, escrow);
+        Ok(())
+    }
+
+    fn release_escrow(&self, context: CallContext<'_>, arg: ReleaseEscrow) -> Self::Output {
+        let (_, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let escrow = schema
+            .public
+            .escrows
+            .get(&arg.escrow_id)
+            .ok_or(Error::EscrowNotFound)?;
+        if author != escrow.arbiter {
+            return Err(Error::NotArbiter.into());
+        }
+        schema.release_escrow(arg.escrow_id, &escrow, &escrow.payee)
+    }
+
+    fn transfer_batch(&self, context: CallContext<'_>, arg: TransferBatch) -> Self::Output {
+        let (tx_hash, from) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        if arg.transfers.is_empty() {
+            return Err(Error::EmptyBatch.into());
+        }
+
+        let sender = schema
+            .public
+            .wallets
+            .get(&from)
+            .ok_or(Error::SenderNotFound)?;
+        if sender.frozen {
+            return Err(Error::WalletFrozen.into());
+        }
+
+        // Validate all transfers before applying any of them.
+        let mut total_amount = 0_u64;
+        for (i, transfer) in arg.transfers.iter().enumerate() {
+            let item_error = |error: Error| error.with_description(format!("transfer #{}", i));
+            if transfer.to == from {
+                return Err(item_error(Error::SenderSameAsReceiver));
+            }
+            let receiver = schema
+                .public
+                .wallets
+                .get(&transfer.to)
+                .ok_or_else(|| item_error(Error::ReceiverNotFound))?;
+            if receiver.frozen {
+                return Err(item_error(Error::WalletFrozen));
+            }
+            total_amount = total_amount
+                .checked_add(transfer.amount)
+                .ok_or_else(|| item_error(Error::InsufficientCurrencyAmount))?;
+        }
+
+        let total_fee = schema
+            .transfer_fee()
+            .checked_mul(arg.transfers.len() as u64)
+            .ok_or(Error::InsufficientCurrencyAmount)?;
+        if !sender.has_funds(total_amount, total_fee) {
+            return Err(Error::InsufficientCurrencyAmount.into());
+        }
+
+        schema.decrease_wallet_balance(sender, total_amount, total_fee, tx_hash);
+        for transfer in arg.transfers {
+            // The same receiver may occur in the batch several times, so the wallet
+            // is retrieved anew for each transfer.
+            let receiver = schema.public.wallets.get(&transfer.to).unwrap();
+            schema.increase_wallet_balance(receiver, transfer.amount, tx_hash);
+        }
+        Ok(())
+    }
+}
+
+fn claim_locked_funds(context: &CallContext<'_>, arg: ClaimFunds) -> Result<(), ExecutionError> {
+    let height = context.data().for_core().next_height().0;
+    let mut schema = SchemaImpl::new(context.service_data());
+    let lock = schema
+        .public
+        .hash_locks
+        .get(&arg.lock_id)
+        .ok_or(Error::LockNotFound)?;
+    if crypto::hash(&arg.secret) != lock.hash_lock {
+        return Err(Error::InvalidSecret.into());
+    }
+    if height >= lock.timeout_height {
+        return Err(Error::LockExpired.into());
+    }
+    schema.release_lock(arg.lock_id, &lock.to, lock.amount)
+}
+
+fn set_wallet_frozen(
+    context: CallContext<'_>,
+    key: PublicKey,
+    frozen: bool,
+) -> Result<(), ExecutionError> {
+    let (_, author) = context
+        .caller()
+        .as_transaction()
+        .ok_or(CommonError::UnauthorizedCaller)?;
+
+    let mut schema = SchemaImpl::new(context.service_data());
+    if schema.config().admin_key != Some(author) {
+        return Err(Error::NotAdmin.into());
+    }
+    let wallet = schema
+        .public
+        .wallets
+        .get(&key)
+        .ok_or(Error::WalletNotFound)?;
+    schema.public.wallets.put(&key, wallet.set_frozen(frozen));
+    Ok(())
 }
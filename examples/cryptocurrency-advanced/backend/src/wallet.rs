@@ -34,6 +34,8 @@ pub struct Wallet {
     pub history_len: u64,
     /// `Hash` of the transactions history.
     pub history_hash: Hash,
+    /// Whether transfers from and to the wallet are forbidden by the administrator.
+    pub frozen: bool,
 }
 
 impl Wallet {
@@ -51,16 +53,29 @@ impl Wallet {
             balance,
             history_len,
             history_hash,
+            frozen: false,
         }
     }
+
+    /// Checks whether the wallet balance is enough to pay `amount` together with `fee`.
+    pub fn has_funds(&self, amount: u64, fee: u64) -> bool {
+        amount
+            .checked_add(fee)
+            .map_or(false, |total| self.balance >= total)
+    }
+
     /// Returns a copy of this wallet with updated balance.
     pub fn set_balance(self, balance: u64, history_hash: &Hash) -> Self {
-        Self::new(
-            &self.pub_key,
-            &self.name,
+        Self {
             balance,
-            self.history_len + 1,
-            history_hash,
-        )
+            history_len: self.history_len + 1,
+            history_hash: *history_hash,
+            ..self
+        }
+    }
+
+    /// Returns a copy of this wallet with updated frozen flag.
+    pub fn set_frozen(self, frozen: bool) -> Self {
+        Self { frozen, ..self }
     }
 }
@@ -0,0 +1,223 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for delegated spending via allowances.
+
+use exonum::{
+    crypto::{self, PublicKey, SecretKey},
+    merkledb::{access::AccessExt, ObjectHash, ProofMapIndex, SystemSchema},
+    messages::{AnyTx, Verified},
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    schema::AllowanceKey,
+    transactions::{Approve, CreateWallet, Error, TransferFrom},
+    CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with wallets of Alice, Bob and Carol.
+fn create_testkit() -> (TestKit, KeyPair, KeyPair, KeyPair) {
+    let artifact = CryptocurrencyService.artifact_id();
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(SERVICE_ID, SERVICE_NAME))
+        .create();
+
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    let carol = crypto::gen_keypair();
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+        carol.create_wallet(SERVICE_ID, CreateWallet::new("Carol")),
+    ]);
+    (testkit, alice, bob, carol)
+}
+
+fn balance(testkit: &TestKit, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema
+        .wallets
+        .get(key)
+        .expect("No wallet persisted")
+        .balance
+}
+
+fn transfer_from(
+    spender: &KeyPair,
+    owner: PublicKey,
+    to: PublicKey,
+    amount: u64,
+) -> Verified<AnyTx> {
+    spender.transfer_from(
+        SERVICE_ID,
+        TransferFrom {
+            owner,
+            to,
+            amount,
+            seed: amount,
+        },
+    )
+}
+
+#[test]
+fn test_transfer_from() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+
+    let approve = alice.approve(
+        SERVICE_ID,
+        Approve {
+            spender: bob.0,
+            amount: 30,
+        },
+    );
+    testkit.create_block_with_transaction(approve)[0]
+        .status()
+        .unwrap();
+
+    // Bob spends a part of the allowance, sending currency to Carol.
+    let block = testkit.create_block_with_transaction(transfer_from(&bob, alice.0, carol.0, 20));
+    block[0].status().unwrap();
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE - 20);
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE);
+    assert_eq!(balance(&testkit, &carol.0), INITIAL_BALANCE + 20);
+
+    // The remaining allowance is insufficient for the next transfer.
+    let block = testkit.create_block_with_transaction(transfer_from(&bob, alice.0, carol.0, 15));
+    let expected_err = ErrorMatch::from_fail(&Error::InsufficientAllowance).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    let block = testkit.create_block_with_transaction(transfer_from(&bob, alice.0, bob.0, 10));
+    block[0].status().unwrap();
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE - 30);
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE + 10);
+}
+
+#[test]
+fn test_transfer_from_without_allowance() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+
+    // Allowance given to Bob cannot be used by Carol.
+    let approve = alice.approve(
+        SERVICE_ID,
+        Approve {
+            spender: bob.0,
+            amount: 30,
+        },
+    );
+    testkit.create_block_with_transaction(approve);
+
+    let block = testkit.create_block_with_transaction(transfer_from(&carol, alice.0, carol.0, 10));
+    let expected_err = ErrorMatch::from_fail(&Error::InsufficientAllowance).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+}
+
+#[test]
+fn test_revoke_allowance() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+
+    let approves = vec![
+        alice.approve(
+            SERVICE_ID,
+            Approve {
+                spender: bob.0,
+                amount: 30,
+            },
+        ),
+        alice.approve(
+            SERVICE_ID,
+            Approve {
+                spender: bob.0,
+                amount: 0,
+            },
+        ),
+    ];
+    testkit.create_block_with_transactions(approves);
+
+    let block = testkit.create_block_with_transaction(transfer_from(&bob, alice.0, carol.0, 10));
+    let expected_err = ErrorMatch::from_fail(&Error::InsufficientAllowance).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_allowances_are_aggregated() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+
+    let approves = vec![
+        alice.approve(
+            SERVICE_ID,
+            Approve {
+                spender: bob.0,
+                amount: 30,
+            },
+        ),
+        carol.approve(
+            SERVICE_ID,
+            Approve {
+                spender: bob.0,
+                amount: 20,
+            },
+        ),
+    ];
+    testkit.create_block_with_transactions(approves);
+
+    let snapshot = testkit.snapshot();
+    let index_name = format!("{}.allowances", SERVICE_NAME);
+    let allowances: ProofMapIndex<_, AllowanceKey, u64> =
+        snapshot.get_proof_map(index_name.as_str());
+    assert_eq!(allowances.get(&AllowanceKey::new(alice.0, bob.0)), Some(30));
+    assert_eq!(allowances.get(&AllowanceKey::new(carol.0, bob.0)), Some(20));
+    assert_eq!(allowances.get(&AllowanceKey::new(bob.0, alice.0)), None);
+
+    // The allowances are covered by the blockchain state hash.
+    let aggregator = SystemSchema::new(&snapshot).state_aggregator();
+    assert_eq!(aggregator.get(&index_name), Some(allowances.object_hash()));
+}
+
+#[test]
+fn test_transfer_from_exceeding_balance() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+
+    let approve = alice.approve(
+        SERVICE_ID,
+        Approve {
+            spender: bob.0,
+            amount: INITIAL_BALANCE * 2,
+        },
+    );
+    testkit.create_block_with_transaction(approve);
+
+    let block = testkit.create_block_with_transaction(transfer_from(
+        &bob,
+        alice.0,
+        carol.0,
+        INITIAL_BALANCE + 1,
+    ));
+    let expected_err =
+        ErrorMatch::from_fail(&Error::InsufficientCurrencyAmount).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+}
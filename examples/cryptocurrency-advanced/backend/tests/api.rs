@@ -17,6 +17,7 @@
 //! Note how API tests predominantly use `TestKitApi` to send transactions and make assertions
 //! about the storage state.
 
+use assert_matches::assert_matches;
 use exonum::{
     crypto::{self, Hash, PublicKey, SecretKey},
     merkledb::ObjectHash,
@@ -24,7 +25,7 @@ use exonum::{
     runtime::SnapshotExt,
 };
 use exonum_explorer_service::ExplorerFactory;
-use exonum_rust_runtime::ServiceFactory;
+use exonum_rust_runtime::{api::Error as ApiError, ServiceFactory};
 use exonum_testkit::{
     explorer::api::{TransactionQuery, TransactionResponse},
     ApiKind, TestKit, TestKitApi, TestKitBuilder,
@@ -33,7 +34,7 @@ use serde_json::json;
 
 // Import data types used in tests from the crate where the service is defined.
 use exonum_cryptocurrency_advanced::{
-    api::{WalletInfo, WalletQuery},
+    api::{WalletHistoryPage, WalletHistoryQuery, WalletInfo, WalletQuery, MAX_HISTORY_PAGE_SIZE},
     schema::Schema,
     transactions::{CreateWallet, Transfer},
     wallet::Wallet,
@@ -256,6 +257,104 @@ fn test_unknown_wallet_request() {
     api.assert_no_wallet(tx.author());
 }
 
+/// Check that the wallet history can be fetched in pages with proofs.
+#[test]
+fn test_wallet_history_pages() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, alice) = api.create_wallet(ALICE_NAME);
+    let (tx_bob, _) = api.create_wallet(BOB_NAME);
+    testkit.create_block();
+
+    let mut history = vec![tx_alice.object_hash()];
+    for seed in 0..4 {
+        let tx = alice.transfer(
+            SERVICE_ID,
+            Transfer {
+                to: tx_bob.author(),
+                amount: 1,
+                seed,
+            },
+        );
+        api.transfer(&tx);
+        testkit.create_block();
+        history.push(tx.object_hash());
+    }
+
+    let mut start = Some(0);
+    let mut fetched = vec![];
+    while let Some(page_start) = start {
+        let page = api.get_wallet_history(tx_alice.author(), page_start, Some(2));
+        let (wallet, hashes) = check_history_page(&page, tx_alice.author());
+        assert_eq!(wallet.history_len, history.len() as u64);
+        assert!(!hashes.is_empty() && hashes.len() <= 2);
+        fetched.extend(hashes);
+        start = page.next_start;
+    }
+    assert_eq!(fetched, history);
+
+    // Pages beyond the history are empty.
+    let page = api.get_wallet_history(tx_alice.author(), 10, None);
+    let (_, hashes) = check_history_page(&page, tx_alice.author());
+    assert!(hashes.is_empty());
+    assert_eq!(page.next_start, None);
+}
+
+/// Check that the wallet history request with invalid page size is rejected.
+#[test]
+fn test_wallet_history_invalid_page_size() {
+    let (_testkit, api) = create_testkit();
+    let pub_key = crypto::gen_keypair().0;
+    for &count in &[0, MAX_HISTORY_PAGE_SIZE + 1] {
+        let err = api
+            .inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&WalletHistoryQuery {
+                pub_key,
+                start: 0,
+                count: Some(count),
+            })
+            .get::<WalletHistoryPage>("v1/wallets/history")
+            .unwrap_err();
+        assert_matches!(err, ApiError::BadRequest(_));
+    }
+}
+
+/// Checks proofs in the history page and returns the proved wallet together with
+/// the hashes of the page transactions.
+fn check_history_page(page: &WalletHistoryPage, pub_key: PublicKey) -> (Wallet, Vec<Hash>) {
+    let state_hash = page.block_proof.block.state_hash;
+    let to_table = page
+        .wallet_proof
+        .to_table
+        .check_against_hash(state_hash)
+        .unwrap();
+    let table_hash = *to_table.entries().next().unwrap().1;
+    let to_wallet = page
+        .wallet_proof
+        .to_wallet
+        .check_against_hash(table_hash)
+        .unwrap();
+    let (_, wallet) = to_wallet
+        .all_entries()
+        .find(|(&key, _)| key == pub_key)
+        .unwrap();
+    let wallet = wallet.cloned().unwrap();
+
+    let history = page.history.as_ref().unwrap();
+    let proof = history
+        .proof
+        .check_against_hash(wallet.history_hash)
+        .unwrap();
+    let hashes: Vec<_> = proof.entries().iter().map(|(_, hash)| *hash).collect();
+    let tx_hashes: Vec<_> = history
+        .transactions
+        .iter()
+        .map(ObjectHash::object_hash)
+        .collect();
+    assert_eq!(hashes, tx_hashes);
+    (wallet, hashes)
+}
+
 /// Wrapper for the cryptocurrency service API allowing to easily use it
 /// (compared to `TestKitApi` calls).
 struct CryptocurrencyApi {
@@ -312,6 +411,23 @@ impl CryptocurrencyApi {
         wallet.cloned()
     }
 
+    fn get_wallet_history(
+        &self,
+        pub_key: PublicKey,
+        start: u64,
+        count: Option<u64>,
+    ) -> WalletHistoryPage {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&WalletHistoryQuery {
+                pub_key,
+                start,
+                count,
+            })
+            .get("v1/wallets/history")
+            .unwrap()
+    }
+
     /// Sends a transfer transaction over HTTP and checks the synchronous result.
     fn transfer(&self, tx: &Verified<AnyTx>) {
         let tx_info: TransactionResponse = self
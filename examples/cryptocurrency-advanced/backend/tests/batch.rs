@@ -0,0 +1,129 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for batch transfers.
+
+use exonum::{
+    crypto::{self, PublicKey, SecretKey},
+    messages::{AnyTx, Verified},
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    transactions::{CreateWallet, Error, TransferBatch, TransferBatchItem},
+    CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with the wallets of Alice, Bob and Carol.
+fn create_testkit() -> (TestKit, KeyPair, KeyPair, KeyPair) {
+    let artifact = CryptocurrencyService.artifact_id();
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(SERVICE_ID, SERVICE_NAME))
+        .create();
+
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    let carol = crypto::gen_keypair();
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+        carol.create_wallet(SERVICE_ID, CreateWallet::new("Carol")),
+    ]);
+    (testkit, alice, bob, carol)
+}
+
+fn transfer_batch(sender: &KeyPair, transfers: &[(PublicKey, u64)]) -> Verified<AnyTx> {
+    let transfers = transfers
+        .iter()
+        .map(|&(to, amount)| TransferBatchItem { to, amount })
+        .collect();
+    sender.transfer_batch(SERVICE_ID, TransferBatch { transfers, seed: 0 })
+}
+
+fn balance(testkit: &TestKit, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema
+        .wallets
+        .get(key)
+        .expect("No wallet persisted")
+        .balance
+}
+
+#[test]
+fn test_transfer_batch() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+    let tx = transfer_batch(&alice, &[(bob.0, 10), (carol.0, 20), (bob.0, 5)]);
+    testkit.create_block_with_transaction(tx)[0]
+        .status()
+        .unwrap();
+
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE - 35);
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE + 15);
+    assert_eq!(balance(&testkit, &carol.0), INITIAL_BALANCE + 20);
+}
+
+#[test]
+fn test_transfer_batch_is_atomic() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+    let unknown = crypto::gen_keypair().0;
+    let tx = transfer_batch(&alice, &[(bob.0, 10), (unknown, 20), (carol.0, 5)]);
+    let block = testkit.create_block_with_transaction(tx);
+
+    // The error refers to the invalid transfer.
+    let expected_err = ErrorMatch::from_fail(&Error::ReceiverNotFound)
+        .with_description_containing("transfer #1")
+        .for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    for key in &[alice.0, bob.0, carol.0] {
+        assert_eq!(balance(&testkit, key), INITIAL_BALANCE);
+    }
+}
+
+#[test]
+fn test_transfer_batch_errors() {
+    let (mut testkit, alice, bob, carol) = create_testkit();
+
+    let tx = transfer_batch(&alice, &[]);
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&Error::EmptyBatch).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    let tx = transfer_batch(&alice, &[(bob.0, 10), (alice.0, 10)]);
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&Error::SenderSameAsReceiver)
+        .with_description_containing("transfer #1")
+        .for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    // Each transfer is affordable, but the whole batch is not.
+    let tx = transfer_batch(&alice, &[(bob.0, 60), (carol.0, 60)]);
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err =
+        ErrorMatch::from_fail(&Error::InsufficientCurrencyAmount).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+}
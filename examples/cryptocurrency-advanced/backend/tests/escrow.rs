@@ -0,0 +1,168 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for escrows with automatic refunds.
+
+use exonum::{
+    crypto::{self, Hash, PublicKey, SecretKey},
+    helpers::Height,
+    merkledb::ObjectHash,
+    messages::{AnyTx, Verified},
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    transactions::{CreateEscrow, CreateWallet, Error, ReleaseEscrow},
+    CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+/// Height at which the escrow is refunded.
+const TIMEOUT_HEIGHT: u64 = 10;
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with the wallets of Alice and Bob.
+fn create_testkit() -> (TestKit, KeyPair, KeyPair) {
+    let artifact = CryptocurrencyService.artifact_id();
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(SERVICE_ID, SERVICE_NAME))
+        .create();
+
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+    ]);
+    (testkit, alice, bob)
+}
+
+fn create_escrow(payer: &KeyPair, payee: &PublicKey, arbiter: &PublicKey) -> Verified<AnyTx> {
+    payer.create_escrow(
+        SERVICE_ID,
+        CreateEscrow {
+            payee: *payee,
+            arbiter: *arbiter,
+            amount: 30,
+            timeout_height: TIMEOUT_HEIGHT,
+            seed: 0,
+        },
+    )
+}
+
+fn balance(testkit: &TestKit, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema
+        .wallets
+        .get(key)
+        .expect("No wallet persisted")
+        .balance
+}
+
+fn has_escrow(testkit: &TestKit, escrow_id: &Hash) -> bool {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.escrows.contains(escrow_id)
+}
+
+fn assert_error(testkit: &mut TestKit, tx: Verified<AnyTx>, error: Error) {
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&error).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_escrow_release() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let arbiter = crypto::gen_keypair();
+    let tx = create_escrow(&alice, &bob.0, &arbiter.0);
+    let escrow_id = tx.object_hash();
+    testkit.create_block_with_transaction(tx)[0]
+        .status()
+        .unwrap();
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE - 30);
+    assert!(has_escrow(&testkit, &escrow_id));
+
+    // Only the arbiter can release the funds.
+    let release = bob.release_escrow(SERVICE_ID, ReleaseEscrow { escrow_id });
+    assert_error(&mut testkit, release, Error::NotArbiter);
+
+    let release = arbiter.release_escrow(SERVICE_ID, ReleaseEscrow { escrow_id });
+    testkit.create_block_with_transaction(release)[0]
+        .status()
+        .unwrap();
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE + 30);
+    assert!(!has_escrow(&testkit, &escrow_id));
+
+    // The released escrow is not refunded after the timeout.
+    testkit.create_blocks_until(Height(TIMEOUT_HEIGHT));
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE - 30);
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE + 30);
+}
+
+#[test]
+fn test_escrow_refund_after_timeout() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let arbiter = crypto::gen_keypair();
+    let tx = create_escrow(&alice, &bob.0, &arbiter.0);
+    let escrow_id = tx.object_hash();
+    testkit.create_block_with_transaction(tx)[0]
+        .status()
+        .unwrap();
+
+    testkit.create_blocks_until(Height(TIMEOUT_HEIGHT - 1));
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE - 30);
+    assert!(has_escrow(&testkit, &escrow_id));
+
+    // The funds are refunded before the transactions of the block at the timeout height.
+    let release = arbiter.release_escrow(SERVICE_ID, ReleaseEscrow { escrow_id });
+    assert_error(&mut testkit, release, Error::EscrowNotFound);
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE);
+    assert!(!has_escrow(&testkit, &escrow_id));
+}
+
+#[test]
+fn test_escrow_creation_errors() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let arbiter = crypto::gen_keypair();
+
+    let tx = create_escrow(&alice, &alice.0, &arbiter.0);
+    assert_error(&mut testkit, tx, Error::SenderSameAsReceiver);
+    let tx = alice.create_escrow(
+        SERVICE_ID,
+        CreateEscrow {
+            payee: bob.0,
+            arbiter: arbiter.0,
+            amount: INITIAL_BALANCE + 1,
+            timeout_height: TIMEOUT_HEIGHT,
+            seed: 0,
+        },
+    );
+    assert_error(&mut testkit, tx, Error::InsufficientCurrencyAmount);
+
+    testkit.create_blocks_until(Height(TIMEOUT_HEIGHT - 1));
+    let tx = create_escrow(&alice, &bob.0, &arbiter.0);
+    assert_error(&mut testkit, tx, Error::LockExpired);
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+}
@@ -0,0 +1,169 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for transfer fees and their distribution among validators.
+
+use exonum::{
+    crypto::{self, PublicKey},
+    runtime::SnapshotExt,
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    transactions::{CreateWallet, Transfer},
+    Config, CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+const TRANSFER_FEE: u64 = 5;
+
+fn create_testkit(validators_count: u16) -> TestKit {
+    let artifact = CryptocurrencyService.artifact_id();
+    let instance = artifact
+        .clone()
+        .into_default_instance(SERVICE_ID, SERVICE_NAME)
+        .with_constructor(Config {
+            transfer_fee: TRANSFER_FEE,
+            ..Config::default()
+        });
+    TestKitBuilder::validator()
+        .with_validators(validators_count)
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact)
+        .with_instance(instance)
+        .create()
+}
+
+fn balance(testkit: &TestKit, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema
+        .wallets
+        .get(key)
+        .expect("No wallet persisted")
+        .balance
+}
+
+fn fee_pool(testkit: &TestKit) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.fee_pool.get().unwrap_or_default()
+}
+
+#[test]
+fn test_transfer_fee_distribution() {
+    let mut testkit = create_testkit(2);
+    let validator_keys: Vec<_> = testkit
+        .network()
+        .validators()
+        .iter()
+        .map(|validator| validator.service_keypair())
+        .collect();
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+
+    let mut txs: Vec<_> = validator_keys
+        .iter()
+        .map(|keys| keys.create_wallet(SERVICE_ID, CreateWallet::new("validator")))
+        .collect();
+    txs.push(alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)));
+    txs.push(bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)));
+    testkit.create_block_with_transactions(txs);
+
+    let transfer = alice.transfer(
+        SERVICE_ID,
+        Transfer {
+            to: bob.0,
+            amount: 10,
+            seed: 0,
+        },
+    );
+    let block = testkit.create_block_with_transaction(transfer);
+    block[0].status().unwrap();
+
+    assert_eq!(
+        balance(&testkit, &alice.0),
+        INITIAL_BALANCE - 10 - TRANSFER_FEE
+    );
+    assert_eq!(balance(&testkit, &bob.0), INITIAL_BALANCE + 10);
+    // The fee is split equally among validators; the remainder stays in the pool.
+    let share = TRANSFER_FEE / 2;
+    for (key, _) in &validator_keys {
+        assert_eq!(balance(&testkit, key), INITIAL_BALANCE + share);
+    }
+    assert_eq!(fee_pool(&testkit), TRANSFER_FEE % 2);
+}
+
+#[test]
+fn test_transfer_with_insufficient_funds_for_fee() {
+    let mut testkit = create_testkit(1);
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+    ]);
+
+    let transfer = alice.transfer(
+        SERVICE_ID,
+        Transfer {
+            to: bob.0,
+            amount: INITIAL_BALANCE,
+            seed: 0,
+        },
+    );
+    let block = testkit.create_block_with_transaction(transfer);
+    assert!(block[0].status().is_err());
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+    assert_eq!(fee_pool(&testkit), 0);
+}
+
+#[test]
+fn test_fees_accumulate_without_validator_wallets() {
+    let mut testkit = create_testkit(1);
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+    ]);
+
+    let transfers = (0..2).map(|seed| {
+        alice.transfer(
+            SERVICE_ID,
+            Transfer {
+                to: bob.0,
+                amount: 1,
+                seed,
+            },
+        )
+    });
+    testkit.create_block_with_transactions(transfers);
+    assert_eq!(fee_pool(&testkit), 2 * TRANSFER_FEE);
+
+    // Once the validator creates a wallet, it receives all accumulated fees.
+    let validator_keys = testkit.us().service_keypair();
+    testkit.create_block_with_transaction(
+        validator_keys.create_wallet(SERVICE_ID, CreateWallet::new("validator")),
+    );
+    assert_eq!(fee_pool(&testkit), 0);
+    assert_eq!(
+        balance(&testkit, &validator_keys.0),
+        INITIAL_BALANCE + 2 * TRANSFER_FEE
+    );
+}
@@ -0,0 +1,155 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for administrative wallet freezing.
+
+use exonum::{
+    crypto::{self, PublicKey, SecretKey},
+    messages::{AnyTx, Verified},
+    runtime::ErrorMatch,
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    api::{WalletInfo, WalletQuery},
+    transactions::{CreateWallet, Error, FreezeWallet, Transfer, UnfreezeWallet},
+    wallet::Wallet,
+    Config, CryptocurrencyInterface, CryptocurrencyService,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with the given admin and two wallets.
+fn create_testkit(admin_key: Option<PublicKey>) -> (TestKit, KeyPair, KeyPair) {
+    let artifact = CryptocurrencyService.artifact_id();
+    let instance = artifact
+        .clone()
+        .into_default_instance(SERVICE_ID, SERVICE_NAME)
+        .with_constructor(Config {
+            admin_key,
+            ..Config::default()
+        });
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact)
+        .with_instance(instance)
+        .create();
+
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+    ]);
+    (testkit, alice, bob)
+}
+
+/// Gets the wallet via HTTP API and checks its proof.
+fn get_wallet(testkit: &mut TestKit, pub_key: PublicKey) -> Wallet {
+    let wallet_info: WalletInfo = testkit
+        .api()
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&WalletQuery { pub_key })
+        .get("v1/wallets/info")
+        .unwrap();
+    let to_wallet = wallet_info.wallet_proof.to_wallet.check().unwrap();
+    let (_, wallet) = to_wallet
+        .all_entries()
+        .find(|(&key, _)| key == pub_key)
+        .unwrap();
+    wallet.cloned().unwrap()
+}
+
+fn transfer(from: &KeyPair, to: PublicKey, seed: u64) -> Verified<AnyTx> {
+    from.transfer(
+        SERVICE_ID,
+        Transfer {
+            to,
+            amount: 10,
+            seed,
+        },
+    )
+}
+
+#[test]
+fn test_freeze_wallet() {
+    let admin = crypto::gen_keypair();
+    let (mut testkit, alice, bob) = create_testkit(Some(admin.0));
+
+    let freeze = admin.freeze_wallet(SERVICE_ID, FreezeWallet { pub_key: alice.0 });
+    testkit.create_block_with_transaction(freeze)[0]
+        .status()
+        .unwrap();
+    // The frozen flag is a part of the proved wallet value.
+    assert!(get_wallet(&mut testkit, alice.0).frozen);
+    assert!(!get_wallet(&mut testkit, bob.0).frozen);
+
+    // Transfers both from and to the frozen wallet fail.
+    let block = testkit.create_block_with_transactions(vec![
+        transfer(&alice, bob.0, 0),
+        transfer(&bob, alice.0, 0),
+    ]);
+    let expected_err = ErrorMatch::from_fail(&Error::WalletFrozen).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    assert_eq!(*block[1].status().unwrap_err(), expected_err);
+
+    let unfreeze = admin.unfreeze_wallet(SERVICE_ID, UnfreezeWallet { pub_key: alice.0 });
+    testkit.create_block_with_transaction(unfreeze)[0]
+        .status()
+        .unwrap();
+    assert!(!get_wallet(&mut testkit, alice.0).frozen);
+
+    let block = testkit.create_block_with_transaction(transfer(&alice, bob.0, 1));
+    block[0].status().unwrap();
+    assert_eq!(get_wallet(&mut testkit, alice.0).balance, 90);
+}
+
+#[test]
+fn test_freeze_wallet_by_non_admin() {
+    let admin = crypto::gen_keypair();
+    let (mut testkit, alice, bob) = create_testkit(Some(admin.0));
+
+    let freeze = bob.freeze_wallet(SERVICE_ID, FreezeWallet { pub_key: alice.0 });
+    let block = testkit.create_block_with_transaction(freeze);
+    let expected_err = ErrorMatch::from_fail(&Error::NotAdmin).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    assert!(!get_wallet(&mut testkit, alice.0).frozen);
+}
+
+#[test]
+fn test_freeze_wallet_without_admin() {
+    let (mut testkit, alice, _) = create_testkit(None);
+
+    let freeze = alice.freeze_wallet(SERVICE_ID, FreezeWallet { pub_key: alice.0 });
+    let block = testkit.create_block_with_transaction(freeze);
+    let expected_err = ErrorMatch::from_fail(&Error::NotAdmin).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_freeze_unknown_wallet() {
+    let admin = crypto::gen_keypair();
+    let (mut testkit, _, _) = create_testkit(Some(admin.0));
+
+    let pub_key = crypto::gen_keypair().0;
+    let freeze = admin.freeze_wallet(SERVICE_ID, FreezeWallet { pub_key });
+    let block = testkit.create_block_with_transaction(freeze);
+    let expected_err = ErrorMatch::from_fail(&Error::WalletNotFound).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
@@ -0,0 +1,186 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the issuance policy specified in the service configuration.
+
+use exonum::{
+    crypto::{self, PublicKey, SecretKey},
+    messages::{AnyTx, Verified},
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_supervisor::{ConfigPropose, Supervisor, SupervisorTestKitExt};
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    transactions::{CreateWallet, Error, Issue},
+    Config, CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with the supervisor and the cryptocurrency service
+/// initialized with the given configuration.
+fn create_testkit(config: Config) -> TestKit {
+    let artifact = CryptocurrencyService.artifact_id();
+    let instance = artifact
+        .clone()
+        .into_default_instance(SERVICE_ID, SERVICE_NAME)
+        .with_constructor(config);
+    TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::simple())
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact)
+        .with_instance(instance)
+        .create()
+}
+
+fn issue(keypair: &KeyPair, amount: u64) -> Verified<AnyTx> {
+    keypair.issue(SERVICE_ID, Issue { amount, seed: 0 })
+}
+
+fn balance(testkit: &TestKit, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema
+        .wallets
+        .get(key)
+        .expect("No wallet persisted")
+        .balance
+}
+
+fn total_supply(testkit: &TestKit) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.total_supply.get().unwrap_or_default()
+}
+
+fn assert_error(testkit: &mut TestKit, tx: Verified<AnyTx>, error: Error) {
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&error).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_issuance_policy() {
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    let mut testkit = create_testkit(Config {
+        initial_balance: 50,
+        issuer_key: Some(alice.0),
+        max_supply: 120,
+        ..Config::default()
+    });
+
+    testkit.create_block_with_transactions(vec![
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+        bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+    ]);
+    assert_eq!(balance(&testkit, &alice.0), 50);
+    assert_eq!(balance(&testkit, &bob.0), 50);
+    assert_eq!(total_supply(&testkit), 100);
+
+    // Initial balances of new wallets are counted towards the total supply.
+    let tx = crypto::gen_keypair().create_wallet(SERVICE_ID, CreateWallet::new("Carol"));
+    assert_error(&mut testkit, tx, Error::SupplyExceeded);
+
+    // Only the issuer can issue currency, and only up to the maximum supply.
+    assert_error(&mut testkit, issue(&bob, 10), Error::NotIssuer);
+    assert_error(&mut testkit, issue(&alice, 21), Error::SupplyExceeded);
+    testkit.create_block_with_transaction(issue(&alice, 20))[0]
+        .status()
+        .unwrap();
+    assert_eq!(balance(&testkit, &alice.0), 70);
+    assert_eq!(total_supply(&testkit), 120);
+}
+
+#[test]
+fn test_issuance_policy_change() {
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    let mut testkit = create_testkit(Config::default());
+    let tx = alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME));
+    testkit.create_block_with_transaction(tx)[0]
+        .status()
+        .unwrap();
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+
+    let config = Config {
+        initial_balance: 10,
+        issuer_key: Some(bob.0),
+        max_supply: INITIAL_BALANCE + 20,
+        ..Config::default()
+    };
+    let propose = ConfigPropose::immediate(0).service_config(SERVICE_ID, config.clone());
+    testkit.commit_configuration_change(propose);
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    assert_eq!(schema.config.get(), Some(config));
+
+    // The new configuration is applied to the following transactions.
+    let tx = bob.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME));
+    testkit.create_block_with_transaction(tx)[0]
+        .status()
+        .unwrap();
+    assert_eq!(balance(&testkit, &bob.0), 10);
+    assert_error(&mut testkit, issue(&alice, 10), Error::NotIssuer);
+    assert_error(&mut testkit, issue(&bob, 11), Error::SupplyExceeded);
+
+    // The maximum supply cannot be set below the currency which is already issued.
+    let config = Config {
+        max_supply: INITIAL_BALANCE,
+        ..Config::default()
+    };
+    let propose =
+        ConfigPropose::immediate(testkit.configuration_number()).service_config(SERVICE_ID, config);
+    let keys = testkit.us().service_keypair();
+    let tx = propose.sign_for_supervisor(keys.0, &keys.1);
+    assert_error(&mut testkit, tx, Error::InvalidConfig);
+
+    // The initial balance omitted in the configuration is decoded as zero, which is rejected.
+    let config = Config {
+        initial_balance: 0,
+        ..Config::default()
+    };
+    let propose =
+        ConfigPropose::immediate(testkit.configuration_number()).service_config(SERVICE_ID, config);
+    let tx = propose.sign_for_supervisor(keys.0, &keys.1);
+    assert_error(&mut testkit, tx, Error::InvalidConfig);
+}
+
+#[test]
+fn test_default_issuance_policy() {
+    let config = Config::default();
+    assert_eq!(config.initial_balance, INITIAL_BALANCE);
+    assert_eq!(config.max_supply, 0);
+    assert!(config.allows_supply(u64::max_value()));
+
+    let alice = crypto::gen_keypair();
+    let mut testkit = create_testkit(config);
+    let tx = alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME));
+    testkit.create_block_with_transaction(tx)[0]
+        .status()
+        .unwrap();
+    assert_eq!(balance(&testkit, &alice.0), INITIAL_BALANCE);
+    testkit.create_block_with_transaction(issue(&alice, 1_000_000))[0]
+        .status()
+        .unwrap();
+    assert_eq!(total_supply(&testkit), INITIAL_BALANCE + 1_000_000);
+}
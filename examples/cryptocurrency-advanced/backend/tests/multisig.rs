@@ -0,0 +1,292 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for multisignature wallets.
+
+use exonum::{
+    crypto::{self, Hash, PublicKey, SecretKey},
+    merkledb::ObjectHash,
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    transactions::{ApproveTransfer, CreateMultisigWallet, CreateWallet, Error, ProposeTransfer},
+    wallet::Wallet,
+    CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+type KeyPair = (PublicKey, SecretKey);
+
+fn create_testkit() -> TestKit {
+    let artifact = CryptocurrencyService.artifact_id();
+    TestKitBuilder::validator()
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(SERVICE_ID, SERVICE_NAME))
+        .create()
+}
+
+fn get_wallet(testkit: &TestKit, key: &PublicKey) -> Wallet {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.wallets.get(key).expect("No wallet persisted")
+}
+
+fn has_pending_transfer(testkit: &TestKit, transfer_id: &Hash) -> bool {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.pending_transfers.contains(transfer_id)
+}
+
+/// Creates a multisignature wallet owned by 3 keys and a regular receiver wallet.
+/// Returns the keys of the owners, the multisignature wallet and the receiver wallet.
+fn create_wallets(testkit: &mut TestKit, quorum: u32) -> (Vec<KeyPair>, PublicKey, PublicKey) {
+    let owners: Vec<_> = (0..3).map(|_| crypto::gen_keypair()).collect();
+    let receiver = crypto::gen_keypair();
+
+    let tx = owners[0].create_multisig_wallet(
+        SERVICE_ID,
+        CreateMultisigWallet {
+            name: ALICE_NAME.to_owned(),
+            owners: owners.iter().map(|(key, _)| *key).collect(),
+            quorum,
+        },
+    );
+    let wallet_key = CreateMultisigWallet::wallet_key(&tx.object_hash());
+    let block = testkit.create_block_with_transactions(vec![
+        tx,
+        receiver.create_wallet(SERVICE_ID, CreateWallet::new(BOB_NAME)),
+    ]);
+    block[0].status().unwrap();
+    block[1].status().unwrap();
+    (owners, wallet_key, receiver.0)
+}
+
+#[test]
+fn test_create_multisig_wallet() {
+    let mut testkit = create_testkit();
+    let (owners, wallet_key, _) = create_wallets(&mut testkit, 2);
+
+    let wallet = get_wallet(&testkit, &wallet_key);
+    assert_eq!(wallet.name, ALICE_NAME);
+    assert_eq!(wallet.balance, INITIAL_BALANCE);
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    let config = schema.multisig_wallets.get(&wallet_key).unwrap();
+    assert_eq!(config.quorum, 2);
+    assert_eq!(config.owners.len(), owners.len());
+    assert!(owners.iter().all(|(key, _)| config.is_owner(key)));
+}
+
+#[test]
+fn test_create_multisig_wallet_with_invalid_config() {
+    let mut testkit = create_testkit();
+    let keypair = crypto::gen_keypair();
+    let other_key = crypto::gen_keypair().0;
+
+    let invalid_configs = vec![
+        (vec![keypair.0, other_key], 0),
+        (vec![keypair.0, other_key], 3),
+        (vec![keypair.0, keypair.0], 2),
+        (vec![], 1),
+    ];
+    for (owners, quorum) in invalid_configs {
+        let tx = keypair.create_multisig_wallet(
+            SERVICE_ID,
+            CreateMultisigWallet {
+                name: ALICE_NAME.to_owned(),
+                owners,
+                quorum,
+            },
+        );
+        let block = testkit.create_block_with_transaction(tx);
+        let expected_err =
+            ErrorMatch::from_fail(&Error::InvalidMultisigConfig).for_service(SERVICE_ID);
+        assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    }
+}
+
+#[test]
+fn test_multisig_transfer() {
+    let mut testkit = create_testkit();
+    let (owners, wallet_key, receiver) = create_wallets(&mut testkit, 2);
+
+    let propose = owners[0].propose_transfer(
+        SERVICE_ID,
+        ProposeTransfer {
+            from: wallet_key,
+            to: receiver,
+            amount: 10,
+            seed: 0,
+        },
+    );
+    let transfer_id = propose.object_hash();
+    testkit.create_block_with_transaction(propose).transactions[0]
+        .status()
+        .unwrap();
+
+    // The transfer is not executed until the quorum is reached.
+    assert!(has_pending_transfer(&testkit, &transfer_id));
+    assert_eq!(get_wallet(&testkit, &wallet_key).balance, INITIAL_BALANCE);
+
+    // Repeated approval by the proposer is rejected.
+    let approve = owners[0].approve_transfer(SERVICE_ID, ApproveTransfer { transfer_id });
+    let block = testkit.create_block_with_transaction(approve);
+    let expected_err =
+        ErrorMatch::from_fail(&Error::TransferAlreadyApproved).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    let approve = owners[2].approve_transfer(SERVICE_ID, ApproveTransfer { transfer_id });
+    testkit.create_block_with_transaction(approve).transactions[0]
+        .status()
+        .unwrap();
+
+    assert!(!has_pending_transfer(&testkit, &transfer_id));
+    let wallet = get_wallet(&testkit, &wallet_key);
+    assert_eq!(wallet.balance, INITIAL_BALANCE - 10);
+    assert_eq!(wallet.history_len, 2);
+    assert_eq!(
+        get_wallet(&testkit, &receiver).balance,
+        INITIAL_BALANCE + 10
+    );
+
+    // The executed transfer cannot be approved anymore.
+    let approve = owners[1].approve_transfer(SERVICE_ID, ApproveTransfer { transfer_id });
+    let block = testkit.create_block_with_transaction(approve);
+    let expected_err = ErrorMatch::from_fail(&Error::TransferNotFound).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_multisig_transfer_with_single_approval() {
+    let mut testkit = create_testkit();
+    let (owners, wallet_key, receiver) = create_wallets(&mut testkit, 1);
+
+    let propose = owners[1].propose_transfer(
+        SERVICE_ID,
+        ProposeTransfer {
+            from: wallet_key,
+            to: receiver,
+            amount: 10,
+            seed: 0,
+        },
+    );
+    let transfer_id = propose.object_hash();
+    testkit.create_block_with_transaction(propose).transactions[0]
+        .status()
+        .unwrap();
+
+    assert!(!has_pending_transfer(&testkit, &transfer_id));
+    assert_eq!(
+        get_wallet(&testkit, &wallet_key).balance,
+        INITIAL_BALANCE - 10
+    );
+}
+
+#[test]
+fn test_multisig_transfer_by_non_owner() {
+    let mut testkit = create_testkit();
+    let (owners, wallet_key, receiver) = create_wallets(&mut testkit, 2);
+    let stranger = crypto::gen_keypair();
+
+    let propose = stranger.propose_transfer(
+        SERVICE_ID,
+        ProposeTransfer {
+            from: wallet_key,
+            to: receiver,
+            amount: 10,
+            seed: 0,
+        },
+    );
+    let block = testkit.create_block_with_transaction(propose);
+    let expected_err = ErrorMatch::from_fail(&Error::NotWalletOwner).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    let propose = owners[0].propose_transfer(
+        SERVICE_ID,
+        ProposeTransfer {
+            from: wallet_key,
+            to: receiver,
+            amount: 10,
+            seed: 1,
+        },
+    );
+    let transfer_id = propose.object_hash();
+    testkit.create_block_with_transaction(propose);
+    let approve = stranger.approve_transfer(SERVICE_ID, ApproveTransfer { transfer_id });
+    let block = testkit.create_block_with_transaction(approve);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    assert!(has_pending_transfer(&testkit, &transfer_id));
+}
+
+#[test]
+fn test_propose_transfer_from_regular_wallet() {
+    let mut testkit = create_testkit();
+    let (_, wallet_key, _) = create_wallets(&mut testkit, 2);
+    let alice = crypto::gen_keypair();
+    testkit.create_block_with_transaction(
+        alice.create_wallet(SERVICE_ID, CreateWallet::new(ALICE_NAME)),
+    );
+
+    let propose = alice.propose_transfer(
+        SERVICE_ID,
+        ProposeTransfer {
+            from: alice.0,
+            to: wallet_key,
+            amount: 10,
+            seed: 0,
+        },
+    );
+    let block = testkit.create_block_with_transaction(propose);
+    let expected_err = ErrorMatch::from_fail(&Error::NotMultisigWallet).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_multisig_transfer_with_insufficient_funds() {
+    let mut testkit = create_testkit();
+    let (owners, wallet_key, receiver) = create_wallets(&mut testkit, 2);
+
+    let propose = owners[0].propose_transfer(
+        SERVICE_ID,
+        ProposeTransfer {
+            from: wallet_key,
+            to: receiver,
+            amount: INITIAL_BALANCE + 1,
+            seed: 0,
+        },
+    );
+    let transfer_id = propose.object_hash();
+    testkit.create_block_with_transaction(propose);
+
+    let approve = owners[1].approve_transfer(SERVICE_ID, ApproveTransfer { transfer_id });
+    let block = testkit.create_block_with_transaction(approve);
+    let expected_err =
+        ErrorMatch::from_fail(&Error::InsufficientCurrencyAmount).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    // The failed approval is not recorded.
+    assert!(has_pending_transfer(&testkit, &transfer_id));
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    let transfer = schema.pending_transfers.get(&transfer_id).unwrap();
+    assert_eq!(transfer.approvals, vec![owners[0].0]);
+}
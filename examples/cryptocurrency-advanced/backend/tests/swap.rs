@@ -0,0 +1,213 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for atomic swaps between two instances of the cryptocurrency service.
+
+use exonum::{
+    crypto::{self, Hash, PublicKey, SecretKey},
+    helpers::Height,
+    merkledb::ObjectHash,
+    runtime::{ErrorMatch, InstanceId, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use exonum_cryptocurrency_advanced::{
+    transactions::{ClaimSwap, CreateWallet, Error, LockFunds, RefundFunds},
+    CryptocurrencyInterface, CryptocurrencyService, Schema, INITIAL_BALANCE,
+};
+
+use crate::constants::{ALICE_NAME, BOB_NAME, SERVICE_ID, SERVICE_NAME};
+
+mod constants;
+
+/// Identifier of the second cryptocurrency instance.
+const OTHER_SERVICE_ID: InstanceId = SERVICE_ID + 1;
+/// Secret used in the swaps.
+const SECRET: &[u8] = b"swap secret";
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with two cryptocurrency instances; Alice and Bob have wallets
+/// in both of them.
+fn create_testkit() -> (TestKit, KeyPair, KeyPair) {
+    let artifact = CryptocurrencyService.artifact_id();
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(CryptocurrencyService)
+        .with_artifact(artifact.clone())
+        .with_instance(
+            artifact
+                .clone()
+                .into_default_instance(SERVICE_ID, SERVICE_NAME),
+        )
+        .with_instance(artifact.into_default_instance(OTHER_SERVICE_ID, "other-token"))
+        .create();
+
+    let alice = crypto::gen_keypair();
+    let bob = crypto::gen_keypair();
+    let txs = [SERVICE_ID, OTHER_SERVICE_ID].iter().flat_map(|&id| {
+        vec![
+            alice.create_wallet(id, CreateWallet::new(ALICE_NAME)),
+            bob.create_wallet(id, CreateWallet::new(BOB_NAME)),
+        ]
+    });
+    testkit.create_block_with_transactions(txs.collect::<Vec<_>>());
+    (testkit, alice, bob)
+}
+
+fn balance(testkit: &TestKit, instance_id: InstanceId, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(instance_id).unwrap();
+    schema
+        .wallets
+        .get(key)
+        .expect("No wallet persisted")
+        .balance
+}
+
+fn has_lock(testkit: &TestKit, instance_id: InstanceId, lock_id: &Hash) -> bool {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(instance_id).unwrap();
+    schema.hash_locks.contains(lock_id)
+}
+
+/// Alice locks 30 tokens for Bob in the first instance, and Bob locks 50 tokens
+/// for Alice in the second instance. Returns the identifiers of the locks.
+fn lock_funds(testkit: &mut TestKit, alice: &KeyPair, bob: &KeyPair) -> (Hash, Hash) {
+    let hash_lock = crypto::hash(SECRET);
+    let alice_lock = alice.lock_funds(
+        SERVICE_ID,
+        LockFunds {
+            to: bob.0,
+            amount: 30,
+            hash_lock,
+            timeout_height: 20,
+        },
+    );
+    // The counterpart lock expires earlier, so that Bob has time to claim Alice's funds
+    // after the secret is revealed.
+    let bob_lock = bob.lock_funds(
+        OTHER_SERVICE_ID,
+        LockFunds {
+            to: alice.0,
+            amount: 50,
+            hash_lock,
+            timeout_height: 10,
+        },
+    );
+    let ids = (alice_lock.object_hash(), bob_lock.object_hash());
+    let block = testkit.create_block_with_transactions(vec![alice_lock, bob_lock]);
+    block[0].status().unwrap();
+    block[1].status().unwrap();
+    ids
+}
+
+#[test]
+fn test_atomic_swap() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let (alice_lock, bob_lock) = lock_funds(&mut testkit, &alice, &bob);
+    assert_eq!(
+        balance(&testkit, SERVICE_ID, &alice.0),
+        INITIAL_BALANCE - 30
+    );
+    assert_eq!(
+        balance(&testkit, OTHER_SERVICE_ID, &bob.0),
+        INITIAL_BALANCE - 50
+    );
+
+    // Alice claims Bob's funds, which also claims her funds on behalf of Bob.
+    let claim = alice.claim_swap(
+        OTHER_SERVICE_ID,
+        ClaimSwap {
+            lock_id: bob_lock,
+            secret: SECRET.to_vec(),
+            counterpart_instance: SERVICE_ID,
+            counterpart_lock_id: alice_lock,
+        },
+    );
+    let block = testkit.create_block_with_transaction(claim);
+    block[0].status().unwrap();
+
+    assert_eq!(
+        balance(&testkit, OTHER_SERVICE_ID, &alice.0),
+        INITIAL_BALANCE + 50
+    );
+    assert_eq!(balance(&testkit, SERVICE_ID, &bob.0), INITIAL_BALANCE + 30);
+    assert!(!has_lock(&testkit, SERVICE_ID, &alice_lock));
+    assert!(!has_lock(&testkit, OTHER_SERVICE_ID, &bob_lock));
+}
+
+#[test]
+fn test_atomic_swap_is_atomic() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let (_, bob_lock) = lock_funds(&mut testkit, &alice, &bob);
+
+    // The counterpart lock does not exist, so the whole swap fails.
+    let claim = alice.claim_swap(
+        OTHER_SERVICE_ID,
+        ClaimSwap {
+            lock_id: bob_lock,
+            secret: SECRET.to_vec(),
+            counterpart_instance: SERVICE_ID,
+            counterpart_lock_id: Hash::zero(),
+        },
+    );
+    let block = testkit.create_block_with_transaction(claim);
+    let expected_err = ErrorMatch::from_fail(&Error::LockNotFound).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    assert!(has_lock(&testkit, OTHER_SERVICE_ID, &bob_lock));
+    assert_eq!(
+        balance(&testkit, OTHER_SERVICE_ID, &alice.0),
+        INITIAL_BALANCE
+    );
+}
+
+#[test]
+fn test_claim_with_invalid_secret() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let (alice_lock, bob_lock) = lock_funds(&mut testkit, &alice, &bob);
+
+    let claim = alice.claim_swap(
+        OTHER_SERVICE_ID,
+        ClaimSwap {
+            lock_id: bob_lock,
+            secret: b"wrong secret".to_vec(),
+            counterpart_instance: SERVICE_ID,
+            counterpart_lock_id: alice_lock,
+        },
+    );
+    let block = testkit.create_block_with_transaction(claim);
+    let expected_err = ErrorMatch::from_fail(&Error::InvalidSecret).for_service(OTHER_SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+    assert!(has_lock(&testkit, OTHER_SERVICE_ID, &bob_lock));
+}
+
+#[test]
+fn test_refund_after_timeout() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let (_, bob_lock) = lock_funds(&mut testkit, &alice, &bob);
+
+    let refund = bob.refund_funds(OTHER_SERVICE_ID, RefundFunds { lock_id: bob_lock });
+    let block = testkit.create_block_with_transaction(refund);
+    let expected_err = ErrorMatch::from_fail(&Error::LockNotExpired).for_service(OTHER_SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    testkit.create_blocks_until(Height(10));
+    let refund = bob.refund_funds(OTHER_SERVICE_ID, RefundFunds { lock_id: bob_lock });
+    let block = testkit.create_block_with_transaction(refund);
+    block[0].status().unwrap();
+    assert_eq!(balance(&testkit, OTHER_SERVICE_ID, &bob.0), INITIAL_BALANCE);
+    assert!(!has_lock(&testkit, OTHER_SERVICE_ID, &bob_lock));
+}
@@ -362,6 +362,7 @@ fn main() {
                 AnyTx {
                     call_info: CallInfo::new(instance_id, 0),
                     arguments: 1_000_u64.into_bytes(),
+                    valid_until: None,
                 }
                 .sign(service_keypair.0, &service_keypair.1),
             )
@@ -375,6 +376,7 @@ fn main() {
                 AnyTx {
                     call_info: CallInfo::new(instance_id, 1),
                     arguments: Vec::default(),
+                    valid_until: None,
                 }
                 .sign(service_keypair.0, &service_keypair.1),
             )
@@ -0,0 +1,114 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supply chain API.
+
+use exonum_merkledb::{ListProof, MapProof};
+
+use exonum::{
+    blockchain::{BlockProof, IndexProof},
+    crypto::Hash,
+    messages::{AnyTx, Verified},
+};
+use exonum_rust_runtime::api::{self, ServiceApiBuilder, ServiceApiState};
+
+use crate::schema::{Item, SchemaImpl};
+
+/// Describes the query parameters for the `item_info` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ItemQuery {
+    /// Identifier of the queried item.
+    pub id: String,
+}
+
+/// Proof of existence for specific item.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemProof {
+    /// Proof of the whole items table.
+    pub to_table: MapProof<String, Hash>,
+    /// Proof of the specific item in this table.
+    pub to_item: MapProof<String, Item>,
+}
+
+/// Item history.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemHistory {
+    /// Proof of the list of transaction hashes.
+    pub proof: ListProof<Hash>,
+    /// List of above transactions.
+    pub transactions: Vec<Verified<AnyTx>>,
+}
+
+/// Item information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate item.
+    pub item_proof: ItemProof,
+    /// History of the appropriate item. The history is proven against
+    /// the `history_hash` of the item.
+    pub item_history: Option<ItemHistory>,
+}
+
+/// Public service API description.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting a single item together with its history.
+    pub fn item_info(self, state: &ServiceApiState<'_>, id: String) -> api::Result<ItemInfo> {
+        let IndexProof {
+            block_proof,
+            index_proof,
+        } = state.data().proof_for_service_index("items").unwrap();
+
+        let schema = SchemaImpl::new(state.service_data());
+        let item_proof = ItemProof {
+            to_table: index_proof,
+            to_item: schema.public.items.get_proof(id.clone()),
+        };
+
+        let item_history = schema.public.items.get(&id).map(|_| {
+            // `history` is always present for existing items.
+            let history = schema.item_history.get(&id);
+            let proof = history.get_range_proof(..);
+
+            let transactions = state.data().for_core().transactions();
+            let transactions = history
+                .iter()
+                .map(|tx_hash| transactions.get(&tx_hash).unwrap())
+                .collect();
+
+            ItemHistory {
+                proof,
+                transactions,
+            }
+        });
+
+        Ok(ItemInfo {
+            block_proof,
+            item_proof,
+            item_history,
+        })
+    }
+
+    /// Wires the above endpoint to public scope of the given `ServiceApiBuilder`.
+    pub fn wire(self, builder: &mut ServiceApiBuilder) {
+        builder.public_scope().endpoint(
+            "v1/items/info",
+            move |state: &ServiceApiState<'_>, query: ItemQuery| self.item_info(state, query.id),
+        );
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supply chain tracking implementation example using [exonum](http://exonum.com/).
+//!
+//! The service tracks items moving between participants of a supply chain. Participants
+//! are registered by the administrator and have roles restricting the transactions
+//! they may perform: only manufacturers may create items, and only the current owner
+//! of an item may transfer it or update its status. Each item has a Merkelized history
+//! of transactions related to it, which can be proven to light clients.
+
+#![deny(unsafe_code, bare_trait_objects)]
+#![warn(missing_docs, missing_debug_implementations)]
+
+#[macro_use]
+extern crate serde_derive; // Required for Protobuf.
+
+pub use crate::{
+    schema::{Config, Item, Participant, Role, Schema},
+    transactions::{Error, SupplyChainInterface},
+};
+
+pub mod api;
+pub mod proto;
+pub mod schema;
+pub mod transactions;
+
+use exonum::{
+    merkledb::BinaryValue,
+    runtime::{CommonError, ExecutionError},
+};
+use exonum_derive::{ServiceDispatcher, ServiceFactory};
+use exonum_rust_runtime::{api::ServiceApiBuilder, CallContext, Service};
+
+use crate::{api::PublicApi, schema::SchemaImpl};
+
+/// Supply chain service implementation.
+#[derive(Debug, ServiceDispatcher, ServiceFactory)]
+#[service_dispatcher(implements("SupplyChainInterface"))]
+#[service_factory(proto_sources = "proto")]
+pub struct SupplyChainService;
+
+impl Service for SupplyChainService {
+    fn initialize(&self, context: CallContext<'_>, params: Vec<u8>) -> Result<(), ExecutionError> {
+        let config = Config::from_bytes(params.into()).map_err(CommonError::malformed_arguments)?;
+        let mut schema = SchemaImpl::new(context.service_data());
+        schema.public.config.set(config);
+        Ok(())
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        PublicApi.wire(builder);
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supply chain database schema.
+
+use exonum::crypto::{Hash, PublicKey};
+use exonum::merkledb::{
+    access::{Access, FromAccess, RawAccessMut},
+    Group, ObjectHash, ProofEntry, ProofListIndex, ProofMapIndex, RawProofMapIndex,
+};
+use exonum_derive::{BinaryValue, FromAccess, ObjectHash, RequireArtifact};
+use exonum_proto::ProtobufConvert;
+
+use crate::proto;
+
+/// Role of a supply chain participant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    /// Participant producing items.
+    Manufacturer,
+    /// Participant delivering items.
+    Carrier,
+    /// Participant selling items to the end customers.
+    Retailer,
+}
+
+impl ProtobufConvert for Role {
+    type ProtoStruct = proto::Role;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        match self {
+            Role::Manufacturer => proto::Role::MANUFACTURER,
+            Role::Carrier => proto::Role::CARRIER,
+            Role::Retailer => proto::Role::RETAILER,
+        }
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        Ok(match pb {
+            proto::Role::MANUFACTURER => Role::Manufacturer,
+            proto::Role::CARRIER => Role::Carrier,
+            proto::Role::RETAILER => Role::Retailer,
+        })
+    }
+}
+
+/// Participant of the supply chain.
+#[derive(Clone, Debug, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Participant", serde_pb_convert)]
+pub struct Participant {
+    /// Key of the participant.
+    pub key: PublicKey,
+    /// Name of the participant.
+    pub name: String,
+    /// Role of the participant.
+    pub role: Role,
+}
+
+/// Item tracked by the service.
+#[derive(Clone, Debug, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Item", serde_pb_convert)]
+pub struct Item {
+    /// Unique identifier of the item.
+    pub id: String,
+    /// Description of the item.
+    pub description: String,
+    /// Key of the current owner.
+    pub owner: PublicKey,
+    /// Current status of the item.
+    pub status: String,
+    /// Length of the item history.
+    pub history_len: u64,
+    /// `Hash` of the item history.
+    pub history_hash: Hash,
+}
+
+/// Configuration of the supply chain service passed to the service constructor.
+#[derive(Clone, Debug, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Config", serde_pb_convert)]
+pub struct Config {
+    /// Key of the administrator allowed to register participants.
+    pub admin_key: PublicKey,
+}
+
+/// Database schema for the supply chain.
+///
+/// Note that the schema is crate-private, but it has a public part.
+#[derive(Debug, FromAccess)]
+pub(crate) struct SchemaImpl<T: Access> {
+    /// Public part of the schema.
+    #[from_access(flatten)]
+    pub public: Schema<T>,
+    /// History for specific items. Each item has its own list of transaction hashes,
+    /// which is stored in the group under the item identifier.
+    pub item_history: Group<T, String, ProofListIndex<T::Base, Hash>>,
+}
+
+/// Public part of the supply chain schema.
+#[derive(Debug, FromAccess, RequireArtifact)]
+pub struct Schema<T: Access> {
+    /// Registered participants of the supply chain.
+    pub participants: RawProofMapIndex<T::Base, PublicKey, Participant>,
+    /// Map of item identifiers to the information about the corresponding item.
+    pub items: ProofMapIndex<T::Base, String, Item>,
+    /// Service configuration.
+    pub config: ProofEntry<T::Base, Config>,
+}
+
+impl<T: Access> SchemaImpl<T> {
+    pub fn new(access: T) -> Self {
+        Self::from_root(access).unwrap()
+    }
+}
+
+impl<T> SchemaImpl<T>
+where
+    T: Access,
+    T::Base: RawAccessMut,
+{
+    /// Creates a new item and appends the first record to its history.
+    pub(crate) fn create_item(
+        &mut self,
+        id: &str,
+        description: &str,
+        owner: PublicKey,
+        transaction: Hash,
+    ) {
+        let mut history = self.item_history.get(&id.to_owned());
+        history.push(transaction);
+        let item = Item {
+            id: id.to_owned(),
+            description: description.to_owned(),
+            owner,
+            status: String::new(),
+            history_len: history.len(),
+            history_hash: history.object_hash(),
+        };
+        self.public.items.put(&item.id, item);
+    }
+
+    /// Updates the item and appends a new record to its history.
+    pub(crate) fn update_item(&mut self, mut item: Item, transaction: Hash) {
+        let mut history = self.item_history.get(&item.id);
+        history.push(transaction);
+        item.history_len = history.len();
+        item.history_hash = history.object_hash();
+        let id = item.id.clone();
+        self.public.items.put(&id, item);
+    }
+}
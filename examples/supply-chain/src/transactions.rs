@@ -0,0 +1,231 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supply chain transactions.
+
+use exonum::{
+    crypto::PublicKey,
+    runtime::{CommonError, ExecutionError},
+};
+use exonum_derive::{exonum_interface, interface_method, BinaryValue, ExecutionFail, ObjectHash};
+use exonum_proto::ProtobufConvert;
+use exonum_rust_runtime::CallContext;
+
+use crate::{
+    proto,
+    schema::{Participant, Role, SchemaImpl},
+    SupplyChainService,
+};
+
+/// Error codes emitted by supply chain transactions during execution.
+#[derive(Debug, ExecutionFail)]
+pub enum Error {
+    /// Transaction author is not the administrator of the service.
+    ///
+    /// Can be emitted by `RegisterParticipant`.
+    NotAdmin = 0,
+    /// Participant is already registered.
+    ///
+    /// Can be emitted by `RegisterParticipant`.
+    ParticipantAlreadyExists = 1,
+    /// Participant is not registered.
+    ///
+    /// Can be emitted by `CreateItem` or `TransferItem`.
+    UnknownParticipant = 2,
+    /// Role of the transaction author does not allow the operation.
+    ///
+    /// Can be emitted by `CreateItem`.
+    RoleNotAllowed = 3,
+    /// Item with the same identifier already exists.
+    ///
+    /// Can be emitted by `CreateItem`.
+    ItemAlreadyExists = 4,
+    /// Item doesn't exist.
+    ///
+    /// Can be emitted by `TransferItem` or `UpdateStatus`.
+    ItemNotFound = 5,
+    /// Transaction author is not the owner of the item.
+    ///
+    /// Can be emitted by `TransferItem` or `UpdateStatus`.
+    NotItemOwner = 6,
+}
+
+/// Register a participant of the supply chain. Can only be performed by the administrator.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::RegisterParticipant", serde_pb_convert)]
+pub struct RegisterParticipant {
+    /// Key of the participant.
+    pub key: PublicKey,
+    /// Name of the participant.
+    pub name: String,
+    /// Role of the participant.
+    pub role: Role,
+}
+
+/// Create a new item owned by the transaction author. Can only be performed
+/// by manufacturers.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::CreateItem", serde_pb_convert)]
+pub struct CreateItem {
+    /// Unique identifier of the item, e.g., its serial number.
+    pub id: String,
+    /// Description of the item.
+    pub description: String,
+}
+
+/// Transfer the item to another registered participant. Can only be performed
+/// by the current owner of the item.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::TransferItem", serde_pb_convert)]
+pub struct TransferItem {
+    /// Identifier of the item.
+    pub id: String,
+    /// Key of the new owner.
+    pub new_owner: PublicKey,
+}
+
+/// Update the status of the item, e.g., its location. Can only be performed
+/// by the current owner of the item.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::UpdateStatus", serde_pb_convert)]
+pub struct UpdateStatus {
+    /// Identifier of the item.
+    pub id: String,
+    /// New status of the item.
+    pub status: String,
+}
+
+/// Supply chain service transactions.
+#[exonum_interface]
+pub trait SupplyChainInterface<Ctx> {
+    /// Output returned by the interface methods.
+    type Output;
+
+    /// Registers a participant of the supply chain.
+    #[interface_method(id = 0)]
+    fn register_participant(&self, ctx: Ctx, arg: RegisterParticipant) -> Self::Output;
+    /// Creates a new item.
+    #[interface_method(id = 1)]
+    fn create_item(&self, ctx: Ctx, arg: CreateItem) -> Self::Output;
+    /// Transfers the item to another participant.
+    #[interface_method(id = 2)]
+    fn transfer_item(&self, ctx: Ctx, arg: TransferItem) -> Self::Output;
+    /// Updates the status of the item.
+    #[interface_method(id = 3)]
+    fn update_status(&self, ctx: Ctx, arg: UpdateStatus) -> Self::Output;
+}
+
+impl SupplyChainInterface<CallContext<'_>> for SupplyChainService {
+    type Output = Result<(), ExecutionError>;
+
+    fn register_participant(
+        &self,
+        context: CallContext<'_>,
+        arg: RegisterParticipant,
+    ) -> Self::Output {
+        let (_, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let config = schema.public.config.get().expect("No service config");
+        if author != config.admin_key {
+            return Err(Error::NotAdmin.into());
+        }
+        if schema.public.participants.contains(&arg.key) {
+            return Err(Error::ParticipantAlreadyExists.into());
+        }
+
+        let participant = Participant {
+            key: arg.key,
+            name: arg.name,
+            role: arg.role,
+        };
+        schema.public.participants.put(&arg.key, participant);
+        Ok(())
+    }
+
+    fn create_item(&self, context: CallContext<'_>, arg: CreateItem) -> Self::Output {
+        let (tx_hash, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let participant = schema
+            .public
+            .participants
+            .get(&author)
+            .ok_or(Error::UnknownParticipant)?;
+        if participant.role != Role::Manufacturer {
+            return Err(Error::RoleNotAllowed.into());
+        }
+        if schema.public.items.contains(&arg.id) {
+            return Err(Error::ItemAlreadyExists.into());
+        }
+
+        schema.create_item(&arg.id, &arg.description, author, tx_hash);
+        Ok(())
+    }
+
+    fn transfer_item(&self, context: CallContext<'_>, arg: TransferItem) -> Self::Output {
+        let (tx_hash, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let mut item = schema
+            .public
+            .items
+            .get(&arg.id)
+            .ok_or(Error::ItemNotFound)?;
+        if item.owner != author {
+            return Err(Error::NotItemOwner.into());
+        }
+        if !schema.public.participants.contains(&arg.new_owner) {
+            return Err(Error::UnknownParticipant.into());
+        }
+
+        item.owner = arg.new_owner;
+        schema.update_item(item, tx_hash);
+        Ok(())
+    }
+
+    fn update_status(&self, context: CallContext<'_>, arg: UpdateStatus) -> Self::Output {
+        let (tx_hash, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let mut item = schema
+            .public
+            .items
+            .get(&arg.id)
+            .ok_or(Error::ItemNotFound)?;
+        if item.owner != author {
+            return Err(Error::NotItemOwner.into());
+        }
+
+        item.status = arg.status;
+        schema.update_item(item, tx_hash);
+        Ok(())
+    }
+}
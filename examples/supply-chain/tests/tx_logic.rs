@@ -0,0 +1,254 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the supply chain service.
+
+use exonum::{
+    crypto::{self, PublicKey, SecretKey},
+    merkledb::ObjectHash,
+    messages::{AnyTx, Verified},
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+use pretty_assertions::assert_eq;
+
+use exonum_supply_chain::{
+    api::{ItemInfo, ItemQuery},
+    transactions::{CreateItem, RegisterParticipant, TransferItem, UpdateStatus},
+    Config, Error, Item, Role, Schema, SupplyChainInterface, SupplyChainService,
+};
+
+const SERVICE_ID: u32 = 100;
+const SERVICE_NAME: &str = "supply-chain";
+const ITEM_ID: &str = "SN-0001";
+
+type KeyPair = (PublicKey, SecretKey);
+
+struct Participants {
+    admin: KeyPair,
+    manufacturer: KeyPair,
+    carrier: KeyPair,
+    retailer: KeyPair,
+}
+
+/// Creates a testkit with registered manufacturer, carrier and retailer.
+fn create_testkit() -> (TestKit, Participants) {
+    let participants = Participants {
+        admin: crypto::gen_keypair(),
+        manufacturer: crypto::gen_keypair(),
+        carrier: crypto::gen_keypair(),
+        retailer: crypto::gen_keypair(),
+    };
+
+    let artifact = SupplyChainService.artifact_id();
+    let instance = artifact
+        .clone()
+        .into_default_instance(SERVICE_ID, SERVICE_NAME)
+        .with_constructor(Config {
+            admin_key: participants.admin.0,
+        });
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(SupplyChainService)
+        .with_artifact(artifact)
+        .with_instance(instance)
+        .create();
+
+    let registrations = vec![
+        (&participants.manufacturer, "Factory", Role::Manufacturer),
+        (&participants.carrier, "Logistics", Role::Carrier),
+        (&participants.retailer, "Shop", Role::Retailer),
+    ];
+    let txs = registrations.into_iter().map(|(keypair, name, role)| {
+        participants.admin.register_participant(
+            SERVICE_ID,
+            RegisterParticipant {
+                key: keypair.0,
+                name: name.to_owned(),
+                role,
+            },
+        )
+    });
+    let block = testkit.create_block_with_transactions(txs);
+    assert!(block.iter().all(|tx| tx.status().is_ok()));
+    (testkit, participants)
+}
+
+fn create_item(keypair: &KeyPair) -> Verified<AnyTx> {
+    keypair.create_item(
+        SERVICE_ID,
+        CreateItem {
+            id: ITEM_ID.to_owned(),
+            description: "Box of apples".to_owned(),
+        },
+    )
+}
+
+fn get_item(testkit: &TestKit) -> Item {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.items.get(&ITEM_ID.to_owned()).expect("No item persisted")
+}
+
+fn assert_error(testkit: &mut TestKit, tx: Verified<AnyTx>, error: Error) {
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&error).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_item_lifecycle() {
+    let (mut testkit, participants) = create_testkit();
+    let create_tx = create_item(&participants.manufacturer);
+    let transfer_tx = participants.manufacturer.transfer_item(
+        SERVICE_ID,
+        TransferItem {
+            id: ITEM_ID.to_owned(),
+            new_owner: participants.carrier.0,
+        },
+    );
+    let status_tx = participants.carrier.update_status(
+        SERVICE_ID,
+        UpdateStatus {
+            id: ITEM_ID.to_owned(),
+            status: "In transit".to_owned(),
+        },
+    );
+    let history = vec![
+        create_tx.object_hash(),
+        transfer_tx.object_hash(),
+        status_tx.object_hash(),
+    ];
+    for tx in vec![create_tx, transfer_tx, status_tx] {
+        testkit.create_block_with_transaction(tx)[0]
+            .status()
+            .unwrap();
+    }
+
+    let item = get_item(&testkit);
+    assert_eq!(item.owner, participants.carrier.0);
+    assert_eq!(item.status, "In transit");
+    assert_eq!(item.history_len, 3);
+
+    // Check the item and its history via HTTP API.
+    let info: ItemInfo = testkit
+        .api()
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&ItemQuery {
+            id: ITEM_ID.to_owned(),
+        })
+        .get("v1/items/info")
+        .unwrap();
+
+    let state_hash = info.block_proof.block.state_hash;
+    let to_table = info
+        .item_proof
+        .to_table
+        .check_against_hash(state_hash)
+        .unwrap();
+    let (_, &table_hash) = to_table.entries().next().unwrap();
+    let to_item = info
+        .item_proof
+        .to_item
+        .check_against_hash(table_hash)
+        .unwrap();
+    let (_, proven_item) = to_item.entries().next().unwrap();
+    assert_eq!(*proven_item, item);
+
+    let item_history = info.item_history.unwrap();
+    let proof = item_history
+        .proof
+        .check_against_hash(item.history_hash)
+        .unwrap();
+    let proven_history: Vec<_> = proof.entries().iter().map(|(_, hash)| *hash).collect();
+    assert_eq!(proven_history, history);
+    let tx_hashes: Vec<_> = item_history
+        .transactions
+        .iter()
+        .map(ObjectHash::object_hash)
+        .collect();
+    assert_eq!(tx_hashes, history);
+}
+
+#[test]
+fn test_roles_restrictions() {
+    let (mut testkit, participants) = create_testkit();
+
+    // Only the administrator can register participants.
+    let tx = participants.carrier.register_participant(
+        SERVICE_ID,
+        RegisterParticipant {
+            key: crypto::gen_keypair().0,
+            name: "Impostor".to_owned(),
+            role: Role::Manufacturer,
+        },
+    );
+    assert_error(&mut testkit, tx, Error::NotAdmin);
+
+    // Only manufacturers can create items.
+    let tx = create_item(&participants.retailer);
+    assert_error(&mut testkit, tx, Error::RoleNotAllowed);
+    let tx = create_item(&crypto::gen_keypair());
+    assert_error(&mut testkit, tx, Error::UnknownParticipant);
+
+    testkit.create_block_with_transaction(create_item(&participants.manufacturer));
+
+    // Only the owner can update the item.
+    let tx = participants.carrier.update_status(
+        SERVICE_ID,
+        UpdateStatus {
+            id: ITEM_ID.to_owned(),
+            status: "Stolen".to_owned(),
+        },
+    );
+    assert_error(&mut testkit, tx, Error::NotItemOwner);
+    let tx = participants.retailer.transfer_item(
+        SERVICE_ID,
+        TransferItem {
+            id: ITEM_ID.to_owned(),
+            new_owner: participants.retailer.0,
+        },
+    );
+    assert_error(&mut testkit, tx, Error::NotItemOwner);
+
+    // Items can only be transferred to registered participants.
+    let tx = participants.manufacturer.transfer_item(
+        SERVICE_ID,
+        TransferItem {
+            id: ITEM_ID.to_owned(),
+            new_owner: crypto::gen_keypair().0,
+        },
+    );
+    assert_error(&mut testkit, tx, Error::UnknownParticipant);
+
+    let item = get_item(&testkit);
+    assert_eq!(item.owner, participants.manufacturer.0);
+    assert_eq!(item.history_len, 1);
+}
+
+#[test]
+fn test_duplicate_item() {
+    let (mut testkit, participants) = create_testkit();
+    testkit.create_block_with_transaction(create_item(&participants.manufacturer));
+
+    let tx = participants.manufacturer.create_item(
+        SERVICE_ID,
+        CreateItem {
+            id: ITEM_ID.to_owned(),
+            description: "Another box".to_owned(),
+        },
+    );
+    assert_error(&mut testkit, tx, Error::ItemAlreadyExists);
+    assert_eq!(get_item(&testkit).description, "Box of apples");
+}
@@ -54,6 +54,10 @@ pub struct PublicApi;
 
 impl PublicApi {
     /// Endpoint for getting a single timestamp.
+    ///
+    /// Since content cannot be timestamped twice, the endpoint always returns
+    /// the original timestamp of the content; in particular, it can be used to retrieve
+    /// the timestamp after a transaction has failed with the `AlreadyTimestamped` error.
     pub fn handle_timestamp(
         self,
         state: &ServiceApiState<'_>,
@@ -20,10 +20,14 @@ use exonum::merkledb::{
     access::{Access, FromAccess, RawAccessMut},
     Entry, RawProofMapIndex,
 };
+use exonum::runtime::{ExecutionError, ExecutionFail};
 use exonum_derive::{BinaryValue, FromAccess, ObjectHash};
 use exonum_proto::ProtobufConvert;
 
-use crate::{proto, transactions::Config};
+use crate::{
+    proto,
+    transactions::{Config, Error},
+};
 
 /// Stores content's hash and some metadata about it.
 #[derive(Clone, Debug, PartialEq)]
@@ -90,15 +94,19 @@ where
     T::Base: RawAccessMut,
 {
     /// Adds the timestamp entry to the database.
-    pub fn add_timestamp(&mut self, timestamp_entry: TimestampEntry) {
-        let timestamp = timestamp_entry.timestamp.clone();
-        let content_hash = &timestamp.content_hash;
-
-        // Check that timestamp with given content_hash does not exist.
-        if self.timestamps.contains(content_hash) {
-            return;
+    ///
+    /// Returns an error if the content is already timestamped. The existing entry
+    /// is left intact, so that the original timestamp is never overwritten.
+    pub fn add_timestamp(&mut self, timestamp_entry: TimestampEntry) -> Result<(), ExecutionError> {
+        let content_hash = timestamp_entry.timestamp.content_hash;
+        if let Some(original) = self.timestamps.get(&content_hash) {
+            let description = format!(
+                "Content is already timestamped in transaction {}.",
+                original.tx_hash.to_hex()
+            );
+            return Err(Error::AlreadyTimestamped.with_description(description));
         }
-        // Add the timestamp.
-        self.timestamps.put(content_hash, timestamp_entry);
+        self.timestamps.put(&content_hash, timestamp_entry);
+        Ok(())
     }
 }
@@ -14,7 +14,10 @@
 
 //! Timestamping transactions.
 
-use exonum::runtime::{CommonError, ExecutionError};
+use exonum::{
+    crypto,
+    runtime::{CommonError, ExecutionError},
+};
 use exonum_derive::{exonum_interface, interface_method, BinaryValue, ExecutionFail, ObjectHash};
 use exonum_proto::ProtobufConvert;
 use exonum_rust_runtime::CallContext;
@@ -27,13 +30,19 @@ use crate::{
     TimestampEntry, TimestampingService,
 };
 
-/// Error codes emitted by wallet transactions during execution.
+/// Error codes emitted by timestamping transactions during execution.
 #[derive(Debug, ExecutionFail)]
 pub enum Error {
-    /// Content hash already exists.
-    HashAlreadyExists = 0,
+    /// Content is already timestamped.
+    ///
+    /// The error description contains the hash of the transaction which has timestamped
+    /// the content originally; the original timestamp can be retrieved via the
+    /// `v1/timestamps/value` endpoint.
+    AlreadyTimestamped = 0,
     /// Time service with the specified name doesn't exist.
     TimeServiceNotFound = 1,
+    /// Content hash is the hash of the empty content.
+    EmptyContent = 2,
 }
 
 /// Timestamping configuration parameters.
@@ -62,6 +71,10 @@ impl TimestampingInterface<CallContext<'_>> for TimestampingService {
             .as_transaction()
             .ok_or(CommonError::UnauthorizedCaller)?;
 
+        if arg.content_hash == crypto::hash(&[]) {
+            return Err(Error::EmptyContent.into());
+        }
+
         let mut schema = Schema::new(context.service_data());
         let config = schema.config.get().expect("Can't read service config");
 
@@ -69,13 +82,9 @@ impl TimestampingInterface<CallContext<'_>> for TimestampingService {
         let time_schema: TimeSchema<_> = data.service_schema(config.time_service_name.as_str())?;
         let time = time_schema.time.get().ok_or(Error::TimeServiceNotFound)?;
 
-        if schema.timestamps.get(&arg.content_hash).is_some() {
-            Err(Error::HashAlreadyExists.into())
-        } else {
-            trace!("Timestamp added: {:?}", arg);
-            let entry = TimestampEntry::new(arg.clone(), tx_hash, time);
-            schema.add_timestamp(entry);
-            Ok(())
-        }
+        let entry = TimestampEntry::new(arg.clone(), tx_hash, time);
+        schema.add_timestamp(entry)?;
+        trace!("Timestamp added: {:?}", arg);
+        Ok(())
     }
 }
@@ -180,7 +180,46 @@ fn test_api_cannot_add_same_content_hash() {
                 "method_id": 0,
             },
             "code": 0,
-            "description": "Content hash already exists.",
+            "description": format!(
+                "Content is already timestamped in transaction {}.",
+                tx_ok.object_hash().to_hex()
+            ),
+            "runtime_id": 0,
+            "type": "service_error",
+        }),
+    );
+
+    // The original timestamp is kept intact.
+    let entry: Option<TimestampEntry> = api
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&TimestampQuery::new(content_hash))
+        .get("v1/timestamps/value")
+        .unwrap();
+    let entry = entry.unwrap();
+    assert_eq!(entry.timestamp, timestamp1);
+    assert_eq!(entry.tx_hash, tx_ok.object_hash());
+}
+
+#[test]
+fn test_api_cannot_add_empty_content() {
+    let (mut testkit, _) = init_testkit();
+    let api = testkit.api();
+    let timestamp = Timestamp::new(&hash(&[]), "metadata");
+    let tx = gen_keypair().timestamp(SERVICE_ID, timestamp);
+
+    testkit.create_block_with_transaction(tx.clone());
+    assert_status(
+        &api,
+        &tx,
+        &json!({
+            "type": "service_error",
+            "call_site": {
+                "call_type": "method",
+                "instance_id": SERVICE_ID,
+                "method_id": 0,
+            },
+            "code": 2,
+            "description": "Content hash is the hash of the empty content.",
             "runtime_id": 0,
             "type": "service_error",
         }),
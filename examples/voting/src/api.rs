@@ -0,0 +1,84 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voting API.
+
+use exonum_merkledb::{ListProof, MapProof};
+
+use exonum::{
+    blockchain::{BlockProof, IndexProof},
+    crypto::Hash,
+};
+use exonum_rust_runtime::api::{self, ServiceApiBuilder, ServiceApiState};
+
+use crate::schema::{Poll, SchemaImpl};
+
+/// Describes the query parameters for the `poll_info` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PollQuery {
+    /// Identifier of the queried poll.
+    pub id: u64,
+}
+
+/// Proof of existence for specific poll.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PollProof {
+    /// Proof of the whole polls table.
+    pub to_table: MapProof<String, Hash>,
+    /// Proof of the specific poll in this table. If the poll doesn't exist,
+    /// this is a proof of absence.
+    pub to_poll: ListProof<Poll>,
+}
+
+/// Poll information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PollInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the appropriate poll. The poll contains its current results;
+    /// the results are final if the poll is closed.
+    pub poll_proof: PollProof,
+}
+
+/// Public service API description.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting a single poll together with its results.
+    pub fn poll_info(self, state: &ServiceApiState<'_>, id: u64) -> api::Result<PollInfo> {
+        let IndexProof {
+            block_proof,
+            index_proof,
+        } = state.data().proof_for_service_index("polls").unwrap();
+
+        let schema = SchemaImpl::new(state.service_data());
+        let poll_proof = PollProof {
+            to_table: index_proof,
+            to_poll: schema.public.polls.get_proof(id),
+        };
+        Ok(PollInfo {
+            block_proof,
+            poll_proof,
+        })
+    }
+
+    /// Wires the above endpoint to public scope of the given `ServiceApiBuilder`.
+    pub fn wire(self, builder: &mut ServiceApiBuilder) {
+        builder.public_scope().endpoint(
+            "v1/polls/info",
+            move |state: &ServiceApiState<'_>, query: PollQuery| self.poll_info(state, query.id),
+        );
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! On-chain voting implementation example using [exonum](http://exonum.com/).
+//!
+//! The administrator of the service creates polls, each with a set of options, a whitelist
+//! of keys allowed to vote and a deadline height. Every whitelisted key may cast a single vote
+//! while the poll is open. Polls are closed automatically in the `before_transactions` hook
+//! of the block at the deadline height, after which their results are final and can be proven
+//! to light clients.
+
+#![deny(unsafe_code, bare_trait_objects)]
+#![warn(missing_docs, missing_debug_implementations)]
+
+#[macro_use]
+extern crate serde_derive; // Required for Protobuf.
+
+pub use crate::{
+    schema::{Config, Poll, Schema},
+    transactions::{Error, VotingInterface},
+};
+
+pub mod api;
+pub mod proto;
+pub mod schema;
+pub mod transactions;
+
+use exonum::{
+    merkledb::BinaryValue,
+    runtime::{CommonError, ExecutionError},
+};
+use exonum_derive::{ServiceDispatcher, ServiceFactory};
+use exonum_rust_runtime::{api::ServiceApiBuilder, CallContext, Service};
+
+use crate::{api::PublicApi, schema::SchemaImpl};
+
+/// Voting service implementation.
+#[derive(Debug, ServiceDispatcher, ServiceFactory)]
+#[service_dispatcher(implements("VotingInterface"))]
+#[service_factory(proto_sources = "proto")]
+pub struct VotingService;
+
+impl Service for VotingService {
+    fn initialize(&self, context: CallContext<'_>, params: Vec<u8>) -> Result<(), ExecutionError> {
+        let config = Config::from_bytes(params.into()).map_err(CommonError::malformed_arguments)?;
+        let mut schema = SchemaImpl::new(context.service_data());
+        schema.public.config.set(config);
+        Ok(())
+    }
+
+    fn before_transactions(&self, context: CallContext<'_>) -> Result<(), ExecutionError> {
+        let height = context.data().for_core().next_height().0;
+        let mut schema = SchemaImpl::new(context.service_data());
+        schema.close_expired_polls(height);
+        Ok(())
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        PublicApi.wire(builder);
+    }
+}
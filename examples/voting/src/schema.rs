@@ -0,0 +1,128 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voting database schema.
+
+use exonum::crypto::PublicKey;
+use exonum::merkledb::{
+    access::{Access, FromAccess, RawAccessMut},
+    Group, KeySetIndex, ProofEntry, ProofListIndex, RawProofMapIndex,
+};
+use exonum_derive::{BinaryValue, FromAccess, ObjectHash, RequireArtifact};
+use exonum_proto::ProtobufConvert;
+
+use crate::proto;
+
+/// Poll stored in the blockchain.
+#[derive(Clone, Debug, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Poll", serde_pb_convert)]
+pub struct Poll {
+    /// Title of the poll.
+    pub title: String,
+    /// Options to vote for.
+    pub options: Vec<String>,
+    /// Keys allowed to vote in the poll.
+    pub voters: Vec<PublicKey>,
+    /// Height starting from which votes are no longer accepted.
+    pub deadline_height: u64,
+    /// Number of votes for each option.
+    pub results: Vec<u64>,
+    /// Whether the poll is closed. The results of a closed poll are final.
+    pub closed: bool,
+}
+
+impl Poll {
+    /// Checks whether the key is allowed to vote in the poll.
+    pub fn is_voter(&self, key: &PublicKey) -> bool {
+        self.voters.contains(key)
+    }
+}
+
+/// Configuration of the voting service passed to the service constructor.
+#[derive(Clone, Debug, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Config", serde_pb_convert)]
+pub struct Config {
+    /// Key of the administrator allowed to create polls.
+    pub admin_key: PublicKey,
+}
+
+/// Database schema for the voting service.
+///
+/// Note that the schema is crate-private, but it has a public part.
+#[derive(Debug, FromAccess)]
+pub(crate) struct SchemaImpl<T: Access> {
+    /// Public part of the schema.
+    #[from_access(flatten)]
+    pub public: Schema<T>,
+    /// Votes cast in specific polls. Each poll has its own map of voter keys
+    /// to the chosen options, which is stored in the group under the poll identifier.
+    pub votes: Group<T, u64, RawProofMapIndex<T::Base, PublicKey, u32>>,
+    /// Identifiers of the polls which are not closed yet.
+    pub open_polls: KeySetIndex<T::Base, u64>,
+}
+
+/// Public part of the voting schema.
+#[derive(Debug, FromAccess, RequireArtifact)]
+pub struct Schema<T: Access> {
+    /// List of all polls. The identifier of a poll is its index in this list.
+    pub polls: ProofListIndex<T::Base, Poll>,
+    /// Service configuration.
+    pub config: ProofEntry<T::Base, Config>,
+}
+
+impl<T: Access> SchemaImpl<T> {
+    pub fn new(access: T) -> Self {
+        Self::from_root(access).unwrap()
+    }
+}
+
+impl<T> SchemaImpl<T>
+where
+    T: Access,
+    T::Base: RawAccessMut,
+{
+    /// Adds a new poll and returns its identifier.
+    pub(crate) fn create_poll(&mut self, poll: Poll) -> u64 {
+        let poll_id = self.public.polls.len();
+        self.public.polls.push(poll);
+        self.open_polls.insert(poll_id);
+        poll_id
+    }
+
+    /// Records the vote of the voter and updates the poll results.
+    pub(crate) fn vote(&mut self, poll_id: u64, mut poll: Poll, voter: PublicKey, option: u32) {
+        self.votes.get(&poll_id).put(&voter, option);
+        poll.results[option as usize] += 1;
+        self.public.polls.set(poll_id, poll);
+    }
+
+    /// Closes all open polls with the deadline not exceeding the given height.
+    pub(crate) fn close_expired_polls(&mut self, height: u64) {
+        let expired: Vec<_> = self
+            .open_polls
+            .iter()
+            .filter(|&poll_id| {
+                let poll = self.public.polls.get(poll_id).unwrap();
+                poll.deadline_height <= height
+            })
+            .collect();
+
+        for poll_id in expired {
+            let mut poll = self.public.polls.get(poll_id).unwrap();
+            poll.closed = true;
+            self.public.polls.set(poll_id, poll);
+            self.open_polls.remove(&poll_id);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voting transactions.
+
+use exonum::{
+    crypto::PublicKey,
+    runtime::{CommonError, ExecutionError},
+};
+use exonum_derive::{exonum_interface, interface_method, BinaryValue, ExecutionFail, ObjectHash};
+use exonum_proto::ProtobufConvert;
+use exonum_rust_runtime::CallContext;
+
+use crate::{
+    proto,
+    schema::{Poll, SchemaImpl},
+    VotingService,
+};
+
+/// Error codes emitted by voting transactions during execution.
+#[derive(Debug, ExecutionFail)]
+pub enum Error {
+    /// Transaction author is not the administrator of the service.
+    ///
+    /// Can be emitted by `CreatePoll`.
+    NotAdmin = 0,
+    /// Poll has no options or voters, or its deadline has already passed.
+    ///
+    /// Can be emitted by `CreatePoll`.
+    InvalidPoll = 1,
+    /// Poll doesn't exist.
+    ///
+    /// Can be emitted by `Vote`.
+    PollNotFound = 2,
+    /// Poll is closed and no longer accepts votes.
+    ///
+    /// Can be emitted by `Vote`.
+    PollClosed = 3,
+    /// Transaction author is not allowed to vote in the poll.
+    ///
+    /// Can be emitted by `Vote`.
+    NotVoter = 4,
+    /// Transaction author has already voted in the poll.
+    ///
+    /// Can be emitted by `Vote`.
+    AlreadyVoted = 5,
+    /// Chosen option doesn't exist.
+    ///
+    /// Can be emitted by `Vote`.
+    InvalidOption = 6,
+}
+
+/// Create a new poll. Can only be performed by the administrator.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::CreatePoll", serde_pb_convert)]
+pub struct CreatePoll {
+    /// Title of the poll.
+    pub title: String,
+    /// Options to vote for.
+    pub options: Vec<String>,
+    /// Keys allowed to vote in the poll.
+    pub voters: Vec<PublicKey>,
+    /// Height starting from which votes are no longer accepted.
+    pub deadline_height: u64,
+}
+
+/// Cast a vote in the poll. Each of the poll voters may vote only once.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Vote", serde_pb_convert)]
+pub struct Vote {
+    /// Identifier of the poll.
+    pub poll_id: u64,
+    /// Index of the chosen option.
+    pub option: u32,
+}
+
+/// Voting service transactions.
+#[exonum_interface]
+pub trait VotingInterface<Ctx> {
+    /// Output returned by the interface methods.
+    type Output;
+
+    /// Creates a new poll.
+    #[interface_method(id = 0)]
+    fn create_poll(&self, ctx: Ctx, arg: CreatePoll) -> Self::Output;
+    /// Casts a vote in the poll.
+    #[interface_method(id = 1)]
+    fn vote(&self, ctx: Ctx, arg: Vote) -> Self::Output;
+}
+
+impl VotingInterface<CallContext<'_>> for VotingService {
+    type Output = Result<(), ExecutionError>;
+
+    fn create_poll(&self, context: CallContext<'_>, arg: CreatePoll) -> Self::Output {
+        let (_, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let height = context.data().for_core().next_height().0;
+        let mut schema = SchemaImpl::new(context.service_data());
+        let config = schema.public.config.get().expect("No service config");
+        if author != config.admin_key {
+            return Err(Error::NotAdmin.into());
+        }
+        if arg.options.is_empty() || arg.voters.is_empty() || arg.deadline_height <= height {
+            return Err(Error::InvalidPoll.into());
+        }
+
+        let poll = Poll {
+            results: vec![0; arg.options.len()],
+            title: arg.title,
+            options: arg.options,
+            voters: arg.voters,
+            deadline_height: arg.deadline_height,
+            closed: false,
+        };
+        schema.create_poll(poll);
+        Ok(())
+    }
+
+    fn vote(&self, context: CallContext<'_>, arg: Vote) -> Self::Output {
+        let (_, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        let mut schema = SchemaImpl::new(context.service_data());
+        let poll = schema
+            .public
+            .polls
+            .get(arg.poll_id)
+            .ok_or(Error::PollNotFound)?;
+        if poll.closed {
+            return Err(Error::PollClosed.into());
+        }
+        if !poll.is_voter(&author) {
+            return Err(Error::NotVoter.into());
+        }
+        if schema.votes.get(&arg.poll_id).contains(&author) {
+            return Err(Error::AlreadyVoted.into());
+        }
+        if arg.option as usize >= poll.options.len() {
+            return Err(Error::InvalidOption.into());
+        }
+
+        schema.vote(arg.poll_id, poll, author, arg.option);
+        Ok(())
+    }
+}
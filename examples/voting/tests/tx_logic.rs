@@ -0,0 +1,181 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the voting service.
+
+use exonum::{
+    crypto::{self, PublicKey, SecretKey},
+    helpers::Height,
+    messages::{AnyTx, Verified},
+    runtime::{ErrorMatch, SnapshotExt},
+};
+use exonum_rust_runtime::ServiceFactory;
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+use pretty_assertions::assert_eq;
+
+use exonum_voting::{
+    api::{PollInfo, PollQuery},
+    transactions::{CreatePoll, Vote},
+    Config, Error, Poll, Schema, VotingInterface, VotingService,
+};
+
+const SERVICE_ID: u32 = 100;
+const SERVICE_NAME: &str = "voting";
+const DEADLINE_HEIGHT: u64 = 10;
+
+type KeyPair = (PublicKey, SecretKey);
+
+/// Creates a testkit with the voting service administered by the returned keypair.
+fn create_testkit() -> (TestKit, KeyPair) {
+    let admin = crypto::gen_keypair();
+    let artifact = VotingService.artifact_id();
+    let instance = artifact
+        .clone()
+        .into_default_instance(SERVICE_ID, SERVICE_NAME)
+        .with_constructor(Config { admin_key: admin.0 });
+    let testkit = TestKitBuilder::validator()
+        .with_rust_service(VotingService)
+        .with_artifact(artifact)
+        .with_instance(instance)
+        .create();
+    (testkit, admin)
+}
+
+fn create_poll(admin: &KeyPair, voters: &[KeyPair]) -> Verified<AnyTx> {
+    admin.create_poll(
+        SERVICE_ID,
+        CreatePoll {
+            title: "Lunch".to_owned(),
+            options: vec!["Pizza".to_owned(), "Sushi".to_owned()],
+            voters: voters.iter().map(|keypair| keypair.0).collect(),
+            deadline_height: DEADLINE_HEIGHT,
+        },
+    )
+}
+
+fn vote(keypair: &KeyPair, poll_id: u64, option: u32) -> Verified<AnyTx> {
+    keypair.vote(SERVICE_ID, Vote { poll_id, option })
+}
+
+fn get_poll(testkit: &TestKit, poll_id: u64) -> Poll {
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    schema.polls.get(poll_id).expect("No poll persisted")
+}
+
+fn assert_error(testkit: &mut TestKit, tx: Verified<AnyTx>, error: Error) {
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&error).for_service(SERVICE_ID);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+}
+
+#[test]
+fn test_poll_lifecycle() {
+    let (mut testkit, admin) = create_testkit();
+    let voters = vec![
+        crypto::gen_keypair(),
+        crypto::gen_keypair(),
+        crypto::gen_keypair(),
+    ];
+    // Poll is created at height 1 and accepts votes until the deadline height.
+    let block = testkit.create_block_with_transaction(create_poll(&admin, &voters));
+    block[0].status().unwrap();
+
+    let block = testkit.create_block_with_transaction(vote(&voters[0], 0, 1));
+    block[0].status().unwrap();
+    let block = testkit.create_block_with_transaction(vote(&voters[1], 0, 1));
+    block[0].status().unwrap();
+    testkit.create_blocks_until(Height(DEADLINE_HEIGHT - 1));
+    let poll = get_poll(&testkit, 0);
+    assert_eq!(poll.results, vec![0, 2]);
+    assert!(!poll.closed);
+
+    // The poll is closed before the transactions of the block at the deadline height.
+    assert_error(&mut testkit, vote(&voters[2], 0, 0), Error::PollClosed);
+    let poll = get_poll(&testkit, 0);
+    assert_eq!(poll.results, vec![0, 2]);
+    assert!(poll.closed);
+
+    // Check the final results via HTTP API.
+    let info: PollInfo = testkit
+        .api()
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&PollQuery { id: 0 })
+        .get("v1/polls/info")
+        .unwrap();
+
+    let state_hash = info.block_proof.block.state_hash;
+    let to_table = info
+        .poll_proof
+        .to_table
+        .check_against_hash(state_hash)
+        .unwrap();
+    let (_, &table_hash) = to_table.entries().next().unwrap();
+    let to_poll = info
+        .poll_proof
+        .to_poll
+        .check_against_hash(table_hash)
+        .unwrap();
+    assert_eq!(to_poll.entries(), &[(0, poll)][..]);
+}
+
+#[test]
+fn test_poll_creation_restrictions() {
+    let (mut testkit, admin) = create_testkit();
+    let voters = vec![crypto::gen_keypair()];
+
+    // Only the administrator can create polls.
+    let tx = create_poll(&voters[0], &voters);
+    assert_error(&mut testkit, tx, Error::NotAdmin);
+
+    // Polls must have options, voters and a deadline in the future.
+    let tx = admin.create_poll(
+        SERVICE_ID,
+        CreatePoll {
+            title: "Nothing to choose from".to_owned(),
+            options: vec![],
+            voters: vec![voters[0].0],
+            deadline_height: DEADLINE_HEIGHT,
+        },
+    );
+    assert_error(&mut testkit, tx, Error::InvalidPoll);
+    let tx = create_poll(&admin, &[]);
+    assert_error(&mut testkit, tx, Error::InvalidPoll);
+
+    // The next block has the deadline height.
+    testkit.create_blocks_until(Height(DEADLINE_HEIGHT - 1));
+    let tx = create_poll(&admin, &voters);
+    assert_error(&mut testkit, tx, Error::InvalidPoll);
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(SERVICE_ID).unwrap();
+    assert!(schema.polls.is_empty());
+}
+
+#[test]
+fn test_vote_restrictions() {
+    let (mut testkit, admin) = create_testkit();
+    let voters = vec![crypto::gen_keypair(), crypto::gen_keypair()];
+    testkit.create_block_with_transaction(create_poll(&admin, &voters));
+
+    assert_error(&mut testkit, vote(&voters[0], 1, 0), Error::PollNotFound);
+    assert_error(&mut testkit, vote(&voters[0], 0, 2), Error::InvalidOption);
+    let outsider = crypto::gen_keypair();
+    assert_error(&mut testkit, vote(&outsider, 0, 0), Error::NotVoter);
+
+    // Each voter may vote only once.
+    testkit.create_block_with_transaction(vote(&voters[1], 0, 0));
+    assert_error(&mut testkit, vote(&voters[1], 0, 1), Error::AlreadyVoted);
+    assert_eq!(get_poll(&testkit, 0).results, vec![1, 0]);
+}
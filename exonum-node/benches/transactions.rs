@@ -99,6 +99,7 @@ fn gen_messages(count: u64, tx_size: usize) -> Vec<Vec<u8>> {
                         method_id: 0,
                     },
                     arguments: vec![0; tx_size],
+                    valid_until: None,
                 },
                 p,
                 &s,
@@ -168,6 +169,7 @@ impl MessageVerifier {
         let internal_part = InternalPart {
             internal_tx: channel.internal_events.0,
             internal_requests_rx: channel.internal_requests.1,
+            tx_verification_capacity: usize::max_value(),
         };
 
         let network_thread = thread::spawn(move || {
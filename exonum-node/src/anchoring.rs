@@ -0,0 +1,125 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extension point for anchoring the blockchain to external chains.
+
+use exonum::{
+    blockchain::{ApiSender, BlockProof, Schema, SendError},
+    crypto::Hash,
+    helpers::Height,
+};
+use futures::Future;
+use log::{info, warn};
+use serde_derive::{Deserialize, Serialize};
+
+use std::fmt;
+
+use crate::{ExternalMessage, NodeHandler};
+
+/// Confirmation that a block has been anchored to an external chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnchoringConfirmation {
+    /// Height of the anchored block.
+    pub height: Height,
+    /// Hash of the anchored block.
+    pub block_hash: Hash,
+    /// Identifier of the external chain, e.g., `bitcoin`.
+    pub chain: String,
+    /// Reference to the anchoring record in the external chain, e.g., a transaction ID.
+    pub reference: String,
+}
+
+/// Hook notified about blocks finalized by the node.
+///
+/// The hook allows to implement anchoring of the blockchain to external chains without
+/// modifying the node: the hook receives proofs of the finalized blocks, and reports
+/// the results of anchoring back to the node with the provided `AnchoringSender`.
+/// The latest confirmations for each chain are available via
+/// `SharedNodeState::anchoring_confirmations`.
+pub trait AnchoringHook: Send {
+    /// Notifies the hook that the node has committed a block. The proof contains
+    /// the block header and the precommits of validators authorizing the block.
+    ///
+    /// The method is invoked on the consensus thread, so anchoring itself should be performed
+    /// asynchronously.
+    fn block_finalized(&self, block_proof: &BlockProof, sender: AnchoringSender);
+}
+
+impl fmt::Debug for dyn AnchoringHook {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_tuple("AnchoringHook").finish()
+    }
+}
+
+/// Sender of anchoring confirmations to the node.
+#[derive(Debug, Clone)]
+pub struct AnchoringSender(ApiSender<ExternalMessage>);
+
+impl AnchoringSender {
+    pub(crate) fn new(inner: ApiSender<ExternalMessage>) -> Self {
+        AnchoringSender(inner)
+    }
+
+    /// Reports to the node that a block has been anchored. Confirmations with the block hash
+    /// not matching the block at the specified height are ignored by the node.
+    pub fn confirm(
+        &self,
+        confirmation: AnchoringConfirmation,
+    ) -> impl Future<Item = (), Error = SendError> {
+        self.0
+            .send_message(ExternalMessage::ConfirmAnchoring(confirmation))
+    }
+}
+
+impl NodeHandler {
+    /// Notifies anchoring hooks about the latest committed block.
+    pub(crate) fn notify_anchoring_hooks(&self) {
+        if self.anchoring_hooks.is_empty() {
+            return;
+        }
+
+        let snapshot = self.blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let block_proof = schema
+            .block_and_precommits(schema.height())
+            .expect("Latest committed block is not found");
+        let sender =
+            AnchoringSender::new(ApiSender::new(self.channel.api_requests.get_ref().clone()));
+        for hook in &self.anchoring_hooks {
+            hook.block_finalized(&block_proof, sender.clone());
+        }
+    }
+
+    /// Records a confirmation of anchoring if it refers to a block known to the node.
+    pub(crate) fn handle_anchoring_confirmation(&mut self, confirmation: AnchoringConfirmation) {
+        let snapshot = self.blockchain.snapshot();
+        let block_hash = Schema::new(&snapshot).block_hash_by_height(confirmation.height);
+        if block_hash != Some(confirmation.block_hash) {
+            warn!(
+                "Ignoring anchoring confirmation for unknown block {} at height {}",
+                confirmation.block_hash, confirmation.height
+            );
+            return;
+        }
+
+        info!(
+            "Block {} at height {} is anchored to {}: {}",
+            confirmation.block_hash,
+            confirmation.height,
+            confirmation.chain,
+            confirmation.reference
+        );
+        self.api_state.add_anchoring_confirmation(confirmation);
+    }
+}
@@ -12,21 +12,91 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use exonum::{crypto::PublicKey, helpers::Height, messages::Verified};
-use log::{error, info, trace};
+use exonum::{
+    crypto::PublicKey,
+    helpers::{Height, Milliseconds},
+    merkledb::{BinaryValue, ObjectHash},
+    messages::{SignedMessage, Verified},
+};
+use log::{error, info, trace, warn};
 use rand::Rng;
+use tracing::info_span;
+
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime},
+};
 
 use crate::{
-    events::{error::LogError, network::ConnectedPeerAddr},
-    messages::{Connect, Message, PeersRequest, Responses, Service, Status},
+    events::{error::LogError, network::ConnectedPeerAddr, InternalRequest, NetworkRequest},
+    messages::{
+        Connect, ConsensusMessage, ExonumMessage, Message, PeersRequest, Requests, Responses,
+        Service, Status,
+    },
     schema::NodeSchema,
-    state::RequestData,
+    state::{RequestData, PEER_BAN_DURATION, PEER_VIOLATIONS_THRESHOLD},
     NodeHandler, NodeRole,
 };
 
 impl NodeHandler {
+    /// Schedules verification of a message received from a peer.
+    ///
+    /// A node in the bootstrap mode verifies requests of syncing peers right away, so that
+    /// serving blocks and transactions does not wait behind other messages in the verification
+    /// queue.
+    pub(crate) fn handle_raw_message(&mut self, raw: Vec<u8>) {
+        if self.bootstrap_mode {
+            if let Ok(msg) = SignedMessage::from_bytes(raw.as_slice().into()) {
+                if Self::is_sync_request(&msg) {
+                    match Message::from_signed(msg) {
+                        Ok(msg) => self.handle_message(msg),
+                        Err(e) => trace!("Received request with invalid signature: {}", e),
+                    }
+                    return;
+                }
+            }
+        }
+        self.execute_later(InternalRequest::VerifyMessage(raw));
+    }
+
+    /// Checks whether the message is a request of a syncing peer.
+    fn is_sync_request(msg: &SignedMessage) -> bool {
+        match ExonumMessage::try_from(msg) {
+            Ok(ExonumMessage::BlockRequest(_)) | Ok(ExonumMessage::TransactionsRequest(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Handles a message received from a peer once its signature is verified.
+    pub(crate) fn handle_verified_message(&mut self, msg: Message) {
+        // Clock skew is estimated here rather than in `handle_consensus`, so that the queued
+        // and cached precommits handled again later do not affect the estimation.
+        if let Message::Consensus(ConsensusMessage::Precommit(ref precommit)) = msg {
+            self.check_clock_skew(precommit);
+        }
+        self.handle_message(msg);
+    }
+
     /// Redirects message to the corresponding `handle_...` function.
     pub(crate) fn handle_message(&mut self, msg: Message) {
+        if self.bootstrap_mode && !self.is_served_in_bootstrap_mode(&msg) {
+            trace!("Ignoring message {:?} in the bootstrap mode", msg);
+            return;
+        }
+
+        // Transactions and consensus messages can be relayed by other peers,
+        // so only the messages which are sent by their authors directly are filtered.
+        let is_relayed = match msg {
+            Message::Consensus(_) | Message::Service(Service::AnyTx(_)) => true,
+            _ => false,
+        };
+        if !is_relayed && self.is_peer_banned(&msg.as_raw().author) {
+            trace!("Ignoring message from banned peer {}", msg.as_raw().author);
+            return;
+        }
+
+        let span = info_span!("handle_message", author = %msg.as_raw().author);
+        let _enter = span.enter();
         match msg {
             Message::Consensus(msg) => self.handle_consensus(msg),
             Message::Requests(ref msg) => self.handle_request(msg),
@@ -44,6 +114,27 @@ impl NodeHandler {
         }
     }
 
+    /// Checks whether the message is processed by a node in the bootstrap mode. Such a node
+    /// only synchronizes with other nodes and serves blocks to them. Transactions are accepted
+    /// only if they are awaited by the block being synchronized.
+    fn is_served_in_bootstrap_mode(&self, msg: &Message) -> bool {
+        match msg {
+            Message::Service(Service::Connect(_))
+            | Message::Service(Service::Status(_))
+            | Message::Requests(Requests::BlockRequest(_))
+            | Message::Requests(Requests::TransactionsRequest(_))
+            | Message::Requests(Requests::PeersRequest(_))
+            | Message::Responses(Responses::BlockResponse(_))
+            | Message::Responses(Responses::TransactionsResponse(_)) => true,
+            Message::Service(Service::AnyTx(tx)) => {
+                self.state.incomplete_block().map_or(false, |block| {
+                    block.unknown_txs().contains(&tx.object_hash())
+                })
+            }
+            _ => false,
+        }
+    }
+
     /// Handles the `Connected` event. Node's `Connect` message is sent as response
     /// if received `Connect` message is correct.
     pub(crate) fn handle_connected(
@@ -52,6 +143,14 @@ impl NodeHandler {
         connect: Verified<Connect>,
     ) {
         info!("Received Connect message from peer: {:?}", address);
+        if self.is_peer_banned(&connect.author()) {
+            info!("Disconnecting from banned peer {}", connect.author());
+            self.channel
+                .network_requests
+                .send(NetworkRequest::DisconnectWithPeer(connect.author()))
+                .log_error();
+            return;
+        }
         // TODO: use `ConnectInfo` instead of connect-messages. (ECR-1452)
         self.state.add_connection(connect.author(), address.clone());
         self.handle_connect(connect);
@@ -71,6 +170,28 @@ impl NodeHandler {
         self.remove_peer_with_addr(key);
     }
 
+    /// Handles the `PeerLatency` event. Node warns if the consensus timeouts are inconsistent
+    /// with the round-trip time to a validator.
+    pub(crate) fn handle_peer_latency(&self, key: PublicKey, round_trip_time: Duration) {
+        trace!(
+            "Estimated round-trip time to {}: {:?}",
+            key,
+            round_trip_time
+        );
+        if !self.state.peer_is_validator(&key) {
+            return;
+        }
+
+        let round_trip_time = round_trip_time.as_millis() as Milliseconds;
+        let config = self.state.consensus_config();
+        for warning in config.latency_warnings(round_trip_time) {
+            warn!(
+                "Consensus timeouts do not match the latency of validator {}: {}",
+                key, warning
+            );
+        }
+    }
+
     /// Removes peer from the state and from the cache. Node will try to connect to that address
     /// again if it was in the validators list.
     fn remove_peer_with_addr(&mut self, key: PublicKey) {
@@ -89,6 +210,72 @@ impl NodeHandler {
         }
     }
 
+    /// Checks if a peer is currently banned.
+    pub(crate) fn is_peer_banned(&self, key: &PublicKey) -> bool {
+        self.state
+            .is_peer_banned(key, self.system_state.current_time())
+    }
+
+    /// Bans a peer until the specified time, or permanently if the time is not specified.
+    /// The node disconnects from the peer and ignores its messages while the ban is active.
+    pub(crate) fn ban_peer(&mut self, key: PublicKey, expires_at: Option<SystemTime>) {
+        info!("Banning peer {}", key);
+        self.state.ban_peer(key, expires_at);
+        self.remove_peer_with_addr(key);
+        self.channel
+            .network_requests
+            .send(NetworkRequest::DisconnectWithPeer(key))
+            .log_error();
+    }
+
+    /// Drops the connection with a peer and removes it from the connect list. If
+    /// `keep_in_connect_list` is set, the peer stays in the connect list, so the node
+    /// reconnects to it if the peer is a validator.
+    pub(crate) fn remove_peer(&mut self, key: PublicKey, keep_in_connect_list: bool) {
+        if keep_in_connect_list {
+            info!("Disconnecting from peer {}", key);
+        } else {
+            if !self.state.remove_peer_from_connect_list(&key) {
+                info!("Peer {} is not in the connect list", key);
+                return;
+            }
+            info!("Removing peer {} from the connect list", key);
+            self.remove_peer_with_addr(key);
+        }
+
+        self.channel
+            .network_requests
+            .send(NetworkRequest::DisconnectWithPeer(key))
+            .log_error();
+        if !keep_in_connect_list {
+            self.store_connect_list();
+        }
+    }
+
+    /// Lifts a ban from a peer, restoring the connection if the peer is a validator.
+    pub(crate) fn unban_peer(&mut self, key: PublicKey) {
+        if !self.state.unban_peer(&key) {
+            info!("Peer {} is not banned", key);
+            return;
+        }
+
+        info!("Lifting ban from peer {}", key);
+        if self.state.peer_is_validator(&key) && self.state.peer_in_connect_list(&key) {
+            self.connect(key);
+        }
+    }
+
+    /// Records a protocol violation by a peer. The peer is banned for `PEER_BAN_DURATION`
+    /// if it violates the protocol too often.
+    pub(crate) fn report_peer_violation(&mut self, key: PublicKey, reason: &str) {
+        warn!("Protocol violation by peer {}: {}", key, reason);
+        let now = self.system_state.current_time();
+        if self.state.add_peer_violation(key, now) >= PEER_VIOLATIONS_THRESHOLD {
+            let expires_at = now + Duration::from_millis(PEER_BAN_DURATION);
+            self.ban_peer(key, Some(expires_at));
+        }
+    }
+
     /// Handles the `Connect` message and connects to a peer as result.
     pub(crate) fn handle_connect(&mut self, message: Verified<Connect>) {
         // TODO Add spam protection (ECR-170)
@@ -113,6 +300,11 @@ impl NodeHandler {
             return;
         }
 
+        if self.is_peer_banned(&public_key) {
+            trace!("Received Connect message from banned peer {}", public_key);
+            return;
+        }
+
         // Check if we have another connect message from peer with the given public_key.
         let mut need_connect = true;
         if let Some(saved_message) = self.state.peers().get(&public_key) {
@@ -124,21 +316,25 @@ impl NodeHandler {
             } else if saved_message.payload().host == message.payload().host {
                 need_connect = false;
             } else {
-                error!("Received weird Connect message from {}", address);
-                return;
-            }
-            if saved_message.payload().host != message.payload().host {
-                info!(
-                    "Updating connect list for peer: {} with new addr: {}",
+                self.report_peer_violation(
                     public_key,
-                    message.payload().host
+                    &format!("weird Connect message from {}", address),
                 );
-                self.state
-                    .connect_list()
-                    .update_peer(&public_key, message.payload().host.to_string())
+                return;
             }
         }
 
+        // The address advertised by the peer takes precedence over the one from the connect list,
+        // since the latter may point to a private address of a node behind NAT.
+        let mut connect_list = self.state.connect_list();
+        if connect_list.find_address_by_key(&public_key) != Some(address.clone()) {
+            info!(
+                "Updating connect list for peer: {} with new addr: {}",
+                public_key, address
+            );
+            connect_list.update_peer(&public_key, address.clone());
+        }
+
         self.state.add_peer(public_key, message.clone());
         info!(
             "Received Connect message from {}. Need to connect: {}",
@@ -192,7 +388,8 @@ impl NodeHandler {
             self.request(RequestData::Block(height), peer);
         }
 
-        if self.uncommitted_txs_count() == 0 && msg.payload().pool_size > 0 {
+        if !self.bootstrap_mode && self.uncommitted_txs_count() == 0 && msg.payload().pool_size > 0
+        {
             self.request(RequestData::PoolTransactions, peer);
         }
     }
@@ -246,15 +443,73 @@ impl NodeHandler {
         }
         self.add_peer_exchange_timeout();
     }
+
+    /// Handles `NodeTimeout::DnsSeeds`. Node asks the network to look for the peers
+    /// from the connect list it is not connected to at the addresses resolved from DNS seeds.
+    pub(crate) fn handle_dns_seeds_timeout(&mut self) {
+        let our_key = self.state.our_connect_message().author();
+        let peers: Vec<PublicKey> = self
+            .state
+            .connect_list()
+            .peers()
+            .into_iter()
+            .map(|info| info.public_key)
+            .filter(|key| *key != our_key && !self.state.peers().contains_key(key))
+            .collect();
+
+        if !peers.is_empty() {
+            trace!("Discover peers {:?} using DNS seeds", peers);
+            let request = NetworkRequest::DiscoverPeers(peers);
+            self.channel.network_requests.send(request).log_error();
+        }
+        self.add_dns_seeds_timeout();
+    }
+
     /// Handles `NodeTimeout::UpdateApiState`.
     /// Node update internal `ApiState` and `NodeRole`.
     pub(crate) fn handle_update_api_state_timeout(&mut self) {
-        self.api_state.update_node_state(&self.state);
-        // FIXME Add special event to update state [ECR-3222]
-        self.node_role = NodeRole::new(self.state.validator_id());
+        self.api_state
+            .update_node_state(&self.state, self.system_state.current_time());
+        self.update_node_role();
         self.add_update_api_state_timeout();
     }
 
+    /// Updates `NodeRole` according to the actual consensus configuration.
+    ///
+    /// If an auditor node is promoted to a validator, it connects to all validators
+    /// it has no connections with, since it now has to exchange consensus messages with them.
+    pub(crate) fn update_node_role(&mut self) {
+        let node_role = NodeRole::new(self.state.validator_id());
+        match (self.node_role, node_role) {
+            (NodeRole::Auditor, NodeRole::Validator(id)) => {
+                info!("The node is promoted to validator with id {}", id);
+                let our_key = self.state.our_connect_message().author();
+                let validators: Vec<_> = self
+                    .state
+                    .validators()
+                    .iter()
+                    .map(|keys| keys.consensus_key)
+                    .filter(|key| {
+                        *key != our_key
+                            && !self.state.peers().contains_key(key)
+                            && self.state.peer_in_connect_list(key)
+                    })
+                    .collect();
+                for key in validators {
+                    info!("Trying to connect with validator {}", key);
+                    self.connect(key);
+                }
+            }
+            (NodeRole::Validator(_), NodeRole::Auditor) => {
+                info!("The node is no longer a validator");
+            }
+            _ => {}
+        }
+
+        self.node_role = node_role;
+        self.api_state.set_node_role(node_role);
+    }
+
     /// Broadcasts the `Status` message to all peers.
     pub(crate) fn broadcast_status(&mut self) {
         let status = Status {
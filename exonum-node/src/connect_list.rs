@@ -31,6 +31,15 @@ pub struct ConnectInfo {
     pub address: String,
     /// Peer public key.
     pub public_key: PublicKey,
+    /// Public key used by the peer to authenticate in encrypted connections.
+    /// If not specified, the peer is expected to use its consensus key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_key: Option<PublicKey>,
+    /// Consensus key of the peer if it differs from the public key identifying the peer.
+    /// This is the case if the peer is a validator signing consensus messages with
+    /// an external signer, so that the validator secret key is not kept by the peer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consensus_key: Option<PublicKey>,
 }
 
 impl fmt::Display for ConnectInfo {
@@ -44,18 +53,25 @@ impl fmt::Display for ConnectInfo {
 pub(crate) struct ConnectList {
     /// Peers to which we can connect.
     pub peers: BTreeMap<PublicKey, String>,
+    /// Network keys of peers which differ from their consensus keys.
+    pub network_keys: BTreeMap<PublicKey, PublicKey>,
+    /// Consensus keys of peers which differ from the keys identifying the peers.
+    pub consensus_keys: BTreeMap<PublicKey, PublicKey>,
+    /// Hostnames resolved to discover addresses of the peers.
+    pub dns_seeds: Vec<String>,
 }
 
 impl ConnectList {
     /// Creates `ConnectList` from config.
     pub fn from_config(config: ConnectListConfig) -> Self {
-        let peers: BTreeMap<_, _> = config
-            .peers
-            .into_iter()
-            .map(|peer| (peer.public_key, peer.address))
-            .collect();
-
-        ConnectList { peers }
+        let mut connect_list = ConnectList {
+            dns_seeds: config.dns_seeds,
+            ..ConnectList::default()
+        };
+        for peer in config.peers {
+            connect_list.add(peer);
+        }
+        connect_list
     }
 
     /// Creates `ConnectList` from the previously saved list of peers.
@@ -66,6 +82,9 @@ impl ConnectList {
                 .into_iter()
                 .map(|(public_key, connect)| (public_key, connect.payload().host.clone()))
                 .collect(),
+            network_keys: BTreeMap::new(),
+            consensus_keys: BTreeMap::new(),
+            dns_seeds: Vec::new(),
         }
     }
 
@@ -79,11 +98,43 @@ impl ConnectList {
         self.peers.get(key).map(String::as_str)
     }
 
+    /// Gets the key used by a peer with the specified public key to authenticate
+    /// in encrypted connections.
+    pub(super) fn network_key(&self, key: &PublicKey) -> PublicKey {
+        self.network_keys.get(key).copied().unwrap_or(*key)
+    }
+
+    /// Gets the key identifying a peer which signs consensus messages with
+    /// the specified consensus key, if the keys differ.
+    pub(super) fn identity_by_consensus_key(&self, key: &PublicKey) -> Option<PublicKey> {
+        self.consensus_keys
+            .iter()
+            .find(|(_, consensus_key)| *consensus_key == key)
+            .map(|(identity, _)| *identity)
+    }
+
     /// Adds peer to the `ConnectList`.
     pub(crate) fn add(&mut self, peer: ConnectInfo) {
+        if let Some(network_key) = peer.network_key {
+            self.network_keys.insert(peer.public_key, network_key);
+        } else {
+            self.network_keys.remove(&peer.public_key);
+        }
+        if let Some(consensus_key) = peer.consensus_key {
+            self.consensus_keys.insert(peer.public_key, consensus_key);
+        } else {
+            self.consensus_keys.remove(&peer.public_key);
+        }
         self.peers.insert(peer.public_key, peer.address);
     }
 
+    /// Removes peer from the `ConnectList`. Returns `false` if the peer is not in the list.
+    pub(crate) fn remove(&mut self, public_key: &PublicKey) -> bool {
+        self.network_keys.remove(public_key);
+        self.consensus_keys.remove(public_key);
+        self.peers.remove(public_key).is_some()
+    }
+
     /// Updates peer address.
     pub(super) fn update_peer(&mut self, public_key: &PublicKey, address: String) {
         self.peers.insert(*public_key, address);
@@ -95,6 +146,11 @@ impl ConnectList {
 pub struct ConnectListConfig {
     /// Peers to which the node knows how to connect.
     pub peers: Vec<ConnectInfo>,
+    /// Hostnames with ports (e.g., `seed.example.com:6333`) which are periodically resolved
+    /// to discover the current addresses of the peers from the list. A resolved address
+    /// is assigned to a peer only if the peer successfully authenticates at this address.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_seeds: Vec<String>,
 }
 
 impl ConnectListConfig {
@@ -107,16 +163,22 @@ impl ConnectListConfig {
             .map(|(address, keys)| ConnectInfo {
                 address: address.to_owned(),
                 public_key: keys.consensus_key,
+                network_key: None,
+                consensus_key: None,
             })
             .collect();
 
-        ConnectListConfig { peers }
+        ConnectListConfig {
+            peers,
+            dns_seeds: Vec::new(),
+        }
     }
 
     /// Creates a `ConnectListConfig` from `ConnectList`.
     pub(super) fn from_connect_list(connect_list: &SharedConnectList) -> Self {
         ConnectListConfig {
             peers: connect_list.peers(),
+            dns_seeds: connect_list.dns_seeds(),
         }
     }
 
@@ -174,11 +236,15 @@ mod test {
         connect_list.add(ConnectInfo {
             public_key: regular[0],
             address: address.clone(),
+            network_key: None,
+            consensus_key: None,
         });
         check_in_connect_list(&connect_list, &regular, &[0], &[1, 2, 3]);
         connect_list.add(ConnectInfo {
             public_key: regular[2],
             address: address.clone(),
+            network_key: None,
+            consensus_key: None,
         });
         check_in_connect_list(&connect_list, &regular, &[0, 2], &[1, 3]);
 
@@ -206,6 +272,8 @@ mod test {
             connect_list.add(ConnectInfo {
                 public_key: *peer,
                 address: address.clone(),
+                network_key: None,
+                consensus_key: None,
             })
         }
     }
@@ -240,10 +308,81 @@ mod test {
         connect_list.add(ConnectInfo {
             public_key,
             address: address.clone(),
+            network_key: None,
+            consensus_key: None,
         });
         assert!(connect_list
             .peers
             .values()
             .any(|peer_addr| *peer_addr == address));
     }
+
+    #[test]
+    fn test_network_keys() {
+        let (public_key, _) = gen_keypair();
+        let (network_key, _) = gen_keypair();
+        let address = "127.0.0.1:80".to_owned();
+
+        let mut connect_list = ConnectList::default();
+        connect_list.add(ConnectInfo {
+            public_key,
+            address: address.clone(),
+            network_key: None,
+            consensus_key: None,
+        });
+        assert_eq!(connect_list.network_key(&public_key), public_key);
+
+        connect_list.add(ConnectInfo {
+            public_key,
+            address: address.clone(),
+            network_key: Some(network_key),
+            consensus_key: None,
+        });
+        assert_eq!(connect_list.network_key(&public_key), network_key);
+        assert!(connect_list.is_peer_allowed(&public_key));
+        assert!(!connect_list.is_peer_allowed(&network_key));
+
+        connect_list.add(ConnectInfo {
+            public_key,
+            address,
+            network_key: None,
+            consensus_key: None,
+        });
+        assert_eq!(connect_list.network_key(&public_key), public_key);
+    }
+
+    #[test]
+    fn test_consensus_keys() {
+        let (public_key, _) = gen_keypair();
+        let (consensus_key, _) = gen_keypair();
+        let address = "127.0.0.1:80".to_owned();
+
+        let mut connect_list = ConnectList::default();
+        connect_list.add(ConnectInfo {
+            public_key,
+            address: address.clone(),
+            network_key: None,
+            consensus_key: Some(consensus_key),
+        });
+        assert_eq!(
+            connect_list.identity_by_consensus_key(&consensus_key),
+            Some(public_key)
+        );
+        assert_eq!(connect_list.identity_by_consensus_key(&public_key), None);
+        assert!(!connect_list.is_peer_allowed(&consensus_key));
+
+        connect_list.remove(&public_key);
+        assert_eq!(connect_list.identity_by_consensus_key(&consensus_key), None);
+    }
+
+    #[test]
+    fn test_dns_seeds_are_preserved() {
+        let config = ConnectListConfig {
+            peers: vec![],
+            dns_seeds: vec!["seed.example.com:6333".to_owned()],
+        };
+        let connect_list =
+            SharedConnectList::from_connect_list(ConnectList::from_config(config.clone()));
+        assert_eq!(ConnectListConfig::from_connect_list(&connect_list), config);
+    }
 }
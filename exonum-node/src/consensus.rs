@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
 use exonum::{
     blockchain::{contains_transaction, Blockchain, BlockchainMut, ProposerId, Schema},
     crypto::{Hash, PublicKey},
@@ -21,18 +22,20 @@ use exonum::{
 };
 use failure::{bail, format_err};
 use log::{error, info, trace, warn};
+use tracing::info_span;
 
-use std::{collections::HashSet, convert::TryFrom};
+use std::{collections::HashSet, convert::TryFrom, mem, sync::atomic::Ordering};
 
 use crate::{
     events::InternalRequest,
     messages::{
-        BlockRequest, BlockResponse, Consensus as ConsensusMessage, PoolTransactionsRequest,
-        Prevote, PrevotesRequest, Propose, ProposeRequest, TransactionsRequest,
-        TransactionsResponse,
+        BlockRequest, BlockResponse, Consensus as ConsensusMessage, Message,
+        PoolTransactionsRequest, Prevote, PrevotesRequest, Propose, ProposeRequest,
+        TransactionsRequest, TransactionsResponse,
     },
     schema::NodeSchema,
-    state::RequestData,
+    state::{RequestData, BLOCK_REQUEST_BATCH_SIZE},
+    tx_selection::TxPool,
     NodeHandler,
 };
 
@@ -40,12 +43,11 @@ use crate::{
 fn into_verified<T: TryFrom<SignedMessage>>(
     raw: &[Vec<u8>],
 ) -> Result<Vec<Verified<T>>, failure::Error> {
-    let mut items = Vec::with_capacity(raw.len());
-    for bytes in raw {
-        let verified = SignedMessage::from_bytes(bytes.into())?.into_verified()?;
-        items.push(verified);
-    }
-    Ok(items)
+    let messages = raw
+        .iter()
+        .map(|bytes| SignedMessage::from_bytes(bytes.into()))
+        .collect::<Result<Vec<_>, _>>()?;
+    SignedMessage::into_verified_batch(messages)
 }
 
 /// Helper trait to efficiently merge changes to the `BlockchainMut`.
@@ -137,6 +139,38 @@ impl NodeHandler {
         }
     }
 
+    /// Estimates the clock skew between the node and the author of the precommit
+    /// and warns if it exceeds the configured threshold.
+    pub(crate) fn check_clock_skew(&mut self, msg: &Verified<Precommit>) {
+        let validator = msg.payload().validator;
+        let author = msg.author();
+        if self.state.consensus_public_key_of(validator) != Some(author) {
+            return;
+        }
+        // Precommits from other heights may be relayed with a delay.
+        let height = msg.payload().height;
+        if height < self.state.height() || height > self.state.height().next() {
+            return;
+        }
+
+        let now: DateTime<Utc> = self.system_state.current_time().into();
+        let skew = now
+            .signed_duration_since(msg.payload().time())
+            .num_milliseconds();
+        let previous_skew = self.state.set_clock_skew(author, skew);
+
+        let max_skew = self.max_clock_skew as i64;
+        let is_excessive = |skew: i64| skew.abs() > max_skew;
+        // Only warn once the skew starts exceeding the threshold in order not to spam the log.
+        if is_excessive(skew) && !previous_skew.map_or(false, is_excessive) {
+            warn!(
+                "Clock of validator {} is skewed by {} ms relative to the local clock \
+                 (maximum allowed skew is {} ms)",
+                validator, skew, max_skew
+            );
+        }
+    }
+
     /// Handles the `Propose` message. For details see the message documentation.
     fn handle_propose(&mut self, from: PublicKey, msg: &Verified<Propose>) {
         debug_assert_eq!(
@@ -190,8 +224,11 @@ impl NodeHandler {
         }
     }
 
-    fn validate_block_response(&self, msg: &Verified<BlockResponse>) -> Result<(), failure::Error> {
-        if msg.payload().to != self.state.keys().consensus_pk() {
+    fn validate_block_response_sender(
+        &self,
+        msg: &Verified<BlockResponse>,
+    ) -> Result<(), failure::Error> {
+        if msg.payload().to != self.state.identity_keys().public_key() {
             bail!(
                 "Received block intended for another peer, to={}, from={}",
                 msg.payload().to().to_hex(),
@@ -205,11 +242,13 @@ impl NodeHandler {
                 msg.author().to_hex()
             );
         }
+        Ok(())
+    }
 
-        let block = msg.payload().block();
-        let block_hash = block.object_hash();
+    fn validate_block_response(&self, msg: &Verified<BlockResponse>) -> Result<(), failure::Error> {
+        self.validate_block_response_sender(msg)?;
 
-        // TODO: Add block with greater height to queue. (ECR-171)
+        let block = msg.payload().block();
         if self.state.height() != block.height {
             bail!("Received block has another height, msg={:?}", msg);
         }
@@ -228,6 +267,13 @@ impl NodeHandler {
         if self.state.incomplete_block().is_some() {
             bail!("Already there is an incomplete block, msg={:?}", msg);
         }
+        Ok(())
+    }
+
+    /// Validates the content of the block, which must be correct regardless of the node state.
+    fn validate_block_content(&self, msg: &Verified<BlockResponse>) -> Result<(), failure::Error> {
+        let block = msg.payload().block();
+        let block_hash = block.object_hash();
 
         if !msg.payload().verify_tx_hash() {
             bail!("Received block has invalid tx_hash, msg={:?}", msg);
@@ -243,7 +289,14 @@ impl NodeHandler {
         &mut self,
         msg: &Verified<BlockResponse>,
     ) -> Result<(), failure::Error> {
+        if msg.payload().block().height > self.state.height() {
+            return self.queue_block(msg);
+        }
         self.validate_block_response(&msg)?;
+        if let Err(err) = self.validate_block_content(&msg) {
+            self.report_peer_violation(msg.author(), &err.to_string());
+            return Err(err);
+        }
 
         let block = msg.payload().block();
         let block_hash = block.object_hash();
@@ -256,6 +309,10 @@ impl NodeHandler {
                 .has_unknown_txs();
 
             let known_nodes = self.remove_request(&RequestData::Block(block.height));
+            if !known_nodes.is_empty() {
+                // The block starts the requested batch.
+                self.request_following_blocks(block.height, msg.author());
+            }
 
             if has_unknown_txs {
                 trace!("REQUEST TRANSACTIONS");
@@ -275,6 +332,23 @@ impl NodeHandler {
         Ok(())
     }
 
+    /// Saves a block received ahead of the current height, so that it will be processed
+    /// once the node reaches its height.
+    fn queue_block(&mut self, msg: &Verified<BlockResponse>) -> Result<(), failure::Error> {
+        self.validate_block_response_sender(msg)?;
+
+        // Blocks further than two batches ahead are never requested.
+        let max_height = Height(self.state.height().0 + 2 * u64::from(BLOCK_REQUEST_BATCH_SIZE));
+        if msg.payload().block().height >= max_height {
+            bail!(
+                "Received block is too far ahead of the current height, msg={:?}",
+                msg
+            );
+        }
+        self.state.queue_block(msg.clone());
+        Ok(())
+    }
+
     /// Checks if propose is correct (doesn't contain invalid transactions), and then
     /// broadcasts a prevote for this propose.
     ///
@@ -623,6 +697,43 @@ impl NodeHandler {
         }
     }
 
+    /// Saves transactions received since the last save to the node schema in a single batch.
+    /// Saved transactions are recovered into the cache after the node restart.
+    fn save_tx_cache(&mut self) {
+        let unsaved_txs = mem::replace(self.state.unsaved_txs_mut(), Vec::new());
+        if unsaved_txs.is_empty() {
+            return;
+        }
+
+        let tx_cache = self.state.tx_cache();
+        self.blockchain.persist_changes(
+            |schema| {
+                let mut transactions_cache = schema.transactions_cache();
+                for hash in &unsaved_txs {
+                    if let Some(tx) = tx_cache.get(hash) {
+                        transactions_cache.put(hash, tx.clone());
+                    }
+                }
+            },
+            "Cannot save transactions to the cache",
+        );
+    }
+
+    /// Saves identities of validators with rotated consensus keys, so that requests
+    /// to these validators are addressed correctly after the node restart.
+    fn save_peer_identities(&mut self) {
+        let identities: Vec<_> = self
+            .state
+            .peer_identities()
+            .iter()
+            .map(|(consensus_key, identity)| (*consensus_key, *identity))
+            .collect();
+        self.blockchain.persist_changes(
+            |schema| schema.save_peer_identities(identities),
+            "Cannot save identities of peers",
+        );
+    }
+
     /// Commits block, so new height is achieved.
     fn commit<I: Iterator<Item = Verified<Precommit>>>(
         &mut self,
@@ -631,6 +742,14 @@ impl NodeHandler {
         round: Option<Round>,
     ) {
         trace!("COMMIT {:?}", block_hash);
+        let span = info_span!("commit", height = self.state.height().0, block_hash = %block_hash);
+        let _enter = span.enter();
+
+        let precommits: Vec<_> = precommits.collect();
+        let precommit_validators = precommits
+            .iter()
+            .map(|precommit| precommit.payload().validator())
+            .collect();
 
         // Merge changes into storage
         let (committed_txs, proposer) = {
@@ -649,17 +768,37 @@ impl NodeHandler {
                     .expect("Cannot commit block");
 
                 // Consensus messages cache is useful only during one height, so it should be
-                // cleared when a new height is achieved.
+                // cleared when a new height is achieved. Cached transactions are moved
+                // to the persistent pool on commit.
+                self.state.unsaved_txs_mut().clear();
                 self.blockchain.persist_changes(
-                    |schema| schema.consensus_messages_cache().clear(),
+                    |schema| {
+                        schema.consensus_messages_cache().clear();
+                        schema.transactions_cache().clear();
+                    },
                     "Cannot clear consensus messages",
                 );
 
                 (committed_txs, proposer)
             };
+            // Record consensus metrics for the height.
+            if round.is_some() {
+                self.state.complete_height(
+                    proposer.0,
+                    precommit_validators,
+                    self.system_state.current_time(),
+                );
+            }
             // Update node state.
-            self.state
-                .update_config(Schema::new(&self.blockchain.snapshot()).consensus_config());
+            let config = Schema::new(&self.blockchain.snapshot()).consensus_config();
+            let config_changed = *self.state.config() != config;
+            self.state.update_config(config);
+            if config_changed {
+                self.save_peer_identities();
+            }
+            self.max_message_len
+                .store(self.state.config().max_message_len, Ordering::Relaxed);
+            self.update_node_role();
             // Update state to new height.
             let block_hash = self.blockchain.as_ref().last_hash();
             self.state
@@ -671,6 +810,7 @@ impl NodeHandler {
         for plugin in &self.plugins {
             plugin.after_commit(&snapshot);
         }
+        self.notify_anchoring_hooks();
 
         let schema = Schema::new(&snapshot);
         let pool_len = schema.transactions_pool_len();
@@ -716,6 +856,8 @@ impl NodeHandler {
     /// This function panics if it receives an invalid transaction for an already committed block.
     pub(crate) fn handle_tx(&mut self, msg: Verified<AnyTx>) -> Result<(), failure::Error> {
         let hash = msg.object_hash();
+        let span = info_span!("handle_tx", tx_hash = %hash);
+        let _enter = span.enter();
 
         let snapshot = self.blockchain.snapshot();
         let schema = Schema::new(&snapshot);
@@ -739,8 +881,10 @@ impl NodeHandler {
                 msg, e
             );
         } else {
-            // Transaction is OK, store it to the cache.
+            // Transaction is OK, store it to the cache. The transaction is saved to the node
+            // schema later together with other received transactions (see `save_tx_cache`).
             self.state.tx_cache_mut().insert(hash, msg);
+            self.state.unsaved_txs_mut().push(hash);
         }
 
         if self.state.is_leader() && self.state.round() != Round::zero() {
@@ -783,7 +927,7 @@ impl NodeHandler {
         &mut self,
         msg: &Verified<TransactionsResponse>,
     ) -> Result<(), failure::Error> {
-        if msg.payload().to != self.state.keys().consensus_pk() {
+        if msg.payload().to != self.state.identity_keys().public_key() {
             bail!(
                 "Received response intended for another peer, to={}, from={}",
                 msg.payload().to().to_hex(),
@@ -797,9 +941,8 @@ impl NodeHandler {
                 msg.author().to_hex()
             )
         }
-        for tx in msg.payload().transactions() {
-            self.execute_later(InternalRequest::VerifyMessage(tx.clone()));
-        }
+        let transactions = msg.payload().transactions().to_vec();
+        self.execute_later(InternalRequest::VerifyMessages(transactions));
         Ok(())
     }
 
@@ -808,6 +951,10 @@ impl NodeHandler {
     #[cfg_attr(feature = "cargo-clippy", allow(clippy::needless_pass_by_value))]
     pub(crate) fn handle_incoming_tx(&mut self, msg: Verified<AnyTx>) {
         trace!("Handle incoming transaction");
+        if self.bootstrap_mode {
+            warn!("Ignoring incoming transaction because the node is in the bootstrap mode");
+            return;
+        }
 
         match self.handle_tx(msg.clone()) {
             Ok(_) => self.broadcast(msg),
@@ -827,6 +974,7 @@ impl NodeHandler {
         }
 
         info!("Jump to a new round = {}", round);
+        self.state.complete_round(self.system_state.current_time());
         self.state.jump_round(round);
         self.add_round_timeout();
         self.process_new_round();
@@ -864,7 +1012,12 @@ impl NodeHandler {
         }
         warn!("ROUND TIMEOUT height={}, round={}", height, round);
 
+        // Save the transactions received during the round, so that they are not lost
+        // if the node is terminated before the next commit.
+        self.save_tx_cache();
+
         // Update state to new round
+        self.state.complete_round(self.system_state.current_time());
         self.state.new_round();
 
         // Add timeout for this round
@@ -893,59 +1046,114 @@ impl NodeHandler {
             let round = self.state.round();
             let txs = self.get_txs_for_propose();
 
-            let propose = self.sign_message(Propose::new(
+            let propose = self.sign_consensus_message(Propose::new(
                 validator_id,
                 self.state.height(),
                 round,
                 self.state.last_hash(),
                 txs,
             ));
-            // Put our propose to the consensus messages cache.
-            self.blockchain.persist_changes(
-                |schema| schema.save_message(round, propose.clone()),
-                "Cannot save `Propose` to message cache",
-            );
+            // If the propose is signed by the external signer, it is handled once signed.
+            if let Some(propose) = propose {
+                self.handle_own_propose(propose);
+            }
+        }
+    }
 
-            trace!("Broadcast propose: {:?}", propose);
-            self.broadcast(propose.clone());
-            self.allow_expedited_propose = true;
+    /// Broadcasts the propose of this node and sends a prevote for it.
+    fn handle_own_propose(&mut self, propose: Verified<Propose>) {
+        let round = propose.payload().round;
+        // Put our propose to the consensus messages cache.
+        self.blockchain.persist_changes(
+            |schema| schema.save_message(round, propose.clone()),
+            "Cannot save `Propose` to message cache",
+        );
 
-            // Save our propose into state
-            let hash = self.state.add_self_propose(propose);
+        trace!("Broadcast propose: {:?}", propose);
+        self.broadcast(propose.clone());
+        self.allow_expedited_propose = true;
 
-            // Send prevote
-            let has_majority_prevotes = self.check_propose_and_broadcast_prevote(round, hash);
-            if has_majority_prevotes {
-                self.handle_majority_prevotes(round, hash);
-            }
+        // Save our propose into state
+        let hash = self.state.add_self_propose(propose);
+
+        // Send prevote
+        let has_majority_prevotes = self.check_propose_and_broadcast_prevote(round, hash);
+        if has_majority_prevotes {
+            self.handle_majority_prevotes(round, hash);
         }
     }
 
-    fn get_txs_for_propose(&self) -> Vec<Hash> {
-        let txs_cache_len = self.state.tx_cache_len() as u64;
-        let tx_block_limit = self.txs_block_limit();
+    /// Handles a consensus message of this node signed by the external signer.
+    ///
+    /// The message is dropped if it has become outdated while being signed, i.e., if the node
+    /// has moved to another height (or, for proposes, to another round).
+    pub(crate) fn handle_signed_message(&mut self, msg: Message) {
+        let msg = match msg {
+            Message::Consensus(msg) => msg,
+            other => {
+                error!(
+                    "Unexpected message signed by the external signer: {:?}",
+                    other
+                );
+                return;
+            }
+        };
 
-        let snapshot = self.blockchain.snapshot();
-        let schema = Schema::new(&snapshot);
-        let pool = schema.transactions_pool();
-        let pool_len = schema.transactions_pool_len();
+        if !self.is_enabled
+            || msg.height() != self.state.height()
+            || self.state.validator_id() != Some(msg.validator())
+        {
+            trace!("Dropping outdated consensus message of the node: {:?}", msg);
+            return;
+        }
 
-        info!("LEADER: pool = {}, cache = {}", pool_len, txs_cache_len);
+        match msg {
+            ConsensusMessage::Propose(propose) => {
+                if propose.payload().round == self.state.round()
+                    && self.state.locked_propose().is_none()
+                {
+                    self.handle_own_propose(propose);
+                } else {
+                    trace!("Dropping outdated propose of the node: {:?}", propose);
+                }
+            }
 
-        let remaining_tx_count = tx_block_limit.saturating_sub(txs_cache_len as u32);
-        let cache_max_count = std::cmp::min(u64::from(tx_block_limit), txs_cache_len);
+            ConsensusMessage::Prevote(prevote) => {
+                let (round, propose_hash) =
+                    (prevote.payload().round, prevote.payload().propose_hash);
+                if self.handle_own_prevote(prevote) {
+                    self.handle_majority_prevotes(round, propose_hash);
+                }
+            }
 
-        let mut cache_txs: Vec<Hash> = self
-            .state
-            .tx_cache()
-            .keys()
-            .take(cache_max_count as usize)
-            .cloned()
-            .collect();
-        let pool_txs: Vec<Hash> = pool.iter().take(remaining_tx_count as usize).collect();
+            ConsensusMessage::Precommit(precommit) => {
+                let round = precommit.payload().round();
+                let propose_hash = *precommit.payload().propose_hash();
+                let block_hash = *precommit.payload().block_hash();
+                if self.handle_own_precommit(precommit) {
+                    self.handle_majority_precommits(round, &propose_hash, &block_hash);
+                }
+            }
+        }
+    }
 
-        cache_txs.extend(pool_txs);
-        cache_txs
+    fn get_txs_for_propose(&self) -> Vec<Hash> {
+        let tx_block_limit = self.txs_block_limit() as usize;
+        let snapshot = self.blockchain.snapshot();
+        let pool = TxPool::new(&*snapshot, self.state.tx_cache());
+        info!(
+            "LEADER: pool = {}, cache = {}",
+            Schema::new(&snapshot).transactions_pool_len(),
+            self.state.tx_cache_len()
+        );
+
+        let mut selected = HashSet::new();
+        self.tx_selector
+            .select(pool, tx_block_limit)
+            .into_iter()
+            .filter(|hash| pool.contains(hash) && selected.insert(*hash))
+            .take(tx_block_limit)
+            .collect()
     }
 
     /// Handles request timeout by sending the corresponding request message to a peer.
@@ -1001,9 +1209,13 @@ impl NodeHandler {
                         self.state.known_prevotes(round, propose_hash),
                     ))
                     .into(),
-                RequestData::Block(height) => {
-                    self.sign_message(BlockRequest::new(peer, height)).into()
-                }
+                RequestData::Block(height) => self
+                    .sign_message(BlockRequest::with_count(
+                        peer,
+                        height,
+                        BLOCK_REQUEST_BATCH_SIZE,
+                    ))
+                    .into(),
             };
             trace!("Send request {:?} to peer {:?}", data, peer);
             self.send_to_peer(peer, message);
@@ -1087,12 +1299,21 @@ impl NodeHandler {
 
     /// Requests a block for the next height from all peers with a bigger height. Called when the
     /// node tries to catch up with other nodes' height.
+    ///
+    /// If the block for the next height has already been received, it is processed instead.
     pub(crate) fn request_next_block(&mut self) {
         if !self.is_enabled {
             trace!("Not sending a request for the next block because the node is paused.");
             return;
         }
 
+        if let Some(msg) = self.state.take_queued_block(self.state.height()) {
+            match self.handle_block(&msg) {
+                Ok(()) => return,
+                Err(err) => warn!("Failed to process queued block: {}", err),
+            }
+        }
+
         // TODO: Randomize next peer. (ECR-171)
         let heights: Vec<_> = self
             .state
@@ -1111,6 +1332,15 @@ impl NodeHandler {
         }
     }
 
+    /// Requests the batch of blocks following the batch starting from `height` in advance,
+    /// so that the next batch is transferred while the current one is being processed.
+    fn request_following_blocks(&mut self, height: Height, peer: PublicKey) {
+        let next_height = Height(height.0 + u64::from(BLOCK_REQUEST_BATCH_SIZE));
+        if self.state.node_height(&peer) > next_height {
+            self.request(RequestData::Block(next_height), peer);
+        }
+    }
+
     /// Removes the specified request from the pending request list.
     fn remove_request(&mut self, data: &RequestData) -> HashSet<PublicKey> {
         // TODO: Clear timeout. (ECR-171)
@@ -1124,13 +1354,27 @@ impl NodeHandler {
             .validator_id()
             .expect("called broadcast_prevote in Auditor node.");
         let locked_round = self.state.locked_round();
-        let prevote = self.sign_message(Prevote::new(
+        let prevote = self.sign_consensus_message(Prevote::new(
             validator_id,
             self.state.height(),
             round,
             propose_hash,
             locked_round,
         ));
+        match prevote {
+            Some(prevote) => self.handle_own_prevote(prevote),
+            None => {
+                // The prevote is signed by the external signer and is handled once signed.
+                self.state.add_pending_prevote(round);
+                self.state.has_majority_prevotes(round, propose_hash)
+            }
+        }
+    }
+
+    /// Broadcasts the prevote of this node. Returns `true` if majority of prevotes
+    /// is achieved.
+    fn handle_own_prevote(&mut self, prevote: Verified<Prevote>) -> bool {
+        let (round, propose_hash) = (prevote.payload().round, prevote.payload().propose_hash);
         let has_majority_prevotes = self.state.add_prevote(prevote.clone());
 
         // save outgoing Prevote to the consensus messages cache before broadcast
@@ -1152,7 +1396,7 @@ impl NodeHandler {
             .state
             .validator_id()
             .expect("called broadcast_precommit in Auditor node.");
-        let precommit = self.sign_message(Precommit::new(
+        let precommit = self.sign_consensus_message(Precommit::new(
             validator_id,
             self.state.height(),
             round,
@@ -1160,7 +1404,17 @@ impl NodeHandler {
             block_hash,
             self.system_state.current_time().into(),
         ));
-        self.state.add_precommit(precommit.clone());
+        // If the precommit is signed by the external signer, it is handled once signed.
+        if let Some(precommit) = precommit {
+            self.handle_own_precommit(precommit);
+        }
+    }
+
+    /// Broadcasts the precommit of this node. Returns `true` if majority of precommits
+    /// is achieved.
+    fn handle_own_precommit(&mut self, precommit: Verified<Precommit>) -> bool {
+        let round = precommit.payload().round();
+        let has_majority_precommits = self.state.add_precommit(precommit.clone());
 
         // Put our Precommit to the consensus cache before broadcast.
         self.blockchain.persist_changes(
@@ -1170,6 +1424,8 @@ impl NodeHandler {
 
         trace!("Broadcast precommit: {:?}", precommit);
         self.broadcast(precommit);
+
+        has_majority_precommits
     }
 
     /// Checks that pre-commits count is correct and calls `validate_precommit` for each of them.
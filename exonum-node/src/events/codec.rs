@@ -21,25 +21,36 @@ use exonum::{
 use failure::bail;
 use tokio_io::codec::{Decoder, Encoder};
 
-use std::mem;
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 
 use crate::events::noise::{TransportWrapper, HEADER_LENGTH as NOISE_HEADER_LENGTH};
 
 #[derive(Debug)]
 pub struct MessagesCodec {
-    /// Maximum message length (in bytes), gets populated from `ConsensusConfig`.
-    max_message_len: u32,
+    /// Maximum message length (in bytes), gets populated from `ConsensusConfig`
+    /// and is updated if the configuration changes.
+    max_message_len: Arc<AtomicU32>,
     /// Noise session to encrypt/decrypt messages.
     session: TransportWrapper,
 }
 
 impl MessagesCodec {
-    pub fn new(max_message_len: u32, session: TransportWrapper) -> Self {
+    pub fn new(max_message_len: Arc<AtomicU32>, session: TransportWrapper) -> Self {
         Self {
             max_message_len,
             session,
         }
     }
+
+    fn max_message_len(&self) -> usize {
+        self.max_message_len.load(Ordering::Relaxed) as usize
+    }
 }
 
 impl Decoder for MessagesCodec {
@@ -60,11 +71,11 @@ impl Decoder for MessagesCodec {
 
         let buf = self.session.decrypt_msg(len, buf)?;
 
-        if buf.len() > self.max_message_len as usize {
+        if buf.len() > self.max_message_len() {
             bail!(
                 "Received message is too long: received_len = {}, allowed_len = {}",
                 buf.len(),
-                self.max_message_len
+                self.max_message_len()
             )
         }
 
@@ -85,7 +96,15 @@ impl Encoder for MessagesCodec {
     type Error = failure::Error;
 
     fn encode(&mut self, msg: Self::Item, buf: &mut BytesMut) -> Result<(), Self::Error> {
-        self.session.encrypt_msg(&msg.into_bytes(), buf)?;
+        let bytes = msg.into_bytes();
+        if bytes.len() > self.max_message_len() {
+            bail!(
+                "Message is too long to be sent: len = {}, allowed_len = {}",
+                bytes.len(),
+                self.max_message_len()
+            )
+        }
+        self.session.encrypt_msg(&bytes, buf)?;
         Ok(())
     }
 }
@@ -101,6 +120,11 @@ mod test {
     };
     use tokio_io::codec::{Decoder, Encoder};
 
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
     use super::MessagesCodec;
     use crate::{
         events::noise::{HandshakeParams, NoiseWrapper, TransportWrapper},
@@ -152,15 +176,9 @@ mod test {
             state: initiator.into_transport_mode().unwrap(),
         };
 
-        let responder_codec = MessagesCodec {
-            max_message_len: 10000,
-            session: initiator,
-        };
-
-        let initiator_codec = MessagesCodec {
-            max_message_len: 10000,
-            session: responder,
-        };
+        let max_message_len = Arc::new(AtomicU32::new(10000));
+        let responder_codec = MessagesCodec::new(Arc::clone(&max_message_len), initiator);
+        let initiator_codec = MessagesCodec::new(max_message_len, responder);
 
         (responder_codec, initiator_codec)
     }
@@ -210,4 +228,23 @@ mod test {
         bytes.clear();
         assert!(responder.decode_eof(&mut bytes).unwrap().is_none());
     }
+
+    #[test]
+    fn encode_message_too_long() {
+        let (_, ref mut initiator) = create_encrypted_codecs();
+        let raw = {
+            let (pk, sk) = gen_keypair();
+            let msg = Verified::from_value(Status::new(Height(0), Hash::zero(), 0), pk, &sk);
+            msg.into_raw()
+        };
+
+        // The limit is shared with the node and can be changed at runtime.
+        initiator
+            .max_message_len
+            .store(SIGNED_MESSAGE_MIN_SIZE as u32, Ordering::Relaxed);
+        let mut bytes: BytesMut = BytesMut::new();
+        let err = initiator.encode(raw, &mut bytes).unwrap_err();
+        assert!(err.to_string().contains("Message is too long to be sent"));
+        assert!(bytes.is_empty());
+    }
 }
@@ -14,21 +14,78 @@
 
 use exonum::{merkledb::BinaryValue, messages::SignedMessage};
 use futures::{
-    future::{self, Either, Executor},
+    future::{self, Either, Executor, Loop},
+    stream,
     sync::mpsc,
     Future, Sink, Stream,
 };
+use log::{error, warn};
 use tokio_core::reactor::{Handle, Timeout};
 
-use std::time::{Duration, SystemTime};
+use std::{
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
 
-use super::{InternalEvent, InternalRequest, TimeoutRequest};
+use super::{InternalEvent, InternalRequest, SignMessageRequest, TimeoutRequest};
 use crate::messages::{ExonumMessage, Message};
 
+/// Maximum number of messages verified as a single batch. Larger sets of messages
+/// are split into several batches, which are verified in parallel.
+const VERIFICATION_BATCH_SIZE: usize = 64;
+/// Maximum number of attempts to sign a consensus message by the external signer.
+const SIGNING_ATTEMPTS: usize = 5;
+/// Delay between consecutive attempts to sign a consensus message by the external signer.
+const SIGNING_RETRY_DELAY: Duration = Duration::from_millis(200);
+
 #[derive(Debug)]
 pub struct InternalPart {
     pub internal_tx: mpsc::Sender<InternalEvent>,
     pub internal_requests_rx: mpsc::Receiver<InternalRequest>,
+    /// Maximum number of transactions from peers awaiting signature verification.
+    /// Transactions exceeding this limit are dropped.
+    ///
+    /// The node sets this limit to 1024 by default (see `EventsPoolCapacity`), which matches
+    /// the capacity of the queue of API requests. Verifying this many signatures takes tens
+    /// of milliseconds, so a burst of transactions from peers cannot noticeably delay
+    /// verification of consensus messages. Dropping transactions is safe: if a dropped
+    /// transaction gets into a proposal, the node requests it from the proposal author.
+    pub tx_verification_capacity: usize,
+}
+
+/// Counter of verification tasks scheduled on the thread pool.
+#[derive(Debug, Clone, Default)]
+struct PendingTasks(Arc<AtomicUsize>);
+
+impl PendingTasks {
+    fn len(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Registers a new task. The task is considered pending until the returned guard is dropped.
+    fn start(&self, count: usize) -> PendingTasksGuard {
+        self.0.fetch_add(count, Ordering::SeqCst);
+        PendingTasksGuard {
+            counter: Arc::clone(&self.0),
+            count,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PendingTasksGuard {
+    counter: Arc<AtomicUsize>,
+    count: usize,
+}
+
+impl Drop for PendingTasksGuard {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(self.count, Ordering::SeqCst);
+    }
 }
 
 impl InternalPart {
@@ -46,42 +103,197 @@ impl InternalPart {
         })
     }
 
+    fn is_transaction(msg: &SignedMessage) -> bool {
+        match ExonumMessage::try_from(msg) {
+            Ok(ExonumMessage::AnyTx(_)) => true,
+            _ => false,
+        }
+    }
+
     fn verify_message(
-        raw: Vec<u8>,
+        msg: SignedMessage,
         internal_tx: mpsc::Sender<InternalEvent>,
     ) -> impl Future<Item = (), Error = ()> {
-        future::lazy(|| {
-            SignedMessage::from_bytes(raw.into())
-                .and_then(SignedMessage::into_verified::<ExonumMessage>)
+        future::lazy(|| msg.into_verified::<ExonumMessage>().map(Message::from))
+            .map_err(drop)
+            .and_then(|msg| {
+                let event = future::ok(InternalEvent::message_verified(msg));
+                Self::send_event(event, internal_tx)
+            })
+    }
+
+    /// Verifies signatures of messages in a batch. If the batch contains an invalid message,
+    /// messages are verified one by one, so that the valid ones are not lost.
+    pub(crate) fn verify_batch(raw: Vec<Vec<u8>>) -> Vec<Message> {
+        let messages: Vec<_> = raw
+            .into_iter()
+            .filter_map(|bytes| SignedMessage::from_bytes(bytes.into()).ok())
+            .collect();
+
+        match SignedMessage::into_verified_batch::<ExonumMessage>(messages.clone()) {
+            Ok(verified) => verified.into_iter().map(Message::from).collect(),
+            Err(_) => messages
+                .into_iter()
+                .filter_map(|msg| msg.into_verified::<ExonumMessage>().ok())
                 .map(Message::from)
+                .collect(),
+        }
+    }
+
+    fn verify_messages(
+        raw: Vec<Vec<u8>>,
+        internal_tx: mpsc::Sender<InternalEvent>,
+    ) -> impl Future<Item = (), Error = ()> {
+        future::lazy(|| Ok::<_, ()>(Self::verify_batch(raw))).and_then(|messages| {
+            stream::iter_ok(messages).for_each(move |msg| {
+                let event = future::ok(InternalEvent::message_verified(msg));
+                Self::send_event(event, internal_tx.clone())
+            })
         })
-        .map_err(drop)
-        .and_then(|msg| {
-            let event = future::ok(InternalEvent::message_verified(msg));
-            Self::send_event(event, internal_tx)
+    }
+
+    /// Signs a consensus message by the external signer. Failed attempts are retried
+    /// after a delay; the message is dropped if all attempts fail. The signature is verified,
+    /// so that a misbehaving signer cannot make the node broadcast invalid messages.
+    fn sign_message(
+        request: SignMessageRequest,
+        handle: Handle,
+    ) -> impl Future<Item = InternalEvent, Error = ()> {
+        let SignMessageRequest { signer, payload } = request;
+        future::loop_fn(1, move |attempt| {
+            let handle = handle.clone();
+            SignedMessage::with_signer(payload.clone(), signer.as_ref())
+                .and_then(SignedMessage::into_verified::<ExonumMessage>)
+                .then(move |res| match res {
+                    Ok(msg) => Either::A(future::ok(Loop::Break(Message::from(msg)))),
+
+                    Err(e) if attempt < SIGNING_ATTEMPTS => {
+                        warn!(
+                            "Cannot sign consensus message (attempt {}/{}): {}",
+                            attempt, SIGNING_ATTEMPTS, e
+                        );
+                        let retry = Timeout::new(SIGNING_RETRY_DELAY, &handle)
+                            .expect("Unable to create timeout")
+                            .map(move |()| Loop::Continue(attempt + 1))
+                            .map_err(|e| panic!("Cannot execute timeout: {:?}", e));
+                        Either::B(Either::A(retry))
+                    }
+
+                    Err(e) => {
+                        error!(
+                            "Cannot sign consensus message in {} attempts, dropping it: {}",
+                            SIGNING_ATTEMPTS, e
+                        );
+                        Either::B(Either::B(future::err(())))
+                    }
+                })
         })
+        .map(InternalEvent::message_signed)
+    }
+
+    /// Signs a consensus message by the external signer in the current thread, retrying
+    /// failed attempts without a delay. Used by the sandbox and the simulated network,
+    /// which do not run an event loop.
+    pub(crate) fn sign_message_now(request: SignMessageRequest) -> Option<Message> {
+        let SignMessageRequest { signer, payload } = request;
+        for attempt in 1..=SIGNING_ATTEMPTS {
+            let res = SignedMessage::with_signer(payload.clone(), signer.as_ref())
+                .wait()
+                .and_then(SignedMessage::into_verified::<ExonumMessage>);
+            match res {
+                Ok(msg) => return Some(Message::from(msg)),
+                Err(e) => warn!(
+                    "Cannot sign consensus message (attempt {}/{}): {}",
+                    attempt, SIGNING_ATTEMPTS, e
+                ),
+            }
+        }
+        error!(
+            "Cannot sign consensus message in {} attempts, dropping it",
+            SIGNING_ATTEMPTS
+        );
+        None
     }
 
     /// Represents a task that processes Internal Requests and produces Internal Events.
     /// `handle` is used to schedule additional tasks within this task.
     /// `verify_executor` is where transaction verification task is executed.
+    ///
+    /// Transactions from peers are dropped if there are more than `tx_verification_capacity`
+    /// transactions awaiting verification, so that a burst of transactions cannot delay
+    /// verification of consensus messages.
     pub fn run<E>(self, handle: Handle, verify_executor: E) -> impl Future<Item = (), Error = ()>
     where
         E: Executor<Box<dyn Future<Item = (), Error = ()> + Send>>,
     {
         let internal_tx = self.internal_tx;
+        let tx_verification_capacity = self.tx_verification_capacity;
+        let pending_txs = PendingTasks::default();
 
         self.internal_requests_rx
             .map(move |request| {
                 let event = match request {
-                    InternalRequest::VerifyMessage(tx) => {
-                        let fut = Self::verify_message(tx, internal_tx.clone());
+                    InternalRequest::VerifyMessage(raw) => {
+                        let msg = match SignedMessage::from_bytes(raw.into()) {
+                            Ok(msg) => msg,
+                            Err(_) => return,
+                        };
+
+                        let guard = if Self::is_transaction(&msg) {
+                            if pending_txs.len() >= tx_verification_capacity {
+                                warn!(
+                                    "Transaction verification queue is full, dropping \
+                                     transaction from {}",
+                                    msg.author
+                                );
+                                return;
+                            }
+                            Some(pending_txs.start(1))
+                        } else {
+                            None
+                        };
+
+                        let fut = Self::verify_message(msg, internal_tx.clone()).then(move |res| {
+                            drop(guard);
+                            res
+                        });
                         verify_executor
                             .execute(Box::new(fut))
                             .expect("cannot schedule message verification");
                         return;
                     }
 
+                    InternalRequest::VerifyMessages(txs) => {
+                        // Transactions in the batch were requested by the node itself,
+                        // so they are never dropped, but occupy the verification queue.
+                        let mut txs = txs.into_iter();
+                        loop {
+                            let chunk: Vec<_> =
+                                txs.by_ref().take(VERIFICATION_BATCH_SIZE).collect();
+                            if chunk.is_empty() {
+                                break;
+                            }
+
+                            let guard = pending_txs.start(chunk.len());
+                            let fut = Self::verify_messages(chunk, internal_tx.clone()).then(
+                                move |res| {
+                                    drop(guard);
+                                    res
+                                },
+                            );
+                            verify_executor
+                                .execute(Box::new(fut))
+                                .expect("cannot schedule messages verification");
+                        }
+                        return;
+                    }
+
+                    InternalRequest::SignMessage(request) => {
+                        let event = Self::sign_message(request, handle.clone());
+                        handle.spawn(Self::send_event(event, internal_tx.clone()));
+                        return;
+                    }
+
                     InternalRequest::Timeout(TimeoutRequest(time, timeout)) => {
                         let duration = time
                             .duration_since(SystemTime::now())
@@ -116,10 +328,12 @@ impl InternalPart {
 #[cfg(test)]
 mod tests {
     use exonum::{
-        crypto::{gen_keypair, Hash, Signature},
+        crypto::{gen_keypair, Hash, KeyPair, PublicKey, Signature},
         helpers::Height,
+        keys::{SignatureFuture, Signer},
         messages::Verified,
     };
+    use failure::format_err;
     use pretty_assertions::assert_eq;
     use tokio_core::reactor::Core;
 
@@ -129,12 +343,17 @@ mod tests {
     use crate::messages::Status;
 
     fn verify_message(msg: Vec<u8>) -> Option<InternalEvent> {
+        process_request(InternalRequest::VerifyMessage(msg))
+    }
+
+    fn process_request(request: InternalRequest) -> Option<InternalEvent> {
         let (internal_tx, internal_rx) = mpsc::channel(16);
         let (internal_requests_tx, internal_requests_rx) = mpsc::channel(16);
 
         let internal_part = InternalPart {
             internal_tx,
             internal_requests_rx,
+            tx_verification_capacity: 16,
         };
 
         let thread = thread::spawn(|| {
@@ -150,11 +369,55 @@ mod tests {
             core.run(task).unwrap()
         });
 
-        let request = InternalRequest::VerifyMessage(msg);
         internal_requests_tx.wait().send(request).unwrap();
         thread.join().unwrap()
     }
 
+    /// Signer failing the specified number of times before it starts producing signatures.
+    #[derive(Debug)]
+    struct FlakySigner {
+        keypair: KeyPair,
+        failures: AtomicUsize,
+    }
+
+    impl FlakySigner {
+        fn new(failures: usize) -> Self {
+            Self {
+                keypair: KeyPair::from(gen_keypair()),
+                failures: AtomicUsize::new(failures),
+            }
+        }
+    }
+
+    impl Signer for FlakySigner {
+        fn public_key(&self) -> PublicKey {
+            self.keypair.public_key()
+        }
+
+        fn sign(&self, data: &[u8]) -> SignatureFuture {
+            let failures = self.failures.load(Ordering::SeqCst);
+            if failures > 0 {
+                self.failures.store(failures - 1, Ordering::SeqCst);
+                Box::new(future::err(format_err!("Signer is unavailable")))
+            } else {
+                Signer::sign(&self.keypair, data)
+            }
+        }
+    }
+
+    fn sign_request(signer: FlakySigner) -> (InternalRequest, Message) {
+        let status = Status::new(Height(1), Hash::zero(), 0);
+        let expected = Verified::from_value(
+            status,
+            signer.keypair.public_key(),
+            signer.keypair.secret_key(),
+        );
+        let payload = expected.as_raw().payload.clone();
+        let request = SignMessageRequest::new(Arc::new(signer), payload);
+        let expected = Message::from_signed(expected.into_raw()).unwrap();
+        (InternalRequest::SignMessage(request), expected)
+    }
+
     fn get_signed_message() -> SignedMessage {
         let (pk, sk) = gen_keypair();
         Verified::from_value(Status::new(Height(0), Hash::zero(), 0), pk, &sk).into_raw()
@@ -178,4 +441,95 @@ mod tests {
         let event = verify_message(tx.into_bytes());
         assert_eq!(event, None);
     }
+
+    #[test]
+    fn sign_msg_with_retries() {
+        let (request, expected) = sign_request(FlakySigner::new(SIGNING_ATTEMPTS - 1));
+        let event = process_request(request);
+        assert_eq!(event, Some(InternalEvent::message_signed(expected)));
+    }
+
+    #[test]
+    fn sign_msg_with_unavailable_signer() {
+        let (request, _) = sign_request(FlakySigner::new(SIGNING_ATTEMPTS));
+        assert_eq!(process_request(request), None);
+    }
+
+    #[test]
+    fn sign_msg_in_current_thread() {
+        let (request, expected) = sign_request(FlakySigner::new(1));
+        let request = match request {
+            InternalRequest::SignMessage(request) => request,
+            _ => unreachable!(),
+        };
+        assert_eq!(InternalPart::sign_message_now(request), Some(expected));
+    }
+
+    #[test]
+    fn pending_tasks_counter() {
+        let pending = PendingTasks::default();
+        let first = pending.start(1);
+        let second = pending.start(3);
+        assert_eq!(pending.len(), 4);
+        drop(first);
+        assert_eq!(pending.len(), 3);
+        drop(second);
+        assert_eq!(pending.len(), 0);
+    }
+
+    #[test]
+    fn verify_msg_batch() {
+        let txs: Vec<_> = (0..3).map(|_| get_signed_message()).collect();
+        let expected: Vec<_> = txs
+            .iter()
+            .map(|tx| Message::from_signed(tx.clone()).unwrap())
+            .collect();
+        let raw = txs.iter().cloned().map(SignedMessage::into_bytes).collect();
+        assert_eq!(InternalPart::verify_batch(raw), expected);
+
+        // Valid messages should be kept even if the batch contains an invalid one.
+        let mut broken = txs;
+        broken[1].signature = Signature::zero();
+        let raw = broken.into_iter().map(SignedMessage::into_bytes).collect();
+        let expected = vec![expected[0].clone(), expected[2].clone()];
+        assert_eq!(InternalPart::verify_batch(raw), expected);
+    }
+
+    #[test]
+    fn verify_large_msg_batch() {
+        // The batch is split into several chunks verified separately.
+        let count = VERIFICATION_BATCH_SIZE * 2 + 1;
+        let txs: Vec<_> = (0..count).map(|_| get_signed_message()).collect();
+
+        let (internal_tx, internal_rx) = mpsc::channel(count);
+        let (internal_requests_tx, internal_requests_rx) = mpsc::channel(16);
+        let internal_part = InternalPart {
+            internal_tx,
+            internal_requests_rx,
+            tx_verification_capacity: 16,
+        };
+
+        let thread = thread::spawn(move || {
+            let mut core = Core::new().unwrap();
+            let handle = core.handle();
+            let verifier = core.handle();
+
+            let task = internal_part
+                .run(handle, verifier)
+                .map_err(drop)
+                .and_then(move |()| internal_rx.take(count as u64).collect().map_err(drop));
+            core.run(task).unwrap()
+        });
+
+        let raw = txs.iter().cloned().map(SignedMessage::into_bytes).collect();
+        let request = InternalRequest::VerifyMessages(raw);
+        internal_requests_tx.wait().send(request).unwrap();
+        let events = thread.join().unwrap();
+
+        assert_eq!(events.len(), count);
+        for tx in txs {
+            let event = InternalEvent::message_verified(Message::from_signed(tx).unwrap());
+            assert!(events.contains(&event));
+        }
+    }
 }
@@ -25,6 +25,7 @@ pub mod noise;
 
 use exonum::{
     helpers::{Height, Round},
+    keys::Signer,
     messages::{AnyTx, Verified},
 };
 use futures::{
@@ -33,7 +34,7 @@ use futures::{
     Async, Future, Poll, Stream,
 };
 
-use std::{cmp::Ordering, time::SystemTime};
+use std::{cmp::Ordering, sync::Arc, time::SystemTime};
 
 use crate::{messages::Message, ExternalMessage, NodeTimeout};
 
@@ -56,6 +57,10 @@ impl InternalEvent {
         InternalEvent(InternalEventInner::MessageVerified(Box::new(message)))
     }
 
+    pub fn message_signed(message: Message) -> Self {
+        InternalEvent(InternalEventInner::MessageSigned(Box::new(message)))
+    }
+
     pub fn is_message_verified(&self) -> bool {
         match self {
             InternalEvent(InternalEventInner::MessageVerified(_)) => true,
@@ -83,6 +88,8 @@ pub(crate) enum InternalEventInner {
     /// Message has been successfully verified.
     /// Message is boxed here so that enum variants have similar size.
     MessageVerified(Box<Message>),
+    /// Consensus message of the node has been signed by the external signer.
+    MessageSigned(Box<Message>),
 }
 
 #[derive(Debug)]
@@ -93,6 +100,23 @@ pub enum InternalRequest {
     Shutdown,
     /// Async request to verify a message in the thread pool.
     VerifyMessage(Vec<u8>),
+    /// Async request to verify a batch of messages in the thread pool.
+    VerifyMessages(Vec<Vec<u8>>),
+    /// Async request to sign a consensus message by the external signer.
+    SignMessage(SignMessageRequest),
+}
+
+/// Consensus message awaiting a signature from the external signer.
+#[derive(Debug)]
+pub struct SignMessageRequest {
+    pub(crate) signer: Arc<dyn Signer>,
+    pub(crate) payload: Vec<u8>,
+}
+
+impl SignMessageRequest {
+    pub(crate) fn new(signer: Arc<dyn Signer>, payload: Vec<u8>) -> Self {
+        Self { signer, payload }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
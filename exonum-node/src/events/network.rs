@@ -15,18 +15,18 @@
 use exonum::{
     crypto::{
         x25519::{self, into_x25519_public_key},
-        PublicKey,
+        KeyPair, PublicKey,
     },
     messages::{SignedMessage, Verified},
 };
 use failure::{bail, ensure, format_err};
 use futures::{
     future::{self, err, Either},
-    stream::{SplitSink, SplitStream},
+    stream::{self, SplitSink, SplitStream},
     sync::mpsc,
     unsync, Future, IntoFuture, Sink, Stream,
 };
-use log::{error, trace, warn};
+use log::{error, info, trace, warn};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_codec::Framed;
 use tokio_core::reactor::Handle;
@@ -35,7 +35,13 @@ use tokio_retry::{
     Retry,
 };
 
-use std::{cell::RefCell, collections::HashMap, net::SocketAddr, rc::Rc, time::Duration};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use super::{error::log_error, to_box};
 use crate::{
@@ -50,6 +56,9 @@ use crate::{
 };
 
 const OUTGOING_CHANNEL_SIZE: usize = 10;
+/// Maximum number of peers which are looked for at the addresses resolved from DNS seeds
+/// at the same time.
+const MAX_DISCOVERED_PEERS: usize = 8;
 
 #[derive(Debug, Clone)]
 pub enum ConnectedPeerAddr {
@@ -73,6 +82,8 @@ pub enum NetworkEvent {
     PeerConnected(ConnectedPeerAddr, Verified<Connect>),
     PeerDisconnected(PublicKey),
     UnableConnectToPeer(PublicKey),
+    /// Round-trip time to the peer estimated during the handshake.
+    PeerLatency(PublicKey, Duration),
 }
 
 #[derive(Debug, Clone)]
@@ -83,6 +94,8 @@ pub enum NetworkRequest {
     DisconnectWithPeer(PublicKey),
     // TODO: This variant is never constructed in main code. Is it necessary? (ECR-4118)
     Shutdown,
+    RotateNetworkKey(KeyPair),
+    DiscoverPeers(Vec<PublicKey>),
 }
 
 #[derive(Debug)]
@@ -132,6 +145,16 @@ impl ConnectionPool {
         peers.insert(*key, ConnectionPoolEntry { sender, address });
     }
 
+    fn addresses(&self) -> HashSet<SocketAddr> {
+        let peers = self.peers.borrow();
+        peers
+            .values()
+            .map(|entry| match entry.address {
+                ConnectedPeerAddr::In(address) | ConnectedPeerAddr::Out(_, address) => address,
+            })
+            .collect()
+    }
+
     fn contains(&self, address: &PublicKey) -> bool {
         let peers = self.peers.borrow();
         peers.get(address).is_some()
@@ -231,8 +254,10 @@ struct NetworkHandler {
     handle: Handle,
     network_config: NetworkConfiguration,
     network_tx: mpsc::Sender<NetworkEvent>,
-    handshake_params: HandshakeParams,
+    handshake_params: Rc<RefCell<HandshakeParams>>,
     connect_list: SharedConnectList,
+    // Peers which are being looked for at the addresses resolved from DNS seeds.
+    discovered_peers: Rc<RefCell<HashSet<PublicKey>>>,
 }
 
 impl NetworkHandler {
@@ -251,8 +276,9 @@ impl NetworkHandler {
             pool: connection_pool,
             network_config,
             network_tx,
-            handshake_params,
+            handshake_params: Rc::new(RefCell::new(handshake_params)),
             connect_list,
+            discovered_peers: Rc::default(),
         }
     }
 
@@ -261,7 +287,7 @@ impl NetworkHandler {
         let server = TcpListener::bind(&listen_address).unwrap().incoming();
         let pool = self.pool.clone();
 
-        let handshake_params = self.handshake_params.clone();
+        let handshake_params = Rc::clone(&self.handshake_params);
         let network_tx = self.network_tx.clone();
         let handle = self.handle.clone();
 
@@ -281,7 +307,8 @@ impl NetworkHandler {
                 let network_tx = network_tx.clone();
                 let handle = handle.clone();
 
-                let handshake = NoiseHandshake::responder(&handshake_params, &listen_address);
+                let handshake =
+                    NoiseHandshake::responder(&handshake_params.borrow(), &listen_address);
                 let holder = incoming_connections_counter.clone();
                 // Check incoming connections count
                 let connections_count = Rc::strong_count(&incoming_connections_counter) - 1;
@@ -297,7 +324,12 @@ impl NetworkHandler {
                 let connect_list = self.connect_list.clone();
                 let listener = handshake
                     .listen(incoming_connection)
-                    .and_then(move |(socket, raw, key)| (Ok(socket), Self::parse_connect_msg(Some(raw), key)))
+                    .and_then({
+                        let connect_list = connect_list.clone();
+                        move |(socket, raw, key)| {
+                            (Ok(socket), Self::parse_connect_msg(Some(raw), key, &connect_list))
+                        }
+                    })
                     .and_then(move |(socket, message)| {
                         if pool.contains(&message.author()) {
                             Box::new(future::ok(()))
@@ -338,78 +370,195 @@ impl NetworkHandler {
         &self,
         key: PublicKey,
         handshake_params: &HandshakeParams,
+    ) -> impl Future<Item = (), Error = failure::Error> {
+        let max_tries = self.network_config.tcp_connect_max_retries as usize;
+        let unresolved_address = self.connect_list.find_address_by_key(&key);
+
+        if let Some(unresolved_address) = unresolved_address {
+            Either::A(self.connect_to(key, unresolved_address, handshake_params, max_tries, false))
+        } else {
+            Either::B(err(format_err!(
+                "Trying to connect to peer not from ConnectList key={}",
+                key
+            )))
+        }
+    }
+
+    /// Connects to the peer with the given key at the given address. If `update_address`
+    /// is set, the address is saved to the connect list once the peer is authenticated.
+    fn connect_to(
+        &self,
+        key: PublicKey,
+        unresolved_address: String,
+        handshake_params: &HandshakeParams,
+        max_tries: usize,
+        update_address: bool,
     ) -> impl Future<Item = (), Error = failure::Error> {
         let handshake_params = handshake_params.clone();
         let handle = self.handle.clone();
         let network_tx = self.network_tx.clone();
         let network_config = self.network_config;
         let timeout = self.network_config.tcp_connect_retry_timeout;
-        let max_tries = self.network_config.tcp_connect_max_retries as usize;
         let max_connections = self.network_config.max_outgoing_connections;
         let strategy = FixedInterval::from_millis(timeout)
             .map(jitter)
             .take(max_tries);
 
-        let unresolved_address = self.connect_list.find_address_by_key(&key);
+        let remote_network_key = self.connect_list.network_key(&key);
+        let connect_list = self.connect_list.clone();
+        let mut updated_connect_list = self.connect_list.clone();
 
-        if let Some(unresolved_address) = unresolved_address {
-            let action = {
-                let unresolved_address = unresolved_address.clone();
-                move || tokio_dns::TcpStream::connect(unresolved_address.as_str())
-            };
+        let action = {
+            let unresolved_address = unresolved_address.clone();
+            move || tokio_dns::TcpStream::connect(unresolved_address.as_str())
+        };
 
-            let (sender_tx, receiver_rx) = mpsc::channel::<SignedMessage>(OUTGOING_CHANNEL_SIZE);
-            let pool = self.pool.clone();
-            Either::A(
-                Retry::spawn(strategy, action)
-                    .map_err(into_failure)
-                    .and_then(move |socket| Self::configure_socket(socket, network_config))
-                    .and_then(move |outgoing_connection| {
-                        Self::build_handshake_initiator(outgoing_connection, key, &handshake_params)
-                    })
-                    .and_then(move |(socket, raw, key)| {
-                        (Ok(socket), Self::parse_connect_msg(Some(raw), key))
-                    })
-                    .and_then(move |(socket, message)| {
-                        let connection_limit_reached = pool.count_outgoing() >= max_connections;
-                        if pool.contains(&message.author()) || connection_limit_reached {
-                            Box::new(future::ok(()))
-                        } else {
-                            let addr = match socket.get_ref().peer_addr() {
-                                Ok(addr) => addr,
-                                Err(e) => {
-                                    return Box::new(err(format_err!(
-                                        "Couldn't take peer addr from socket = {}",
-                                        e
-                                    )))
-                                        as Box<dyn Future<Error = failure::Error, Item = ()>>;
-                                }
-                            };
-                            let conn_addr = ConnectedPeerAddr::Out(unresolved_address, addr);
-                            pool.add(&key, conn_addr.clone(), sender_tx);
-                            let connection = Connection::new(
-                                handle,
-                                socket,
-                                receiver_rx,
-                                conn_addr,
-                                message.author(),
-                            );
-                            to_box(Self::handle_connection(
-                                connection,
-                                message,
-                                pool,
-                                &network_tx,
-                            ))
+        let (sender_tx, receiver_rx) = mpsc::channel::<SignedMessage>(OUTGOING_CHANNEL_SIZE);
+        let pool = self.pool.clone();
+        Retry::spawn(strategy, action)
+            .map_err(into_failure)
+            .and_then(move |socket| Self::configure_socket(socket, network_config))
+            .and_then(move |outgoing_connection| {
+                let handshake_start = Instant::now();
+                Self::build_handshake_initiator(
+                    outgoing_connection,
+                    remote_network_key,
+                    &handshake_params,
+                )
+                .map(move |(socket, raw, key)| (socket, raw, key, handshake_start.elapsed()))
+            })
+            .and_then(move |(socket, raw, key, handshake_time)| {
+                (
+                    Ok(socket),
+                    Self::parse_connect_msg(Some(raw), key, &connect_list),
+                    Ok(handshake_time),
+                )
+            })
+            .and_then(move |(socket, message, handshake_time)| {
+                let connection_limit_reached = pool.count_outgoing() >= max_connections;
+                if pool.contains(&message.author()) || connection_limit_reached {
+                    Box::new(future::ok(()))
+                } else {
+                    let addr = match socket.get_ref().peer_addr() {
+                        Ok(addr) => addr,
+                        Err(e) => {
+                            return Box::new(err(format_err!(
+                                "Couldn't take peer addr from socket = {}",
+                                e
+                            )))
+                                as Box<dyn Future<Error = failure::Error, Item = ()>>;
                         }
-                    })
-                    .map(drop),
-            )
-        } else {
-            Either::B(err(format_err!(
-                "Trying to connect to peer not from ConnectList key={}",
-                key
-            )))
+                    };
+                    if update_address {
+                        info!("Found peer {} at address {}", key, unresolved_address);
+                        updated_connect_list.update_peer(&key, unresolved_address.clone());
+                    }
+                    // The initiator waits for two round trips to complete the handshake
+                    // and receive the `Connect` message. The estimate is dropped
+                    // if the events channel is full.
+                    let latency = NetworkEvent::PeerLatency(key, handshake_time / 2);
+                    network_tx.clone().try_send(latency).ok();
+
+                    let conn_addr = ConnectedPeerAddr::Out(unresolved_address, addr);
+                    pool.add(&key, conn_addr.clone(), sender_tx);
+                    let connection =
+                        Connection::new(handle, socket, receiver_rx, conn_addr, message.author());
+                    to_box(Self::handle_connection(
+                        connection,
+                        message,
+                        pool,
+                        &network_tx,
+                    ))
+                }
+            })
+            .map(drop)
+    }
+
+    /// Resolves DNS seeds from the connect list and looks for the given peers at the resolved
+    /// addresses which are not used by established connections.
+    ///
+    /// Peers which are connected or are already being looked for are skipped. At most
+    /// `MAX_DISCOVERED_PEERS` peers are looked for at the same time; the rest of the peers
+    /// are looked for on the next request.
+    fn discover_peers(
+        &self,
+        peers: Vec<PublicKey>,
+    ) -> impl Future<Item = (), Error = failure::Error> {
+        let peers: Vec<_> = {
+            let mut discovered_peers = self.discovered_peers.borrow_mut();
+            let capacity = MAX_DISCOVERED_PEERS.saturating_sub(discovered_peers.len());
+            let peers: Vec<_> = peers
+                .into_iter()
+                .filter(|key| !self.pool.contains(key) && !discovered_peers.contains(key))
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .take(capacity)
+                .collect();
+            discovered_peers.extend(&peers);
+            peers
+        };
+        if peers.is_empty() {
+            return Either::A(future::ok(()));
         }
+
+        let resolved_seeds = self.connect_list.dns_seeds().into_iter().map(|seed| {
+            tokio_dns::resolve_sock_addr(seed.as_str()).then(move |res| {
+                Ok::<_, failure::Error>(res.unwrap_or_else(|e| {
+                    warn!("Unable to resolve DNS seed {}: {}", seed, e);
+                    Vec::new()
+                }))
+            })
+        });
+
+        let handler = self.clone();
+        let discovery = future::join_all(resolved_seeds).map(move |addresses| {
+            let connected_addresses = handler.pool.addresses();
+            let addresses: Vec<_> = addresses
+                .into_iter()
+                .flatten()
+                .filter(|address| !connected_addresses.contains(address))
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+
+            for key in peers {
+                let discovery = handler.discover_peer(key, addresses.clone());
+                handler.handle.spawn(discovery);
+            }
+        });
+        Either::B(discovery)
+    }
+
+    /// Tries to connect to the peer at the given addresses one by one until the peer is found.
+    fn discover_peer(
+        &self,
+        key: PublicKey,
+        addresses: Vec<SocketAddr>,
+    ) -> impl Future<Item = (), Error = ()> {
+        let handler = self.clone();
+        let discovered_peers = self.discovered_peers.clone();
+        let handshake_params = self.handshake_params.borrow().clone();
+
+        stream::iter_ok(addresses)
+            .for_each(move |address| {
+                if handler.pool.contains(&key) {
+                    // The peer is found, so the remaining addresses are not checked.
+                    return Either::A(future::err(()));
+                }
+                let attempt = handler
+                    .connect_to(key, address.to_string(), &handshake_params, 0, true)
+                    .then(move |res| {
+                        if let Err(e) = res {
+                            trace!("Peer {} is not found at {}: {}", key, address, e);
+                        }
+                        Ok::<_, ()>(())
+                    });
+                Either::B(attempt)
+            })
+            .then(move |_| {
+                discovered_peers.borrow_mut().remove(&key);
+                Ok(())
+            })
     }
 
     fn process_messages(
@@ -496,6 +645,7 @@ impl NetworkHandler {
     fn parse_connect_msg(
         raw: Option<Vec<u8>>,
         key: x25519::PublicKey,
+        connect_list: &SharedConnectList,
     ) -> Result<Verified<Connect>, failure::Error> {
         let raw = raw.ok_or_else(|| format_err!("Incoming socket closed"))?;
         let message = Message::from_raw_buffer(raw)?;
@@ -506,10 +656,10 @@ impl NetworkHandler {
                 other
             ),
         };
-        let author = into_x25519_public_key(connect.author());
+        let network_key = into_x25519_public_key(connect_list.network_key(&connect.author()));
 
         ensure!(
-            author == key,
+            network_key == key,
             "Connect message public key doesn't match with the received peer key"
         );
 
@@ -538,6 +688,10 @@ impl NetworkHandler {
                         .ok_or_else(|| format_err!("shutdown twice"))
                         .into_future(),
                 ),
+                NetworkRequest::RotateNetworkKey(keypair) => {
+                    to_box(self.handle_rotate_network_key(&keypair))
+                }
+                NetworkRequest::DiscoverPeers(peers) => to_box(self.discover_peers(peers)),
             }
             .map_err(log_error);
 
@@ -564,21 +718,40 @@ impl NetworkHandler {
         }
     }
 
+    /// Replaces the key used in handshakes and drops established connections from the pool,
+    /// so that subsequent messages are sent over connections re-established with the new key.
+    /// Messages already queued for the old connections are still delivered.
+    fn handle_rotate_network_key(
+        &self,
+        keypair: &KeyPair,
+    ) -> impl Future<Item = (), Error = failure::Error> {
+        self.handshake_params
+            .borrow_mut()
+            .rotate_key(keypair.public_key(), keypair.secret_key().to_owned());
+
+        let peers: Vec<PublicKey> = self.pool.peers.borrow().keys().cloned().collect();
+        let disconnects: Vec<_> = peers
+            .iter()
+            .map(|peer| self.pool.disconnect_with_peer(peer, &self.network_tx))
+            .collect();
+        future::join_all(disconnects).map(drop)
+    }
+
     fn create_new_connection(
         &self,
         key: PublicKey,
         message: SignedMessage,
     ) -> impl Future<Item = (), Error = failure::Error> {
         let pool = self.pool.clone();
-        let connect = self.handshake_params.connect.clone();
-        self.connect(key, &self.handshake_params)
-            .and_then(move |_| {
-                if &message == connect.as_raw() {
-                    Either::A(future::ok(()))
-                } else {
-                    Either::B(pool.send_message(&key, message))
-                }
-            })
+        let handshake_params = self.handshake_params.borrow().clone();
+        let connect = handshake_params.connect.clone();
+        self.connect(key, &handshake_params).and_then(move |_| {
+            if &message == connect.as_raw() {
+                Either::A(future::ok(()))
+            } else {
+                Either::B(pool.send_message(&key, message))
+            }
+        })
     }
 
     fn send_peer_connected_event(
@@ -25,7 +25,11 @@ use futures::future::{done, Future};
 use tokio_codec::Decoder;
 use tokio_io::{AsyncRead, AsyncWrite};
 
-use std::net::SocketAddr;
+use std::{
+    mem,
+    net::SocketAddr,
+    sync::{atomic::AtomicU32, Arc},
+};
 
 use super::wrapper::NoiseWrapper;
 use crate::{
@@ -35,16 +39,19 @@ use crate::{
     },
     messages::Connect,
     state::SharedConnectList,
+    CipherSuite,
 };
 
 /// Params needed to establish secured connection using Noise Protocol.
 #[derive(Debug, Clone)]
 pub struct HandshakeParams {
     pub(super) secret_key: x25519::SecretKey,
+    pub(super) previous_secret_key: Option<x25519::SecretKey>,
     pub(super) remote_key: Option<x25519::PublicKey>,
+    pub(super) cipher_suite: CipherSuite,
     pub(crate) connect_list: SharedConnectList,
     pub(crate) connect: Verified<Connect>,
-    max_message_len: u32,
+    max_message_len: Arc<AtomicU32>,
 }
 
 impl HandshakeParams {
@@ -59,23 +66,47 @@ impl HandshakeParams {
 
         HandshakeParams {
             secret_key,
-            max_message_len,
+            max_message_len: Arc::new(AtomicU32::new(max_message_len)),
+            previous_secret_key: None,
             remote_key: None,
+            cipher_suite: CipherSuite::default(),
             connect,
             connect_list,
         }
     }
 
+    pub(crate) fn with_cipher_suite(mut self, cipher_suite: CipherSuite) -> Self {
+        self.cipher_suite = cipher_suite;
+        self
+    }
+
+    /// Makes connections use the maximum message length shared with the node,
+    /// so that the node can change it at runtime.
+    pub(crate) fn with_shared_max_message_len(mut self, max_message_len: Arc<AtomicU32>) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
     pub fn set_remote_key(&mut self, remote_key: PublicKey) {
         self.remote_key = Some(into_x25519_public_key(remote_key));
     }
+
+    /// Replaces the key used to authenticate the node in the handshake. The previous key
+    /// is still accepted for incoming connections until the next rotation, so that peers
+    /// which are not yet aware of the new key are able to connect.
+    pub fn rotate_key(&mut self, public_key: PublicKey, secret_key: SecretKey) {
+        let (_, secret_key) = into_x25519_keypair(public_key, secret_key).unwrap();
+        let previous_secret_key = mem::replace(&mut self.secret_key, secret_key);
+        self.previous_secret_key = Some(previous_secret_key);
+    }
 }
 
 #[derive(Debug)]
 pub struct NoiseHandshake {
     noise: NoiseWrapper,
+    fallback_noise: Option<NoiseWrapper>,
     peer_address: SocketAddr,
-    max_message_len: u32,
+    max_message_len: Arc<AtomicU32>,
     connect_list: SharedConnectList,
     connect: Verified<Connect>,
 }
@@ -85,8 +116,9 @@ impl NoiseHandshake {
         let noise = NoiseWrapper::initiator(params);
         NoiseHandshake {
             noise,
+            fallback_noise: None,
             peer_address: *peer_address,
-            max_message_len: params.max_message_len,
+            max_message_len: Arc::clone(&params.max_message_len),
             connect_list: params.connect_list.clone(),
             connect: params.connect.clone(),
         }
@@ -94,10 +126,15 @@ impl NoiseHandshake {
 
     pub fn responder(params: &HandshakeParams, peer_address: &SocketAddr) -> Self {
         let noise = NoiseWrapper::responder(params);
+        let fallback_noise = params
+            .previous_secret_key
+            .as_ref()
+            .map(|secret_key| NoiseWrapper::responder_with_key(params, secret_key));
         NoiseHandshake {
             noise,
+            fallback_noise,
             peer_address: *peer_address,
-            max_message_len: params.max_message_len,
+            max_message_len: Arc::clone(&params.max_message_len),
             connect_list: params.connect_list.clone(),
             connect: params.connect.clone(),
         }
@@ -108,7 +145,20 @@ impl NoiseHandshake {
         stream: S,
     ) -> impl Future<Item = (S, Self, Vec<u8>), Error = failure::Error> {
         HandshakeRawMessage::read(stream).and_then(move |(stream, msg)| {
-            let message = self.noise.read_handshake_msg(&msg.0)?;
+            let message = match self.noise.read_handshake_msg(&msg.0) {
+                Ok(message) => message,
+                // The initiator may still use our previous key; if so, the first
+                // handshake message can only be decrypted by the fallback state.
+                Err(e) => match self.fallback_noise.take() {
+                    Some(mut noise) => {
+                        let message = noise.read_handshake_msg(&msg.0).map_err(|_| e)?;
+                        self.noise = noise;
+                        message
+                    }
+                    None => return Err(e.into()),
+                },
+            };
+            self.fallback_noise = None;
             Ok((stream, self, message))
         })
     }
@@ -153,7 +203,7 @@ impl NoiseHandshake {
         self.connect_list
             .peers()
             .iter()
-            .map(|info| into_x25519_public_key(info.public_key))
+            .map(|info| into_x25519_public_key(info.network_key.unwrap_or(info.public_key)))
             .any(|key| remote_static_key == &key)
     }
 }
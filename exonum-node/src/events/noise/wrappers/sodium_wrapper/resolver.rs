@@ -16,7 +16,8 @@
 
 use byteorder::{ByteOrder, LittleEndian};
 use exonum_sodiumoxide::crypto::{
-    aead::chacha20poly1305_ietf as sodium_chacha20poly1305, hash::sha256 as sodium_sha256,
+    aead::chacha20poly1305_ietf as sodium_chacha20poly1305,
+    hash::{sha256 as sodium_sha256, sha512 as sodium_sha512},
 };
 use log::error;
 use rand::{thread_rng, CryptoRng, Error, RngCore};
@@ -55,6 +56,7 @@ impl CryptoResolver for SodiumResolver {
     fn resolve_hash(&self, choice: &HashChoice) -> Option<Box<dyn Hash>> {
         match *choice {
             HashChoice::SHA256 => Some(Box::new(SodiumSha256::default())),
+            HashChoice::SHA512 => Some(Box::new(SodiumSha512::default())),
             _ => None,
         }
     }
@@ -273,6 +275,37 @@ impl Hash for SodiumSha256 {
     }
 }
 
+// Hash Sha512.
+#[derive(Debug, Default)]
+struct SodiumSha512(sodium_sha512::State);
+
+impl Hash for SodiumSha512 {
+    fn name(&self) -> &'static str {
+        "SHA512"
+    }
+
+    fn block_len(&self) -> usize {
+        128
+    }
+
+    fn hash_len(&self) -> usize {
+        sodium_sha512::DIGESTBYTES
+    }
+
+    fn reset(&mut self) {
+        self.0 = sodium_sha512::State::init();
+    }
+
+    fn input(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        let digest = self.0.clone().finalize();
+        out[..self.hash_len()].copy_from_slice(digest.as_ref());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -359,4 +392,24 @@ mod tests {
         // Results are expected to be the same.
         assert_eq!(our_shared_secret, remote_shared_secret);
     }
+
+    #[test]
+    fn test_sha512() {
+        // Value is cited from FIPS 180-2: Appendix C.1.
+        let mut hash = SodiumSha512::default();
+        hash.reset();
+        hash.input(b"abc");
+        let mut output = [0_u8; 64];
+        hash.result(&mut output);
+
+        assert_eq!(
+            output.as_ref(),
+            Vec::<u8>::from_hex(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            )
+            .unwrap()
+            .as_slice()
+        );
+    }
 }
@@ -18,6 +18,7 @@
 
 use byteorder::{ByteOrder, LittleEndian};
 use bytes::BytesMut;
+use exonum::crypto::x25519;
 use snow::{Builder, HandshakeState, TransportState};
 
 use std::fmt::{self, Error, Formatter};
@@ -31,12 +32,6 @@ pub const HANDSHAKE_HEADER_LENGTH: usize = 2;
 pub const MAX_HANDSHAKE_MESSAGE_LENGTH: usize = 65535;
 pub const MIN_HANDSHAKE_MESSAGE_LENGTH: usize = 32;
 
-// We choose XK pattern since it provides mutual authentication,
-// transmission of static public keys and requires pre-defined remote public
-// key to establish connection.
-// See: https://noiseprotocol.org/noise.html#interactive-patterns
-static PARAMS: &str = "Noise_XK_25519_ChaChaPoly_SHA256";
-
 /// Wrapper around noise handshake state to provide latter convenient interface.
 pub struct NoiseWrapper {
     pub state: HandshakeState,
@@ -45,7 +40,7 @@ pub struct NoiseWrapper {
 impl NoiseWrapper {
     pub fn initiator(params: &HandshakeParams) -> Self {
         if let Some(ref remote_key) = params.remote_key {
-            let builder: Builder<'_> = Self::noise_builder()
+            let builder: Builder<'_> = Self::noise_builder(params)
                 .local_private_key(params.secret_key.as_ref())
                 .remote_public_key(remote_key.as_ref());
             let state = builder
@@ -58,10 +53,16 @@ impl NoiseWrapper {
     }
 
     pub fn responder(params: &HandshakeParams) -> Self {
-        let builder: Builder<'_> = Self::noise_builder();
+        Self::responder_with_key(params, &params.secret_key)
+    }
+
+    /// Creates a responder which authenticates itself with the specified key
+    /// rather than the current one from `params`.
+    pub fn responder_with_key(params: &HandshakeParams, secret_key: &x25519::SecretKey) -> Self {
+        let builder: Builder<'_> = Self::noise_builder(params);
 
         let state = builder
-            .local_private_key(params.secret_key.as_ref())
+            .local_private_key(secret_key.as_ref())
             .build_responder()
             .expect("Noise session responder failed to initialize");
 
@@ -92,8 +93,16 @@ impl NoiseWrapper {
         Ok(TransportWrapper { state })
     }
 
-    fn noise_builder<'a>() -> Builder<'a> {
-        Builder::with_resolver(PARAMS.parse().unwrap(), Box::new(SodiumResolver::new()))
+    fn noise_builder<'a>(params: &HandshakeParams) -> Builder<'a> {
+        // We choose XK pattern since it provides mutual authentication,
+        // transmission of static public keys and requires pre-defined remote public
+        // key to establish connection.
+        // See: https://noiseprotocol.org/noise.html#interactive-patterns
+        let noise_params = params.cipher_suite.noise_params();
+        Builder::with_resolver(
+            noise_params.parse().unwrap(),
+            Box::new(SodiumResolver::new()),
+        )
     }
 }
 
@@ -15,7 +15,7 @@
 use exonum::{
     blockchain::ConsensusConfig,
     crypto::{
-        gen_keypair, gen_keypair_from_seed, PublicKey, SecretKey, Seed, PUBLIC_KEY_LENGTH,
+        gen_keypair, gen_keypair_from_seed, KeyPair, PublicKey, SecretKey, Seed, PUBLIC_KEY_LENGTH,
         SEED_LENGTH, SIGNATURE_LENGTH,
     },
     helpers::user_agent,
@@ -67,15 +67,21 @@ impl TestHandler {
     }
 
     fn wait_for_event(&mut self) -> Result<NetworkEvent, ()> {
-        let rx = self.network_events_rx.by_ref();
-        let future = rx
-            .into_future()
-            .timeout(Duration::from_secs(30))
-            .map_err(drop);
-
-        let mut core = Core::new().unwrap();
-        let (event, _) = core.run(future)?;
-        event.ok_or(())
+        loop {
+            let rx = self.network_events_rx.by_ref();
+            let future = rx
+                .into_future()
+                .timeout(Duration::from_secs(30))
+                .map_err(drop);
+
+            let mut core = Core::new().unwrap();
+            let (event, _) = core.run(future)?;
+            // Latency estimates are emitted alongside outgoing connections; skip them.
+            match event {
+                Some(NetworkEvent::PeerLatency(..)) => continue,
+                event => return event.ok_or(()),
+            }
+        }
     }
 
     pub fn disconnect_with(&self, key: PublicKey) {
@@ -94,6 +100,22 @@ impl TestHandler {
             .unwrap();
     }
 
+    pub fn rotate_network_key(&self, keypair: KeyPair) {
+        self.network_requests_tx
+            .clone()
+            .send(NetworkRequest::RotateNetworkKey(keypair))
+            .wait()
+            .unwrap();
+    }
+
+    pub fn discover_peers(&self, peers: Vec<PublicKey>) {
+        self.network_requests_tx
+            .clone()
+            .send(NetworkRequest::DiscoverPeers(peers))
+            .wait()
+            .unwrap();
+    }
+
     pub fn send_to(&self, key: PublicKey, raw: SignedMessage) {
         self.network_requests_tx
             .clone()
@@ -267,6 +289,8 @@ impl ConnectionParams {
         let connect_info = ConnectInfo {
             address: address.to_string(),
             public_key,
+            network_key: None,
+            consensus_key: None,
         };
 
         ConnectionParams {
@@ -603,3 +627,93 @@ fn test_connect_list_ignore_when_listening() {
     e1.wait_for_connect();
     e2.wait_for_connect();
 }
+
+#[test]
+fn test_network_key_rotation() {
+    let first = "127.0.0.1:20330".parse().unwrap();
+    let second = "127.0.0.1:20331".parse().unwrap();
+    let third = "127.0.0.1:20332".parse().unwrap();
+
+    let network_keypair = KeyPair::from(gen_keypair());
+
+    let mut t1 = ConnectionParams::from_address(first);
+    let first_key = t1.connect_info.public_key;
+    let mut t2 = ConnectionParams::from_address(second);
+    let second_key = t2.connect_info.public_key;
+    let mut t3 = ConnectionParams::from_address(third);
+
+    let mut first_list = ConnectList::default();
+    first_list.add(t2.connect_info.clone());
+    first_list.add(t3.connect_info.clone());
+    let first_list = SharedConnectList::from_connect_list(first_list);
+
+    // The second node is aware of the new network key of the first node.
+    let mut second_list = ConnectList::default();
+    second_list.add(ConnectInfo {
+        network_key: Some(network_keypair.public_key()),
+        ..t1.connect_info.clone()
+    });
+    let second_list = SharedConnectList::from_connect_list(second_list);
+
+    // The third node still uses the consensus key of the first node.
+    let mut third_list = ConnectList::default();
+    third_list.add(t1.connect_info.clone());
+    let third_list = SharedConnectList::from_connect_list(third_list);
+
+    let e1 = TestEvents::with_addr(first, &first_list);
+    let e2 = TestEvents::with_addr(second, &second_list);
+    let e3 = TestEvents::with_addr(third, &third_list);
+
+    let mut e1 = t1.spawn(e1, first_list);
+    let mut e2 = t2.spawn(e2, second_list);
+    let mut e3 = t3.spawn(e3, third_list);
+
+    e1.rotate_network_key(network_keypair);
+
+    // Outgoing connections are authenticated with the new key.
+    e1.connect_with(second_key, t1.connect.clone());
+    assert_eq!(e2.wait_for_connect(), t1.connect.clone());
+    assert_eq!(e1.wait_for_connect(), t2.connect.clone());
+
+    // Incoming connections are still accepted with the previous key.
+    e3.connect_with(first_key, t3.connect.clone());
+    assert_eq!(e1.wait_for_connect(), t3.connect.clone());
+    assert_eq!(e3.wait_for_connect(), t1.connect.clone());
+}
+
+#[test]
+fn test_network_dns_seeds() {
+    let first = "127.0.0.1:20430".parse().unwrap();
+    let second = "127.0.0.1:20431".parse().unwrap();
+
+    let mut t1 = ConnectionParams::from_address(first);
+    let mut t2 = ConnectionParams::from_address(second);
+    let second_key = t2.connect_info.public_key;
+
+    // The first node knows an outdated address of the second node.
+    let mut first_list = ConnectList::default();
+    first_list.add(ConnectInfo {
+        address: "127.0.0.1:20439".to_owned(),
+        ..t2.connect_info.clone()
+    });
+    first_list.dns_seeds = vec!["localhost:20431".to_owned()];
+    let first_list = SharedConnectList::from_connect_list(first_list);
+
+    let mut second_list = ConnectList::default();
+    second_list.add(t1.connect_info.clone());
+    let second_list = SharedConnectList::from_connect_list(second_list);
+
+    let e1 = TestEvents::with_addr(first, &first_list);
+    let e2 = TestEvents::with_addr(second, &second_list);
+
+    let mut e1 = t1.spawn(e1, first_list.clone());
+    let mut e2 = t2.spawn(e2, second_list);
+
+    e1.discover_peers(vec![second_key]);
+    assert_eq!(e2.wait_for_connect(), t1.connect.clone());
+    assert_eq!(e1.wait_for_connect(), t2.connect.clone());
+    assert_eq!(
+        first_list.find_address_by_key(&second_key),
+        Some(second.to_string())
+    );
+}
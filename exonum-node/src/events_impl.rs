@@ -12,18 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use exonum::blockchain::Schema;
+use exonum::{blockchain::Schema, helpers::LogContext};
 use log::{info, trace, warn};
 
 use super::{ConnectListConfig, ExternalMessage, NodeHandler, NodeTimeout};
 
-use crate::events::{
-    error::LogError, Event, EventHandler, InternalEvent, InternalEventInner, InternalRequest,
-    NetworkEvent,
+use crate::{
+    events::{
+        error::LogError, Event, EventHandler, InternalEvent, InternalEventInner, InternalRequest,
+        NetworkEvent, NetworkRequest,
+    },
+    schema::NodeSchema,
 };
 
 impl EventHandler for NodeHandler {
     fn handle_event(&mut self, event: Event) {
+        LogContext::replace(LogContext {
+            height: Some(self.state.height()),
+            round: Some(self.state.round()),
+            ..LogContext::default()
+        });
+
         match event {
             Event::Network(network) => self.handle_network_event(network),
             Event::Transaction(tx) => self.handle_incoming_tx(tx),
@@ -41,7 +50,8 @@ impl NodeHandler {
             InternalEventInner::Shutdown => {
                 panic!("Shutdown should be processed in the event loop")
             }
-            InternalEventInner::MessageVerified(msg) => self.handle_message(*msg),
+            InternalEventInner::MessageVerified(msg) => self.handle_verified_message(*msg),
+            InternalEventInner::MessageSigned(msg) => self.handle_signed_message(*msg),
         }
     }
 
@@ -50,9 +60,10 @@ impl NodeHandler {
             NetworkEvent::PeerConnected(peer, connect) => self.handle_connected(&peer, connect),
             NetworkEvent::PeerDisconnected(peer) => self.handle_disconnected(peer),
             NetworkEvent::UnableConnectToPeer(peer) => self.handle_unable_to_connect(peer),
-            NetworkEvent::MessageReceived(raw) => {
-                self.execute_later(InternalRequest::VerifyMessage(raw))
+            NetworkEvent::PeerLatency(peer, round_trip_time) => {
+                self.handle_peer_latency(peer, round_trip_time)
             }
+            NetworkEvent::MessageReceived(raw) => self.handle_raw_message(raw),
         }
     }
 
@@ -62,13 +73,11 @@ impl NodeHandler {
                 info!("Send Connect message to {}", info);
                 self.state.add_peer_to_connect_list(info.clone());
                 self.connect(info.public_key);
+                self.store_connect_list();
+            }
 
-                if let Some(ref mut config_manager) = self.config_manager {
-                    let connect_list_config =
-                        ConnectListConfig::from_connect_list(&self.state.connect_list());
-
-                    config_manager.store_connect_list(connect_list_config);
-                }
+            ExternalMessage::PeerRemove(public_key, keep_in_connect_list) => {
+                self.remove_peer(public_key, keep_in_connect_list)
             }
 
             ExternalMessage::Enable(value) => {
@@ -85,35 +94,90 @@ impl NodeHandler {
                 }
             }
 
+            ExternalMessage::RotateNetworkKey(keypair) => {
+                info!("Rotating network key, new key: {}", keypair.public_key());
+                if let Some(ref mut config_manager) = self.config_manager {
+                    config_manager.store_network_keys(keypair.clone());
+                }
+                let request = NetworkRequest::RotateNetworkKey(keypair);
+                self.channel.network_requests.send(request).log_error();
+            }
+
+            ExternalMessage::RegisterConsensusKey(keypair) => {
+                info!("Registering new consensus key: {}", keypair.public_key());
+                self.state.register_consensus_keys(keypair);
+            }
+
+            ExternalMessage::BanPeer(public_key, duration) => {
+                let expires_at =
+                    duration.map(|duration| self.system_state.current_time() + duration);
+                self.ban_peer(public_key, expires_at);
+            }
+
+            ExternalMessage::UnbanPeer(public_key) => self.unban_peer(public_key),
+
+            ExternalMessage::ConfirmAnchoring(confirmation) => {
+                self.handle_anchoring_confirmation(confirmation)
+            }
+
             ExternalMessage::Shutdown => self.handle_shutdown(),
             ExternalMessage::__NonExhaustive => unreachable!("Variant never created"),
         }
     }
 
     fn handle_timeout(&mut self, timeout: NodeTimeout) {
+        if self.bootstrap_mode {
+            if let NodeTimeout::Round(..) | NodeTimeout::Propose(..) = timeout {
+                return;
+            }
+        }
+
         match timeout {
             NodeTimeout::Round(height, round) => self.handle_round_timeout(height, round),
             NodeTimeout::Request(data, peer) => self.handle_request_timeout(&data, peer),
             NodeTimeout::Status(height) => self.handle_status_timeout(height),
             NodeTimeout::PeerExchange => self.handle_peer_exchange_timeout(),
+            NodeTimeout::DnsSeeds => self.handle_dns_seeds_timeout(),
             NodeTimeout::UpdateApiState => self.handle_update_api_state_timeout(),
             NodeTimeout::Propose(height, round) => self.handle_propose_timeout(height, round),
         }
     }
 
+    /// Saves the connect list to the node configuration, if the configuration manager is set.
+    pub(crate) fn store_connect_list(&mut self) {
+        if let Some(ref mut config_manager) = self.config_manager {
+            let connect_list_config =
+                ConnectListConfig::from_connect_list(&self.state.connect_list());
+
+            config_manager.store_connect_list(connect_list_config);
+        }
+    }
+
     /// Schedule execution for later time.
     pub(crate) fn execute_later(&mut self, event: InternalRequest) {
         self.channel.internal_requests.send(event).log_error();
     }
 
     /// Shutdown current node.
+    ///
+    /// The node stops participating in the consensus right away, so the current round
+    /// is aborted; the round number is persisted and is restored after restart.
     pub(crate) fn handle_shutdown(&mut self) {
+        info!("Shutting down the node");
+        // Stop proposing and voting.
+        self.is_enabled = false;
         // Send `Shutdown` to stop event-loop.
         self.execute_later(InternalRequest::Shutdown);
         // Flush transactions stored in tx_cache to persistent pool.
         self.flush_txs_into_pool();
         // Notify the blockchain about the shutdown.
         self.blockchain.shutdown();
+        // Close connections with peers, so they learn about the departure of the node
+        // without waiting for the network thread to be dropped.
+        self.channel
+            .network_requests
+            .send(NetworkRequest::Shutdown)
+            .log_error();
     }
 
     fn flush_txs_into_pool(&mut self) {
@@ -131,6 +195,8 @@ impl NodeHandler {
         for tx in self.state().tx_cache().values() {
             schema.add_transaction_into_pool(tx.clone());
         }
+        NodeSchema::new(&fork).transactions_cache().clear();
+        self.state.unsaved_txs_mut().clear();
 
         if self.blockchain.merge(fork.into_patch()).is_ok() {
             info!(
@@ -28,6 +28,23 @@
 //! such APIs are considered an implementation detail and are exempt from semantic versioning.
 //! (In other words, these APIs may change or be removed in any release without prior warning.)
 //!
+//! # Tracing
+//!
+//! Processing of messages and transactions by the node is instrumented with [`tracing`] spans:
+//!
+//! - `handle_message` and `handle_tx` for incoming messages and transactions
+//! - `create_block` and `execute_transaction` for block execution
+//! - `commit` and `commit_block` for committing blocks
+//! - `api_request` for HTTP API endpoints
+//!
+//! Spans include the transaction hash, block height and hash where applicable, so latency
+//! of each processing stage can be traced per transaction. To export spans, install
+//! a `tracing` subscriber in the node application. With the `otlp` crate feature,
+//! [`otlp::install_otlp_exporter`] installs a subscriber sending spans to an OTLP collector.
+//! If no subscriber is installed, spans have negligible overhead.
+//!
+//! [`tracing`]: https://docs.rs/tracing/
+//! [`otlp::install_otlp_exporter`]: otlp/fn.install_otlp_exporter.html
 //! [`Node`]: struct.Node.html
 //! [`NodeChannel`]: struct.NodeChannel.html
 //! [`ShutdownHandle`]: struct.ShutdownHandle.html
@@ -36,19 +53,26 @@
 // spell-checker:ignore cors
 
 pub use crate::{
+    anchoring::{AnchoringConfirmation, AnchoringHook, AnchoringSender},
     connect_list::{ConnectInfo, ConnectListConfig},
-    plugin::{NodePlugin, PluginApiContext, SharedNodeState},
+    plugin::{
+        BannedPeer, CacheUsage, ConnectionState, ConsensusMetrics, HeightMetrics, NodePlugin,
+        PeerConnection, PluginApiContext, RoundMetrics, SharedNodeState, ValidatorLiveness,
+    },
+    tx_selection::{
+        PoolTransactions, SelectTransactions, TransactionFees, TxPool, TxSelectionStrategy,
+    },
 };
 
 use exonum::{
     blockchain::{
         config::GenesisConfig, ApiSender, Blockchain, BlockchainBuilder, BlockchainMut,
-        ConsensusConfig, Schema, SendError,
+        ConsensusConfig, ProposeTimeoutStrategy, Schema, SendError,
     },
-    crypto::{self, Hash, PublicKey, SecretKey},
+    crypto::{self, Hash, KeyPair, PublicKey, SecretKey},
     helpers::{user_agent, Height, Milliseconds, Round, ValidateInput, ValidatorId},
-    keys::Keys,
-    merkledb::{Database, ObjectHash},
+    keys::{Keys, Signer},
+    merkledb::{BinaryValue, Database, ObjectHash, Snapshot},
     messages::{AnyTx, IntoMessage, SignedMessage, Verified},
     runtime::RuntimeInstance,
 };
@@ -58,17 +82,20 @@ use exonum_api::{
 };
 use failure::{ensure, format_err, Error};
 use futures::{sync::mpsc, Future, Sink};
-use log::{info, trace};
+use log::{error, info, trace, warn};
 use serde_derive::{Deserialize, Serialize};
 use tokio_core::reactor::Core;
 use tokio_threadpool::Builder as ThreadPoolBuilder;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     fmt,
     net::SocketAddr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
     thread,
     time::{Duration, SystemTime},
 };
@@ -79,13 +106,14 @@ use crate::{
         error::{into_failure, LogError},
         noise::HandshakeParams,
         EventHandler, HandlerPart, InternalEvent, InternalPart, InternalRequest, NetworkEvent,
-        NetworkPart, NetworkRequest, SyncSender, TimeoutRequest,
+        NetworkPart, NetworkRequest, SignMessageRequest, SyncSender, TimeoutRequest,
     },
     messages::Connect,
     schema::NodeSchema,
     state::{RequestData, State},
 };
 
+mod anchoring;
 mod basic;
 mod connect_list;
 mod consensus;
@@ -93,13 +121,18 @@ mod events;
 mod events_impl;
 pub mod helpers;
 mod messages;
+#[cfg(feature = "otlp")]
+pub mod otlp;
 mod plugin;
 mod proto;
 mod requests;
 #[cfg(test)]
 mod sandbox;
 mod schema;
+#[cfg(feature = "simulation")]
+pub mod simulation;
 mod state;
+mod tx_selection;
 
 // Logically private types re-exported for benchmarks.
 #[doc(hidden)]
@@ -120,8 +153,32 @@ pub mod _bench_types {
 pub enum ExternalMessage {
     /// Add a new connection.
     PeerAdd(ConnectInfo),
+    /// Drop the connection with a peer and remove it from the connect list. If the flag
+    /// is set, the peer stays in the connect list, so the node reconnects to it if the peer
+    /// is a validator.
+    PeerRemove(PublicKey, bool),
     /// Enable or disable the node.
     Enable(bool),
+    /// Replace the key pair used to authenticate the node in encrypted connections
+    /// with peers. The consensus key pair is not affected.
+    RotateNetworkKey(KeyPair),
+    /// Register a consensus key pair in advance. This is the first step of the consensus
+    /// key rotation; on the second step, the validator proposes a consensus configuration
+    /// replacing its consensus key via the supervisor service (the supervisor accepts such
+    /// changes only from the validator whose key is replaced). The node switches to
+    /// the registered key pair once the configuration is applied.
+    ///
+    /// If the key pair is not registered before the configuration is applied, the node
+    /// drops out of the consensus. The node keeps its original key to authenticate itself
+    /// among peers.
+    RegisterConsensusKey(KeyPair),
+    /// Ban a peer for the specified duration, or permanently if the duration is not specified.
+    /// The node disconnects from the banned peer and ignores its messages.
+    BanPeer(PublicKey, Option<Duration>),
+    /// Lift a ban from a peer.
+    UnbanPeer(PublicKey),
+    /// Record a confirmation that a block has been anchored to an external chain.
+    ConfirmAnchoring(AnchoringConfirmation),
     /// Shutdown the node.
     Shutdown,
     #[doc(hidden)]
@@ -143,6 +200,8 @@ pub(crate) enum NodeTimeout {
     UpdateApiState,
     /// Exchange peers timeout.
     PeerExchange,
+    /// Resolve DNS seeds timeout.
+    DnsSeeds,
 }
 
 /// A helper trait that provides the node with information about the state of the system such
@@ -167,6 +226,8 @@ pub(crate) struct NodeHandler {
     pub blockchain: BlockchainMut,
     /// Node plugins.
     plugins: Vec<Box<dyn NodePlugin>>,
+    /// Hooks notified about finalized blocks.
+    anchoring_hooks: Vec<Box<dyn AnchoringHook>>,
     /// State of the `NodeHandler`.
     state: State,
     /// System state.
@@ -175,6 +236,12 @@ pub(crate) struct NodeHandler {
     channel: NodeSender,
     /// Known peer addresses.
     peer_discovery: Vec<String>,
+    /// Interval between resolutions of DNS seeds.
+    dns_seeds_refresh_interval: Milliseconds,
+    /// Clock skew of validators that triggers a warning.
+    max_clock_skew: Milliseconds,
+    /// Policy of selecting transactions for proposals.
+    tx_selector: Box<dyn SelectTransactions>,
     /// Does this node participate in the consensus?
     is_enabled: bool,
     /// Node role.
@@ -183,6 +250,12 @@ pub(crate) struct NodeHandler {
     config_manager: Option<Box<dyn ConfigManager>>,
     /// Can we speed up Propose with transaction pressure?
     allow_expedited_propose: bool,
+    /// Maximum length of network messages, shared with the network thread.
+    max_message_len: Arc<AtomicU32>,
+    /// Does this node only serve blocks to other nodes without participating in the consensus?
+    bootstrap_mode: bool,
+    /// External signer of consensus messages.
+    consensus_signer: Option<Arc<dyn Signer>>,
 }
 
 /// HTTP API configuration options.
@@ -259,6 +332,22 @@ pub struct NetworkConfiguration {
     pub tcp_connect_retry_timeout: Milliseconds,
     /// Maximum number of retries when connecting to a peer.
     pub tcp_connect_max_retries: u64,
+    /// Cipher suite used to encrypt connections with peers.
+    ///
+    /// All peers in the network must use the same cipher suite; otherwise, the handshake
+    /// between them will fail.
+    #[serde(default)]
+    pub cipher_suite: CipherSuite,
+    /// Interval between resolutions of DNS seeds from the connect list.
+    #[serde(default = "NetworkConfiguration::default_dns_seeds_refresh_interval")]
+    pub dns_seeds_refresh_interval: Milliseconds,
+    /// Maximum clock skew between the node and other validators, in milliseconds.
+    ///
+    /// The skew is estimated from timestamps of precommits received from validators.
+    /// If the estimation exceeds this value, the node logs a warning; the estimations
+    /// are also available via the `validators_liveness` method of `SharedNodeState`.
+    #[serde(default = "NetworkConfiguration::default_max_clock_skew")]
+    pub max_clock_skew: Milliseconds,
 }
 
 impl Default for NetworkConfiguration {
@@ -270,10 +359,52 @@ impl Default for NetworkConfiguration {
             tcp_nodelay: true,
             tcp_connect_retry_timeout: 15_000,
             tcp_connect_max_retries: 10,
+            cipher_suite: CipherSuite::default(),
+            dns_seeds_refresh_interval: Self::default_dns_seeds_refresh_interval(),
+            max_clock_skew: Self::default_max_clock_skew(),
+        }
+    }
+}
+
+impl NetworkConfiguration {
+    fn default_dns_seeds_refresh_interval() -> Milliseconds {
+        60_000
+    }
+
+    fn default_max_clock_skew() -> Milliseconds {
+        1_000
+    }
+}
+
+/// Cipher suite of the Noise protocol used to encrypt connections with peers.
+///
+/// The handshake pattern is always `XK`, since it provides mutual authentication
+/// and requires the remote public key to be known in advance; the suites differ in the
+/// cryptographic primitives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CipherSuite {
+    /// `ChaCha20-Poly1305` cipher and `SHA-256` hash function.
+    ChaChaPolySha256,
+    /// `ChaCha20-Poly1305` cipher and `SHA-512` hash function.
+    ChaChaPolySha512,
+}
+
+impl CipherSuite {
+    /// Returns the name of Noise protocol parameters corresponding to the suite.
+    pub(crate) fn noise_params(self) -> &'static str {
+        match self {
+            CipherSuite::ChaChaPolySha256 => "Noise_XK_25519_ChaChaPoly_SHA256",
+            CipherSuite::ChaChaPolySha512 => "Noise_XK_25519_ChaChaPoly_SHA512",
         }
     }
 }
 
+impl Default for CipherSuite {
+    fn default() -> Self {
+        CipherSuite::ChaChaPolySha256
+    }
+}
+
 /// Events pool capacities.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct EventsPoolCapacity {
@@ -285,6 +416,10 @@ pub struct EventsPoolCapacity {
     internal_events_capacity: usize,
     /// Maximum number of queued requests from api.
     api_requests_capacity: usize,
+    /// Maximum number of transactions from peers awaiting signature verification.
+    /// If the limit is reached, new transactions from peers are dropped.
+    #[serde(default = "EventsPoolCapacity::default_tx_verification_capacity")]
+    tx_verification_capacity: usize,
 }
 
 impl Default for EventsPoolCapacity {
@@ -294,19 +429,37 @@ impl Default for EventsPoolCapacity {
             network_events_capacity: 512,
             internal_events_capacity: 128,
             api_requests_capacity: 1024,
+            tx_verification_capacity: Self::default_tx_verification_capacity(),
         }
     }
 }
 
+impl EventsPoolCapacity {
+    fn default_tx_verification_capacity() -> usize {
+        1024
+    }
+}
+
 /// Memory pool configuration parameters.
 ///
-/// The internal structure of this type is an implementation detail. For most applications,
-/// you should use the value returned by `Default::default()`.
+/// The internal structure of events pool capacities is an implementation detail.
+/// For most applications, you should use the value returned by `Default::default()`.
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct MemoryPoolConfig {
+    /// Policy of selecting transactions from the pool when the node proposes a block.
+    #[serde(default)]
+    pub tx_selection: TxSelectionStrategy,
     /// Sets the maximum number of messages that can be buffered on the event loop's
     /// notification channel before a send will fail.
     events_pool_capacity: EventsPoolCapacity,
+    /// Priorities of service instances keyed by the instance name. When the node proposes
+    /// a block, transactions to services with higher priorities are included first.
+    /// Services not mentioned here have zero priority.
+    ///
+    /// Note that if priorities are set, the node has to inspect all transactions
+    /// in the pool each time it creates a proposal.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub service_priorities: BTreeMap<String, u8>,
 }
 
 /// Configuration for the `Node`.
@@ -316,7 +469,11 @@ pub struct NodeConfig {
     pub consensus: ConsensusConfig,
     /// Network listening address.
     pub listen_address: SocketAddr,
-    /// Remote Network address used by this node.
+    /// Publicly reachable network address of the node advertised to its peers.
+    ///
+    /// The address may differ from `listen_address`, e.g., if the node is behind NAT
+    /// or a load balancer. Peers replace the address of the node in their connect lists
+    /// with the advertised one once they receive a `Connect` message from the node.
     pub external_address: String,
     /// P2P network configuration.
     pub network: NetworkConfiguration,
@@ -326,7 +483,8 @@ pub struct NodeConfig {
     pub mempool: MemoryPoolConfig,
     /// List of peers the node will connect to on start.
     pub connect_list: ConnectListConfig,
-    /// Number of threads allocated for transaction verification.
+    /// Number of threads allocated for signature verification of incoming messages.
+    /// If not set, the number of threads equals to the number of CPU cores.
     pub thread_pool_size: Option<u8>,
     /// Validator keys.
     #[serde(skip)]
@@ -400,6 +558,8 @@ pub(crate) struct Configuration {
     pub mempool: MemoryPoolConfig,
     /// Validator keys.
     pub keys: Keys,
+    /// Whether the node runs in the bootstrap mode.
+    pub bootstrap_mode: bool,
 }
 
 /// Channel for messages, timeouts and api requests. Consumed by the `NodeHandler` constructor.
@@ -416,7 +576,7 @@ pub(crate) struct NodeSender {
 }
 
 /// Node role.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum NodeRole {
     /// Validator node.
     Validator(ValidatorId),
@@ -467,6 +627,7 @@ impl NodeHandler {
         let snapshot = blockchain.snapshot();
         let consensus_config = Schema::new(&snapshot).consensus_config();
         info!("Creating a node with config: {:#?}", consensus_config);
+        let max_message_len = Arc::new(AtomicU32::new(consensus_config.max_message_len));
 
         let validator_id = consensus_config
             .validator_keys
@@ -474,6 +635,9 @@ impl NodeHandler {
             .position(|pk| pk.consensus_key == config.keys.consensus_pk())
             .map(|id| ValidatorId(id as u16));
         info!("Validator id = '{:?}'", validator_id);
+        if config.bootstrap_mode && validator_id.is_some() {
+            warn!("The node is a validator, but it will not participate in the consensus in the bootstrap mode");
+        }
         let connect = Verified::from_value(
             Connect::new(
                 external_address,
@@ -509,26 +673,76 @@ impl NodeHandler {
             blockchain,
             api_state,
             plugins: vec![],
+            anchoring_hooks: vec![],
             system_state,
             state,
             channel: sender,
             peer_discovery: config.peer_discovery,
+            dns_seeds_refresh_interval: config.network.dns_seeds_refresh_interval,
+            max_clock_skew: config.network.max_clock_skew,
+            tx_selector: config
+                .mempool
+                .tx_selection
+                .selector(config.mempool.service_priorities, None),
             is_enabled,
             node_role,
             config_manager,
             allow_expedited_propose: true,
+            max_message_len,
+            bootstrap_mode: config.bootstrap_mode,
+            consensus_signer: None,
         }
     }
 
+    /// Signs a message exchanged with peers by the key identifying the node.
     fn sign_message<T>(&self, message: T) -> Verified<T>
     where
         T: TryFrom<SignedMessage> + IntoMessage,
     {
-        Verified::from_value(
+        let identity = self.state.identity_keys();
+        Verified::from_value(message, identity.public_key(), identity.secret_key())
+    }
+
+    /// Signs a consensus message by the current consensus key.
+    ///
+    /// If consensus messages are signed by the external signer, the message is passed
+    /// to the signer asynchronously and `None` is returned. Once the message is signed,
+    /// it is processed by `handle_signed_message`.
+    fn sign_consensus_message<T>(&mut self, message: T) -> Option<Verified<T>>
+    where
+        T: TryFrom<SignedMessage> + IntoMessage,
+    {
+        let signer = self
+            .consensus_signer
+            .as_ref()
+            .filter(|_| self.state.external_consensus_key().is_some())
+            .cloned();
+        if let Some(signer) = signer {
+            let container: T::Container = message.into();
+            let request = SignMessageRequest::new(signer, container.into_bytes());
+            self.execute_later(InternalRequest::SignMessage(request));
+            return None;
+        }
+
+        let keys = self.state.keys();
+        Some(Verified::from_value(
             message,
-            self.state.keys().consensus_pk(),
-            self.state.keys().consensus_sk(),
-        )
+            keys.consensus_pk(),
+            keys.consensus_sk(),
+        ))
+    }
+
+    /// Makes the node sign consensus messages by the specified external signer.
+    /// The node is a validator if the public key of the signer is present
+    /// in the consensus configuration.
+    fn set_consensus_signer(&mut self, signer: Arc<dyn Signer>) {
+        self.state.set_external_consensus_key(signer.public_key());
+        self.consensus_signer = Some(signer);
+
+        let validator_id = self.state.validator_id();
+        info!("Validator id = '{:?}'", validator_id);
+        self.node_role = NodeRole::new(validator_id);
+        self.api_state.set_node_role(self.node_role);
     }
 
     /// Return internal `SharedNodeState`
@@ -578,6 +792,11 @@ impl NodeHandler {
         self.state().consensus_config().propose_timeout_threshold
     }
 
+    /// Returns the algorithm used to choose the propose timeout.
+    fn propose_timeout_strategy(&self) -> ProposeTimeoutStrategy {
+        self.state().consensus_config().propose_timeout_strategy
+    }
+
     /// Returns `State` of the node.
     pub(crate) fn state(&self) -> &State {
         &self.state
@@ -619,6 +838,23 @@ impl NodeHandler {
         self.state.jump_round(round);
         info!("Jump to round {}", round);
 
+        self.state
+            .restore_peer_identities(schema.peer_identities().iter());
+
+        // Recover transactions which were not moved to the persistent pool
+        // before the node was terminated.
+        let core_schema = Schema::new(&snapshot);
+        let transactions = core_schema.transactions();
+        for (hash, tx) in schema.transactions_cache().iter() {
+            if !transactions.contains(&hash) {
+                self.state.tx_cache_mut().insert(hash, tx);
+            }
+        }
+        let tx_cache_len = self.state.tx_cache_len();
+        if tx_cache_len > 0 {
+            info!("Recovered {} transactions from the cache", tx_cache_len);
+        }
+
         self.add_timeouts();
 
         // Recover cached consensus messages if any. We do this after main initialization and before
@@ -635,6 +871,10 @@ impl NodeHandler {
         self.add_status_timeout();
         self.add_peer_exchange_timeout();
         self.add_update_api_state_timeout();
+        if !self.state.connect_list().dns_seeds().is_empty() {
+            // Seeds are resolved right away to bootstrap connections with the peers.
+            self.handle_dns_seeds_timeout();
+        }
     }
 
     /// Sends the given message to a peer by its public key.
@@ -666,6 +906,10 @@ impl NodeHandler {
 
     /// Performs connection to the specified network address.
     fn connect(&mut self, key: PublicKey) {
+        if self.is_peer_banned(&key) {
+            trace!("Not connecting to banned peer {}", key);
+            return;
+        }
         let connect = self.state.our_connect_message().clone();
         self.send_to_peer(key, connect);
     }
@@ -681,6 +925,9 @@ impl NodeHandler {
 
     /// Adds request timeout if it isn't already requested.
     fn request(&mut self, data: RequestData, peer: PublicKey) {
+        // Consensus keys of validators may differ from the keys identifying them among peers
+        // if the consensus keys were rotated.
+        let peer = self.state.peer_identity(&peer);
         let is_new = self.state.request(data.clone(), peer);
         if is_new {
             self.add_request_timeout(data, None);
@@ -730,9 +977,39 @@ impl NodeHandler {
 
     fn need_faster_propose(&self) -> bool {
         let snapshot = self.blockchain.snapshot();
-        let pending_tx_count =
-            Schema::new(&snapshot).transactions_pool_len() + self.state.tx_cache_len() as u64;
-        pending_tx_count >= u64::from(self.propose_timeout_threshold())
+        let threshold = u64::from(self.propose_timeout_threshold());
+
+        match self.propose_timeout_strategy() {
+            ProposeTimeoutStrategy::Constant => false,
+            ProposeTimeoutStrategy::MempoolSize => {
+                let pending_tx_count = Schema::new(&snapshot).transactions_pool_len()
+                    + self.state.tx_cache_len() as u64;
+                pending_tx_count >= threshold
+            }
+            ProposeTimeoutStrategy::BlockFullness { blocks_window } => {
+                Self::average_block_tx_count(&*snapshot, blocks_window) >= threshold
+            }
+        }
+    }
+
+    /// Returns the average number of transactions in `blocks_window` latest blocks.
+    fn average_block_tx_count(snapshot: &dyn Snapshot, blocks_window: u32) -> u64 {
+        let schema = Schema::new(snapshot);
+        let block_hashes = schema.block_hashes_by_height();
+        let start = block_hashes.len().saturating_sub(u64::from(blocks_window));
+        let blocks = schema.blocks();
+
+        let (count, tx_count) = block_hashes
+            .iter_from(start)
+            .filter_map(|hash| blocks.get(&hash))
+            .fold((0, 0), |(count, tx_count), block| {
+                (count + 1, tx_count + u64::from(block.tx_count))
+            });
+        if count == 0 {
+            0
+        } else {
+            tx_count / count
+        }
     }
 
     /// Adds `NodeTimeout::Status` timeout to the channel.
@@ -756,6 +1033,13 @@ impl NodeHandler {
         self.add_timeout(NodeTimeout::PeerExchange, time);
     }
 
+    /// Adds `NodeTimeout::DnsSeeds` timeout to the channel.
+    fn add_dns_seeds_timeout(&mut self) {
+        let time = self.system_state.current_time()
+            + Duration::from_millis(self.dns_seeds_refresh_interval);
+        self.add_timeout(NodeTimeout::DnsSeeds, time);
+    }
+
     /// Adds `NodeTimeout::UpdateApiState` timeout to the channel.
     fn add_update_api_state_timeout(&mut self) {
         let time = self.system_state.current_time()
@@ -799,6 +1083,25 @@ impl fmt::Debug for NodeHandler {
     }
 }
 
+/// Shuts down the node gracefully once the process receives `SIGTERM`.
+#[cfg(unix)]
+fn shutdown_on_sigterm(handle: ShutdownHandle) -> impl Future<Item = (), Error = ()> {
+    use futures::Stream;
+    use log::{error, warn};
+    use tokio_signal::unix::{Signal, SIGTERM};
+
+    Signal::new(SIGTERM)
+        .flatten_stream()
+        .into_future()
+        .map_err(|(e, _)| error!("Cannot listen to SIGTERM: {}", e))
+        .and_then(move |_| {
+            info!("Received SIGTERM");
+            handle
+                .shutdown()
+                .map_err(|e| warn!("Cannot shut down the node: {}", e))
+        })
+}
+
 /// Handle allowing to shut down the node.
 #[derive(Debug, Clone)]
 pub struct ShutdownHandle {
@@ -879,6 +1182,10 @@ pub struct NodeChannel {
 pub trait ConfigManager: Send {
     /// Update connect list in the node configuration.
     fn store_connect_list(&mut self, connect_list: ConnectListConfig);
+
+    /// Save the key pair used to authenticate the node in connections with peers
+    /// after it has been rotated, so that the node keeps using it after restart.
+    fn store_network_keys(&mut self, keys: KeyPair);
 }
 
 /// Node capable of processing requests from external clients and participating in the consensus
@@ -890,8 +1197,9 @@ pub struct Node {
     network_config: NetworkConfiguration,
     handler: NodeHandler,
     channel: NodeChannel,
-    max_message_len: u32,
     thread_pool_size: Option<u8>,
+    tx_verification_capacity: usize,
+    network_keys: Option<KeyPair>,
 }
 
 impl Default for NodeChannel {
@@ -942,6 +1250,12 @@ pub struct NodeBuilder {
     node_config: NodeConfig,
     config_manager: Option<Box<dyn ConfigManager>>,
     plugins: Vec<Box<dyn NodePlugin>>,
+    anchoring_hooks: Vec<Box<dyn AnchoringHook>>,
+    bootstrap_mode: bool,
+    tx_selector: Option<Box<dyn SelectTransactions>>,
+    tx_fees: Option<Box<dyn TransactionFees>>,
+    consensus_signer: Option<Arc<dyn Signer>>,
+    network_keys: Option<KeyPair>,
 }
 
 impl fmt::Debug for NodeBuilder {
@@ -951,6 +1265,7 @@ impl fmt::Debug for NodeBuilder {
             .field("channel", &self.channel)
             .field("blockchain_builder", &self.blockchain_builder)
             .field("node_config", &self.node_config)
+            .field("bootstrap_mode", &self.bootstrap_mode)
             .finish()
     }
 }
@@ -979,6 +1294,12 @@ impl NodeBuilder {
             node_config,
             config_manager: None,
             plugins: vec![],
+            anchoring_hooks: vec![],
+            bootstrap_mode: false,
+            tx_selector: None,
+            tx_fees: None,
+            consensus_signer: None,
+            network_keys: None,
         }
     }
 
@@ -1014,16 +1335,93 @@ impl NodeBuilder {
         self
     }
 
+    /// Adds a hook notified about blocks finalized by the node, e.g., to anchor
+    /// the blockchain to an external chain.
+    pub fn with_anchoring_hook<T: AnchoringHook + 'static>(mut self, hook: T) -> Self {
+        self.anchoring_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Switches the node into the bootstrap mode.
+    ///
+    /// A node in the bootstrap mode does not participate in the consensus, does not process
+    /// transactions except for the ones from the blocks being synchronized and does not expose
+    /// HTTP API of services (API of plugins is still available). The node only synchronizes
+    /// with other nodes and serves blocks and their transactions to the peers which are
+    /// catching up, thus it can be used to scale out the synchronization bandwidth
+    /// of the network. Requests of such peers are served ahead of other messages.
+    /// The node should not be a validator.
+    pub fn with_bootstrap_mode(mut self) -> Self {
+        self.bootstrap_mode = true;
+        self
+    }
+
+    /// Sets a custom policy of selecting transactions for block proposals, which
+    /// overrides the strategy specified in the memory pool configuration.
+    pub fn with_tx_selector<T: SelectTransactions + 'static>(mut self, selector: T) -> Self {
+        self.tx_selector = Some(Box::new(selector));
+        self
+    }
+
+    /// Supplies fees paid by transactions, which are used to order transactions
+    /// to services with equal priorities when the node proposes a block.
+    ///
+    /// Fees are ignored if a custom policy is set with `with_tx_selector`.
+    pub fn with_tx_fees<T: TransactionFees + 'static>(mut self, fees: T) -> Self {
+        self.tx_fees = Some(Box::new(fees));
+        self
+    }
+
+    /// Delegates signing of consensus messages to the specified signer, e.g., a remote signer
+    /// daemon or an HSM. Messages are signed asynchronously; failed attempts to sign
+    /// a message are retried after a delay.
+    ///
+    /// The node acts as a validator if the public key of the signer is present
+    /// in the consensus configuration, so the secret key of the validator does not need
+    /// to be kept by the node. The consensus key pair in the node configuration should then
+    /// be a separate key pair, which is used only to identify the node among peers and
+    /// to authenticate peer connections (unless other keys are set with `with_network_keys`).
+    /// Peers address the validator by this key if its entry in their connect lists specifies
+    /// the consensus key of the validator in the `consensus_key` field.
+    ///
+    /// The signer is not used for service keys; the service key pair from the node
+    /// configuration is used to sign transactions of the node as usual.
+    /// If a new consensus key pair is registered and the consensus key is rotated,
+    /// messages are signed by the registered key pair.
+    pub fn with_consensus_signer<T: Signer + 'static>(mut self, signer: T) -> Self {
+        self.consensus_signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Sets the key pair used to authenticate the node in encrypted connections with peers.
+    /// If not set, the consensus key pair from the node configuration is used.
+    ///
+    /// Peers expect the public key of this pair to be specified in the `network_key` field
+    /// of their connect lists.
+    pub fn with_network_keys(mut self, keys: KeyPair) -> Self {
+        self.network_keys = Some(keys);
+        self
+    }
+
     /// Converts this builder into a `Node`.
     pub fn build(self) -> Node {
         let blockchain = self.blockchain_builder.build();
-        Node::with_blockchain(
+        let mut node = Node::with_blockchain(
             blockchain,
             self.channel,
             self.node_config,
             self.config_manager,
             self.plugins,
-        )
+            self.bootstrap_mode,
+            self.tx_selector,
+            self.tx_fees,
+        );
+        node.handler.anchoring_hooks = self.anchoring_hooks;
+        node.network_keys = self.network_keys;
+        if let Some(signer) = self.consensus_signer {
+            node.handler.set_consensus_signer(signer);
+        }
+        node
     }
 }
 
@@ -1035,16 +1433,30 @@ impl Node {
         node_cfg: NodeConfig,
         config_manager: Option<Box<dyn ConfigManager>>,
         plugins: Vec<Box<dyn NodePlugin>>,
+        bootstrap_mode: bool,
+        tx_selector: Option<Box<dyn SelectTransactions>>,
+        tx_fees: Option<Box<dyn TransactionFees>>,
     ) -> Self {
         crypto::init();
 
+        let tx_selector = tx_selector.or_else(|| {
+            let mempool = &node_cfg.mempool;
+            let priorities = mempool.service_priorities.clone();
+            tx_fees.map(|fees| mempool.tx_selection.selector(priorities, Some(fees)))
+        });
+
         let peers = node_cfg.connect_list.addresses();
+        let tx_verification_capacity = node_cfg
+            .mempool
+            .events_pool_capacity
+            .tx_verification_capacity;
         let config = Configuration {
             connect_list: ConnectList::from_config(node_cfg.connect_list),
             mempool: node_cfg.mempool,
             network: node_cfg.network,
             peer_discovery: peers,
             keys: node_cfg.keys,
+            bootstrap_mode,
         };
 
         let api_state = SharedNodeState::new(node_cfg.api.state_update_timeout as u64);
@@ -1065,17 +1477,13 @@ impl Node {
 
         let mut servers = HashMap::new();
         if let Some(listen_address) = api_cfg.public_api_address {
-            let server_config = WebServerConfig {
-                listen_address,
-                allow_origin: api_cfg.public_allow_origin.clone(),
-            };
+            let mut server_config = WebServerConfig::new(listen_address);
+            server_config.allow_origin = api_cfg.public_allow_origin.clone();
             servers.insert(ApiAccess::Public, server_config);
         }
         if let Some(listen_address) = api_cfg.private_api_address {
-            let server_config = WebServerConfig {
-                listen_address,
-                allow_origin: api_cfg.private_allow_origin.clone(),
-            };
+            let mut server_config = WebServerConfig::new(listen_address);
+            server_config.allow_origin = api_cfg.private_allow_origin.clone();
             servers.insert(ApiAccess::Private, server_config);
         }
 
@@ -1096,15 +1504,19 @@ impl Node {
             config_manager,
         );
         handler.plugins = plugins;
+        if let Some(tx_selector) = tx_selector {
+            handler.tx_selector = tx_selector;
+        }
 
         Self {
             api_options: api_cfg,
             handler,
             channel,
             network_config,
-            max_message_len: node_cfg.consensus.max_message_len,
             thread_pool_size: node_cfg.thread_pool_size,
+            tx_verification_capacity,
             api_manager_config: api_runtime_config,
+            network_keys: None,
         }
     }
 
@@ -1114,22 +1526,31 @@ impl Node {
         self.handler.initialize();
 
         let pool_size = self.thread_pool_size;
+        let shutdown_handle = self.shutdown_handle();
         let (handler_part, network_part, internal_part) = self.into_reactor();
         let handshake_params = handshake_params.clone();
 
-        let network_thread = thread::spawn(move || {
+        // Internal requests (including signature verification) are processed in a separate
+        // thread, so that a burst of incoming transactions does not slow down the network
+        // event loop. Signatures are verified on a dedicated thread pool.
+        let internal_thread = thread::spawn(move || {
             let mut core = Core::new().map_err(into_failure)?;
             let handle = core.handle();
 
             let mut pool_builder = ThreadPoolBuilder::new();
+            pool_builder.name_prefix("exonum-verifier-");
             if let Some(pool_size) = pool_size {
                 pool_builder.pool_size(pool_size as usize);
             }
             let thread_pool = pool_builder.build();
             let executor = thread_pool.sender().clone();
 
-            core.handle().spawn(internal_part.run(handle, executor));
+            core.run(internal_part.run(handle, executor))
+                .map_err(|_| format_err!("An error in the `Internal` thread occurred"))
+        });
 
+        let network_thread = thread::spawn(move || {
+            let mut core = Core::new().map_err(into_failure)?;
             let network_handler = network_part.run(&core.handle(), &handshake_params);
             core.run(network_handler)
                 .map(drop)
@@ -1137,10 +1558,15 @@ impl Node {
         });
 
         let mut core = Core::new().map_err(into_failure)?;
+        #[cfg(unix)]
+        core.handle().spawn(shutdown_on_sigterm(shutdown_handle));
+        #[cfg(not(unix))]
+        drop(shutdown_handle);
         core.run(handler_part.run())
             .map_err(|_| format_err!("An error in the `Handler` thread occurred"))?;
 
-        network_thread.join().unwrap()
+        network_thread.join().unwrap()?;
+        internal_thread.join().unwrap()
     }
 
     /// Launches a `Node` and optionally creates threads for public and private API handlers,
@@ -1149,13 +1575,19 @@ impl Node {
         trace!("Running node.");
 
         // Runs NodeHandler.
+        let network_keys = self
+            .network_keys
+            .clone()
+            .unwrap_or_else(|| self.state().keys().consensus.clone());
         let handshake_params = HandshakeParams::new(
-            self.state().keys().consensus_pk(),
-            self.state().keys().consensus_sk().to_owned(),
+            network_keys.public_key(),
+            network_keys.secret_key().to_owned(),
             self.state().connect_list().clone(),
             self.state().our_connect_message().clone(),
-            self.max_message_len,
-        );
+            self.state().config().max_message_len,
+        )
+        .with_cipher_suite(self.network_config.cipher_suite)
+        .with_shared_max_message_len(Arc::clone(&self.handler.max_message_len));
         self.run_handler(&handshake_params)?;
         Ok(())
     }
@@ -1163,7 +1595,16 @@ impl Node {
     fn into_reactor(self) -> (HandlerPart<impl EventHandler>, NetworkPart, InternalPart) {
         let connect_message = self.state().our_connect_message().clone();
         let connect_list = self.state().connect_list().clone();
-        let api_manager = ApiManager::new(self.api_manager_config, self.channel.endpoints.1);
+        let max_message_len = self.state().config().max_message_len;
+        let endpoints_rx = if self.handler.bootstrap_mode {
+            // Service endpoints are not exposed in the bootstrap mode, thus updates of endpoints
+            // are not received. `ApiManager` keeps serving the initial endpoints once
+            // the stream of updates is finished.
+            mpsc::channel(0).1
+        } else {
+            self.channel.endpoints.1
+        };
+        let api_manager = ApiManager::new(self.api_manager_config, endpoints_rx);
         SystemRuntime::start(api_manager).expect("Failed to start api_runtime.");
         let (network_tx, network_rx) = self.channel.network_events;
         let internal_requests_rx = self.channel.internal_requests.1;
@@ -1173,7 +1614,7 @@ impl Node {
             network_requests: self.channel.network_requests,
             network_tx,
             network_config: self.network_config,
-            max_message_len: self.max_message_len,
+            max_message_len,
             connect_list,
         };
 
@@ -1189,6 +1630,7 @@ impl Node {
         let internal_part = InternalPart {
             internal_tx,
             internal_requests_rx,
+            tx_verification_capacity: self.tx_verification_capacity,
         };
         (handler_part, network_part, internal_part)
     }
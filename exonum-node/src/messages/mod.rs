@@ -68,7 +68,7 @@ pub enum Consensus {
 }
 
 impl Consensus {
-    fn as_raw(&self) -> &SignedMessage {
+    pub(crate) fn as_raw(&self) -> &SignedMessage {
         match self {
             Consensus::Precommit(ref msg) => msg.as_raw(),
             Consensus::Propose(ref msg) => msg.as_raw(),
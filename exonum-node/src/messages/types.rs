@@ -586,13 +586,14 @@ impl PeersRequest {
     }
 }
 
-/// Request for the block with the given `height`.
+/// Request for `count` consecutive blocks starting from the given `height`.
 ///
 /// ### Validation
 /// The message is ignored if its `height` is bigger than the node's one.
 ///
 /// ### Processing
-/// `BlockResponse` message is sent as the response.
+/// A `BlockResponse` message is sent as the response for each requested block
+/// known to the node. The number of responses is additionally limited by the node.
 ///
 /// ### Generation
 /// This message can be sent during `Status` processing.
@@ -603,12 +604,19 @@ pub struct BlockRequest {
     pub to: PublicKey,
     /// The height to which the message is related.
     pub height: Height,
+    /// Number of requested blocks. Zero value is treated as a request for a single block.
+    pub count: u32,
 }
 
 impl BlockRequest {
-    /// Create new `BlockRequest`.
+    /// Create new `BlockRequest` for a single block.
     pub fn new(to: PublicKey, height: Height) -> Self {
-        Self { to, height }
+        Self::with_count(to, height, 1)
+    }
+
+    /// Create new `BlockRequest` for `count` consecutive blocks starting from `height`.
+    pub fn with_count(to: PublicKey, height: Height, count: u32) -> Self {
+        Self { to, height, count }
     }
     /// Public key of the recipient.
     pub fn to(&self) -> &PublicKey {
@@ -618,6 +626,10 @@ impl BlockRequest {
     pub fn height(&self) -> Height {
         self.height
     }
+    /// Number of requested blocks.
+    pub fn count(&self) -> u32 {
+        std::cmp::max(self.count, 1)
+    }
 }
 
 impl BlockResponse {
@@ -0,0 +1,75 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of `tracing` spans to OpenTelemetry collectors via OTLP.
+//!
+//! This module is available with the `otlp` crate feature.
+
+use opentelemetry::{
+    sdk::{trace, Resource},
+    KeyValue,
+};
+use opentelemetry_otlp::Uninstall;
+use tracing_subscriber::layer::SubscriberExt;
+
+use std::fmt;
+
+/// Configuration of the OTLP exporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpConfig {
+    /// Address of the OTLP collector, e.g., `http://localhost:4317`.
+    pub endpoint: String,
+    /// Name of the service reported to the collector, which is used to tell spans
+    /// of different nodes apart.
+    pub service_name: String,
+}
+
+/// Guard of the installed OTLP exporter. Remaining spans are flushed and the exporter
+/// is shut down once the guard is dropped.
+pub struct OtlpGuard {
+    _uninstall: Uninstall,
+}
+
+impl fmt::Debug for OtlpGuard {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("OtlpGuard").finish()
+    }
+}
+
+/// Installs a global `tracing` subscriber exporting spans of the node to the OTLP collector
+/// specified in the `config`. The guard returned by the function should be kept alive
+/// while the node is running.
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be created, or if the global `tracing` subscriber
+/// is already installed.
+pub fn install_otlp_exporter(config: &OtlpConfig) -> Result<OtlpGuard, failure::Error> {
+    let resource = Resource::new(vec![KeyValue::new(
+        "service.name",
+        config.service_name.clone(),
+    )]);
+    let (tracer, uninstall) = opentelemetry_otlp::new_pipeline()
+        .with_endpoint(config.endpoint.as_str())
+        .with_trace_config(trace::config().with_resource(resource))
+        .install()
+        .map_err(|e| failure::format_err!("Cannot install OTLP exporter: {}", e))?;
+
+    let subscriber =
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(OtlpGuard {
+        _uninstall: uninstall,
+    })
+}
@@ -12,21 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
 use exonum::{
     blockchain::{ApiSender, Blockchain, ValidatorKeys},
-    helpers::Milliseconds,
+    crypto::PublicKey,
+    helpers::{Height, Milliseconds, Round, ValidatorId},
     merkledb::Snapshot,
 };
 use exonum_api::ApiBuilder;
+use serde_derive::{Deserialize, Serialize};
 
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     fmt,
     sync::{Arc, RwLock},
+    time::SystemTime,
 };
 
 use crate::{
-    events::network::ConnectedPeerAddr, state::State, ConnectInfo, ExternalMessage, NodeRole,
+    events::network::ConnectedPeerAddr, state::State, AnchoringConfirmation, ConnectInfo,
+    ExternalMessage, NodeRole,
 };
 
 #[derive(Debug, Default)]
@@ -39,6 +44,13 @@ struct ApiNodeState {
     majority_count: usize,
     validators: Vec<ValidatorKeys>,
     tx_cache_len: usize,
+    banned_peers: Vec<BannedPeer>,
+    consensus_metrics: Option<ConsensusMetrics>,
+    is_leader: bool,
+    validators_liveness: Vec<ValidatorLiveness>,
+    peer_connections: Vec<PeerConnection>,
+    cache_usage: CacheUsage,
+    anchoring_confirmations: BTreeMap<String, AnchoringConfirmation>,
 }
 
 impl ApiNodeState {
@@ -50,6 +62,136 @@ impl ApiNodeState {
     }
 }
 
+/// Information about a peer banned by the node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BannedPeer {
+    /// Public key of the peer.
+    pub public_key: PublicKey,
+    /// Time when the ban expires. `None` means that the ban is permanent.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// State of the connection with a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// The peer has connected to the node.
+    Incoming,
+    /// The node has connected to the peer.
+    Outgoing,
+    /// There is no connection with the peer.
+    Disconnected,
+}
+
+/// Connection with a peer from the connect list of the node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerConnection {
+    /// Consensus key of the peer.
+    pub public_key: PublicKey,
+    /// Address of the peer specified in the connect list.
+    pub address: String,
+    /// State of the connection with the peer.
+    pub state: ConnectionState,
+    /// Whether the peer is a validator according to the current consensus configuration.
+    pub is_validator: bool,
+}
+
+/// Metrics of a single consensus round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundMetrics {
+    /// Round number.
+    pub round: Round,
+    /// Identifier of the validator which was the leader of the round.
+    pub leader: ValidatorId,
+    /// Time spent by the node in the round, in milliseconds.
+    pub duration: Milliseconds,
+}
+
+/// Metrics of a height committed by the consensus algorithm.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeightMetrics {
+    /// Height of the committed block.
+    pub height: Height,
+    /// Time spent by the node at the height, in milliseconds.
+    pub duration: Milliseconds,
+    /// Rounds passed by the node at the height. The last round is the one
+    /// in which the block was committed.
+    pub rounds: Vec<RoundMetrics>,
+    /// Identifier of the validator which proposed the committed block.
+    pub proposer_id: ValidatorId,
+    /// Identifiers of the validators whose precommits were used to commit the block.
+    /// Validators missing from this list were not fast enough to vote for the block.
+    pub precommits: Vec<ValidatorId>,
+}
+
+/// Liveness of a validator as seen by the node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorLiveness {
+    /// Identifier of the validator.
+    pub id: ValidatorId,
+    /// Consensus key of the validator.
+    pub consensus_key: PublicKey,
+    /// Whether the node is connected to the validator. Always `true` for the node itself.
+    pub is_connected: bool,
+    /// The latest height reported by the validator.
+    pub height: Height,
+    /// The latest round at the current height in which the node has received messages
+    /// from the validator, or `None` if there were no such messages.
+    pub round: Option<Round>,
+    /// Estimated difference between the local clock of the node and the clock of the validator,
+    /// in milliseconds, or `None` if the node has not received precommits from the validator yet.
+    ///
+    /// The skew is estimated from timestamps of the validator precommits, thus it includes
+    /// the network latency. A positive value means that the validator clock is behind.
+    pub clock_skew: Option<i64>,
+}
+
+/// Consensus metrics of the node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusMetrics {
+    /// Current height of the node.
+    pub height: Height,
+    /// Current round of the node.
+    pub round: Round,
+    /// Time spent by the node at the current height, in milliseconds.
+    pub height_duration: Milliseconds,
+    /// Time spent by the node in the current round, in milliseconds.
+    pub round_duration: Milliseconds,
+    /// Number of consensus messages from future rounds or heights queued for processing.
+    pub queued_messages: usize,
+    /// Number of blocks received ahead of the current height during synchronization.
+    pub queued_blocks: usize,
+    /// Number of pending requests to other nodes.
+    pub pending_requests: usize,
+    /// Number of unknown transactions required to process received proposals.
+    pub unknown_txs: usize,
+    /// Size of the transaction cache.
+    pub tx_cache_len: usize,
+    /// Metrics of the recently committed heights, from the oldest to the newest.
+    /// Heights committed during synchronization with other nodes are not included.
+    pub committed_heights: Vec<HeightMetrics>,
+}
+
+/// Usage of the in-memory caches of the node.
+///
+/// Sizes of the cached messages are measured in their serialized form, so they only
+/// approximate the memory consumed by the caches.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheUsage {
+    /// Number of transactions in the transaction cache.
+    pub tx_cache_len: usize,
+    /// Total size of transactions in the transaction cache, in bytes.
+    pub tx_cache_size: usize,
+    /// Number of consensus messages from future rounds or heights queued for processing.
+    pub queued_messages: usize,
+    /// Total size of the queued consensus messages, in bytes.
+    pub queued_messages_size: usize,
+    /// Number of blocks received ahead of the current height during synchronization.
+    pub queued_blocks: usize,
+    /// Total size of the queued blocks, in bytes.
+    pub queued_blocks_size: usize,
+}
+
 /// Shared part of the context, used to take some values from the `Node`.
 /// As there is no way to directly access the node state, this entity is
 /// regularly updated with information about the node and transfers this
@@ -123,8 +265,64 @@ impl SharedNodeState {
         state.is_enabled
     }
 
+    /// Returns a list of peers currently banned by the node.
+    pub fn banned_peers(&self) -> Vec<BannedPeer> {
+        let state = self.node.read().expect("Expected read lock.");
+        state.banned_peers.clone()
+    }
+
+    /// Returns consensus metrics of the node, or `None` if the node has not reported
+    /// its state yet.
+    pub fn consensus_metrics(&self) -> Option<ConsensusMetrics> {
+        let state = self.node.read().expect("Expected read lock.");
+        state.consensus_metrics.clone()
+    }
+
+    /// Returns a boolean value which indicates whether the node is the leader
+    /// of the current consensus round.
+    pub fn is_leader(&self) -> bool {
+        let state = self.node.read().expect("Expected read lock.");
+        state.is_leader
+    }
+
+    /// Returns liveness of the validators as seen by the node.
+    pub fn validators_liveness(&self) -> Vec<ValidatorLiveness> {
+        let state = self.node.read().expect("Expected read lock.");
+        state.validators_liveness.clone()
+    }
+
+    /// Returns connections with the peers from the connect list of the node.
+    pub fn peer_connections(&self) -> Vec<PeerConnection> {
+        let state = self.node.read().expect("Expected read lock.");
+        state.peer_connections.clone()
+    }
+
+    /// Returns usage of the in-memory caches of the node.
+    pub fn cache_usage(&self) -> CacheUsage {
+        let state = self.node.read().expect("Expected read lock.");
+        state.cache_usage.clone()
+    }
+
+    /// Returns the latest anchoring confirmations reported to the node, one per
+    /// external chain. Confirmations are not persisted across node restarts.
+    pub fn anchoring_confirmations(&self) -> Vec<AnchoringConfirmation> {
+        let state = self.node.read().expect("Expected read lock.");
+        state.anchoring_confirmations.values().cloned().collect()
+    }
+
+    pub(crate) fn add_anchoring_confirmation(&self, confirmation: AnchoringConfirmation) {
+        let mut state = self.node.write().expect("Expected write lock.");
+        let latest = state
+            .anchoring_confirmations
+            .entry(confirmation.chain.clone())
+            .or_insert_with(|| confirmation.clone());
+        if latest.height < confirmation.height {
+            *latest = confirmation;
+        }
+    }
+
     /// Updates internal state, from `State` of a blockchain node.
-    pub(crate) fn update_node_state(&self, state: &State) {
+    pub(crate) fn update_node_state(&self, state: &State, now: SystemTime) {
         let mut lock = self.node.write().expect("Expected write lock.");
 
         lock.incoming_connections.clear();
@@ -133,6 +331,35 @@ impl SharedNodeState {
         lock.node_role = NodeRole::new(state.validator_id());
         lock.validators = state.validators().to_vec();
         lock.tx_cache_len = state.tx_cache_len();
+        lock.banned_peers = state
+            .banned_peers(now)
+            .map(|(public_key, expires_at)| BannedPeer {
+                public_key,
+                expires_at: expires_at.map(DateTime::from),
+            })
+            .collect();
+        lock.consensus_metrics = Some(state.consensus_metrics(now));
+        lock.is_leader = state.is_leader();
+        lock.validators_liveness = state.validators_liveness();
+        lock.cache_usage = state.cache_usage();
+        lock.peer_connections = state
+            .connect_list()
+            .peers()
+            .into_iter()
+            .map(|info| {
+                let connection_state = match state.connections().get(&info.public_key) {
+                    Some(ConnectedPeerAddr::In(_)) => ConnectionState::Incoming,
+                    Some(ConnectedPeerAddr::Out(..)) => ConnectionState::Outgoing,
+                    None => ConnectionState::Disconnected,
+                };
+                PeerConnection {
+                    public_key: info.public_key,
+                    is_validator: state.peer_is_validator(&info.public_key),
+                    address: info.address,
+                    state: connection_state,
+                }
+            })
+            .collect();
 
         for (public_key, addr) in state.connections() {
             match addr {
@@ -140,6 +367,8 @@ impl SharedNodeState {
                     let conn_info = ConnectInfo {
                         address: addr.to_string(),
                         public_key: *public_key,
+                        network_key: None,
+                        consensus_key: None,
                     };
                     lock.incoming_connections.insert(conn_info);
                 }
@@ -147,6 +376,8 @@ impl SharedNodeState {
                     let conn_info = ConnectInfo {
                         address: addr.to_string(),
                         public_key: *public_key,
+                        network_key: None,
+                        consensus_key: None,
                     };
                     lock.outgoing_connections.insert(conn_info);
                 }
@@ -166,6 +397,11 @@ impl SharedNodeState {
         node.node_role = role;
     }
 
+    pub(crate) fn node_role(&self) -> NodeRole {
+        let node = self.node.read().expect("Expected read lock.");
+        node.node_role
+    }
+
     /// Returns the value of the `state_update_timeout`.
     pub fn state_update_timeout(&self) -> Milliseconds {
         self.state_update_timeout
@@ -15,12 +15,13 @@
 use exonum::{
     blockchain::{get_transaction, Schema},
     crypto::{Hash, PublicKey},
+    helpers::Height,
     merkledb::BinaryValue,
     messages::Verified,
 };
 use log::{error, trace};
 
-use std::mem;
+use std::{cmp, mem};
 
 use crate::{
     messages::{
@@ -28,6 +29,7 @@ use crate::{
         Requests, TransactionsRequest, TransactionsResponse, TX_RES_EMPTY_SIZE,
         TX_RES_PB_OVERHEAD_PAYLOAD,
     },
+    state::{BLOCK_REQUEST_BATCH_SIZE, PEER_REQUESTS_LIMIT},
     NodeHandler,
 };
 
@@ -38,7 +40,7 @@ impl NodeHandler {
     /// Validates request, then redirects it to the corresponding `handle_...` function.
     pub(crate) fn handle_request(&mut self, msg: &Requests) {
         // Request are sent to us
-        if msg.to() != self.state.keys().consensus_pk() {
+        if msg.to() != self.state.identity_keys().public_key() {
             error!("Received message addressed to other peer = {:?}.", msg.to());
             return;
         }
@@ -51,6 +53,12 @@ impl NodeHandler {
             return;
         }
 
+        let now = self.system_state.current_time();
+        if self.state.add_peer_request(msg.author(), now) > PEER_REQUESTS_LIMIT {
+            self.report_peer_violation(msg.author(), "too many requests");
+            return;
+        }
+
         match msg {
             Requests::ProposeRequest(ref msg) => self.handle_request_propose(msg),
             Requests::TransactionsRequest(ref msg) => self.handle_request_txs(msg),
@@ -166,19 +174,24 @@ impl NodeHandler {
         let snapshot = self.blockchain.snapshot();
         let schema = Schema::new(&snapshot);
 
-        let height = msg.payload().height();
-        let block_hash = schema.block_hash_by_height(height).unwrap();
+        let first_height = msg.payload().height().0;
+        let count = cmp::min(msg.payload().count(), BLOCK_REQUEST_BATCH_SIZE);
+        let last_height = cmp::min(first_height + u64::from(count), self.state.height().0);
 
-        let block = schema.blocks().get(&block_hash).unwrap();
-        let precommits = schema.precommits(&block_hash);
-        let transactions = schema.block_transactions(height);
+        for height in (first_height..last_height).map(Height) {
+            let block_hash = schema.block_hash_by_height(height).unwrap();
 
-        let block_msg = self.sign_message(BlockResponse::new(
-            msg.author(),
-            block,
-            precommits.iter().map(|p| p.to_bytes()),
-            transactions.iter(),
-        ));
-        self.send_to_peer(msg.author(), block_msg);
+            let block = schema.blocks().get(&block_hash).unwrap();
+            let precommits = schema.precommits(&block_hash);
+            let transactions = schema.block_transactions(height);
+
+            let block_msg = self.sign_message(BlockResponse::new(
+                msg.author(),
+                block,
+                precommits.iter().map(|p| p.to_bytes()),
+                transactions.iter(),
+            ));
+            self.send_to_peer(msg.author(), block_msg);
+        }
     }
 }
@@ -23,7 +23,7 @@ use exonum::{
     blockchain::{
         config::{GenesisConfig, GenesisConfigBuilder, InstanceInitParams},
         contains_transaction, Block, BlockProof, Blockchain, BlockchainBuilder, BlockchainMut,
-        ConsensusConfig, Schema, ValidatorKeys,
+        ConsensusConfig, LeaderRotation, ProposeTimeoutStrategy, Schema, ValidatorKeys,
     },
     crypto::{gen_keypair_from_seed, Hash, PublicKey, SecretKey, Seed, SEED_LENGTH},
     helpers::{user_agent, Height, Round, ValidatorId},
@@ -39,7 +39,7 @@ use std::{
     cell::{Ref, RefCell, RefMut},
     collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque},
     convert::TryFrom,
-    fmt::Debug,
+    fmt::{self, Debug},
     iter::FromIterator,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     ops::{AddAssign, Deref, DerefMut},
@@ -56,17 +56,18 @@ use self::{
 use crate::{
     connect_list::ConnectList,
     events::{
-        Event, EventHandler, InternalEvent, InternalRequest, NetworkEvent, NetworkRequest,
-        TimeoutRequest,
+        Event, EventHandler, InternalEvent, InternalPart, InternalRequest, NetworkEvent,
+        NetworkRequest, TimeoutRequest,
     },
     messages::{
         BlockRequest, BlockResponse, Connect, ExonumMessage, Message, PeersRequest,
         PoolTransactionsRequest, Prevote, PrevotesRequest, Propose, ProposeRequest, Status,
         TransactionsRequest, TransactionsResponse,
     },
-    state::State,
+    state::{State, BLOCK_REQUEST_BATCH_SIZE},
     ApiSender, Configuration, ConnectInfo, ConnectListConfig, ExternalMessage,
-    NetworkConfiguration, NodeHandler, NodeSender, SharedNodeState, SystemStateProvider,
+    NetworkConfiguration, NodeHandler, NodeRole, NodeSender, SharedNodeState, SystemStateProvider,
+    TransactionFees, TxSelectionStrategy,
 };
 
 pub type SharedTime = Arc<Mutex<SystemTime>>;
@@ -123,7 +124,10 @@ impl SandboxInner {
                         let msg = Message::from_signed(msg).expect("Expected valid message.");
                         self.sent.push_back((peer, msg))
                     }
-                    NetworkRequest::DisconnectWithPeer(_) | NetworkRequest::Shutdown => {}
+                    NetworkRequest::DisconnectWithPeer(_)
+                    | NetworkRequest::Shutdown
+                    | NetworkRequest::RotateNetworkKey(_)
+                    | NetworkRequest::DiscoverPeers(_) => {}
                 }
             }
             Ok(())
@@ -151,7 +155,22 @@ impl SandboxInner {
                             .handle_event(InternalEvent::message_verified(msg).into())
                     }
 
-                    InternalRequest::Shutdown => unreachable!(),
+                    InternalRequest::VerifyMessages(raw) => {
+                        for msg in InternalPart::verify_batch(raw) {
+                            self.handler
+                                .handle_event(InternalEvent::message_verified(msg).into())
+                        }
+                    }
+
+                    InternalRequest::SignMessage(request) => {
+                        if let Some(msg) = InternalPart::sign_message_now(request) {
+                            self.handler
+                                .handle_event(InternalEvent::message_signed(msg).into())
+                        }
+                    }
+
+                    // The sandbox does not run the event loop, so there is nothing to stop.
+                    InternalRequest::Shutdown => {}
                 }
             }
             Ok(())
@@ -231,7 +250,7 @@ impl Sandbox {
         self.addresses[id].address.clone()
     }
 
-    /// Creates a `BlockRequest` message signed by this validator.
+    /// Creates a `BlockRequest` message for a batch of blocks signed by this validator.
     pub fn create_block_request(
         &self,
         author: PublicKey,
@@ -239,7 +258,29 @@ impl Sandbox {
         height: Height,
         secret_key: &SecretKey,
     ) -> Verified<BlockRequest> {
-        Verified::from_value(BlockRequest::new(to, height), author, secret_key)
+        self.create_block_request_with_count(
+            author,
+            to,
+            height,
+            BLOCK_REQUEST_BATCH_SIZE,
+            secret_key,
+        )
+    }
+
+    /// Creates a `BlockRequest` message for `count` blocks signed by this validator.
+    pub fn create_block_request_with_count(
+        &self,
+        author: PublicKey,
+        to: PublicKey,
+        height: Height,
+        count: u32,
+        secret_key: &SecretKey,
+    ) -> Verified<BlockRequest> {
+        Verified::from_value(
+            BlockRequest::with_count(to, height, count),
+            author,
+            secret_key,
+        )
     }
 
     /// Creates a `Status` message signed by this validator.
@@ -462,6 +503,21 @@ impl Sandbox {
         Ref::map(self.inner.borrow(), |inner| inner.handler.state())
     }
 
+    /// Returns the role of the node and the role exposed via the node API state.
+    pub(crate) fn node_roles(&self) -> (NodeRole, NodeRole) {
+        let inner = self.inner.borrow();
+        (inner.handler.node_role, inner.handler.api_state.node_role())
+    }
+
+    /// Updates the node state exposed via the API, as it is done on the `UpdateApiState`
+    /// timeout, and returns this state.
+    pub(crate) fn update_api_state(&self) -> SharedNodeState {
+        let mut inner = self.inner.borrow_mut();
+        inner.handler.handle_update_api_state_timeout();
+        inner.process_events();
+        inner.handler.api_state.clone()
+    }
+
     pub fn blockchain(&self) -> Blockchain {
         self.inner.borrow().handler.blockchain.as_ref().clone()
     }
@@ -487,6 +543,11 @@ impl Sandbox {
         self.inner.borrow_mut().process_events();
     }
 
+    /// Sends an external message to the node.
+    pub fn send_external_message(&self, msg: ExternalMessage) {
+        self.inner.borrow_mut().handle_event(msg);
+    }
+
     pub fn pop_sent_message(&self) -> Option<(PublicKey, Message)> {
         self.inner.borrow_mut().sent.pop_front()
     }
@@ -886,6 +947,7 @@ impl Sandbox {
             peer_discovery: Vec::new(),
             mempool: Default::default(),
             keys,
+            bootstrap_mode: false,
         };
 
         let system_state = SandboxSystemStateProvider {
@@ -927,15 +989,14 @@ impl Sandbox {
     }
 
     fn node_public_key(&self) -> PublicKey {
-        self.node_state().keys().consensus_pk()
+        self.node_state().identity_keys().public_key()
     }
 
     fn node_secret_key(&self) -> SecretKey {
-        self.node_state().keys().consensus_sk().to_owned()
+        self.node_state().identity_keys().secret_key().to_owned()
     }
 }
 
-#[derive(Debug)]
 pub struct SandboxBuilder {
     initialize: bool,
     services: Vec<InstanceInitParams>,
@@ -944,6 +1005,24 @@ pub struct SandboxBuilder {
     rust_runtime: RustRuntimeBuilder,
     instances: Vec<InstanceInitParams>,
     artifacts: HashMap<ArtifactId, Vec<u8>>,
+    service_priorities: BTreeMap<String, u8>,
+    tx_selection: TxSelectionStrategy,
+    tx_fees: Option<Box<dyn TransactionFees>>,
+    bootstrap_mode: bool,
+}
+
+impl fmt::Debug for SandboxBuilder {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("SandboxBuilder")
+            .field("initialize", &self.initialize)
+            .field("validators_count", &self.validators_count)
+            .field("consensus_config", &self.consensus_config)
+            .field("service_priorities", &self.service_priorities)
+            .field("tx_selection", &self.tx_selection)
+            .field("bootstrap_mode", &self.bootstrap_mode)
+            .finish()
+    }
 }
 
 impl Default for SandboxBuilder {
@@ -961,11 +1040,17 @@ impl Default for SandboxBuilder {
                 min_propose_timeout: PROPOSE_TIMEOUT,
                 max_propose_timeout: PROPOSE_TIMEOUT,
                 propose_timeout_threshold: std::u32::MAX,
+                propose_timeout_strategy: ProposeTimeoutStrategy::default(),
+                leader_rotation: LeaderRotation::default(),
                 validator_keys: Vec::default(),
             },
             rust_runtime: RustRuntimeBuilder::new(),
             instances: Vec::new(),
             artifacts: HashMap::new(),
+            service_priorities: BTreeMap::new(),
+            tx_selection: TxSelectionStrategy::default(),
+            tx_fees: None,
+            bootstrap_mode: false,
         }
     }
 }
@@ -1031,6 +1116,31 @@ impl SandboxBuilder {
         self
     }
 
+    /// Sets priority of transactions to the service instance with the given name.
+    pub fn with_service_priority(mut self, instance_name: &str, priority: u8) -> Self {
+        self.service_priorities
+            .insert(instance_name.to_owned(), priority);
+        self
+    }
+
+    /// Sets the strategy of selecting transactions for proposals.
+    pub fn with_tx_selection(mut self, strategy: TxSelectionStrategy) -> Self {
+        self.tx_selection = strategy;
+        self
+    }
+
+    /// Supplies fees paid by transactions to the transaction selection strategy.
+    pub fn with_tx_fees(mut self, fees: impl TransactionFees + 'static) -> Self {
+        self.tx_fees = Some(Box::new(fees));
+        self
+    }
+
+    /// Runs the node in the bootstrap mode.
+    pub fn with_bootstrap_mode(mut self) -> Self {
+        self.bootstrap_mode = true;
+        self
+    }
+
     pub fn build(self) -> Sandbox {
         let mut sandbox = sandbox_with_services_uninitialized(
             self.rust_runtime,
@@ -1040,6 +1150,10 @@ impl SandboxBuilder {
             self.validators_count,
         );
 
+        sandbox.inner.borrow_mut().handler.tx_selector = self
+            .tx_selection
+            .selector(self.service_priorities, self.tx_fees);
+        sandbox.inner.borrow_mut().handler.bootstrap_mode = self.bootstrap_mode;
         sandbox.inner.borrow_mut().sent.clear(); // To clear initial connect messages.
         if self.initialize {
             let time = sandbox.time();
@@ -1127,6 +1241,8 @@ fn sandbox_with_services_uninitialized(
         .map(|(keys, a)| ConnectInfo {
             address: a.clone(),
             public_key: keys.consensus_pk(),
+            network_key: None,
+            consensus_key: None,
         })
         .collect();
 
@@ -1163,6 +1279,7 @@ fn sandbox_with_services_uninitialized(
         peer_discovery: Vec::new(),
         mempool: Default::default(),
         keys: keys[0].clone(),
+        bootstrap_mode: false,
     };
 
     let system_state = SandboxSystemStateProvider {
@@ -1255,6 +1372,8 @@ mod unit_tests {
                 .add_peer_to_connect_list(ConnectInfo {
                     address: addr.to_string(),
                     public_key,
+                    network_key: None,
+                    consensus_key: None,
                 });
         }
 
@@ -1309,6 +1428,46 @@ mod unit_tests {
         );
     }
 
+    #[test]
+    fn test_sandbox_connect_updates_peer_address() {
+        let s = timestamping_sandbox();
+        let consensus = gen_keypair();
+        let service = gen_keypair();
+        let validator_keys = ValidatorKeys {
+            consensus_key: consensus.0,
+            service_key: service.0,
+        };
+
+        // The peer is known by its private address, but advertises the public one.
+        let private_addr = gen_primitive_socket_addr(2);
+        let external_addr = gen_primitive_socket_addr(3);
+        s.add_peer_to_connect_list(private_addr, validator_keys);
+
+        s.recv(&s.create_connect(
+            &consensus.0,
+            external_addr.to_string(),
+            s.time().into(),
+            &user_agent(),
+            &consensus.1,
+        ));
+        s.send(
+            consensus.0,
+            &s.create_connect(
+                &s.public_key(ValidatorId(0)),
+                s.address(ValidatorId(0)),
+                s.time().into(),
+                &user_agent(),
+                s.secret_key(ValidatorId(0)),
+            ),
+        );
+
+        let address = s
+            .node_state()
+            .connect_list()
+            .find_address_by_key(&consensus.0);
+        assert_eq!(address, Some(external_addr.to_string()));
+    }
+
     #[test]
     fn test_sandbox_assert_status() {
         let s = timestamping_sandbox();
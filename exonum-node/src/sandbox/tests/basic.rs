@@ -17,22 +17,97 @@
 //! to add block after receiving correct consensus messages.
 
 use exonum::{
-    crypto::{gen_keypair_from_seed, Hash, Seed, HASH_SIZE, SEED_LENGTH},
+    blockchain::BlockProof,
+    crypto::{gen_keypair, gen_keypair_from_seed, Hash, Seed, HASH_SIZE, SEED_LENGTH},
     helpers::{Height, Round, ValidatorId},
     merkledb::ObjectHash,
     messages::{Precommit, Verified},
     runtime::SnapshotExt,
 };
 use rand::{thread_rng, Rng};
+use tracing::{
+    span::{Attributes, Id},
+    Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer,
+};
 
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use crate::sandbox::{
-    sandbox_tests_helper::*,
-    timestamping::{TimestampingTxGenerator, DATA_SIZE},
-    timestamping_sandbox, timestamping_sandbox_builder,
+use crate::{
+    sandbox::{
+        sandbox_tests_helper::*,
+        timestamping::{TimestampingTxGenerator, DATA_SIZE},
+        timestamping_sandbox, timestamping_sandbox_builder,
+    },
+    AnchoringConfirmation, AnchoringHook, AnchoringSender, ConnectInfo, ExternalMessage,
 };
 
+/// Records names of created spans together with names of their parents.
+#[derive(Debug, Clone, Default)]
+struct SpanRecorder(Arc<Mutex<Vec<(String, Option<String>)>>>);
+
+impl SpanRecorder {
+    fn parents(&self, name: &str) -> Vec<Option<String>> {
+        let spans = self.0.lock().unwrap();
+        spans
+            .iter()
+            .filter(|(span_name, _)| span_name == name)
+            .map(|(_, parent)| parent.clone())
+            .collect()
+    }
+}
+
+impl<S> Layer<S> for SpanRecorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).expect("Span is not registered");
+        let parent = span.parent().map(|parent| parent.name().to_owned());
+        self.0
+            .lock()
+            .unwrap()
+            .push((span.name().to_owned(), parent));
+    }
+}
+
+/// Checks that spans of transaction execution and block commitment are nested into the spans
+/// of the corresponding blocks.
+#[test]
+fn tracing_spans_propagation() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    let recorder = SpanRecorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        add_one_height(&sandbox, &sandbox_state);
+    });
+
+    assert!(!recorder.parents("handle_tx").is_empty());
+
+    let executed_txs = recorder.parents("execute_transaction");
+    assert!(!executed_txs.is_empty());
+    for parent in executed_txs {
+        assert_eq!(parent.as_ref().map(String::as_str), Some("create_block"));
+    }
+
+    let committed_blocks = recorder.parents("commit_block");
+    assert_eq!(committed_blocks.len(), 1);
+    assert_eq!(
+        committed_blocks[0].as_ref().map(String::as_str),
+        Some("commit")
+    );
+}
+
 /// idea of the test is to verify that at certain periodic rounds we (`validator_0`) become a leader
 /// assumption: in some loops current node becomes a leader
 #[test]
@@ -218,6 +293,190 @@ fn test_query_state_hash() {
     }
 }
 
+/// Idea of the test is to check that consensus metrics are collected for committed heights:
+/// - rounds at a height are listed in order with their leaders and durations
+/// - the durations of rounds add up to the duration of the height
+/// - precommits used to commit the block are attributed to their validators
+/// - the metrics are exposed via the node API state
+#[test]
+fn test_consensus_metrics() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    let majority_count = sandbox.majority_count(sandbox.validators().len());
+
+    for _ in 0..2 {
+        let height_start = sandbox.time();
+        // Leaders of the rounds at the height; the node commits a block in the first round
+        // it is the leader in, after at least one round timeout.
+        let mut leaders = vec![sandbox.leader(Round(1))];
+        let mut round = Round(1);
+        while round == Round(1) || leaders.last() != Some(&ValidatorId(0)) {
+            round.increment();
+            leaders.push(sandbox.leader(round));
+        }
+        let round_timeouts: Vec<_> = (0..leaders.len() as u64)
+            .map(|i| sandbox.first_round_timeout() + i * sandbox.round_timeout_increase())
+            .collect();
+
+        add_one_height(&sandbox, &sandbox_state);
+
+        let metrics = sandbox.node_state().consensus_metrics(sandbox.time());
+        let height_metrics = metrics.committed_heights.last().unwrap();
+        assert_eq!(height_metrics.height, sandbox.current_height().previous());
+        assert_eq!(
+            height_metrics.duration,
+            sandbox
+                .time()
+                .duration_since(height_start)
+                .unwrap()
+                .as_millis() as u64
+        );
+        assert_eq!(height_metrics.proposer_id, ValidatorId(0));
+
+        let rounds: Vec<_> = height_metrics.rounds.iter().map(|m| m.round).collect();
+        let expected_rounds: Vec<_> = (1..=leaders.len() as u32).map(Round).collect();
+        assert_eq!(rounds, expected_rounds);
+        let round_leaders: Vec<_> = height_metrics.rounds.iter().map(|m| m.leader).collect();
+        assert_eq!(round_leaders, leaders);
+        // All rounds except for the last one are completed by the round timeout.
+        let (last_round, timed_out_rounds) = height_metrics.rounds.split_last().unwrap();
+        for (round_metrics, timeout) in timed_out_rounds.iter().zip(&round_timeouts) {
+            assert_eq!(round_metrics.duration, *timeout);
+        }
+        let total_duration: u64 = height_metrics.rounds.iter().map(|m| m.duration).sum();
+        assert_eq!(total_duration, height_metrics.duration);
+        assert!(last_round.duration < round_timeouts[leaders.len() - 1]);
+
+        let mut precommits = height_metrics.precommits.clone();
+        precommits.sort();
+        let expected_precommits: Vec<_> = (0..majority_count as u16).map(ValidatorId).collect();
+        assert_eq!(precommits, expected_precommits);
+    }
+
+    let metrics = sandbox.node_state().consensus_metrics(sandbox.time());
+    assert_eq!(metrics.height, Height(3));
+    assert_eq!(metrics.round, Round(1));
+    assert_eq!(metrics.height_duration, 0);
+    assert_eq!(metrics.round_duration, 0);
+    assert_eq!(metrics.queued_messages, 0);
+    let heights: Vec<_> = metrics.committed_heights.iter().map(|m| m.height).collect();
+    assert_eq!(heights, vec![Height(1), Height(2)]);
+
+    // The metrics are reported via the API once the node updates its API state.
+    let api_state = sandbox.update_api_state();
+    assert_eq!(api_state.consensus_metrics(), Some(metrics));
+}
+
+/// Idea of the test is to check that validators liveness includes all validators
+/// and reports the actual state for the node itself.
+#[test]
+fn test_validators_liveness() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+
+    let liveness = sandbox.node_state().validators_liveness();
+    assert_eq!(liveness.len(), sandbox.validators().len());
+    for (i, validator) in liveness.iter().enumerate() {
+        assert_eq!(validator.id, ValidatorId(i as u16));
+        assert_eq!(validator.consensus_key, sandbox.public_key(validator.id));
+    }
+
+    let ours = &liveness[0];
+    assert!(ours.is_connected);
+    assert_eq!(ours.height, Height(2));
+    assert_eq!(ours.round, Some(Round(1)));
+}
+
+/// Idea of the test is to check that the node estimates clock skew of other validators
+/// using timestamps of their precommits.
+#[test]
+fn test_clock_skew_estimation() {
+    let sandbox = timestamping_sandbox();
+
+    let skewed_time = sandbox.time() - Duration::from_secs(5);
+    sandbox.recv(&sandbox.create_precommit(
+        ValidatorId(1),
+        Height(1),
+        Round(1),
+        Hash::zero(),
+        Hash::zero(),
+        skewed_time.into(),
+        sandbox.secret_key(ValidatorId(1)),
+    ));
+    sandbox.recv(&sandbox.create_precommit(
+        ValidatorId(2),
+        Height(1),
+        Round(1),
+        Hash::zero(),
+        Hash::zero(),
+        sandbox.time().into(),
+        sandbox.secret_key(ValidatorId(2)),
+    ));
+
+    let liveness = sandbox.node_state().validators_liveness();
+    assert_eq!(liveness[0].clock_skew, Some(0));
+    assert_eq!(liveness[1].clock_skew, Some(5_000));
+    assert_eq!(liveness[2].clock_skew, Some(0));
+    assert_eq!(liveness[3].clock_skew, None);
+}
+
+/// Idea of the test is to check that the clock skew is estimated once the precommit
+/// is received, rather than when the queued precommit is handled.
+#[test]
+fn test_clock_skew_estimation_with_queued_precommit() {
+    let sandbox = timestamping_sandbox();
+
+    // The precommit from the future round is queued.
+    sandbox.recv(&sandbox.create_precommit(
+        ValidatorId(1),
+        Height(1),
+        Round(2),
+        Hash::zero(),
+        Hash::zero(),
+        sandbox.time().into(),
+        sandbox.secret_key(ValidatorId(1)),
+    ));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.assert_state(Height(1), Round(2));
+
+    let liveness = sandbox.node_state().validators_liveness();
+    assert_eq!(liveness[1].clock_skew, Some(0));
+}
+
+/// Idea of the test is to check that the node in the bootstrap mode does not participate
+/// in the consensus and ignores transactions.
+#[test]
+fn test_bootstrap_mode() {
+    let sandbox = timestamping_sandbox_builder().with_bootstrap_mode().build();
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.assert_tx_cache_len(0);
+
+    sandbox.recv(&sandbox.create_transactions_request(
+        sandbox.public_key(ValidatorId(1)),
+        sandbox.public_key(ValidatorId(0)),
+        vec![tx.object_hash()],
+        sandbox.secret_key(ValidatorId(1)),
+    ));
+
+    let leader = sandbox.leader(Round(1));
+    sandbox.recv(&sandbox.create_propose(
+        leader,
+        Height(1),
+        Round(1),
+        sandbox.last_hash(),
+        vec![],
+        sandbox.secret_key(leader),
+    ));
+    sandbox.check_unexpected_message();
+
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.assert_state(Height(1), Round(1));
+}
+
 #[test]
 fn test_retrieve_block_and_precommits() {
     let sandbox = timestamping_sandbox();
@@ -311,3 +570,146 @@ fn tx_cache_with_tx_block_limit() {
 
     //TODO: check pool after commit.
 }
+
+#[derive(Debug, Default, Clone)]
+struct RecordingHook(Arc<Mutex<Vec<BlockProof>>>);
+
+impl AnchoringHook for RecordingHook {
+    fn block_finalized(&self, block_proof: &BlockProof, _sender: AnchoringSender) {
+        self.0.lock().unwrap().push(block_proof.clone());
+    }
+}
+
+/// Idea of the test is to check that anchoring hooks are notified about committed blocks
+/// and that the node records confirmations only for known blocks.
+#[test]
+fn test_anchoring_hook() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    let hook = RecordingHook::default();
+    sandbox
+        .inner
+        .borrow_mut()
+        .handler
+        .anchoring_hooks
+        .push(Box::new(hook.clone()));
+
+    add_one_height(&sandbox, &sandbox_state);
+
+    let block_proofs = hook.0.lock().unwrap().clone();
+    assert_eq!(block_proofs.len(), 1);
+    let block = &block_proofs[0].block;
+    assert_eq!(block.height, Height(1));
+    assert_eq!(block.object_hash(), sandbox.last_hash());
+    assert!(!block_proofs[0].precommits.is_empty());
+
+    let confirmation = AnchoringConfirmation {
+        height: Height(1),
+        block_hash: sandbox.last_hash(),
+        chain: "bitcoin".to_owned(),
+        reference: "tx".to_owned(),
+    };
+    sandbox.send_external_message(ExternalMessage::ConfirmAnchoring(AnchoringConfirmation {
+        block_hash: Hash::zero(),
+        ..confirmation.clone()
+    }));
+    let api_state = sandbox.inner.borrow().handler.api_state.clone();
+    assert!(api_state.anchoring_confirmations().is_empty());
+
+    sandbox.send_external_message(ExternalMessage::ConfirmAnchoring(confirmation.clone()));
+    assert_eq!(api_state.anchoring_confirmations(), vec![confirmation]);
+}
+
+/// Idea of the test is to check that peers can be removed from the connect list
+/// and added back without restarting the node.
+#[test]
+fn test_connect_list_hot_reload() {
+    let sandbox = timestamping_sandbox();
+    let key = sandbox.public_key(ValidatorId(3));
+
+    sandbox.send_external_message(ExternalMessage::PeerRemove(key, false));
+    assert!(!sandbox
+        .inner
+        .borrow()
+        .handler
+        .state
+        .peer_in_connect_list(&key));
+
+    sandbox.send_external_message(ExternalMessage::PeerAdd(ConnectInfo {
+        address: sandbox.address(ValidatorId(3)),
+        public_key: key,
+        network_key: None,
+        consensus_key: None,
+    }));
+    assert!(sandbox
+        .inner
+        .borrow()
+        .handler
+        .state
+        .peer_in_connect_list(&key));
+    sandbox.send(key, sandbox.connect().unwrap());
+}
+
+/// Idea of the test is to check that a validator signing consensus messages with
+/// an external signer is addressed by the key specified in the connect list.
+#[test]
+fn test_connect_list_with_consensus_key() {
+    let sandbox = timestamping_sandbox();
+    let consensus_key = sandbox.public_key(ValidatorId(3));
+    let (identity, _) = gen_keypair();
+
+    sandbox.send_external_message(ExternalMessage::PeerRemove(consensus_key, false));
+    sandbox.send_external_message(ExternalMessage::PeerAdd(ConnectInfo {
+        address: sandbox.address(ValidatorId(3)),
+        public_key: identity,
+        network_key: None,
+        consensus_key: Some(consensus_key),
+    }));
+    sandbox.send(identity, sandbox.connect().unwrap());
+
+    let inner = sandbox.inner.borrow();
+    let state = &inner.handler.state;
+    assert_eq!(state.peer_identity(&consensus_key), identity);
+    assert!(state.peer_is_validator(&identity));
+    assert!(!state.peer_in_connect_list(&consensus_key));
+}
+
+/// Idea of the test is to check that a peer can be disconnected while staying
+/// in the connect list.
+#[test]
+fn test_peer_remove_keeping_connect_list() {
+    let sandbox = timestamping_sandbox();
+    let key = sandbox.public_key(ValidatorId(3));
+
+    sandbox.send_external_message(ExternalMessage::PeerRemove(key, true));
+    assert!(sandbox
+        .inner
+        .borrow()
+        .handler
+        .state
+        .peer_in_connect_list(&key));
+}
+
+/// Idea of the test is to verify that on shutdown the node flushes the transaction cache
+/// into the persistent pool and stops participating in the consensus.
+#[test]
+fn test_graceful_shutdown() {
+    let sandbox = timestamping_sandbox();
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.assert_tx_cache_len(1);
+    sandbox.assert_pool_len(0);
+
+    sandbox.send_external_message(ExternalMessage::Shutdown);
+    sandbox.assert_tx_cache_len(0);
+    sandbox.assert_pool_len(1);
+    assert_eq!(sandbox.transactions_hashes(), vec![tx.object_hash()]);
+
+    // The node ignores consensus messages, so it does not vote for the proposal.
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.object_hash()])
+        .build();
+    sandbox.recv(&propose);
+    sandbox.assert_state(Height(1), Round(1));
+}
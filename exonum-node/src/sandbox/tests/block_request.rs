@@ -15,6 +15,7 @@
 //! Tests in this module are designed to test communication related to block requests.
 
 use exonum::{
+    crypto::Hash,
     helpers::{Height, Round, ValidatorId},
     merkledb::ObjectHash,
 };
@@ -22,8 +23,12 @@ use exonum::{
 use std::time::Duration;
 
 use crate::{
-    sandbox::{sandbox_tests_helper::*, timestamping_sandbox},
-    state::{BLOCK_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT},
+    sandbox::{sandbox_tests_helper::*, timestamping_sandbox, timestamping_sandbox_builder},
+    state::{
+        BLOCK_REQUEST_TIMEOUT, PEER_REQUESTS_LIMIT, PEER_VIOLATIONS_THRESHOLD,
+        TRANSACTIONS_REQUEST_TIMEOUT,
+    },
+    ExternalMessage,
 };
 
 /// Handle block response:
@@ -894,6 +899,304 @@ fn received_block_while_there_is_pending_block() {
     ));
 }
 
+/// HANDLE block request
+
+/// - should respond with all requested blocks known to the node
+#[test]
+fn respond_to_block_request_with_several_blocks() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+
+    sandbox.recv(&sandbox.create_block_request(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.public_key(ValidatorId(0)),
+        Height(1),
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+
+    for height in 1..3 {
+        let proof = sandbox.block_and_precommits(Height(height)).unwrap();
+        sandbox.send(
+            sandbox.public_key(ValidatorId(3)),
+            &sandbox.create_block_response(
+                sandbox.public_key(ValidatorId(0)),
+                sandbox.public_key(ValidatorId(3)),
+                proof.block,
+                proof.precommits,
+                vec![],
+                sandbox.secret_key(ValidatorId(0)),
+            ),
+        );
+    }
+}
+
+/// Idea of the test is to check that the node in the bootstrap mode synchronizes a block
+/// with a transaction unknown to it and then serves the block and the transaction to other peers:
+///
+/// - the transaction broadcast before the block is ignored
+/// - after receiving `BlockResponse`, the node requests the transaction and commits the block
+/// - the node responds to `TransactionsRequest` and `BlockRequest` with the committed data
+#[test]
+fn bootstrap_node_syncs_and_serves_block_with_transactions() {
+    let sandbox = timestamping_sandbox_builder().with_bootstrap_mode().build();
+    let tx = gen_timestamping_tx();
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let block = sandbox.create_block(&[tx.clone()]);
+    let precommits: Vec<_> = (1..4)
+        .map(|i| {
+            sandbox.create_precommit(
+                ValidatorId(i),
+                Height(1),
+                Round(1),
+                propose.object_hash(),
+                block.object_hash(),
+                sandbox.time().into(),
+                sandbox.secret_key(ValidatorId(i)),
+            )
+        })
+        .collect();
+
+    // Transactions not awaited by a synchronized block are ignored.
+    sandbox.recv(&tx);
+    sandbox.assert_tx_cache_len(0);
+
+    sandbox.recv(&sandbox.create_status(
+        sandbox.public_key(ValidatorId(3)),
+        Height(2),
+        block.object_hash(),
+        0,
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(3)),
+        &sandbox.create_block_request(
+            sandbox.public_key(ValidatorId(0)),
+            sandbox.public_key(ValidatorId(3)),
+            Height(1),
+            sandbox.secret_key(ValidatorId(0)),
+        ),
+    );
+
+    sandbox.recv(&sandbox.create_block_response(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.public_key(ValidatorId(0)),
+        block.clone(),
+        precommits.clone(),
+        vec![tx.object_hash()],
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+
+    sandbox.add_time(Duration::from_millis(TRANSACTIONS_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(3)),
+        &sandbox.create_transactions_request(
+            sandbox.public_key(ValidatorId(0)),
+            sandbox.public_key(ValidatorId(3)),
+            vec![tx.object_hash()],
+            sandbox.secret_key(ValidatorId(0)),
+        ),
+    );
+
+    sandbox.recv(&sandbox.create_transactions_response(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.public_key(ValidatorId(0)),
+        vec![tx.clone()],
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.broadcast(&sandbox.create_status(
+        sandbox.public_key(ValidatorId(0)),
+        Height(2),
+        block.object_hash(),
+        0,
+        sandbox.secret_key(ValidatorId(0)),
+    ));
+
+    // The synchronized data is served to other peers.
+    sandbox.recv(&sandbox.create_transactions_request(
+        sandbox.public_key(ValidatorId(1)),
+        sandbox.public_key(ValidatorId(0)),
+        vec![tx.object_hash()],
+        sandbox.secret_key(ValidatorId(1)),
+    ));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(1)),
+        &sandbox.create_transactions_response(
+            sandbox.public_key(ValidatorId(0)),
+            sandbox.public_key(ValidatorId(1)),
+            vec![tx.clone()],
+            sandbox.secret_key(ValidatorId(0)),
+        ),
+    );
+
+    sandbox.recv(&sandbox.create_block_request(
+        sandbox.public_key(ValidatorId(1)),
+        sandbox.public_key(ValidatorId(0)),
+        Height(1),
+        sandbox.secret_key(ValidatorId(1)),
+    ));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(1)),
+        &sandbox.create_block_response(
+            sandbox.public_key(ValidatorId(0)),
+            sandbox.public_key(ValidatorId(1)),
+            block,
+            precommits,
+            vec![tx.object_hash()],
+            sandbox.secret_key(ValidatorId(0)),
+        ),
+    );
+    sandbox.check_unexpected_message();
+}
+
+/// HANDLE block response
+
+/// - blocks received ahead of the current height should be processed after the previous ones
+/// idea of test is:
+/// - getting Status from other node with later height, send BlockRequest to this node
+/// - receive BlockResponse for the second block
+/// - receive BlockResponse for the first block
+/// - both blocks should be committed
+#[test]
+fn handle_block_responses_out_of_order() {
+    // Blocks are obtained from another sandbox with the same configuration.
+    let donor = timestamping_sandbox();
+    let donor_state = SandboxState::new();
+    add_one_height(&donor, &donor_state);
+    add_one_height(&donor, &donor_state);
+    let proofs: Vec<_> = (1..3)
+        .map(|height| donor.block_and_precommits(Height(height)).unwrap())
+        .collect();
+
+    let sandbox = timestamping_sandbox();
+    sandbox.recv(&sandbox.create_status(
+        sandbox.public_key(ValidatorId(3)),
+        Height(3),
+        proofs[1].block.object_hash(),
+        0,
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(3)),
+        &sandbox.create_block_request(
+            sandbox.public_key(ValidatorId(0)),
+            sandbox.public_key(ValidatorId(3)),
+            Height(1),
+            sandbox.secret_key(ValidatorId(0)),
+        ),
+    );
+
+    for proof in proofs.iter().rev() {
+        sandbox.recv(&sandbox.create_block_response(
+            sandbox.public_key(ValidatorId(3)),
+            sandbox.public_key(ValidatorId(0)),
+            proof.block.clone(),
+            proof.precommits.clone(),
+            vec![],
+            sandbox.secret_key(ValidatorId(3)),
+        ));
+    }
+
+    sandbox.assert_state(Height(3), Round(1));
+    for (height, proof) in (2..).zip(&proofs) {
+        sandbox.broadcast(&sandbox.create_status(
+            sandbox.public_key(ValidatorId(0)),
+            Height(height),
+            proof.block.object_hash(),
+            0,
+            sandbox.secret_key(ValidatorId(0)),
+        ));
+    }
+}
+
+/// - peer sending malformed blocks should be banned
+/// idea of test is:
+/// - receive several BlockResponses with invalid `tx_hash` from the same peer
+/// - the peer should be banned, so its Status is ignored
+/// - lift the ban, the node should connect to the peer again
+#[test]
+fn ban_peer_sending_invalid_blocks() {
+    let sandbox = timestamping_sandbox();
+    let tx = gen_timestamping_tx();
+    let block = sandbox.create_block(&[tx]);
+
+    // Block without transactions has invalid `tx_hash`.
+    let block_response = sandbox.create_block_response(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.public_key(ValidatorId(0)),
+        block.clone(),
+        vec![],
+        vec![],
+        sandbox.secret_key(ValidatorId(3)),
+    );
+    for _ in 0..PEER_VIOLATIONS_THRESHOLD {
+        sandbox.recv(&block_response);
+    }
+
+    sandbox.recv(&sandbox.create_status(
+        sandbox.public_key(ValidatorId(3)),
+        Height(2),
+        block.object_hash(),
+        0,
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+
+    sandbox.send_external_message(ExternalMessage::UnbanPeer(
+        sandbox.public_key(ValidatorId(3)),
+    ));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.connect().unwrap(),
+    );
+}
+
+/// - peer flooding the node with requests should be banned
+/// idea of test is:
+/// - receive more than `PEER_REQUESTS_LIMIT` requests from the same peer within a short period
+/// - the peer should be banned, so its Status is ignored
+/// - lift the ban, the node should connect to the peer again
+#[test]
+fn ban_peer_sending_too_many_requests() {
+    let sandbox = timestamping_sandbox();
+
+    // Requests for a propose at a future height are ignored, so the node sends no responses.
+    let propose_request = sandbox.create_propose_request(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.public_key(ValidatorId(0)),
+        Height(10),
+        Hash::zero(),
+        sandbox.secret_key(ValidatorId(3)),
+    );
+    for _ in 0..PEER_REQUESTS_LIMIT + PEER_VIOLATIONS_THRESHOLD {
+        sandbox.recv(&propose_request);
+    }
+
+    sandbox.recv(&sandbox.create_status(
+        sandbox.public_key(ValidatorId(3)),
+        Height(2),
+        Hash::zero(),
+        0,
+        sandbox.secret_key(ValidatorId(3)),
+    ));
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+
+    sandbox.send_external_message(ExternalMessage::UnbanPeer(
+        sandbox.public_key(ValidatorId(3)),
+    ));
+    sandbox.send(
+        sandbox.public_key(ValidatorId(3)),
+        sandbox.connect().unwrap(),
+    );
+}
+
 // TODO: Rewrite sandbox methods so that you can receive/send messages in batches.
 // Now the same messages are sent to the validators in a random order. (ECR-376)
 
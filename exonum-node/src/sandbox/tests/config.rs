@@ -15,11 +15,18 @@
 //! Tests in this module are designed to test configuration change protocol.
 
 use exonum::{
-    helpers::{Height, ValidatorId},
-    merkledb::BinaryValue,
+    crypto::{gen_keypair, KeyPair},
+    helpers::{Height, Round, ValidatorId},
+    merkledb::{BinaryValue, ObjectHash},
 };
 
-use crate::sandbox::{config_updater::TxConfig, sandbox_tests_helper::*, timestamping_sandbox};
+use std::time::Duration;
+
+use crate::{
+    sandbox::{config_updater::TxConfig, sandbox_tests_helper::*, timestamping_sandbox},
+    schema::NodeSchema,
+    ExternalMessage, NodeRole,
+};
 
 /// - exclude validator from consensus
 /// - idea of test is to exclude sandbox validator from consensus
@@ -48,5 +55,170 @@ fn test_exclude_validator_from_consensus() {
     add_one_height_with_transactions_from_other_validator(&sandbox, &sandbox_state, &[]);
 }
 
-// - include validator to consensus
-// TODO [ECR-3222]
+/// - rotate consensus key of the sandbox validator
+/// - idea of test is to register a new consensus key in advance and switch to it
+///   once the configuration with this key is applied
+/// - node continues as a validator signing consensus messages with the new key
+#[test]
+fn test_rotate_consensus_key() {
+    let mut sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    let (new_consensus_pk, new_consensus_sk) = gen_keypair();
+    sandbox
+        .validators_map
+        .insert(new_consensus_pk, new_consensus_sk.clone());
+    sandbox.send_external_message(ExternalMessage::RegisterConsensusKey(KeyPair::from_keys(
+        new_consensus_pk,
+        new_consensus_sk,
+    )));
+
+    add_one_height(&sandbox, &sandbox_state);
+
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.validator_keys[0].consensus_key = new_consensus_pk;
+
+        TxConfig::create_signed(
+            sandbox.public_key(ValidatorId(0)),
+            &consensus_cfg.into_bytes(),
+            Height(0),
+            sandbox.secret_key(ValidatorId(0)),
+        )
+    };
+
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg]);
+    assert_eq!(sandbox.public_key(ValidatorId(0)), new_consensus_pk);
+    assert_eq!(sandbox.node_state().validator_id(), Some(ValidatorId(0)));
+    // Node stays in the validator set and votes using the new key.
+    add_one_height(&sandbox, &sandbox_state);
+}
+
+/// - rotate consensus key of another validator
+/// - idea of test is to check that requests to a validator which has rotated its consensus key
+///   are addressed to the key identifying the validator among peers (its original consensus key)
+/// - the validator answers the request, and the node continues the consensus
+#[test]
+fn test_request_to_validator_with_rotated_key() {
+    let mut sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    let identity = sandbox.public_key(ValidatorId(2));
+
+    let (new_consensus_pk, new_consensus_sk) = gen_keypair();
+    sandbox
+        .validators_map
+        .insert(new_consensus_pk, new_consensus_sk);
+
+    add_one_height(&sandbox, &sandbox_state);
+
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.validator_keys[2].consensus_key = new_consensus_pk;
+
+        TxConfig::create_signed(
+            sandbox.public_key(ValidatorId(0)),
+            &consensus_cfg.into_bytes(),
+            Height(0),
+            sandbox.secret_key(ValidatorId(0)),
+        )
+    };
+
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg]);
+    assert_eq!(sandbox.public_key(ValidatorId(2)), new_consensus_pk);
+    assert_eq!(
+        sandbox.node_state().peer_identity(&new_consensus_pk),
+        identity
+    );
+    // Make another validator the leader of the first round.
+    add_one_height(&sandbox, &sandbox_state);
+    assert_ne!(sandbox.current_leader(), ValidatorId(0));
+
+    let height = sandbox.current_height();
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.recv(&sandbox.create_prevote(
+        ValidatorId(2),
+        height,
+        Round(1),
+        propose.object_hash(),
+        NOT_LOCKED,
+        sandbox.secret_key(ValidatorId(2)),
+    ));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout() - 1));
+    sandbox.send(
+        identity,
+        &sandbox.create_propose_request(
+            sandbox.public_key(ValidatorId(0)),
+            identity,
+            height,
+            propose.object_hash(),
+            sandbox.secret_key(ValidatorId(0)),
+        ),
+    );
+
+    // The validator responds with the requested proposal.
+    sandbox.recv(&propose);
+    sandbox.broadcast(&sandbox.create_prevote(
+        ValidatorId(0),
+        height,
+        Round(1),
+        propose.object_hash(),
+        NOT_LOCKED,
+        sandbox.secret_key(ValidatorId(0)),
+    ));
+
+    // Identities of validators are persisted to be restored after the node restart.
+    let snapshot = sandbox.blockchain().snapshot();
+    let identities = NodeSchema::new(&snapshot).peer_identities();
+    assert_eq!(identities.get(&new_consensus_pk), Some(identity));
+}
+
+/// - include validator to consensus
+/// - idea of test is to exclude sandbox validator from consensus and include it back
+/// - node switches from `auditor` to `validator` at the activation height without restart
+#[test]
+fn test_include_validator_to_consensus() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    let our_keys = sandbox.cfg().validator_keys[0];
+
+    add_one_height(&sandbox, &sandbox_state);
+
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.validator_keys.swap_remove(0);
+
+        TxConfig::create_signed(
+            sandbox.public_key(ValidatorId(0)),
+            &consensus_cfg.into_bytes(),
+            Height(0),
+            sandbox.secret_key(ValidatorId(0)),
+        )
+    };
+
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg]);
+    // node loses validator status
+    assert_eq!(sandbox.node_state().validator_id(), None);
+    assert!(!sandbox.node_state().is_validator());
+    assert_eq!(sandbox.node_roles(), (NodeRole::Auditor, NodeRole::Auditor));
+
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.validator_keys.insert(0, our_keys);
+
+        TxConfig::create_signed(
+            sandbox.public_key(ValidatorId(1)),
+            &consensus_cfg.into_bytes(),
+            Height(0),
+            sandbox.secret_key(ValidatorId(1)),
+        )
+    };
+
+    add_one_height_with_transactions_from_other_validator(&sandbox, &sandbox_state, &[tx_cfg]);
+    // node becomes a validator again
+    assert_eq!(sandbox.node_state().validator_id(), Some(ValidatorId(0)));
+    assert!(sandbox.node_state().is_validator());
+    let role = NodeRole::Validator(ValidatorId(0));
+    assert_eq!(sandbox.node_roles(), (role, role));
+    assert_eq!(sandbox.cfg().validator_keys[0], our_keys);
+    add_one_height(&sandbox, &sandbox_state);
+}
@@ -63,6 +63,27 @@ fn should_not_send_propose_and_prevote_after_node_restart() {
     // if there any sent messages
 }
 
+/// Scenario:
+/// - Node receives a transaction.
+/// - Round timeout happens, so the transaction is saved to the node schema.
+/// - Node restarts before the transaction gets into a block.
+/// - Node should recover the transaction into its cache.
+#[test]
+fn should_recover_transactions_cache_after_node_restart() {
+    let sandbox = timestamping_sandbox();
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.assert_tx_cache_len(1);
+    sandbox.assert_pool_len(0);
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.assert_state(Height(1), Round(2));
+
+    let sandbox_restarted = sandbox.restart();
+    sandbox_restarted.assert_tx_cache_len(1);
+    sandbox_restarted.assert_pool_len(0);
+}
+
 /// Idea:
 /// - Node gets locked on some Propose from certain validator.
 /// - Node restarts.
@@ -16,24 +16,26 @@
 
 use bit_vec::BitVec;
 use exonum::{
-    blockchain::ProposerId,
+    blockchain::{ProposeTimeoutStrategy, ProposerId},
     crypto::{gen_keypair, Hash},
     helpers::{Height, Round, ValidatorId},
-    merkledb::{BinaryValue, ObjectHash},
+    merkledb::{BinaryValue, ObjectHash, Snapshot},
     messages::{AnyTx, Verified},
 };
+use exonum_rust_runtime::DefaultInstance;
 
 use std::time::Duration;
 
 use crate::{
     messages::{TX_RES_EMPTY_SIZE, TX_RES_PB_OVERHEAD_PAYLOAD},
     sandbox::{
-        config_updater::TxConfig,
+        config_updater::{ConfigUpdaterService, TxConfig},
         sandbox_tests_helper::*,
         timestamping::{TimestampingTxGenerator, DATA_SIZE},
         timestamping_sandbox, timestamping_sandbox_builder, Milliseconds, Sandbox,
     },
     state::TRANSACTIONS_REQUEST_TIMEOUT,
+    TxSelectionStrategy,
 };
 
 const MAX_PROPOSE_TIMEOUT: Milliseconds = 200;
@@ -697,3 +699,143 @@ fn expedited_propose_on_transaction_pressure() {
     sandbox.broadcast(&propose);
     sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
 }
+
+#[test]
+fn regular_propose_with_constant_timeout_strategy() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.max_propose_timeout = MAX_PROPOSE_TIMEOUT;
+            config.min_propose_timeout = MIN_PROPOSE_TIMEOUT;
+            config.propose_timeout_threshold = PROPOSE_THRESHOLD;
+            config.propose_timeout_strategy = ProposeTimeoutStrategy::Constant;
+        })
+        .build();
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+
+    // Generate and receive some transactions (at the threshold).
+    let transactions = TimestampingTxGenerator::new(64)
+        .take(PROPOSE_THRESHOLD as usize)
+        .collect::<Vec<_>>();
+
+    for tx in &transactions {
+        sandbox.recv(tx);
+    }
+
+    // Proposal should not be expedited despite the transaction pressure.
+    sandbox.add_time(Duration::from_millis(MIN_PROPOSE_TIMEOUT));
+    sandbox.add_time(Duration::from_millis(
+        MAX_PROPOSE_TIMEOUT - MIN_PROPOSE_TIMEOUT,
+    ));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&tx_hashes(&transactions))
+        .build();
+
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+#[test]
+fn propose_with_service_priorities() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.txs_block_limit = 1)
+        .with_service_priority(ConfigUpdaterService::INSTANCE_NAME, 1)
+        .build();
+
+    // Wait for us to become the leader.
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    assert!(sandbox.is_leader());
+
+    let tx = gen_timestamping_tx();
+    let config_tx = TxConfig::create_signed(
+        sandbox.public_key(ValidatorId(0)),
+        &sandbox.cfg().into_bytes(),
+        sandbox.current_height().next(),
+        sandbox.secret_key(ValidatorId(0)),
+    );
+    sandbox.recv(&tx);
+    sandbox.recv(&config_tx);
+
+    sandbox.add_time(Duration::from_millis(PROPOSE_TIMEOUT));
+
+    // The transaction to the prioritized service is proposed despite being received later.
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[config_tx.object_hash()])
+        .build();
+
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+#[test]
+fn propose_with_service_round_robin() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.txs_block_limit = 2)
+        .with_tx_selection(TxSelectionStrategy::ServiceRoundRobin)
+        .build();
+
+    // Wait for us to become the leader.
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    assert!(sandbox.is_leader());
+
+    let transactions: Vec<_> = (0..2).map(|_| gen_timestamping_tx()).collect();
+    for tx in &transactions {
+        sandbox.recv(tx);
+    }
+    let config_tx = TxConfig::create_signed(
+        sandbox.public_key(ValidatorId(0)),
+        &sandbox.cfg().into_bytes(),
+        sandbox.current_height().next(),
+        sandbox.secret_key(ValidatorId(0)),
+    );
+    sandbox.recv(&config_tx);
+
+    sandbox.add_time(Duration::from_millis(PROPOSE_TIMEOUT));
+
+    // Transactions are taken from each service in turn, so the config transaction
+    // is not blocked by the timestamping ones.
+    let timestamping_hash = tx_hashes(&transactions)[0];
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[config_tx.object_hash(), timestamping_hash])
+        .build();
+
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+#[test]
+fn propose_with_tx_fees() {
+    let transactions: Vec<_> = (0..3).map(|_| gen_timestamping_tx()).collect();
+    let expensive_tx = transactions[1].object_hash();
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.txs_block_limit = 1)
+        .with_tx_fees(move |_: &dyn Snapshot, tx: &Verified<AnyTx>| {
+            if tx.object_hash() == expensive_tx {
+                10
+            } else {
+                1
+            }
+        })
+        .build();
+
+    // Wait for us to become the leader.
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    assert!(sandbox.is_leader());
+
+    for tx in &transactions {
+        sandbox.recv(tx);
+    }
+    sandbox.add_time(Duration::from_millis(PROPOSE_TIMEOUT));
+
+    // The transaction with the highest fee is proposed regardless of the pool order.
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[expensive_tx])
+        .build();
+
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
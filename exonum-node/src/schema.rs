@@ -13,13 +13,13 @@
 // limitations under the License.
 
 use exonum::{
-    crypto::PublicKey,
+    crypto::{Hash, PublicKey},
     helpers::Round,
     merkledb::{
         access::{Access, AccessExt, RawAccessMut},
         ListIndex, MapIndex,
     },
-    messages::Verified,
+    messages::{AnyTx, Verified},
 };
 
 use std::iter;
@@ -29,6 +29,8 @@ use crate::messages::{Connect, Message};
 const CONSENSUS_MESSAGES_CACHE: &str = "core.consensus_messages_cache";
 const CONSENSUS_ROUND: &str = "core.consensus_round";
 const PEERS_CACHE: &str = "core.peers_cache";
+const PEER_IDENTITIES: &str = "core.peer_identities";
+const TRANSACTIONS_CACHE: &str = "core.transactions_cache";
 
 /// Schema for an Exonum node.
 #[derive(Debug)]
@@ -47,12 +49,24 @@ impl<T: Access> NodeSchema<T> {
         self.access.clone().get_map(PEERS_CACHE)
     }
 
+    /// Returns keys identifying validators among peers, keyed by the current consensus keys
+    /// of the validators. Only validators which have rotated their consensus keys are present.
+    pub fn peer_identities(&self) -> MapIndex<T::Base, PublicKey, PublicKey> {
+        self.access.clone().get_map(PEER_IDENTITIES)
+    }
+
     /// Returns consensus messages that have to be recovered in case of process restart
     /// after abnormal termination.
     pub fn consensus_messages_cache(&self) -> ListIndex<T::Base, Message> {
         self.access.clone().get_list(CONSENSUS_MESSAGES_CACHE)
     }
 
+    /// Returns transactions accepted by the node, but not yet moved to the persistent pool,
+    /// that have to be recovered in case of process restart after abnormal termination.
+    pub fn transactions_cache(&self) -> MapIndex<T::Base, Hash, Verified<AnyTx>> {
+        self.access.clone().get_map(TRANSACTIONS_CACHE)
+    }
+
     /// Returns the saved value of the consensus round. Returns the first round
     /// if it has not been saved.
     pub fn consensus_round(&self) -> Round {
@@ -92,6 +106,17 @@ where
         self.peers_cache().put(pubkey, peer);
     }
 
+    /// Saves keys identifying validators with rotated consensus keys.
+    pub fn save_peer_identities<I>(&mut self, identities: I)
+    where
+        I: IntoIterator<Item = (PublicKey, PublicKey)>,
+    {
+        let mut index = self.peer_identities();
+        for (consensus_key, identity) in identities {
+            index.put(&consensus_key, identity);
+        }
+    }
+
     /// Removes from the cache the `Connect` message from a peer.
     pub fn remove_peer_with_pubkey(&mut self, key: &PublicKey) {
         self.peers_cache().remove(key);
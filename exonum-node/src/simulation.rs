@@ -0,0 +1,619 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-process simulation of a network of Exonum nodes.
+//!
+//! [`SimulatedNetwork`] runs several [`Node`]s in a single thread. Nodes exchange real
+//! consensus messages via an in-memory transport, while timeouts are driven by a virtual
+//! clock. Message latencies are drawn from a pseudo-random generator with a fixed seed,
+//! so that a specific interleaving of messages can be reproduced by reusing the seed.
+//!
+//! Unlike the testkit, which emulates the network by creating blocks directly,
+//! the simulation runs the full consensus algorithm, so it can be used to reproduce bugs
+//! which only manifest with genuine interleaving of messages from several nodes.
+//!
+//! The module is intended for tests only and is available with the `simulation`
+//! crate feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use exonum::{blockchain::config::GenesisConfigBuilder, helpers::Height, merkledb::TemporaryDB};
+//! use exonum_node::{generate_testnet_config, simulation::SimulatedNetwork, NodeBuilder};
+//! use std::time::Duration;
+//!
+//! let nodes = generate_testnet_config(4, 16_000).into_iter().map(|node_config| {
+//!     let genesis_config =
+//!         GenesisConfigBuilder::with_consensus_config(node_config.consensus.clone()).build();
+//!     NodeBuilder::new(TemporaryDB::new(), node_config, genesis_config).build()
+//! });
+//! let mut network = SimulatedNetwork::new(nodes).with_seed(42);
+//! assert!(network.run_until_height(Height(3), Duration::from_secs(60)));
+//! ```
+//!
+//! [`SimulatedNetwork`]: struct.SimulatedNetwork.html
+//! [`Node`]: ../struct.Node.html
+
+use exonum::{
+    blockchain::{ApiSender, Blockchain},
+    crypto::PublicKey,
+    helpers::Height,
+    merkledb::BinaryValue,
+    messages::{AnyTx, SignedMessage, Verified},
+};
+use exonum_api::UpdateEndpoints;
+use futures::{sync::mpsc, Async, Future, Stream};
+use log::trace;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    events::{
+        network::ConnectedPeerAddr, Event, EventHandler, InternalEvent, InternalPart,
+        InternalRequest, NetworkEvent, NetworkRequest, TimeoutRequest,
+    },
+    messages::{Connect, ExonumMessage, Message},
+    ExternalMessage, Node, NodeHandler, SystemStateProvider,
+};
+
+/// Default bounds of message latency.
+const DEFAULT_LATENCY: (Duration, Duration) = (Duration::from_millis(5), Duration::from_millis(50));
+
+/// Delay after which a node is notified that it cannot connect to a peer.
+const CONNECT_FAILURE_DELAY: Duration = Duration::from_secs(1);
+
+/// Clock shared by the simulated nodes.
+type SharedTime = Arc<Mutex<SystemTime>>;
+
+#[derive(Debug)]
+struct SimulatedSystemState {
+    listen_address: SocketAddr,
+    time: SharedTime,
+}
+
+impl SystemStateProvider for SimulatedSystemState {
+    fn listen_address(&self) -> SocketAddr {
+        self.listen_address
+    }
+
+    fn current_time(&self) -> SystemTime {
+        *self.time.lock().unwrap()
+    }
+}
+
+/// Node participating in the simulation.
+struct SimulatedNode {
+    handler: NodeHandler,
+    connect: Verified<Connect>,
+    listen_address: SocketAddr,
+    network_requests_rx: mpsc::Receiver<NetworkRequest>,
+    internal_requests_rx: mpsc::Receiver<InternalRequest>,
+    transactions_rx: mpsc::Receiver<Verified<AnyTx>>,
+    api_requests_rx: mpsc::Receiver<ExternalMessage>,
+    endpoints_rx: mpsc::Receiver<UpdateEndpoints>,
+    is_running: bool,
+}
+
+impl SimulatedNode {
+    fn public_key(&self) -> PublicKey {
+        self.connect.author()
+    }
+}
+
+/// Scheduled action in the simulation.
+#[derive(Debug)]
+enum Action {
+    /// Delivery of a message from one node to another.
+    Deliver {
+        from: usize,
+        to: usize,
+        message: SignedMessage,
+    },
+    /// Expiration of a timeout requested by the node.
+    Timeout {
+        node: usize,
+        request: TimeoutRequest,
+    },
+    /// Failure of the node to connect to a peer.
+    ConnectFailure { node: usize, peer: usize },
+}
+
+#[derive(Debug)]
+struct ScheduledAction {
+    time: SystemTime,
+    /// Sequence number used to order actions scheduled for the same time.
+    seq: u64,
+    action: Action,
+}
+
+impl PartialEq for ScheduledAction {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledAction {}
+
+impl PartialOrd for ScheduledAction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledAction {
+    // Reversed, so that the earliest action is on top of the `BinaryHeap`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.time, self.seq)
+            .cmp(&(other.time, other.seq))
+            .reverse()
+    }
+}
+
+/// Network of nodes running in a single thread with an in-memory transport and
+/// a virtual clock. See [the module docs](index.html) for details.
+pub struct SimulatedNetwork {
+    nodes: Vec<SimulatedNode>,
+    node_indexes: HashMap<PublicKey, usize>,
+    time: SharedTime,
+    actions: BinaryHeap<ScheduledAction>,
+    next_seq: u64,
+    rng: StdRng,
+    latency: (Duration, Duration),
+    /// Pairs of nodes with established connections. Pairs are stored with the smaller index first.
+    connections: HashSet<(usize, usize)>,
+    /// Pairs of nodes which cannot communicate. Pairs are stored with the smaller index first.
+    broken_links: HashSet<(usize, usize)>,
+}
+
+impl fmt::Debug for SimulatedNetwork {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("SimulatedNetwork")
+            .field("nodes", &self.nodes.len())
+            .field("time", &self.time())
+            .field("pending_actions", &self.actions.len())
+            .finish()
+    }
+}
+
+impl SimulatedNetwork {
+    /// Creates a simulation of the specified nodes. The nodes should be created
+    /// with configurations referring to each other, e.g., with `generate_testnet_config`.
+    ///
+    /// Network addresses of the nodes are used only to identify them; the simulation
+    /// does not open any sockets or start HTTP servers.
+    pub fn new(nodes: impl IntoIterator<Item = Node>) -> Self {
+        let time = Arc::new(Mutex::new(SystemTime::now()));
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|node| {
+                let Node {
+                    mut handler,
+                    channel,
+                    ..
+                } = node;
+                let listen_address = handler.system_state.listen_address();
+                handler.system_state = Box::new(SimulatedSystemState {
+                    listen_address,
+                    time: Arc::clone(&time),
+                });
+                SimulatedNode {
+                    connect: handler.state.our_connect_message().clone(),
+                    handler,
+                    listen_address,
+                    network_requests_rx: channel.network_requests.1,
+                    internal_requests_rx: channel.internal_requests.1,
+                    transactions_rx: channel.transactions.1,
+                    api_requests_rx: channel.api_requests.1,
+                    endpoints_rx: channel.endpoints.1,
+                    is_running: true,
+                }
+            })
+            .collect();
+
+        let node_indexes = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.public_key(), i))
+            .collect();
+
+        let mut network = Self {
+            nodes,
+            node_indexes,
+            time,
+            actions: BinaryHeap::new(),
+            next_seq: 0,
+            rng: StdRng::seed_from_u64(0),
+            latency: DEFAULT_LATENCY,
+            connections: HashSet::new(),
+            broken_links: HashSet::new(),
+        };
+        for i in 0..network.nodes.len() {
+            network.nodes[i].handler.initialize();
+            network.process_requests(i);
+        }
+        network
+    }
+
+    /// Sets the seed of the generator of message latencies.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Sets bounds of message latency. Latency of each message is uniformly distributed
+    /// between the bounds.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `min` is greater than `max`.
+    pub fn with_latency(mut self, min: Duration, max: Duration) -> Self {
+        assert!(
+            min <= max,
+            "Minimum latency is greater than the maximum one"
+        );
+        self.latency = (min, max);
+        self
+    }
+
+    /// Returns the number of nodes in the network.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Checks if the network has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the current virtual time.
+    pub fn time(&self) -> SystemTime {
+        *self.time.lock().unwrap()
+    }
+
+    /// Returns the blockchain of the node with the specified index.
+    pub fn blockchain(&self, node: usize) -> &Blockchain {
+        self.nodes[node].handler.blockchain.as_ref()
+    }
+
+    /// Returns the height of the latest block committed by the node.
+    pub fn height(&self, node: usize) -> Height {
+        self.blockchain(node).last_block().height
+    }
+
+    /// Returns the sender of transactions to the node with the specified index.
+    /// Transactions are processed by the node on the next step of the simulation.
+    pub fn api_sender(&self, node: usize) -> ApiSender {
+        self.blockchain(node).sender().clone()
+    }
+
+    /// Sends an external message (e.g., `ExternalMessage::Shutdown`) to the node
+    /// with the specified index.
+    pub fn send_external_message(&mut self, node: usize, message: ExternalMessage) {
+        if self.nodes[node].is_running {
+            self.nodes[node].handler.handle_event(message.into());
+            self.process_requests(node);
+        }
+    }
+
+    /// Breaks or restores the link between two nodes. Messages sent over a broken link are lost,
+    /// and the nodes are notified that they are disconnected.
+    pub fn set_link_broken(&mut self, first: usize, second: usize, is_broken: bool) {
+        let link = Self::link(first, second);
+        if is_broken {
+            self.broken_links.insert(link);
+            self.disconnect(first, second);
+        } else {
+            self.broken_links.remove(&link);
+        }
+    }
+
+    /// Breaks or restores all links of the node with the specified index.
+    pub fn set_node_isolated(&mut self, node: usize, is_isolated: bool) {
+        for other in 0..self.nodes.len() {
+            if other != node {
+                self.set_link_broken(node, other, is_isolated);
+            }
+        }
+    }
+
+    /// Performs the next scheduled action: delivers a message or fires a timeout.
+    /// Returns `false` if there are no scheduled actions.
+    pub fn step(&mut self) -> bool {
+        // Transactions and external messages may be sent to nodes outside the simulation loop.
+        for i in 0..self.nodes.len() {
+            self.process_requests(i);
+        }
+
+        let scheduled = match self.actions.pop() {
+            Some(scheduled) => scheduled,
+            None => return false,
+        };
+        *self.time.lock().unwrap() = scheduled.time;
+
+        match scheduled.action {
+            Action::Deliver { from, to, message } => self.deliver(from, to, message),
+            Action::Timeout { node, request } => {
+                if self.nodes[node].is_running {
+                    self.handle_event(node, request.event());
+                }
+            }
+            Action::ConnectFailure { node, peer } => {
+                if self.nodes[node].is_running {
+                    let peer_key = self.nodes[peer].public_key();
+                    self.handle_event(node, NetworkEvent::UnableConnectToPeer(peer_key).into());
+                }
+            }
+        }
+        true
+    }
+
+    /// Runs the simulation for the specified amount of virtual time.
+    pub fn run_for(&mut self, duration: Duration) {
+        let deadline = self.time() + duration;
+        while self
+            .next_action_time()
+            .map_or(false, |time| time <= deadline)
+        {
+            self.step();
+        }
+        *self.time.lock().unwrap() = deadline;
+    }
+
+    /// Runs the simulation until the predicate holds, but no longer than `timeout`
+    /// of virtual time. Returns `true` if the predicate holds.
+    pub fn run_until<F>(&mut self, mut predicate: F, timeout: Duration) -> bool
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        let deadline = self.time() + timeout;
+        while !predicate(self) {
+            if self.next_action_time().map_or(true, |time| time > deadline) {
+                return false;
+            }
+            self.step();
+        }
+        true
+    }
+
+    /// Runs the simulation until all running nodes commit a block with the specified height,
+    /// but no longer than `timeout` of virtual time. Returns `true` if the height is reached.
+    pub fn run_until_height(&mut self, height: Height, timeout: Duration) -> bool {
+        self.run_until(
+            |network| {
+                (0..network.len())
+                    .filter(|&i| network.nodes[i].is_running)
+                    .all(|i| network.height(i) >= height)
+            },
+            timeout,
+        )
+    }
+
+    fn next_action_time(&self) -> Option<SystemTime> {
+        self.actions.peek().map(|scheduled| scheduled.time)
+    }
+
+    fn link(first: usize, second: usize) -> (usize, usize) {
+        if first < second {
+            (first, second)
+        } else {
+            (second, first)
+        }
+    }
+
+    fn schedule(&mut self, time: SystemTime, action: Action) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.actions.push(ScheduledAction { time, seq, action });
+    }
+
+    fn random_latency(&mut self) -> Duration {
+        let (min, max) = self.latency;
+        let min = min.as_micros() as u64;
+        let max = max.as_micros() as u64;
+        Duration::from_micros(self.rng.gen_range(min, max + 1))
+    }
+
+    /// Handles an event by the node and processes requests produced by it.
+    fn handle_event(&mut self, node: usize, event: Event) {
+        self.nodes[node].handler.handle_event(event);
+        self.process_requests(node);
+    }
+
+    fn deliver(&mut self, from: usize, to: usize, message: SignedMessage) {
+        let link = Self::link(from, to);
+        if !self.nodes[from].is_running
+            || !self.nodes[to].is_running
+            || self.broken_links.contains(&link)
+        {
+            trace!("Message from node {} to node {} is lost", from, to);
+            // The node retries to connect after a failure, similar to the real network.
+            if self.nodes[from].is_running && message == *self.nodes[from].connect.as_raw() {
+                let time = self.time() + CONNECT_FAILURE_DELAY;
+                let action = Action::ConnectFailure {
+                    node: from,
+                    peer: to,
+                };
+                self.schedule(time, action);
+            }
+            return;
+        }
+
+        if self.connections.insert(link) {
+            // Emulate the handshake: both nodes learn `Connect` messages of each other.
+            let incoming = ConnectedPeerAddr::In(self.nodes[from].listen_address);
+            let from_connect = self.nodes[from].connect.clone();
+            self.handle_event(
+                to,
+                NetworkEvent::PeerConnected(incoming, from_connect).into(),
+            );
+
+            let to_address = self.nodes[to].listen_address;
+            let outgoing = ConnectedPeerAddr::Out(to_address.to_string(), to_address);
+            let to_connect = self.nodes[to].connect.clone();
+            self.handle_event(
+                from,
+                NetworkEvent::PeerConnected(outgoing, to_connect).into(),
+            );
+
+            if message == *self.nodes[from].connect.as_raw() {
+                return;
+            }
+        }
+
+        let event = NetworkEvent::MessageReceived(message.into_bytes());
+        self.handle_event(to, event.into());
+    }
+
+    fn disconnect(&mut self, first: usize, second: usize) {
+        if self.connections.remove(&Self::link(first, second)) {
+            let first_key = self.nodes[first].public_key();
+            let second_key = self.nodes[second].public_key();
+            if self.nodes[first].is_running {
+                self.handle_event(first, NetworkEvent::PeerDisconnected(second_key).into());
+            }
+            if self.nodes[second].is_running {
+                self.handle_event(second, NetworkEvent::PeerDisconnected(first_key).into());
+            }
+        }
+    }
+
+    /// Processes requests produced by the node until there are none left.
+    fn process_requests(&mut self, node: usize) {
+        loop {
+            let mut processed = false;
+
+            for request in poll_all(&mut self.nodes[node].internal_requests_rx) {
+                processed = true;
+                self.process_internal_request(node, request);
+            }
+            for request in poll_all(&mut self.nodes[node].network_requests_rx) {
+                processed = true;
+                self.process_network_request(node, request);
+            }
+            for message in poll_all(&mut self.nodes[node].api_requests_rx) {
+                processed = true;
+                if self.nodes[node].is_running {
+                    self.nodes[node].handler.handle_event(message.into());
+                }
+            }
+            for transaction in poll_all(&mut self.nodes[node].transactions_rx) {
+                processed = true;
+                if self.nodes[node].is_running {
+                    self.nodes[node].handler.handle_event(transaction.into());
+                }
+            }
+            // HTTP API is not served in the simulation.
+            poll_all(&mut self.nodes[node].endpoints_rx);
+
+            if !processed {
+                break;
+            }
+        }
+    }
+
+    fn process_internal_request(&mut self, node: usize, request: InternalRequest) {
+        if !self.nodes[node].is_running {
+            return;
+        }
+
+        let handler = &mut self.nodes[node].handler;
+        let event = match request {
+            InternalRequest::JumpToRound(height, round) => {
+                handler.handle_event(InternalEvent::jump_to_round(height, round).into());
+                return;
+            }
+            InternalRequest::VerifyMessage(raw) => {
+                let message = SignedMessage::from_bytes(raw.into())
+                    .and_then(SignedMessage::into_verified::<ExonumMessage>)
+                    .map(Message::from);
+                if let Ok(message) = message {
+                    handler.handle_event(InternalEvent::message_verified(message).into());
+                }
+                return;
+            }
+            InternalRequest::VerifyMessages(raw) => {
+                for message in InternalPart::verify_batch(raw) {
+                    handler.handle_event(InternalEvent::message_verified(message).into());
+                }
+                return;
+            }
+            InternalRequest::SignMessage(request) => {
+                if let Some(message) = InternalPart::sign_message_now(request) {
+                    handler.handle_event(InternalEvent::message_signed(message).into());
+                }
+                return;
+            }
+            event => event,
+        };
+
+        match event {
+            InternalRequest::Timeout(request) => {
+                self.schedule(request.time(), Action::Timeout { node, request });
+            }
+            InternalRequest::Shutdown => self.nodes[node].is_running = false,
+            _ => unreachable!("Request has been processed above"),
+        }
+    }
+
+    fn process_network_request(&mut self, node: usize, request: NetworkRequest) {
+        match request {
+            NetworkRequest::SendMessage(key, message) => {
+                if let Some(&to) = self.node_indexes.get(&key) {
+                    let time = self.time() + self.random_latency();
+                    let action = Action::Deliver {
+                        from: node,
+                        to,
+                        message,
+                    };
+                    self.schedule(time, action);
+                }
+            }
+            NetworkRequest::DisconnectWithPeer(key) => {
+                if let Some(&other) = self.node_indexes.get(&key) {
+                    self.disconnect(node, other);
+                }
+            }
+            NetworkRequest::Shutdown => {
+                for other in 0..self.nodes.len() {
+                    if other != node {
+                        self.disconnect(node, other);
+                    }
+                }
+            }
+            NetworkRequest::RotateNetworkKey(_) | NetworkRequest::DiscoverPeers(_) => {}
+        }
+    }
+}
+
+/// Polls all items ready in the channel.
+fn poll_all<T>(receiver: &mut mpsc::Receiver<T>) -> Vec<T> {
+    futures::lazy(|| -> Result<_, ()> {
+        let mut items = vec![];
+        while let Async::Ready(Some(item)) = receiver.poll()? {
+            items.push(item);
+        }
+        Ok(items)
+    })
+    .wait()
+    .unwrap()
+}
@@ -17,17 +17,18 @@
 use bit_vec::BitVec;
 use exonum::{
     blockchain::{contains_transaction, ConsensusConfig, ProposerId, ValidatorKeys},
-    crypto::{Hash, PublicKey},
+    crypto::{Hash, KeyPair, PublicKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH},
     helpers::{byzantine_quorum, Height, Milliseconds, Round, ValidatorId},
     keys::Keys,
     merkledb::{access::RawAccess, KeySetIndex, MapIndex, ObjectHash, Patch},
-    messages::{AnyTx, Precommit, Verified},
+    messages::{AnyTx, Precommit, SignedMessage, Verified},
 };
 use failure::bail;
-use log::{error, trace};
+use log::{error, info, trace};
 
 use std::{
-    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
+    mem,
     sync::{Arc, RwLock},
     time::{Duration, SystemTime},
 };
@@ -36,7 +37,7 @@ use crate::{
     connect_list::ConnectList,
     events::network::ConnectedPeerAddr,
     messages::{BlockResponse, Connect, Consensus as ConsensusMessage, Prevote, Propose},
-    ConnectInfo,
+    CacheUsage, ConnectInfo, ConsensusMetrics, HeightMetrics, RoundMetrics, ValidatorLiveness,
 };
 
 // TODO: Move request timeouts into node configuration. (ECR-171)
@@ -49,6 +50,24 @@ pub const TRANSACTIONS_REQUEST_TIMEOUT: Milliseconds = 100;
 pub const PREVOTES_REQUEST_TIMEOUT: Milliseconds = 100;
 /// Timeout value for the `BlockRequest` message.
 pub const BLOCK_REQUEST_TIMEOUT: Milliseconds = 100;
+/// Maximum number of blocks requested or sent in response to a single `BlockRequest` message.
+pub const BLOCK_REQUEST_BATCH_SIZE: u32 = 16;
+
+/// Number of protocol violations within `PEER_VIOLATIONS_PERIOD` after which
+/// the peer is temporarily banned.
+pub const PEER_VIOLATIONS_THRESHOLD: u32 = 5;
+/// Period (in milliseconds) within which protocol violations of a peer are counted.
+pub const PEER_VIOLATIONS_PERIOD: Milliseconds = 60_000;
+/// Duration (in milliseconds) of the automatic peer ban.
+pub const PEER_BAN_DURATION: Milliseconds = 600_000;
+/// Maximum number of requests a peer may send within `PEER_REQUESTS_PERIOD`. Each request
+/// exceeding this limit is considered spam and counted as a protocol violation.
+pub const PEER_REQUESTS_LIMIT: u32 = 500;
+/// Period (in milliseconds) within which requests of a peer are counted.
+pub const PEER_REQUESTS_PERIOD: Milliseconds = 1_000;
+
+/// Number of recently committed heights for which consensus metrics are retained.
+pub const CONSENSUS_METRICS_HISTORY_LEN: usize = 100;
 
 /// State of the `NodeHandler`.
 #[derive(Debug)]
@@ -61,10 +80,17 @@ pub(crate) struct State {
 
     peers: HashMap<PublicKey, Verified<Connect>>,
     connections: HashMap<PublicKey, ConnectedPeerAddr>,
+    // Banned peers with the ban expiration time; `None` means that the ban is permanent.
+    banned_peers: HashMap<PublicKey, Option<SystemTime>>,
+    // Start of the counting period and the number of protocol violations for misbehaving peers.
+    peer_violations: HashMap<PublicKey, (SystemTime, u32)>,
+    // Start of the counting period and the number of requests received from peers.
+    peer_requests: HashMap<PublicKey, (SystemTime, u32)>,
     height_start_time: SystemTime,
     height: Height,
 
     round: Round,
+    round_start_time: SystemTime,
     locked_round: Round,
     locked_propose: Option<Hash>,
     last_hash: Hash,
@@ -85,13 +111,24 @@ pub(crate) struct State {
 
     // Maximum of node height in consensus messages.
     nodes_max_height: BTreeMap<PublicKey, Height>,
+    // Estimated clock skew of validators in milliseconds.
+    clock_skews: BTreeMap<PublicKey, i64>,
 
     validators_rounds: BTreeMap<ValidatorId, Round>,
 
     incomplete_block: Option<IncompleteBlock>,
+    // Blocks received ahead of the current height during synchronization.
+    queued_blocks: BTreeMap<Height, Verified<BlockResponse>>,
+
+    // Metrics of the rounds completed at the current height.
+    completed_rounds: Vec<RoundMetrics>,
+    // Metrics of the recently committed heights, from the oldest to the newest.
+    committed_heights: VecDeque<HeightMetrics>,
 
     // Cache that stores transactions before adding to persistent pool.
     tx_cache: BTreeMap<Hash, Verified<AnyTx>>,
+    // Hashes of the cached transactions which are not yet saved to the node schema.
+    unsaved_txs: Vec<Hash>,
 
     // An in-memory set of transaction hashes, rejected by a node
     // within block.
@@ -105,6 +142,19 @@ pub(crate) struct State {
     invalid_txs: HashSet<Hash>,
 
     keys: Keys,
+    // Key pair identifying this node among peers. It matches the initial consensus key pair
+    // and is retained if the consensus key is rotated.
+    identity: KeyPair,
+    // Consensus key pair registered in advance, which will replace the current one
+    // once it appears in the consensus configuration.
+    next_consensus_keys: Option<KeyPair>,
+    // Public key of the external signer, which signs consensus messages of the node
+    // instead of the consensus key pair in `keys`.
+    external_consensus_key: Option<PublicKey>,
+    // Keys identifying validators among peers keyed by their current consensus keys.
+    // Only validators which have rotated their consensus keys are present here;
+    // other validators are identified by their consensus keys.
+    peer_identities: HashMap<PublicKey, PublicKey>,
 }
 
 /// State of a validator node.
@@ -113,6 +163,8 @@ pub(crate) struct ValidatorState {
     id: ValidatorId,
     our_prevotes: HashMap<Round, Verified<Prevote>>,
     our_precommits: HashMap<Round, Verified<Precommit>>,
+    // Rounds for which our pre-votes are being signed by the external signer.
+    pending_prevotes: HashSet<Round>,
 }
 
 /// `RequestData` represents a request for some data to other nodes. Each enum variant will be
@@ -204,6 +256,7 @@ impl ValidatorState {
             id,
             our_precommits: HashMap::new(),
             our_prevotes: HashMap::new(),
+            pending_prevotes: HashSet::new(),
         }
     }
 
@@ -217,15 +270,17 @@ impl ValidatorState {
         self.id = id;
     }
 
-    /// Checks if the node has pre-vote for the specified round.
+    /// Checks if the node has pre-vote for the specified round, or if the pre-vote
+    /// is being signed by the external signer.
     pub fn have_prevote(&self, round: Round) -> bool {
-        self.our_prevotes.get(&round).is_some()
+        self.our_prevotes.get(&round).is_some() || self.pending_prevotes.contains(&round)
     }
 
     /// Clears pre-commits and pre-votes.
     pub fn clear(&mut self) {
         self.our_precommits.clear();
         self.our_prevotes.clear();
+        self.pending_prevotes.clear();
     }
 }
 
@@ -415,16 +470,37 @@ impl SharedConnectList {
             .map(|(pk, addr)| ConnectInfo {
                 address: addr.to_owned(),
                 public_key: *pk,
+                network_key: connect_list.network_keys.get(pk).copied(),
+                consensus_key: connect_list.consensus_keys.get(pk).copied(),
             })
             .collect()
     }
 
+    /// Returns DNS seeds from the underlying `ConnectList`.
+    pub(crate) fn dns_seeds(&self) -> Vec<String> {
+        let connect_list = self.inner.read().expect("ConnectList read lock");
+        connect_list.dns_seeds.clone()
+    }
+
     /// Update peer address in the connect list.
     pub(super) fn update_peer(&mut self, public_key: &PublicKey, address: String) {
         let mut conn_list = self.inner.write().expect("ConnectList write lock");
         conn_list.update_peer(public_key, address);
     }
 
+    /// Get the key identifying the peer which signs consensus messages with the specified
+    /// consensus key, if the keys differ.
+    pub(crate) fn identity_by_consensus_key(&self, consensus_key: &PublicKey) -> Option<PublicKey> {
+        let connect_list = self.inner.read().expect("ConnectList read lock");
+        connect_list.identity_by_consensus_key(consensus_key)
+    }
+
+    /// Get the key used by the peer to authenticate in encrypted connections.
+    pub(crate) fn network_key(&self, public_key: &PublicKey) -> PublicKey {
+        let connect_list = self.inner.read().expect("ConnectList read lock");
+        connect_list.network_key(public_key)
+    }
+
     /// Get peer address using public key.
     pub(crate) fn find_address_by_key(&self, public_key: &PublicKey) -> Option<String> {
         let connect_list = self.inner.read().expect("ConnectList read lock");
@@ -453,9 +529,13 @@ impl State {
             connect_list: SharedConnectList::from_connect_list(connect_list),
             peers,
             connections: HashMap::new(),
+            banned_peers: HashMap::new(),
+            peer_violations: HashMap::new(),
+            peer_requests: HashMap::new(),
             height: last_height,
             height_start_time,
             round: Round::zero(),
+            round_start_time: height_start_time,
             locked_round: Round::zero(),
             locked_propose: None,
             last_hash,
@@ -471,6 +551,7 @@ impl State {
             precommits_confirmed_by_majority: HashMap::new(),
 
             nodes_max_height: BTreeMap::new(),
+            clock_skews: BTreeMap::new(),
             validators_rounds: BTreeMap::new(),
 
             our_connect_message: connect,
@@ -480,11 +561,20 @@ impl State {
             config,
 
             incomplete_block: None,
+            queued_blocks: BTreeMap::new(),
+
+            completed_rounds: Vec::new(),
+            committed_heights: VecDeque::new(),
 
             tx_cache: BTreeMap::new(),
+            unsaved_txs: Vec::new(),
 
             invalid_txs: HashSet::default(),
 
+            identity: keys.consensus.clone(),
+            next_consensus_keys: None,
+            external_consensus_key: None,
+            peer_identities: HashMap::new(),
             keys,
         }
     }
@@ -552,20 +642,101 @@ impl State {
         }
 
         trace!("Updating node config={:#?}", config);
-        let validator_id = config
-            .validator_keys
-            .iter()
-            .position(|pk| pk.consensus_key == self.keys().consensus_pk())
-            .map(|id| ValidatorId(id as u16));
+        let consensus_pk = self.consensus_public_key();
+        let mut validator_id =
+            config.find_validator(|validator_keys| validator_keys.consensus_key == consensus_pk);
+
+        if validator_id.is_none() {
+            let next_consensus_pk = self.next_consensus_keys.as_ref().map(KeyPair::public_key);
+            if let Some(next_consensus_pk) = next_consensus_pk {
+                validator_id = config.find_validator(|validator_keys| {
+                    validator_keys.consensus_key == next_consensus_pk
+                });
+                if validator_id.is_some() {
+                    info!("Switching to the new consensus key {}", next_consensus_pk);
+                    self.keys.consensus = self.next_consensus_keys.take().unwrap();
+                    self.external_consensus_key = None;
+                }
+            }
+        }
+
+        if validator_id.is_none() {
+            let service_pk = self.keys().service_pk();
+            let rotated_key = config
+                .validator_keys
+                .iter()
+                .find(|validator_keys| validator_keys.service_key == service_pk)
+                .map(|validator_keys| validator_keys.consensus_key);
+            if let Some(rotated_key) = rotated_key {
+                error!(
+                    "Consensus key of the node was replaced with {} in the new configuration, \
+                     but the corresponding key pair is not registered; the node cannot \
+                     participate in the consensus",
+                    rotated_key
+                );
+            }
+        }
 
         // TODO: update connect list (ECR-1745)
 
         self.renew_validator_id(validator_id);
         trace!("Validator={:#?}", self.validator_state());
 
+        self.update_peer_identities(&config);
         self.config = config;
     }
 
+    /// Tracks identities of validators rotating their consensus keys in the new configuration.
+    /// A validator keeps its service key during the rotation, so its new consensus key
+    /// is matched with the previous one by the service key.
+    fn update_peer_identities(&mut self, config: &ConsensusConfig) {
+        for validator_keys in &config.validator_keys {
+            let previous_key = self
+                .config
+                .validator_keys
+                .iter()
+                .find(|keys| keys.service_key == validator_keys.service_key)
+                .map(|keys| keys.consensus_key);
+
+            if let Some(previous_key) = previous_key {
+                if previous_key != validator_keys.consensus_key {
+                    let identity = self.peer_identity(&previous_key);
+                    self.peer_identities
+                        .insert(validator_keys.consensus_key, identity);
+                }
+            }
+        }
+    }
+
+    /// Returns the key identifying a validator among peers by its consensus key.
+    /// Requests to the validator are addressed and sent to this key.
+    ///
+    /// The keys differ if the validator has rotated its consensus key, or if the validator
+    /// signs consensus messages with an external signer and its connect list entry
+    /// specifies the consensus key.
+    pub(crate) fn peer_identity(&self, consensus_key: &PublicKey) -> PublicKey {
+        self.peer_identities
+            .get(consensus_key)
+            .copied()
+            .or_else(|| self.connect_list.identity_by_consensus_key(consensus_key))
+            .unwrap_or(*consensus_key)
+    }
+
+    /// Returns identities of validators which have rotated their consensus keys,
+    /// keyed by the current consensus keys.
+    pub(crate) fn peer_identities(&self) -> &HashMap<PublicKey, PublicKey> {
+        &self.peer_identities
+    }
+
+    /// Restores identities of validators with rotated consensus keys, e.g., after
+    /// the node restart.
+    pub(crate) fn restore_peer_identities(
+        &mut self,
+        identities: impl IntoIterator<Item = (PublicKey, PublicKey)>,
+    ) {
+        self.peer_identities.extend(identities);
+    }
+
     /// Adds the public key, address, and `Connect` message of a validator.
     pub(super) fn add_peer(&mut self, pubkey: PublicKey, msg: Verified<Connect>) -> bool {
         self.peers.insert(pubkey, msg).is_none()
@@ -592,7 +763,7 @@ impl State {
         self.config
             .validator_keys
             .iter()
-            .any(|x| &x.consensus_key == pubkey)
+            .any(|x| self.peer_identity(&x.consensus_key) == *pubkey)
     }
 
     /// Checks if a peer is in this node's connection list.
@@ -600,6 +771,52 @@ impl State {
         self.connect_list.is_peer_allowed(pubkey)
     }
 
+    /// Checks if a peer is banned at the given time.
+    pub(super) fn is_peer_banned(&self, pubkey: &PublicKey, now: SystemTime) -> bool {
+        match self.banned_peers.get(pubkey) {
+            Some(Some(expires_at)) => *expires_at > now,
+            Some(None) => true,
+            None => false,
+        }
+    }
+
+    /// Bans a peer until the specified time, or permanently if the time is not specified.
+    pub(super) fn ban_peer(&mut self, pubkey: PublicKey, expires_at: Option<SystemTime>) {
+        self.peer_violations.remove(&pubkey);
+        self.peer_requests.remove(&pubkey);
+        self.banned_peers.insert(pubkey, expires_at);
+    }
+
+    /// Lifts a ban from a peer. Returns `false` if the peer is not banned.
+    pub(super) fn unban_peer(&mut self, pubkey: &PublicKey) -> bool {
+        self.banned_peers.remove(pubkey).is_some()
+    }
+
+    /// Returns peers banned at the given time with the ban expiration times.
+    pub(crate) fn banned_peers(
+        &self,
+        now: SystemTime,
+    ) -> impl Iterator<Item = (PublicKey, Option<SystemTime>)> + '_ {
+        self.banned_peers
+            .iter()
+            .filter(move |(_, expires_at)| expires_at.map_or(true, |expires_at| expires_at > now))
+            .map(|(pubkey, expires_at)| (*pubkey, *expires_at))
+    }
+
+    /// Records a protocol violation by a peer and returns the number of violations
+    /// within the current `PEER_VIOLATIONS_PERIOD`.
+    pub(super) fn add_peer_violation(&mut self, pubkey: PublicKey, now: SystemTime) -> u32 {
+        let counter = self.peer_violations.entry(pubkey).or_insert((now, 0));
+        increment_counter(counter, now, PEER_VIOLATIONS_PERIOD)
+    }
+
+    /// Records a request from a peer and returns the number of requests
+    /// within the current `PEER_REQUESTS_PERIOD`.
+    pub(super) fn add_peer_request(&mut self, pubkey: PublicKey, now: SystemTime) -> u32 {
+        let counter = self.peer_requests.entry(pubkey).or_insert((now, 0));
+        increment_counter(counter, now, PEER_REQUESTS_PERIOD)
+    }
+
     /// Returns the keys of known peers with their `Connect` messages.
     pub fn peers(&self) -> &HashMap<PublicKey, Verified<Connect>> {
         &self.peers
@@ -621,11 +838,44 @@ impl State {
         &self.keys
     }
 
+    /// Returns the key pair identifying this node among peers.
+    pub(crate) fn identity_keys(&self) -> &KeyPair {
+        &self.identity
+    }
+
+    /// Registers a consensus key pair to switch to once the public key appears
+    /// in the consensus configuration instead of the current one.
+    pub(crate) fn register_consensus_keys(&mut self, keys: KeyPair) {
+        self.next_consensus_keys = Some(keys);
+    }
+
+    /// Returns the public key of the external signer if consensus messages of the node
+    /// are signed by it.
+    pub(crate) fn external_consensus_key(&self) -> Option<PublicKey> {
+        self.external_consensus_key
+    }
+
+    /// Returns the public key used to sign consensus messages of the node.
+    pub(crate) fn consensus_public_key(&self) -> PublicKey {
+        self.external_consensus_key
+            .unwrap_or_else(|| self.keys.consensus_pk())
+    }
+
+    /// Makes the node sign consensus messages by the external signer with the specified
+    /// public key and updates validator id of the node accordingly.
+    pub(crate) fn set_external_consensus_key(&mut self, public_key: PublicKey) {
+        self.external_consensus_key = Some(public_key);
+        let validator_id = self
+            .config
+            .find_validator(|validator_keys| validator_keys.consensus_key == public_key);
+        self.renew_validator_id(validator_id);
+    }
+
     /// Returns the leader id for the specified round and current height.
     pub fn leader(&self, round: Round) -> ValidatorId {
-        let height: u64 = self.height().into();
-        let round: u64 = round.into();
-        ValidatorId(((height + round) % (self.validators().len() as u64)) as u16)
+        self.config
+            .leader_rotation
+            .leader(self.validators().len(), self.height(), round)
     }
 
     /// Updates known round for a validator and returns
@@ -685,6 +935,18 @@ impl State {
             .or_insert_with(Height::zero) = height;
     }
 
+    /// Returns the estimated clock skew of a validator identified by the public key,
+    /// or `None` if the skew was never estimated.
+    pub(super) fn clock_skew(&self, key: &PublicKey) -> Option<i64> {
+        self.clock_skews.get(key).cloned()
+    }
+
+    /// Updates the estimated clock skew of a validator identified by the public key.
+    /// Returns the previous estimation.
+    pub(super) fn set_clock_skew(&mut self, key: PublicKey, skew: i64) -> Option<i64> {
+        self.clock_skews.insert(key, skew)
+    }
+
     /// Returns a list of nodes whose height is bigger than one of the current node.
     pub(super) fn nodes_with_bigger_height(&self) -> Vec<&PublicKey> {
         self.nodes_max_height
@@ -777,11 +1039,122 @@ impl State {
         self.incomplete_block.as_ref()
     }
 
+    /// Records metrics of the current round before the node moves to another round.
+    pub(super) fn complete_round(&mut self, now: SystemTime) {
+        let metrics = RoundMetrics {
+            round: self.round,
+            leader: self.leader(self.round),
+            duration: millis_between(self.round_start_time, now),
+        };
+        self.completed_rounds.push(metrics);
+        self.round_start_time = now;
+    }
+
+    /// Records metrics of the current height once a block is committed at it
+    /// by the consensus algorithm.
+    pub(super) fn complete_height(
+        &mut self,
+        proposer_id: ValidatorId,
+        precommits: Vec<ValidatorId>,
+        now: SystemTime,
+    ) {
+        self.complete_round(now);
+        let metrics = HeightMetrics {
+            height: self.height,
+            duration: millis_between(self.height_start_time, now),
+            rounds: mem::replace(&mut self.completed_rounds, Vec::new()),
+            proposer_id,
+            precommits,
+        };
+
+        if self.committed_heights.len() == CONSENSUS_METRICS_HISTORY_LEN {
+            self.committed_heights.pop_front();
+        }
+        self.committed_heights.push_back(metrics);
+    }
+
+    /// Returns consensus metrics of the node.
+    pub(crate) fn consensus_metrics(&self, now: SystemTime) -> ConsensusMetrics {
+        ConsensusMetrics {
+            height: self.height,
+            round: self.round,
+            height_duration: millis_between(self.height_start_time, now),
+            round_duration: millis_between(self.round_start_time, now),
+            queued_messages: self.queued.len(),
+            queued_blocks: self.queued_blocks.len(),
+            pending_requests: self.requests.len(),
+            unknown_txs: self.unknown_txs.len(),
+            tx_cache_len: self.tx_cache.len(),
+            committed_heights: self.committed_heights.iter().cloned().collect(),
+        }
+    }
+
+    /// Returns usage of the in-memory caches.
+    pub(crate) fn cache_usage(&self) -> CacheUsage {
+        fn message_size(raw: &SignedMessage) -> usize {
+            raw.payload.len() + PUBLIC_KEY_LENGTH + SIGNATURE_LENGTH
+        }
+
+        CacheUsage {
+            tx_cache_len: self.tx_cache.len(),
+            tx_cache_size: self
+                .tx_cache
+                .values()
+                .map(|tx| message_size(tx.as_raw()))
+                .sum(),
+            queued_messages: self.queued.len(),
+            queued_messages_size: self
+                .queued
+                .iter()
+                .map(|msg| message_size(msg.as_raw()))
+                .sum(),
+            queued_blocks: self.queued_blocks.len(),
+            queued_blocks_size: self
+                .queued_blocks
+                .values()
+                .map(|block| message_size(block.as_raw()))
+                .sum(),
+        }
+    }
+
+    /// Returns liveness of the validators as seen by the node.
+    pub(crate) fn validators_liveness(&self) -> Vec<ValidatorLiveness> {
+        let our_id = self.validator_id();
+        self.validators()
+            .iter()
+            .enumerate()
+            .map(|(i, keys)| {
+                let id = ValidatorId(i as u16);
+                if our_id == Some(id) {
+                    return ValidatorLiveness {
+                        id,
+                        consensus_key: keys.consensus_key,
+                        is_connected: true,
+                        height: self.height,
+                        round: Some(self.round),
+                        clock_skew: Some(0),
+                    };
+                }
+
+                ValidatorLiveness {
+                    id,
+                    consensus_key: keys.consensus_key,
+                    is_connected: self.connections.contains_key(&keys.consensus_key),
+                    height: self.node_height(&keys.consensus_key),
+                    round: self.validators_rounds.get(&id).cloned(),
+                    clock_skew: self.clock_skew(&keys.consensus_key),
+                }
+            })
+            .collect()
+    }
+
     /// Increments the node height by one and resets previous height data.
     pub(super) fn new_height(&mut self, block_hash: &Hash, height_start_time: SystemTime) {
         self.height.increment();
         self.height_start_time = height_start_time;
         self.round = Round::first();
+        self.round_start_time = height_start_time;
+        self.completed_rounds.clear();
         self.locked_round = Round::zero();
         self.locked_propose = None;
         self.last_hash = *block_hash;
@@ -795,7 +1168,13 @@ impl State {
         if let Some(ref mut validator_state) = self.validator_state {
             validator_state.clear();
         }
-        self.requests.clear(); // FIXME: Clear all timeouts. (ECR-171)
+        // Requests for the following blocks are retained, so that blocks could be
+        // requested in advance during synchronization.
+        let height = self.height;
+        self.requests.retain(|data, _| match data {
+            RequestData::Block(block_height) => *block_height >= height,
+            _ => false,
+        }); // FIXME: Clear all timeouts. (ECR-171)
         self.incomplete_block = None;
         self.invalid_txs.clear();
     }
@@ -938,6 +1317,20 @@ impl State {
         }
     }
 
+    /// Marks that the pre-vote of this node for the specified round is being signed
+    /// by the external signer, so that the node does not vote in this round once again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this method is called for a non-validator node.
+    pub(super) fn add_pending_prevote(&mut self, round: Round) {
+        if let Some(ref mut validator_state) = self.validator_state {
+            validator_state.pending_prevotes.insert(round);
+        } else {
+            panic!("called add_pending_prevote for auditor node")
+        }
+    }
+
     /// Adds propose from this node to the proposes list for the current height. Such propose
     /// cannot contain unknown transactions. Returns hash of the propose.
     pub(super) fn add_self_propose(&mut self, msg: Verified<Propose>) -> Hash {
@@ -1207,6 +1600,18 @@ impl State {
         false
     }
 
+    /// Saves a block received ahead of the current height to process it later.
+    pub(super) fn queue_block(&mut self, msg: Verified<BlockResponse>) {
+        self.queued_blocks.insert(msg.payload().block().height, msg);
+    }
+
+    /// Takes a previously queued block for the given height. Blocks with lower heights
+    /// are discarded.
+    pub(super) fn take_queued_block(&mut self, height: Height) -> Option<Verified<BlockResponse>> {
+        self.queued_blocks = self.queued_blocks.split_off(&height);
+        self.queued_blocks.remove(&height)
+    }
+
     /// Adds data-request to the queue. Returns `true` if it is a new request.
     pub(super) fn request(&mut self, data: RequestData, peer: PublicKey) -> bool {
         let state = self.requests.entry(data).or_insert_with(RequestState::new);
@@ -1261,6 +1666,16 @@ impl State {
         list.add(peer);
     }
 
+    /// Remove peer from node's `ConnectList`. Returns `false` if the peer is not in the list.
+    pub fn remove_peer_from_connect_list(&mut self, public_key: &PublicKey) -> bool {
+        let mut list = self
+            .connect_list
+            .inner
+            .write()
+            .expect("ConnectList write lock");
+        list.remove(public_key)
+    }
+
     /// Returns the transactions cache length.
     pub fn tx_cache_len(&self) -> usize {
         self.tx_cache.len()
@@ -1276,8 +1691,34 @@ impl State {
         &mut self.tx_cache
     }
 
+    /// Returns mutable reference to the hashes of the cached transactions which
+    /// are not yet saved to the node schema.
+    pub(super) fn unsaved_txs_mut(&mut self) -> &mut Vec<Hash> {
+        &mut self.unsaved_txs
+    }
+
     /// Returns mutable reference to the invalid transactions cache.
     pub(super) fn invalid_txs_mut(&mut self) -> &mut HashSet<Hash> {
         &mut self.invalid_txs
     }
 }
+
+/// Returns the number of milliseconds elapsed between two moments of time.
+fn millis_between(start: SystemTime, end: SystemTime) -> Milliseconds {
+    end.duration_since(start).unwrap_or_default().as_millis() as Milliseconds
+}
+
+/// Increments the counter of events for a peer, restarting it if the counting
+/// period has elapsed. Returns the number of events within the current period.
+fn increment_counter(
+    (period_start, count): &mut (SystemTime, u32),
+    now: SystemTime,
+    period: Milliseconds,
+) -> u32 {
+    if millis_between(*period_start, now) > period {
+        *period_start = now;
+        *count = 0;
+    }
+    *count += 1;
+    *count
+}
@@ -0,0 +1,525 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Selection of transactions for block proposals.
+
+use exonum::{
+    blockchain::Schema,
+    crypto::Hash,
+    merkledb::{KeySetIndex, MapIndex, Snapshot},
+    messages::{AnyTx, Verified},
+    runtime::{InstanceId, SnapshotExt},
+};
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    cmp::Reverse,
+    collections::{btree_map, BTreeMap, HashMap, VecDeque},
+    fmt, iter,
+};
+
+/// Number of hashes loaded from the persistent pool at once by `PoolTransactions`.
+const POOL_HASHES_BATCH_SIZE: usize = 128;
+
+/// Uncommitted transactions which may be included into a block proposal.
+#[derive(Clone, Copy)]
+pub struct TxPool<'a> {
+    snapshot: &'a dyn Snapshot,
+    cache: &'a BTreeMap<Hash, Verified<AnyTx>>,
+}
+
+impl<'a> TxPool<'a> {
+    pub(crate) fn new(
+        snapshot: &'a dyn Snapshot,
+        cache: &'a BTreeMap<Hash, Verified<AnyTx>>,
+    ) -> Self {
+        Self { snapshot, cache }
+    }
+
+    /// Returns the snapshot of the blockchain state the proposal is created for.
+    pub fn snapshot(&self) -> &'a dyn Snapshot {
+        self.snapshot
+    }
+
+    /// Returns the number of transactions in the pool.
+    pub fn len(&self) -> usize {
+        let persistent_len = Schema::new(self.snapshot).transactions_pool_len();
+        self.cache.len() + persistent_len as usize
+    }
+
+    /// Checks whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks whether the pool contains a transaction with the specified hash.
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.cache.contains_key(hash)
+            || Schema::new(self.snapshot)
+                .transactions_pool()
+                .contains(hash)
+    }
+
+    /// Returns at most `limit` hashes of transactions in the default order: transactions from
+    /// the in-memory cache go first, followed by transactions from the persistent pool.
+    ///
+    /// Unlike `transactions`, this method does not load transactions from the storage.
+    pub fn hashes(&self, limit: usize) -> Vec<Hash> {
+        let mut hashes: Vec<_> = self.cache.keys().take(limit).cloned().collect();
+        let remaining = limit - hashes.len();
+        let pool = Schema::new(self.snapshot).transactions_pool();
+        hashes.extend(pool.iter().take(remaining));
+        hashes
+    }
+
+    /// Returns an iterator over transactions in the pool in the default order.
+    ///
+    /// Transactions from the persistent pool are loaded from the storage lazily, so
+    /// the iteration can be stopped early without reading the entire pool.
+    pub fn transactions(&self) -> PoolTransactions<'a> {
+        let schema = Schema::new(self.snapshot);
+        PoolTransactions {
+            cache: self.cache.iter(),
+            pool: schema.transactions_pool(),
+            transactions: schema.transactions(),
+            pool_hashes: VecDeque::new(),
+            last_pool_hash: None,
+            pool_exhausted: false,
+        }
+    }
+}
+
+impl fmt::Debug for TxPool<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("TxPool")
+            .field("cache_len", &self.cache.len())
+            .finish()
+    }
+}
+
+/// Iterator over transactions in a `TxPool` returned by `TxPool::transactions()`.
+pub struct PoolTransactions<'a> {
+    cache: btree_map::Iter<'a, Hash, Verified<AnyTx>>,
+    pool: KeySetIndex<&'a dyn Snapshot, Hash>,
+    transactions: MapIndex<&'a dyn Snapshot, Hash, Verified<AnyTx>>,
+    pool_hashes: VecDeque<Hash>,
+    last_pool_hash: Option<Hash>,
+    pool_exhausted: bool,
+}
+
+impl PoolTransactions<'_> {
+    /// Loads the next batch of hashes from the persistent pool.
+    fn load_pool_hashes(&mut self) {
+        let hashes: Box<dyn Iterator<Item = Hash>> = match self.last_pool_hash {
+            // `iter_from` yields the hash it starts from, which has been processed already.
+            Some(ref last_hash) => Box::new(self.pool.iter_from(last_hash).skip(1)),
+            None => Box::new(self.pool.iter()),
+        };
+        self.pool_hashes.extend(hashes.take(POOL_HASHES_BATCH_SIZE));
+        self.pool_exhausted = self.pool_hashes.len() < POOL_HASHES_BATCH_SIZE;
+        if let Some(&last_hash) = self.pool_hashes.back() {
+            self.last_pool_hash = Some(last_hash);
+        }
+    }
+}
+
+impl Iterator for PoolTransactions<'_> {
+    type Item = (Hash, Verified<AnyTx>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((hash, tx)) = self.cache.next() {
+            return Some((*hash, tx.clone()));
+        }
+
+        loop {
+            if self.pool_hashes.is_empty() {
+                if self.pool_exhausted {
+                    return None;
+                }
+                self.load_pool_hashes();
+            }
+            let hash = self.pool_hashes.pop_front()?;
+            if let Some(tx) = self.transactions.get(&hash) {
+                return Some((hash, tx));
+            }
+        }
+    }
+}
+
+impl iter::FusedIterator for PoolTransactions<'_> {}
+
+impl fmt::Debug for PoolTransactions<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PoolTransactions")
+            .field("loaded_pool_hashes", &self.pool_hashes.len())
+            .field("pool_exhausted", &self.pool_exhausted)
+            .finish()
+    }
+}
+
+/// Source of fees paid by transactions, which are used by the built-in selection policies
+/// to order transactions to services with equal priorities.
+///
+/// The node has no notion of fees by itself; fees are defined by services (e.g., metering
+/// of a cryptocurrency service). The fees can be supplied with `NodeBuilder::with_tx_fees`.
+pub trait TransactionFees: Send {
+    /// Returns the fee paid by the `transaction` if it is executed on top of the `snapshot`.
+    /// Transactions for which the fee is unknown should be assigned zero fee.
+    fn fee(&self, snapshot: &dyn Snapshot, transaction: &Verified<AnyTx>) -> u64;
+}
+
+impl<F> TransactionFees for F
+where
+    F: Fn(&dyn Snapshot, &Verified<AnyTx>) -> u64 + Send,
+{
+    fn fee(&self, snapshot: &dyn Snapshot, transaction: &Verified<AnyTx>) -> u64 {
+        self(snapshot, transaction)
+    }
+}
+
+/// Policy of selecting transactions for block proposals created by the node.
+///
+/// The policy is applied each time the node is the leader of a consensus round. Besides
+/// the built-in policies defined in `TxSelectionStrategy`, custom policies can be set
+/// with `NodeBuilder::with_tx_selector`.
+pub trait SelectTransactions: Send {
+    /// Selects at most `limit` transactions from the `pool` to be included into a proposal.
+    /// Transactions are executed in the order of returned hashes. Hashes of transactions
+    /// not belonging to the pool are ignored.
+    fn select(&self, pool: TxPool<'_>, limit: usize) -> Vec<Hash>;
+}
+
+/// Built-in policies of selecting transactions for block proposals.
+///
+/// Both policies take priorities of services specified in the memory pool configuration
+/// into account: transactions to services with higher priorities are always selected first.
+/// If transaction fees are supplied to the node, transactions with higher fees are selected
+/// first among transactions to services with the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxSelectionStrategy {
+    /// Transactions are selected in the default pool order.
+    Fifo,
+    /// Transactions are selected alternately from each service, so that a service
+    /// with a lot of transactions cannot delay transactions to other services.
+    ServiceRoundRobin,
+}
+
+impl Default for TxSelectionStrategy {
+    fn default() -> Self {
+        TxSelectionStrategy::Fifo
+    }
+}
+
+impl TxSelectionStrategy {
+    /// Creates a transaction selector for this strategy.
+    pub(crate) fn selector(
+        self,
+        service_priorities: BTreeMap<String, u8>,
+        fees: Option<Box<dyn TransactionFees>>,
+    ) -> Box<dyn SelectTransactions> {
+        match self {
+            TxSelectionStrategy::Fifo => Box::new(Fifo {
+                service_priorities,
+                fees,
+            }),
+            TxSelectionStrategy::ServiceRoundRobin => Box::new(ServiceRoundRobin {
+                service_priorities,
+                fees,
+            }),
+        }
+    }
+}
+
+/// Returns the fee paid by the transaction, or zero if fees are not supplied.
+fn tx_fee(
+    fees: &Option<Box<dyn TransactionFees>>,
+    snapshot: &dyn Snapshot,
+    transaction: &Verified<AnyTx>,
+) -> u64 {
+    fees.as_ref()
+        .map_or(0, |fees| fees.fee(snapshot, transaction))
+}
+
+/// Adds a transaction with the specified fee to the `bucket`, retaining only transactions
+/// which may be among the first `limit` selected ones. If transactions are ordered by fees,
+/// the bucket should be finalized with `truncate_by_fee` once all transactions are added.
+fn push_bounded(bucket: &mut Vec<(Hash, u64)>, entry: (Hash, u64), limit: usize, by_fee: bool) {
+    if !by_fee {
+        // The order of transactions is retained, so the first `limit` transactions win.
+        if bucket.len() < limit {
+            bucket.push(entry);
+        }
+        return;
+    }
+
+    bucket.push(entry);
+    if bucket.len() >= 2 * limit {
+        truncate_by_fee(bucket, limit);
+    }
+}
+
+/// Orders transactions in the `bucket` by fees and retains `limit` transactions with
+/// the highest fees. Sorting is stable, so the order of transactions with equal fees
+/// is retained.
+fn truncate_by_fee(bucket: &mut Vec<(Hash, u64)>, limit: usize) {
+    bucket.sort_by_key(|&(_, fee)| Reverse(fee));
+    bucket.truncate(limit);
+}
+
+/// Resolves priorities of service instances keyed by the instance name.
+fn instance_priorities(
+    snapshot: &dyn Snapshot,
+    service_priorities: &BTreeMap<String, u8>,
+) -> HashMap<InstanceId, u8> {
+    let dispatcher_schema = snapshot.for_dispatcher();
+    service_priorities
+        .iter()
+        .filter_map(|(name, &priority)| {
+            let instance = dispatcher_schema.get_instance(name.as_str())?;
+            Some((instance.spec.id, priority))
+        })
+        .collect()
+}
+
+struct Fifo {
+    service_priorities: BTreeMap<String, u8>,
+    fees: Option<Box<dyn TransactionFees>>,
+}
+
+impl fmt::Debug for Fifo {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Fifo")
+            .field("service_priorities", &self.service_priorities)
+            .field("has_fees", &self.fees.is_some())
+            .finish()
+    }
+}
+
+impl SelectTransactions for Fifo {
+    fn select(&self, pool: TxPool<'_>, limit: usize) -> Vec<Hash> {
+        if limit == 0 {
+            return vec![];
+        }
+        if self.service_priorities.is_empty() && self.fees.is_none() {
+            return pool.hashes(limit);
+        }
+
+        let snapshot = pool.snapshot();
+        let priorities = instance_priorities(snapshot, &self.service_priorities);
+        let max_priority = priorities.values().copied().max().unwrap_or(0);
+        let by_fee = self.fees.is_some();
+
+        let mut buckets: BTreeMap<Reverse<u8>, Vec<(Hash, u64)>> = BTreeMap::new();
+        for (hash, tx) in pool.transactions() {
+            let instance_id = tx.payload().call_info.instance_id;
+            let priority = priorities.get(&instance_id).copied().unwrap_or(0);
+            let fee = tx_fee(&self.fees, snapshot, &tx);
+            let bucket = buckets.entry(Reverse(priority)).or_default();
+            push_bounded(bucket, (hash, fee), limit, by_fee);
+
+            // If transactions are not ordered by fees, the remaining transactions cannot
+            // displace the selected ones once the bucket with the highest priority is full.
+            if !by_fee && priority == max_priority && bucket.len() == limit {
+                break;
+            }
+        }
+
+        let mut selected = Vec::with_capacity(limit);
+        for mut bucket in buckets.into_iter().map(|(_, bucket)| bucket) {
+            if by_fee {
+                truncate_by_fee(&mut bucket, limit);
+            }
+            selected.extend(bucket.into_iter().map(|(hash, _)| hash));
+        }
+        selected.truncate(limit);
+        selected
+    }
+}
+
+struct ServiceRoundRobin {
+    service_priorities: BTreeMap<String, u8>,
+    fees: Option<Box<dyn TransactionFees>>,
+}
+
+impl fmt::Debug for ServiceRoundRobin {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ServiceRoundRobin")
+            .field("service_priorities", &self.service_priorities)
+            .field("has_fees", &self.fees.is_some())
+            .finish()
+    }
+}
+
+impl SelectTransactions for ServiceRoundRobin {
+    fn select(&self, pool: TxPool<'_>, limit: usize) -> Vec<Hash> {
+        if limit == 0 {
+            return vec![];
+        }
+
+        let snapshot = pool.snapshot();
+        let priorities = instance_priorities(snapshot, &self.service_priorities);
+        let by_fee = self.fees.is_some();
+
+        // Queues of transactions to each service grouped by the service priority. A service
+        // cannot get more than `limit` transactions into the proposal, so longer queues
+        // are truncated.
+        let mut buckets: BTreeMap<Reverse<u8>, BTreeMap<InstanceId, Vec<(Hash, u64)>>> =
+            BTreeMap::new();
+        for (hash, tx) in pool.transactions() {
+            let instance_id = tx.payload().call_info.instance_id;
+            let priority = priorities.get(&instance_id).copied().unwrap_or(0);
+            let fee = tx_fee(&self.fees, snapshot, &tx);
+            let bucket = buckets
+                .entry(Reverse(priority))
+                .or_default()
+                .entry(instance_id)
+                .or_default();
+            push_bounded(bucket, (hash, fee), limit, by_fee);
+        }
+
+        // Transactions with higher fees go first within the queue of each service.
+        let mut queues: BTreeMap<Reverse<u8>, BTreeMap<InstanceId, VecDeque<(Hash, u64)>>> =
+            BTreeMap::new();
+        for (priority, services) in buckets {
+            let services = services
+                .into_iter()
+                .map(|(instance_id, mut bucket)| {
+                    if by_fee {
+                        truncate_by_fee(&mut bucket, limit);
+                    }
+                    (instance_id, VecDeque::from(bucket))
+                })
+                .collect();
+            queues.insert(priority, services);
+        }
+
+        let mut selected = Vec::with_capacity(limit);
+        for services in queues.values_mut() {
+            while selected.len() < limit && services.values().any(|queue| !queue.is_empty()) {
+                for queue in services.values_mut() {
+                    if selected.len() == limit {
+                        break;
+                    }
+                    if let Some((hash, _)) = queue.pop_front() {
+                        selected.push(hash);
+                    }
+                }
+            }
+        }
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum::{
+        crypto::gen_keypair,
+        merkledb::{Database, ObjectHash, TemporaryDB},
+        runtime::CallInfo,
+    };
+
+    use super::*;
+
+    fn create_tx(instance_id: InstanceId, index: usize) -> Verified<AnyTx> {
+        let keypair = gen_keypair();
+        AnyTx {
+            call_info: CallInfo::new(instance_id, 0),
+            arguments: index.to_le_bytes().to_vec(),
+            valid_until: None,
+        }
+        .sign(keypair.0, &keypair.1)
+    }
+
+    /// Creates a database with `persistent_len` transactions in the persistent pool
+    /// and `cache_len` transactions in the in-memory cache.
+    fn create_pool(
+        persistent_len: usize,
+        cache_len: usize,
+    ) -> (TemporaryDB, BTreeMap<Hash, Verified<AnyTx>>) {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut schema = Schema::new(&fork);
+        for i in 0..persistent_len {
+            schema.add_transaction_into_pool(create_tx(0, i));
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let cache = (0..cache_len)
+            .map(|i| {
+                let tx = create_tx(0, persistent_len + i);
+                (tx.object_hash(), tx)
+            })
+            .collect();
+        (db, cache)
+    }
+
+    #[test]
+    fn pool_transactions_are_loaded_lazily() {
+        let persistent_len = POOL_HASHES_BATCH_SIZE * 2 + 5;
+        let (db, cache) = create_pool(persistent_len, 3);
+        let snapshot = db.snapshot();
+        let pool = TxPool::new(&*snapshot, &cache);
+        assert_eq!(pool.len(), persistent_len + 3);
+
+        let hashes: Vec<_> = pool.transactions().map(|(hash, _)| hash).collect();
+        assert_eq!(hashes, pool.hashes(persistent_len + 3));
+        let pool_hashes: Vec<_> = Schema::new(&snapshot).transactions_pool().iter().collect();
+        assert_eq!(hashes[..3], cache.keys().cloned().collect::<Vec<_>>()[..]);
+        assert_eq!(hashes[3..], pool_hashes[..]);
+
+        // Only a single batch of hashes is loaded from the persistent pool.
+        let mut transactions = pool.transactions();
+        transactions.by_ref().take(5).for_each(drop);
+        assert_eq!(transactions.pool_hashes.len(), POOL_HASHES_BATCH_SIZE - 2);
+        assert!(!transactions.pool_exhausted);
+        assert_eq!(transactions.count(), persistent_len - 2);
+    }
+
+    #[test]
+    fn fee_selection_with_large_pool() {
+        let (db, cache) = create_pool(100, 20);
+        let snapshot = db.snapshot();
+        let pool = TxPool::new(&*snapshot, &cache);
+        let fee =
+            |_: &dyn Snapshot, tx: &Verified<AnyTx>| u64::from(tx.object_hash().as_ref()[0] % 8);
+
+        // Reference selection: stable sort of the entire pool by fees.
+        let mut expected: Vec<_> = pool
+            .transactions()
+            .map(|(hash, tx)| (hash, fee(&*snapshot, &tx)))
+            .collect();
+        expected.sort_by_key(|&(_, fee)| Reverse(fee));
+        let expected: Vec<_> = expected.into_iter().map(|(hash, _)| hash).collect();
+
+        for &strategy in &[
+            TxSelectionStrategy::Fifo,
+            TxSelectionStrategy::ServiceRoundRobin,
+        ] {
+            let selector = strategy.selector(
+                BTreeMap::new(),
+                Some(Box::new(fee) as Box<dyn TransactionFees>),
+            );
+            for &limit in &[0, 1, 7, 50, 200] {
+                let selected = selector.select(pool, limit);
+                let expected_len = limit.min(expected.len());
+                assert_eq!(selected[..], expected[..expected_len]);
+            }
+        }
+    }
+}
@@ -15,22 +15,33 @@
 //! High-level tests for the Exonum node.
 
 use exonum::{
-    blockchain::config::GenesisConfigBuilder,
+    blockchain::{config::GenesisConfigBuilder, Schema},
+    crypto::{gen_keypair, KeyPair, PublicKey},
+    helpers::Height,
+    keys::{SignatureFuture, Signer},
     merkledb::{Database, TemporaryDB},
 };
 use exonum_derive::{ServiceDispatcher, ServiceFactory};
 use exonum_rust_runtime::{AfterCommitContext, RustRuntime, Service, ServiceFactory};
-use futures::{sync::mpsc, Future, Stream};
+use failure::format_err;
+use futures::{future, sync::mpsc, Future, Stream};
 use tokio::util::FutureExt;
 use tokio_core::reactor::Core;
 
 use std::{
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     thread,
     time::Duration,
 };
 
-use exonum_node::{generate_testnet_config, Node, NodeBuilder, NodeConfig, ShutdownHandle};
+use exonum_node::{
+    generate_testnet_config, ConnectListConfig, Node, NodeBuilder, NodeConfig, ShutdownHandle,
+};
+#[cfg(feature = "simulation")]
+use exonum_node::simulation::SimulatedNetwork;
 
 #[derive(Debug)]
 struct RunHandle {
@@ -95,6 +106,27 @@ impl StartCheckerServiceFactory {
     }
 }
 
+/// Signer failing every other attempt to sign a message.
+#[derive(Debug)]
+struct FlakySigner {
+    keypair: KeyPair,
+    attempts: AtomicUsize,
+}
+
+impl Signer for FlakySigner {
+    fn public_key(&self) -> PublicKey {
+        self.keypair.public_key()
+    }
+
+    fn sign(&self, data: &[u8]) -> SignatureFuture {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) % 2 == 0 {
+            Box::new(future::err(format_err!("Signer is unavailable")))
+        } else {
+            Signer::sign(&self.keypair, data)
+        }
+    }
+}
+
 fn run_nodes(count: u16, start_port: u16) -> (Vec<RunHandle>, Vec<mpsc::UnboundedReceiver<()>>) {
     let mut node_threads = Vec::new();
     let mut commit_rxs = Vec::new();
@@ -139,6 +171,55 @@ fn test_node_run() {
     }
 }
 
+#[test]
+fn test_node_with_external_consensus_signer() {
+    let mut node_cfg = generate_testnet_config(1, 16_700).pop().unwrap();
+    // The validator key pair is held by the signer only; the node has a separate key pair
+    // identifying it among peers.
+    let validator_keys = node_cfg.keys.consensus.clone();
+    node_cfg.keys.consensus = KeyPair::from(gen_keypair());
+    node_cfg.connect_list = ConnectListConfig::default();
+    let signer = FlakySigner {
+        keypair: validator_keys.clone(),
+        attempts: AtomicUsize::new(0),
+    };
+
+    let (commit_tx, commit_rx) = mpsc::unbounded();
+    let service = CommitWatcherService(commit_tx);
+    let artifact = service.artifact_id();
+    let genesis_config = GenesisConfigBuilder::with_consensus_config(node_cfg.consensus.clone())
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(2, "commit-watcher"))
+        .build();
+    let node = NodeBuilder::new(TemporaryDB::new(), node_cfg, genesis_config)
+        .with_runtime_fn(|channel| {
+            RustRuntime::builder()
+                .with_factory(service)
+                .build(channel.endpoints_sender())
+        })
+        .with_consensus_signer(signer)
+        .build();
+    let blockchain = node.blockchain().clone();
+    let handle = RunHandle::new(node);
+
+    // Blocks are created even though every other attempt to sign a message fails.
+    let mut core = Core::new().unwrap();
+    let future = commit_rx
+        .take(3)
+        .collect()
+        .timeout(Duration::from_secs(60))
+        .map_err(drop);
+    core.run(future).expect("Node has not created blocks");
+    handle.join();
+
+    let snapshot = blockchain.snapshot();
+    let proof = Schema::new(&snapshot)
+        .block_and_precommits(Height(1))
+        .unwrap();
+    assert_eq!(proof.precommits.len(), 1);
+    assert_eq!(proof.precommits[0].author(), validator_keys.public_key());
+}
+
 #[test]
 fn test_node_restart_regression() {
     let start_node = |node_cfg: NodeConfig, db, start_times| {
@@ -173,3 +254,49 @@ fn test_node_restart_regression() {
     // and `commit_service` methods), and then once on each new node startup.
     assert_eq!(*start_times.lock().unwrap(), 3);
 }
+
+#[test]
+#[cfg(feature = "simulation")]
+fn test_simulated_network() {
+    let nodes = generate_testnet_config(4, 16_400)
+        .into_iter()
+        .map(|node_cfg| {
+            let (commit_tx, _) = mpsc::unbounded();
+            let service = CommitWatcherService(commit_tx);
+            let artifact = service.artifact_id();
+            let genesis_config =
+                GenesisConfigBuilder::with_consensus_config(node_cfg.consensus.clone())
+                    .with_artifact(artifact.clone())
+                    .with_instance(artifact.into_default_instance(2, "commit-watcher"))
+                    .build();
+
+            NodeBuilder::new(TemporaryDB::new(), node_cfg, genesis_config)
+                .with_runtime_fn(|channel| {
+                    RustRuntime::builder()
+                        .with_factory(service)
+                        .build(channel.endpoints_sender())
+                })
+                .build()
+        });
+
+    let timeout = Duration::from_secs(60);
+    let mut network = SimulatedNetwork::new(nodes).with_seed(7);
+    assert!(network.run_until_height(Height(3), timeout));
+
+    // The remaining 3 validators form a supermajority, so they continue creating blocks.
+    network.set_node_isolated(3, true);
+    let isolated_height = network.height(3);
+    assert!(network.run_until(|network| network.height(0) >= Height(6), timeout));
+    assert_eq!(network.height(3), isolated_height);
+
+    // After the isolation ends, the node catches up with the others.
+    network.set_node_isolated(3, false);
+    let target_height = network.height(0);
+    assert!(network.run_until(|network| network.height(3) >= target_height, timeout));
+    assert_eq!(
+        network.blockchain(3).last_hash(),
+        Schema::new(&network.blockchain(0).snapshot())
+            .block_hash_by_height(network.height(3))
+            .unwrap()
+    );
+}
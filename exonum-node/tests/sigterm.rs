@@ -0,0 +1,74 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests the graceful shutdown of the node on `SIGTERM`. The test is placed
+//! in a separate binary, since the signal is received by all nodes running in the process.
+
+#![cfg(unix)]
+
+use exonum::{
+    blockchain::{config::GenesisConfigBuilder, Schema},
+    helpers::Height,
+    merkledb::TemporaryDB,
+};
+use exonum_node::{generate_testnet_config, NodeBuilder};
+use exonum_rust_runtime::RustRuntime;
+
+use std::{
+    process::Command,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn test_node_shutdown_on_sigterm() {
+    let node_cfg = generate_testnet_config(1, 16_600).pop().unwrap();
+    let genesis_config =
+        GenesisConfigBuilder::with_consensus_config(node_cfg.consensus.clone()).build();
+    let node = NodeBuilder::new(TemporaryDB::new(), node_cfg, genesis_config)
+        .with_runtime_fn(|channel| RustRuntime::builder().build(channel.endpoints_sender()))
+        .build();
+    let blockchain = node.blockchain().clone();
+
+    let (result_tx, result_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = node.run().map_err(|e| e.to_string());
+        result_tx.send(result).ok();
+    });
+
+    // Wait until the node creates a block, so that the signal handler is set up.
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while Schema::new(&blockchain.snapshot()).height() < Height(1) {
+        assert!(Instant::now() < deadline, "Node has not created a block");
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let status = Command::new("kill")
+        .arg("-TERM")
+        .arg(std::process::id().to_string())
+        .status()
+        .expect("Cannot send SIGTERM");
+    assert!(status.success());
+
+    let result = result_rx
+        .recv_timeout(Duration::from_secs(30))
+        .expect("Node has not shut down on SIGTERM");
+    result.expect("Node has shut down with an error");
+
+    // No blocks are created after the shutdown.
+    let height = Schema::new(&blockchain.snapshot()).height();
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(Schema::new(&blockchain.snapshot()).height(), height);
+}
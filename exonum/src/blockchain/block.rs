@@ -13,16 +13,17 @@
 // limitations under the License.
 
 use exonum_derive::{BinaryValue, ObjectHash};
-use exonum_merkledb::{BinaryValue, MapProof};
+use exonum_merkledb::{BinaryValue, MapProof, ObjectHash};
 use exonum_proto::ProtobufConvert;
-use failure::Error;
+use failure::{ensure, format_err, Error};
+use serde::de::{Deserialize, Deserializer};
 
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, collections::HashSet, fmt};
 
 use crate::{
-    crypto::Hash,
-    helpers::{Height, OrderedMap, ValidatorId},
-    messages::{Precommit, Verified},
+    crypto::{Hash, PublicKey},
+    helpers::{byzantine_quorum, Height, OrderedMap, ValidatorId},
+    messages::{Precommit, SignedMessage, Verified},
     proto,
 };
 
@@ -216,9 +217,98 @@ pub struct BlockProof {
     /// in the block, etc.
     pub block: Block,
     /// List of `Precommit` messages for the block.
+    #[serde(deserialize_with = "deserialize_precommits")]
+    #[protobuf_convert(with = "self::pb_precommits")]
     pub precommits: Vec<Verified<Precommit>>,
 }
 
+/// Deserializes precommits of a block, verifying their signatures in a single batch.
+fn deserialize_precommits<'de, D>(deserializer: D) -> Result<Vec<Verified<Precommit>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let precommits = Vec::<SignedMessage>::deserialize(deserializer)?;
+    SignedMessage::into_verified_batch(precommits).map_err(serde::de::Error::custom)
+}
+
+/// Protobuf conversion of block precommits, which verifies their signatures in a single batch.
+mod pb_precommits {
+    use exonum_proto::ProtobufConvert;
+    use protobuf::RepeatedField;
+
+    use crate::{
+        messages::{Precommit, SignedMessage, Verified},
+        proto,
+    };
+
+    pub fn from_pb(
+        pb: impl IntoIterator<Item = proto::Verified>,
+    ) -> Result<Vec<Verified<Precommit>>, failure::Error> {
+        let precommits = pb
+            .into_iter()
+            .map(|mut precommit| SignedMessage::from_pb(precommit.take_raw()))
+            .collect::<Result<Vec<_>, _>>()?;
+        SignedMessage::into_verified_batch(precommits)
+    }
+
+    pub fn to_pb(precommits: &[Verified<Precommit>]) -> RepeatedField<proto::Verified> {
+        RepeatedField::from_vec(precommits.iter().map(ProtobufConvert::to_pb).collect())
+    }
+}
+
+impl BlockProof {
+    /// Verifies that the block is authorized by a supermajority of the validators
+    /// with the given consensus keys. Keys must be ordered by validator ID,
+    /// as in the consensus configuration which was actual at the block height.
+    ///
+    /// A verified block proof can serve as a trusted checkpoint: the `state_hash`
+    /// of the block may be used to check proofs for the blockchain state
+    /// obtained from untrusted peers.
+    pub fn verify(&self, validator_keys: &[PublicKey]) -> Result<(), Error> {
+        let block_hash = self.block.object_hash();
+        let mut voted_validators = HashSet::with_capacity(self.precommits.len());
+
+        for precommit in &self.precommits {
+            let payload = precommit.payload();
+            ensure!(
+                payload.height() == self.block.height,
+                "Precommit from validator {} is for height {}, expected {}",
+                payload.validator(),
+                payload.height(),
+                self.block.height
+            );
+            ensure!(
+                *payload.block_hash() == block_hash,
+                "Precommit from validator {} is for another block",
+                payload.validator()
+            );
+
+            let validator_key = validator_keys
+                .get(payload.validator().0 as usize)
+                .ok_or_else(|| format_err!("Unknown validator {}", payload.validator()))?;
+            ensure!(
+                precommit.author() == *validator_key,
+                "Precommit from validator {} is signed with an unexpected key",
+                payload.validator()
+            );
+            ensure!(
+                voted_validators.insert(payload.validator()),
+                "Duplicate precommit from validator {}",
+                payload.validator()
+            );
+        }
+
+        let quorum = byzantine_quorum(validator_keys.len());
+        ensure!(
+            voted_validators.len() >= quorum,
+            "Insufficient number of precommits: {}, expected at least {}",
+            voted_validators.len(),
+            quorum
+        );
+        Ok(())
+    }
+}
+
 /// Proof of authenticity for a single index within the database.
 #[derive(Debug, Clone, Serialize, Deserialize, ProtobufConvert)]
 #[protobuf_convert(source = "proto::IndexProof")]
@@ -233,14 +323,40 @@ pub struct IndexProof {
     pub index_proof: MapProof<String, Hash>,
 }
 
+impl IndexProof {
+    /// Verifies the proof against the consensus keys of the validators and returns
+    /// the name and the hash of the proved index.
+    ///
+    /// See [`BlockProof::verify`] for the requirements to `validator_keys`.
+    ///
+    /// [`BlockProof::verify`]: struct.BlockProof.html#method.verify
+    pub fn verify(&self, validator_keys: &[PublicKey]) -> Result<(&str, Hash), Error> {
+        self.block_proof.verify(validator_keys)?;
+        let checked_proof = self
+            .index_proof
+            .check_against_hash(self.block_proof.block.state_hash)?;
+
+        let mut entries = checked_proof.entries();
+        let (index_name, index_hash) = entries
+            .next()
+            .ok_or_else(|| format_err!("Index proof does not contain any entries"))?;
+        ensure!(
+            entries.next().is_none(),
+            "Index proof contains more than one entry"
+        );
+        Ok((index_name.as_str(), *index_hash))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use exonum_crypto::hash;
+    use chrono::Utc;
+    use exonum_crypto::{gen_keypair, hash, SecretKey, Signature};
     use exonum_merkledb::ObjectHash;
     use pretty_assertions::{assert_eq, assert_ne};
 
     use super::*;
-    use crate::runtime::InstanceId;
+    use crate::{helpers::Round, runtime::InstanceId};
 
     impl BlockHeaderKey for Hash {
         const NAME: &'static str = "HASH";
@@ -380,4 +496,126 @@ mod tests {
         let services = block.get_header::<ActiveServices>();
         assert!(services.is_err());
     }
+
+    fn create_precommit(
+        validator: u16,
+        block: &Block,
+        keys: &(PublicKey, SecretKey),
+    ) -> Verified<Precommit> {
+        let precommit = Precommit::new(
+            ValidatorId(validator),
+            block.height,
+            Round::first(),
+            hash(&[validator as u8]),
+            block.object_hash(),
+            Utc::now(),
+        );
+        Verified::from_value(precommit, keys.0, &keys.1)
+    }
+
+    #[test]
+    fn block_proof_verify() {
+        let keys: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let validator_keys: Vec<_> = keys.iter().map(|(pk, _)| *pk).collect();
+        let block = create_block(AdditionalHeaders::new());
+
+        let precommits = (0..3)
+            .map(|i| create_precommit(i, &block, &keys[i as usize]))
+            .collect();
+        let proof = BlockProof { block, precommits };
+        proof.verify(&validator_keys).unwrap();
+
+        // Not enough precommits.
+        let mut short_proof = proof.clone();
+        short_proof.precommits.pop();
+        assert!(short_proof.verify(&validator_keys).is_err());
+
+        // Duplicate precommit.
+        let mut duplicate_proof = short_proof.clone();
+        duplicate_proof
+            .precommits
+            .push(duplicate_proof.precommits[0].clone());
+        assert!(duplicate_proof.verify(&validator_keys).is_err());
+
+        // Precommit signed by the key of another validator.
+        let mut forged_proof = short_proof.clone();
+        forged_proof
+            .precommits
+            .push(create_precommit(2, &forged_proof.block, &keys[3]));
+        assert!(forged_proof.verify(&validator_keys).is_err());
+
+        // Precommit for another block.
+        let mut other_block = proof.block.clone();
+        other_block.height = Height(1);
+        let mut wrong_block_proof = short_proof;
+        wrong_block_proof
+            .precommits
+            .push(create_precommit(2, &other_block, &keys[2]));
+        assert!(wrong_block_proof.verify(&validator_keys).is_err());
+    }
+
+    #[test]
+    fn block_proof_serialization() {
+        let keys: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let block = create_block(AdditionalHeaders::new());
+        let precommits = (0..3)
+            .map(|i| create_precommit(i, &block, &keys[i as usize]))
+            .collect();
+        let proof = BlockProof { block, precommits };
+
+        let json = serde_json::to_value(&proof).unwrap();
+        let restored: BlockProof = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(restored, proof);
+        let pb = proof.to_pb();
+        let restored = BlockProof::from_pb(pb.clone()).unwrap();
+        assert_eq!(restored, proof);
+
+        // Precommits with invalid signatures are rejected.
+        let mut broken_precommit = proof.precommits[1].clone().into_raw();
+        broken_precommit.signature = Signature::zero();
+
+        let mut broken_json = json;
+        broken_json["precommits"][1] = serde_json::to_value(&broken_precommit).unwrap();
+        assert!(serde_json::from_value::<BlockProof>(broken_json).is_err());
+        let mut broken_pb = pb;
+        broken_pb.mut_precommits()[1].set_raw(broken_precommit.to_pb());
+        assert!(BlockProof::from_pb(broken_pb).is_err());
+    }
+
+    #[test]
+    fn block_proof_precommits_are_verified_as_single_messages() {
+        // Order of the Ed25519 base point; adding it to the `S` part of a signature
+        // yields a non-canonical encoding of the same scalar.
+        const GROUP_ORDER: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+        ];
+
+        let keys: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let block = create_block(AdditionalHeaders::new());
+        let precommits = (0..3)
+            .map(|i| create_precommit(i, &block, &keys[i as usize]))
+            .collect();
+        let proof = BlockProof { block, precommits };
+
+        let mut malleated = proof.precommits[1].clone().into_raw();
+        let mut signature = malleated.signature.as_ref().to_vec();
+        let mut carry = 0_u16;
+        for (byte, order_byte) in signature[32..].iter_mut().zip(&GROUP_ORDER) {
+            let sum = u16::from(*byte) + u16::from(*order_byte) + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        malleated.signature = Signature::from_slice(&signature).unwrap();
+        // The malleated precommit is rejected when verified on its own...
+        assert!(malleated.clone().into_verified::<Precommit>().is_err());
+
+        // ...and so is a block proof containing it.
+        let mut json = serde_json::to_value(&proof).unwrap();
+        json["precommits"][1] = serde_json::to_value(&malleated).unwrap();
+        assert!(serde_json::from_value::<BlockProof>(json).is_err());
+        let mut pb = proof.to_pb();
+        pb.mut_precommits()[1].set_raw(malleated.to_pb());
+        assert!(BlockProof::from_pb(pb).is_err());
+    }
 }
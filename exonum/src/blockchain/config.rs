@@ -23,14 +23,18 @@
 
 use exonum_derive::{BinaryValue, ObjectHash};
 use exonum_proto::ProtobufConvert;
-use failure::{bail, ensure};
+use failure::{bail, ensure, format_err};
 use log::warn;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
 
 use crate::{
-    crypto::{gen_keypair, PublicKey},
-    helpers::{Milliseconds, ValidateInput, ValidatorId},
+    crypto::{gen_keypair, hash, PublicKey},
+    helpers::{Height, Milliseconds, Round, ValidateInput, ValidatorId},
     keys::Keys,
     merkledb::BinaryValue,
     messages::SIGNED_MESSAGE_MIN_SIZE,
@@ -63,6 +67,184 @@ impl ValidateInput for ValidatorKeys {
     }
 }
 
+/// Algorithm used by a validator to choose between `min_propose_timeout` and
+/// `max_propose_timeout` of the consensus configuration.
+///
+/// Different workloads benefit from different trade-offs: a mempool-driven strategy
+/// reacts instantly to transaction bursts, while averaging over recent blocks smooths out
+/// short spikes of load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProposeTimeoutStrategy {
+    /// The minimal propose timeout is used if the number of pending transactions
+    /// is not less than `propose_timeout_threshold`. This is the default strategy.
+    MempoolSize,
+    /// The maximal propose timeout is always used.
+    Constant,
+    /// The minimal propose timeout is used if the average number of transactions
+    /// in `blocks_window` latest blocks is not less than `propose_timeout_threshold`.
+    BlockFullness {
+        /// Number of the latest blocks to average over.
+        blocks_window: u32,
+    },
+}
+
+impl Default for ProposeTimeoutStrategy {
+    fn default() -> Self {
+        ProposeTimeoutStrategy::MempoolSize
+    }
+}
+
+impl ProposeTimeoutStrategy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ProtobufConvert for ProposeTimeoutStrategy {
+    type ProtoStruct = blockchain::ProposeTimeoutStrategy;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        use blockchain::ProposeTimeoutStrategy_Type::*;
+
+        let mut pb = Self::ProtoStruct::new();
+        match *self {
+            ProposeTimeoutStrategy::MempoolSize => pb.set_field_type(MEMPOOL_SIZE),
+            ProposeTimeoutStrategy::Constant => pb.set_field_type(CONSTANT),
+            ProposeTimeoutStrategy::BlockFullness { blocks_window } => {
+                pb.set_field_type(BLOCK_FULLNESS);
+                pb.set_blocks_window(blocks_window);
+            }
+        }
+        pb
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        use blockchain::ProposeTimeoutStrategy_Type::*;
+
+        Ok(match pb.get_field_type() {
+            MEMPOOL_SIZE => ProposeTimeoutStrategy::MempoolSize,
+            CONSTANT => ProposeTimeoutStrategy::Constant,
+            BLOCK_FULLNESS => ProposeTimeoutStrategy::BlockFullness {
+                blocks_window: pb.get_blocks_window(),
+            },
+        })
+    }
+}
+
+/// Policy used to select the leader (proposer) of a consensus round.
+///
+/// Consortium agreements may require specific fairness properties for block proposal,
+/// e.g., that validators propose blocks in proportion to their stake.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LeaderRotation {
+    /// Validators take turns in the order of their identifiers; the leader is
+    /// `(height + round) mod n`. This is the default policy.
+    RoundRobin,
+    /// The leader is chosen pseudo-randomly based on the hash of the height and round,
+    /// so that the order of proposers is not predictable far in advance.
+    Hashed,
+    /// Validators lead the first round of a height proportionally to their weights
+    /// (e.g., stake or number of seats). Each next round at the same height is led by the next
+    /// validator with non-zero weight, so that every such validator leads a round within
+    /// the number of rounds equal to the number of such validators. Validators with zero weight
+    /// never propose blocks.
+    Weighted {
+        /// Weights of validators in the order of their identifiers.
+        weights: Vec<u32>,
+    },
+}
+
+impl Default for LeaderRotation {
+    fn default() -> Self {
+        LeaderRotation::RoundRobin
+    }
+}
+
+impl LeaderRotation {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the leader for the specified height and round in a network with
+    /// `validators_count` validators.
+    pub fn leader(&self, validators_count: usize, height: Height, round: Round) -> ValidatorId {
+        let id = match self {
+            LeaderRotation::RoundRobin => (height.0 + u64::from(round.0)) % validators_count as u64,
+            LeaderRotation::Hashed => {
+                let mut bytes = [0_u8; 16];
+                bytes[..8].copy_from_slice(&height.0.to_le_bytes());
+                bytes[8..12].copy_from_slice(&round.0.to_le_bytes());
+                let digest = hash(&bytes);
+                let mut seed = [0_u8; 8];
+                seed.copy_from_slice(&digest.as_ref()[..8]);
+                u64::from_le_bytes(seed) % validators_count as u64
+            }
+            LeaderRotation::Weighted { weights } => {
+                // The leader of the first round occupies the slot of the height among
+                // the weighted slots of all validators.
+                let total_weight: u64 = weights.iter().map(|&weight| u64::from(weight)).sum();
+                let mut slot = height.0 % total_weight;
+                let first_leader = weights
+                    .iter()
+                    .position(|&weight| {
+                        if slot < u64::from(weight) {
+                            true
+                        } else {
+                            slot -= u64::from(weight);
+                            false
+                        }
+                    })
+                    .expect("Slot is less than the total weight");
+
+                // Each validator with non-zero weight leads a single round in turn,
+                // starting from the leader of the first round.
+                let candidates: Vec<_> = (0..weights.len())
+                    .map(|i| (first_leader + i) % weights.len())
+                    .filter(|&id| weights[id] > 0)
+                    .collect();
+                let round_index = round.0.saturating_sub(1) as usize;
+                candidates[round_index % candidates.len()] as u64
+            }
+        };
+        ValidatorId(id as u16)
+    }
+}
+
+impl ProtobufConvert for LeaderRotation {
+    type ProtoStruct = blockchain::LeaderRotation;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        use blockchain::LeaderRotation_Type::*;
+
+        let mut pb = Self::ProtoStruct::new();
+        match self {
+            LeaderRotation::RoundRobin => pb.set_field_type(ROUND_ROBIN),
+            LeaderRotation::Hashed => pb.set_field_type(HASHED),
+            LeaderRotation::Weighted { weights } => {
+                pb.set_field_type(WEIGHTED);
+                pb.set_weights(weights.clone());
+            }
+        }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        use blockchain::LeaderRotation_Type::*;
+
+        Ok(match pb.get_field_type() {
+            ROUND_ROBIN => LeaderRotation::RoundRobin,
+            HASHED => LeaderRotation::Hashed,
+            WEIGHTED => LeaderRotation::Weighted {
+                weights: pb.take_weights(),
+            },
+        })
+    }
+}
+
 /// Consensus algorithm parameters.
 ///
 /// This configuration is initially created with default recommended values,
@@ -76,10 +258,9 @@ impl ValidateInput for ValidatorKeys {
 ///
 /// For additional information on the Exonum consensus algorithm, refer to
 /// [Consensus in Exonum](https://exonum.com/doc/version/latest/architecture/consensus/).
-#[protobuf_convert(source = "blockchain::Config")]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[derive(Serialize, Deserialize)]
-#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[derive(BinaryValue, ObjectHash)]
 pub struct ConsensusConfig {
     /// List of validators public keys.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -120,6 +301,12 @@ pub struct ConsensusConfig {
     /// in a block if the transaction pool is almost empty, and create blocks faster when there are
     /// enough transactions in the pool.
     pub propose_timeout_threshold: u32,
+    /// Algorithm used to choose between `min_propose_timeout` and `max_propose_timeout`.
+    #[serde(default, skip_serializing_if = "ProposeTimeoutStrategy::is_default")]
+    pub propose_timeout_strategy: ProposeTimeoutStrategy,
+    /// Policy used to select the leader of a consensus round.
+    #[serde(default, skip_serializing_if = "LeaderRotation::is_default")]
+    pub leader_rotation: LeaderRotation,
 }
 
 impl Default for ConsensusConfig {
@@ -134,7 +321,56 @@ impl Default for ConsensusConfig {
             min_propose_timeout: 10,
             max_propose_timeout: 200,
             propose_timeout_threshold: 500,
+            propose_timeout_strategy: ProposeTimeoutStrategy::default(),
+            leader_rotation: LeaderRotation::default(),
+        }
+    }
+}
+
+// The conversion is implemented manually rather than derived, so that parameters
+// with default values are omitted from the Protobuf message. Thus, hashes of configurations
+// not using these parameters are the same as before the parameters were introduced.
+impl ProtobufConvert for ConsensusConfig {
+    type ProtoStruct = blockchain::Config;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_validator_keys(self.validator_keys.to_pb().into());
+        pb.set_first_round_timeout(self.first_round_timeout);
+        pb.set_status_timeout(self.status_timeout);
+        pb.set_peers_timeout(self.peers_timeout);
+        pb.set_txs_block_limit(self.txs_block_limit);
+        pb.set_max_message_len(self.max_message_len);
+        pb.set_min_propose_timeout(self.min_propose_timeout);
+        pb.set_max_propose_timeout(self.max_propose_timeout);
+        pb.set_propose_timeout_threshold(self.propose_timeout_threshold);
+        if !self.propose_timeout_strategy.is_default() {
+            pb.set_propose_timeout_strategy(self.propose_timeout_strategy.to_pb());
+        }
+        if !self.leader_rotation.is_default() {
+            pb.set_leader_rotation(self.leader_rotation.to_pb());
         }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        Ok(Self {
+            validator_keys: ProtobufConvert::from_pb(pb.take_validator_keys().into_vec())?,
+            first_round_timeout: pb.get_first_round_timeout(),
+            status_timeout: pb.get_status_timeout(),
+            peers_timeout: pb.get_peers_timeout(),
+            txs_block_limit: pb.get_txs_block_limit(),
+            max_message_len: pb.get_max_message_len(),
+            min_propose_timeout: pb.get_min_propose_timeout(),
+            max_propose_timeout: pb.get_max_propose_timeout(),
+            propose_timeout_threshold: pb.get_propose_timeout_threshold(),
+            // An absent message is decoded as the default strategy.
+            propose_timeout_strategy: ProposeTimeoutStrategy::from_pb(
+                pb.take_propose_timeout_strategy(),
+            )?,
+            // An absent message is decoded as the default policy.
+            leader_rotation: LeaderRotation::from_pb(pb.take_leader_rotation())?,
+        })
     }
 }
 
@@ -244,6 +480,44 @@ impl ConsensusConfig {
             .map(|id| ValidatorId(id as u16))
     }
 
+    /// Checks whether the consensus timeouts are consistent with the given round-trip time
+    /// between validators (in milliseconds) and returns descriptions of discovered problems.
+    ///
+    /// Unlike `validate`, this method does not reject the configuration; an empty vector
+    /// means that the timeouts look reasonable for the network with the specified latency.
+    pub fn latency_warnings(&self, round_trip_time: Milliseconds) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        // A proposal and two rounds of votes need to be delivered within the first round.
+        let min_round_timeout = self.max_propose_timeout + 4 * round_trip_time;
+        if self.first_round_timeout < min_round_timeout {
+            warnings.push(format!(
+                "first_round_timeout ({}) is too small for round-trip time {} ms; \
+                 it is recommended to be at least {}",
+                self.first_round_timeout, round_trip_time, min_round_timeout
+            ));
+        }
+
+        if self.max_propose_timeout < round_trip_time {
+            warnings.push(format!(
+                "max_propose_timeout ({}) is less than round-trip time {} ms; \
+                 proposals may be created before transactions reach the leader",
+                self.max_propose_timeout, round_trip_time
+            ));
+        }
+
+        let min_status_timeout = 10 * round_trip_time;
+        if self.status_timeout < min_status_timeout {
+            warnings.push(format!(
+                "status_timeout ({}) is too small for round-trip time {} ms; \
+                 it is recommended to be at least {}",
+                self.status_timeout, round_trip_time, min_status_timeout
+            ));
+        }
+
+        warnings
+    }
+
     /// Produce warnings if configuration contains non-optimal values.
     ///
     /// Validation for logical correctness is performed in the `StoredConfiguration::try_deserialize`
@@ -305,6 +579,29 @@ impl ValidateInput for ConsensusConfig {
             );
         }
 
+        if let ProposeTimeoutStrategy::BlockFullness { blocks_window } =
+            self.propose_timeout_strategy
+        {
+            ensure!(
+                blocks_window > 0,
+                "blocks_window of the propose timeout strategy should not be equal to zero"
+            );
+        }
+
+        if let LeaderRotation::Weighted { weights } = &self.leader_rotation {
+            ensure!(
+                weights.len() == self.validator_keys.len(),
+                "Number of weights in the leader rotation policy ({}) must be equal \
+                 to the number of validators ({})",
+                weights.len(),
+                self.validator_keys.len()
+            );
+            ensure!(
+                weights.iter().any(|&weight| weight > 0),
+                "At least one validator weight in the leader rotation policy must be positive"
+            );
+        }
+
         // Check transactions limit.
         if self.txs_block_limit == 0 {
             bail!("txs_block_limit should not be equal to zero",);
@@ -326,6 +623,78 @@ impl ValidateInput for ConsensusConfig {
     }
 }
 
+/// Named presets of consensus parameters for typical deployments.
+///
+/// Timeouts of each preset are derived from the expected round-trip time between validators,
+/// which can be obtained with the `round_trip_time` method. The preset for a local network
+/// coincides with the default consensus configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsensusPreset {
+    /// Validators are located in a single data center.
+    Lan,
+    /// Validators are located in several data centers within a single continent.
+    Wan,
+    /// Validators are distributed across the globe.
+    GeoDistributed,
+}
+
+impl ConsensusPreset {
+    /// Returns the round-trip time between validators (in milliseconds) assumed by the preset.
+    pub fn round_trip_time(self) -> Milliseconds {
+        match self {
+            ConsensusPreset::Lan => 10,
+            ConsensusPreset::Wan => 150,
+            ConsensusPreset::GeoDistributed => 400,
+        }
+    }
+
+    /// Creates a consensus configuration with the timeouts of the preset and no validators.
+    pub fn consensus_config(self) -> ConsensusConfig {
+        let defaults = ConsensusConfig::default();
+        let rtt = self.round_trip_time();
+
+        let max_propose_timeout = defaults.max_propose_timeout.max(2 * rtt);
+        ConsensusConfig {
+            min_propose_timeout: defaults.min_propose_timeout.max(rtt / 2),
+            max_propose_timeout,
+            first_round_timeout: defaults
+                .first_round_timeout
+                .max(max_propose_timeout + 20 * rtt),
+            status_timeout: defaults.status_timeout.max(40 * rtt),
+            peers_timeout: defaults.peers_timeout.max(100 * rtt),
+            ..defaults
+        }
+    }
+}
+
+impl fmt::Display for ConsensusPreset {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConsensusPreset::Lan => "lan",
+            ConsensusPreset::Wan => "wan",
+            ConsensusPreset::GeoDistributed => "geo_distributed",
+        };
+        formatter.write_str(name)
+    }
+}
+
+impl FromStr for ConsensusPreset {
+    type Err = failure::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "lan" => Ok(ConsensusPreset::Lan),
+            "wan" => Ok(ConsensusPreset::Wan),
+            "geo_distributed" => Ok(ConsensusPreset::GeoDistributed),
+            _ => Err(format_err!(
+                "Invalid consensus preset: {}. Could be 'lan', 'wan' or 'geo_distributed'",
+                input
+            )),
+        }
+    }
+}
+
 /// Genesis config parameters.
 ///
 /// Information from this entity get saved to the genesis block.
@@ -588,6 +957,33 @@ mod tests {
                 },
                 "max_message_len (0) must be at least",
             ),
+            (
+                ConsensusConfig {
+                    propose_timeout_strategy: ProposeTimeoutStrategy::BlockFullness {
+                        blocks_window: 0,
+                    },
+                    ..gen_consensus_config()
+                },
+                "blocks_window of the propose timeout strategy should not be equal to zero",
+            ),
+            (
+                ConsensusConfig {
+                    leader_rotation: LeaderRotation::Weighted {
+                        weights: vec![1, 2],
+                    },
+                    ..gen_consensus_config()
+                },
+                "Number of weights in the leader rotation policy (2) must be equal",
+            ),
+            (
+                ConsensusConfig {
+                    leader_rotation: LeaderRotation::Weighted {
+                        weights: vec![0; 4],
+                    },
+                    ..gen_consensus_config()
+                },
+                "At least one validator weight in the leader rotation policy must be positive",
+            ),
         ];
 
         for (cfg, expected_msg) in &cases {
@@ -595,6 +991,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn propose_timeout_strategy_roundtrip() {
+        let strategies = [
+            ProposeTimeoutStrategy::MempoolSize,
+            ProposeTimeoutStrategy::Constant,
+            ProposeTimeoutStrategy::BlockFullness { blocks_window: 10 },
+        ];
+
+        for &strategy in &strategies {
+            let cfg = ConsensusConfig {
+                propose_timeout_strategy: strategy,
+                ..gen_consensus_config()
+            };
+            let bytes = cfg.to_bytes();
+            assert_eq!(ConsensusConfig::from_bytes(bytes.into()).unwrap(), cfg);
+
+            let json = serde_json::to_string(&cfg).unwrap();
+            assert_eq!(serde_json::from_str::<ConsensusConfig>(&json).unwrap(), cfg);
+        }
+    }
+
+    #[test]
+    fn default_optional_parameters_are_omitted() {
+        let cfg = gen_consensus_config();
+        assert!(!cfg.to_pb().has_propose_timeout_strategy());
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert!(!json.contains("propose_timeout_strategy"));
+        assert!(!cfg.to_pb().has_leader_rotation());
+        assert!(!json.contains("leader_rotation"));
+
+        let cfg = ConsensusConfig {
+            propose_timeout_strategy: ProposeTimeoutStrategy::Constant,
+            ..cfg
+        };
+        assert!(cfg.to_pb().has_propose_timeout_strategy());
+    }
+
+    #[test]
+    fn leader_rotation_roundtrip() {
+        let policies = vec![
+            LeaderRotation::RoundRobin,
+            LeaderRotation::Hashed,
+            LeaderRotation::Weighted {
+                weights: vec![1, 0, 3, 2],
+            },
+        ];
+
+        for policy in policies {
+            let cfg = ConsensusConfig {
+                leader_rotation: policy,
+                ..gen_consensus_config()
+            };
+            let bytes = cfg.to_bytes();
+            assert_eq!(ConsensusConfig::from_bytes(bytes.into()).unwrap(), cfg);
+
+            let json = serde_json::to_string(&cfg).unwrap();
+            assert_eq!(serde_json::from_str::<ConsensusConfig>(&json).unwrap(), cfg);
+        }
+    }
+
+    #[test]
+    fn leader_rotation_policies() {
+        let leaders = |policy: &LeaderRotation| -> Vec<u16> {
+            (1..=7)
+                .map(|round| policy.leader(4, Height(1), Round(round)).0)
+                .collect()
+        };
+
+        assert_eq!(
+            leaders(&LeaderRotation::RoundRobin),
+            vec![2, 3, 0, 1, 2, 3, 0]
+        );
+
+        // Rounds at the same height are led by distinct validators with non-zero weight.
+        let weighted = LeaderRotation::Weighted {
+            weights: vec![1, 0, 3, 2],
+        };
+        assert_eq!(leaders(&weighted), vec![2, 3, 0, 2, 3, 0, 2]);
+
+        // First rounds of heights are led proportionally to the weights.
+        let first_round_leaders: Vec<_> = (0..12)
+            .map(|height| weighted.leader(4, Height(height), Round(1)).0)
+            .collect();
+        assert_eq!(
+            first_round_leaders,
+            vec![0, 2, 2, 2, 3, 3, 0, 2, 2, 2, 3, 3]
+        );
+
+        // Hashed policy is deterministic and yields valid validator identifiers.
+        let hashed = leaders(&LeaderRotation::Hashed);
+        assert_eq!(hashed, leaders(&LeaderRotation::Hashed));
+        assert!(hashed.iter().all(|&id| id < 4));
+    }
+
     #[test]
     fn genesis_config_creation() {
         let consensus = gen_consensus_config();
@@ -636,4 +1126,49 @@ mod tests {
         assert_eq!(genesis_config.artifacts.len(), 1);
         assert_eq!(genesis_config.artifacts[0].payload, correct_payload);
     }
+
+    #[test]
+    fn consensus_presets() {
+        assert_eq!(
+            ConsensusPreset::Lan.consensus_config(),
+            ConsensusConfig::default()
+        );
+
+        for &preset in &[
+            ConsensusPreset::Lan,
+            ConsensusPreset::Wan,
+            ConsensusPreset::GeoDistributed,
+        ] {
+            let config = ConsensusConfig {
+                validator_keys: (0..4).map(gen_validator_keys).collect(),
+                ..preset.consensus_config()
+            };
+            config
+                .validate()
+                .expect("Preset should produce valid config");
+            assert!(config.latency_warnings(preset.round_trip_time()).is_empty());
+            assert_eq!(
+                preset.to_string().parse::<ConsensusPreset>().unwrap(),
+                preset
+            );
+        }
+
+        let e = "moon".parse::<ConsensusPreset>().unwrap_err();
+        assert_err_contains(e, "Invalid consensus preset");
+    }
+
+    #[test]
+    fn consensus_config_latency_warnings() {
+        let config = ConsensusConfig::default();
+        assert!(config.latency_warnings(10).is_empty());
+
+        let warnings = config.latency_warnings(ConsensusPreset::GeoDistributed.round_trip_time());
+        assert_eq!(warnings.len(), 1);
+        assert_err_contains(&warnings[0], "max_propose_timeout (200)");
+
+        let warnings = config.latency_warnings(1_000);
+        assert_eq!(warnings.len(), 3);
+        assert_err_contains(&warnings[0], "first_round_timeout (3000)");
+        assert_err_contains(&warnings[2], "status_timeout (5000)");
+    }
 }
@@ -18,7 +18,9 @@ pub use self::{
     api_sender::{ApiSender, SendError},
     block::{AdditionalHeaders, Block, BlockHeaderKey, BlockProof, IndexProof, ProposerId},
     builder::BlockchainBuilder,
-    config::{ConsensusConfig, ValidatorKeys},
+    config::{
+        ConsensusConfig, ConsensusPreset, LeaderRotation, ProposeTimeoutStrategy, ValidatorKeys,
+    },
     schema::{CallInBlock, Schema, TxLocation},
 };
 
@@ -33,13 +35,14 @@ use exonum_merkledb::{
 };
 use failure::Error;
 use futures::Future;
+use tracing::info_span;
 
 use std::{collections::BTreeMap, sync::Arc};
 
 use crate::{
     blockchain::config::GenesisConfig,
     crypto::{Hash, PublicKey, SecretKey},
-    helpers::{Height, ValidateInput, ValidatorId},
+    helpers::{Height, LogContext, ValidateInput, ValidatorId},
     messages::{AnyTx, Precommit, Verified},
     runtime::{ArtifactSpec, Dispatcher},
 };
@@ -92,6 +95,11 @@ impl Blockchain {
         &self.db
     }
 
+    /// Returns the size of the database on disk in bytes, or `None` if it cannot be determined.
+    pub fn database_size(&self) -> Option<u64> {
+        self.db.size_on_disk()
+    }
+
     /// Creates a read-only snapshot of the current storage state.
     pub fn snapshot(&self) -> Box<dyn Snapshot> {
         self.db.snapshot()
@@ -204,6 +212,30 @@ impl BlockchainMut {
         self.inner.db.merge(patch)
     }
 
+    /// Performs an `action` with the supervisor privileges outside of the block processing
+    /// and commits its changes to the blockchain storage. Changes in the service lifecycle
+    /// initiated by the action take effect after the next block is committed.
+    ///
+    /// If the action returns an error, its changes are discarded.
+    ///
+    /// This method is intended to be used in tests only (e.g., to stop a service instance
+    /// in the testkit without the supervisor service) and is available only with
+    /// the `testkit` crate feature.
+    #[cfg(feature = "testkit")]
+    pub fn execute_with_supervisor_privileges<F>(&mut self, action: F) -> Result<(), ExecutionError>
+    where
+        F: FnOnce(crate::runtime::SupervisorExtensions<'_>) -> Result<(), ExecutionError>,
+    {
+        use crate::runtime::{Caller, ExecutionContext};
+
+        let mut fork = self.fork();
+        let mut context = ExecutionContext::new(&self.dispatcher, &mut fork, Caller::Blockchain);
+        action(context.supervisor_extensions())?;
+        self.merge(fork.into_patch())
+            .expect("Cannot merge changes made with supervisor privileges");
+        Ok(())
+    }
+
     /// Creates and commits the genesis block with the given genesis configuration.
     ///
     /// # Panics
@@ -297,6 +329,9 @@ impl BlockchainMut {
         tx_hashes: &[Hash],
         tx_cache: &mut BTreeMap<Hash, Verified<AnyTx>>,
     ) -> (Hash, Patch) {
+        let span = info_span!("create_block", height = height.0, txs = tx_hashes.len());
+        let _enter = span.enter();
+
         // Skip execution for genesis block.
         if height > Height(0) {
             let errors = self.dispatcher.before_transactions(&mut fork);
@@ -377,7 +412,22 @@ impl BlockchainMut {
             .unwrap_or_else(|| panic!("BUG: Cannot find transaction {:?} in database", tx_hash));
         fork.flush();
 
-        let tx_result = self.dispatcher.execute(fork, tx_hash, index, &transaction);
+        let instance_id = transaction.payload().call_info.instance_id;
+        let span = info_span!(
+            "execute_transaction",
+            tx_hash = %tx_hash,
+            service_id = instance_id
+        );
+        let _enter = span.enter();
+
+        let log_context = LogContext {
+            height: Some(height),
+            service_id: Some(instance_id),
+            tx_hash: Some(tx_hash),
+            ..LogContext::current()
+        };
+        let tx_result =
+            log_context.scope(|| self.dispatcher.execute(fork, tx_hash, index, &transaction));
         let mut schema = Schema::new(&*fork);
 
         if let Err(e) = tx_result {
@@ -405,6 +455,9 @@ impl BlockchainMut {
     where
         I: IntoIterator<Item = Verified<Precommit>>,
     {
+        let span = info_span!("commit_block", block_hash = %block_hash);
+        let _enter = span.enter();
+
         let fork: Fork = patch.into();
         let mut schema = Schema::new(&fork);
         schema.precommits(&block_hash).extend(precommits);
@@ -301,6 +301,25 @@ where
         self.transactions().put(&tx.object_hash(), tx);
     }
 
+    /// Removes a transaction from the persistent pool together with its content.
+    /// Returns the removed transaction, or `None` if the transaction is not in the pool.
+    ///
+    /// This method is intended to be used in tests only and is available only with
+    /// the `testkit` crate feature.
+    #[cfg(feature = "testkit")]
+    pub fn remove_transaction_from_pool(&mut self, hash: &Hash) -> Option<Verified<AnyTx>> {
+        if !self.transactions_pool().contains(hash) {
+            return None;
+        }
+
+        self.transactions_pool().remove(hash);
+        let txs_pool_len = self.transactions_pool_len_index().get().unwrap();
+        self.transactions_pool_len_index().set(txs_pool_len - 1);
+        let transaction = self.transactions().get(hash);
+        self.transactions().remove(hash);
+        transaction
+    }
+
     /// Changes the transaction status from `in_pool`, to `committed`.
     pub(crate) fn commit_transaction(&mut self, hash: &Hash, height: Height, tx: Verified<AnyTx>) {
         if !self.transactions().contains(hash) {
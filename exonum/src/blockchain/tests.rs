@@ -163,6 +163,7 @@ impl Transaction {
         let tx = AnyTx {
             arguments: self.into_bytes(),
             call_info: CallInfo::new(instance_id, 0),
+            valid_until: None,
         };
         Verified::from_value(tx, public_key, secret_key)
     }
@@ -676,6 +677,44 @@ fn test_check_tx() {
     );
 }
 
+/// Checks that transactions are neither pooled nor executed after their `valid_until` height.
+#[test]
+fn expired_transactions() {
+    let (pk, sk) = exonum_crypto::gen_keypair();
+    let sign_until = |tx: Transaction, valid_until: Height| {
+        let tx = AnyTx {
+            arguments: tx.into_bytes(),
+            call_info: CallInfo::new(TEST_SERVICE_ID, 0),
+            valid_until: Some(valid_until),
+        };
+        Verified::from_value(tx, pk, &sk)
+    };
+
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    // The next block has height 1.
+    let snapshot = blockchain.snapshot();
+    let expired_tx = sign_until(Transaction::AddValue(1), Height(0));
+    assert_eq!(
+        Blockchain::check_tx(&snapshot, &expired_tx).unwrap_err(),
+        ErrorMatch::from_fail(&CoreError::TransactionExpired)
+    );
+    let err = execute_transaction(&mut blockchain, expired_tx).unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::TransactionExpired));
+    let snapshot = blockchain.snapshot();
+    assert!(InspectorSchema::new(&snapshot).values.is_empty());
+
+    // The next block has height 2, which is the last height for the transaction.
+    let tx = sign_until(Transaction::AddValue(2), Height(2));
+    Blockchain::check_tx(&snapshot, &tx).expect("Transaction is not expired");
+    execute_transaction(&mut blockchain, tx).expect("Transaction must succeed");
+    let snapshot = blockchain.snapshot();
+    assert_eq!(InspectorSchema::new(&snapshot).values.get(0), Some(2));
+}
+
 #[test]
 #[should_panic(expected = "already used")]
 fn finalize_duplicate_services() {
@@ -0,0 +1,370 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logger with filtering directives adjustable at runtime.
+
+use chrono::{SecondsFormat, Utc};
+use env_logger::{fmt::Formatter, Builder, Logger, DEFAULT_FILTER_ENV};
+use failure::{bail, ensure, format_err};
+use lazy_static::lazy_static;
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_derive::Serialize;
+
+use std::{
+    cell::Cell,
+    env,
+    io::{self, Write},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
+
+use crate::{
+    crypto::Hash,
+    helpers::{Height, Round},
+    runtime::InstanceId,
+};
+
+/// Name of the environment variable specifying the format of log records,
+/// either `text` (default) or `json`.
+pub const LOG_FORMAT_ENV: &str = "EXONUM_LOG_FORMAT";
+
+lazy_static! {
+    static ref LOGGER: ReloadableLogger = ReloadableLogger::from_env();
+}
+
+thread_local! {
+    static LOG_CONTEXT: Cell<LogContext> = Cell::new(LogContext::default());
+}
+
+/// Whether `LOGGER` is installed as the global logger.
+static IS_INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Format of log records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text.
+    Text,
+    /// JSON object per line, which includes fields of the `LogContext` of the thread
+    /// emitting the record.
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = failure::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format_err!(
+                "Invalid log format: {}. Could be 'text' or 'json'",
+                input
+            )),
+        }
+    }
+}
+
+/// Contextual fields attached to the records logged by the current thread
+/// in the JSON format.
+///
+/// The node sets the consensus height and round for the records related to the consensus,
+/// and the blockchain sets the service identifier and the transaction hash for the records
+/// emitted during transaction execution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LogContext {
+    /// Height of the block being processed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<Height>,
+    /// Consensus round.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<Round>,
+    /// Identifier of the service instance being called.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<InstanceId>,
+    /// Hash of the transaction being executed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<Hash>,
+}
+
+impl LogContext {
+    /// Returns the context of the current thread.
+    pub fn current() -> Self {
+        LOG_CONTEXT.with(Cell::get)
+    }
+
+    /// Replaces the context of the current thread, returning the previous context.
+    pub fn replace(context: Self) -> Self {
+        LOG_CONTEXT.with(|cell| cell.replace(context))
+    }
+
+    /// Executes the closure with this context set for the current thread. The previous
+    /// context is restored afterwards.
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        struct Guard(LogContext);
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                LogContext::replace(self.0);
+            }
+        }
+
+        let _guard = Guard(Self::replace(self));
+        f()
+    }
+}
+
+/// Log record in the JSON format.
+#[derive(Debug, Serialize)]
+struct JsonRecord<'a> {
+    timestamp: String,
+    level: &'a str,
+    target: &'a str,
+    message: String,
+    #[serde(flatten)]
+    context: LogContext,
+}
+
+fn format_json(buf: &mut Formatter, record: &Record<'_>) -> io::Result<()> {
+    let record = JsonRecord {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Nanos, true),
+        level: record.level().as_str(),
+        target: record.target(),
+        message: record.args().to_string(),
+        context: LogContext::current(),
+    };
+    serde_json::to_writer(&mut *buf, &record)?;
+    writeln!(buf)
+}
+
+/// `env_logger` wrapper which allows to replace filtering directives.
+struct ReloadableLogger {
+    format: LogFormat,
+    inner: RwLock<(String, Logger)>,
+}
+
+impl ReloadableLogger {
+    fn from_env() -> Self {
+        let filters = env::var(DEFAULT_FILTER_ENV).unwrap_or_default();
+        // Invalid formats are reported by `init_logger`.
+        let format = log_format_from_env().unwrap_or_default();
+        let logger = Self::build(&filters, format);
+        Self {
+            format,
+            inner: RwLock::new((filters, logger)),
+        }
+    }
+
+    fn build(filters: &str, format: LogFormat) -> Logger {
+        let mut builder = Builder::new();
+        builder.parse_filters(filters);
+        match format {
+            LogFormat::Text => builder.default_format_timestamp_nanos(true),
+            LogFormat::Json => builder.format(format_json),
+        };
+        builder.build()
+    }
+
+    fn filters(&self) -> String {
+        self.inner.read().expect("Logger read lock").0.clone()
+    }
+
+    fn set_filters(&self, filters: &str) {
+        let logger = Self::build(filters, self.format);
+        log::set_max_level(logger.filter());
+        *self.inner.write().expect("Logger write lock") = (filters.to_owned(), logger);
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.inner
+            .read()
+            .expect("Logger read lock")
+            .1
+            .enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        self.inner.read().expect("Logger read lock").1.log(record)
+    }
+
+    fn flush(&self) {}
+}
+
+/// Reads the format of log records from the `EXONUM_LOG_FORMAT` environment variable.
+fn log_format_from_env() -> Result<LogFormat, failure::Error> {
+    match env::var(LOG_FORMAT_ENV) {
+        Ok(format) => format
+            .parse()
+            .map_err(|e| format_err!("Invalid `{}` environment variable: {}", LOG_FORMAT_ENV, e)),
+        Err(env::VarError::NotPresent) => Ok(LogFormat::default()),
+        Err(e) => bail!("Invalid `{}` environment variable: {}", LOG_FORMAT_ENV, e),
+    }
+}
+
+/// Performs the logger initialization.
+///
+/// Filtering directives are read from the `RUST_LOG` environment variable
+/// and can be changed afterwards with [`set_log_filters`]. Records are formatted as text
+/// unless the `EXONUM_LOG_FORMAT` environment variable is set to `json`.
+///
+/// # Errors
+///
+/// Returns an error if the `EXONUM_LOG_FORMAT` environment variable contains an unknown
+/// format, or if the global logger is already set.
+///
+/// [`set_log_filters`]: fn.set_log_filters.html
+pub fn init_logger() -> Result<(), failure::Error> {
+    log_format_from_env()?;
+    let max_level = LOGGER.inner.read().expect("Logger read lock").1.filter();
+    log::set_logger(&*LOGGER)?;
+    log::set_max_level(max_level);
+    IS_INITIALIZED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Returns filtering directives of the logger installed by [`init_logger`].
+///
+/// [`init_logger`]: fn.init_logger.html
+pub fn log_filters() -> Result<String, failure::Error> {
+    ensure!(
+        IS_INITIALIZED.load(Ordering::SeqCst),
+        "Logger is not initialized with `init_logger`"
+    );
+    Ok(LOGGER.filters())
+}
+
+/// Checks that filtering directives use the `RUST_LOG` syntax. Unlike `env_logger`,
+/// which skips malformed directives with a warning, this function returns an error
+/// describing the first malformed directive.
+pub fn validate_log_filters(filters: &str) -> Result<(), failure::Error> {
+    // Directives may be followed by a regular expression filtering messages.
+    let directives = filters.splitn(2, '/').next().unwrap_or_default();
+    for directive in directives.split(',').map(str::trim) {
+        if directive.is_empty() {
+            continue;
+        }
+
+        let mut parts = directive.split('=');
+        let level = match (parts.next(), parts.next(), parts.next()) {
+            // Either a level or a module path, or a module path with the maximal level.
+            (Some(_), None, None) | (Some(_), Some(""), None) => continue,
+            (Some(_), Some(level), None) => level,
+            _ => bail!("Invalid logging directive `{}`", directive),
+        };
+        level.parse::<LevelFilter>().map_err(|_| {
+            format_err!(
+                "Invalid log level `{}` in the logging directive `{}`",
+                level,
+                directive
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Replaces filtering directives of the logger installed by [`init_logger`].
+/// Directives use the `RUST_LOG` syntax, e.g., `info,exonum_node::consensus=trace`.
+/// Malformed directives are rejected, see [`validate_log_filters`].
+///
+/// [`init_logger`]: fn.init_logger.html
+/// [`validate_log_filters`]: fn.validate_log_filters.html
+pub fn set_log_filters(filters: &str) -> Result<(), failure::Error> {
+    ensure!(
+        IS_INITIALIZED.load(Ordering::SeqCst),
+        "Logger is not initialized with `init_logger`"
+    );
+    validate_log_filters(filters)?;
+    LOGGER.set_filters(filters);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash;
+
+    #[test]
+    fn log_format_from_str() {
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn log_format_from_env_var() {
+        // The test is the only one in the crate modifying the variable.
+        env::remove_var(LOG_FORMAT_ENV);
+        assert_eq!(log_format_from_env().unwrap(), LogFormat::Text);
+        env::set_var(LOG_FORMAT_ENV, "json");
+        assert_eq!(log_format_from_env().unwrap(), LogFormat::Json);
+
+        env::set_var(LOG_FORMAT_ENV, "xml");
+        let err = log_format_from_env().unwrap_err().to_string();
+        assert!(err.contains("Invalid `EXONUM_LOG_FORMAT` environment variable"));
+        assert!(err.contains("Invalid log format: xml"));
+        env::remove_var(LOG_FORMAT_ENV);
+    }
+
+    #[test]
+    fn log_filters_validation() {
+        let valid_filters = [
+            "",
+            "info",
+            "exonum_node",
+            "exonum_node=",
+            "info,exonum_node::consensus=trace",
+            "warn,exonum=debug/round",
+        ];
+        for filters in &valid_filters {
+            validate_log_filters(filters).unwrap();
+        }
+
+        let err = validate_log_filters("info,exonum=loud").unwrap_err();
+        assert!(err.to_string().contains("Invalid log level `loud`"));
+        let err = validate_log_filters("exonum=info=debug").unwrap_err();
+        assert!(err.to_string().contains("Invalid logging directive"));
+    }
+
+    #[test]
+    fn log_context_scope() {
+        let consensus_context = LogContext {
+            height: Some(Height(5)),
+            round: Some(Round(2)),
+            ..LogContext::default()
+        };
+        LogContext::replace(consensus_context);
+
+        let tx_context = LogContext {
+            service_id: Some(100),
+            tx_hash: Some(hash(&[1, 2, 3])),
+            ..consensus_context
+        };
+        let context = tx_context.scope(LogContext::current);
+        assert_eq!(context, tx_context);
+        assert_eq!(LogContext::current(), consensus_context);
+
+        let json = serde_json::to_value(&consensus_context).unwrap();
+        assert_eq!(json, serde_json::json!({ "height": 5, "round": 2 }));
+    }
+}
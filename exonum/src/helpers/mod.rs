@@ -14,7 +14,13 @@
 
 //! Different assorted utilities.
 
-pub use self::types::{Height, Round, ValidatorId};
+pub use self::{
+    logger::{
+        init_logger, log_filters, set_log_filters, validate_log_filters, LogContext, LogFormat,
+        LOG_FORMAT_ENV,
+    },
+    types::{Height, Round, ValidatorId},
+};
 
 // Required by `consensus-tests`. This is not a public API, since `user_agent::get` is hidden
 // under `doc(hidden)`.
@@ -24,21 +30,11 @@ pub(crate) use self::ordered_map::OrderedMap;
 // `Milliseconds` is just `u64`, but more readable within context.
 pub use self::types::Milliseconds;
 
+mod logger;
 mod ordered_map;
-
-use env_logger::Builder;
-use log::SetLoggerError;
-
 mod types;
 mod user_agent;
 
-/// Performs the logger initialization.
-pub fn init_logger() -> Result<(), SetLoggerError> {
-    Builder::from_default_env()
-        .default_format_timestamp_nanos(true)
-        .try_init()
-}
-
 /// Basic trait to validate user defined input.
 pub trait ValidateInput: Sized {
     /// The type returned in the event of a validate error.
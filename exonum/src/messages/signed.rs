@@ -15,6 +15,7 @@
 use exonum_merkledb::{impl_serde_hex_for_binary_value, BinaryValue, ObjectHash};
 use exonum_proto::ProtobufConvert;
 use failure::{ensure, Error};
+use futures::Future;
 use serde::{
     de::{Deserialize, Deserializer},
     ser::{Serialize, Serializer},
@@ -27,6 +28,7 @@ use std::{
 
 use crate::{
     crypto::{self, Hash, PublicKey, SecretKey},
+    keys::Signer,
     messages::types::SignedMessage,
     proto,
 };
@@ -43,6 +45,21 @@ impl SignedMessage {
         }
     }
 
+    /// Creates a new signed message from the given binary value, delegating signing
+    /// to the `signer`. The returned future fails if the signer fails to produce a signature.
+    pub fn with_signer(
+        payload: impl BinaryValue,
+        signer: &dyn Signer,
+    ) -> impl Future<Item = Self, Error = Error> {
+        let payload = payload.into_bytes();
+        let author = signer.public_key();
+        signer.sign(payload.as_ref()).map(move |signature| Self {
+            payload,
+            author,
+            signature,
+        })
+    }
+
     /// Verifies message signature and returns the corresponding checked message.
     pub fn into_verified<T>(self) -> Result<Verified<T>, failure::Error>
     where
@@ -59,6 +76,36 @@ impl SignedMessage {
 
         Ok(Verified { raw: self, inner })
     }
+
+    /// Verifies signatures of several messages at once and returns the corresponding
+    /// checked messages. Fails if any of the signatures is invalid or any of the messages
+    /// cannot be decoded.
+    ///
+    /// This method is more efficient than calling `into_verified` for each message,
+    /// since all signatures are verified in a single batch. Messages are verified
+    /// one by one only if the batch is rejected, in order to find the invalid signature.
+    pub fn into_verified_batch<T>(messages: Vec<Self>) -> Result<Vec<Verified<T>>, failure::Error>
+    where
+        T: TryFrom<Self>,
+    {
+        // Verifies message signatures.
+        let batch: Vec<_> = messages
+            .iter()
+            .map(|msg| (msg.payload.as_slice(), &msg.signature, &msg.author))
+            .collect();
+        if !crypto::verify_batch(&batch) {
+            return messages.into_iter().map(Self::into_verified::<T>).collect();
+        }
+        // Deserializes messages.
+        messages
+            .into_iter()
+            .map(|raw| {
+                let inner = T::try_from(raw.clone())
+                    .map_err(|_| failure::format_err!("Failed to decode message from payload."))?;
+                Ok(Verified { raw, inner })
+            })
+            .collect()
+    }
 }
 
 impl_serde_hex_for_binary_value! { SignedMessage }
@@ -150,6 +197,21 @@ where
     pub fn from_value(inner: T, public_key: PublicKey, secret_key: &SecretKey) -> Self {
         let container: T::Container = inner.into();
         let raw = SignedMessage::new(container.to_bytes(), public_key, secret_key);
+        Self::from_raw_parts(raw, container)
+    }
+
+    /// Signs the specified value with the `signer` and creates a new verified message from it.
+    /// The returned future fails if the signer fails to produce a signature.
+    pub fn from_value_with_signer(
+        inner: T,
+        signer: &dyn Signer,
+    ) -> impl Future<Item = Self, Error = Error> {
+        let container: T::Container = inner.into();
+        SignedMessage::with_signer(container.to_bytes(), signer)
+            .map(move |raw| Self::from_raw_parts(raw, container))
+    }
+
+    fn from_raw_parts(raw: SignedMessage, container: T::Container) -> Self {
         // Converts back to the inner type.
         let inner: T = if let Ok(inner) = container.try_into() {
             inner
@@ -245,12 +307,15 @@ where
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
-    use exonum_crypto::{self as crypto, Signature};
+    use exonum_crypto::{self as crypto, KeyPair, Signature};
+    use failure::format_err;
+    use futures::future;
     use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::{
         helpers::{Height, Round, ValidatorId},
+        keys::SignatureFuture,
         messages::Precommit,
         runtime::{AnyTx, CallInfo},
     };
@@ -266,6 +331,7 @@ mod tests {
                     method_id: 2,
                 },
                 arguments: vec![1, 2, 3, 4],
+                valid_until: None,
             },
             keypair.0,
             &keypair.1,
@@ -288,6 +354,7 @@ mod tests {
                     method_id: 2,
                 },
                 arguments: vec![1, 2, 3, 4],
+                valid_until: None,
             },
             keypair.0,
             &keypair.1,
@@ -299,6 +366,76 @@ mod tests {
         assert_eq!(msg, from_pb);
     }
 
+    #[derive(Debug)]
+    struct UnavailableSigner(PublicKey);
+
+    impl Signer for UnavailableSigner {
+        fn public_key(&self) -> PublicKey {
+            self.0
+        }
+
+        fn sign(&self, _data: &[u8]) -> SignatureFuture {
+            Box::new(future::err(format_err!("Signer is unavailable")))
+        }
+    }
+
+    #[test]
+    fn test_verified_with_signer() {
+        let keypair = KeyPair::from(crypto::gen_keypair());
+        let precommit = Precommit::new(
+            ValidatorId(0),
+            Height(15),
+            Round(1),
+            crypto::hash(&[1, 2, 3]),
+            crypto::hash(&[3, 2, 1]),
+            Utc::now(),
+        );
+
+        let msg = Verified::from_value_with_signer(precommit.clone(), &keypair)
+            .wait()
+            .unwrap();
+        let expected = Verified::from_value(
+            precommit.clone(),
+            keypair.public_key(),
+            keypair.secret_key(),
+        );
+        assert_eq!(msg, expected);
+        msg.into_raw().into_verified::<Precommit>().unwrap();
+
+        let signer = UnavailableSigner(keypair.public_key());
+        let err = Verified::from_value_with_signer(precommit, &signer)
+            .wait()
+            .unwrap_err();
+        assert!(err.to_string().contains("Signer is unavailable"));
+    }
+
+    #[test]
+    fn test_verified_batch() {
+        let (pub_key, secret_key) = crypto::gen_keypair();
+        let precommits: Vec<_> = (0..3)
+            .map(|i| {
+                let precommit = Precommit::new(
+                    ValidatorId(i),
+                    Height(15),
+                    Round(1),
+                    crypto::hash(&[1, 2, 3]),
+                    crypto::hash(&[3, 2, 1]),
+                    Utc::now(),
+                );
+                Verified::from_value(precommit, pub_key, &secret_key)
+            })
+            .collect();
+
+        let raw: Vec<_> = precommits.iter().map(|msg| msg.as_raw().clone()).collect();
+        let verified = SignedMessage::into_verified_batch::<Precommit>(raw.clone()).unwrap();
+        assert_eq!(verified, precommits);
+
+        let mut broken = raw;
+        broken[1].signature = Signature::zero();
+        let err = SignedMessage::into_verified_batch::<Precommit>(broken).unwrap_err();
+        assert!(err.to_string().contains("Failed to verify signature"));
+    }
+
     #[test]
     #[should_panic(expected = "Failed to verify signature.")]
     fn test_precommit_serde_wrong_signature() {
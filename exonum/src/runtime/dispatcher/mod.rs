@@ -481,8 +481,11 @@ impl Dispatcher {
         snapshot: &dyn Snapshot,
         tx: &Verified<AnyTx>,
     ) -> Result<(), ExecutionError> {
-        // Currently the only check is that destination service exists, but later
-        // functionality of this method can be extended.
+        let next_height = CoreSchema::new(snapshot).next_height();
+        if tx.as_ref().is_expired_at(next_height) {
+            return Err(CoreError::TransactionExpired.into());
+        }
+
         let call_info = &tx.as_ref().call_info;
         let instance = Schema::new(snapshot)
             .get_instance(call_info.instance_id)
@@ -516,6 +519,13 @@ impl Dispatcher {
         tx_index: u32,
         tx: &Verified<AnyTx>,
     ) -> Result<(), ExecutionError> {
+        let height = CoreSchema::new(&*fork).next_height();
+        if tx.as_ref().is_expired_at(height) {
+            let err = ExecutionError::from(CoreError::TransactionExpired);
+            Self::report_error(&err, fork, CallInBlock::transaction(tx_index));
+            return Err(err);
+        }
+
         let caller = Caller::Transaction {
             author: tx.author(),
             hash: tx_id,
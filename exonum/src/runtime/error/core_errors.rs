@@ -57,6 +57,8 @@ pub enum CoreError {
     /// An attempt to resume the service instance with the wrong artifact. The specified
     /// artifact has a different name or an inappropriate version.
     CannotResumeService = 13,
+    /// Transaction cannot be executed after its `valid_until` height.
+    TransactionExpired = 14,
 }
 
 impl CoreError {
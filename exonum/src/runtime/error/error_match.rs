@@ -47,6 +47,25 @@ use crate::runtime::InstanceId;
 /// assert_eq!(*err, matcher);
 /// # }
 /// ```
+///
+/// Matchers can be compared with execution results directly, which is convenient
+/// for checking statuses of committed transactions. A successful result never matches.
+///
+/// ```
+/// # use exonum::runtime::{ErrorMatch, ExecutionError};
+/// # use exonum_derive::ExecutionFail;
+/// # #[derive(Debug, ExecutionFail)]
+/// # pub enum Error {
+/// #     /// Content hash already exists.
+/// #     HashAlreadyExists = 0,
+/// # }
+/// # fn not_run(status: Result<(), &ExecutionError>) {
+/// // `status` is obtained, e.g., from `CommittedTransaction::status()` in the explorer.
+/// let status: Result<(), &ExecutionError> = // ...
+/// #    status;
+/// assert_eq!(status, ErrorMatch::from_fail(&Error::HashAlreadyExists).for_service(100));
+/// # }
+/// ```
 #[derive(Debug)]
 pub struct ErrorMatch {
     kind: ErrorKind,
@@ -162,6 +181,24 @@ impl PartialEq<ExecutionError> for ErrorMatch {
     }
 }
 
+impl<T> PartialEq<ErrorMatch> for Result<T, ExecutionError> {
+    fn eq(&self, error_match: &ErrorMatch) -> bool {
+        match self {
+            Ok(_) => false,
+            Err(err) => *err == *error_match,
+        }
+    }
+}
+
+impl<T> PartialEq<ErrorMatch> for Result<T, &ExecutionError> {
+    fn eq(&self, error_match: &ErrorMatch) -> bool {
+        match self {
+            Ok(_) => false,
+            Err(err) => **err == *error_match,
+        }
+    }
+}
+
 enum StringMatch {
     Any,
     Exact(String),
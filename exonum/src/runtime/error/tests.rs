@@ -363,3 +363,31 @@ fn unknown_panic() {
     let panic = make_panic(1);
     assert_eq!(ExecutionError::from_panic(panic).description, "");
 }
+
+#[test]
+fn error_match_for_execution_result() {
+    let mut err = ExecutionError::service(3, "Insufficient funds");
+    err.set_call_site(|| CallSite {
+        instance_id: 100,
+        call_type: CallType::Method {
+            interface: String::new(),
+            id: 0,
+        },
+    });
+    let matcher = || {
+        ErrorMatch::new(
+            ErrorKind::Service { code: 3 },
+            "Insufficient funds".to_owned(),
+        )
+        .for_service(100)
+    };
+
+    let result: Result<(), ExecutionError> = Err(err);
+    assert_eq!(result, matcher());
+    assert_eq!(result.as_ref().map(drop), matcher());
+    assert_ne!(result, matcher().for_service(101));
+    assert_ne!(result, matcher().with_description_containing("funds!"));
+
+    let ok_result: Result<(), &ExecutionError> = Ok(());
+    assert_ne!(ok_result, matcher());
+}
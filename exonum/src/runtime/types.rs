@@ -21,6 +21,7 @@ use exonum_merkledb::{
 };
 use exonum_proto::ProtobufConvert;
 use failure::{bail, ensure, format_err};
+use protobuf::well_known_types::UInt64Value;
 use semver::Version;
 use serde_derive::{Deserialize, Serialize};
 
@@ -32,7 +33,9 @@ use std::{
 
 use super::InstanceDescriptor;
 use crate::{
-    blockchain::config::InstanceInitParams, helpers::ValidateInput, messages::Verified,
+    blockchain::config::InstanceInitParams,
+    helpers::{Height, ValidateInput},
+    messages::Verified,
     proto::schema,
 };
 
@@ -93,7 +96,9 @@ impl CallInfo {
 ///             ..CallInfo::default()
 ///         },
 ///         // Transaction payload.
-///         arguments: "Talk is cheap. Show me the code. – Linus Torvalds".to_owned().into_bytes()
+///         arguments: "Talk is cheap. Show me the code. – Linus Torvalds".to_owned().into_bytes(),
+///         // Transaction does not expire.
+///         valid_until: None,
 ///     },
 ///     keypair.0,
 ///     &keypair.1
@@ -101,16 +106,66 @@ impl CallInfo {
 /// ```
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Debug)]
 #[derive(Serialize, Deserialize)]
-#[derive(ProtobufConvert)]
-#[protobuf_convert(source = "schema::runtime::AnyTx")]
 pub struct AnyTx {
     /// Information required for the call of the corresponding executor.
     pub call_info: CallInfo,
     /// Serialized transaction arguments.
     pub arguments: Vec<u8>,
+    /// The last height at which the transaction can be executed. Nodes refuse to add
+    /// the transaction to the pool once the blockchain reaches this height, and
+    /// the transaction fails with `CoreError::TransactionExpired` if it is included
+    /// into a block with a greater height.
+    ///
+    /// If not specified, the transaction does not expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<Height>,
+}
+
+// The conversion is implemented manually, since `valid_until` is wrapped into a message
+// to distinguish a zero height from an unset one.
+impl ProtobufConvert for AnyTx {
+    type ProtoStruct = schema::runtime::AnyTx;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_call_info(self.call_info.to_pb());
+        pb.set_arguments(self.arguments.clone());
+        if let Some(valid_until) = self.valid_until {
+            let mut height = UInt64Value::new();
+            height.set_value(valid_until.0);
+            pb.set_valid_until(height);
+        }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        let valid_until = if pb.has_valid_until() {
+            Some(Height(pb.get_valid_until().get_value()))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            call_info: CallInfo::from_pb(pb.take_call_info())?,
+            arguments: pb.take_arguments(),
+            valid_until,
+        })
+    }
 }
 
 impl AnyTx {
+    /// Sets the last height at which the transaction can be executed.
+    pub fn with_valid_until(mut self, height: Height) -> Self {
+        self.valid_until = Some(height);
+        self
+    }
+
+    /// Checks whether the transaction cannot be executed at the specified height.
+    pub fn is_expired_at(&self, height: Height) -> bool {
+        self.valid_until
+            .map_or(false, |valid_until| height > valid_until)
+    }
+
     /// Signs a transaction with the specified Ed25519 keypair.
     pub fn sign(self, public_key: PublicKey, secret_key: &SecretKey) -> Verified<Self> {
         Verified::from_value(self, public_key, secret_key)
@@ -734,7 +789,24 @@ mod tests {
     use pretty_assertions::assert_eq;
     use serde_json::json;
 
-    use super::{ArtifactId, InstanceSpec, Version};
+    use exonum_proto::ProtobufConvert;
+
+    use super::{AnyTx, ArtifactId, CallInfo, InstanceSpec, Version};
+    use crate::helpers::Height;
+
+    #[test]
+    fn any_tx_valid_until_roundtrip() {
+        for &valid_until in &[None, Some(Height(0)), Some(Height(10))] {
+            let tx = AnyTx {
+                call_info: CallInfo::new(1, 2),
+                arguments: vec![3; 4],
+                valid_until,
+            };
+            let pb = tx.to_pb();
+            assert_eq!(pb.has_valid_until(), valid_until.is_some());
+            assert_eq!(AnyTx::from_pb(pb).unwrap(), tx);
+        }
+    }
 
     #[test]
     fn parse_artifact_id_correct() {
@@ -111,6 +111,7 @@ impl GenericCall<InstanceId> for TxStub {
         AnyTx {
             call_info,
             arguments: args,
+            valid_until: None,
         }
     }
 }
@@ -382,27 +382,25 @@ pub use exonum_explorer::{
         CommittedTransactionSummary, Notification, SubscriptionType, TransactionFilter,
     },
     api::{
-        BlockInfo, BlockQuery, BlocksQuery, BlocksRange, CallStatusQuery, CallStatusResponse,
-        TransactionHex, TransactionQuery, TransactionResponse, MAX_BLOCKS_PER_REQUEST,
+        BlockInfo, BlockQuery, BlocksQuery, BlocksQueryError, BlocksRange, CallStatusQuery,
+        CallStatusResponse, TransactionHex, TransactionQuery, TransactionResponse,
+        MAX_BLOCKS_PER_REQUEST,
     },
     TransactionInfo,
 };
 
 use exonum::{
     blockchain::{ApiSender, Blockchain, CallInBlock, Schema},
-    helpers::Height,
     merkledb::{ObjectHash, Snapshot},
     messages::SignedMessage,
     runtime::ExecutionStatus,
 };
-use exonum_explorer::{median_precommits_time, BlockchainExplorer};
+use exonum_explorer::BlockchainExplorer;
 use exonum_rust_runtime::api::{Error as ApiError, FutureResult, ServiceApiScope};
 use futures::{Future, IntoFuture};
 use hex::FromHex;
 use serde_json::json;
 
-use std::ops::Bound;
-
 pub mod websocket;
 
 /// Exonum blockchain explorer API.
@@ -419,64 +417,9 @@ impl ExplorerApi {
 
     fn blocks(schema: Schema<&dyn Snapshot>, query: BlocksQuery) -> Result<BlocksRange, ApiError> {
         let explorer = BlockchainExplorer::from_schema(schema);
-        if query.count > MAX_BLOCKS_PER_REQUEST {
-            return Err(ApiError::BadRequest(format!(
-                "Max block count per request exceeded ({})",
-                MAX_BLOCKS_PER_REQUEST
-            )));
-        }
-
-        let (upper, upper_bound) = if let Some(upper) = query.latest {
-            if upper > explorer.height() {
-                return Err(ApiError::NotFound(format!(
-                    "Requested latest height {} is greater than the current blockchain height {}",
-                    upper,
-                    explorer.height()
-                )));
-            }
-            (upper, Bound::Included(upper))
-        } else {
-            (explorer.height(), Bound::Unbounded)
-        };
-        let lower_bound = if let Some(lower) = query.earliest {
-            Bound::Included(lower)
-        } else {
-            Bound::Unbounded
-        };
-
-        let blocks: Vec<_> = explorer
-            .blocks((lower_bound, upper_bound))
-            .rev()
-            .filter(|block| !query.skip_empty_blocks || !block.is_empty())
-            .take(query.count)
-            .map(|block| BlockInfo {
-                txs: None,
-
-                time: if query.add_blocks_time {
-                    Some(median_precommits_time(&block.precommits()))
-                } else {
-                    None
-                },
-
-                precommits: if query.add_precommits {
-                    Some(block.precommits().to_vec())
-                } else {
-                    None
-                },
-
-                block: block.into_header(),
-            })
-            .collect();
-
-        let height = if blocks.len() < query.count {
-            query.earliest.unwrap_or(Height(0))
-        } else {
-            blocks.last().map_or(Height(0), |info| info.block.height)
-        };
-
-        Ok(BlocksRange {
-            range: height..upper.next(),
-            blocks,
+        explorer.blocks_range(&query).map_err(|err| match err {
+            BlocksQueryError::TooManyBlocks => ApiError::BadRequest(err.to_string()),
+            BlocksQueryError::HeightOutOfRange { .. } => ApiError::NotFound(err.to_string()),
         })
     }
 
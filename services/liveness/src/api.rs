@@ -0,0 +1,127 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP API of the liveness service.
+
+use exonum::{
+    blockchain::{BlockProof, IndexProof},
+    crypto::{Hash, PublicKey},
+};
+use exonum_merkledb::{ListProof, MapProof};
+use exonum_rust_runtime::api::{self, ServiceApiBuilder, ServiceApiState};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::schema::{LivenessSchema, LivenessWindow, ValidatorUptime};
+
+/// Query parameters for the `v1/uptime` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct UptimeQuery {
+    /// Consensus key of the validator.
+    pub consensus_key: PublicKey,
+}
+
+/// Query parameters for the `v1/windows` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowQuery {
+    /// Zero-based index of the completed window.
+    pub index: u64,
+}
+
+/// Proof of the uptime of a validator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UptimeProof {
+    /// Proof of the latest block.
+    pub block_proof: BlockProof,
+    /// Proof of the `uptime` index in the blockchain state.
+    pub index_proof: MapProof<String, Hash>,
+    /// Proof of the validator uptime in the `uptime` index.
+    pub uptime_proof: MapProof<PublicKey, ValidatorUptime>,
+}
+
+/// Proof of the statistics of a completed window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowProof {
+    /// Proof of the latest block.
+    pub block_proof: BlockProof,
+    /// Proof of the `windows` index in the blockchain state.
+    pub index_proof: MapProof<String, Hash>,
+    /// Proof of the window statistics in the `windows` index.
+    pub window_proof: ListProof<LivenessWindow>,
+}
+
+/// Public API of the liveness service.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting the uptime of a validator together with its proof.
+    pub fn uptime(state: &ServiceApiState<'_>, query: UptimeQuery) -> api::Result<UptimeProof> {
+        let IndexProof {
+            block_proof,
+            index_proof,
+        } = Self::index_proof(state, "uptime")?;
+        let schema = LivenessSchema::new(state.service_data());
+        Ok(UptimeProof {
+            block_proof,
+            index_proof,
+            uptime_proof: schema.uptime.get_proof(query.consensus_key),
+        })
+    }
+
+    /// Endpoint for getting the statistics of a completed window together with its proof.
+    pub fn window(state: &ServiceApiState<'_>, query: WindowQuery) -> api::Result<WindowProof> {
+        let IndexProof {
+            block_proof,
+            index_proof,
+        } = Self::index_proof(state, "windows")?;
+        let schema = LivenessSchema::new(state.service_data());
+        if query.index >= schema.windows.len() {
+            return Err(api::Error::NotFound(format!(
+                "Window with index {} is not completed yet",
+                query.index
+            )));
+        }
+        Ok(WindowProof {
+            block_proof,
+            index_proof,
+            window_proof: schema.windows.get_proof(query.index),
+        })
+    }
+
+    /// Endpoint for getting the statistics of the window which is not completed yet.
+    pub fn current_window(
+        state: &ServiceApiState<'_>,
+        _query: (),
+    ) -> api::Result<Option<LivenessWindow>> {
+        Ok(LivenessSchema::new(state.service_data())
+            .current_window
+            .get())
+    }
+
+    fn index_proof(state: &ServiceApiState<'_>, index_name: &str) -> api::Result<IndexProof> {
+        state
+            .data()
+            .proof_for_service_index(index_name)
+            .ok_or_else(|| api::Error::NotFound("Liveness statistics are empty".to_owned()))
+    }
+
+    /// Wires the above endpoints to public API scope of the given `ServiceApiBuilder`.
+    pub fn wire(self, builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/uptime", Self::uptime)
+            .endpoint("v1/windows", Self::window)
+            .endpoint("v1/windows/current", Self::current_window);
+    }
+}
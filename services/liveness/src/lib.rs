@@ -0,0 +1,145 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The validator liveness reporting service for Exonum.
+//!
+//! The service records which validators have signed precommits for each committed block.
+//! The statistics are aggregated both over the whole lifetime of the service and over
+//! windows of heights of the configurable size. All statistics are stored in Merkelized
+//! indexes, thus the public API of the service returns them together with cryptographic
+//! proofs, which can be used, e.g., to enforce service level agreements between
+//! the validator operators.
+//!
+//! Note that a precommit of a validator may be absent from a block even if the validator
+//! is alive, since the block is committed as soon as precommits of +2/3 validators
+//! are collected. Hence, the statistics should be interpreted over sufficiently large windows.
+
+#![deny(
+    unsafe_code,
+    bare_trait_objects,
+    missing_docs,
+    missing_debug_implementations
+)]
+
+pub use crate::schema::{Config, LivenessSchema, LivenessWindow, ValidatorUptime};
+
+/// HTTP API of the service.
+pub mod api;
+/// Protobuf generated structs.
+pub mod proto;
+mod schema;
+
+use exonum::{
+    blockchain::config::InstanceInitParams,
+    merkledb::{BinaryValue, ObjectHash},
+    runtime::{CommonError, ExecutionError, InstanceId},
+};
+use exonum_derive::{ExecutionFail, ServiceDispatcher, ServiceFactory};
+use exonum_rust_runtime::{
+    api::ServiceApiBuilder, CallContext, DefaultInstance, Service, ServiceFactory as _,
+};
+
+use std::collections::HashSet;
+
+/// Errors of the liveness service.
+#[derive(Debug, ExecutionFail)]
+pub enum Error {
+    /// The size of windows must be positive.
+    InvalidWindowSize = 0,
+}
+
+/// Validator liveness reporting service.
+#[derive(Debug, ServiceDispatcher, ServiceFactory)]
+#[service_factory(proto_sources = "proto")]
+pub struct LivenessService;
+
+impl LivenessService {
+    /// Default size of windows.
+    pub const DEFAULT_WINDOW_SIZE: u64 = 1_000;
+
+    /// Creates parameters of the builtin service instance with the given configuration.
+    pub fn builtin_instance(config: Config) -> InstanceInitParams {
+        LivenessService
+            .artifact_id()
+            .into_default_instance(Self::INSTANCE_ID, Self::INSTANCE_NAME)
+            .with_constructor(config)
+    }
+}
+
+impl Service for LivenessService {
+    fn initialize(&self, context: CallContext<'_>, params: Vec<u8>) -> Result<(), ExecutionError> {
+        let config = Config::from_bytes(params.into()).map_err(CommonError::malformed_arguments)?;
+        if config.window_size == 0 {
+            return Err(Error::InvalidWindowSize.into());
+        }
+        let consensus_config = context.data().for_core().consensus_config();
+        let mut schema = LivenessSchema::new(context.service_data());
+        schema.config.set(config);
+        schema.consensus_config.set(consensus_config);
+        Ok(())
+    }
+
+    /// Records precommits of the latest committed block.
+    fn before_transactions(&self, context: CallContext<'_>) -> Result<(), ExecutionError> {
+        if context.in_genesis_block() {
+            return Ok(());
+        }
+
+        let core_schema = context.data().for_core();
+        let height = core_schema.height();
+        // The genesis block is not signed by validators.
+        if height.0 == 0 {
+            return Ok(());
+        }
+
+        let block_hash = core_schema.last_block().object_hash();
+        let signers: HashSet<_> = core_schema
+            .precommits(&block_hash)
+            .iter()
+            .map(|precommit| precommit.author())
+            .collect();
+        // The consensus configuration may have been changed by the latest block. Such a change
+        // takes effect only from the next height, so the block is signed by the validators
+        // from the configuration stored while the block was being executed.
+        let consensus_config = core_schema.consensus_config();
+        let mut schema = LivenessSchema::new(context.service_data());
+        let stored_config = schema.consensus_config.get();
+        let validator_keys = stored_config
+            .as_ref()
+            .map_or(&consensus_config.validator_keys, |config| {
+                &config.validator_keys
+            });
+        schema.record_block(height, validator_keys, &signers);
+        // The configuration is rarely changed, so it is not rewritten at every height.
+        if stored_config.as_ref() != Some(&consensus_config) {
+            schema.consensus_config.set(consensus_config);
+        }
+        Ok(())
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        api::PublicApi.wire(builder);
+    }
+}
+
+impl DefaultInstance for LivenessService {
+    const INSTANCE_ID: InstanceId = 3;
+    const INSTANCE_NAME: &'static str = "liveness";
+
+    fn default_instance(&self) -> InstanceInitParams {
+        Self::builtin_instance(Config {
+            window_size: Self::DEFAULT_WINDOW_SIZE,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Database schema of the liveness service.
+
+use exonum::{
+    blockchain::{ConsensusConfig, ValidatorKeys},
+    crypto::PublicKey,
+    helpers::Height,
+};
+use exonum_derive::{BinaryValue, FromAccess, ObjectHash, RequireArtifact};
+use exonum_merkledb::{
+    access::{Access, FromAccess, RawAccessMut},
+    ProofEntry, ProofListIndex, ProofMapIndex,
+};
+use exonum_proto::ProtobufConvert;
+use serde_derive::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+
+use crate::proto;
+
+/// Configuration of the liveness service.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::Config")]
+pub struct Config {
+    /// Number of heights in a window. Statistics of the completed windows are stored
+    /// separately from each other.
+    pub window_size: u64,
+}
+
+/// Number of blocks signed by a validator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ValidatorUptime")]
+pub struct ValidatorUptime {
+    /// Consensus key of the validator.
+    pub consensus_key: PublicKey,
+    /// Number of blocks containing a precommit of the validator.
+    pub signed_blocks: u64,
+    /// Number of blocks committed while the key belonged to the validator set.
+    pub total_blocks: u64,
+}
+
+impl ValidatorUptime {
+    fn new(consensus_key: PublicKey) -> Self {
+        Self {
+            consensus_key,
+            signed_blocks: 0,
+            total_blocks: 0,
+        }
+    }
+
+    fn record(&mut self, signed: bool) {
+        self.total_blocks += 1;
+        if signed {
+            self.signed_blocks += 1;
+        }
+    }
+}
+
+/// Statistics of validators over a window of heights.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::LivenessWindow")]
+pub struct LivenessWindow {
+    /// The first height in the window.
+    pub first_height: Height,
+    /// The last height in the window.
+    pub last_height: Height,
+    /// Uptime of the validators within the window.
+    pub validators: Vec<ValidatorUptime>,
+}
+
+impl LivenessWindow {
+    fn new(first_height: Height) -> Self {
+        Self {
+            first_height,
+            last_height: first_height,
+            validators: Vec::new(),
+        }
+    }
+
+    /// Returns the number of heights in the window.
+    pub fn heights_count(&self) -> u64 {
+        self.last_height.0 - self.first_height.0 + 1
+    }
+
+    fn record(&mut self, consensus_key: PublicKey, signed: bool) {
+        let position = self
+            .validators
+            .iter()
+            .position(|uptime| uptime.consensus_key == consensus_key);
+        let uptime = match position {
+            Some(position) => &mut self.validators[position],
+            None => {
+                self.validators.push(ValidatorUptime::new(consensus_key));
+                self.validators.last_mut().unwrap()
+            }
+        };
+        uptime.record(signed);
+    }
+}
+
+/// Database schema of the liveness service. The schema is fully public.
+#[derive(Debug, FromAccess, RequireArtifact)]
+pub struct LivenessSchema<T: Access> {
+    /// Service configuration.
+    pub config: ProofEntry<T::Base, Config>,
+    /// Uptime of validators since the service start, keyed by the consensus key.
+    /// May contain keys corresponding to past validators.
+    pub uptime: ProofMapIndex<T::Base, PublicKey, ValidatorUptime>,
+    /// Statistics of the completed windows.
+    pub windows: ProofListIndex<T::Base, LivenessWindow>,
+    /// Statistics of the window which is not completed yet.
+    pub current_window: ProofEntry<T::Base, LivenessWindow>,
+    /// Consensus configuration active at the next height. Validators from this configuration
+    /// are expected to sign the block at the next height.
+    pub consensus_config: ProofEntry<T::Base, ConsensusConfig>,
+}
+
+impl<T: Access> LivenessSchema<T> {
+    pub(crate) fn new(access: T) -> Self {
+        Self::from_root(access).unwrap()
+    }
+}
+
+impl<T: Access> LivenessSchema<T>
+where
+    T::Base: RawAccessMut,
+{
+    /// Records which validators have signed precommits for the block at the specified height.
+    pub(crate) fn record_block(
+        &mut self,
+        height: Height,
+        validator_keys: &[ValidatorKeys],
+        signers: &HashSet<PublicKey>,
+    ) {
+        let window_size = self.config.get().expect("No service config").window_size;
+        let mut window = self
+            .current_window
+            .get()
+            .unwrap_or_else(|| LivenessWindow::new(height));
+        window.last_height = height;
+
+        for keys in validator_keys {
+            let consensus_key = keys.consensus_key;
+            let signed = signers.contains(&consensus_key);
+            window.record(consensus_key, signed);
+
+            let mut uptime = self
+                .uptime
+                .get(&consensus_key)
+                .unwrap_or_else(|| ValidatorUptime::new(consensus_key));
+            uptime.record(signed);
+            self.uptime.put(&consensus_key, uptime);
+        }
+
+        if window.heights_count() >= window_size {
+            self.windows.push(window);
+            self.current_window.remove();
+        } else {
+            self.current_window.set(window);
+        }
+    }
+}
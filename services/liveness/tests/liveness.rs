@@ -0,0 +1,222 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the liveness service.
+
+use exonum::{blockchain::IndexProof, crypto::PublicKey, helpers::Height, runtime::SnapshotExt};
+use exonum_rust_runtime::{DefaultInstance, ServiceFactory};
+use exonum_supervisor::{ConfigPropose, Supervisor};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use exonum_liveness::{
+    api::{UptimeProof, UptimeQuery, WindowProof, WindowQuery},
+    Config, LivenessSchema, LivenessService, LivenessWindow, ValidatorUptime,
+};
+
+const WINDOW_SIZE: u64 = 2;
+
+fn create_testkit() -> TestKit {
+    let config = Config {
+        window_size: WINDOW_SIZE,
+    };
+    TestKitBuilder::validator()
+        .with_validators(4)
+        .with_artifact(LivenessService.artifact_id())
+        .with_instance(LivenessService::builtin_instance(config))
+        .with_rust_service(LivenessService)
+        .create()
+}
+
+fn consensus_keys(testkit: &TestKit) -> Vec<PublicKey> {
+    testkit
+        .consensus_config()
+        .validator_keys
+        .iter()
+        .map(|keys| keys.consensus_key)
+        .collect()
+}
+
+#[test]
+fn uptime_is_recorded() {
+    let mut testkit = create_testkit();
+    testkit.create_blocks_until(Height(5));
+
+    // Precommits for heights 1..=4 have been recorded; the genesis block is not signed.
+    let snapshot = testkit.snapshot();
+    let schema: LivenessSchema<_> = snapshot
+        .service_schema(LivenessService::INSTANCE_NAME)
+        .unwrap();
+    for consensus_key in consensus_keys(&testkit) {
+        let uptime = schema.uptime.get(&consensus_key).unwrap();
+        assert_eq!(
+            uptime,
+            ValidatorUptime {
+                consensus_key,
+                signed_blocks: 4,
+                total_blocks: 4,
+            }
+        );
+    }
+
+    assert_eq!(schema.windows.len(), 2);
+    let window = schema.windows.get(1).unwrap();
+    assert_eq!(window.first_height, Height(3));
+    assert_eq!(window.last_height, Height(4));
+    assert_eq!(window.heights_count(), WINDOW_SIZE);
+    assert_eq!(window.validators.len(), 4);
+    assert!(schema.current_window.get().is_none());
+
+    testkit.create_block();
+    let snapshot = testkit.snapshot();
+    let schema: LivenessSchema<_> = snapshot
+        .service_schema(LivenessService::INSTANCE_NAME)
+        .unwrap();
+    let window = schema.current_window.get().unwrap();
+    assert_eq!(window.first_height, Height(5));
+    assert_eq!(window.heights_count(), 1);
+}
+
+#[test]
+fn uptime_across_validator_set_change() {
+    let config = Config {
+        window_size: WINDOW_SIZE,
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_artifact(LivenessService.artifact_id())
+        .with_instance(LivenessService::builtin_instance(config))
+        .with_rust_service(LivenessService)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::simple())
+        .with_rust_service(Supervisor)
+        .create();
+
+    // Replace the last validator with a new node starting from height 3.
+    let old_keys = consensus_keys(&testkit);
+    let mut new_config = testkit.consensus_config();
+    let new_node_keys = testkit.network_mut().add_node().public_keys();
+    new_config.validator_keys[3] = new_node_keys;
+    let (public_key, secret_key) = testkit.us().service_keypair();
+    testkit.create_block_with_transaction(
+        ConfigPropose::new(0, Height(3))
+            .consensus_config(new_config)
+            .sign_for_supervisor(public_key, &secret_key),
+    );
+    testkit.create_blocks_until(Height(5));
+    assert_eq!(
+        consensus_keys(&testkit),
+        vec![
+            old_keys[0],
+            old_keys[1],
+            old_keys[2],
+            new_node_keys.consensus_key
+        ]
+    );
+
+    // Blocks at heights 1 and 2 are signed by the old validators, and blocks at heights 3 and 4
+    // are signed by the new ones.
+    let snapshot = testkit.snapshot();
+    let schema: LivenessSchema<_> = snapshot
+        .service_schema(LivenessService::INSTANCE_NAME)
+        .unwrap();
+    for &consensus_key in &old_keys[..3] {
+        let uptime = schema.uptime.get(&consensus_key).unwrap();
+        assert_eq!(uptime.total_blocks, 4);
+        assert_eq!(uptime.signed_blocks, 4);
+    }
+    let removed_uptime = schema.uptime.get(&old_keys[3]).unwrap();
+    assert_eq!(removed_uptime.total_blocks, 2);
+    assert_eq!(removed_uptime.signed_blocks, 2);
+    let added_uptime = schema.uptime.get(&new_node_keys.consensus_key).unwrap();
+    assert_eq!(added_uptime.total_blocks, 2);
+    assert_eq!(added_uptime.signed_blocks, 2);
+
+    let first_window = schema.windows.get(0).unwrap();
+    assert!(first_window
+        .validators
+        .iter()
+        .all(|uptime| uptime.consensus_key != new_node_keys.consensus_key));
+    let second_window = schema.windows.get(1).unwrap();
+    assert!(second_window
+        .validators
+        .iter()
+        .all(|uptime| uptime.consensus_key != old_keys[3]));
+}
+
+#[test]
+fn uptime_proof() {
+    let mut testkit = create_testkit();
+    testkit.create_blocks_until(Height(3));
+    let consensus_keys = consensus_keys(&testkit);
+
+    let api = testkit.api();
+    let proof: UptimeProof = api
+        .public(ApiKind::Service(LivenessService::INSTANCE_NAME))
+        .query(&UptimeQuery {
+            consensus_key: consensus_keys[1],
+        })
+        .get("v1/uptime")
+        .unwrap();
+
+    let index_proof = IndexProof {
+        block_proof: proof.block_proof,
+        index_proof: proof.index_proof,
+    };
+    let (index_name, index_hash) = index_proof.verify(&consensus_keys).unwrap();
+    assert_eq!(index_name, "liveness.uptime");
+
+    let checked_proof = proof.uptime_proof.check_against_hash(index_hash).unwrap();
+    let entries: Vec<_> = checked_proof.entries().collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(*entries[0].0, consensus_keys[1]);
+    assert_eq!(entries[0].1.signed_blocks, 2);
+}
+
+#[test]
+fn window_proof() {
+    let mut testkit = create_testkit();
+    testkit.create_blocks_until(Height(3));
+    let consensus_keys = consensus_keys(&testkit);
+
+    let api = testkit.api();
+    let proof: WindowProof = api
+        .public(ApiKind::Service(LivenessService::INSTANCE_NAME))
+        .query(&WindowQuery { index: 0 })
+        .get("v1/windows")
+        .unwrap();
+
+    let index_proof = IndexProof {
+        block_proof: proof.block_proof,
+        index_proof: proof.index_proof,
+    };
+    let (index_name, index_hash) = index_proof.verify(&consensus_keys).unwrap();
+    assert_eq!(index_name, "liveness.windows");
+
+    let checked_proof = proof.window_proof.check_against_hash(index_hash).unwrap();
+    let (index, window) = &checked_proof.entries()[0];
+    assert_eq!(*index, 0);
+    assert_eq!(window.first_height, Height(1));
+    assert_eq!(window.last_height, Height(2));
+
+    // The next window is not completed yet.
+    api.public(ApiKind::Service(LivenessService::INSTANCE_NAME))
+        .query(&WindowQuery { index: 1 })
+        .get::<WindowProof>("v1/windows")
+        .unwrap_err();
+    let current_window: Option<LivenessWindow> = api
+        .public(ApiKind::Service(LivenessService::INSTANCE_NAME))
+        .get("v1/windows/current")
+        .unwrap();
+    assert!(current_window.is_none());
+}
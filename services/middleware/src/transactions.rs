@@ -228,6 +228,7 @@ fn checked_call_in_json() {
         inner: AnyTx {
             call_info: CallInfo::new(100, 0),
             arguments: vec![],
+            valid_until: None,
         },
     };
     assert_eq!(
@@ -12,7 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use exonum::{blockchain::ConsensusConfig, crypto::Hash, helpers::Height, runtime::ArtifactId};
+use exonum::{
+    blockchain::ConsensusConfig,
+    crypto::{Hash, PublicKey},
+    helpers::Height,
+    runtime::ArtifactId,
+};
+use exonum_merkledb::access::Access;
 use exonum_rust_runtime::{
     api::{self, ServiceApiBuilder, ServiceApiState},
     Broadcaster,
@@ -23,8 +29,9 @@ use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use super::{
-    schema::SchemaImpl, transactions::SupervisorInterface, ConfigProposalWithHash, ConfigPropose,
-    ConfigVote, DeployRequest, DeployState, SupervisorConfig,
+    schema::SchemaImpl, transactions::SupervisorInterface, ActivationRecord,
+    ConfigProposalWithHash, ConfigPropose, ConfigVote, DeployRequest, DeployState, ProposalRecord,
+    ProposalState, SupervisorConfig, VoteRecord,
 };
 
 /// Query for retrieving information about deploy state.
@@ -92,6 +99,107 @@ impl DeployResponse {
     }
 }
 
+/// Query for retrieving information about a configuration proposal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct ProposalQuery {
+    /// Hash of the configuration proposal.
+    pub hash: Hash,
+}
+
+/// Query for retrieving configuration proposals.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct ProposalsQuery {
+    /// If set, only proposals in this state are returned.
+    pub state: Option<ProposalState>,
+}
+
+/// Maximum number of records in a page returned by the `proposals-by-author`
+/// and `votes-by-validator` endpoints.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// Query for retrieving configuration proposals of a certain author.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct ProposalAuthorQuery {
+    /// Public service key of the proposal author.
+    pub author: PublicKey,
+    /// Position of the first proposal of the page among the proposals of the author.
+    #[serde(default)]
+    pub start: u64,
+    /// Maximum number of proposals in the page. Cannot exceed `MAX_PAGE_SIZE`,
+    /// which is also used as the default value.
+    pub count: Option<u64>,
+}
+
+/// Checks the requested page size, substituting the default value if necessary.
+fn page_size(count: Option<u64>) -> Result<u64, api::Error> {
+    let count = count.unwrap_or(MAX_PAGE_SIZE);
+    if count == 0 || count > MAX_PAGE_SIZE {
+        return Err(api::Error::BadRequest(format!(
+            "Page size should be positive and not exceed {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok(count)
+}
+
+/// Query for retrieving votes cast by a certain validator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct ValidatorVotesQuery {
+    /// Public service key of the validator.
+    pub validator: PublicKey,
+    /// Position of the first vote of the page among the votes of the validator.
+    #[serde(default)]
+    pub start: u64,
+    /// Maximum number of votes in the page. Cannot exceed `MAX_PAGE_SIZE`,
+    /// which is also used as the default value.
+    pub count: Option<u64>,
+}
+
+/// Information about a configuration proposal.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct ProposalInfo {
+    /// Hash of the proposal.
+    pub propose_hash: Hash,
+    /// Configuration number of the proposal.
+    pub configuration_number: u64,
+    /// The height until which the proposal should be applied.
+    pub actual_from: Height,
+    /// Human-readable description of the proposal.
+    pub description: String,
+    /// URL of the document describing the proposal in detail.
+    pub reference_url: String,
+    /// Hash of the proposal which should be applied before this one.
+    pub depends_on: Option<Hash>,
+    /// Current state of the proposal.
+    pub state: Option<ProposalState>,
+    /// Height of the block in which the proposal was committed.
+    pub height: Height,
+    /// Hash of the transaction with the proposal.
+    pub tx_hash: Hash,
+}
+
+impl ProposalInfo {
+    fn new<T: Access>(schema: &SchemaImpl<T>, propose_hash: Hash, record: ProposalRecord) -> Self {
+        let propose = record.config_propose;
+        Self {
+            propose_hash,
+            configuration_number: propose.configuration_number,
+            actual_from: propose.actual_from,
+            description: propose.description,
+            reference_url: propose.reference_url,
+            depends_on: propose.depends_on,
+            state: schema.public.proposal_states.get(&propose_hash),
+            height: record.height,
+            tx_hash: record.tx_hash,
+        }
+    }
+}
+
 /// Private API specification of the supervisor service.
 pub trait PrivateApi {
     /// Error type for the current API implementation.
@@ -109,6 +217,18 @@ pub trait PrivateApi {
     /// by the current node, and returns its hash.
     fn confirm_config(&self, vote: ConfigVote) -> Result<Hash, Self::Error>;
 
+    /// Creates and broadcasts the `ConfigVote` transaction rejecting the proposal,
+    /// which is signed by the current node, and returns its hash.
+    fn reject_config(&self, vote: ConfigVote) -> Result<Hash, Self::Error>;
+
+    /// Creates and broadcasts the transaction cancelling the pending proposal
+    /// authored by the current node, and returns its hash.
+    fn cancel_config(&self, vote: ConfigVote) -> Result<Hash, Self::Error>;
+
+    /// Creates and broadcasts the transaction revoking the vote of the current node
+    /// for the pending proposal, and returns its hash.
+    fn revoke_config_vote(&self, vote: ConfigVote) -> Result<Hash, Self::Error>;
+
     /// Returns the number of processed configurations.
     fn configuration_number(&self) -> Result<u64, Self::Error>;
 
@@ -126,6 +246,30 @@ pub trait PublicApi {
     fn consensus_config(&self) -> Result<ConsensusConfig, Self::Error>;
     /// Returns an pending propose config change.
     fn config_proposal(&self) -> Result<Option<ConfigProposalWithHash>, Self::Error>;
+    /// Returns a config change proposal queued behind the pending one.
+    fn queued_config_proposal(&self) -> Result<Option<ConfigProposalWithHash>, Self::Error>;
+    /// Returns the state of the configuration proposal with the given hash.
+    fn proposal_state(&self, query: ProposalQuery) -> Result<Option<ProposalState>, Self::Error>;
+    /// Returns information about the configuration proposal with the given hash.
+    fn proposal_info(&self, query: ProposalQuery) -> Result<Option<ProposalInfo>, Self::Error>;
+    /// Returns information about the accepted configuration proposals ordered
+    /// by the configuration number, optionally filtered by the proposal state.
+    fn proposals(&self, query: ProposalsQuery) -> Result<Vec<ProposalInfo>, Self::Error>;
+    /// Returns a page of information about the configuration proposals of the given author
+    /// in the order they were accepted.
+    fn proposals_by_author(
+        &self,
+        query: ProposalAuthorQuery,
+    ) -> Result<Vec<ProposalInfo>, Self::Error>;
+    /// Returns a page of votes for the configuration proposals cast by the given validator
+    /// in the order they were cast.
+    fn votes_by_validator(
+        &self,
+        query: ValidatorVotesQuery,
+    ) -> Result<Vec<VoteRecord>, Self::Error>;
+    /// Returns records about all applied configuration proposals in the order
+    /// they were applied.
+    fn activation_log(&self) -> Result<Vec<ActivationRecord>, Self::Error>;
 }
 
 struct ApiImpl<'a>(&'a ServiceApiState<'a>);
@@ -159,6 +303,24 @@ impl PrivateApi for ApiImpl<'_> {
             .map_err(|e| api::Error::InternalError(e.into()))
     }
 
+    fn reject_config(&self, vote: ConfigVote) -> Result<Hash, Self::Error> {
+        self.broadcaster()?
+            .reject_config_change((), vote)
+            .map_err(|e| api::Error::InternalError(e.into()))
+    }
+
+    fn cancel_config(&self, vote: ConfigVote) -> Result<Hash, Self::Error> {
+        self.broadcaster()?
+            .cancel_config_change((), vote)
+            .map_err(|e| api::Error::InternalError(e.into()))
+    }
+
+    fn revoke_config_vote(&self, vote: ConfigVote) -> Result<Hash, Self::Error> {
+        self.broadcaster()?
+            .revoke_config_vote((), vote)
+            .map_err(|e| api::Error::InternalError(e.into()))
+    }
+
     fn configuration_number(&self) -> Result<u64, Self::Error> {
         let configuration_number =
             SchemaImpl::new(self.0.service_data()).get_configuration_number();
@@ -192,6 +354,80 @@ impl PublicApi for ApiImpl<'_> {
             .pending_proposal
             .get())
     }
+
+    fn queued_config_proposal(&self) -> Result<Option<ConfigProposalWithHash>, Self::Error> {
+        Ok(SchemaImpl::new(self.0.service_data())
+            .public
+            .queued_proposal
+            .get())
+    }
+
+    fn proposal_state(&self, query: ProposalQuery) -> Result<Option<ProposalState>, Self::Error> {
+        Ok(SchemaImpl::new(self.0.service_data())
+            .public
+            .proposal_states
+            .get(&query.hash))
+    }
+
+    fn proposal_info(&self, query: ProposalQuery) -> Result<Option<ProposalInfo>, Self::Error> {
+        let schema = SchemaImpl::new(self.0.service_data());
+        let record = schema.public.proposals.get(&query.hash);
+        Ok(record.map(|record| ProposalInfo::new(&schema, query.hash, record)))
+    }
+
+    fn proposals(&self, query: ProposalsQuery) -> Result<Vec<ProposalInfo>, Self::Error> {
+        let schema = SchemaImpl::new(self.0.service_data());
+        let mut proposals = schema
+            .public
+            .proposals
+            .iter()
+            .map(|(propose_hash, record)| ProposalInfo::new(&schema, propose_hash, record))
+            .filter(|info| query.state.map_or(true, |state| info.state == Some(state)))
+            .collect::<Vec<_>>();
+        proposals.sort_by_key(|info| info.configuration_number);
+        Ok(proposals)
+    }
+
+    fn proposals_by_author(
+        &self,
+        query: ProposalAuthorQuery,
+    ) -> Result<Vec<ProposalInfo>, Self::Error> {
+        let count = page_size(query.count)?;
+        let schema = SchemaImpl::new(self.0.service_data());
+        let proposals = schema
+            .public
+            .proposals_by_author
+            .get(&query.author)
+            .iter_from(query.start)
+            .take(count as usize)
+            .filter_map(|propose_hash| {
+                let record = schema.public.proposals.get(&propose_hash)?;
+                Some(ProposalInfo::new(&schema, propose_hash, record))
+            })
+            .collect();
+        Ok(proposals)
+    }
+
+    fn votes_by_validator(
+        &self,
+        query: ValidatorVotesQuery,
+    ) -> Result<Vec<VoteRecord>, Self::Error> {
+        let count = page_size(query.count)?;
+        let schema = SchemaImpl::new(self.0.service_data());
+        let votes = schema
+            .public
+            .votes_by_validator
+            .get(&query.validator)
+            .iter_from(query.start)
+            .take(count as usize)
+            .collect();
+        Ok(votes)
+    }
+
+    fn activation_log(&self) -> Result<Vec<ActivationRecord>, Self::Error> {
+        let schema = SchemaImpl::new(self.0.service_data());
+        Ok(schema.public.config_activation_log.iter().collect())
+    }
 }
 
 pub fn wire(builder: &mut ServiceApiBuilder) {
@@ -206,6 +442,15 @@ pub fn wire(builder: &mut ServiceApiBuilder) {
         .endpoint_mut("confirm-config", |state, query| {
             ApiImpl(state).confirm_config(query)
         })
+        .endpoint_mut("reject-config", |state, query| {
+            ApiImpl(state).reject_config(query)
+        })
+        .endpoint_mut("cancel-config", |state, query| {
+            ApiImpl(state).cancel_config(query)
+        })
+        .endpoint_mut("revoke-config-vote", |state, query| {
+            ApiImpl(state).revoke_config_vote(query)
+        })
         .endpoint("configuration-number", |state, _query: ()| {
             ApiImpl(state).configuration_number()
         })
@@ -222,5 +467,24 @@ pub fn wire(builder: &mut ServiceApiBuilder) {
         })
         .endpoint("config-proposal", |state, _query: ()| {
             ApiImpl(state).config_proposal()
+        })
+        .endpoint("queued-config-proposal", |state, _query: ()| {
+            ApiImpl(state).queued_config_proposal()
+        })
+        .endpoint("proposal-state", |state, query| {
+            ApiImpl(state).proposal_state(query)
+        })
+        .endpoint("proposal-info", |state, query| {
+            ApiImpl(state).proposal_info(query)
+        })
+        .endpoint("proposals", |state, query| ApiImpl(state).proposals(query))
+        .endpoint("proposals-by-author", |state, query| {
+            ApiImpl(state).proposals_by_author(query)
+        })
+        .endpoint("votes-by-validator", |state, query| {
+            ApiImpl(state).votes_by_validator(query)
+        })
+        .endpoint("activation-log", |state, _query: ()| {
+            ApiImpl(state).activation_log()
         });
 }
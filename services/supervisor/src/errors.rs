@@ -70,4 +70,19 @@ pub enum ConfigurationError {
     IncorrectConfigurationNumber = 52,
     /// Invalid configuration for supervisor.
     InvalidConfig = 53,
+    /// Time oracle service specified for the configuration activation does not exist.
+    UnknownTimeOracle = 54,
+    /// Transaction author has not voted for the configuration change proposal.
+    NoVoteToRevoke = 55,
+    /// Configuration change proposal is scheduled earlier than the minimal activation delay allows.
+    ActivationTooEarly = 56,
+    /// Consensus configuration in the proposal is outside of the safe ranges.
+    UnsafeConsensusConfig = 57,
+    /// Delegate key belongs to a validator or is already used by another validator.
+    InvalidDelegate = 58,
+    /// Configuration change proposal depends on a proposal which is unknown, already applied
+    /// or cannot be applied.
+    InvalidDependency = 59,
+    /// Consensus key of a validator is replaced by a proposal of another validator.
+    UnauthorizedKeyRotation = 60,
 }
@@ -35,9 +35,10 @@
 //! # Interaction
 //!
 //! The intended way to interact with supervisor is the REST API. To be precise, requests should
-//! be sent to the one of the following endpoints: `deploy-artifact`, `propose-config` or
-//! `confirm-config`. Once received, supervisor will convert the request into appropriate
-//! transaction, sign it with the validator keys and broadcast for the rest of the network.
+//! be sent to the one of the following endpoints: `deploy-artifact`, `propose-config`,
+//! `confirm-config`, `reject-config`, `cancel-config` or `revoke-config-vote`.
+//! Once received, supervisor will convert the request into appropriate transaction,
+//! sign it with the validator keys and broadcast for the rest of the network.
 //!
 //! Key point here is that user **should not** send transactions to the supervisor by himself.
 //!
@@ -51,14 +52,75 @@
 //! should also receive [`ConfigVote`] messages with a hash of the proposed configuration.
 //! The proposal initiator that receives the original [`ConfigPropose`] message must not vote for the configuration.
 //! This node votes for the configuration propose automatically.
+//! Validators disagreeing with the proposal may send [`ConfigVote`] messages to the
+//! `reject-config` endpoint; the proposal is discarded once it is rejected by one node
+//! (within the "simple" mode) or by the majority of the nodes (within the "decentralized" mode).
+//! The proposal initiator may withdraw a pending proposal by sending its hash to the
+//! `cancel-config` endpoint. Validators may revoke their votes for the pending proposal
+//! (e.g., to change their decision) via the `revoke-config-vote` endpoint.
+//! The state of any proposal (see [`ProposalState`]) can be queried via the public
+//! `proposal-state` endpoint; votes for expired proposals are refused.
+//! Proposals may carry a human-readable description and a reference URL, which are
+//! returned together with the proposal state by the public `proposal-info` and `proposals`
+//! endpoints. Proposals of a certain validator can be retrieved via the public
+//! `proposals-by-author` endpoint, and the votes cast by a certain validator
+//! (see [`VoteRecord`]) via the public `votes-by-validator` endpoint.
+//! Applied proposals are recorded in the append-only activation log (see [`ActivationRecord`]),
+//! which is available via the public `activation-log` endpoint.
+//! Proposals scheduled earlier than `min_activation_delay` blocks after their acceptance
+//! (see [`SupervisorConfig`]) are refused, as well as proposals with a consensus configuration
+//! outside of the safe ranges set in the supervisor configuration (see [`ConsensusLimits`])
+//! or removing too many of the current validators at once.
+//!
+//! Validators may delegate proposing and voting for configuration changes to another key
+//! (see [`DelegateConfigKey`]); transactions signed by the delegate key are treated as sent
+//! by the validator.
+//!
+//! Within the decentralized mode, votes of the validators for configuration changes may have
+//! different weights (see [`ValidatorWeight`]). In this case, a proposal is approved or rejected
+//! once the validators that have voted for or against it have more than two thirds of the total
+//! weight of the current validators.
+//!
+//! While the pending proposal is approved but not yet applied, another proposal may be queued
+//! behind it (the queued proposal should become actual after the pending one). Votes for
+//! the queued proposal are accepted once the pending proposal is applied; the queued proposal
+//! is discarded if the pending one is not applied.
+//!
+//! For multi-stage upgrades, a proposal may depend on another proposal (see
+//! [`ConfigPropose::after_proposal`]). The dependent proposal can be voted for while
+//! the proposal it depends on is not applied yet; once the dependency is applied, the dependent
+//! proposal becomes pending and is applied as soon as it is approved, with `actual_from` serving
+//! as the deadline height. If the dependency is not applied, the dependent proposal is discarded.
+//!
+//! Instead of the full consensus configuration, a proposal may contain a partial update
+//! of the actual configuration (see [`ConsensusConfigPatch`]).
 //!
 //! The operation of starting a service is treated similarly to a configuration change and follows the same rules.
 //!
+//! A configuration change is applied at the `actual_from` height of the proposal. Alternatively,
+//! the proposal may specify the activation time (see [`ConfigPropose::activate_at_time`]);
+//! in this case, the change is applied once the consolidated time reported by the time oracle
+//! service (such as `exonum-time`) specified in the supervisor configuration reaches
+//! the activation time, and `actual_from` serves as the deadline height for the activation.
+//! Regardless of the activation mode, a change is not applied earlier than
+//! `min_activation_delay` blocks after the proposal is accepted, even if the activation time
+//! has already been reached.
+//!
 //! [exonum]: https://github.com/exonum/exonum
 //! [runtime-docs]: https://docs.rs/exonum/latest/exonum/runtime/index.html
 //! [`DeployRequest`]: struct.DeployRequest.html
 //! [`ConfigPropose`]: struct.ConfigPropose.html
+//! [`ConfigPropose::activate_at_time`]: struct.ConfigPropose.html#method.activate_at_time
+//! [`ConfigPropose::after_proposal`]: struct.ConfigPropose.html#method.after_proposal
 //! [`ConfigVote`]: struct.ConfigVote.html
+//! [`ProposalState`]: enum.ProposalState.html
+//! [`ConsensusConfigPatch`]: struct.ConsensusConfigPatch.html
+//! [`SupervisorConfig`]: struct.SupervisorConfig.html
+//! [`ConsensusLimits`]: struct.ConsensusLimits.html
+//! [`DelegateConfigKey`]: struct.DelegateConfigKey.html
+//! [`ValidatorWeight`]: struct.ValidatorWeight.html
+//! [`VoteRecord`]: struct.VoteRecord.html
+//! [`ActivationRecord`]: struct.ActivationRecord.html
 
 #![deny(
     missing_debug_implementations,
@@ -67,30 +129,43 @@
     bare_trait_objects
 )]
 
+#[cfg(feature = "testkit")]
+pub use self::testkit::SupervisorTestKitExt;
 pub use self::{
-    api::{DeployInfoQuery, DeployResponse},
+    api::{
+        DeployInfoQuery, DeployResponse, ProposalAuthorQuery, ProposalInfo, ProposalQuery,
+        ProposalsQuery, ValidatorVotesQuery, MAX_PAGE_SIZE,
+    },
     configure::{Configure, CONFIGURE_INTERFACE_NAME},
     deploy_state::DeployState,
     errors::{ArtifactError, CommonError, ConfigurationError, ServiceError},
+    proposal_state::ProposalState,
     proto_structures::{
-        ConfigChange, ConfigProposalWithHash, ConfigPropose, ConfigVote, DeployRequest,
-        DeployResult, ServiceConfig, StartService, StopService, SupervisorConfig,
+        ActivationRecord, ConfigChange, ConfigProposalWithHash, ConfigPropose, ConfigVote,
+        ConsensusConfigPatch, ConsensusLimits, DelegateConfigKey, DeployRequest, DeployResult,
+        ProposalRecord, ServiceConfig, StartService, StopService, SupervisorConfig,
+        ValidatorWeight, VoteRecord,
     },
     schema::Schema,
     transactions::SupervisorInterface,
+    vote_kind::VoteKind,
 };
 
+use chrono::{DateTime, Utc};
 use exonum::{
-    blockchain::config::InstanceInitParams,
-    runtime::{ExecutionError, InstanceId, SUPERVISOR_INSTANCE_ID},
+    blockchain::{config::InstanceInitParams, ValidatorKeys},
+    helpers::byzantine_quorum,
+    runtime::{BlockchainData, ExecutionError, ExecutionFail, InstanceId, SUPERVISOR_INSTANCE_ID},
 };
 use exonum_derive::*;
-use exonum_merkledb::BinaryValue;
+use exonum_merkledb::{access::FromAccess, BinaryValue, Fork, ProofEntry};
 use exonum_rust_runtime::{
     api::ServiceApiBuilder, AfterCommitContext, Broadcaster, CallContext, Service,
     ServiceFactory as _,
 };
 
+use std::collections::HashSet;
+
 use crate::{configure::ConfigureMut, mode::Mode, schema::SchemaImpl};
 
 pub mod mode;
@@ -100,10 +175,14 @@ mod configure;
 mod deploy_state;
 mod errors;
 mod multisig;
+mod proposal_state;
 mod proto;
 mod proto_structures;
 mod schema;
+#[cfg(feature = "testkit")]
+mod testkit;
 mod transactions;
+mod vote_kind;
 
 /// Returns the `Supervisor` entity name.
 pub const fn supervisor_name() -> &'static str {
@@ -114,6 +193,91 @@ pub const fn supervisor_name() -> &'static str {
 const NOT_SUPERVISOR_MSG: &str = "`Supervisor` is installed as a non-privileged service. \
                                   For correct operation, `Supervisor` needs to have numeric ID 0.";
 
+/// Name of the entry with the consolidated time in the schema of the time oracle service.
+/// Matches the layout of the `exonum-time` service schema.
+const ORACLE_TIME_ENTRY: &str = "time";
+
+/// Returns the consolidated time reported by the time oracle service with the given name,
+/// or `None` if the service does not exist or has not reported the time yet.
+fn oracle_time(data: &BlockchainData<'_, &Fork>, time_service: &str) -> Option<DateTime<Utc>> {
+    let access = data.for_service(time_service)?;
+    ProofEntry::<_, DateTime<Utc>>::from_access(access, ORACLE_TIME_ENTRY.into())
+        .ok()?
+        .get()
+}
+
+/// Checks that the supervisor configuration is consistent with the current validators.
+fn verify_supervisor_config(
+    config: &SupervisorConfig,
+    validator_keys: &[ValidatorKeys],
+) -> Result<(), ExecutionError> {
+    let validators = validator_keys.len();
+    if let Some(count) = config.majority_count {
+        if config.mode == Mode::Simple {
+            let msg = "`majority_count` can only be set within the decentralized mode";
+            return Err(ConfigurationError::InvalidConfig.with_description(msg));
+        }
+
+        let quorum = byzantine_quorum(validators);
+        let count = count as usize;
+        if count < quorum || count > validators {
+            let msg = format!(
+                "`majority_count` should be between {} and {}, but it is {}",
+                quorum, validators, count
+            );
+            return Err(ConfigurationError::InvalidConfig.with_description(msg));
+        }
+    }
+
+    if !config.vote_weights.is_empty() {
+        if config.mode == Mode::Simple || config.majority_count.is_some() {
+            let msg = "`vote_weights` can only be set within the decentralized mode \
+                       without `majority_count`";
+            return Err(ConfigurationError::InvalidConfig.with_description(msg));
+        }
+
+        let mut service_keys = HashSet::new();
+        for entry in &config.vote_weights {
+            if entry.weight == 0 || !service_keys.insert(entry.service_key) {
+                let msg = format!(
+                    "Weight of the validator {:?} should be positive and set only once",
+                    entry.service_key
+                );
+                return Err(ConfigurationError::InvalidConfig.with_description(msg));
+            }
+        }
+        if validator_keys
+            .iter()
+            .all(|keys| config.vote_weight(&keys.service_key) == 0)
+        {
+            let msg = "At least one of the current validators should have a positive vote weight";
+            return Err(ConfigurationError::InvalidConfig.with_description(msg));
+        }
+    }
+
+    if let Some(percent) = config.max_removed_validators_percent {
+        if percent == 0 || percent > 100 {
+            let msg = format!(
+                "`max_removed_validators_percent` should be between 1 and 100, but it is {}",
+                percent
+            );
+            return Err(ConfigurationError::InvalidConfig.with_description(msg));
+        }
+    }
+
+    if let Some(limits) = &config.consensus_limits {
+        if limits.max_txs_block_limit == 0
+            || limits.min_txs_block_limit > limits.max_txs_block_limit
+            || limits.min_timeout > limits.max_timeout
+        {
+            let msg = "`consensus_limits` should define non-empty ranges with positive \
+                       `max_txs_block_limit`";
+            return Err(ConfigurationError::InvalidConfig.with_description(msg));
+        }
+    }
+    Ok(())
+}
+
 /// Applies configuration changes.
 /// Upon any failure, execution of this method stops and `Err(())` is returned.
 fn update_configs(
@@ -189,6 +353,12 @@ fn update_configs(
                     .supervisor_extensions()
                     .initiate_stopping_service(stop_service.instance_id)?;
             }
+
+            ConfigChange::ConsensusPatch(_) => {
+                unreachable!(
+                    "BUG: Consensus config patches are resolved once the proposal is received."
+                );
+            }
         }
     }
     Ok(())
@@ -243,13 +413,27 @@ impl Supervisor {
 
     /// Creates a configuration for a simple `Supervisor`.
     pub fn simple_config() -> SupervisorConfig {
-        SupervisorConfig { mode: Mode::Simple }
+        SupervisorConfig {
+            mode: Mode::Simple,
+            majority_count: None,
+            min_activation_delay: 0,
+            consensus_limits: None,
+            max_removed_validators_percent: None,
+            vote_weights: Vec::new(),
+            time_service: None,
+        }
     }
 
     /// Creates a configuration for a decentralized `Supervisor`.
     pub fn decentralized_config() -> SupervisorConfig {
         SupervisorConfig {
             mode: Mode::Decentralized,
+            majority_count: None,
+            min_activation_delay: 0,
+            consensus_limits: None,
+            max_removed_validators_percent: None,
+            vote_weights: Vec::new(),
+            time_service: None,
         }
     }
 
@@ -284,6 +468,8 @@ impl Service for Supervisor {
         // will cause genesis block creation to fail, and thus blockchain won't start.
         let config = SupervisorConfig::from_bytes(Cow::from(&params))
             .map_err(|_| ConfigurationError::InvalidConfig)?;
+        let consensus_config = context.data().for_core().consensus_config();
+        verify_supervisor_config(&config, &consensus_config.validator_keys)?;
 
         let mut schema = SchemaImpl::new(context.service_data());
         schema.public.configuration.set(config);
@@ -318,7 +504,7 @@ impl Service for Supervisor {
             if entry.config_propose.actual_from <= height {
                 // Remove pending config proposal for which deadline was exceeded.
                 log::trace!("Removed outdated config proposal");
-                schema.public.pending_proposal.remove();
+                schema.remove_pending_proposal(ProposalState::Expired);
             }
         }
         Ok(())
@@ -326,36 +512,50 @@ impl Service for Supervisor {
 
     fn after_transactions(&self, mut context: CallContext<'_>) -> Result<(), ExecutionError> {
         let mut schema = SchemaImpl::new(context.service_data());
-        let configuration = schema.supervisor_config();
         let core_schema = context.data().for_core();
         let next_height = core_schema.next_height();
-        let validator_count = core_schema.consensus_config().validator_keys.len();
+        let validator_keys = core_schema.consensus_config().validator_keys;
 
         // Check if we should apply a new config.
         let entry = schema.public.pending_proposal.get();
         if let Some(entry) = entry {
-            if entry.config_propose.actual_from == next_height {
+            let activation_reached = match entry.config_propose.activation_time {
+                // Config should be applied once the time oracle reaches the activation time.
+                Some(activation_time) => schema
+                    .supervisor_config()
+                    .time_service
+                    .and_then(|time_service| oracle_time(&context.data(), &time_service))
+                    .map_or(false, |time| time >= activation_time),
+                // Config depending on another proposal becomes pending only after
+                // the dependency is applied, so it should be applied once approved.
+                None if entry.config_propose.depends_on.is_some() => true,
                 // Config should be applied at the next height.
-                if configuration.mode.config_approved(
-                    &entry.propose_hash,
-                    &schema.config_confirms,
-                    validator_count,
-                ) {
-                    log::info!(
-                        "New configuration has been accepted: {:?}",
-                        entry.config_propose
-                    );
-
-                    // Remove config from proposals.
-                    // If the config update will fail, this entry will be restored due to rollback.
-                    // However, it won't be actual anymore and will be removed at the beginning
-                    // of the next height (within `before_transactions` hook).
-                    schema.public.pending_proposal.remove();
-                    drop(schema);
+                None => entry.config_propose.actual_from == next_height,
+            };
+            // Regardless of the activation mode, config is not applied earlier
+            // than the minimal activation delay allows.
+            let should_apply = activation_reached
+                && schema.activation_delay_passed(&entry.propose_hash, next_height);
+
+            if should_apply && schema.config_approved(&entry.propose_hash, &validator_keys) {
+                log::info!(
+                    "New configuration has been accepted: {:?}",
+                    entry.config_propose
+                );
 
-                    // Perform the application of configs.
-                    update_configs(&mut context, entry.config_propose.changes)?;
-                }
+                // Remove config from proposals.
+                // If the config update will fail, this entry will be restored due to rollback.
+                // A height-activated entry won't be actual anymore and will be removed
+                // at the beginning of the next height (within `before_transactions` hook).
+                // A time-activated entry will be retried until its deadline height.
+                // The queued proposal or the proposal depending on the applied one
+                // (if any) becomes pending.
+                schema.log_activation(entry.propose_hash, next_height);
+                schema.remove_pending_proposal(ProposalState::Applied);
+                drop(schema);
+
+                // Perform the application of configs.
+                update_configs(&mut context, entry.config_propose.changes)?;
             }
         }
         Ok(())
@@ -415,11 +615,11 @@ impl Configure for Supervisor {
 
     fn verify_config(
         &self,
-        _context: CallContext<'_>,
-        _params: Self::Params,
+        context: CallContext<'_>,
+        params: Self::Params,
     ) -> Result<(), ExecutionError> {
-        // If config was decoded, it's OK.
-        Ok(())
+        let consensus_config = context.data().for_core().consensus_config();
+        verify_supervisor_config(&params, &consensus_config.validator_keys)
     }
 
     fn apply_config(
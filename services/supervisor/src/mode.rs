@@ -104,6 +104,22 @@ impl Mode {
             }
         }
     }
+
+    /// Checks whether config proposal is rejected by the network.
+    pub fn config_rejected<T: Access>(
+        self,
+        config_hash: &Hash,
+        config_rejections: &MultisigIndex<T, Hash>,
+        validators: usize,
+    ) -> bool {
+        let rejections = config_rejections.confirmations(config_hash);
+        match self {
+            // For simple supervisor one rejection is enough.
+            Mode::Simple => rejections >= 1,
+            // Discard pending config in case 2/3+1 validators voted against it.
+            Mode::Decentralized => rejections >= byzantine_quorum(validators),
+        }
+    }
 }
 
 impl FromStr for Mode {
@@ -81,6 +81,24 @@ where
         self.index.put(id, confirmations);
         len
     }
+
+    /// Removes all votes for the given value.
+    pub fn remove(&mut self, id: &V) {
+        self.index.remove(id);
+    }
+
+    /// Removes the vote of the given author. Returns the number of remaining votes.
+    pub fn revoke(&mut self, id: &V, author: &PublicKey) -> usize {
+        let mut confirmations = self.index.get(id).unwrap_or_default();
+        confirmations.0.remove(author);
+        let len = confirmations.0.len();
+        if len == 0 {
+            self.index.remove(id);
+        } else {
+            self.index.put(id, confirmations);
+        }
+        len
+    }
 }
 
 impl<T, V> ObjectHash for MultisigIndex<T, V>
@@ -0,0 +1,92 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum_derive::*;
+use exonum_proto::ProtobufConvert;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::proto as pb_supervisor;
+
+/// State of the configuration proposal processed by `Supervisor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(BinaryValue, ObjectHash)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalState {
+    /// Proposal is waiting for votes from validators.
+    Pending,
+    /// Proposal was approved and applied.
+    Applied,
+    /// Proposal was rejected by validators.
+    Rejected,
+    /// Deadline height was reached before the proposal was applied.
+    Expired,
+    /// Proposal was cancelled by its author.
+    Cancelled,
+    /// Proposal is queued behind the approved pending proposal. Votes for the proposal
+    /// are accepted once the pending proposal is applied.
+    Queued,
+    /// Proposal has collected the required majority of votes and waits for activation.
+    Approved,
+    /// Queued or dependent proposal was discarded because the proposal it was queued behind
+    /// or depended on was not applied.
+    Obsoleted,
+}
+
+impl ProposalState {
+    /// Returns `true` if the proposal can no longer change its state.
+    pub fn is_final(self) -> bool {
+        match self {
+            ProposalState::Pending | ProposalState::Queued | ProposalState::Approved => false,
+            _ => true,
+        }
+    }
+}
+
+impl ProtobufConvert for ProposalState {
+    type ProtoStruct = pb_supervisor::ProposalState;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        use pb_supervisor::ProposalState_Type::*;
+
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_state(match self {
+            ProposalState::Pending => PENDING,
+            ProposalState::Applied => APPLIED,
+            ProposalState::Rejected => REJECTED,
+            ProposalState::Expired => EXPIRED,
+            ProposalState::Cancelled => CANCELLED,
+            ProposalState::Queued => QUEUED,
+            ProposalState::Approved => APPROVED,
+            ProposalState::Obsoleted => OBSOLETED,
+        });
+        pb
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        use pb_supervisor::ProposalState_Type::*;
+
+        let state = match pb.get_state() {
+            PENDING => ProposalState::Pending,
+            APPLIED => ProposalState::Applied,
+            REJECTED => ProposalState::Rejected,
+            EXPIRED => ProposalState::Expired,
+            CANCELLED => ProposalState::Cancelled,
+            QUEUED => ProposalState::Queued,
+            APPROVED => ProposalState::Approved,
+            OBSOLETED => ProposalState::Obsoleted,
+        };
+        Ok(state)
+    }
+}
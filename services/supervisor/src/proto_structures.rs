@@ -12,10 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
 use exonum::{
     blockchain::ConsensusConfig,
     crypto::{Hash, PublicKey, SecretKey},
-    helpers::Height,
+    helpers::{byzantine_quorum, Height},
     merkledb::{
         impl_binary_key_for_binary_value, impl_serde_hex_for_binary_value, BinaryValue, ObjectHash,
     },
@@ -25,19 +26,280 @@ use exonum::{
 use exonum_derive::{BinaryValue, ObjectHash};
 use exonum_proto::ProtobufConvert;
 use exonum_rust_runtime::TxStub;
+use protobuf::well_known_types::UInt32Value;
 use serde_derive::{Deserialize, Serialize};
 
-use super::{mode::Mode, proto, transactions::SupervisorInterface};
+use super::{mode::Mode, proto, transactions::SupervisorInterface, VoteKind};
 
 /// Supervisor service configuration (not to be confused with `ConfigPropose`, which
 /// contains core/service configuration change proposal).
 #[derive(Debug, Clone, PartialEq)]
 #[derive(Serialize, Deserialize)]
-#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
-#[protobuf_convert(source = "proto::Config")]
+#[derive(BinaryValue, ObjectHash)]
 pub struct SupervisorConfig {
     /// Supervisor operating mode.
     pub mode: Mode,
+    /// Number of validator confirmations required to apply a configuration change
+    /// within the decentralized mode. If not set, a byzantine majority of validators
+    /// is required. The value must not be less than the byzantine majority
+    /// and must not exceed the number of validators.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub majority_count: Option<u32>,
+    /// Minimal number of blocks between the block in which a configuration proposal
+    /// is accepted and its `actual_from` height. Gives lagging nodes time to observe
+    /// the proposal before it is applied. Zero means no additional delay.
+    #[serde(default)]
+    pub min_activation_delay: u64,
+    /// Safe ranges of the consensus configuration parameters. If set, proposals
+    /// with consensus configuration outside of these ranges are refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consensus_limits: Option<ConsensusLimits>,
+    /// Maximal percentage of the validators that can be removed or replaced by a single
+    /// configuration change. The validators are compared with the consensus configuration
+    /// replaced by the change, which is the scheduled one for queued and dependent proposals.
+    /// If not set, the validator set can be changed arbitrarily. The value must be
+    /// between 1 and 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_removed_validators_percent: Option<u32>,
+    /// Weights of the validator votes for configuration changes within the decentralized
+    /// mode. If set, a configuration change is applied once the validators that have
+    /// confirmed it have more than two thirds of the total weight of the current validators;
+    /// validators absent from the list have zero weight. If empty, confirmations are counted
+    /// according to `majority_count`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vote_weights: Vec<ValidatorWeight>,
+    /// Name of the time oracle service instance, such as the `exonum-time` service, used
+    /// to activate configuration changes by the blockchain time (see
+    /// `ConfigPropose::activate_at_time`). The oracle must store the consolidated time
+    /// in the `time` entry of its schema, as `exonum-time` does. If not set, proposals
+    /// activated by time are refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_service: Option<String>,
+}
+
+// The conversion is implemented manually, since `max_removed_validators_percent` is wrapped
+// into a message to distinguish zero, which is an invalid value, from an unset one.
+impl ProtobufConvert for SupervisorConfig {
+    type ProtoStruct = proto::Config;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_mode(self.mode.to_pb());
+        pb.set_majority_count(pb_optional_u32::to_pb(&self.majority_count));
+        pb.set_min_activation_delay(self.min_activation_delay);
+        pb.set_consensus_limits(pb_optional_consensus_limits::to_pb(&self.consensus_limits));
+        if let Some(percent) = self.max_removed_validators_percent {
+            let mut value = UInt32Value::new();
+            value.set_value(percent);
+            pb.set_max_removed_validators_percent(value);
+        }
+        pb.set_vote_weights(self.vote_weights.to_pb().into());
+        pb.set_time_service(pb_optional_string::to_pb(&self.time_service));
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        let max_removed_validators_percent = if pb.has_max_removed_validators_percent() {
+            Some(pb.get_max_removed_validators_percent().get_value())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            mode: Mode::from_pb(pb.get_mode())?,
+            majority_count: pb_optional_u32::from_pb(pb.get_majority_count())?,
+            min_activation_delay: pb.get_min_activation_delay(),
+            consensus_limits: pb_optional_consensus_limits::from_pb(pb.take_consensus_limits())?,
+            max_removed_validators_percent,
+            vote_weights: ProtobufConvert::from_pb(pb.take_vote_weights().into_vec())?,
+            time_service: pb_optional_string::from_pb(pb.take_time_service())?,
+        })
+    }
+}
+
+/// Weight of the validator vote for configuration changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ValidatorWeight")]
+pub struct ValidatorWeight {
+    /// Service key of the validator.
+    pub service_key: PublicKey,
+    /// Weight of the validator vote.
+    pub weight: u64,
+}
+
+mod pb_optional_consensus_limits {
+    use super::*;
+
+    pub fn from_pb(pb: proto::ConsensusLimits) -> Result<Option<ConsensusLimits>, failure::Error> {
+        if pb.get_max_txs_block_limit() == 0 {
+            Ok(None)
+        } else {
+            ConsensusLimits::from_pb(pb).map(Some)
+        }
+    }
+
+    pub fn to_pb(value: &Option<ConsensusLimits>) -> proto::ConsensusLimits {
+        if let Some(limits) = value {
+            limits.to_pb()
+        } else {
+            proto::ConsensusLimits::new()
+        }
+    }
+}
+
+/// Safe ranges of the consensus configuration parameters.
+///
+/// Proposals with a consensus configuration violating these ranges are refused
+/// by the supervisor, so that a misconfigured proposal cannot halt the network
+/// once it is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ConsensusLimits")]
+pub struct ConsensusLimits {
+    /// Minimal number of validators.
+    pub min_validators: u32,
+    /// Minimal value of `txs_block_limit`.
+    pub min_txs_block_limit: u32,
+    /// Maximal value of `txs_block_limit`. Must be positive.
+    pub max_txs_block_limit: u32,
+    /// Minimal value of `first_round_timeout`, `status_timeout` and `peers_timeout`
+    /// in milliseconds.
+    pub min_timeout: u64,
+    /// Maximal value of `first_round_timeout`, `status_timeout` and `peers_timeout`
+    /// in milliseconds.
+    pub max_timeout: u64,
+}
+
+impl ConsensusLimits {
+    /// Checks that the consensus configuration is within the safe ranges.
+    pub fn check(&self, config: &ConsensusConfig) -> Result<(), String> {
+        let validators = config.validator_keys.len();
+        if validators < self.min_validators as usize {
+            return Err(format!(
+                "Number of validators {} is less than {}",
+                validators, self.min_validators
+            ));
+        }
+
+        let txs_block_limit = config.txs_block_limit;
+        if txs_block_limit < self.min_txs_block_limit || txs_block_limit > self.max_txs_block_limit
+        {
+            return Err(format!(
+                "`txs_block_limit` {} is outside of the range {}..={}",
+                txs_block_limit, self.min_txs_block_limit, self.max_txs_block_limit
+            ));
+        }
+
+        let timeouts = [
+            ("first_round_timeout", config.first_round_timeout),
+            ("status_timeout", config.status_timeout),
+            ("peers_timeout", config.peers_timeout),
+        ];
+        for &(name, timeout) in &timeouts {
+            if timeout < self.min_timeout || timeout > self.max_timeout {
+                return Err(format!(
+                    "`{}` {} is outside of the range {}..={}",
+                    name, timeout, self.min_timeout, self.max_timeout
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Conversion of optional positive numbers, with zero standing for `None`.
+mod pb_optional_u32 {
+    pub fn from_pb(pb: u32) -> Result<Option<u32>, failure::Error> {
+        Ok(if pb == 0 { None } else { Some(pb) })
+    }
+
+    pub fn to_pb(value: &Option<u32>) -> u32 {
+        value.unwrap_or(0)
+    }
+}
+
+mod pb_optional_string {
+    pub fn from_pb(pb: String) -> Result<Option<String>, failure::Error> {
+        Ok(if pb.is_empty() { None } else { Some(pb) })
+    }
+
+    pub fn to_pb(value: &Option<String>) -> String {
+        value.clone().unwrap_or_default()
+    }
+}
+
+impl SupervisorConfig {
+    /// Checks that the new consensus configuration does not remove or replace too many
+    /// validators of the configuration it replaces, and that at least one of the new
+    /// validators has a positive vote weight if vote weights are set.
+    pub fn check_validators_change(
+        &self,
+        base: &ConsensusConfig,
+        new: &ConsensusConfig,
+    ) -> Result<(), String> {
+        if !self.vote_weights.is_empty()
+            && new
+                .validator_keys
+                .iter()
+                .all(|keys| self.vote_weight(&keys.service_key) == 0)
+        {
+            return Err(
+                "At least one of the new validators should have a positive vote weight".to_owned(),
+            );
+        }
+
+        let percent = match self.max_removed_validators_percent {
+            Some(percent) => percent as usize,
+            None => return Ok(()),
+        };
+
+        let base_validators = base.validator_keys.len();
+        let removed = base
+            .validator_keys
+            .iter()
+            .filter(|keys| !new.validator_keys.contains(keys))
+            .count();
+        if removed * 100 > percent * base_validators {
+            return Err(format!(
+                "Configuration change removes or replaces {} of {} validators, \
+                 while at most {}% is allowed",
+                removed, base_validators, percent
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the weight of the vote of the validator with the given service key.
+    ///
+    /// If vote weights are not set, each validator vote has the weight of 1.
+    pub fn vote_weight(&self, service_key: &PublicKey) -> u64 {
+        if self.vote_weights.is_empty() {
+            return 1;
+        }
+        self.vote_weights
+            .iter()
+            .find(|entry| entry.service_key == *service_key)
+            .map_or(0, |entry| entry.weight)
+    }
+
+    /// Returns the number of confirmations required to apply a configuration change
+    /// in the network with the given number of validators.
+    ///
+    /// If the validator set has changed since `majority_count` was set, the value is
+    /// clamped to the range between the byzantine majority and the number of validators.
+    pub fn config_majority(&self, validators: usize) -> usize {
+        match self.mode {
+            Mode::Simple => 1,
+            Mode::Decentralized => {
+                let quorum = byzantine_quorum(validators);
+                self.majority_count
+                    .map_or(quorum, |count| (count as usize).min(validators).max(quorum))
+            }
+        }
+    }
 }
 
 /// Request for the artifact deployment.
@@ -110,6 +372,55 @@ pub struct ServiceConfig {
     pub params: Vec<u8>,
 }
 
+/// Partial update of the consensus configuration.
+///
+/// The patch is expressed in the JSON merge patch format ([RFC 7386]) and is applied to
+/// the JSON representation of the consensus configuration actual at the moment
+/// the proposal is received. The supervisor replaces the patch with the resulting
+/// configuration, so that the proposal hash commits to the exact configuration to be applied.
+///
+/// [RFC 7386]: https://tools.ietf.org/html/rfc7386
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ConsensusConfigPatch")]
+pub struct ConsensusConfigPatch {
+    /// JSON merge patch for the consensus configuration.
+    pub merge_patch: String,
+}
+
+impl ConsensusConfigPatch {
+    /// Applies the patch to the given consensus configuration.
+    pub fn apply(&self, config: &ConsensusConfig) -> Result<ConsensusConfig, failure::Error> {
+        let patch: serde_json::Value = serde_json::from_str(&self.merge_patch)?;
+        let mut value = serde_json::to_value(config)?;
+        merge_patch(&mut value, &patch);
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Applies a JSON merge patch to the target value as described in RFC 7386.
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    if let serde_json::Value::Object(patch) = patch {
+        if !target.is_object() {
+            *target = serde_json::Value::Object(serde_json::Map::new());
+        }
+        let target = target.as_object_mut().unwrap();
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                let entry = target
+                    .entry(key.as_str())
+                    .or_insert(serde_json::Value::Null);
+                merge_patch(entry, value);
+            }
+        }
+    } else {
+        *target = patch.clone();
+    }
+}
+
 /// Atomic configuration change.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[derive(Serialize, Deserialize)]
@@ -124,12 +435,13 @@ pub enum ConfigChange {
     StartService(StartService),
     /// Existing service instance stop request.
     StopService(StopService),
+    /// Partial update of the consensus config.
+    ConsensusPatch(ConsensusConfigPatch),
 }
 
 /// Request for the configuration change
 #[derive(Debug, Clone, Eq, PartialEq)]
-#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
-#[protobuf_convert(source = "proto::ConfigPropose")]
+#[derive(BinaryValue, ObjectHash)]
 pub struct ConfigPropose {
     /// The height until which the update configuration procedure should be completed.
     pub actual_from: Height,
@@ -137,9 +449,74 @@ pub struct ConfigPropose {
     pub changes: Vec<ConfigChange>,
     /// Configuration proposal number to avoid conflicting proposals.
     pub configuration_number: u64,
+    /// Activation of the change by the blockchain time. If set, the change is applied
+    /// once the consolidated time reported by the time oracle specified in the supervisor
+    /// configuration reaches the specified time, and `actual_from` serves as the deadline
+    /// height for the activation.
+    pub activation_time: Option<DateTime<Utc>>,
+    /// Human-readable description of the proposal. May be empty.
+    pub description: String,
+    /// URL of the document describing the proposal in detail (e.g., a governance
+    /// discussion). May be empty.
+    pub reference_url: String,
+    /// Hash of the proposal which should be applied before this one. If set, the change
+    /// is applied once the dependency is applied and the proposal collects enough votes,
+    /// and `actual_from` serves as the deadline height for the activation.
+    pub depends_on: Option<Hash>,
+}
+
+// The conversion is implemented manually rather than derived, so that optional fields
+// are omitted from the Protobuf message if not set. Thus, hashes of proposals not using
+// these fields are the same as before the fields were introduced.
+impl ProtobufConvert for ConfigPropose {
+    type ProtoStruct = proto::ConfigPropose;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_actual_from(self.actual_from.0);
+        pb.set_changes(self.changes.to_pb().into());
+        pb.set_configuration_number(self.configuration_number);
+        if let Some(activation_time) = &self.activation_time {
+            pb.set_activation_time(activation_time.to_pb());
+        }
+        pb.set_description(self.description.clone());
+        pb.set_reference_url(self.reference_url.clone());
+        if let Some(depends_on) = &self.depends_on {
+            pb.set_depends_on(depends_on.to_pb());
+        }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        let activation_time = if pb.has_activation_time() {
+            Some(DateTime::from_pb(pb.take_activation_time())?)
+        } else {
+            None
+        };
+        let depends_on = if pb.has_depends_on() {
+            Some(Hash::from_pb(pb.take_depends_on())?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            actual_from: Height(pb.get_actual_from()),
+            changes: ProtobufConvert::from_pb(pb.take_changes().into_vec())?,
+            configuration_number: pb.get_configuration_number(),
+            activation_time,
+            description: pb.take_description(),
+            reference_url: pb.take_reference_url(),
+            depends_on,
+        })
+    }
 }
 
 impl ConfigPropose {
+    /// Maximum length of the proposal description in bytes.
+    pub const MAX_DESCRIPTION_LEN: usize = 4096;
+    /// Maximum length of the proposal reference URL in bytes.
+    pub const MAX_REFERENCE_URL_LEN: usize = 1024;
+
     /// Signs the proposal for the supervisor service.
     pub fn sign_for_supervisor(
         self,
@@ -157,9 +534,41 @@ impl ConfigPropose {
             actual_from,
             changes: Vec::default(),
             configuration_number,
+            activation_time: None,
+            description: String::new(),
+            reference_url: String::new(),
+            depends_on: None,
         }
     }
 
+    /// Sets the human-readable description of the proposal.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the URL of the document describing the proposal in detail.
+    pub fn reference_url(mut self, reference_url: impl Into<String>) -> Self {
+        self.reference_url = reference_url.into();
+        self
+    }
+
+    /// Makes the proposal activate once the consolidated time reported by the time oracle
+    /// reaches `time`. The oracle is specified by the `time_service` field of
+    /// the supervisor configuration. The height set for the proposal serves as the deadline
+    /// for the activation.
+    pub fn activate_at_time(mut self, time: DateTime<Utc>) -> Self {
+        self.activation_time = Some(time);
+        self
+    }
+
+    /// Makes the proposal activate only after the proposal with the specified hash
+    /// is applied. The height set for the proposal serves as the deadline for the activation.
+    pub fn after_proposal(mut self, propose_hash: Hash) -> Self {
+        self.depends_on = Some(propose_hash);
+        self
+    }
+
     /// Creates a new proposal which should be activated at the next height.
     pub fn immediate(configuration_number: u64) -> Self {
         Self::new(configuration_number, Height(0))
@@ -171,6 +580,32 @@ impl ConfigPropose {
         self
     }
 
+    /// Adds a partial update of consensus configuration to this proposal.
+    /// See [`ConsensusConfigPatch`] for details.
+    ///
+    /// [`ConsensusConfigPatch`]: struct.ConsensusConfigPatch.html
+    pub fn consensus_config_patch(mut self, merge_patch: impl Into<String>) -> Self {
+        self.changes
+            .push(ConfigChange::ConsensusPatch(ConsensusConfigPatch {
+                merge_patch: merge_patch.into(),
+            }));
+        self
+    }
+
+    /// Replaces consensus config patches in this proposal with the configs obtained
+    /// by applying the patches to the provided actual config.
+    pub(crate) fn resolve_patches(
+        &mut self,
+        actual_config: &ConsensusConfig,
+    ) -> Result<(), failure::Error> {
+        for change in &mut self.changes {
+            if let ConfigChange::ConsensusPatch(patch) = change {
+                *change = ConfigChange::Consensus(patch.apply(actual_config)?);
+            }
+        }
+        Ok(())
+    }
+
     /// Adds change of the configuration for the specified service instance.
     pub fn service_config(mut self, instance_id: InstanceId, config: impl BinaryValue) -> Self {
         self.changes.push(ConfigChange::Service(ServiceConfig {
@@ -205,6 +640,19 @@ impl ConfigPropose {
     }
 }
 
+/// Delegation of configuration proposals and votes to another key.
+///
+/// The delegate key is allowed to propose configuration changes and vote for them
+/// on behalf of the validator that has signed the delegation, so that the validator
+/// service key does not need to be kept on the machine sending such transactions.
+/// Delegating to the validator key itself removes the delegation.
+#[derive(Debug, Clone, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::DelegateConfigKey")]
+pub struct DelegateConfigKey {
+    /// Key allowed to propose and vote on behalf of the validator.
+    pub delegate: PublicKey,
+}
+
 /// Confirmation vote for the configuration change
 #[derive(Debug, Clone, PartialEq, ProtobufConvert, BinaryValue, ObjectHash)]
 #[protobuf_convert(source = "proto::ConfigVote")]
@@ -225,6 +673,54 @@ pub struct ConfigProposalWithHash {
     pub config_propose: ConfigPropose,
 }
 
+/// Vote cast by a validator for a configuration proposal.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::VoteRecord")]
+pub struct VoteRecord {
+    /// Hash of the configuration proposal.
+    pub propose_hash: Hash,
+    /// Kind of the vote.
+    pub kind: VoteKind,
+    /// Height of the block in which the vote was committed.
+    pub height: Height,
+    /// Hash of the transaction with the vote.
+    pub tx_hash: Hash,
+}
+
+/// Record about the applied configuration proposal.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ActivationRecord")]
+pub struct ActivationRecord {
+    /// Hash of the applied configuration proposal.
+    pub propose_hash: Hash,
+    /// Hash of the previously applied configuration proposal, or `Hash::zero()`
+    /// if the proposal is the first one applied.
+    pub previous_propose_hash: Hash,
+    /// Height of the block in which the proposal was applied.
+    pub height: Height,
+    /// Hash of the transaction which made the proposal approved, i.e., the confirmation
+    /// which collected the required majority of votes.
+    pub deciding_tx_hash: Hash,
+}
+
+/// Configuration proposal accepted by the supervisor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::ProposalRecord")]
+pub struct ProposalRecord {
+    /// The configuration change proposal.
+    pub config_propose: ConfigPropose,
+    /// Height of the block in which the proposal was committed.
+    pub height: Height,
+    /// Hash of the transaction with the proposal.
+    pub tx_hash: Hash,
+}
+
 impl_binary_key_for_binary_value! { DeployRequest }
 impl_binary_key_for_binary_value! { DeployResult }
 impl_binary_key_for_binary_value! { StartService }
@@ -264,3 +760,32 @@ impl From<ConfigPropose> for ConfigVote {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn config_propose_optional_fields_are_omitted() {
+        let propose = ConfigPropose::new(1, Height(5));
+        let pb = propose.to_pb();
+        assert!(!pb.has_activation_time());
+        assert!(!pb.has_depends_on());
+        assert_eq!(
+            ConfigPropose::from_bytes(propose.to_bytes().into()).unwrap(),
+            propose
+        );
+
+        let propose = propose
+            .activate_at_time(Utc.timestamp(100, 0))
+            .after_proposal(Hash::zero());
+        let pb = propose.to_pb();
+        assert!(pb.has_activation_time());
+        assert!(pb.has_depends_on());
+        assert_eq!(
+            ConfigPropose::from_bytes(propose.to_bytes().into()).unwrap(),
+            propose
+        );
+    }
+}
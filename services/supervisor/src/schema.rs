@@ -13,17 +13,20 @@
 // limitations under the License.
 
 use exonum::{
-    crypto::Hash,
+    blockchain::ValidatorKeys,
+    crypto::{Hash, PublicKey},
+    helpers::Height,
     runtime::{ArtifactId, InstanceId},
 };
 use exonum_derive::*;
 use exonum_merkledb::{
     access::{Access, FromAccess, Prefixed},
-    Entry, Fork, ProofEntry, ProofMapIndex,
+    Entry, Fork, Group, ObjectHash, ProofEntry, ProofListIndex, ProofMapIndex,
 };
 
 use super::{
-    multisig::MultisigIndex, ConfigProposalWithHash, DeployRequest, DeployState, SupervisorConfig,
+    multisig::MultisigIndex, ActivationRecord, ConfigProposalWithHash, DeployRequest, DeployState,
+    ProposalRecord, ProposalState, SupervisorConfig, VoteRecord,
 };
 
 /// Service information schema.
@@ -45,6 +48,12 @@ pub(crate) struct SchemaImpl<T: Access> {
     pub pending_deployments: ProofMapIndex<T::Base, ArtifactId, DeployRequest>,
     /// Votes for a configuration change.
     pub config_confirms: MultisigIndex<T, Hash>,
+    /// Hashes of the transactions which made configuration proposals approved.
+    pub deciding_votes: ProofMapIndex<T::Base, Hash, Hash>,
+    /// Votes against a configuration change.
+    pub config_rejections: MultisigIndex<T, Hash>,
+    /// Authors of the configuration proposals.
+    pub proposal_authors: ProofMapIndex<T::Base, Hash, PublicKey>,
     /// Number of the processed configurations. Used to avoid conflicting configuration proposals.
     pub configuration_number: Entry<T::Base, u64>,
     /// The following free instance ID for assignment.
@@ -58,6 +67,30 @@ pub struct Schema<T: Access> {
     pub configuration: ProofEntry<T::Base, SupervisorConfig>,
     /// Current pending configuration proposal.
     pub pending_proposal: ProofEntry<T::Base, ConfigProposalWithHash>,
+    /// Configuration proposal queued behind the approved pending proposal.
+    pub queued_proposal: ProofEntry<T::Base, ConfigProposalWithHash>,
+    /// Configuration proposals waiting for the proposals they depend on to be applied,
+    /// keyed by the hash of the dependency.
+    pub waiting_proposals: ProofMapIndex<T::Base, Hash, ConfigProposalWithHash>,
+    /// All accepted configuration proposals keyed by the proposal hash.
+    pub proposals: ProofMapIndex<T::Base, Hash, ProposalRecord>,
+    /// Hashes of the accepted configuration proposals grouped by the proposal author.
+    pub proposals_by_author: Group<T, PublicKey, ProofListIndex<T::Base, Hash>>,
+    /// Hashes of the lists in `proposals_by_author` keyed by the proposal author.
+    /// Unlike the group, this index is aggregated into the blockchain state.
+    pub proposals_by_author_hashes: ProofMapIndex<T::Base, PublicKey, Hash>,
+    /// Votes for the configuration proposals grouped by the validator who cast them.
+    pub votes_by_validator: Group<T, PublicKey, ProofListIndex<T::Base, VoteRecord>>,
+    /// Hashes of the lists in `votes_by_validator` keyed by the validator service key.
+    /// Unlike the group, this index is aggregated into the blockchain state.
+    pub votes_by_validator_hashes: ProofMapIndex<T::Base, PublicKey, Hash>,
+    /// Keys allowed to propose and vote for configuration changes on behalf of the validators,
+    /// keyed by the validator service key.
+    pub config_key_delegates: ProofMapIndex<T::Base, PublicKey, PublicKey>,
+    /// Append-only log of the applied configuration proposals.
+    pub config_activation_log: ProofListIndex<T::Base, ActivationRecord>,
+    /// States of the configuration proposals keyed by the proposal hash.
+    pub proposal_states: ProofMapIndex<T::Base, Hash, ProposalState>,
 }
 
 impl<T: Access> SchemaImpl<T> {
@@ -80,6 +113,146 @@ impl<T: Access> SchemaImpl<T> {
             .get()
             .expect("Supervisor entity was not configured; unable to load configuration")
     }
+
+    /// Returns the proposal with the given hash if it waits for its dependency to be applied.
+    pub fn waiting_proposal(&self, propose_hash: &Hash) -> Option<ConfigProposalWithHash> {
+        let record = self.public.proposals.get(propose_hash)?;
+        let dependency = record.config_propose.depends_on?;
+        self.public
+            .waiting_proposals
+            .get(&dependency)
+            .filter(|entry| entry.propose_hash == *propose_hash)
+    }
+
+    /// Checks whether the minimal activation delay (see [`SupervisorConfig`]) has passed
+    /// by the given height since the configuration proposal was accepted.
+    ///
+    /// [`SupervisorConfig`]: struct.SupervisorConfig.html
+    pub fn activation_delay_passed(&self, propose_hash: &Hash, height: Height) -> bool {
+        let accepted_at = self
+            .public
+            .proposals
+            .get(propose_hash)
+            .map_or(0, |record| record.height.0);
+        height.0 >= accepted_at + self.supervisor_config().min_activation_delay
+    }
+
+    /// Checks whether the configuration proposal has collected enough confirmations
+    /// from the given validators to be applied.
+    ///
+    /// If vote weights are set in the supervisor configuration, the confirmed weight should
+    /// exceed two thirds of the total weight of the validators. Otherwise, the number of
+    /// confirmations should reach the configured majority.
+    pub fn config_approved(&self, propose_hash: &Hash, validator_keys: &[ValidatorKeys]) -> bool {
+        let config = self.supervisor_config();
+        if config.vote_weights.is_empty() {
+            let majority = config.config_majority(validator_keys.len());
+            return self.config_confirms.confirmations(propose_hash) >= majority;
+        }
+        weighted_majority(&config, &self.config_confirms, propose_hash, validator_keys)
+    }
+
+    /// Checks whether the configuration proposal is rejected by the given validators.
+    ///
+    /// If vote weights are set in the supervisor configuration, the rejected weight should
+    /// exceed two thirds of the total weight of the validators.
+    pub fn config_rejected(&self, propose_hash: &Hash, validator_keys: &[ValidatorKeys]) -> bool {
+        let config = self.supervisor_config();
+        if config.vote_weights.is_empty() {
+            return config.mode.config_rejected(
+                propose_hash,
+                &self.config_rejections,
+                validator_keys.len(),
+            );
+        }
+        weighted_majority(
+            &config,
+            &self.config_rejections,
+            propose_hash,
+            validator_keys,
+        )
+    }
+}
+
+/// Checks whether the validators that have voted for the proposal have more than two thirds
+/// of the total weight of the validators.
+///
+/// If none of the validators has a positive weight, the votes are counted as if the weights
+/// were not set, so that the proposals can still be decided.
+fn weighted_majority<T: Access>(
+    config: &SupervisorConfig,
+    votes: &MultisigIndex<T, Hash>,
+    propose_hash: &Hash,
+    validator_keys: &[ValidatorKeys],
+) -> bool {
+    let mut voted_weight = 0_u128;
+    let mut total_weight = 0_u128;
+    for keys in validator_keys {
+        let weight = u128::from(config.vote_weight(&keys.service_key));
+        total_weight += weight;
+        if votes.confirmed_by(propose_hash, &keys.service_key) {
+            voted_weight += weight;
+        }
+    }
+
+    if total_weight == 0 {
+        let votes_count = validator_keys
+            .iter()
+            .filter(|keys| votes.confirmed_by(propose_hash, &keys.service_key))
+            .count();
+        return votes_count >= config.config_majority(validator_keys.len());
+    }
+    voted_weight * 3 > total_weight * 2
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum::crypto::gen_keypair;
+    use exonum_merkledb::{Database, IndexAddress, TemporaryDB};
+
+    use super::*;
+    use crate::{Supervisor, ValidatorWeight};
+
+    #[test]
+    fn weighted_majority_without_weighted_validators() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut votes: MultisigIndex<_, Hash> =
+            MultisigIndex::from_access(&fork, IndexAddress::from_root("votes")).unwrap();
+        let validator_keys: Vec<_> = (0..4)
+            .map(|_| ValidatorKeys {
+                consensus_key: gen_keypair().0,
+                service_key: gen_keypair().0,
+            })
+            .collect();
+        // Weight is set only for the key which does not belong to the validators.
+        let config = SupervisorConfig {
+            vote_weights: vec![ValidatorWeight {
+                service_key: gen_keypair().0,
+                weight: 1,
+            }],
+            ..Supervisor::decentralized_config()
+        };
+
+        // Votes are counted as if weights were not set, so the byzantine majority is required.
+        let propose_hash = Hash::zero();
+        for keys in &validator_keys[..2] {
+            votes.confirm(&propose_hash, keys.service_key);
+        }
+        assert!(!weighted_majority(
+            &config,
+            &votes,
+            &propose_hash,
+            &validator_keys
+        ));
+        votes.confirm(&propose_hash, validator_keys[2].service_key);
+        assert!(weighted_majority(
+            &config,
+            &votes,
+            &propose_hash,
+            &validator_keys
+        ));
+    }
 }
 
 impl SchemaImpl<Prefixed<'_, &Fork>> {
@@ -89,6 +262,183 @@ impl SchemaImpl<Prefixed<'_, &Fork>> {
         self.configuration_number.set(new_configuration_number);
     }
 
+    /// Removes the pending config proposal and records its final state.
+    ///
+    /// If the pending proposal is applied, the queued proposal (if any) becomes pending.
+    /// Otherwise, the queued proposal is discarded as well, since it was proposed
+    /// on top of the removed one. Similarly, the proposal waiting for the applied proposal
+    /// becomes pending, while proposals depending on a discarded one are discarded too.
+    ///
+    /// Votes for the removed proposals are pruned. The proposals themselves and their
+    /// final states are kept, so that the history of the proposals can be audited.
+    pub fn remove_pending_proposal(&mut self, state: ProposalState) {
+        let mut applied_hash = None;
+        if let Some(entry) = self.public.pending_proposal.get() {
+            self.public.pending_proposal.remove();
+            self.public.proposal_states.put(&entry.propose_hash, state);
+            self.prune_votes(&entry.propose_hash);
+            if state == ProposalState::Applied {
+                applied_hash = Some(entry.propose_hash);
+            } else {
+                self.discard_dependent_proposals(entry.propose_hash);
+            }
+        }
+
+        if let Some(queued) = self.public.queued_proposal.get() {
+            self.public.queued_proposal.remove();
+            if state == ProposalState::Applied {
+                let state = self.voting_state(&queued.propose_hash);
+                self.public.proposal_states.put(&queued.propose_hash, state);
+                self.public.pending_proposal.set(queued);
+            } else {
+                self.public
+                    .proposal_states
+                    .put(&queued.propose_hash, ProposalState::Obsoleted);
+                self.prune_votes(&queued.propose_hash);
+                self.discard_dependent_proposals(queued.propose_hash);
+            }
+        }
+
+        // The proposal waiting for the applied one becomes pending. The pending slot
+        // is free, since proposals cannot be queued behind a proposal with a dependent one.
+        if let Some(applied_hash) = applied_hash {
+            if let Some(entry) = self.public.waiting_proposals.get(&applied_hash) {
+                self.public.waiting_proposals.remove(&applied_hash);
+                self.public.pending_proposal.set(entry);
+            }
+        }
+    }
+
+    /// Removes the pending or waiting configuration proposal with the given hash
+    /// and sets the provided final state for it.
+    pub fn remove_proposal(&mut self, propose_hash: &Hash, state: ProposalState) {
+        let is_pending = self
+            .public
+            .pending_proposal
+            .get()
+            .map_or(false, |entry| entry.propose_hash == *propose_hash);
+        if is_pending {
+            self.remove_pending_proposal(state);
+        } else if let Some(entry) = self.waiting_proposal(propose_hash) {
+            let dependency = entry.config_propose.depends_on.unwrap();
+            self.public.waiting_proposals.remove(&dependency);
+            self.public.proposal_states.put(propose_hash, state);
+            self.prune_votes(propose_hash);
+            self.discard_dependent_proposals(*propose_hash);
+        }
+    }
+
+    /// Discards the chain of proposals waiting for the proposal which will not be applied.
+    fn discard_dependent_proposals(&mut self, mut dependency: Hash) {
+        while let Some(entry) = self.public.waiting_proposals.get(&dependency) {
+            self.public.waiting_proposals.remove(&dependency);
+            self.public
+                .proposal_states
+                .put(&entry.propose_hash, ProposalState::Obsoleted);
+            self.prune_votes(&entry.propose_hash);
+            dependency = entry.propose_hash;
+        }
+    }
+
+    /// Removes the votes for the configuration proposal which can no longer be applied.
+    fn prune_votes(&mut self, propose_hash: &Hash) {
+        self.config_confirms.remove(propose_hash);
+        self.config_rejections.remove(propose_hash);
+        self.deciding_votes.remove(propose_hash);
+    }
+
+    /// Adds the validator confirmation for the configuration proposal. If the confirmation
+    /// makes the proposal approved, the transaction is remembered as the deciding one.
+    pub fn confirm_config(
+        &mut self,
+        propose_hash: &Hash,
+        author: PublicKey,
+        tx_hash: Hash,
+        validator_keys: &[ValidatorKeys],
+    ) {
+        self.config_confirms.confirm(propose_hash, author);
+        if !self.deciding_votes.contains(propose_hash)
+            && self.config_approved(propose_hash, validator_keys)
+        {
+            self.deciding_votes.put(propose_hash, tx_hash);
+            self.update_voting_state(propose_hash);
+        }
+    }
+
+    /// Removes the validator confirmation for the configuration proposal.
+    pub fn revoke_config_confirmation(
+        &mut self,
+        propose_hash: &Hash,
+        author: &PublicKey,
+        validator_keys: &[ValidatorKeys],
+    ) {
+        self.config_confirms.revoke(propose_hash, author);
+        if self.deciding_votes.contains(propose_hash)
+            && !self.config_approved(propose_hash, validator_keys)
+        {
+            self.deciding_votes.remove(propose_hash);
+            self.update_voting_state(propose_hash);
+        }
+    }
+
+    /// Returns the state of the proposal open for voting depending on whether
+    /// it has collected enough confirmations.
+    pub fn voting_state(&self, propose_hash: &Hash) -> ProposalState {
+        if self.deciding_votes.contains(propose_hash) {
+            ProposalState::Approved
+        } else {
+            ProposalState::Pending
+        }
+    }
+
+    /// Switches the proposal between the `Pending` and `Approved` states after
+    /// the number of confirmations has changed. Proposals in other states are not affected.
+    fn update_voting_state(&mut self, propose_hash: &Hash) {
+        match self.public.proposal_states.get(propose_hash) {
+            Some(ProposalState::Pending) | Some(ProposalState::Approved) => {
+                let state = self.voting_state(propose_hash);
+                self.public.proposal_states.put(propose_hash, state);
+            }
+            _ => {}
+        }
+    }
+
+    /// Appends the applied configuration proposal to the activation log.
+    pub fn log_activation(&mut self, propose_hash: Hash, height: Height) {
+        let log = &mut self.public.config_activation_log;
+        let previous_propose_hash = log
+            .last()
+            .map_or_else(Hash::zero, |record| record.propose_hash);
+        let deciding_tx_hash = self
+            .deciding_votes
+            .get(&propose_hash)
+            .unwrap_or_else(Hash::zero);
+        log.push(ActivationRecord {
+            propose_hash,
+            previous_propose_hash,
+            height,
+            deciding_tx_hash,
+        });
+    }
+
+    /// Records the accepted configuration proposal of the author.
+    pub fn record_proposal(&mut self, author: PublicKey, propose_hash: Hash) {
+        let mut proposals = self.public.proposals_by_author.get(&author);
+        proposals.push(propose_hash);
+        self.public
+            .proposals_by_author_hashes
+            .put(&author, proposals.object_hash());
+    }
+
+    /// Records the vote cast by the validator for the configuration proposal.
+    pub fn record_vote(&mut self, validator: PublicKey, vote: VoteRecord) {
+        let mut votes = self.public.votes_by_validator.get(&validator);
+        votes.push(vote);
+        self.public
+            .votes_by_validator_hashes
+            .put(&validator, votes.object_hash());
+    }
+
     /// Assigns a unique identifier for an instance.
     /// Returns `None` if `vacant_instance_id` entry was not initialized.
     pub(crate) fn assign_instance_id(&mut self) -> Option<InstanceId> {
@@ -0,0 +1,209 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to change the configuration via the supervisor in the testkit.
+
+use exonum::{
+    crypto::Hash,
+    helpers::Height,
+    runtime::{SnapshotExt, SUPERVISOR_INSTANCE_ID},
+};
+use exonum_merkledb::ObjectHash;
+use exonum_testkit::{TestKit, TestNode};
+
+use std::cmp::Reverse;
+
+use super::{schema::SchemaImpl, supervisor_name, ConfigPropose, ConfigVote, SupervisorInterface};
+
+/// Extension trait for `TestKit` allowing to change the configuration of the blockchain
+/// through the supervisor service without the boilerplate of signing proposals and votes.
+///
+/// The supervisor must be instantiated in the testkit. Proposals and votes are signed
+/// with the validator keys of the emulated network: the proposal is authored by the node
+/// from whose perspective the testkit operates (or by the first validator if this node
+/// is an auditor), and the votes are cast by other validators in the amount required
+/// by the supervisor configuration. Byzantine validators (see `TestNetwork::make_byzantine`)
+/// neither author proposals nor vote for them.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum_rust_runtime::ServiceFactory;
+/// # use exonum_supervisor::{ConfigPropose, Supervisor, SupervisorTestKitExt};
+/// # use exonum_testkit::TestKitBuilder;
+/// let mut testkit = TestKitBuilder::validator()
+///     .with_validators(4)
+///     .with_rust_service(Supervisor)
+///     .with_artifact(Supervisor.artifact_id())
+///     .with_instance(Supervisor::decentralized())
+///     .create();
+///
+/// let mut consensus_config = testkit.consensus_config();
+/// consensus_config.min_propose_timeout += 1;
+/// let propose = ConfigPropose::immediate(0).consensus_config(consensus_config.clone());
+/// testkit.commit_configuration_change(propose);
+/// assert_eq!(testkit.consensus_config(), consensus_config);
+/// ```
+pub trait SupervisorTestKitExt {
+    /// Returns the configuration number which the supervisor expects in the next proposal.
+    fn configuration_number(&self) -> u64;
+
+    /// Proposes a configuration change and casts the votes of validators required to
+    /// approve it. The proposal and the votes are committed in a single new block.
+    ///
+    /// The configuration number of the proposal is replaced with the one expected by
+    /// the supervisor. If the `actual_from` height of the proposal is not set, it is set
+    /// to the earliest height allowed by the minimal activation delay, as the supervisor
+    /// would do.
+    ///
+    /// Returns the hash of the proposal.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the proposal or any of the votes are rejected by the supervisor.
+    /// - Panics if there are not enough honest validators to approve the proposal.
+    fn propose_configuration_change(&mut self, propose: ConfigPropose) -> Hash;
+
+    /// Proposes a configuration change as [`propose_configuration_change`] does, and creates
+    /// blocks until the change is applied.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the proposal or any of the votes are rejected by the supervisor.
+    /// - Panics if the change is not applied by the `actual_from` height of the proposal
+    ///   (e.g., if the change is discarded because of an error during its application).
+    ///
+    /// [`propose_configuration_change`]: #tymethod.propose_configuration_change
+    fn commit_configuration_change(&mut self, propose: ConfigPropose);
+}
+
+impl SupervisorTestKitExt for TestKit {
+    fn configuration_number(&self) -> u64 {
+        let snapshot = self.snapshot();
+        let access = snapshot
+            .for_service(supervisor_name())
+            .expect("Supervisor is not instantiated in the testkit");
+        SchemaImpl::new(access).get_configuration_number()
+    }
+
+    fn propose_configuration_change(&mut self, mut propose: ConfigPropose) -> Hash {
+        let config = {
+            let snapshot = self.snapshot();
+            let access = snapshot
+                .for_service(supervisor_name())
+                .expect("Supervisor is not instantiated in the testkit");
+            let schema = SchemaImpl::new(access);
+            propose.configuration_number = schema.get_configuration_number();
+            schema.supervisor_config()
+        };
+        if propose.actual_from == Height(0) {
+            propose.actual_from = Height(self.height().next().0 + config.min_activation_delay);
+        }
+        // Consensus config patches are resolved by the supervisor before hashing the proposal.
+        propose
+            .resolve_patches(&self.consensus_config())
+            .expect("Cannot apply consensus config patch");
+        let propose_hash = propose.object_hash();
+
+        let network = self.network();
+        let validators = network.honest_validators();
+        let us = network.us();
+        let initiator = if us.validator_id().is_some() && network.byzantine_behavior(us).is_none() {
+            us.clone()
+        } else {
+            validators
+                .first()
+                .expect("Not enough honest validators to approve configuration change")
+                .clone()
+        };
+        let other_validators = validators
+            .iter()
+            .filter(|validator| validator.validator_id() != initiator.validator_id());
+        // The vote of the initiator is counted automatically.
+        let voters: Vec<_> = if config.vote_weights.is_empty() {
+            let required_votes = config.config_majority(network.validators().len()) - 1;
+            assert!(
+                validators.len() > required_votes,
+                "Not enough honest validators to approve configuration change"
+            );
+            other_validators.take(required_votes).collect()
+        } else {
+            // Validators with larger weights vote first, until the confirmed weight
+            // exceeds two thirds of the total weight.
+            let weight = |validator: &TestNode| {
+                u128::from(config.vote_weight(&validator.service_keypair().0))
+            };
+            let total_weight: u128 = network.validators().iter().map(weight).sum();
+            let mut voted_weight = weight(&initiator);
+            let mut voters: Vec<_> = other_validators.collect();
+            voters.sort_by_key(|validator| Reverse(weight(*validator)));
+            let voters = voters
+                .into_iter()
+                .take_while(|validator| {
+                    if voted_weight * 3 > total_weight * 2 {
+                        return false;
+                    }
+                    voted_weight += weight(*validator);
+                    true
+                })
+                .collect();
+            assert!(
+                voted_weight * 3 > total_weight * 2,
+                "Not enough honest validators to approve configuration change"
+            );
+            voters
+        };
+
+        let keys = initiator.service_keypair();
+        let mut transactions = vec![propose.sign_for_supervisor(keys.0, &keys.1)];
+        let votes = voters.into_iter().map(|validator| {
+            validator
+                .service_keypair()
+                .confirm_config_change(SUPERVISOR_INSTANCE_ID, ConfigVote { propose_hash })
+        });
+        transactions.extend(votes);
+
+        let block = self.create_block_with_transactions(transactions);
+        for transaction in &block.transactions {
+            transaction
+                .status()
+                .expect("Configuration change was rejected by the supervisor");
+        }
+        propose_hash
+    }
+
+    fn commit_configuration_change(&mut self, propose: ConfigPropose) {
+        let propose_hash = self.propose_configuration_change(propose);
+        // Returns the deadline height of the proposal if it is still pending.
+        let pending_deadline = |testkit: &Self| {
+            let snapshot = testkit.snapshot();
+            let access = snapshot.for_service(supervisor_name()).unwrap();
+            SchemaImpl::new(access)
+                .public
+                .pending_proposal
+                .get()
+                .filter(|entry| entry.propose_hash == propose_hash)
+                .map(|entry| entry.config_propose.actual_from)
+        };
+
+        // The proposal may already be applied in the block with the votes.
+        while let Some(actual_from) = pending_deadline(self) {
+            assert!(
+                self.height() < actual_from,
+                "Configuration change was not applied by the supervisor"
+            );
+            self.create_block();
+        }
+    }
+}
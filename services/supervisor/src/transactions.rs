@@ -13,20 +13,22 @@
 // limitations under the License.
 
 use exonum::{
-    crypto::PublicKey,
+    blockchain::ConsensusConfig,
+    crypto::{Hash, PublicKey},
     helpers::{Height, ValidateInput},
     runtime::{CommonError, ExecutionError, ExecutionFail, InstanceSpec, InstanceStatus},
 };
 use exonum_derive::*;
-use exonum_merkledb::ObjectHash;
+use exonum_merkledb::{access::Access, ObjectHash};
 use exonum_rust_runtime::CallContext;
 
 use std::collections::HashSet;
 
 use super::{
     configure::ConfigureMut, ArtifactError, CommonError as SupervisorCommonError, ConfigChange,
-    ConfigProposalWithHash, ConfigPropose, ConfigVote, ConfigurationError, DeployRequest,
-    DeployResult, DeployState, SchemaImpl, ServiceError, StartService, StopService, Supervisor,
+    ConfigProposalWithHash, ConfigPropose, ConfigVote, ConfigurationError, DelegateConfigKey,
+    DeployRequest, DeployResult, DeployState, ProposalRecord, ProposalState, SchemaImpl,
+    ServiceError, StartService, StopService, Supervisor, VoteKind, VoteRecord,
 };
 
 /// Supervisor service transactions.
@@ -61,7 +63,9 @@ pub trait SupervisorInterface<Ctx> {
     /// are not required for the `Simple` mode, and for `Decentralized` mode (2/3+1) confirmations
     /// are required.
     ///
-    /// **Note:** only one proposal at time is possible.
+    /// **Note:** only one proposal at time is possible. The exceptions are a proposal queued
+    /// behind the approved pending proposal and a proposal waiting for the proposal it depends
+    /// on (see `ConfigPropose::depends_on`) to be applied.
     #[interface_method(id = 2)]
     fn propose_config_change(&self, context: Ctx, propose: ConfigPropose) -> Self::Output;
 
@@ -73,6 +77,42 @@ pub trait SupervisorInterface<Ctx> {
     /// The configuration application rules depend on the `Supervisor` mode.
     #[interface_method(id = 3)]
     fn confirm_config_change(&self, context: Ctx, vote: ConfigVote) -> Self::Output;
+
+    /// Reject config change
+    ///
+    /// This vote should be sent by validators disagreeing with the proposed configuration.
+    /// A validator cannot both confirm and reject the same proposal.
+    /// Once the proposal is rejected by the number of validators depending on the
+    /// `Supervisor` mode (a single validator for the `Simple` mode, and (2/3+1) validators
+    /// for the `Decentralized` mode), the proposal is discarded and cannot be applied.
+    #[interface_method(id = 4)]
+    fn reject_config_change(&self, context: Ctx, vote: ConfigVote) -> Self::Output;
+
+    /// Cancel config change
+    ///
+    /// This transaction can only be sent by the validator that has authored the pending
+    /// proposal, e.g., if the proposal contains a mistake. The cancelled proposal is
+    /// discarded immediately and cannot be voted for anymore. Other validators may
+    /// discard the proposal by rejecting it (see `reject_config_change`).
+    #[interface_method(id = 5)]
+    fn cancel_config_change(&self, context: Ctx, vote: ConfigVote) -> Self::Output;
+
+    /// Revoke config vote
+    ///
+    /// This transaction revokes the vote (either confirming or rejecting) previously cast
+    /// by the validator for the pending proposal, so that the validator can vote again.
+    /// The vote can be revoked as long as the proposal is not applied.
+    #[interface_method(id = 6)]
+    fn revoke_config_vote(&self, context: Ctx, vote: ConfigVote) -> Self::Output;
+
+    /// Delegate config key
+    ///
+    /// This transaction should be sent by a validator to allow another key to propose
+    /// configuration changes and vote for them on behalf of the validator. The delegate
+    /// key cannot belong to another validator or be used by another validator as a delegate.
+    /// Delegating to the validator key itself removes the delegation.
+    #[interface_method(id = 7)]
+    fn delegate_config_key(&self, context: Ctx, request: DelegateConfigKey) -> Self::Output;
 }
 
 impl StartService {
@@ -135,6 +175,184 @@ impl StopService {
     }
 }
 
+/// Returns the pending config proposal (or the proposal waiting for its dependency)
+/// with the specified hash, provided that its deadline height has not been reached.
+fn get_pending_proposal<T: Access>(
+    schema: &SchemaImpl<T>,
+    propose_hash: &Hash,
+    height: Height,
+) -> Result<ConfigProposalWithHash, ExecutionError> {
+    let entry = schema
+        .public
+        .pending_proposal
+        .get()
+        .filter(|entry| entry.propose_hash == *propose_hash)
+        .or_else(|| schema.waiting_proposal(propose_hash));
+
+    // Verifies that this config proposal is registered.
+    let entry = match entry {
+        Some(entry) => entry,
+        None => {
+            let state = schema.public.proposal_states.get(propose_hash);
+            return Err(match state {
+                Some(ProposalState::Expired) => SupervisorCommonError::DeadlineExceeded
+                    .with_description("Config proposal has expired"),
+                Some(ProposalState::Cancelled) => ConfigurationError::ConfigProposeNotRegistered
+                    .with_description("Config proposal has been cancelled by its author"),
+                Some(ProposalState::Queued) => ConfigurationError::ConfigProposeNotRegistered
+                    .with_description(
+                        "Config proposal is queued and cannot be voted for until \
+                         the scheduled proposal is applied",
+                    ),
+                Some(ProposalState::Obsoleted) => ConfigurationError::ConfigProposeNotRegistered
+                    .with_description(
+                        "Config proposal has been discarded since the proposal it was \
+                         queued behind or depended on was not applied",
+                    ),
+                _ => ConfigurationError::ConfigProposeNotRegistered.into(),
+            });
+        }
+    };
+
+    // Verifies that we didn't reach the deadline height.
+    if entry.config_propose.actual_from <= height {
+        let msg = format!(
+            "Deadline height {} for the config proposal has been reached",
+            entry.config_propose.actual_from
+        );
+        return Err(SupervisorCommonError::DeadlineExceeded.with_description(msg));
+    }
+    Ok(entry)
+}
+
+/// Returns the proposal with the specified hash which the new proposal should wait for.
+/// Proposals cannot depend on the applied proposals, since the activation height
+/// of such a proposal would be ambiguous.
+fn get_dependency<T: Access>(
+    schema: &SchemaImpl<T>,
+    dependency: &Hash,
+) -> Result<ConfigProposalWithHash, ExecutionError> {
+    match schema.public.proposal_states.get(dependency) {
+        Some(ProposalState::Applied) => {
+            let msg = format!(
+                "Config proposal {:?} the new proposal depends on is already applied; \
+                 the new proposal should not specify `depends_on`",
+                dependency
+            );
+            Err(ConfigurationError::InvalidDependency.with_description(msg))
+        }
+        Some(state) if !state.is_final() => {
+            let record = schema.public.proposals.get(dependency).unwrap();
+            Ok(ConfigProposalWithHash {
+                config_propose: record.config_propose,
+                propose_hash: *dependency,
+            })
+        }
+        Some(state) => {
+            let msg = format!(
+                "Config proposal {:?} the new proposal depends on is {:?} and cannot be applied",
+                dependency, state
+            );
+            Err(ConfigurationError::InvalidDependency.with_description(msg))
+        }
+        None => {
+            let msg = format!("Config proposal {:?} is unknown", dependency);
+            Err(ConfigurationError::InvalidDependency.with_description(msg))
+        }
+    }
+}
+
+/// Returns the consensus config which the proposal changes to, if any.
+fn scheduled_consensus_config(propose: &ConfigPropose) -> Option<ConsensusConfig> {
+    propose
+        .changes
+        .iter()
+        .filter_map(|change| match change {
+            ConfigChange::Consensus(config) => Some(config.clone()),
+            _ => None,
+        })
+        .last()
+}
+
+/// Checks that the vote refers to the pending config proposal, and that the author
+/// has not voted for the proposal yet.
+fn check_vote<T: Access>(
+    schema: &SchemaImpl<T>,
+    vote: &ConfigVote,
+    author: &PublicKey,
+    height: Height,
+) -> Result<(), ExecutionError> {
+    let entry = get_pending_proposal(schema, &vote.propose_hash, height)?;
+    if schema
+        .config_confirms
+        .confirmed_by(&entry.propose_hash, author)
+        || schema
+            .config_rejections
+            .confirmed_by(&entry.propose_hash, author)
+    {
+        return Err(ConfigurationError::AttemptToVoteTwice.into());
+    }
+    Ok(())
+}
+
+/// Returns the height of the block being created and the hash of the executed transaction.
+fn call_location(context: &CallContext<'_>) -> (Height, Hash) {
+    let height = context.data().for_core().next_height();
+    // Supervisor transactions are authorized by `get_validator`, so the hash is always known.
+    let tx_hash = context
+        .caller()
+        .transaction_hash()
+        .unwrap_or_else(Hash::zero);
+    (height, tx_hash)
+}
+
+/// Checks if method was called by transaction, and transaction author is either a validator
+/// or a key delegated by a validator. Returns the service key of the validator.
+fn get_config_voter(context: &CallContext<'_>) -> Result<PublicKey, ExecutionError> {
+    let author = context
+        .caller()
+        .author()
+        .ok_or(CommonError::UnauthorizedCaller)?;
+
+    let consensus_config = context.data().for_core().consensus_config();
+    let schema = SchemaImpl::new(context.service_data());
+    consensus_config
+        .validator_keys
+        .iter()
+        .map(|keys| keys.service_key)
+        .find(|&validator| {
+            validator == author
+                || schema.public.config_key_delegates.get(&validator) == Some(author)
+        })
+        .ok_or_else(|| CommonError::UnauthorizedCaller.into())
+}
+
+/// Checks that consensus keys of the retained validators are only replaced by proposals
+/// of the validators themselves. This is the second step of the consensus key rotation:
+/// the validator should register the new key pair with its node beforehand, otherwise
+/// the node drops out of the consensus once the configuration is applied.
+fn verify_key_rotations(
+    current: &ConsensusConfig,
+    new: &ConsensusConfig,
+    author: PublicKey,
+) -> Result<(), ExecutionError> {
+    for keys in &new.validator_keys {
+        let is_rotated = current.validator_keys.iter().any(|current_keys| {
+            current_keys.service_key == keys.service_key
+                && current_keys.consensus_key != keys.consensus_key
+        });
+        if is_rotated && keys.service_key != author {
+            let msg = format!(
+                "Consensus key of the validator with service key {} can only be replaced \
+                 by a proposal of this validator",
+                keys.service_key
+            );
+            return Err(ConfigurationError::UnauthorizedKeyRotation.with_description(msg));
+        }
+    }
+    Ok(())
+}
+
 /// Checks if method was called by transaction, and transaction author is a validator.
 fn get_validator(context: &CallContext<'_>) -> Result<PublicKey, ExecutionError> {
     let author = context
@@ -160,91 +378,389 @@ impl SupervisorInterface<CallContext<'_>> for Supervisor {
         mut context: CallContext<'_>,
         mut propose: ConfigPropose,
     ) -> Self::Output {
-        let author = get_validator(&context)?;
+        let author = get_config_voter(&context)?;
+        let (height, tx_hash) = call_location(&context);
 
         let current_height = context.data().for_core().height();
 
-        // If `actual_from` field is not set, set it to the next height.
+        if propose.activation_time.is_some() {
+            // Time-activated proposals should specify the deadline height explicitly.
+            if propose.actual_from == Height(0) {
+                return Err(ConfigurationError::MalformedConfigPropose.into());
+            }
+            let time_service = SchemaImpl::new(context.service_data())
+                .supervisor_config()
+                .time_service
+                .ok_or_else(|| {
+                    let msg = "Time oracle service is not specified in the supervisor config";
+                    ConfigurationError::UnknownTimeOracle.with_description(msg)
+                })?;
+            if context.data().for_service(time_service.as_str()).is_none() {
+                let msg = format!("Time oracle service `{}` does not exist", time_service);
+                return Err(ConfigurationError::UnknownTimeOracle.with_description(msg));
+            }
+        }
+
+        if propose.description.len() > ConfigPropose::MAX_DESCRIPTION_LEN {
+            let msg = format!(
+                "Proposal description should not exceed {} bytes",
+                ConfigPropose::MAX_DESCRIPTION_LEN
+            );
+            return Err(ConfigurationError::MalformedConfigPropose.with_description(msg));
+        }
+        if propose.reference_url.len() > ConfigPropose::MAX_REFERENCE_URL_LEN {
+            let msg = format!(
+                "Proposal reference URL should not exceed {} bytes",
+                ConfigPropose::MAX_REFERENCE_URL_LEN
+            );
+            return Err(ConfigurationError::MalformedConfigPropose.with_description(msg));
+        }
+
+        let min_activation_delay = SchemaImpl::new(context.service_data())
+            .supervisor_config()
+            .min_activation_delay;
+        let earliest_height = Height(current_height.next().0 + min_activation_delay);
+
+        // If `actual_from` field is not set, set it to the earliest allowed height.
         if propose.actual_from == Height(0) {
-            propose.actual_from = current_height.next();
+            propose.actual_from = earliest_height;
         }
         // Otherwise verify that the `actual_from` height is in the future.
         else if current_height >= propose.actual_from {
-            return Err(SupervisorCommonError::ActualFromIsPast.into());
+            let msg = format!(
+                "`actual_from` height {} is not greater than the current height {}",
+                propose.actual_from, current_height
+            );
+            return Err(SupervisorCommonError::ActualFromIsPast.with_description(msg));
+        }
+        // Verify that nodes will have enough time to observe the proposal.
+        else if propose.actual_from < earliest_height {
+            let msg = format!(
+                "`actual_from` height {} should not be less than {} \
+                 due to the minimal activation delay of {} blocks",
+                propose.actual_from, earliest_height, min_activation_delay
+            );
+            return Err(ConfigurationError::ActivationTooEarly.with_description(msg));
         }
 
+        let mut base_config = context.data().for_core().consensus_config();
+        let validator_keys = base_config.validator_keys.clone();
         let mut schema = SchemaImpl::new(context.service_data());
 
-        // Verifies that there are no pending config changes.
-        if let Some(proposal) = schema.public.pending_proposal.get() {
+        // Proposal depending on the proposal which is not applied yet waits for it
+        // instead of becoming pending.
+        let dependency = match propose.depends_on {
+            Some(dependency) => Some(get_dependency(&schema, &dependency)?),
+            None => None,
+        };
+
+        let mut queued = false;
+        if let Some(dependency) = &dependency {
+            let dependency_hash = dependency.propose_hash;
+            if schema.public.waiting_proposals.contains(&dependency_hash) {
+                let msg = format!(
+                    "Another config proposal already waits for the proposal {:?}",
+                    dependency_hash
+                );
+                return Err(ConfigurationError::ConfigProposeExists.with_description(msg));
+            }
+            let is_pending = schema
+                .public
+                .pending_proposal
+                .get()
+                .map_or(false, |entry| entry.propose_hash == dependency_hash);
+            if is_pending && schema.public.queued_proposal.exists() {
+                let msg = format!(
+                    "Config proposal is queued behind the proposal {:?}",
+                    dependency_hash
+                );
+                return Err(ConfigurationError::ConfigProposeExists.with_description(msg));
+            }
+            if propose.actual_from <= dependency.config_propose.actual_from {
+                let msg = format!(
+                    "Dependent config proposal should be activated after the height {} \
+                     of the proposal it depends on",
+                    dependency.config_propose.actual_from
+                );
+                return Err(ConfigurationError::MalformedConfigPropose.with_description(msg));
+            }
+
+            // Consensus config patches are applied on top of the config of the dependency.
+            if let Some(config) = scheduled_consensus_config(&dependency.config_propose) {
+                base_config = config;
+            }
+        } else if let Some(proposal) = schema.public.pending_proposal.get() {
+            // Verifies that the pending change is already approved, so that the new
+            // proposal can be queued behind it.
             // We have a proposal, check that it's actual.
             if current_height < proposal.config_propose.actual_from {
-                return Err(ConfigurationError::ConfigProposeExists.into());
+                let approved = schema.config_approved(&proposal.propose_hash, &validator_keys);
+                if !approved
+                    || schema.public.queued_proposal.exists()
+                    || schema
+                        .public
+                        .waiting_proposals
+                        .contains(&proposal.propose_hash)
+                {
+                    let msg = format!(
+                        "Config proposal {:?} is pending until height {}",
+                        proposal.propose_hash, proposal.config_propose.actual_from
+                    );
+                    return Err(ConfigurationError::ConfigProposeExists.with_description(msg));
+                }
+                if propose.actual_from <= proposal.config_propose.actual_from {
+                    let msg = format!(
+                        "Queued config proposal should be activated after the height {} \
+                         of the scheduled proposal",
+                        proposal.config_propose.actual_from
+                    );
+                    return Err(ConfigurationError::MalformedConfigPropose.with_description(msg));
+                }
+
+                // Consensus config patches are applied on top of the scheduled config.
+                if let Some(config) = scheduled_consensus_config(&proposal.config_propose) {
+                    base_config = config;
+                }
+                queued = true;
             } else {
                 // Proposal is outdated but was not removed (e.g. because of the panic
                 // during config applying), clean it.
-                schema.public.pending_proposal.remove();
+                schema.remove_pending_proposal(ProposalState::Expired);
             }
         }
         drop(schema);
 
+        // Replace consensus config patches with the patched configs, so that the proposal
+        // hash commits to the exact consensus config to be applied.
+        propose.resolve_patches(&base_config).map_err(|e| {
+            let msg = format!("Cannot apply consensus config patch: {}", e);
+            ConfigurationError::MalformedConfigPropose.with_description(msg)
+        })?;
+
         // Verify changes in the proposal.
-        self.verify_config_changeset(&mut context, &propose.changes)?;
+        self.verify_config_changeset(&mut context, &propose.changes, &base_config, author)?;
         let mut schema = SchemaImpl::new(context.service_data());
 
         // After all the checks verify that configuration number is expected one.
-        if propose.configuration_number != schema.get_configuration_number() {
-            return Err(ConfigurationError::IncorrectConfigurationNumber.into());
+        let expected_number = schema.get_configuration_number();
+        if propose.configuration_number != expected_number {
+            let msg = format!(
+                "Expected configuration number {}, got {}",
+                expected_number, propose.configuration_number
+            );
+            return Err(ConfigurationError::IncorrectConfigurationNumber.with_description(msg));
         }
         schema.increase_configuration_number();
 
         let propose_hash = propose.object_hash();
-        schema.config_confirms.confirm(&propose_hash, author);
+        schema.confirm_config(&propose_hash, author, tx_hash, &validator_keys);
+        schema.record_vote(
+            author,
+            VoteRecord {
+                propose_hash,
+                kind: VoteKind::Confirm,
+                height,
+                tx_hash,
+            },
+        );
+        schema.proposal_authors.put(&propose_hash, author);
+        let record = ProposalRecord {
+            config_propose: propose.clone(),
+            height,
+            tx_hash,
+        };
+        schema.public.proposals.put(&propose_hash, record);
+        schema.record_proposal(author, propose_hash);
 
         let config_entry = ConfigProposalWithHash {
             config_propose: propose,
             propose_hash,
         };
-        schema.public.pending_proposal.set(config_entry);
+        if let Some(dependency) = dependency {
+            log::trace!(
+                "Config proposal {:?} waits for the proposal {:?}",
+                propose_hash,
+                dependency.propose_hash
+            );
+            schema
+                .public
+                .waiting_proposals
+                .put(&dependency.propose_hash, config_entry);
+            let state = schema.voting_state(&propose_hash);
+            schema.public.proposal_states.put(&propose_hash, state);
+        } else if queued {
+            log::trace!("Config proposal {:?} has been queued", propose_hash);
+            schema.public.queued_proposal.set(config_entry);
+            schema
+                .public
+                .proposal_states
+                .put(&propose_hash, ProposalState::Queued);
+        } else {
+            schema.public.pending_proposal.set(config_entry);
+            let state = schema.voting_state(&propose_hash);
+            schema.public.proposal_states.put(&propose_hash, state);
+        }
 
         Ok(())
     }
 
     fn confirm_config_change(&self, context: CallContext<'_>, vote: ConfigVote) -> Self::Output {
-        let author = get_validator(&context)?;
+        let author = get_config_voter(&context)?;
+        let (vote_height, tx_hash) = call_location(&context);
+        let core_schema = context.data().for_core();
+        let height = core_schema.height();
+        let validator_keys = core_schema.consensus_config().validator_keys;
+        let mut schema = SchemaImpl::new(context.service_data());
+        check_vote(&schema, &vote, &author, height)?;
+
+        schema.confirm_config(&vote.propose_hash, author, tx_hash, &validator_keys);
+        schema.record_vote(
+            author,
+            VoteRecord {
+                propose_hash: vote.propose_hash,
+                kind: VoteKind::Confirm,
+                height: vote_height,
+                tx_hash,
+            },
+        );
+        log::trace!(
+            "Propose config {:?} has been confirmed by {:?}",
+            vote.propose_hash,
+            author
+        );
 
+        Ok(())
+    }
+
+    fn reject_config_change(&self, context: CallContext<'_>, vote: ConfigVote) -> Self::Output {
+        let author = get_config_voter(&context)?;
+        let (vote_height, tx_hash) = call_location(&context);
         let core_schema = context.data().for_core();
+        let height = core_schema.height();
+        let validator_keys = core_schema.consensus_config().validator_keys;
         let mut schema = SchemaImpl::new(context.service_data());
-        let entry = schema
-            .public
-            .pending_proposal
-            .get()
-            .ok_or(ConfigurationError::ConfigProposeNotRegistered)?;
+        check_vote(&schema, &vote, &author, height)?;
+
+        schema.config_rejections.confirm(&vote.propose_hash, author);
+        schema.record_vote(
+            author,
+            VoteRecord {
+                propose_hash: vote.propose_hash,
+                kind: VoteKind::Reject,
+                height: vote_height,
+                tx_hash,
+            },
+        );
+        log::trace!(
+            "Propose config {:?} has been rejected by {:?}",
+            vote.propose_hash,
+            author
+        );
 
-        // Verifies that this config proposal is registered.
-        if entry.propose_hash != vote.propose_hash {
-            return Err(ConfigurationError::ConfigProposeNotRegistered.into());
+        if schema.config_rejected(&vote.propose_hash, &validator_keys) {
+            log::info!("Propose config {:?} has been rejected", vote.propose_hash);
+            schema.remove_proposal(&vote.propose_hash, ProposalState::Rejected);
         }
+        Ok(())
+    }
 
-        let config_propose = entry.config_propose;
-        // Verifies that we didn't reach the deadline height.
-        if config_propose.actual_from <= core_schema.height() {
-            return Err(SupervisorCommonError::DeadlineExceeded.into());
+    fn cancel_config_change(&self, context: CallContext<'_>, vote: ConfigVote) -> Self::Output {
+        let author = get_config_voter(&context)?;
+        let height = context.data().for_core().height();
+        let mut schema = SchemaImpl::new(context.service_data());
+        get_pending_proposal(&schema, &vote.propose_hash, height)?;
+        if schema.proposal_authors.get(&vote.propose_hash) != Some(author) {
+            let msg = "Config proposal can only be cancelled by its author";
+            return Err(CommonError::UnauthorizedCaller.with_description(msg));
         }
+
+        log::info!(
+            "Propose config {:?} has been cancelled by {:?}",
+            vote.propose_hash,
+            author
+        );
+        schema.remove_proposal(&vote.propose_hash, ProposalState::Cancelled);
+        Ok(())
+    }
+
+    fn revoke_config_vote(&self, context: CallContext<'_>, vote: ConfigVote) -> Self::Output {
+        let author = get_config_voter(&context)?;
+        let (vote_height, tx_hash) = call_location(&context);
+        let core_schema = context.data().for_core();
+        let height = core_schema.height();
+        let validator_keys = core_schema.consensus_config().validator_keys;
+        let mut schema = SchemaImpl::new(context.service_data());
+        get_pending_proposal(&schema, &vote.propose_hash, height)?;
+
         if schema
             .config_confirms
-            .confirmed_by(&entry.propose_hash, &author)
+            .confirmed_by(&vote.propose_hash, &author)
         {
-            return Err(ConfigurationError::AttemptToVoteTwice.into());
+            schema.revoke_config_confirmation(&vote.propose_hash, &author, &validator_keys);
+        } else if schema
+            .config_rejections
+            .confirmed_by(&vote.propose_hash, &author)
+        {
+            schema.config_rejections.revoke(&vote.propose_hash, &author);
+        } else {
+            return Err(ConfigurationError::NoVoteToRevoke.into());
         }
+        schema.record_vote(
+            author,
+            VoteRecord {
+                propose_hash: vote.propose_hash,
+                kind: VoteKind::Revoke,
+                height: vote_height,
+                tx_hash,
+            },
+        );
 
-        schema.config_confirms.confirm(&vote.propose_hash, author);
         log::trace!(
-            "Propose config {:?} has been confirmed by {:?}",
+            "Vote for propose config {:?} has been revoked by {:?}",
             vote.propose_hash,
             author
         );
+        Ok(())
+    }
+
+    fn delegate_config_key(
+        &self,
+        context: CallContext<'_>,
+        request: DelegateConfigKey,
+    ) -> Self::Output {
+        let author = get_validator(&context)?;
+        let core_schema = context.data().for_core();
+        let mut schema = SchemaImpl::new(context.service_data());
 
+        if request.delegate == author {
+            schema.public.config_key_delegates.remove(&author);
+            log::trace!("Config key delegation has been removed by {:?}", author);
+            return Ok(());
+        }
+
+        if core_schema.validator_id(request.delegate).is_some() {
+            let msg = "Delegate key belongs to a validator";
+            return Err(ConfigurationError::InvalidDelegate.with_description(msg));
+        }
+        let used_by_other = schema
+            .public
+            .config_key_delegates
+            .iter()
+            .any(|(validator, delegate)| validator != author && delegate == request.delegate);
+        if used_by_other {
+            let msg = "Delegate key is already used by another validator";
+            return Err(ConfigurationError::InvalidDelegate.with_description(msg));
+        }
+
+        schema
+            .public
+            .config_key_delegates
+            .put(&author, request.delegate);
+        log::trace!(
+            "Config key {:?} has been delegated by {:?}",
+            request.delegate,
+            author
+        );
         Ok(())
     }
 
@@ -363,10 +879,14 @@ impl SupervisorInterface<CallContext<'_>> for Supervisor {
 
 impl Supervisor {
     /// Verifies that each change introduced within config proposal is valid.
+    /// `base_config` is the consensus config replaced by the proposal, which differs
+    /// from the current one for queued and dependent proposals.
     fn verify_config_changeset(
         &self,
         context: &mut CallContext<'_>,
         changes: &[ConfigChange],
+        base_config: &ConsensusConfig,
+        author: PublicKey,
     ) -> Result<(), ExecutionError> {
         // To prevent multiple consensus change proposition in one request
         let mut consensus_propose_added = false;
@@ -375,6 +895,9 @@ impl Supervisor {
         // To prevent multiple services start in one request.
         let mut services_to_start = HashSet::new();
 
+        let current_config = context.data().for_core().consensus_config();
+        let supervisor_config = SchemaImpl::new(context.service_data()).supervisor_config();
+
         // Perform config verification.
         for change in changes {
             match change {
@@ -388,6 +911,17 @@ impl Supervisor {
                     config.validate().map_err(|e| {
                         ConfigurationError::MalformedConfigPropose.with_description(e)
                     })?;
+                    if let Some(limits) = &supervisor_config.consensus_limits {
+                        limits.check(config).map_err(|e| {
+                            ConfigurationError::UnsafeConsensusConfig.with_description(e)
+                        })?;
+                    }
+                    supervisor_config
+                        .check_validators_change(base_config, config)
+                        .map_err(|e| {
+                            ConfigurationError::UnsafeConsensusConfig.with_description(e)
+                        })?;
+                    verify_key_rotations(&current_config, config, author)?;
                 }
 
                 ConfigChange::Service(config) => {
@@ -417,6 +951,12 @@ impl Supervisor {
                     }
                     stop_service.validate(&context)?;
                 }
+
+                ConfigChange::ConsensusPatch(_) => {
+                    unreachable!(
+                        "BUG: Consensus config patches are resolved before the verification."
+                    );
+                }
             }
         }
         Ok(())
@@ -0,0 +1,60 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum_derive::*;
+use exonum_proto::ProtobufConvert;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::proto as pb_supervisor;
+
+/// Kind of the vote cast by a validator for a configuration proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(BinaryValue, ObjectHash)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteKind {
+    /// Validator voted for the proposal.
+    Confirm,
+    /// Validator voted against the proposal.
+    Reject,
+    /// Validator revoked the previously cast vote.
+    Revoke,
+}
+
+impl ProtobufConvert for VoteKind {
+    type ProtoStruct = pb_supervisor::VoteKind;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        use pb_supervisor::VoteKind_Type::*;
+
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_kind(match self {
+            VoteKind::Confirm => CONFIRM,
+            VoteKind::Reject => REJECT,
+            VoteKind::Revoke => REVOKE,
+        });
+        pb
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
+        use pb_supervisor::VoteKind_Type::*;
+
+        let kind = match pb.get_kind() {
+            CONFIRM => VoteKind::Confirm,
+            REJECT => VoteKind::Reject,
+            REVOKE => VoteKind::Revoke,
+        };
+        Ok(kind)
+    }
+}
@@ -12,20 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{TimeZone, Utc};
 use exonum::{
-    blockchain::CallInBlock,
-    crypto,
+    blockchain::{CallInBlock, ValidatorKeys},
+    crypto::{self, Hash},
     helpers::{Height, ValidatorId},
-    merkledb::ObjectHash,
+    keys::Keys,
+    merkledb::{access::AccessExt, ObjectHash, ProofMapIndex},
     runtime::{CommonError, ErrorMatch, InstanceId, SnapshotExt, SUPERVISOR_INSTANCE_ID},
 };
 use exonum_rust_runtime::ServiceFactory;
-use exonum_testkit::TestKitBuilder;
+use exonum_testkit::{TestKit, TestKitBuilder};
+use exonum_time::{time_provider::MockTimeProvider, TimeServiceFactory};
 
 use crate::{utils::*, IncService as ConfigChangeService};
 use exonum_supervisor::{
-    CommonError as SupervisorCommonError, ConfigVote, ConfigurationError, Supervisor,
-    SupervisorInterface,
+    mode::Mode, supervisor_name, CommonError as SupervisorCommonError, ConfigPropose, ConfigVote,
+    ConfigurationError, DelegateConfigKey, ProposalState, Schema, Supervisor, SupervisorConfig,
+    SupervisorInterface, ValidatorWeight,
 };
 
 #[test]
@@ -436,6 +440,7 @@ fn test_incorrect_actual_from_field() {
     assert_eq!(
         *err,
         ErrorMatch::from_fail(&SupervisorCommonError::ActualFromIsPast)
+            .with_description_containing("is not greater than the current height")
             .for_service(SUPERVISOR_INSTANCE_ID)
     );
 }
@@ -475,6 +480,7 @@ fn test_another_configuration_change_proposal() {
     assert_eq!(
         *err,
         ErrorMatch::from_fail(&ConfigurationError::ConfigProposeExists)
+            .with_description_containing("is pending until height")
             .for_service(SUPERVISOR_INSTANCE_ID)
     );
 
@@ -799,6 +805,7 @@ fn test_discard_incorrect_configuration_number() {
     assert_eq!(
         *err,
         ErrorMatch::from_fail(&ConfigurationError::IncorrectConfigurationNumber)
+            .with_description_containing("Expected configuration number")
             .for_service(SUPERVISOR_INSTANCE_ID)
     );
     assert_eq!(config_propose_entry(&testkit), None);
@@ -852,6 +859,7 @@ fn test_discard_incorrect_configuration_number() {
     assert_eq!(
         *err,
         ErrorMatch::from_fail(&ConfigurationError::IncorrectConfigurationNumber)
+            .with_description_containing("Expected configuration number")
             .for_service(SUPERVISOR_INSTANCE_ID)
     );
     assert_eq!(config_propose_entry(&testkit), None);
@@ -906,3 +914,1005 @@ fn test_all_changes_are_discarded_on_panic() {
         assert_eq!(testkit.consensus_config(), old_consensus_config);
     }
 }
+
+#[test]
+fn test_config_activation_by_time() {
+    const TIME_SERVICE_ID: InstanceId = 112;
+    const TIME_SERVICE_NAME: &str = "clock";
+
+    let mock_provider = MockTimeProvider::new(Utc.timestamp(0, 0));
+    let time_service = TimeServiceFactory::with_provider(mock_provider.clone());
+    let time_artifact = time_service.artifact_id();
+    let supervisor_config = SupervisorConfig {
+        time_service: Some(TIME_SERVICE_NAME.to_owned()),
+        ..Supervisor::simple_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .with_artifact(time_artifact.clone())
+        .with_instance(time_artifact.into_default_instance(TIME_SERVICE_ID, TIME_SERVICE_NAME))
+        .with_rust_service(time_service)
+        .create();
+
+    let activation_time = Utc.timestamp(100, 0);
+    let deadline_height = Height(10);
+    let new_consensus_config = consensus_config_propose_first_variant(&testkit);
+    let propose = ConfigProposeBuilder::new(deadline_height)
+        .extend_consensus_config_propose(new_consensus_config.clone())
+        .build()
+        .activate_at_time(activation_time);
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            propose,
+            ValidatorId(0),
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The config is not applied until the time oracle reaches the activation time.
+    mock_provider.set_time(Utc.timestamp(50, 0));
+    testkit.create_blocks_until(Height(4));
+    assert!(config_propose_entry(&testkit).is_some());
+    assert_ne!(testkit.consensus_config(), new_consensus_config);
+
+    mock_provider.set_time(activation_time);
+    testkit.create_blocks_until(Height(7));
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), new_consensus_config);
+}
+
+#[test]
+fn test_config_activation_by_time_with_activation_delay() {
+    const TIME_SERVICE_ID: InstanceId = 112;
+    const TIME_SERVICE_NAME: &str = "clock";
+
+    // The time oracle reaches the activation time before the proposal is accepted.
+    let activation_time = Utc.timestamp(100, 0);
+    let mock_provider = MockTimeProvider::new(activation_time);
+    let time_service = TimeServiceFactory::with_provider(mock_provider);
+    let time_artifact = time_service.artifact_id();
+    let supervisor_config = SupervisorConfig {
+        time_service: Some(TIME_SERVICE_NAME.to_owned()),
+        min_activation_delay: 5,
+        ..Supervisor::simple_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .with_artifact(time_artifact.clone())
+        .with_instance(time_artifact.into_default_instance(TIME_SERVICE_ID, TIME_SERVICE_NAME))
+        .with_rust_service(time_service)
+        .create();
+
+    let new_consensus_config = consensus_config_propose_first_variant(&testkit);
+    let propose = ConfigProposeBuilder::new(Height(10))
+        .extend_consensus_config_propose(new_consensus_config.clone())
+        .build()
+        .activate_at_time(activation_time);
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            propose,
+            ValidatorId(0),
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The proposal is accepted in the block 1, so it cannot be applied before the height 6.
+    testkit.create_blocks_until(Height(5));
+    assert!(config_propose_entry(&testkit).is_some());
+    assert_ne!(testkit.consensus_config(), new_consensus_config);
+
+    testkit.create_block();
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), new_consensus_config);
+}
+
+#[test]
+fn test_config_activation_by_time_with_unknown_oracle() {
+    // The time oracle is not specified in the supervisor config.
+    let mut testkit = testkit_with_supervisor(1);
+
+    let propose = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build()
+        .activate_at_time(Utc.timestamp(100, 0));
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnknownTimeOracle)
+            .with_description_containing("not specified in the supervisor config")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_eq!(config_propose_entry(&testkit), None);
+
+    // The time oracle specified in the supervisor config does not exist.
+    let supervisor_config = SupervisorConfig {
+        time_service: Some("unknown-time".to_owned()),
+        ..Supervisor::simple_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+
+    let propose = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build()
+        .activate_at_time(Utc.timestamp(100, 0));
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnknownTimeOracle)
+            .with_description_containing("`unknown-time` does not exist")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_eq!(config_propose_entry(&testkit), None);
+}
+
+#[test]
+fn test_reject_config_by_majority() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+    let base_consensus_config = testkit.consensus_config();
+
+    let consensus_config = consensus_config_propose_first_variant(&testkit);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config)
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let vote = ConfigVote {
+        propose_hash: proposal_hash,
+    };
+    let rejections: Vec<_> = testkit.network().validators()[1..]
+        .iter()
+        .map(|validator| {
+            validator
+                .service_keypair()
+                .reject_config_change(SUPERVISOR_INSTANCE_ID, vote.clone())
+        })
+        .collect();
+
+    // Two rejections are not enough to discard the proposal.
+    let block = testkit.create_block_with_transactions(rejections[..2].to_vec());
+    block.transactions[0].status().unwrap();
+    block.transactions[1].status().unwrap();
+    assert!(config_propose_entry(&testkit).is_some());
+
+    // The third rejection forms a byzantine majority.
+    testkit
+        .create_block_with_transaction(rejections[2].clone())
+        .transactions[0]
+        .status()
+        .expect("Transaction with rejection discarded.");
+    assert_eq!(config_propose_entry(&testkit), None);
+
+    // Rejected proposal cannot be confirmed anymore.
+    let keys = testkit.network().validators()[0].service_keypair();
+    let confirm = keys.confirm_config_change(SUPERVISOR_INSTANCE_ID, vote);
+    let block = testkit.create_block_with_transaction(confirm);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::ConfigProposeNotRegistered)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT.next());
+    assert_eq!(testkit.consensus_config(), base_consensus_config);
+}
+
+#[test]
+fn test_reject_config_after_confirmation() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The proposer has already confirmed the proposal and cannot reject it.
+    let keys = testkit.network().validators()[0].service_keypair();
+    let reject = keys.reject_config_change(
+        SUPERVISOR_INSTANCE_ID,
+        ConfigVote {
+            propose_hash: proposal_hash,
+        },
+    );
+    let block = testkit.create_block_with_transaction(reject);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::AttemptToVoteTwice)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert!(config_propose_entry(&testkit).is_some());
+}
+
+#[test]
+fn test_apply_config_with_custom_majority_count() {
+    let supervisor_config = SupervisorConfig {
+        mode: Mode::Decentralized,
+        majority_count: Some(4),
+        min_activation_delay: 0,
+        consensus_limits: None,
+        max_removed_validators_percent: None,
+        vote_weights: Vec::new(),
+        time_service: None,
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let consensus_config = consensus_config_propose_first_variant(&testkit);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config.clone())
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let vote = ConfigVote {
+        propose_hash: proposal_hash,
+    };
+    let confirmations: Vec<_> = testkit.network().validators()[1..]
+        .iter()
+        .map(|validator| {
+            validator
+                .service_keypair()
+                .confirm_config_change(SUPERVISOR_INSTANCE_ID, vote.clone())
+        })
+        .collect();
+
+    // The byzantine majority of votes is not enough to apply the config.
+    testkit.create_block_with_transactions(confirmations[..2].to_vec());
+    assert!(config_propose_entry(&testkit).is_some());
+    assert_ne!(testkit.consensus_config(), consensus_config);
+
+    // The last vote makes the decision unanimous.
+    testkit
+        .create_block_with_transaction(confirmations[2].clone())
+        .transactions[0]
+        .status()
+        .expect("Transaction with confirmation discarded.");
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), consensus_config);
+}
+
+#[test]
+fn test_weighted_config_approval() {
+    let keys: Vec<_> = (0..4)
+        .map(|_| {
+            let (consensus_pk, consensus_sk) = crypto::gen_keypair();
+            let (service_pk, service_sk) = crypto::gen_keypair();
+            Keys::from_keys(consensus_pk, consensus_sk, service_pk, service_sk)
+        })
+        .collect();
+    // The second validator has more than a half of the total weight.
+    let weights = [1, 6, 1, 1];
+    let supervisor_config = SupervisorConfig {
+        vote_weights: keys
+            .iter()
+            .zip(&weights)
+            .map(|(keys, &weight)| ValidatorWeight {
+                service_key: keys.service_pk(),
+                weight,
+            })
+            .collect(),
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_keys(keys)
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let consensus_config = consensus_config_propose_first_variant(&testkit);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config.clone())
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let vote = ConfigVote {
+        propose_hash: proposal_hash,
+    };
+    let confirm = |testkit: &TestKit, index: usize| {
+        testkit.network().validators()[index]
+            .service_keypair()
+            .confirm_config_change(SUPERVISOR_INSTANCE_ID, vote.clone())
+    };
+
+    // The byzantine majority of validators has only 3 of 9 weight units.
+    let confirmations = vec![confirm(&testkit, 2), confirm(&testkit, 3)];
+    testkit.create_block_with_transactions(confirmations);
+    assert!(config_propose_entry(&testkit).is_some());
+    assert_ne!(testkit.consensus_config(), consensus_config);
+
+    // The vote of the heavy validator makes the proposal approved.
+    let confirmation = confirm(&testkit, 1);
+    testkit
+        .create_block_with_transaction(confirmation)
+        .transactions[0]
+        .status()
+        .expect("Transaction with confirmation discarded.");
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), consensus_config);
+}
+
+#[test]
+fn test_weighted_config_without_weighted_validators() {
+    let (consensus_pk, consensus_sk) = crypto::gen_keypair();
+    let (service_pk, service_sk) = crypto::gen_keypair();
+    let keys = Keys::from_keys(consensus_pk, consensus_sk, service_pk, service_sk);
+    let supervisor_config = SupervisorConfig {
+        vote_weights: vec![ValidatorWeight {
+            service_key: keys.service_pk(),
+            weight: 1,
+        }],
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_keys(vec![keys])
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+
+    // None of the new validators has a vote weight, so no proposal could be approved
+    // after the change.
+    let mut consensus_config = testkit.consensus_config();
+    consensus_config.validator_keys[0] = ValidatorKeys {
+        consensus_key: crypto::gen_keypair().0,
+        service_key: crypto::gen_keypair().0,
+    };
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config)
+        .build();
+    let signed_proposal =
+        sign_config_propose_transaction(&testkit, config_proposal, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnsafeConsensusConfig)
+            .with_description_containing("should have a positive vote weight")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_eq!(config_propose_entry(&testkit), None);
+}
+
+#[test]
+fn test_cancel_config_proposal() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+    let base_consensus_config = testkit.consensus_config();
+
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let vote = ConfigVote {
+        propose_hash: proposal_hash,
+    };
+
+    // Only the proposal author can cancel it.
+    let keys = testkit.network().validators()[1].service_keypair();
+    let cancel = keys.cancel_config_change(SUPERVISOR_INSTANCE_ID, vote.clone());
+    let block = testkit.create_block_with_transaction(cancel);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&CommonError::UnauthorizedCaller)
+            .with_description_containing("can only be cancelled by its author")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert!(config_propose_entry(&testkit).is_some());
+
+    let keys = testkit.network().validators()[initiator_id.0 as usize].service_keypair();
+    let cancel = keys.cancel_config_change(SUPERVISOR_INSTANCE_ID, vote.clone());
+    testkit.create_block_with_transaction(cancel).transactions[0]
+        .status()
+        .expect("Transaction with cancellation discarded.");
+    assert_eq!(config_propose_entry(&testkit), None);
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    assert_eq!(
+        schema.proposal_states.get(&proposal_hash),
+        Some(ProposalState::Cancelled)
+    );
+
+    // Cancelled proposal cannot be voted for.
+    let keys = testkit.network().validators()[1].service_keypair();
+    let confirm = keys.confirm_config_change(SUPERVISOR_INSTANCE_ID, vote);
+    let block = testkit.create_block_with_transaction(confirm);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::ConfigProposeNotRegistered)
+            .with_description_containing("cancelled")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT.next());
+    assert_eq!(testkit.consensus_config(), base_consensus_config);
+}
+
+#[test]
+fn test_queue_proposal_behind_approved_one() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let first_height = Height(5);
+    let first_config = consensus_config_propose_first_variant(&testkit);
+    let first_proposal = ConfigProposeBuilder::new(first_height)
+        .extend_consensus_config_propose(first_config.clone())
+        .build();
+    let first_hash = first_proposal.object_hash();
+    let signed_proposal = sign_config_propose_transaction(&testkit, first_proposal, initiator_id);
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+    let signed_txs = build_confirmation_transactions(&testkit, first_hash, initiator_id);
+    testkit.create_block_with_transactions(signed_txs);
+
+    // The first proposal is approved, so the next one is queued behind it.
+    // Consensus config patch is applied on top of the scheduled config.
+    let second_height = Height(8);
+    let second_proposal =
+        ConfigPropose::new(1, second_height).consensus_config_patch(r#"{ "txs_block_limit": 42 }"#);
+    let mut second_config = first_config.clone();
+    second_config.txs_block_limit = 42;
+    let second_hash = ConfigPropose::new(1, second_height)
+        .consensus_config(second_config.clone())
+        .object_hash();
+    let signed_proposal = sign_config_propose_transaction(&testkit, second_proposal, initiator_id);
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    assert_eq!(
+        schema.queued_proposal.get().unwrap().propose_hash,
+        second_hash
+    );
+    assert_eq!(
+        schema.proposal_states.get(&second_hash),
+        Some(ProposalState::Queued)
+    );
+
+    // Votes for the queued proposal are not accepted yet.
+    let keys = testkit.network().validators()[1].service_keypair();
+    let vote = ConfigVote {
+        propose_hash: second_hash,
+    };
+    let confirm = keys.confirm_config_change(SUPERVISOR_INSTANCE_ID, vote);
+    let block = testkit.create_block_with_transaction(confirm);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::ConfigProposeNotRegistered)
+            .with_description_containing("queued")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Once the first proposal is applied, the queued one becomes pending.
+    testkit.create_blocks_until(first_height);
+    assert_eq!(testkit.consensus_config(), first_config);
+    let pending_proposal = config_propose_entry(&testkit).unwrap();
+    assert_eq!(pending_proposal.object_hash(), second_hash);
+
+    let signed_txs = build_confirmation_transactions(&testkit, second_hash, initiator_id);
+    testkit.create_block_with_transactions(signed_txs);
+    testkit.create_blocks_until(second_height);
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), second_config);
+}
+
+#[test]
+fn test_revoke_config_vote() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+    let base_consensus_config = testkit.consensus_config();
+
+    let cfg_change_height = Height(5);
+    let config_proposal = ConfigProposeBuilder::new(cfg_change_height)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let vote = ConfigVote {
+        propose_hash: proposal_hash,
+    };
+    let keys = testkit.network().validators()[1].service_keypair();
+
+    // Nothing to revoke yet.
+    let revoke = keys.revoke_config_vote(SUPERVISOR_INSTANCE_ID, vote.clone());
+    let block = testkit.create_block_with_transaction(revoke);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::NoVoteToRevoke)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // The validator confirms the proposal and then revokes the confirmation.
+    let confirm = keys.confirm_config_change(SUPERVISOR_INSTANCE_ID, vote.clone());
+    let revoke = keys.revoke_config_vote(SUPERVISOR_INSTANCE_ID, vote.clone());
+    let block = testkit.create_block_with_transactions(vec![confirm, revoke]);
+    block.transactions[0].status().unwrap();
+    block.transactions[1].status().unwrap();
+
+    // After revocation, the validator may vote again, e.g., against the proposal.
+    let reject = keys.reject_config_change(SUPERVISOR_INSTANCE_ID, vote);
+    testkit.create_block_with_transaction(reject).transactions[0]
+        .status()
+        .expect("Transaction with rejection discarded.");
+
+    // The proposal is not approved by the byzantine majority.
+    testkit.create_blocks_until(cfg_change_height.next());
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), base_consensus_config);
+}
+
+#[test]
+fn test_min_activation_delay() {
+    let supervisor_config = SupervisorConfig {
+        min_activation_delay: 5,
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    // The proposal is accepted in the block 1, so it cannot be applied before the height 6.
+    let config_proposal = ConfigProposeBuilder::new(Height(5))
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let block = testkit.create_block_with_transaction(sign_config_propose_transaction(
+        &testkit,
+        config_proposal,
+        initiator_id,
+    ));
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::ActivationTooEarly)
+            .with_description_containing("should not be less than 6")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_eq!(config_propose_entry(&testkit), None);
+
+    // If `actual_from` is not specified, the earliest allowed height is used.
+    let config_proposal = ConfigProposeBuilder::new(Height(0))
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+    let pending_proposal = config_propose_entry(&testkit).unwrap();
+    assert_eq!(pending_proposal.actual_from, Height(7));
+}
+
+#[test]
+fn test_prune_votes_for_final_proposals() {
+    let mut testkit = testkit_with_supervisor(2);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let has_votes = |testkit: &TestKit| {
+        let snapshot = testkit.snapshot();
+        let confirms: ProofMapIndex<_, Hash, Vec<u8>> =
+            snapshot.get_proof_map("supervisor.config_confirms");
+        confirms.contains(&proposal_hash)
+    };
+    assert!(has_votes(&testkit));
+
+    // Votes for the expired proposal are pruned, but the proposal itself is kept.
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT.next());
+    assert!(!has_votes(&testkit));
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    assert!(schema.proposals.contains(&proposal_hash));
+    assert_eq!(
+        schema.proposal_states.get(&proposal_hash),
+        Some(ProposalState::Expired)
+    );
+}
+
+#[test]
+fn test_delegated_config_key() {
+    let mut testkit = testkit_with_supervisor(2);
+    let validator_keys = testkit.network().validators()[1].service_keypair();
+    let delegate_keys = crypto::gen_keypair();
+    let cfg_change_height = Height(10);
+
+    // Keys without delegation cannot propose config changes.
+    let config_proposal = ConfigProposeBuilder::new(cfg_change_height)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+    let signed_proposal = config_proposal
+        .clone()
+        .sign_for_supervisor(delegate_keys.0, &delegate_keys.1);
+    let block = testkit.create_block_with_transaction(signed_proposal.clone());
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&CommonError::UnauthorizedCaller).for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Keys of other validators cannot be used as delegates.
+    let other_validator_key = testkit.network().validators()[0].service_keypair().0;
+    let delegation = validator_keys.delegate_config_key(
+        SUPERVISOR_INSTANCE_ID,
+        DelegateConfigKey {
+            delegate: other_validator_key,
+        },
+    );
+    let block = testkit.create_block_with_transaction(delegation);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::InvalidDelegate)
+            .with_description_containing("belongs to a validator")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Once delegated, the key proposes the change on behalf of the validator.
+    let delegation = validator_keys.delegate_config_key(
+        SUPERVISOR_INSTANCE_ID,
+        DelegateConfigKey {
+            delegate: delegate_keys.0,
+        },
+    );
+    testkit
+        .create_block_with_transaction(delegation)
+        .transactions[0]
+        .status()
+        .unwrap();
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    block.transactions[0].status().unwrap();
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    assert_eq!(
+        schema.proposals_by_author.get(&validator_keys.0).last(),
+        Some(proposal_hash)
+    );
+
+    // The validator itself cannot vote once more, since the delegate has voted for it.
+    let vote = ConfigVote {
+        propose_hash: proposal_hash,
+    };
+    let confirm = validator_keys.confirm_config_change(SUPERVISOR_INSTANCE_ID, vote.clone());
+    let block = testkit.create_block_with_transaction(confirm);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::AttemptToVoteTwice)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Removal of the delegation revokes the rights of the delegate key.
+    let removal = validator_keys.delegate_config_key(
+        SUPERVISOR_INSTANCE_ID,
+        DelegateConfigKey {
+            delegate: validator_keys.0,
+        },
+    );
+    testkit.create_block_with_transaction(removal).transactions[0]
+        .status()
+        .unwrap();
+    let reject = delegate_keys.reject_config_change(SUPERVISOR_INSTANCE_ID, vote);
+    let block = testkit.create_block_with_transaction(reject);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&CommonError::UnauthorizedCaller).for_service(SUPERVISOR_INSTANCE_ID)
+    );
+}
+
+#[test]
+fn test_dependent_config_proposal() {
+    let mut testkit = testkit_with_supervisor(2);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+    let first_config = consensus_config_propose_first_variant(&testkit);
+    let second_config = consensus_config_propose_second_variant(&testkit);
+
+    // Proposals cannot depend on unknown proposals.
+    let config_proposal = ConfigProposeBuilder::new(Height(10))
+        .extend_consensus_config_propose(second_config.clone())
+        .build()
+        .after_proposal(crypto::hash(b"unknown"));
+    let block = testkit.create_block_with_transaction(sign_config_propose_transaction(
+        &testkit,
+        config_proposal,
+        initiator_id,
+    ));
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::InvalidDependency)
+            .with_description_containing("is unknown")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    let cfg_change_height = Height(5);
+    let first_proposal = ConfigProposeBuilder::new(cfg_change_height)
+        .extend_consensus_config_propose(first_config.clone())
+        .build();
+    let first_hash = first_proposal.object_hash();
+    let second_proposal = ConfigProposeBuilder::new(Height(10))
+        .extend_consensus_config_propose(second_config.clone())
+        .configuration_number(1)
+        .build()
+        .after_proposal(first_hash);
+    let second_hash = second_proposal.object_hash();
+
+    // The dependent proposal waits for the first one instead of being refused.
+    let transactions = vec![
+        sign_config_propose_transaction(&testkit, first_proposal, initiator_id),
+        sign_config_propose_transaction(&testkit, second_proposal, initiator_id),
+    ];
+    let block = testkit.create_block_with_transactions(transactions);
+    block.transactions[0].status().unwrap();
+    block.transactions[1].status().unwrap();
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    let waiting = schema.waiting_proposals.get(&first_hash).unwrap();
+    assert_eq!(waiting.propose_hash, second_hash);
+
+    // Both proposals are approved, but the dependent one is blocked until the first
+    // proposal is applied.
+    let keys = testkit.network().validators()[1].service_keypair();
+    let confirmations = vec![
+        keys.confirm_config_change(
+            SUPERVISOR_INSTANCE_ID,
+            ConfigVote {
+                propose_hash: second_hash,
+            },
+        ),
+        keys.confirm_config_change(
+            SUPERVISOR_INSTANCE_ID,
+            ConfigVote {
+                propose_hash: first_hash,
+            },
+        ),
+    ];
+    testkit.create_block_with_transactions(confirmations);
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    assert_eq!(
+        schema.proposal_states.get(&second_hash),
+        Some(ProposalState::Approved)
+    );
+
+    testkit.create_blocks_until(cfg_change_height.previous());
+    assert_ne!(testkit.consensus_config(), first_config);
+
+    // Once the first proposal is applied, the dependent proposal becomes pending
+    // and is applied in the next block.
+    testkit.create_block();
+    assert_eq!(testkit.consensus_config(), first_config);
+    assert_eq!(
+        config_propose_entry(&testkit).map(|propose| propose.object_hash()),
+        Some(second_hash)
+    );
+
+    testkit.create_block();
+    assert_eq!(testkit.consensus_config(), second_config);
+    assert_eq!(config_propose_entry(&testkit), None);
+
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema(supervisor_name()).unwrap();
+    assert_eq!(
+        schema.proposal_states.get(&second_hash),
+        Some(ProposalState::Applied)
+    );
+    assert!(schema.waiting_proposals.is_empty());
+}
+
+#[test]
+fn test_dependency_on_applied_proposal() {
+    let mut testkit = testkit_with_supervisor(1);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let first_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let first_hash = first_proposal.object_hash();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            first_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT);
+
+    // Proposals cannot depend on applied proposals, since the dependency would not delay
+    // their activation.
+    let config_proposal = ConfigProposeBuilder::new(Height(10))
+        .extend_consensus_config_propose(consensus_config_propose_second_variant(&testkit))
+        .configuration_number(1)
+        .build()
+        .after_proposal(first_hash);
+    let block = testkit.create_block_with_transaction(sign_config_propose_transaction(
+        &testkit,
+        config_proposal,
+        initiator_id,
+    ));
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::InvalidDependency)
+            .with_description_containing("is already applied")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_eq!(config_propose_entry(&testkit), None);
+}
+
+#[test]
+fn test_dependent_config_proposal_with_activation_delay() {
+    let supervisor_config = SupervisorConfig {
+        min_activation_delay: 5,
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+    let first_config = consensus_config_propose_first_variant(&testkit);
+    let second_config = consensus_config_propose_second_variant(&testkit);
+
+    let first_proposal = ConfigProposeBuilder::new(Height(6))
+        .extend_consensus_config_propose(first_config.clone())
+        .build();
+    let first_hash = first_proposal.object_hash();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            first_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The dependent proposal is accepted in the block 4, so it cannot be applied
+    // before the height 9, even though the first proposal is applied earlier.
+    testkit.create_blocks_until(Height(3));
+    let second_proposal = ConfigProposeBuilder::new(Height(20))
+        .extend_consensus_config_propose(second_config.clone())
+        .configuration_number(1)
+        .build()
+        .after_proposal(first_hash);
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            second_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with dependent change propose discarded.");
+
+    testkit.create_blocks_until(Height(6));
+    assert_eq!(testkit.consensus_config(), first_config);
+    testkit.create_blocks_until(Height(8));
+    assert_eq!(testkit.consensus_config(), first_config);
+    testkit.create_block();
+    assert_eq!(testkit.consensus_config(), second_config);
+}
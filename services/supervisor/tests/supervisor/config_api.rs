@@ -13,14 +13,22 @@
 // limitations under the License.
 
 use exonum::{
-    blockchain::ConsensusConfig, crypto::Hash, helpers::ValidatorId,
-    runtime::SUPERVISOR_INSTANCE_ID,
+    blockchain::ConsensusConfig,
+    crypto::{Hash, PublicKey},
+    helpers::{Height, ValidatorId},
+    runtime::{ErrorMatch, SnapshotExt, SUPERVISOR_INSTANCE_ID},
 };
-use exonum_merkledb::ObjectHash;
+use exonum_merkledb::{ObjectHash, SystemSchema};
+use exonum_rust_runtime::api::Error as ApiError;
 use exonum_testkit::{ApiKind, TestKit, TestKitApi};
 
 use crate::utils::*;
-use exonum_supervisor::{ConfigProposalWithHash, ConfigPropose, ConfigVote, SupervisorInterface};
+use exonum_supervisor::{
+    ActivationRecord, CommonError, ConfigProposalWithHash, ConfigPropose, ConfigVote,
+    ConfigurationError, ProposalAuthorQuery, ProposalInfo, ProposalQuery, ProposalState,
+    ProposalsQuery, Schema, SupervisorInterface, ValidatorVotesQuery, VoteKind, VoteRecord,
+    MAX_PAGE_SIZE,
+};
 
 fn actual_consensus_config(api: &TestKitApi) -> ConsensusConfig {
     api.public(ApiKind::Service("supervisor"))
@@ -52,6 +60,64 @@ fn confirm_config(api: &TestKitApi, confirm: ConfigVote) -> Hash {
     hash
 }
 
+fn proposal_state(api: &TestKitApi, hash: Hash) -> Option<ProposalState> {
+    api.public(ApiKind::Service("supervisor"))
+        .query(&ProposalQuery { hash })
+        .get("proposal-state")
+        .unwrap()
+}
+
+fn proposal_info(api: &TestKitApi, hash: Hash) -> Option<ProposalInfo> {
+    api.public(ApiKind::Service("supervisor"))
+        .query(&ProposalQuery { hash })
+        .get("proposal-info")
+        .unwrap()
+}
+
+fn proposals_by_author(api: &TestKitApi, author: PublicKey) -> Vec<ProposalInfo> {
+    proposals_by_author_page(api, author, 0, None).unwrap()
+}
+
+fn proposals_by_author_page(
+    api: &TestKitApi,
+    author: PublicKey,
+    start: u64,
+    count: Option<u64>,
+) -> Result<Vec<ProposalInfo>, ApiError> {
+    api.public(ApiKind::Service("supervisor"))
+        .query(&ProposalAuthorQuery {
+            author,
+            start,
+            count,
+        })
+        .get("proposals-by-author")
+}
+
+fn votes_by_validator(api: &TestKitApi, validator: PublicKey) -> Vec<VoteRecord> {
+    votes_by_validator_page(api, validator, 0, None).unwrap()
+}
+
+fn votes_by_validator_page(
+    api: &TestKitApi,
+    validator: PublicKey,
+    start: u64,
+    count: Option<u64>,
+) -> Result<Vec<VoteRecord>, ApiError> {
+    api.public(ApiKind::Service("supervisor"))
+        .query(&ValidatorVotesQuery {
+            validator,
+            start,
+            count,
+        })
+        .get("votes-by-validator")
+}
+
+fn activation_log(api: &TestKitApi) -> Vec<ActivationRecord> {
+    api.public(ApiKind::Service("supervisor"))
+        .get("activation-log")
+        .unwrap()
+}
+
 fn configuration_number(api: &TestKitApi) -> u64 {
     api.private(ApiKind::Service("supervisor"))
         .get("configuration-number")
@@ -150,6 +216,53 @@ fn test_send_proposal_with_api() {
     testkit.create_blocks_until(CFG_CHANGE_HEIGHT.next());
     let consensus_config = actual_consensus_config(&testkit.api());
     assert_eq!(consensus_proposal, consensus_config);
+    assert_eq!(
+        proposal_state(&testkit.api(), proposal_hash),
+        Some(ProposalState::Applied)
+    );
+}
+
+#[test]
+fn test_expired_proposal_state() {
+    let mut testkit = testkit_with_supervisor(2);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+    assert_eq!(proposal_state(&testkit.api(), proposal_hash), None);
+
+    let hash = create_proposal(&testkit.api(), config_proposal);
+    let block = testkit.create_block();
+    block[hash].status().unwrap();
+    assert_eq!(
+        proposal_state(&testkit.api(), proposal_hash),
+        Some(ProposalState::Pending)
+    );
+
+    // The proposal does not get enough confirmations before the deadline.
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT.next());
+    assert_eq!(current_config_proposal(&testkit.api()), None);
+    assert_eq!(
+        proposal_state(&testkit.api(), proposal_hash),
+        Some(ProposalState::Expired)
+    );
+
+    // Votes for the expired proposal are refused.
+    let keypair = testkit.network().validators()[1].service_keypair();
+    let signed_confirm = keypair.confirm_config_change(
+        SUPERVISOR_INSTANCE_ID,
+        ConfigVote {
+            propose_hash: proposal_hash,
+        },
+    );
+    let block = testkit.create_block_with_transaction(signed_confirm);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&CommonError::DeadlineExceeded)
+            .with_description_containing("has expired")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
 }
 
 /// Applies some config via API.
@@ -203,3 +316,403 @@ fn test_configuration_number() {
     let new_configuration_number = configuration_number(&testkit.api());
     assert_eq!(new_configuration_number, 1);
 }
+
+#[test]
+fn test_proposal_metadata() {
+    let mut testkit = testkit_with_supervisor(1);
+    let config_proposal = ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+        .consensus_config(consensus_config_propose_first_variant(&testkit))
+        .description("Increase the propose timeout")
+        .reference_url("https://example.com/proposals/1");
+    let proposal_hash = config_proposal.object_hash();
+
+    let hash = create_proposal(&testkit.api(), config_proposal);
+    testkit.create_block()[hash].status().unwrap();
+
+    let expected_info = ProposalInfo {
+        propose_hash: proposal_hash,
+        configuration_number: 0,
+        actual_from: CFG_CHANGE_HEIGHT,
+        description: "Increase the propose timeout".to_owned(),
+        reference_url: "https://example.com/proposals/1".to_owned(),
+        depends_on: None,
+        // The proposal from the only validator has enough votes to be applied.
+        state: Some(ProposalState::Approved),
+        height: Height(1),
+        tx_hash: hash,
+    };
+    assert_eq!(
+        proposal_info(&testkit.api(), proposal_hash),
+        Some(expected_info.clone())
+    );
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT.next());
+    let proposals: Vec<ProposalInfo> = testkit
+        .api()
+        .public(ApiKind::Service("supervisor"))
+        .get("proposals")
+        .unwrap();
+    let expected_info = ProposalInfo {
+        state: Some(ProposalState::Applied),
+        ..expected_info
+    };
+    assert_eq!(proposals, vec![expected_info]);
+
+    // Proposals with too long description are not accepted.
+    let config_proposal = ConfigPropose::new(1, CFG_CHANGE_HEIGHT.next().next())
+        .consensus_config(consensus_config_propose_second_variant(&testkit))
+        .description("x".repeat(ConfigPropose::MAX_DESCRIPTION_LEN + 1));
+    let hash = create_proposal(&testkit.api(), config_proposal);
+    let block = testkit.create_block();
+    let err = block[hash].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::MalformedConfigPropose)
+            .with_description_containing("description should not exceed")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+}
+
+#[test]
+fn test_proposals_by_author() {
+    let mut testkit = testkit_with_supervisor(2);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let proposal_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            ValidatorId(1),
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let author = testkit.validator(ValidatorId(1)).service_keypair().0;
+    let proposals = proposals_by_author(&testkit.api(), author);
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].propose_hash, proposal_hash);
+    assert_eq!(proposals[0].state, Some(ProposalState::Pending));
+
+    let other_validator = testkit.validator(ValidatorId(0)).service_keypair().0;
+    assert!(proposals_by_author(&testkit.api(), other_validator).is_empty());
+}
+
+#[test]
+fn test_proposals_by_author_pages() {
+    let mut testkit = testkit_with_supervisor(2);
+    let keys = testkit.validator(ValidatorId(1)).service_keypair();
+    let author = keys.0;
+
+    // Propose and cancel several proposals, so that the author has a history.
+    let mut proposal_hashes = vec![];
+    for i in 0..3 {
+        let config_proposal = ConfigProposeBuilder::new(Height(10))
+            .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+            .configuration_number(i)
+            .build();
+        let propose_hash = config_proposal.object_hash();
+        let propose = sign_config_propose_transaction(&testkit, config_proposal, ValidatorId(1));
+        testkit.create_block_with_transaction(propose).transactions[0]
+            .status()
+            .expect("Transaction with change propose discarded.");
+
+        let cancel = keys.cancel_config_change(SUPERVISOR_INSTANCE_ID, ConfigVote { propose_hash });
+        testkit.create_block_with_transaction(cancel).transactions[0]
+            .status()
+            .expect("Transaction with cancellation discarded.");
+        proposal_hashes.push(propose_hash);
+    }
+
+    let api = testkit.api();
+    let page_hashes = |start, count| {
+        proposals_by_author_page(&api, author, start, count)
+            .unwrap()
+            .into_iter()
+            .map(|info| info.propose_hash)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(page_hashes(0, None), proposal_hashes);
+    assert_eq!(page_hashes(0, Some(2)), proposal_hashes[..2].to_vec());
+    assert_eq!(page_hashes(2, Some(2)), proposal_hashes[2..].to_vec());
+    assert!(page_hashes(3, None).is_empty());
+
+    for &count in &[0, MAX_PAGE_SIZE + 1] {
+        let err = proposals_by_author_page(&api, author, 0, Some(count)).unwrap_err();
+        match err {
+            ApiError::BadRequest(_) => {}
+            other => panic!("Unexpected API error: {:?}", other),
+        }
+    }
+
+    // The proposals of the author are covered by the blockchain state hash.
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema("supervisor").unwrap();
+    assert_eq!(
+        schema.proposals_by_author_hashes.get(&author),
+        Some(schema.proposals_by_author.get(&author).object_hash())
+    );
+    let aggregator = SystemSchema::new(&snapshot).state_aggregator();
+    assert_eq!(
+        aggregator.get("supervisor.proposals_by_author_hashes"),
+        Some(schema.proposals_by_author_hashes.object_hash())
+    );
+}
+
+#[test]
+fn test_votes_by_validator() {
+    let mut testkit = testkit_with_supervisor(3);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let propose_hash = config_proposal.object_hash();
+    let propose_tx = sign_config_propose_transaction(&testkit, config_proposal, ValidatorId(1));
+    let propose_tx_hash = propose_tx.object_hash();
+
+    testkit
+        .create_block_with_transaction(propose_tx)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let vote = ConfigVote { propose_hash };
+    let keys = testkit.validator(ValidatorId(2)).service_keypair();
+    let reject = keys.reject_config_change(SUPERVISOR_INSTANCE_ID, vote.clone());
+    let revoke = keys.revoke_config_vote(SUPERVISOR_INSTANCE_ID, vote);
+    let (reject_hash, revoke_hash) = (reject.object_hash(), revoke.object_hash());
+    let block = testkit.create_block_with_transactions(vec![reject, revoke]);
+    block.transactions[0].status().unwrap();
+    block.transactions[1].status().unwrap();
+
+    // The proposal author automatically votes for the proposal.
+    let author = testkit.validator(ValidatorId(1)).service_keypair().0;
+    assert_eq!(
+        votes_by_validator(&testkit.api(), author),
+        vec![VoteRecord {
+            propose_hash,
+            kind: VoteKind::Confirm,
+            height: Height(1),
+            tx_hash: propose_tx_hash,
+        }]
+    );
+
+    let validator = keys.0;
+    assert_eq!(
+        votes_by_validator(&testkit.api(), validator),
+        vec![
+            VoteRecord {
+                propose_hash,
+                kind: VoteKind::Reject,
+                height: Height(2),
+                tx_hash: reject_hash,
+            },
+            VoteRecord {
+                propose_hash,
+                kind: VoteKind::Revoke,
+                height: Height(2),
+                tx_hash: revoke_hash,
+            },
+        ]
+    );
+
+    // Validators that have not voted have no records.
+    let idle_validator = testkit.validator(ValidatorId(0)).service_keypair().0;
+    assert!(votes_by_validator(&testkit.api(), idle_validator).is_empty());
+
+    // Votes can be retrieved page by page.
+    let api = testkit.api();
+    let first_page = votes_by_validator_page(&api, validator, 0, Some(1)).unwrap();
+    assert_eq!(first_page.len(), 1);
+    assert_eq!(first_page[0].tx_hash, reject_hash);
+    let second_page = votes_by_validator_page(&api, validator, 1, Some(1)).unwrap();
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page[0].tx_hash, revoke_hash);
+    assert!(votes_by_validator_page(&api, validator, 2, None)
+        .unwrap()
+        .is_empty());
+    for &count in &[0, MAX_PAGE_SIZE + 1] {
+        let err = votes_by_validator_page(&api, validator, 0, Some(count)).unwrap_err();
+        match err {
+            ApiError::BadRequest(_) => {}
+            other => panic!("Unexpected API error: {:?}", other),
+        }
+    }
+
+    // The votes are covered by the blockchain state hash.
+    let snapshot = testkit.snapshot();
+    let schema: Schema<_> = snapshot.service_schema("supervisor").unwrap();
+    assert_eq!(
+        schema.votes_by_validator_hashes.get(&validator),
+        Some(schema.votes_by_validator.get(&validator).object_hash())
+    );
+    assert_eq!(schema.votes_by_validator_hashes.get(&idle_validator), None);
+    let aggregator = SystemSchema::new(&snapshot).state_aggregator();
+    assert_eq!(
+        aggregator.get("supervisor.votes_by_validator_hashes"),
+        Some(schema.votes_by_validator_hashes.object_hash())
+    );
+}
+
+#[test]
+fn test_activation_log() {
+    let mut testkit = testkit_with_supervisor(2);
+    let config_proposal = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let propose_hash = config_proposal.object_hash();
+
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            ValidatorId(1),
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The confirmation from the second validator collects the majority of votes.
+    let keys = testkit.validator(ValidatorId(0)).service_keypair();
+    let confirm = keys.confirm_config_change(SUPERVISOR_INSTANCE_ID, ConfigVote { propose_hash });
+    let confirm_hash = confirm.object_hash();
+    testkit.create_block_with_transaction(confirm).transactions[0]
+        .status()
+        .unwrap();
+    assert!(activation_log(&testkit.api()).is_empty());
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT);
+    let first_record = ActivationRecord {
+        propose_hash,
+        previous_propose_hash: Hash::zero(),
+        height: CFG_CHANGE_HEIGHT,
+        deciding_tx_hash: confirm_hash,
+    };
+    assert_eq!(activation_log(&testkit.api()), vec![first_record]);
+
+    // The next applied proposal refers to the previous one.
+    let next_change_height = Height(CFG_CHANGE_HEIGHT.0 + 3);
+    let config_proposal = ConfigProposeBuilder::new(next_change_height)
+        .configuration_number(1)
+        .extend_consensus_config_propose(consensus_config_propose_second_variant(&testkit))
+        .build();
+    let next_propose_hash = config_proposal.object_hash();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            config_proposal,
+            ValidatorId(1),
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    let confirm = keys.confirm_config_change(
+        SUPERVISOR_INSTANCE_ID,
+        ConfigVote {
+            propose_hash: next_propose_hash,
+        },
+    );
+    let confirm_hash = confirm.object_hash();
+    testkit.create_block_with_transaction(confirm);
+    testkit.create_blocks_until(next_change_height);
+
+    let second_record = ActivationRecord {
+        propose_hash: next_propose_hash,
+        previous_propose_hash: propose_hash,
+        height: next_change_height,
+        deciding_tx_hash: confirm_hash,
+    };
+    assert_eq!(
+        activation_log(&testkit.api()),
+        vec![first_record, second_record]
+    );
+}
+
+#[test]
+fn test_proposal_state_tracking() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let first_height = Height(5);
+    let first_proposal = ConfigProposeBuilder::new(first_height)
+        .extend_consensus_config_propose(consensus_config_propose_first_variant(&testkit))
+        .build();
+    let first_hash = first_proposal.object_hash();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            first_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+    assert_eq!(
+        proposal_state(&testkit.api(), first_hash),
+        Some(ProposalState::Pending)
+    );
+
+    // Two more confirmations make the proposal approved.
+    let confirmations = build_confirmation_transactions(&testkit, first_hash, initiator_id);
+    testkit.create_block_with_transactions(confirmations[..2].to_vec());
+    assert_eq!(
+        proposal_state(&testkit.api(), first_hash),
+        Some(ProposalState::Approved)
+    );
+
+    // The next proposal is queued behind the approved one.
+    let second_proposal = ConfigProposeBuilder::new(Height(8))
+        .configuration_number(1)
+        .extend_consensus_config_propose(consensus_config_propose_second_variant(&testkit))
+        .build();
+    let second_hash = second_proposal.object_hash();
+    testkit
+        .create_block_with_transaction(sign_config_propose_transaction(
+            &testkit,
+            second_proposal,
+            initiator_id,
+        ))
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+    assert_eq!(
+        proposal_state(&testkit.api(), second_hash),
+        Some(ProposalState::Queued)
+    );
+
+    // Revoking a confirmation makes the first proposal pending again.
+    let keys = testkit.validator(ValidatorId(1)).service_keypair();
+    let revoke = keys.revoke_config_vote(
+        SUPERVISOR_INSTANCE_ID,
+        ConfigVote {
+            propose_hash: first_hash,
+        },
+    );
+    testkit.create_block_with_transaction(revoke).transactions[0]
+        .status()
+        .unwrap();
+    assert_eq!(
+        proposal_state(&testkit.api(), first_hash),
+        Some(ProposalState::Pending)
+    );
+
+    // The first proposal expires, and the queued one becomes obsolete.
+    testkit.create_blocks_until(Height(6));
+    assert_eq!(
+        proposal_state(&testkit.api(), first_hash),
+        Some(ProposalState::Expired)
+    );
+    let obsoleted: Vec<ProposalInfo> = testkit
+        .api()
+        .public(ApiKind::Service("supervisor"))
+        .query(&ProposalsQuery {
+            state: Some(ProposalState::Obsoleted),
+        })
+        .get("proposals")
+        .unwrap();
+    assert_eq!(obsoleted.len(), 1);
+    assert_eq!(obsoleted[0].propose_hash, second_hash);
+}
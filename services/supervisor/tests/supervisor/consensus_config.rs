@@ -15,11 +15,18 @@
 use exonum_merkledb::ObjectHash;
 use exonum_testkit::TestKitBuilder;
 
-use exonum::helpers::ValidatorId;
+use exonum::{
+    blockchain::ValidatorKeys,
+    crypto::gen_keypair,
+    helpers::{Height, ValidatorId},
+    runtime::{ErrorMatch, SUPERVISOR_INSTANCE_ID},
+};
 use exonum_rust_runtime::ServiceFactory;
 
 use crate::utils::*;
-use exonum_supervisor::Supervisor;
+use exonum_supervisor::{
+    ConfigChange, ConfigPropose, ConfigurationError, ConsensusLimits, Supervisor, SupervisorConfig,
+};
 
 #[test]
 fn test_add_nodes_to_validators() {
@@ -157,3 +164,249 @@ fn test_change_our_validator_id() {
     assert_eq!(&testkit.network().validators()[1], testkit.network().us());
     assert_eq!(testkit.consensus_config(), new_consensus_config);
 }
+
+#[test]
+fn test_consensus_config_patch() {
+    let mut testkit = testkit_with_supervisor(1);
+    let mut expected_config = testkit.consensus_config();
+    expected_config.max_message_len = 1024;
+    expected_config.txs_block_limit = 500;
+
+    let patch = r#"{ "max_message_len": 1024, "txs_block_limit": 500 }"#;
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT).consensus_config_patch(patch);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The patch is replaced with the full consensus config.
+    let pending_proposal = config_propose_entry(&testkit).unwrap();
+    assert_eq!(
+        pending_proposal.changes,
+        vec![ConfigChange::Consensus(expected_config.clone())]
+    );
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT);
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), expected_config);
+}
+
+#[test]
+fn test_invalid_consensus_config_patch() {
+    let mut testkit = testkit_with_supervisor(1);
+    let base_config = testkit.consensus_config();
+
+    let patch = r#"{ "max_message_len": "not a number" }"#;
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT).consensus_config_patch(patch);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::MalformedConfigPropose)
+            .with_description_containing("Cannot apply consensus config patch")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT);
+    assert_eq!(testkit.consensus_config(), base_config);
+}
+
+#[test]
+fn test_unsafe_consensus_config() {
+    let supervisor_config = SupervisorConfig {
+        consensus_limits: Some(ConsensusLimits {
+            min_validators: 1,
+            min_txs_block_limit: 10,
+            max_txs_block_limit: 10_000,
+            min_timeout: 100,
+            max_timeout: 60_000,
+        }),
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+    let base_config = testkit.consensus_config();
+
+    // `txs_block_limit` is below the safe range.
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+        .consensus_config_patch(r#"{ "txs_block_limit": 1 }"#);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnsafeConsensusConfig)
+            .with_description_containing("`txs_block_limit` 1 is outside of the range")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Timeout is above the safe range.
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+        .consensus_config_patch(r#"{ "status_timeout": 3600000 }"#);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnsafeConsensusConfig)
+            .with_description_containing("`status_timeout` 3600000 is outside of the range")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT);
+    assert_eq!(testkit.consensus_config(), base_config);
+
+    // Proposals within the safe ranges are accepted.
+    let new_height = CFG_CHANGE_HEIGHT.next().next();
+    let propose =
+        ConfigPropose::new(0, new_height).consensus_config_patch(r#"{ "txs_block_limit": 500 }"#);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+    testkit.create_blocks_until(new_height);
+    assert_eq!(testkit.consensus_config().txs_block_limit, 500);
+}
+
+#[test]
+fn test_remove_too_many_validators() {
+    let supervisor_config = SupervisorConfig {
+        max_removed_validators_percent: Some(25),
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+
+    // Removing a half of the validators is not allowed.
+    let mut consensus_config = testkit.consensus_config();
+    consensus_config.validator_keys.truncate(2);
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT).consensus_config(consensus_config);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnsafeConsensusConfig)
+            .with_description_containing("removes or replaces 2 of 4 validators")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Removing a single validator is within the limit.
+    let mut consensus_config = testkit.consensus_config();
+    consensus_config.validator_keys.truncate(3);
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT).consensus_config(consensus_config);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+}
+
+#[test]
+fn test_consensus_key_rotation_by_other_validator() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(2)
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::decentralized())
+        .create();
+
+    let mut consensus_config = testkit.consensus_config();
+    consensus_config.validator_keys[1].consensus_key = gen_keypair().0;
+
+    // Consensus key of the second validator cannot be replaced by the first one.
+    let propose =
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT).consensus_config(consensus_config.clone());
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnauthorizedKeyRotation)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // The validator can propose to replace its own key.
+    let propose = ConfigPropose::new(0, CFG_CHANGE_HEIGHT).consensus_config(consensus_config);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(1));
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+}
+
+#[test]
+fn test_remove_too_many_validators_after_dependency() {
+    let supervisor_config = SupervisorConfig {
+        max_removed_validators_percent: Some(25),
+        ..Supervisor::decentralized_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .create();
+    let initial_keys = testkit.consensus_config().validator_keys;
+    let new_keys = || ValidatorKeys {
+        consensus_key: gen_keypair().0,
+        service_key: gen_keypair().0,
+    };
+
+    // The first proposal replaces the last validator.
+    let mut first_config = testkit.consensus_config();
+    first_config.validator_keys[3] = new_keys();
+    let propose = ConfigPropose::new(0, Height(10)).consensus_config(first_config.clone());
+    let first_hash = propose.object_hash();
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with change propose discarded.");
+
+    // The dependent proposal replaces only one of the current validators, but two validators
+    // of the configuration scheduled by the first proposal.
+    let mut consensus_config = first_config.clone();
+    consensus_config.validator_keys[2] = initial_keys[3];
+    consensus_config.validator_keys[3] = new_keys();
+    let propose = ConfigPropose::new(1, Height(12))
+        .consensus_config(consensus_config)
+        .after_proposal(first_hash);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    let block = testkit.create_block_with_transaction(signed_proposal);
+    let err = block.transactions[0].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::UnsafeConsensusConfig)
+            .with_description_containing("removes or replaces 2 of 4 validators")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+
+    // Replacing a single validator of the scheduled configuration is within the limit.
+    let mut consensus_config = first_config;
+    consensus_config.validator_keys[3] = initial_keys[3];
+    let propose = ConfigPropose::new(1, Height(12))
+        .consensus_config(consensus_config)
+        .after_proposal(first_hash);
+    let signed_proposal = sign_config_propose_transaction(&testkit, propose, ValidatorId(0));
+    testkit
+        .create_block_with_transaction(signed_proposal)
+        .transactions[0]
+        .status()
+        .expect("Transaction with dependent change propose discarded.");
+}
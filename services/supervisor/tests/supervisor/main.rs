@@ -44,6 +44,8 @@ mod deploy_failures;
 mod inc;
 mod service_lifecycle;
 mod supervisor_config;
+#[cfg(feature = "testkit")]
+mod testkit_ext;
 mod utils;
 
 fn default_artifact() -> ArtifactId {
@@ -342,6 +344,7 @@ fn test_start_service_instance_with_already_passed_deadline_height() {
     let block = testkit.create_block();
 
     let expected_err = ErrorMatch::from_fail(&SupervisorCommonError::ActualFromIsPast)
+        .with_description_containing("is not greater than the current height")
         .for_service(SUPERVISOR_INSTANCE_ID);
     assert_eq!(*block[hash].status().unwrap_err(), expected_err);
 }
@@ -18,15 +18,15 @@
 
 use exonum::{
     blockchain::config::InstanceInitParams,
-    runtime::{InstanceSpec, SnapshotExt, SUPERVISOR_INSTANCE_ID},
+    runtime::{ErrorMatch, InstanceSpec, SnapshotExt, SUPERVISOR_INSTANCE_ID},
 };
 use exonum_merkledb::BinaryValue;
 use exonum_rust_runtime::ServiceFactory;
 use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
 
 use exonum_supervisor::{
-    supervisor_name, ConfigChange, ConfigPropose, Schema, ServiceConfig, Supervisor,
-    SupervisorConfig,
+    mode::Mode, supervisor_name, ConfigChange, ConfigPropose, ConfigurationError, Schema,
+    ServiceConfig, Supervisor, SupervisorConfig,
 };
 
 use crate::{config_api::create_proposal, utils::CFG_CHANGE_HEIGHT};
@@ -105,6 +105,10 @@ fn configure_call() {
         actual_from: CFG_CHANGE_HEIGHT,
         changes: vec![ConfigChange::Service(configuration_change)],
         configuration_number: 0,
+        activation_time: None,
+        description: String::new(),
+        reference_url: String::new(),
+        depends_on: None,
     };
 
     // Apply it (in simple mode no confirmations required).
@@ -115,6 +119,92 @@ fn configure_call() {
     assert_supervisor_config(&testkit, Supervisor::decentralized_config());
 }
 
+/// Checks that a configuration with `majority_count` exceeding the number of validators
+/// is not accepted.
+#[test]
+fn incorrect_majority_count() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::simple())
+        .create();
+
+    let new_config = SupervisorConfig {
+        mode: Mode::Decentralized,
+        majority_count: Some(2),
+        min_activation_delay: 0,
+        consensus_limits: None,
+        max_removed_validators_percent: None,
+        vote_weights: Vec::new(),
+        time_service: None,
+    };
+    let configuration_change = ServiceConfig {
+        instance_id: SUPERVISOR_INSTANCE_ID,
+        params: new_config.into_bytes(),
+    };
+    let config_proposal = ConfigPropose {
+        actual_from: CFG_CHANGE_HEIGHT,
+        changes: vec![ConfigChange::Service(configuration_change)],
+        configuration_number: 0,
+        activation_time: None,
+        description: String::new(),
+        reference_url: String::new(),
+        depends_on: None,
+    };
+
+    let hash = create_proposal(&testkit.api(), config_proposal);
+    let block = testkit.create_block();
+    let err = block[hash].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::InvalidConfig)
+            .with_description_containing("`majority_count` should be between 1 and 1")
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_supervisor_config(&testkit, Supervisor::simple_config());
+}
+
+/// Checks that a configuration with zero `max_removed_validators_percent` is not accepted.
+#[test]
+fn zero_max_removed_validators_percent() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::simple())
+        .create();
+
+    let new_config = SupervisorConfig {
+        max_removed_validators_percent: Some(0),
+        ..Supervisor::simple_config()
+    };
+    let configuration_change = ServiceConfig {
+        instance_id: SUPERVISOR_INSTANCE_ID,
+        params: new_config.into_bytes(),
+    };
+    let config_proposal = ConfigPropose {
+        actual_from: CFG_CHANGE_HEIGHT,
+        changes: vec![ConfigChange::Service(configuration_change)],
+        configuration_number: 0,
+        activation_time: None,
+        description: String::new(),
+        reference_url: String::new(),
+        depends_on: None,
+    };
+
+    let hash = create_proposal(&testkit.api(), config_proposal);
+    let block = testkit.create_block();
+    let err = block[hash].status().unwrap_err();
+    assert_eq!(
+        *err,
+        ErrorMatch::from_fail(&ConfigurationError::InvalidConfig)
+            .with_description_containing(
+                "`max_removed_validators_percent` should be between 1 and 100, but it is 0"
+            )
+            .for_service(SUPERVISOR_INSTANCE_ID)
+    );
+    assert_supervisor_config(&testkit, Supervisor::simple_config());
+}
+
 /// Checks that `supervisor-config` works as expected.
 #[test]
 fn supervisor_config_api() {
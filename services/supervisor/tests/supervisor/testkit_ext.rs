@@ -0,0 +1,109 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the configuration change helpers of the testkit.
+
+use exonum::{
+    helpers::{Height, ValidatorId},
+    merkledb::ObjectHash,
+};
+use exonum_supervisor::{ConfigPropose, SupervisorTestKitExt};
+use exonum_testkit::ByzantineBehavior;
+
+use crate::utils::*;
+
+#[test]
+fn test_commit_consensus_config_change() {
+    let mut testkit = testkit_with_supervisor(4);
+    let consensus_config = consensus_config_propose_first_variant(&testkit);
+    let propose = ConfigPropose::immediate(0).consensus_config(consensus_config.clone());
+
+    testkit.commit_configuration_change(propose);
+    assert_eq!(testkit.height(), Height(1));
+    assert_eq!(testkit.consensus_config(), consensus_config);
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.configuration_number(), 1);
+}
+
+#[test]
+fn test_commit_service_config_change() {
+    let mut testkit = testkit_with_supervisor_and_service(4);
+    let params = "I am a new parameter".to_owned();
+    let propose = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_service_config_propose(params.clone())
+        .build();
+
+    testkit.commit_configuration_change(propose);
+    assert_eq!(testkit.height(), CFG_CHANGE_HEIGHT);
+    check_service_actual_param(&testkit, Some(params));
+
+    // The configuration number is filled in automatically.
+    let params = "I am the second parameter".to_owned();
+    let propose = ConfigProposeBuilder::new(Height(5))
+        .extend_service_config_propose(params.clone())
+        .build();
+    testkit.commit_configuration_change(propose);
+    check_service_actual_param(&testkit, Some(params));
+}
+
+#[test]
+fn test_propose_configuration_change() {
+    let mut testkit = testkit_with_supervisor_and_service(4);
+    let propose = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_service_config_propose("param".to_owned())
+        .build();
+
+    let propose_hash = testkit.propose_configuration_change(propose);
+    assert_eq!(testkit.height(), Height(1));
+    let pending = config_propose_entry(&testkit).unwrap();
+    assert_eq!(pending.object_hash(), propose_hash);
+    check_service_actual_param(&testkit, None);
+}
+
+#[test]
+#[should_panic(expected = "Configuration change was not applied by the supervisor")]
+fn test_commit_failing_config_change() {
+    let mut testkit = testkit_with_supervisor_and_service(4);
+    let propose = ConfigProposeBuilder::new(CFG_CHANGE_HEIGHT)
+        .extend_service_config_propose("apply_error".to_owned())
+        .build();
+    testkit.commit_configuration_change(propose);
+}
+
+#[test]
+fn test_config_change_with_byzantine_validator() {
+    let mut testkit = testkit_with_supervisor(4);
+    testkit
+        .network_mut()
+        .make_byzantine(ValidatorId(0), ByzantineBehavior::WithholdPrecommits);
+    let consensus_config = consensus_config_propose_first_variant(&testkit);
+    let propose = ConfigPropose::immediate(0).consensus_config(consensus_config.clone());
+
+    testkit.commit_configuration_change(propose);
+    assert_eq!(testkit.consensus_config(), consensus_config);
+}
+
+#[test]
+#[should_panic(expected = "Not enough honest validators to approve configuration change")]
+fn test_config_change_with_too_many_byzantine_validators() {
+    let mut testkit = testkit_with_supervisor(4);
+    for i in 0..2 {
+        testkit
+            .network_mut()
+            .make_byzantine(ValidatorId(i), ByzantineBehavior::ConflictingPrecommits);
+    }
+    let consensus_config = consensus_config_propose_first_variant(&testkit);
+    let propose = ConfigPropose::immediate(0).consensus_config(consensus_config);
+    testkit.propose_configuration_change(propose);
+}
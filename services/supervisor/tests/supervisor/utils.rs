@@ -89,6 +89,10 @@ impl ConfigProposeBuilder {
                 // As in the common cases we test only one config, it's ok
                 // to have default value of 0 for test purposes.
                 configuration_number: 0,
+                activation_time: None,
+                description: String::new(),
+                reference_url: String::new(),
+                depends_on: None,
             },
         }
     }
@@ -20,8 +20,10 @@ use exonum::{
 };
 use exonum_merkledb::{access::Access, Snapshot};
 use exonum_rust_runtime::ServiceFactory;
-use exonum_supervisor::{ConfigPropose, Supervisor};
-use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder, TestNode};
+use exonum_supervisor::{ConfigPropose, Supervisor, SupervisorConfig};
+use exonum_testkit::{
+    explorer::BlockWithTransactions, ApiKind, TestKit, TestKitApi, TestKitBuilder, TestNode,
+};
 use pretty_assertions::assert_eq;
 
 use std::{collections::HashMap, iter::FromIterator};
@@ -257,6 +259,144 @@ fn test_exonum_time_service_with_7_validators() {
     }
 }
 
+/// Creates a testkit with the time oracle, which reports the testkit time
+/// via a mock time provider.
+fn create_testkit_with_mock_time(validator_count: u16) -> TestKit {
+    let mock_provider = MockTimeProvider::default();
+    let time_service = TimeServiceFactory::with_provider(mock_provider.clone());
+    let artifact = time_service.artifact_id();
+    TestKitBuilder::validator()
+        .with_validators(validator_count)
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(INSTANCE_ID, INSTANCE_NAME))
+        .with_rust_service(time_service)
+        .with_time_observer(move |time| mock_provider.set_time(time))
+        .create()
+}
+
+/// Makes all validators report the current testkit time to the oracle, so that
+/// the consolidated time is equal to the testkit time right after the created block.
+fn sync_oracle_time(testkit: &mut TestKit) -> BlockWithTransactions {
+    let time = TxTime::new(testkit.time());
+    let reports: Vec<_> = testkit
+        .network()
+        .validators()
+        .iter()
+        .map(|validator| {
+            validator
+                .service_keypair()
+                .report_time(INSTANCE_ID, time.clone())
+        })
+        .collect();
+
+    let block = testkit.create_block_with_transactions(reports);
+    for transaction in &block.transactions {
+        if let Err(err) = transaction.status() {
+            panic!(
+                "Time reported by validator {} is rejected: {}",
+                transaction.content().author(),
+                err
+            );
+        }
+    }
+    block
+}
+
+#[test]
+fn test_testkit_time_with_mock_provider() {
+    let mut testkit = create_testkit_with_mock_time(1);
+    let validators = testkit.network().validators().to_vec();
+
+    testkit.advance_time(Duration::seconds(10));
+    testkit.create_blocks_until(Height(2));
+    let time = Some(Utc.timestamp(10, 0));
+    assert_storage_times_eq(&testkit.snapshot(), &validators, time, &[time]);
+
+    testkit.set_time(Utc.timestamp(50, 0));
+    testkit.create_blocks_until(Height(4));
+    let time = Some(Utc.timestamp(50, 0));
+    assert_storage_times_eq(&testkit.snapshot(), &validators, time, &[time]);
+}
+
+#[test]
+fn test_oracle_time_control_in_testkit() {
+    let mut testkit = create_testkit_with_mock_time(4);
+    let validators = testkit.network().validators().to_vec();
+
+    testkit.advance_time(Duration::seconds(10));
+    let block = sync_oracle_time(&mut testkit);
+    assert_eq!(block.len(), 4);
+    let time = Some(Utc.timestamp(10, 0));
+    assert_storage_times_eq(&testkit.snapshot(), &validators, time, &[time; 4]);
+
+    // The consolidated time does not change until validators report a new time.
+    testkit.advance_time(Duration::seconds(10));
+    testkit.create_blocks_until(Height(5));
+    assert_eq!(get_schema(&testkit.snapshot()).time.get(), time);
+
+    testkit.set_time(Utc.timestamp(100, 0));
+    sync_oracle_time(&mut testkit);
+    let time = Some(Utc.timestamp(100, 0));
+    assert_storage_times_eq(&testkit.snapshot(), &validators, time, &[time; 4]);
+}
+
+#[test]
+#[should_panic(expected = "is rejected")]
+fn test_oracle_time_cannot_go_backwards_in_testkit() {
+    let mut testkit = create_testkit_with_mock_time(4);
+    testkit.set_time(Utc.timestamp(100, 0));
+    sync_oracle_time(&mut testkit);
+    testkit.set_time(Utc.timestamp(50, 0));
+    sync_oracle_time(&mut testkit);
+}
+
+#[test]
+fn test_dependent_service_observes_testkit_time() {
+    let mock_provider = MockTimeProvider::default();
+    let time_service = TimeServiceFactory::with_provider(mock_provider.clone());
+    let artifact = time_service.artifact_id();
+    let supervisor_config = SupervisorConfig {
+        time_service: Some(INSTANCE_NAME.to_owned()),
+        ..Supervisor::simple_config()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_artifact(artifact.clone())
+        .with_instance(artifact.into_default_instance(INSTANCE_ID, INSTANCE_NAME))
+        .with_rust_service(time_service)
+        .with_rust_service(Supervisor)
+        .with_artifact(Supervisor.artifact_id())
+        .with_instance(Supervisor::builtin_instance(supervisor_config))
+        .with_time_observer(move |time| mock_provider.set_time(time))
+        .create();
+
+    // The supervisor activates the proposal by the time reported by the oracle.
+    let activation_time = Utc.timestamp(100, 0);
+    let mut new_config = testkit.consensus_config();
+    new_config.min_propose_timeout += 1;
+    let keypair = testkit.network().validators()[0].service_keypair();
+    let block = testkit.create_block_with_transaction(
+        ConfigPropose::new(0, Height(20))
+            .consensus_config(new_config.clone())
+            .activate_at_time(activation_time)
+            .sign_for_supervisor(keypair.0, &keypair.1),
+    );
+    block[0].status().unwrap();
+
+    testkit.set_time(Utc.timestamp(50, 0));
+    sync_oracle_time(&mut testkit);
+    assert_ne!(testkit.consensus_config(), new_config);
+
+    // The time set in the testkit is observed by the supervisor in the next block.
+    testkit.set_time(activation_time);
+    sync_oracle_time(&mut testkit);
+    assert_eq!(
+        get_schema(&testkit.snapshot()).time.get(),
+        Some(activation_time)
+    );
+    assert_eq!(testkit.consensus_config(), new_config);
+}
+
 #[test]
 fn test_mock_provider() {
     let mock_provider = MockTimeProvider::default();
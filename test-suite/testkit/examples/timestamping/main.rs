@@ -12,21 +12,71 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Simple timestamping service implementation.
+//! Timestamping service implementation.
+//!
+//! The service stores hashes of arbitrary content together with the author and the time
+//! reported by the time oracle, and provides an HTTP endpoint returning proofs of existence
+//! for the timestamped hashes. In this example, the time oracle is emulated with `MockOracle`,
+//! which has the same schema layout as the `exonum-time` service.
 
+use chrono::{DateTime, TimeZone, Utc};
 use exonum::{
-    crypto::gen_keypair,
-    merkledb::ObjectHash,
-    runtime::{ExecutionError, SnapshotExt},
+    blockchain::{BlockProof, IndexProof},
+    crypto::{self, gen_keypair, Hash, PublicKey},
+    merkledb::{
+        access::{Access, AccessExt, FromAccess},
+        proof_map::Raw,
+        MapProof, RawProofMapIndex,
+    },
+    runtime::{CommonError, ErrorMatch, ExecutionError},
 };
 use exonum_derive::*;
-use exonum_rust_runtime::{CallContext, Service, ServiceFactory};
-use exonum_testkit::TestKitBuilder;
+use exonum_rust_runtime::{
+    api::{self, ServiceApiBuilder, ServiceApiState},
+    CallContext, Service, ServiceFactory,
+};
+use exonum_testkit::{ApiKind, MockOracle, TestKitBuilder};
+use serde_derive::{Deserialize, Serialize};
+
+/// Information about the timestamped content.
+#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[derive(BinaryValue, ObjectHash)]
+#[binary_value(codec = "bincode")]
+struct TimestampEntry {
+    /// Author of the timestamping transaction.
+    author: PublicKey,
+    /// Time reported by the time oracle when the content was timestamped.
+    time: DateTime<Utc>,
+}
+
+/// Name of the time oracle instance.
+const TIME_SERVICE_NAME: &str = "time";
+
+#[derive(Debug, FromAccess)]
+struct TimestampingSchema<T: Access> {
+    /// Timestamped content hashes. Hashes are used as keys directly, hence the raw map.
+    timestamps: RawProofMapIndex<T::Base, Hash, TimestampEntry>,
+}
+
+impl<T: Access> TimestampingSchema<T> {
+    fn new(access: T) -> Self {
+        Self::from_root(access).unwrap()
+    }
+}
+
+#[derive(Debug, ExecutionFail)]
+enum Error {
+    /// The content hash is already timestamped.
+    HashAlreadyExists = 0,
+    /// The time oracle does not report the current time.
+    TimeNotAvailable = 1,
+}
 
 #[exonum_interface(auto_ids)]
 trait TimestampingInterface<Ctx> {
     type Output;
-    fn timestamp(&self, ctx: Ctx, arg: String) -> Self::Output;
+    fn timestamp(&self, ctx: Ctx, content_hash: Hash) -> Self::Output;
 }
 
 #[derive(Debug, ServiceDispatcher, ServiceFactory)]
@@ -37,39 +87,161 @@ struct TimestampingService;
 impl TimestampingInterface<CallContext<'_>> for TimestampingService {
     type Output = Result<(), ExecutionError>;
 
-    fn timestamp(&self, _ctx: CallContext<'_>, _arg: String) -> Self::Output {
+    fn timestamp(&self, context: CallContext<'_>, content_hash: Hash) -> Self::Output {
+        let (_, author) = context
+            .caller()
+            .as_transaction()
+            .ok_or(CommonError::UnauthorizedCaller)?;
+
+        // The consolidated time is stored in the `time` entry of the oracle schema.
+        let time = context
+            .data()
+            .for_service(TIME_SERVICE_NAME)
+            .and_then(|access| access.get_proof_entry::<_, DateTime<Utc>>("time").get())
+            .ok_or(Error::TimeNotAvailable)?;
+
+        let mut schema = TimestampingSchema::new(context.service_data());
+        if schema.timestamps.contains(&content_hash) {
+            return Err(Error::HashAlreadyExists.into());
+        }
+        schema
+            .timestamps
+            .put(&content_hash, TimestampEntry { author, time });
         Ok(())
     }
 }
 
-impl Service for TimestampingService {}
+/// Query parameters of the proof endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct TimestampQuery {
+    /// Hash of the timestamped content.
+    hash: Hash,
+}
+
+/// Proof of existence (or absence) of the timestamped content hash.
+#[derive(Debug, Serialize, Deserialize)]
+struct TimestampProof {
+    /// Proof of the last block.
+    block_proof: BlockProof,
+    /// Proof of the `timestamps` index against the state hash of the block.
+    index_proof: MapProof<String, Hash>,
+    /// Proof of the content hash in the `timestamps` index.
+    timestamp_proof: MapProof<Hash, TimestampEntry, Raw>,
+}
+
+impl TimestampProof {
+    /// Checks the proof and returns the timestamp entry, if it exists.
+    fn verify(&self, hash: &Hash) -> Option<TimestampEntry> {
+        let state_hash = self.block_proof.block.state_hash;
+        let index_proof = self.index_proof.check_against_hash(state_hash).unwrap();
+        let (_, &index_hash) = index_proof.entries().next().unwrap();
+        let timestamp_proof = self.timestamp_proof.check_against_hash(index_hash).unwrap();
+        timestamp_proof
+            .all_entries()
+            .find(|(key, _)| *key == *hash)
+            .and_then(|(_, entry)| entry.cloned())
+    }
+}
+
+fn timestamp_proof(state: &ServiceApiState<'_>, hash: Hash) -> api::Result<TimestampProof> {
+    let IndexProof {
+        block_proof,
+        index_proof,
+    } = state
+        .data()
+        .proof_for_service_index("timestamps")
+        .ok_or_else(|| api::Error::NotFound("Timestamps index is not initialized".to_owned()))?;
+    let schema = TimestampingSchema::new(state.service_data());
+    Ok(TimestampProof {
+        block_proof,
+        index_proof,
+        timestamp_proof: schema.timestamps.get_proof(hash),
+    })
+}
+
+impl Service for TimestampingService {
+    fn initialize(&self, context: CallContext<'_>, _params: Vec<u8>) -> Result<(), ExecutionError> {
+        // Initialize the index, so that the proof endpoint works before the first timestamp.
+        TimestampingSchema::new(context.service_data());
+        Ok(())
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        builder.public_scope().endpoint(
+            "v1/timestamps/proof",
+            |state: &ServiceApiState<'_>, query: TimestampQuery| timestamp_proof(state, query.hash),
+        );
+    }
+}
 
 fn main() {
     let instance_id = 512;
-    // Create a testkit for a network with four validators.
+    // Create a testkit for a network with four validators and the time oracle.
     let service = TimestampingService;
     let artifact = service.artifact_id();
+    let oracle = MockOracle::<DateTime<Utc>>::new("time-oracle");
+    let oracle_artifact = oracle.artifact_id();
+    let time_feed = oracle.feed();
     let mut testkit = TestKitBuilder::validator()
         .with_validators(4)
+        .with_artifact(oracle_artifact.clone())
+        .with_instance(oracle_artifact.into_default_instance(256, TIME_SERVICE_NAME))
+        .with_rust_service(oracle)
         .with_artifact(artifact.clone())
         .with_instance(artifact.into_default_instance(instance_id, "timestamping"))
         .with_rust_service(service)
         .create();
-    // Create few transactions.
+
+    // Content cannot be timestamped until the oracle reports the time.
     let keypair = gen_keypair();
-    let tx1 = keypair.timestamp(instance_id, "Down To Earth".to_owned());
-    let tx2 = keypair.timestamp(instance_id, "Cry Over Spilt Milk".to_owned());
-    let tx3 = keypair.timestamp(instance_id, "Dropping Like Flies".to_owned());
+    let tx = keypair.timestamp(instance_id, crypto::hash(b"Too early"));
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&Error::TimeNotAvailable).for_service(instance_id);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    // The time set via the feed is visible to transactions in the next block.
+    let time = Utc.timestamp(1_500_000_000, 0);
+    time_feed.set(time);
+    // Create few transactions.
+    let hashes: Vec<_> = [
+        "Down To Earth",
+        "Cry Over Spilt Milk",
+        "Dropping Like Flies",
+    ]
+    .iter()
+    .map(|content| crypto::hash(content.as_bytes()))
+    .collect();
+    let txs = hashes
+        .iter()
+        .map(|&hash| keypair.timestamp(instance_id, hash));
 
     // Commit them into blockchain.
-    let block = testkit.create_block_with_transactions(vec![tx1.clone(), tx2.clone(), tx3.clone()]);
+    let block = testkit.create_block_with_transactions(txs);
     assert_eq!(block.len(), 3);
     assert!(block.iter().all(|transaction| transaction.status().is_ok()));
 
-    // Check results with the core schema.
-    let snapshot = testkit.snapshot();
-    let schema = snapshot.for_core();
-    assert!(schema.transactions().contains(&tx1.object_hash()));
-    assert!(schema.transactions().contains(&tx2.object_hash()));
-    assert!(schema.transactions().contains(&tx3.object_hash()));
+    // Timestamping the same content again fails.
+    let other_keypair = gen_keypair();
+    let tx = other_keypair.timestamp(instance_id, hashes[0]);
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&Error::HashAlreadyExists).for_service(instance_id);
+    assert_eq!(*block[0].status().unwrap_err(), expected_err);
+
+    // Check existence proofs via the service API.
+    let api = testkit.api();
+    let get_proof = |hash: Hash| -> TimestampProof {
+        api.public(ApiKind::Service("timestamping"))
+            .query(&TimestampQuery { hash })
+            .get("v1/timestamps/proof")
+            .unwrap()
+    };
+    for hash in &hashes {
+        let entry = get_proof(*hash)
+            .verify(hash)
+            .expect("Content is not timestamped");
+        assert_eq!(entry.author, keypair.0);
+        assert_eq!(entry.time, time);
+    }
+    let unknown_hash = crypto::hash(b"Unknown content");
+    assert!(get_proof(unknown_hash).verify(&unknown_hash).is_none());
 }
@@ -26,7 +26,8 @@ use failure::format_err;
 use futures::Future;
 use log::{info, trace};
 use reqwest::{
-    header, Client, ClientBuilder, RedirectPolicy, RequestBuilder as ReqwestBuilder, Response,
+    header::{self, HeaderMap},
+    Client, ClientBuilder, Method, RedirectPolicy, RequestBuilder as ReqwestBuilder, Response,
     StatusCode,
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -149,6 +150,8 @@ pub struct RequestBuilder<'a, 'b, Q = ()> {
     prefix: String,
     query: Option<&'b Q>,
     modifier: Option<ReqwestModifier<'b>>,
+    headers: Vec<(String, String)>,
+    body: Option<(Vec<u8>, String)>,
     expected_headers: HashMap<String, String>,
 }
 
@@ -161,6 +164,7 @@ where
             .field("access", &self.access)
             .field("prefix", &self.prefix)
             .field("query", &self.query)
+            .field("headers", &self.headers)
             .finish()
     }
 }
@@ -182,6 +186,8 @@ where
             prefix,
             query: None,
             modifier: None,
+            headers: vec![],
+            body: None,
             expected_headers: HashMap::new(),
         }
     }
@@ -195,6 +201,8 @@ where
             prefix: self.prefix,
             query: Some(query),
             modifier: self.modifier,
+            headers: self.headers,
+            body: self.body,
             expected_headers: self.expected_headers,
         }
     }
@@ -210,6 +218,26 @@ where
         }
     }
 
+    /// Adds a header to the request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Adds an `Authorization` header with the specified bearer token to the request.
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.header(header::AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+
+    /// Sets a raw body of the request with the specified content type. The body is only
+    /// sent by the [`send`](#method.send) method.
+    pub fn raw_body(self, body: impl Into<Vec<u8>>, content_type: &str) -> Self {
+        Self {
+            body: Some((body.into(), content_type.to_owned())),
+            ..self
+        }
+    }
+
     /// Allows to check that response will contain a specific header.
     pub fn expect_header(self, header: &str, value: &str) -> Self {
         let mut expected_headers = self.expected_headers;
@@ -226,31 +254,11 @@ where
     where
         R: DeserializeOwned + 'static,
     {
-        let params = self
-            .query
-            .as_ref()
-            .map(|query| {
-                format!(
-                    "?{}",
-                    serde_urlencoded::to_string(query).expect("Unable to serialize query.")
-                )
-            })
-            .unwrap_or_default();
-        let url = format!(
-            "{url}{access}/{prefix}/{endpoint}{query}",
-            url = self.test_server_url,
-            access = self.access,
-            prefix = self.prefix,
-            endpoint = endpoint,
-            query = params
-        );
-
+        let url = self.url_with_query(endpoint);
         trace!("GET {}", url);
 
-        let mut builder = self.test_client.get(&url);
-        if let Some(modifier) = self.modifier {
-            builder = modifier(builder);
-        }
+        let builder = self.test_client.get(&url);
+        let builder = Self::prepare(builder, self.headers, self.modifier);
         let response = builder.send().expect("Unable to send request");
         Self::verify_headers(self.expected_headers, &response);
         Self::response_to_api_result(response)
@@ -279,14 +287,71 @@ where
         } else {
             builder.json(&serde_json::Value::Null)
         };
-        if let Some(modifier) = self.modifier {
-            builder = modifier(builder);
-        }
+        builder = Self::prepare(builder, self.headers, self.modifier);
         let response = builder.send().expect("Unable to send request");
         Self::verify_headers(self.expected_headers, &response);
         Self::response_to_api_result(response)
     }
 
+    /// Sends a request with an arbitrary HTTP method to the testing API endpoint and returns
+    /// the raw response.
+    ///
+    /// Unlike `get` and `post`, the query data is always encoded in the URL, and the body
+    /// is only sent if set with [`raw_body`](#method.raw_body). The response status is not
+    /// interpreted, so this method can be used to check responses of any kind.
+    pub fn send(self, method: Method, endpoint: &str) -> RawResponse {
+        let url = self.url_with_query(endpoint);
+        trace!("{} {}", method, url);
+
+        let mut builder = self.test_client.request(method, &url);
+        if let Some((body, content_type)) = self.body {
+            builder = builder
+                .header(header::CONTENT_TYPE, content_type)
+                .body(body);
+        }
+        let builder = Self::prepare(builder, self.headers, self.modifier);
+        let response = builder.send().expect("Unable to send request");
+        Self::verify_headers(self.expected_headers, &response);
+        RawResponse::from(response)
+    }
+
+    /// Returns the endpoint URL with the query data encoded in it.
+    fn url_with_query(&self, endpoint: &str) -> String {
+        let params = self
+            .query
+            .as_ref()
+            .map(|query| {
+                format!(
+                    "?{}",
+                    serde_urlencoded::to_string(query).expect("Unable to serialize query.")
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            "{url}{access}/{prefix}/{endpoint}{query}",
+            url = self.test_server_url,
+            access = self.access,
+            prefix = self.prefix,
+            endpoint = endpoint,
+            query = params
+        )
+    }
+
+    /// Applies custom headers and the modifier to the request.
+    fn prepare(
+        mut builder: ReqwestBuilder,
+        headers: Vec<(String, String)>,
+        modifier: Option<ReqwestModifier<'b>>,
+    ) -> ReqwestBuilder {
+        for (name, value) in headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if let Some(modifier) = modifier {
+            builder = modifier(builder);
+        }
+        builder
+    }
+
     // Checks that response contains headers expected by the request author.
     fn verify_headers(expected_headers: HashMap<String, String>, response: &Response) {
         let headers = response.headers();
@@ -361,6 +426,93 @@ where
     }
 }
 
+/// Raw HTTP response returned by [`RequestBuilder::send`].
+///
+/// [`RequestBuilder::send`]: struct.RequestBuilder.html#method.send
+#[derive(Debug)]
+pub struct RawResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl From<Response> for RawResponse {
+    fn from(mut response: Response) -> Self {
+        let mut body = vec![];
+        response
+            .copy_to(&mut body)
+            .expect("Unable to get response body");
+        Self {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body,
+        }
+    }
+}
+
+impl RawResponse {
+    /// Returns the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the value of the specified response header, if it is present and is
+    /// a valid string.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// Returns the body of the response.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns the body of the response as a string, replacing invalid UTF-8 sequences.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Decodes the body of the response from JSON.
+    pub fn json<R: DeserializeOwned>(&self) -> serde_json::Result<R> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// Checks that the response has the specified status code.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the status code differs from the expected one.
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(
+            self.status,
+            status,
+            "Unexpected response status; body: {}",
+            self.text()
+        );
+        self
+    }
+
+    /// Checks that the response contains the specified header.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the header is absent or has a different value.
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        assert_eq!(
+            self.header(name),
+            Some(value),
+            "Unexpected value of response header {}",
+            name
+        );
+        self
+    }
+}
+
 /// Create a test server.
 fn create_test_server(aggregator: ApiAggregator) -> TestServer {
     let server = TestServer::with_factory(move || {
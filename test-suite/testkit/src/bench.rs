@@ -0,0 +1,173 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarking of block execution.
+
+use std::{fmt, time::Duration};
+
+/// Options of [`TestKit::bench_block_with_options`].
+///
+/// [`TestKit::bench_block_with_options`]: struct.TestKit.html#method.bench_block_with_options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchOptions {
+    iterations: usize,
+    warmup_iterations: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self::new(10).with_warmup(3)
+    }
+}
+
+impl BenchOptions {
+    /// Creates options with the specified number of measured iterations and no warm-up.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `iterations` is zero.
+    pub fn new(iterations: usize) -> Self {
+        assert!(iterations > 0, "Number of iterations should be positive");
+        Self {
+            iterations,
+            warmup_iterations: 0,
+        }
+    }
+
+    /// Sets the number of warm-up iterations, which are executed before the measured
+    /// ones and are not included into statistics.
+    pub fn with_warmup(mut self, warmup_iterations: usize) -> Self {
+        self.warmup_iterations = warmup_iterations;
+        self
+    }
+
+    pub(crate) fn total_iterations(self) -> usize {
+        self.warmup_iterations + self.iterations
+    }
+
+    pub(crate) fn is_warmup(self, iteration: usize) -> bool {
+        iteration < self.warmup_iterations
+    }
+}
+
+/// Time spent on creating and committing a single block.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockTimings {
+    pub execution: Duration,
+    pub commit: Duration,
+}
+
+/// Statistics of durations measured over several iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationStats {
+    /// Minimum duration.
+    pub min: Duration,
+    /// Maximum duration.
+    pub max: Duration,
+    /// Mean duration.
+    pub mean: Duration,
+    /// Median duration.
+    pub median: Duration,
+}
+
+impl DurationStats {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        debug_assert!(!samples.is_empty());
+        samples.sort();
+        let len = samples.len();
+        let total: Duration = samples.iter().sum();
+        let median = if len % 2 == 0 {
+            (samples[len / 2 - 1] + samples[len / 2]) / 2
+        } else {
+            samples[len / 2]
+        };
+
+        Self {
+            min: samples[0],
+            max: samples[len - 1],
+            mean: total / len as u32,
+            median,
+        }
+    }
+}
+
+impl fmt::Display for DurationStats {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "median {:?}, mean {:?}, min {:?}, max {:?}",
+            self.median, self.mean, self.min, self.max
+        )
+    }
+}
+
+/// Results of benchmarking block execution with [`TestKit::bench_block`].
+///
+/// The block execution time includes executing `before_transactions` hooks, transactions
+/// and `after_transactions` hooks, and calculating the state hash. The commit time
+/// includes merging the block changes into the database and executing `after_commit` hooks.
+///
+/// [`TestKit::bench_block`]: struct.TestKit.html#method.bench_block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockBenchmark {
+    /// Number of transactions in the block.
+    pub transactions: usize,
+    /// Number of measured iterations.
+    pub iterations: usize,
+    /// Statistics of the block execution time.
+    pub execution: DurationStats,
+    /// Statistics of the block commit time.
+    pub commit: DurationStats,
+}
+
+impl BlockBenchmark {
+    pub(crate) fn new(transactions: usize, timings: Vec<BlockTimings>) -> Self {
+        let iterations = timings.len();
+        let execution = timings.iter().map(|timings| timings.execution).collect();
+        let commit = timings.iter().map(|timings| timings.commit).collect();
+        Self {
+            transactions,
+            iterations,
+            execution: DurationStats::new(execution),
+            commit: DurationStats::new(commit),
+        }
+    }
+
+    /// Returns the median throughput of block execution measured in transactions per second.
+    pub fn transactions_per_second(&self) -> f64 {
+        let median = self.execution.median + self.commit.median;
+        let seconds = median.as_secs() as f64 + f64::from(median.subsec_nanos()) * 1e-9;
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.transactions as f64 / seconds
+    }
+}
+
+impl fmt::Display for BlockBenchmark {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            formatter,
+            "block with {} transactions, {} iterations:",
+            self.transactions, self.iterations
+        )?;
+        writeln!(formatter, "  execution: {}", self.execution)?;
+        writeln!(formatter, "  commit: {}", self.commit)?;
+        write!(
+            formatter,
+            "  throughput: {:.0} tx/s",
+            self.transactions_per_second()
+        )
+    }
+}
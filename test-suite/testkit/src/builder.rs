@@ -14,6 +14,7 @@
 
 //! Testkit builder.
 
+use chrono::{DateTime, Utc};
 use exonum::{
     blockchain::config::{GenesisConfigBuilder, InstanceInitParams},
     crypto,
@@ -29,7 +30,9 @@ use futures::sync::mpsc;
 
 use std::{collections::HashMap, net::SocketAddr};
 
-use crate::{ApiNotifierChannel, TestKit, TestNetwork};
+use crate::{
+    time::TestKitClock, ApiNotifierChannel, NetworkFaults, TestKit, TestKitServer, TestNetwork,
+};
 
 /// Builder for `TestKit`.
 ///
@@ -73,6 +76,18 @@ use crate::{ApiNotifierChannel, TestKit, TestNetwork};
 ///
 /// Returns the latest block from the blockchain on success.
 ///
+/// ## Change validator count
+///
+/// POST `{baseURL}/v1/network/validators`
+///
+/// Changes the number of validators in the test network to the `validator_count` value
+/// specified in the JSON body (a positive integer). Validators with the lowest identifiers
+/// retain their keys; if the count increases, new validator nodes are added to the network.
+/// The new consensus configuration is committed together with a new block, so it is reverted
+/// if this block is rolled back.
+///
+/// Returns the status of the testkit (in the same format as the status endpoint) on success.
+///
 /// [`serve`]: #method.serve
 /// [`create_block`]: struct.TestKit.html#method.create_block
 /// [`create_block_with_tx_hashes`]: struct.TestKit.html#method.create_block_with_tx_hashes
@@ -122,6 +137,8 @@ pub struct TestKitBuilder {
     plugins: Vec<Box<dyn NodePlugin>>,
     instances: Vec<InstanceInitParams>,
     artifacts: HashMap<ArtifactId, Vec<u8>>,
+    clock: TestKitClock,
+    network_faults: Option<NetworkFaults>,
 }
 
 impl TestKitBuilder {
@@ -184,6 +201,52 @@ impl TestKitBuilder {
         self
     }
 
+    /// Sets the initial time of the testkit. By default, the time is set to the Unix epoch start.
+    pub fn with_time(mut self, time: DateTime<Utc>) -> Self {
+        self.clock.set(time);
+        self
+    }
+
+    /// Adds a function called with the current time of the testkit on the testkit creation
+    /// and after each change of the time with `TestKit::set_time` or `TestKit::advance_time`.
+    ///
+    /// The observer allows to pass the testkit time to time-dependent services, e.g.,
+    /// to a `MockTimeProvider` of the `exonum-time` oracle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use chrono::{DateTime, Duration, TimeZone, Utc};
+    /// # use exonum_testkit::TestKitBuilder;
+    /// # use std::sync::{Arc, RwLock};
+    /// let service_time = Arc::new(RwLock::new(None::<DateTime<Utc>>));
+    /// let service_time_ = Arc::clone(&service_time);
+    /// let mut testkit = TestKitBuilder::validator()
+    ///     .with_time(Utc.timestamp(1_000, 0))
+    ///     .with_time_observer(move |time| *service_time_.write().unwrap() = Some(time))
+    ///     .create();
+    /// assert_eq!(*service_time.read().unwrap(), Some(Utc.timestamp(1_000, 0)));
+    ///
+    /// testkit.advance_time(Duration::seconds(10));
+    /// assert_eq!(*service_time.read().unwrap(), Some(Utc.timestamp(1_010, 0)));
+    /// ```
+    pub fn with_time_observer(
+        mut self,
+        observer: impl Fn(DateTime<Utc>) + Send + Sync + 'static,
+    ) -> Self {
+        self.clock.add_observer(Box::new(observer));
+        self
+    }
+
+    /// Injects faults into the delivery of transactions broadcast within the emulated network.
+    /// See [`NetworkFaults`] for details.
+    ///
+    /// [`NetworkFaults`]: struct.NetworkFaults.html
+    pub fn with_network_faults(mut self, faults: NetworkFaults) -> Self {
+        self.network_faults = Some(faults);
+        self
+    }
+
     /// Enables a logger inside the testkit.
     pub fn with_logger(mut self) -> Self {
         self.logger = true;
@@ -276,19 +339,24 @@ impl TestKitBuilder {
                 genesis_config,
                 self.additional_runtimes,
                 self.api_notifier_channel,
+                self.clock,
             );
             testkit.set_plugins(self.plugins);
+            testkit.set_network_faults(self.network_faults);
             testkit
         }
         #[cfg(not(feature = "exonum-node"))]
         {
-            TestKit::assemble(
+            let mut testkit = TestKit::assemble(
                 TemporaryDB::new(),
                 network,
                 genesis_config,
                 self.additional_runtimes,
                 self.api_notifier_channel,
-            )
+                self.clock,
+            );
+            testkit.set_network_faults(self.network_faults);
+            testkit
         }
     }
 
@@ -304,6 +372,28 @@ impl TestKitBuilder {
         testkit.run(public_api_address, private_api_address);
     }
 
+    /// Starts a testkit web server in a background thread, similar to [`serve`].
+    /// The public and private APIs are bound to free ports on the loopback interface,
+    /// which are chosen automatically and can be retrieved from the returned handle.
+    /// Thus, several servers can be run in parallel without port collisions.
+    ///
+    /// The server is stopped when the returned handle is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum_testkit::{TestKitBuilder, TestKitStatus};
+    /// let server = TestKitBuilder::validator().spawn_server();
+    /// let url = format!("{}testkit/v1/status", server.private_api_url());
+    /// let status: TestKitStatus = reqwest::get(&url).unwrap().json().unwrap();
+    /// assert_eq!(status.height, exonum::helpers::Height(0));
+    /// ```
+    ///
+    /// [`serve`]: #method.serve
+    pub fn spawn_server(self) -> TestKitServer {
+        TestKitServer::spawn(self.create())
+    }
+
     // Creates testkit for validator or auditor node.
     fn new(validator_id: Option<ValidatorId>) -> Self {
         let api_notifier_channel = mpsc::channel(16);
@@ -318,6 +408,8 @@ impl TestKitBuilder {
             plugins: vec![],
             instances: vec![],
             artifacts: HashMap::new(),
+            clock: TestKitClock::default(),
+            network_faults: None,
         }
     }
 }
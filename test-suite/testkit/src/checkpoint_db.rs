@@ -76,6 +76,13 @@ impl<T: Database> Database for CheckpointDb<T> {
     fn merge_sync(&self, patch: Patch) -> StorageResult<()> {
         self.merge(patch)
     }
+
+    fn size_on_disk(&self) -> Option<u64> {
+        self.inner
+            .read()
+            .expect("Cannot lock CheckpointDb for size_on_disk")
+            .size_on_disk()
+    }
 }
 
 impl<T: Database> From<CheckpointDb<T>> for Arc<dyn Database> {
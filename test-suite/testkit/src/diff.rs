@@ -0,0 +1,228 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Differences between blockchain states.
+
+use exonum::{
+    crypto::Hash,
+    merkledb::{access::AccessExt, IndexType, Snapshot, SystemSchema},
+};
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    ops::AddAssign,
+};
+
+/// Kind of the change of an index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexChange {
+    /// The index was created.
+    Created,
+    /// The index existed in both states, but its contents were changed.
+    Modified,
+    /// The index was removed.
+    Removed,
+}
+
+/// Numbers of changed entries in one or more indexes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryChanges {
+    /// Number of added entries.
+    pub added: usize,
+    /// Number of entries with modified values.
+    pub modified: usize,
+    /// Number of removed entries.
+    pub removed: usize,
+}
+
+impl AddAssign for EntryChanges {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.modified += other.modified;
+        self.removed += other.removed;
+    }
+}
+
+impl fmt::Display for EntryChanges {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "+{} ~{} -{}",
+            self.added, self.modified, self.removed
+        )
+    }
+}
+
+/// Changes of a single index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexDiff {
+    /// Kind of the index change.
+    pub change: IndexChange,
+    /// Changed entries of the index. For lists, entries are identified by their positions;
+    /// for entries, the index value is considered as a single entry.
+    pub entries: EntryChanges,
+}
+
+/// Differences between two states of the blockchain, e.g., before and after a block.
+///
+/// The diff allows to find out which indexes were changed, which is useful when debugging
+/// unexpected changes of the state hash. Only Merkelized indexes aggregated into the state
+/// hash are compared; indexes in index groups and non-Merkelized indexes are not considered.
+/// Entries of changed indexes are compared as raw bytes, so the diff does not require
+/// knowledge of the types of keys and values.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum_testkit::TestKitBuilder;
+/// let mut testkit = TestKitBuilder::validator().create();
+/// let before = testkit.snapshot();
+/// testkit.create_block();
+///
+/// let diff = testkit.diff_since(before.as_ref());
+/// // The core schema is changed by the new block.
+/// assert!(diff.namespaces().contains_key("core"));
+/// assert!(diff.to_string().contains("core: "));
+///
+/// // The state does not differ from itself.
+/// let diff = testkit.diff_since(testkit.snapshot().as_ref());
+/// assert!(diff.is_empty());
+/// assert_eq!(diff.to_string(), "no changes");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    /// Changed indexes keyed by the full index name.
+    pub indexes: BTreeMap<String, IndexDiff>,
+}
+
+impl StateDiff {
+    /// Computes differences between two states.
+    pub fn new(before: &dyn Snapshot, after: &dyn Snapshot) -> Self {
+        let before_hashes = index_hashes(before);
+        let after_hashes = index_hashes(after);
+        let names: BTreeSet<_> = before_hashes.keys().chain(after_hashes.keys()).collect();
+
+        let indexes = names
+            .into_iter()
+            .filter_map(|name| {
+                let change = match (before_hashes.get(name), after_hashes.get(name)) {
+                    (Some(old_hash), Some(new_hash)) if old_hash == new_hash => return None,
+                    (Some(_), Some(_)) => IndexChange::Modified,
+                    (None, Some(_)) => IndexChange::Created,
+                    (Some(_), None) => IndexChange::Removed,
+                    (None, None) => unreachable!(),
+                };
+                let entries =
+                    diff_entries(&index_entries(before, name), &index_entries(after, name));
+                Some((name.to_owned(), IndexDiff { change, entries }))
+            })
+            .collect();
+        Self { indexes }
+    }
+
+    /// Checks whether the states are equal.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// Returns the total changes of entries grouped by the namespace of indexes. The namespace
+    /// is the part of the index name before the first dot; for service indexes, it coincides
+    /// with the service name. Core indexes are grouped into the `core` namespace.
+    pub fn namespaces(&self) -> BTreeMap<String, EntryChanges> {
+        let mut namespaces = BTreeMap::new();
+        for (name, diff) in &self.indexes {
+            let namespace = name.split('.').next().unwrap_or_default();
+            *namespaces
+                .entry(namespace.to_owned())
+                .or_insert_with(EntryChanges::default) += diff.entries;
+        }
+        namespaces
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return formatter.write_str("no changes");
+        }
+
+        for (namespace, entries) in self.namespaces() {
+            writeln!(formatter, "{}: {}", namespace, entries)?;
+            let prefix = format!("{}.", namespace);
+            let indexes = self
+                .indexes
+                .iter()
+                .filter(|(name, _)| name.starts_with(&prefix) || **name == namespace);
+            for (name, diff) in indexes {
+                writeln!(
+                    formatter,
+                    "  {} ({:?}): {}",
+                    name, diff.change, diff.entries
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn index_hashes(snapshot: &dyn Snapshot) -> BTreeMap<String, Hash> {
+    SystemSchema::new(snapshot)
+        .state_aggregator()
+        .iter()
+        .collect()
+}
+
+/// Reads entries of a Merkelized index as raw bytes. Returns an empty map if the index
+/// does not exist.
+fn index_entries(snapshot: &dyn Snapshot, name: &str) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    match snapshot.index_type(name) {
+        Some(IndexType::ProofEntry) => snapshot
+            .get_proof_entry::<_, Vec<u8>>(name)
+            .get()
+            .map(|value| (vec![], value))
+            .into_iter()
+            .collect(),
+        Some(IndexType::ProofList) => snapshot
+            .get_proof_list::<_, Vec<u8>>(name)
+            .iter()
+            .enumerate()
+            .map(|(i, value)| ((i as u64).to_be_bytes().to_vec(), value))
+            .collect(),
+        Some(IndexType::ProofMap) => snapshot
+            .get_proof_map::<_, [u8], Vec<u8>>(name)
+            .iter()
+            .collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+fn diff_entries(
+    before: &BTreeMap<Vec<u8>, Vec<u8>>,
+    after: &BTreeMap<Vec<u8>, Vec<u8>>,
+) -> EntryChanges {
+    let mut changes = EntryChanges::default();
+    for (key, value) in after {
+        match before.get(key) {
+            None => changes.added += 1,
+            Some(old_value) if old_value != value => changes.modified += 1,
+            Some(_) => {}
+        }
+    }
+    changes.removed = before
+        .keys()
+        .filter(|key| !after.contains_key(*key))
+        .count();
+    changes
+}
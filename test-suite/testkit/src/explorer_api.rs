@@ -0,0 +1,161 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facade of the explorer HTTP API operating directly on the testkit database.
+
+use exonum::{
+    blockchain::CallInBlock,
+    merkledb::Snapshot,
+    runtime::{ExecutionStatus, InstanceId},
+};
+use exonum_api::{self as api, Error as ApiError};
+use exonum_explorer::{
+    api::{
+        BlockInfo, BlockQuery, BlocksQuery, BlocksQueryError, BlocksRange, CallStatusQuery,
+        CallStatusResponse, TransactionQuery,
+    },
+    BlockchainExplorer, TransactionInfo,
+};
+
+use std::fmt;
+
+/// Facade of the explorer HTTP API operating directly on a snapshot of the testkit database.
+///
+/// Methods of the facade accept the same queries and return the same responses and errors
+/// as the corresponding endpoints of the explorer service, so assertions in tests
+/// look the same as in client code working with the HTTP API. Unlike the HTTP API,
+/// the facade does not require the explorer service to be instantiated in the testkit.
+///
+/// The facade is created with [`TestKit::explorer`] and reflects the blockchain state
+/// at the moment of its creation.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::helpers::Height;
+/// # use exonum_testkit::{explorer::api::BlocksQuery, TestKitBuilder};
+/// let mut testkit = TestKitBuilder::validator().create();
+/// testkit.create_blocks_until(Height(5));
+///
+/// let query = BlocksQuery {
+///     count: 2,
+///     ..BlocksQuery::default()
+/// };
+/// let response = testkit.explorer().blocks(query).unwrap();
+/// assert_eq!(response.range, Height(4)..Height(6));
+/// assert_eq!(response.blocks[0].block.height, Height(5));
+/// ```
+///
+/// [`TestKit::explorer`]: struct.TestKit.html#method.explorer
+pub struct TestKitExplorer {
+    snapshot: Box<dyn Snapshot>,
+}
+
+impl fmt::Debug for TestKitExplorer {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("TestKitExplorer").finish()
+    }
+}
+
+impl TestKitExplorer {
+    pub(crate) fn new(snapshot: Box<dyn Snapshot>) -> Self {
+        Self { snapshot }
+    }
+
+    /// Returns the blockchain explorer for the underlying snapshot.
+    pub fn inner(&self) -> BlockchainExplorer<'_> {
+        BlockchainExplorer::new(self.snapshot.as_ref())
+    }
+
+    /// Returns blocks selected by the query. Corresponds to the `v1/blocks` endpoint.
+    pub fn blocks(&self, query: BlocksQuery) -> api::Result<BlocksRange> {
+        self.inner().blocks_range(&query).map_err(|err| match err {
+            BlocksQueryError::TooManyBlocks => ApiError::BadRequest(err.to_string()),
+            BlocksQueryError::HeightOutOfRange { .. } => ApiError::NotFound(err.to_string()),
+        })
+    }
+
+    /// Returns the block at the specified height. Corresponds to the `v1/block` endpoint.
+    pub fn block(&self, query: BlockQuery) -> api::Result<BlockInfo> {
+        let explorer = self.inner();
+        explorer.block(query.height).map(From::from).ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Requested block height ({}) exceeds the blockchain height ({})",
+                query.height,
+                explorer.height()
+            ))
+        })
+    }
+
+    /// Returns information about the transaction, including its execution status
+    /// and the proof of its location in the block if the transaction is committed.
+    /// Corresponds to the `v1/transactions` endpoint.
+    pub fn transaction_info(&self, query: TransactionQuery) -> api::Result<TransactionInfo> {
+        self.inner().transaction(&query.hash).ok_or_else(|| {
+            let description = serde_json::json!({ "type": "unknown" }).to_string();
+            ApiError::NotFound(description)
+        })
+    }
+
+    /// Returns the execution status of a committed transaction. Corresponds to the
+    /// `v1/call_status/transaction` endpoint.
+    pub fn transaction_status(&self, query: TransactionQuery) -> api::Result<CallStatusResponse> {
+        let explorer = self.inner();
+        let tx_info = explorer.transaction(&query.hash).ok_or_else(|| {
+            ApiError::NotFound(format!("Unknown transaction hash ({})", query.hash))
+        })?;
+        let tx_info = tx_info.as_committed().ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Requested transaction ({}) is not executed yet",
+                query.hash
+            ))
+        })?;
+
+        let call_in_block = CallInBlock::transaction(tx_info.location().position_in_block());
+        let block_height = tx_info.location().block_height();
+        let status = ExecutionStatus(explorer.call_status(block_height, call_in_block));
+        Ok(CallStatusResponse { status })
+    }
+
+    /// Returns the execution status of the `before_transactions` hook of a service.
+    /// Corresponds to the `v1/call_status/before_transactions` endpoint.
+    pub fn before_transactions_status(
+        &self,
+        query: CallStatusQuery,
+    ) -> api::Result<CallStatusResponse> {
+        self.hook_status(query, CallInBlock::before_transactions)
+    }
+
+    /// Returns the execution status of the `after_transactions` hook of a service.
+    /// Corresponds to the `v1/call_status/after_transactions` endpoint.
+    pub fn after_transactions_status(
+        &self,
+        query: CallStatusQuery,
+    ) -> api::Result<CallStatusResponse> {
+        self.hook_status(query, CallInBlock::after_transactions)
+    }
+
+    fn hook_status(
+        &self,
+        query: CallStatusQuery,
+        call: fn(InstanceId) -> CallInBlock,
+    ) -> api::Result<CallStatusResponse> {
+        let status = self
+            .inner()
+            .call_status(query.height, call(query.service_id));
+        Ok(CallStatusResponse {
+            status: ExecutionStatus(status),
+        })
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Emulation of unreliable network delivery of transactions.
+
+use exonum::messages::{AnyTx, Verified};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::{mem, sync::Mutex};
+
+/// Faults injected into the delivery of transactions broadcast within the emulated network.
+///
+/// Faults apply to all transactions delivered to the transaction pool of the emulated node:
+/// transactions submitted through the service HTTP API, transactions broadcast by services
+/// (e.g., votes of validators sent in `after_commit` hooks) and transactions added with
+/// `TestKit::add_tx`. Transactions explicitly included into a block with
+/// `TestKit::create_block_with_transactions` are not affected, since they are not delivered
+/// via the network.
+///
+/// Each transaction may be:
+///
+/// - lost, i.e., never delivered to the transaction pool
+/// - delayed, i.e., delivered only after the specified number of blocks is created
+/// - duplicated, i.e., delivered the second time with an independent delay
+///
+/// Delays make transactions arrive out of order. Faults are generated by a pseudo-random
+/// generator initialized with the specified seed, so tests using them are reproducible.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum_testkit::{NetworkFaults, TestKitBuilder};
+/// let faults = NetworkFaults::new(42)
+///     .with_loss(0.1)
+///     .with_duplication(0.2)
+///     .with_max_delay(3);
+/// let mut testkit = TestKitBuilder::validator()
+///     .with_validators(4)
+///     .with_network_faults(faults)
+///     .create();
+/// testkit.create_blocks_until(exonum::helpers::Height(5));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkFaults {
+    seed: u64,
+    loss_probability: f64,
+    duplication_probability: f64,
+    max_delay: u32,
+}
+
+impl NetworkFaults {
+    /// Creates a configuration without faults, using `seed` to generate faults which
+    /// are enabled later.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            loss_probability: 0.0,
+            duplication_probability: 0.0,
+            max_delay: 0,
+        }
+    }
+
+    /// Sets the probability of a transaction to be lost.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the probability is not in the `[0, 1]` interval.
+    pub fn with_loss(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.loss_probability = probability;
+        self
+    }
+
+    /// Sets the probability of a transaction to be delivered twice.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the probability is not in the `[0, 1]` interval.
+    pub fn with_duplication(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.duplication_probability = probability;
+        self
+    }
+
+    /// Sets the maximum delay of transaction delivery measured in blocks. The delay
+    /// of each delivered transaction is uniformly distributed between zero and `max_delay`.
+    pub fn with_max_delay(mut self, max_delay: u32) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+fn assert_probability(probability: f64) {
+    assert!(
+        probability >= 0.0 && probability <= 1.0,
+        "Probability should be in the [0, 1] interval"
+    );
+}
+
+/// Routes transactions delivered to the pool through the fault injector, if any. Returns
+/// transactions which should be added to the pool immediately.
+pub(crate) fn route_transactions(
+    faults: &Mutex<Option<FaultInjector>>,
+    transactions: impl IntoIterator<Item = Verified<AnyTx>>,
+) -> Vec<Verified<AnyTx>> {
+    match faults.lock().unwrap().as_mut() {
+        Some(faults) => transactions
+            .into_iter()
+            .flat_map(|transaction| faults.route(transaction))
+            .collect(),
+        None => transactions.into_iter().collect(),
+    }
+}
+
+/// Applies faults to the delivered transactions.
+#[derive(Debug, Clone)]
+pub(crate) struct FaultInjector {
+    faults: NetworkFaults,
+    rng: StdRng,
+    /// Delayed transactions together with the number of blocks remaining until their delivery.
+    delayed: Vec<(u32, Verified<AnyTx>)>,
+}
+
+impl FaultInjector {
+    pub fn new(faults: NetworkFaults) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(faults.seed),
+            faults,
+            delayed: vec![],
+        }
+    }
+
+    /// Routes a sent transaction, returning transactions which should be delivered immediately.
+    pub fn route(&mut self, transaction: Verified<AnyTx>) -> Vec<Verified<AnyTx>> {
+        if self.rng.gen_bool(self.faults.loss_probability) {
+            return vec![];
+        }
+
+        let copies = if self.rng.gen_bool(self.faults.duplication_probability) {
+            2
+        } else {
+            1
+        };
+        let mut delivered = vec![];
+        for _ in 0..copies {
+            let delay = self.rng.gen_range(0, self.faults.max_delay + 1);
+            if delay == 0 {
+                delivered.push(transaction.clone());
+            } else {
+                self.delayed.push((delay, transaction.clone()));
+            }
+        }
+        delivered
+    }
+
+    /// Advances the emulated network by a block, returning transactions which should
+    /// be delivered after it.
+    pub fn next_block(&mut self) -> Vec<Verified<AnyTx>> {
+        let (due, delayed): (Vec<_>, Vec<_>) = mem::replace(&mut self.delayed, vec![])
+            .into_iter()
+            .map(|(delay, transaction)| (delay - 1, transaction))
+            .partition(|(delay, _)| *delay == 0);
+        self.delayed = delayed;
+        due.into_iter()
+            .map(|(_, transaction)| transaction)
+            .collect()
+    }
+
+    /// Returns all delayed transactions, clearing the delivery queue.
+    pub fn flush(&mut self) -> Vec<Verified<AnyTx>> {
+        mem::replace(&mut self.delayed, vec![])
+            .into_iter()
+            .map(|(_, transaction)| transaction)
+            .collect()
+    }
+}
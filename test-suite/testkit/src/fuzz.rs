@@ -0,0 +1,221 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property-based testing of service transactions with [`proptest`].
+//!
+//! The module provides strategies generating transactions for a service interface
+//! and [`check_blocks`], which executes generated transactions in the testkit and verifies
+//! the following invariants:
+//!
+//! - Transaction execution does not panic. (Panics are caught by the runtime and manifest
+//!   themselves as execution errors with the `Unexpected` kind.)
+//! - Transaction execution is deterministic, i.e., executing the same block twice
+//!   leads to the same state hash.
+//! - The state hash recorded in the block header corresponds to the blockchain state.
+//! - Any additional invariants checked by the caller after each block.
+//!
+//! Transactions are generated both structurally valid (with arguments produced
+//! by a strategy for the argument type) and invalid (with arbitrary method identifiers
+//! and arguments). Invalid transactions are expected to be rejected gracefully by the service.
+//!
+//! This module is available only if the `fuzz` crate feature is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use exonum::runtime::SnapshotExt;
+//! use exonum_testkit::{fuzz, TestKit};
+//! use proptest::{prelude::*, prop_assert};
+//! # use exonum_derive::*;
+//! # use exonum_rust_runtime::{CallContext, Service, ServiceFactory};
+//! # use exonum::runtime::ExecutionError;
+//! #
+//! # #[exonum_interface(auto_ids)]
+//! # pub trait CounterInterface<Ctx> {
+//! #     type Output;
+//! #     fn increment(&self, ctx: Ctx, by: u64) -> Self::Output;
+//! # }
+//! #
+//! # #[derive(Debug, ServiceDispatcher, ServiceFactory)]
+//! # #[service_factory(artifact_name = "counter", artifact_version = "1.0.0")]
+//! # #[service_dispatcher(implements("CounterInterface"))]
+//! # pub struct CounterService;
+//! #
+//! # impl CounterInterface<CallContext<'_>> for CounterService {
+//! #     type Output = Result<(), ExecutionError>;
+//! #     fn increment(&self, _ctx: CallContext<'_>, _by: u64) -> Self::Output {
+//! #         Ok(())
+//! #     }
+//! # }
+//! #
+//! # impl Service for CounterService {}
+//! const SERVICE_ID: u32 = 100;
+//!
+//! let transactions = prop_oneof![
+//!     // Well-formed calls of the `increment` method.
+//!     fuzz::call(SERVICE_ID, 0, any::<u64>()),
+//!     // Calls with arbitrary method IDs and payloads.
+//!     fuzz::malformed_call(SERVICE_ID, 0..4),
+//! ];
+//! let blocks = fuzz::blocks(fuzz::signed(transactions), 1..5, 0..10);
+//!
+//! proptest!(|(blocks in blocks)| {
+//!     let mut testkit = TestKit::for_rust_service(CounterService, "counter", SERVICE_ID, ());
+//!     fuzz::check_blocks(&mut testkit, blocks, |snapshot| {
+//!         prop_assert!(snapshot.for_service("counter").is_some());
+//!         Ok(())
+//!     })?;
+//! });
+//! ```
+//!
+//! [`proptest`]: https://docs.rs/proptest/
+//! [`check_blocks`]: fn.check_blocks.html
+
+use exonum::{
+    blockchain::Blockchain,
+    crypto::{gen_keypair_from_seed, Seed, SEED_LENGTH},
+    merkledb::{BinaryValue, ObjectHash, Snapshot, SystemSchema},
+    messages::{AnyTx, Verified},
+    runtime::{CallInfo, ErrorKind, InstanceId, MethodId},
+};
+use proptest::{
+    collection::{self, SizeRange},
+    prelude::*,
+    prop_assert, prop_assert_eq,
+    test_runner::TestCaseError,
+};
+
+use std::{collections::HashSet, fmt, ops::Range};
+
+use crate::TestKit;
+
+/// Blocks of transactions generated by [`blocks`](fn.blocks.html).
+pub type Blocks = Vec<Vec<Verified<AnyTx>>>;
+
+/// Generates calls of the specified service method with arguments produced by `arguments`.
+pub fn call<S>(
+    instance_id: InstanceId,
+    method_id: MethodId,
+    arguments: S,
+) -> impl Strategy<Value = AnyTx>
+where
+    S: Strategy,
+    S::Value: BinaryValue + fmt::Debug,
+{
+    arguments.prop_map(move |arguments| AnyTx {
+        call_info: CallInfo::new(instance_id, method_id),
+        arguments: arguments.into_bytes(),
+        valid_until: None,
+    })
+}
+
+/// Generates calls of the service with method IDs from the specified range and arbitrary
+/// arguments. Most of the generated calls are expected to be rejected by the service.
+pub fn malformed_call(
+    instance_id: InstanceId,
+    method_ids: Range<MethodId>,
+) -> impl Strategy<Value = AnyTx> {
+    (method_ids, collection::vec(any::<u8>(), 0..256)).prop_map(move |(method_id, arguments)| {
+        AnyTx {
+            call_info: CallInfo::new(instance_id, method_id),
+            arguments,
+            valid_until: None,
+        }
+    })
+}
+
+/// Signs transactions produced by `transactions` with keypairs derived from generated seeds.
+pub fn signed<S>(transactions: S) -> impl Strategy<Value = Verified<AnyTx>>
+where
+    S: Strategy<Value = AnyTx>,
+{
+    (transactions, any::<[u8; SEED_LENGTH]>()).prop_map(|(transaction, seed)| {
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new(seed));
+        transaction.sign(public_key, &secret_key)
+    })
+}
+
+/// Groups transactions produced by `transactions` into blocks.
+pub fn blocks<S>(
+    transactions: S,
+    block_count: impl Into<SizeRange>,
+    transactions_per_block: impl Into<SizeRange>,
+) -> impl Strategy<Value = Blocks>
+where
+    S: Strategy<Value = Verified<AnyTx>>,
+{
+    collection::vec(
+        collection::vec(transactions, transactions_per_block),
+        block_count,
+    )
+}
+
+/// Executes blocks of transactions in the testkit and checks the invariants described
+/// in the [module docs](index.html). The `invariant` closure is called with the blockchain
+/// snapshot after each block and may check service-specific invariants.
+///
+/// Transactions which do not pass the preliminary check (e.g., ones addressed to a
+/// non-existing service) are skipped, since such transactions cannot get into a block.
+/// Duplicate transactions are skipped as well.
+pub fn check_blocks<F>(
+    testkit: &mut TestKit,
+    blocks: Blocks,
+    mut invariant: F,
+) -> Result<(), TestCaseError>
+where
+    F: FnMut(&dyn Snapshot) -> Result<(), TestCaseError>,
+{
+    let mut seen_transactions = HashSet::new();
+    for transactions in blocks {
+        let snapshot = testkit.snapshot();
+        let transactions: Vec<_> = transactions
+            .into_iter()
+            .filter(|tx| seen_transactions.insert(tx.object_hash()))
+            .filter(|tx| Blockchain::check_tx(snapshot.as_ref(), tx).is_ok())
+            .collect();
+
+        // Execute the block once on a checkpoint to check that execution is deterministic.
+        testkit.checkpoint();
+        testkit.create_block_with_transactions(transactions.clone());
+        let probed_snapshot = testkit.snapshot();
+        testkit.rollback();
+
+        let block = testkit.create_block_with_transactions(transactions);
+        for transaction in &block.transactions {
+            if let Err(err) = transaction.status() {
+                prop_assert!(
+                    err.kind() != ErrorKind::Unexpected,
+                    "Transaction {:?} has panicked: {}",
+                    transaction.content(),
+                    err
+                );
+            }
+        }
+
+        let snapshot = testkit.snapshot();
+        let state_hash = SystemSchema::new(&snapshot).state_hash();
+        prop_assert_eq!(
+            block.header.state_hash,
+            state_hash,
+            "State hash in the block header does not match the blockchain state"
+        );
+        prop_assert_eq!(
+            SystemSchema::new(&probed_snapshot).state_hash(),
+            state_hash,
+            "Transaction execution is not deterministic"
+        );
+        invariant(snapshot.as_ref())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,214 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden state hashes allowing to detect unintended changes of the blockchain state.
+
+use exonum::{
+    crypto::Hash,
+    merkledb::{
+        access::AccessExt, Database, ObjectHash, ProofMapIndex, Snapshot, SystemSchema, TemporaryDB,
+    },
+    runtime::SnapshotExt,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use std::{collections::BTreeMap, env, fmt::Write as _, fs, path::Path};
+
+/// Environment variable which, if set, makes `StateHashes::assert_golden` overwrite
+/// golden files instead of comparing hashes with them.
+const UPDATE_GOLDEN_ENV: &str = "EXONUM_TESTKIT_UPDATE_GOLDEN";
+
+/// Hashes of the blockchain state captured by [`TestKit::state_hashes`].
+///
+/// Since hashes of Merkelized indexes depend on the binary serialization of the stored
+/// values and on the names of indexes, comparing hashes after a scripted scenario with
+/// the stored ("golden") values allows to catch unintended changes of serialization
+/// or schema layout of services during refactoring.
+///
+/// Only the service state is compared with golden values. The aggregated state hash
+/// also covers the core schema, which includes the keys of validators randomly generated
+/// by the testkit, and thus differs between testkit instances.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use exonum_testkit::TestKitBuilder;
+/// let mut testkit = TestKitBuilder::validator().create();
+/// // Execute a scripted scenario...
+/// testkit.create_blocks_until(exonum::helpers::Height(5));
+/// // ...and compare the state with the golden file. The file should be created
+/// // beforehand, e.g., by running the test with `EXONUM_TESTKIT_UPDATE_GOLDEN` set.
+/// testkit.state_hashes().assert_golden("tests/golden/scenario.json");
+/// ```
+///
+/// [`TestKit::state_hashes`]: struct.TestKit.html#method.state_hashes
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateHashes {
+    /// Aggregated state hash of the blockchain, as recorded in block headers.
+    pub state_hash: Hash,
+    /// Hashes of the state of each service, keyed by the service name.
+    pub services: BTreeMap<String, ServiceStateHashes>,
+}
+
+/// Hashes of the state of a single service.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceStateHashes {
+    /// Aggregated hash of the service state. The hash is calculated in the same way
+    /// as the aggregated state hash of the blockchain, but only involves Merkelized indexes
+    /// of the service.
+    pub state_hash: Hash,
+    /// Hashes of Merkelized indexes of the service, keyed by the full index name.
+    pub indexes: BTreeMap<String, Hash>,
+}
+
+impl StateHashes {
+    pub(crate) fn new(snapshot: &dyn Snapshot) -> Self {
+        let system_schema = SystemSchema::new(snapshot);
+        let aggregator = system_schema.state_aggregator();
+
+        let services = snapshot
+            .for_dispatcher()
+            .service_instances()
+            .keys()
+            .map(|name| {
+                let prefix = format!("{}.", name);
+                let indexes: BTreeMap<_, _> = aggregator
+                    .iter()
+                    .filter(|(index_name, _)| index_name.starts_with(&prefix))
+                    .collect();
+                let hashes = ServiceStateHashes {
+                    state_hash: aggregate(&indexes),
+                    indexes,
+                };
+                (name, hashes)
+            })
+            .collect();
+
+        Self {
+            state_hash: system_schema.state_hash(),
+            services,
+        }
+    }
+
+    /// Compares these hashes with the golden hashes stored in a JSON file at `path`.
+    ///
+    /// If the `EXONUM_TESTKIT_UPDATE_GOLDEN` environment variable is set, the file
+    /// is (over)written with these hashes instead. Golden files are supposed to be committed
+    /// to the version control together with tests.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the hashes differ from the golden ones. The panic message lists
+    ///   the services and indexes with changed hashes.
+    /// - Panics if the golden file does not exist and the `EXONUM_TESTKIT_UPDATE_GOLDEN`
+    ///   environment variable is not set.
+    /// - Panics if the golden file cannot be read, parsed or written.
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            self.write_golden(path);
+            return;
+        }
+        if !path.exists() {
+            panic!(
+                "Golden file {} does not exist. \
+                 Set the {} environment variable to create it",
+                path.display(),
+                UPDATE_GOLDEN_ENV
+            );
+        }
+
+        let contents = fs::read_to_string(path).expect("Cannot read golden file");
+        let golden: BTreeMap<String, ServiceStateHashes> =
+            serde_json::from_str(&contents).expect("Cannot parse golden file");
+        if self.services != golden {
+            panic!(
+                "State hashes differ from golden ones in {}:\n{}\
+                 Set the {} environment variable to update the golden file",
+                path.display(),
+                diff(&golden, &self.services),
+                UPDATE_GOLDEN_ENV
+            );
+        }
+    }
+
+    /// Writes these hashes to a golden file at `path`, creating parent directories
+    /// if necessary.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the golden file cannot be written.
+    pub fn write_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("Cannot create directory for golden file");
+        }
+        let contents = serde_json::to_string_pretty(&self.services).unwrap();
+        fs::write(path, contents).expect("Cannot write golden file");
+    }
+}
+
+/// Describes differences between expected and actual service hashes in a human-readable form.
+fn diff(
+    expected: &BTreeMap<String, ServiceStateHashes>,
+    actual: &BTreeMap<String, ServiceStateHashes>,
+) -> String {
+    let expected_indexes = expected.values().flat_map(|service| &service.indexes);
+    let actual_indexes: BTreeMap<_, _> = actual
+        .values()
+        .flat_map(|service| &service.indexes)
+        .collect();
+
+    let mut diff = String::new();
+    for name in expected.keys().filter(|name| !actual.contains_key(*name)) {
+        writeln!(diff, "service `{}`: missing", name).unwrap();
+    }
+    for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+        writeln!(diff, "service `{}`: unexpected", name).unwrap();
+    }
+    for (name, hash) in expected_indexes {
+        match actual_indexes.get(name) {
+            None => writeln!(diff, "index `{}`: missing", name).unwrap(),
+            Some(&actual_hash) if actual_hash != hash => writeln!(
+                diff,
+                "index `{}`: expected {}, got {}",
+                name,
+                hash.to_hex(),
+                actual_hash.to_hex()
+            )
+            .unwrap(),
+            Some(_) => {}
+        }
+    }
+    for name in actual_indexes.keys() {
+        let is_expected = expected
+            .values()
+            .any(|service| service.indexes.contains_key(*name));
+        if !is_expected {
+            writeln!(diff, "index `{}`: unexpected", name).unwrap();
+        }
+    }
+    diff
+}
+
+/// Aggregates index hashes into a Merkelized map, similar to the database state aggregator.
+fn aggregate(indexes: &BTreeMap<String, Hash>) -> Hash {
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    let mut aggregator: ProofMapIndex<_, str, Hash> = fork.get_proof_map("aggregator");
+    for (name, hash) in indexes {
+        aggregator.put(name, *hash);
+    }
+    aggregator.object_hash()
+}
@@ -100,23 +100,44 @@
 #![deny(unsafe_code, bare_trait_objects)]
 
 pub use crate::{
-    api::{ApiKind, TestKitApi},
+    api::{ApiKind, RawResponse, TestKitApi},
+    bench::{BenchOptions, BlockBenchmark, DurationStats},
     builder::TestKitBuilder,
-    network::{TestNetwork, TestNode},
-    server::TestKitStatus,
+    diff::{EntryChanges, IndexChange, IndexDiff, StateDiff},
+    explorer_api::TestKitExplorer,
+    faults::NetworkFaults,
+    golden::{ServiceStateHashes, StateHashes},
+    network::{ByzantineBehavior, TestNetwork, TestNode},
+    oracle::{MockOracle, MockOracleSchema, OracleFeed},
+    server::{TestKitServer, TestKitStatus},
 };
 pub use exonum_explorer as explorer;
 
+/// Simulation of a network of real nodes exchanging consensus messages in memory.
+///
+/// Unlike `TestKit`, which emulates the network by creating blocks directly, the simulation
+/// runs the consensus algorithm on each node, which allows to reproduce issues caused
+/// by a specific interleaving of messages.
+#[cfg(feature = "exonum-node")]
+pub mod simulation {
+    pub use exonum_node::simulation::*;
+}
+
+use chrono::{DateTime, Duration, Utc};
 use exonum::{
     blockchain::{
-        config::{GenesisConfig, GenesisConfigBuilder},
-        ApiSender, Blockchain, BlockchainBuilder, BlockchainMut, ConsensusConfig,
+        config::{GenesisConfig, GenesisConfigBuilder, InstanceInitParams},
+        ApiSender, Block, BlockProof, Blockchain, BlockchainBuilder, BlockchainMut,
+        ConsensusConfig, IndexProof, Schema,
     },
-    crypto::{self, Hash},
+    crypto::{self, Hash, PublicKey},
     helpers::{byzantine_quorum, Height, ValidatorId},
-    merkledb::{BinaryValue, Database, ObjectHash, Snapshot, TemporaryDB},
-    messages::{AnyTx, Verified},
-    runtime::{InstanceId, RuntimeInstance, SnapshotExt},
+    merkledb::{BinaryValue, Database, ObjectHash, Snapshot, SystemSchema, TemporaryDB},
+    messages::{AnyTx, Precommit, Verified},
+    runtime::{
+        ArtifactId, ArtifactStatus, CoreError, ExecutionError, InstanceId, RuntimeInstance,
+        SnapshotExt,
+    },
 };
 use exonum_api::{
     backends::actix::SystemRuntime, ApiAccess, ApiAggregator, ApiManager, ApiManagerConfig,
@@ -124,32 +145,47 @@ use exonum_api::{
 };
 use exonum_explorer::{BlockWithTransactions, BlockchainExplorer};
 use exonum_rust_runtime::{RustRuntimeBuilder, ServiceFactory};
-use futures::{sync::mpsc, Future, Stream};
+use failure::{ensure, format_err};
+use futures::{future, sync::mpsc, Future, Stream};
 use tokio_core::reactor::Core;
 
 #[cfg(feature = "exonum-node")]
 use exonum_node::{ExternalMessage, NodePlugin, PluginApiContext, SharedNodeState};
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt, iter, mem,
     net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 use crate::{
+    bench::BlockTimings,
     checkpoint_db::{CheckpointDb, CheckpointDbHandler},
+    faults::{route_transactions, FaultInjector},
     poll_events::{poll_events, poll_latest},
     server::TestKitActor,
+    time::TestKitClock,
 };
 
 mod api;
+mod bench;
 mod builder;
 mod checkpoint_db;
+mod diff;
+mod explorer_api;
+mod faults;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+mod golden;
 pub mod migrations;
 mod network;
+mod oracle;
 mod poll_events;
+pub mod scenario;
 mod server;
+mod time;
 
 type ApiNotifierChannel = (
     mpsc::Sender<UpdateEndpoints>,
@@ -167,6 +203,10 @@ pub struct TestKit {
     api_sender: ApiSender,
     api_notifier_channel: ApiNotifierChannel,
     api_aggregator: ApiAggregator,
+    clock: TestKitClock,
+    faults: Arc<Mutex<Option<FaultInjector>>>,
+    checkpoints: Vec<Checkpoint>,
+    conflicting_precommits: BTreeMap<Height, Vec<Verified<Precommit>>>,
     #[cfg(feature = "exonum-node")]
     plugins: Vec<Box<dyn NodePlugin>>,
     #[cfg(feature = "exonum-node")]
@@ -181,6 +221,7 @@ impl fmt::Debug for TestKit {
         f.debug_struct("TestKit")
             .field("blockchain", &self.blockchain)
             .field("network", &self.network)
+            .field("time", &self.clock.now())
             .finish()
     }
 }
@@ -211,6 +252,7 @@ impl TestKit {
         genesis_config: GenesisConfig,
         runtimes: Vec<RuntimeInstance>,
         api_notifier_channel: ApiNotifierChannel,
+        clock: TestKitClock,
     ) -> Self {
         let api_channel = mpsc::channel(1_000);
         let api_sender = ApiSender::new(api_channel.0.clone());
@@ -233,11 +275,14 @@ impl TestKit {
 
         let processing_lock = Arc::new(Mutex::new(()));
         let processing_lock_ = Arc::clone(&processing_lock);
+        let faults = Arc::new(Mutex::new(None));
+        let faults_ = Arc::clone(&faults);
 
         let events_stream: Box<dyn Stream<Item = (), Error = ()> + Send + Sync> =
             Box::new(api_channel.1.and_then(move |transaction| {
                 let _guard = processing_lock_.lock().unwrap();
-                BlockchainMut::add_transactions_into_db_pool(db.as_ref(), iter::once(transaction));
+                let transactions = route_transactions(&faults_, iter::once(transaction));
+                BlockchainMut::add_transactions_into_db_pool(db.as_ref(), transactions);
                 Ok(())
             }));
 
@@ -250,6 +295,10 @@ impl TestKit {
             network,
             api_notifier_channel,
             api_aggregator: ApiAggregator::new(),
+            clock,
+            faults,
+            checkpoints: vec![],
+            conflicting_precommits: BTreeMap::new(),
             #[cfg(feature = "exonum-node")]
             plugins: vec![],
             #[cfg(feature = "exonum-node")]
@@ -326,8 +375,19 @@ impl TestKit {
     }
 
     /// Sets a checkpoint for a future [`rollback`](#method.rollback).
+    ///
+    /// Besides the blockchain state, the checkpoint covers the state of the emulated network,
+    /// such as the validator set and transactions delayed by [network faults].
+    /// Checkpoints may be nested.
+    ///
+    /// [network faults]: struct.NetworkFaults.html
     pub fn checkpoint(&mut self) {
-        self.db_handler.checkpoint()
+        self.poll_events();
+        self.db_handler.checkpoint();
+        self.checkpoints.push(Checkpoint {
+            network: self.network.clone(),
+            faults: self.faults.lock().unwrap().clone(),
+        });
     }
 
     /// Rolls the blockchain back to the latest [`checkpoint`](#method.checkpoint).
@@ -402,29 +462,162 @@ impl TestKit {
     /// # }
     /// ```
     pub fn rollback(&mut self) {
-        self.db_handler.rollback()
+        self.db_handler.rollback();
+        let next_height = self.height().next();
+        self.conflicting_precommits.split_off(&next_height);
+        // Checkpoints of the emulated network may be absent if the checkpoint was set
+        // directly with the database handler.
+        if let Some(checkpoint) = self.checkpoints.pop() {
+            self.network = checkpoint.network;
+            *self.faults.lock().unwrap() = checkpoint.faults;
+        }
+    }
+
+    /// Measures the execution and commit time of a block with the specified transactions
+    /// using the [default options](struct.BenchOptions.html). See
+    /// [`bench_block_with_options`](#method.bench_block_with_options) for details.
+    pub fn bench_block(&mut self, transactions: Vec<Verified<AnyTx>>) -> BlockBenchmark {
+        self.bench_block_with_options(transactions, BenchOptions::default())
+    }
+
+    /// Measures the execution and commit time of a block with the specified transactions.
+    ///
+    /// The block is created several times on top of the current blockchain state; after
+    /// each iteration, the blockchain is rolled back, so the state of the testkit is not
+    /// changed by this method. Results of the warm-up iterations are not taken into account.
+    ///
+    /// Absolute timings depend on the hardware and should be compared only between runs
+    /// on the same machine, e.g., to detect performance regressions of a service in CI.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if any of transactions has been already committed to the blockchain.
+    /// - Panics if any of the transactions is incorrect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum_testkit::{BenchOptions, TestKitBuilder};
+    /// let mut testkit = TestKitBuilder::validator().create();
+    /// // Transactions for a service would be used in a real benchmark.
+    /// let benchmark =
+    ///     testkit.bench_block_with_options(vec![], BenchOptions::new(5).with_warmup(1));
+    /// assert_eq!(benchmark.iterations, 5);
+    /// assert_eq!(benchmark.transactions, 0);
+    /// assert!(benchmark.execution.min <= benchmark.execution.median);
+    /// assert!(benchmark.execution.median <= benchmark.execution.max);
+    /// // The blockchain state is not changed by the benchmark.
+    /// assert_eq!(testkit.height(), exonum::helpers::Height(0));
+    /// ```
+    pub fn bench_block_with_options(
+        &mut self,
+        transactions: Vec<Verified<AnyTx>>,
+        options: BenchOptions,
+    ) -> BlockBenchmark {
+        self.poll_events();
+        let transactions_count = transactions.len();
+        let mut timings = Vec::with_capacity(options.total_iterations());
+        for iteration in 0..options.total_iterations() {
+            self.checkpoint();
+            let tx_hashes = self.add_transactions_for_block(transactions.clone());
+            let iteration_timings = self.execute_and_commit_block(&tx_hashes);
+            self.rollback();
+
+            if !options.is_warmup(iteration) {
+                timings.push(iteration_timings);
+            }
+        }
+        BlockBenchmark::new(transactions_count, timings)
     }
 
     fn do_create_block(&mut self, tx_hashes: &[Hash]) -> BlockWithTransactions {
-        let new_block_height = self.height().next();
         let saved_consensus_config = self.consensus_config();
+        let validator_keys = self.validator_consensus_keys();
+        self.execute_and_commit_block(tx_hashes);
+
+        // Auditor nodes verify each received block, in the same way as real auditors do.
+        if self.us().validator_id().is_none() {
+            let height = self.height();
+            if let Err(err) = self.audit_block(height, &validator_keys) {
+                panic!("Auditor has rejected block at height {}: {}", height, err);
+            }
+        }
+
+        // Deliver transactions delayed by the emulated network.
+        let delayed_transactions = match self.faults.lock().unwrap().as_mut() {
+            Some(faults) => faults.next_block(),
+            None => vec![],
+        };
+        self.blockchain
+            .add_transactions_into_pool(delayed_transactions);
+
+        // Modify the self configuration
+        let actual_consensus_config = self.consensus_config();
+        if actual_consensus_config != saved_consensus_config {
+            self.network_mut()
+                .update_consensus_config(actual_consensus_config);
+        }
+
+        self.poll_events();
+        let snapshot = self.snapshot();
+
+        #[cfg(feature = "exonum-node")]
+        for plugin in &self.plugins {
+            plugin.after_commit(&snapshot);
+        }
+
+        BlockchainExplorer::new(&snapshot)
+            .block_with_txs(self.height())
+            .unwrap()
+    }
+
+    /// Executes a block with the specified transactions and commits it, measuring the time
+    /// spent on each of these stages.
+    fn execute_and_commit_block(&mut self, tx_hashes: &[Hash]) -> BlockTimings {
+        let new_block_height = self.height().next();
         let validator_id = self.leader().validator_id().unwrap();
+        let validators = self.network().validators();
+        let precommitting_validators: Vec<_> = validators
+            .iter()
+            .filter(|node| {
+                self.network().byzantine_behavior(node)
+                    != Some(ByzantineBehavior::WithholdPrecommits)
+            })
+            .collect();
+        assert!(
+            precommitting_validators.len() >= byzantine_quorum(validators.len()),
+            "Cannot create a block: byzantine validators withhold too many precommits"
+        );
 
-        let guard = self.processing_lock.lock().unwrap();
+        let _guard = self.processing_lock.lock().unwrap();
+        let start = Instant::now();
         let (block_hash, patch) = self.blockchain.create_patch(
             validator_id.into(),
             new_block_height,
             tx_hashes,
             &mut BTreeMap::new(),
         );
+        let execution = start.elapsed();
 
-        let precommits: Vec<_> = self
-            .network()
-            .validators()
+        let precommits: Vec<_> = precommitting_validators
             .iter()
             .map(|v| v.create_precommit(new_block_height, block_hash))
             .collect();
+        let conflicting_block_hash = crypto::hash(block_hash.as_ref());
+        let conflicting_precommits: Vec<_> = precommitting_validators
+            .iter()
+            .filter(|node| {
+                self.network().byzantine_behavior(node)
+                    == Some(ByzantineBehavior::ConflictingPrecommits)
+            })
+            .map(|v| v.create_precommit(new_block_height, conflicting_block_hash))
+            .collect();
+        if !conflicting_precommits.is_empty() {
+            self.conflicting_precommits
+                .insert(new_block_height, conflicting_precommits);
+        }
 
+        let start = Instant::now();
         self.blockchain
             .commit(
                 patch,
@@ -433,26 +626,10 @@ impl TestKit {
                 &mut BTreeMap::new(),
             )
             .unwrap();
-        drop(guard);
-
-        // Modify the self configuration
-        let actual_consensus_config = self.consensus_config();
-        if actual_consensus_config != saved_consensus_config {
-            self.network_mut()
-                .update_consensus_config(actual_consensus_config);
-        }
-
-        self.poll_events();
-        let snapshot = self.snapshot();
-
-        #[cfg(feature = "exonum-node")]
-        for plugin in &self.plugins {
-            plugin.after_commit(&snapshot);
+        BlockTimings {
+            execution,
+            commit: start.elapsed(),
         }
-
-        BlockchainExplorer::new(&snapshot)
-            .block_with_txs(self.height())
-            .unwrap()
     }
 
     /// Creates a block with the given transactions.
@@ -467,6 +644,16 @@ impl TestKit {
     /// - Panics if any of transactions has been already committed to the blockchain.
     /// - Panics if any of the transactions is incorrect.
     pub fn create_block_with_transactions<I>(&mut self, txs: I) -> BlockWithTransactions
+    where
+        I: IntoIterator<Item = Verified<AnyTx>>,
+    {
+        let tx_hashes = self.add_transactions_for_block(txs);
+        self.create_block_with_tx_hashes(&tx_hashes)
+    }
+
+    /// Checks transactions and adds ones unknown to the blockchain into the pool,
+    /// returning hashes of all transactions.
+    fn add_transactions_for_block<I>(&mut self, txs: I) -> Vec<Hash>
     where
         I: IntoIterator<Item = Verified<AnyTx>>,
     {
@@ -494,7 +681,7 @@ impl TestKit {
             .collect();
         self.blockchain
             .add_transactions_into_pool(unknown_transactions);
-        self.create_block_with_tx_hashes(&tx_hashes)
+        tx_hashes
     }
 
     /// Creates a block with the given transaction.
@@ -515,6 +702,10 @@ impl TestKit {
     /// Creates block with the specified transactions. The transactions must be previously
     /// sent to the node via API or directly put into the `channel()`.
     ///
+    /// Transactions are executed in the order of the hashes, which allows to reproduce
+    /// issues depending on the transaction ordering. Use [`pool_transactions`] to inspect
+    /// the pool contents when determining the order.
+    ///
     /// # Return value
     ///
     /// Returns information about the created block.
@@ -522,6 +713,9 @@ impl TestKit {
     /// # Panics
     ///
     /// - Panics in the case any of transaction hashes are not in the pool.
+    /// - Panics if any of transaction hashes is repeated.
+    ///
+    /// [`pool_transactions`]: #method.pool_transactions
     pub fn create_block_with_tx_hashes(
         &mut self,
         tx_hashes: &[crypto::Hash],
@@ -530,8 +724,18 @@ impl TestKit {
 
         let snapshot = self.blockchain.snapshot();
         let schema = snapshot.for_core();
+        let mut unique_hashes = HashSet::with_capacity(tx_hashes.len());
         for hash in tx_hashes {
-            assert!(schema.transactions_pool().contains(hash));
+            assert!(
+                schema.transactions_pool().contains(hash),
+                "Transaction {} is not in the pool",
+                hash.to_hex()
+            );
+            assert!(
+                unique_hashes.insert(hash),
+                "Transaction {} is repeated",
+                hash.to_hex()
+            );
         }
         self.do_create_block(tx_hashes)
     }
@@ -552,12 +756,177 @@ impl TestKit {
         self.do_create_block(&tx_hashes)
     }
 
+    /// Stops an active service instance without involving the supervisor service.
+    /// The service is stopped in an empty block created by this method; after that,
+    /// transactions addressed to the service are rejected, and the service hooks
+    /// are not invoked.
+    ///
+    /// Returns information about the created block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service instance does not exist or is not active.
+    /// In this case, no block is created.
+    pub fn stop_service(
+        &mut self,
+        instance_id: InstanceId,
+    ) -> Result<BlockWithTransactions, ExecutionError> {
+        self.poll_events();
+        {
+            let _guard = self.processing_lock.lock().unwrap();
+            self.blockchain
+                .execute_with_supervisor_privileges(|extensions| {
+                    extensions.initiate_stopping_service(instance_id)
+                })?;
+        }
+        Ok(self.do_create_block(&[]))
+    }
+
+    /// Resumes a stopped service instance without involving the supervisor service.
+    /// The service is resumed with the same artifact (accounting for data migrations
+    /// performed on the service) and the `params` passed to the service `resume` method.
+    /// The service is resumed in an empty block created by this method.
+    ///
+    /// Returns information about the created block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service instance does not exist or is not stopped,
+    /// or if the service rejects the resume parameters. In this case, no block is created.
+    pub fn restart_service(
+        &mut self,
+        instance_id: InstanceId,
+        params: impl BinaryValue,
+    ) -> Result<BlockWithTransactions, ExecutionError> {
+        self.poll_events();
+        let artifact = {
+            let snapshot = self.snapshot();
+            let state = snapshot
+                .for_dispatcher()
+                .get_instance(instance_id)
+                .ok_or(CoreError::IncorrectInstanceId)?;
+            ArtifactId {
+                version: state.data_version().clone(),
+                ..state.spec.artifact
+            }
+        };
+        {
+            let _guard = self.processing_lock.lock().unwrap();
+            self.blockchain
+                .execute_with_supervisor_privileges(|mut extensions| {
+                    extensions.initiate_resuming_service(instance_id, artifact, params)
+                })?;
+        }
+        Ok(self.do_create_block(&[]))
+    }
+
+    /// Deploys an artifact without involving the supervisor service. The artifact
+    /// is deployed in an empty block created by this method.
+    ///
+    /// The artifact should be available to the corresponding runtime; e.g., a Rust service
+    /// factory should be added with [`TestKitBuilder::with_rust_service`] without
+    /// deploying its artifact at the genesis block.
+    ///
+    /// Returns information about the created block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the artifact is already deployed. In this case, no block is created.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the runtime cannot deploy the artifact.
+    ///
+    /// [`TestKitBuilder::with_rust_service`]: struct.TestKitBuilder.html#method.with_rust_service
+    pub fn deploy_artifact(
+        &mut self,
+        artifact: impl Into<ArtifactId>,
+        deploy_spec: impl BinaryValue,
+    ) -> Result<BlockWithTransactions, ExecutionError> {
+        self.poll_events();
+        let artifact = artifact.into();
+        if self
+            .snapshot()
+            .for_dispatcher()
+            .get_artifact(&artifact)
+            .is_some()
+        {
+            return Err(CoreError::ArtifactAlreadyDeployed.into());
+        }
+
+        {
+            let _guard = self.processing_lock.lock().unwrap();
+            self.blockchain
+                .execute_with_supervisor_privileges(|extensions| {
+                    extensions.start_artifact_registration(artifact, deploy_spec.into_bytes());
+                    Ok(())
+                })?;
+        }
+        Ok(self.do_create_block(&[]))
+    }
+
+    /// Adds a new service instance without involving the supervisor service. The artifact
+    /// of the service should be deployed, e.g., with [`deploy_artifact`]. The service
+    /// is started in an empty block created by this method.
+    ///
+    /// Returns information about the created block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service cannot be started; e.g., if its artifact is not deployed,
+    /// if the instance ID or name is already taken, or if the service constructor fails.
+    /// In this case, no block is created.
+    ///
+    /// [`deploy_artifact`]: #method.deploy_artifact
+    pub fn start_service(
+        &mut self,
+        instance: impl Into<InstanceInitParams>,
+    ) -> Result<BlockWithTransactions, ExecutionError> {
+        self.poll_events();
+        let InstanceInitParams {
+            instance_spec,
+            constructor,
+        } = instance.into();
+
+        // The runtime expects these conditions to be checked by the supervisor.
+        {
+            let snapshot = self.snapshot();
+            let schema = snapshot.for_dispatcher();
+            let is_deployed = schema
+                .get_artifact(&instance_spec.artifact)
+                .map_or(false, |state| state.status == ArtifactStatus::Active);
+            if !is_deployed {
+                return Err(CoreError::ArtifactNotDeployed.into());
+            }
+            if schema.get_instance(instance_spec.id).is_some() {
+                return Err(CoreError::ServiceIdExists.into());
+            }
+            if schema.get_instance(instance_spec.name.as_str()).is_some() {
+                return Err(CoreError::ServiceNameExists.into());
+            }
+        }
+
+        {
+            let _guard = self.processing_lock.lock().unwrap();
+            self.blockchain
+                .execute_with_supervisor_privileges(|mut extensions| {
+                    extensions.initiate_adding_service(instance_spec, constructor)
+                })?;
+        }
+        Ok(self.do_create_block(&[]))
+    }
+
     /// Adds transaction into persistent pool.
+    ///
+    /// The transaction is subject to [network faults] set for the testkit, in the same way
+    /// as transactions broadcast within the emulated network.
+    ///
+    /// [network faults]: struct.NetworkFaults.html
     pub fn add_tx(&mut self, transaction: Verified<AnyTx>) {
         self.check_tx(&transaction);
 
-        self.blockchain
-            .add_transactions_into_pool(iter::once(transaction));
+        let transactions = route_transactions(&self.faults, iter::once(transaction));
+        self.blockchain.add_transactions_into_pool(transactions);
     }
 
     /// Calls `Blockchain::check_tx` and panics on an error.
@@ -575,6 +944,45 @@ impl TestKit {
             .contains(tx_hash)
     }
 
+    /// Returns all transactions in the pool, sorted by their hashes in ascending order.
+    /// [`create_block`] includes the pool transactions into the block in the same order.
+    ///
+    /// [`create_block`]: #method.create_block
+    pub fn pool_transactions(&self) -> Vec<Verified<AnyTx>> {
+        let snapshot = self.snapshot();
+        let schema = snapshot.for_core();
+        let transactions = schema.transactions();
+        schema
+            .transactions_pool()
+            .iter()
+            .map(|hash| {
+                transactions
+                    .get(&hash)
+                    .expect("BUG: Cannot find transaction from the pool")
+            })
+            .collect()
+    }
+
+    /// Removes the transaction with the specified hash from the pool. The transaction
+    /// is not included into subsequent blocks, but it can be added to the pool again,
+    /// e.g., with [`add_tx`]. Committed transactions are not affected.
+    ///
+    /// Returns the removed transaction, or `None` if the transaction is not in the pool.
+    ///
+    /// [`add_tx`]: #method.add_tx
+    pub fn remove_tx(&mut self, tx_hash: &Hash) -> Option<Verified<AnyTx>> {
+        self.poll_events();
+
+        let fork = self.blockchain.fork();
+        let transaction = Schema::new(&fork).remove_transaction_from_pool(tx_hash);
+        if transaction.is_some() {
+            self.blockchain
+                .merge(fork.into_patch())
+                .expect("Cannot update transaction pool");
+        }
+        transaction
+    }
+
     /// Creates a chain of blocks until a given height.
     ///
     /// # Example
@@ -595,6 +1003,52 @@ impl TestKit {
         }
     }
 
+    /// Creates blocks with all transactions in the pool until the blockchain state satisfies
+    /// the specified condition, and returns the height at which it happens. If the condition
+    /// is satisfied by the current state, no blocks are created.
+    ///
+    /// This is useful to test behavior triggered at a certain height (e.g., activation
+    /// of a configuration or expiration of a scheduled action) without hard-coding
+    /// the height in the test.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the condition is not satisfied at `max_height`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use exonum::helpers::Height;
+    /// # use exonum::runtime::SnapshotExt;
+    /// # use exonum_testkit::TestKitBuilder;
+    /// let mut testkit = TestKitBuilder::validator().create();
+    /// let height = testkit.create_blocks_until_condition(Height(10), |snapshot| {
+    ///     snapshot.for_core().height() >= Height(3)
+    /// });
+    /// assert_eq!(height, Height(3));
+    /// ```
+    pub fn create_blocks_until_condition<F>(
+        &mut self,
+        max_height: Height,
+        mut condition: F,
+    ) -> Height
+    where
+        F: FnMut(&dyn Snapshot) -> bool,
+    {
+        loop {
+            self.poll_events();
+            if condition(self.snapshot().as_ref()) {
+                return self.height();
+            }
+            assert!(
+                self.height() < max_height,
+                "Condition is not satisfied at height {}",
+                max_height
+            );
+            self.create_block();
+        }
+    }
+
     /// Returns the hash of latest committed block.
     pub fn last_block_hash(&self) -> crypto::Hash {
         self.blockchain.as_ref().last_hash()
@@ -605,6 +1059,180 @@ impl TestKit {
         self.blockchain.as_ref().last_block().height
     }
 
+    /// Sets faults injected into the delivery of transactions broadcast within the emulated
+    /// network, or disables faults if `None` is passed. See [`NetworkFaults`] for details.
+    ///
+    /// Transactions delayed by the previously set faults are delivered to the pool immediately.
+    ///
+    /// [`NetworkFaults`]: struct.NetworkFaults.html
+    pub fn set_network_faults(&mut self, faults: Option<NetworkFaults>) {
+        self.poll_events();
+        let injector = faults.map(FaultInjector::new);
+        let previous = mem::replace(&mut *self.faults.lock().unwrap(), injector);
+        if let Some(mut previous) = previous {
+            self.blockchain.add_transactions_into_pool(previous.flush());
+        }
+    }
+
+    /// Returns the current time of the testkit clock.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Sets the time of the testkit clock. The new time is passed to the observers registered
+    /// with [`TestKitBuilder::with_time_observer`], such as a mock time provider of a time oracle.
+    ///
+    /// Note that Exonum blocks do not contain timestamps; services relying on time should
+    /// obtain it from a time oracle fed with the testkit time.
+    ///
+    /// [`TestKitBuilder::with_time_observer`]: struct.TestKitBuilder.html#method.with_time_observer
+    pub fn set_time(&mut self, time: DateTime<Utc>) {
+        self.clock.set(time);
+    }
+
+    /// Moves the testkit clock forward by the specified `duration`.
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.clock.advance(duration);
+    }
+
+    /// Verifies a block proof (e.g., one returned by a service HTTP API) against the consensus
+    /// keys of the validators in the current configuration, and checks that the proved block
+    /// belongs to the testkit blockchain.
+    ///
+    /// Note that the proof for a block created before a change of the validator set
+    /// cannot be verified with this method.
+    pub fn verify_block_proof(&self, proof: &BlockProof) -> Result<(), failure::Error> {
+        proof.verify(&self.validator_consensus_keys())?;
+        self.check_block_is_committed(&proof.block)
+    }
+
+    /// Verifies an index proof (e.g., one returned by a service HTTP API) in the same way
+    /// as [`verify_block_proof`] and checks that the proof is for the index with the specified
+    /// full name (e.g., `cryptocurrency.wallets`). Returns the hash of the index, which can be
+    /// used to verify proofs for the index contents, such as `MapProof`s.
+    ///
+    /// [`verify_block_proof`]: #method.verify_block_proof
+    pub fn verify_index_proof(
+        &self,
+        proof: &IndexProof,
+        index_name: &str,
+    ) -> Result<Hash, failure::Error> {
+        let (proved_name, index_hash) = proof.verify(&self.validator_consensus_keys())?;
+        self.check_block_is_committed(&proof.block_proof.block)?;
+        ensure!(
+            proved_name == index_name,
+            "Index proof is for index `{}`, expected `{}`",
+            proved_name,
+            index_name
+        );
+        Ok(index_hash)
+    }
+
+    /// Verifies a committed block in the same way as an auditor node verifies blocks received
+    /// from validators: checks that the block is approved by a quorum of validators
+    /// in the current configuration, and that the block header is consistent with
+    /// the blockchain data (the previous block hash, transactions and execution errors
+    /// in the block, and, for the latest block, the state hash).
+    ///
+    /// Testkits created with [`TestKitBuilder::auditor`] perform this verification
+    /// automatically for each created block, and panic if the verification fails.
+    ///
+    /// Note that the block created before a change of the validator set cannot be verified
+    /// with this method. The precommits of the genesis block are not verified,
+    /// since the genesis block is not approved by validators.
+    ///
+    /// [`TestKitBuilder::auditor`]: struct.TestKitBuilder.html#method.auditor
+    pub fn verify_block(&self, height: Height) -> Result<(), failure::Error> {
+        self.audit_block(height, &self.validator_consensus_keys())
+    }
+
+    fn audit_block(
+        &self,
+        height: Height,
+        validator_keys: &[PublicKey],
+    ) -> Result<(), failure::Error> {
+        let snapshot = self.snapshot();
+        let schema = snapshot.for_core();
+        let proof = schema
+            .block_and_precommits(height)
+            .ok_or_else(|| format_err!("Block at height {} is not committed", height))?;
+        let block = &proof.block;
+        ensure!(
+            block.height == height,
+            "Block has height {}, expected {}",
+            block.height,
+            height
+        );
+
+        if height > Height(0) {
+            proof.verify(validator_keys)?;
+            let prev_hash = schema.block_hash_by_height(height.previous()).unwrap();
+            ensure!(
+                block.prev_hash == prev_hash,
+                "Block does not reference the previous block"
+            );
+        }
+
+        let transactions = schema.block_transactions(height);
+        ensure!(
+            block.tx_count as u64 == transactions.len(),
+            "Block has {} transactions in the header, but {} transactions in fact",
+            block.tx_count,
+            transactions.len()
+        );
+        ensure!(
+            block.tx_hash == transactions.object_hash(),
+            "Block transactions do not match the block header"
+        );
+        ensure!(
+            block.error_hash == schema.call_errors(height).object_hash(),
+            "Execution errors in the block do not match the block header"
+        );
+        if height == schema.height() {
+            ensure!(
+                block.state_hash == SystemSchema::new(&snapshot).state_hash(),
+                "Blockchain state does not match the state hash in the block header"
+            );
+        }
+        Ok(())
+    }
+
+    fn validator_consensus_keys(&self) -> Vec<PublicKey> {
+        self.consensus_config()
+            .validator_keys
+            .iter()
+            .map(|keys| keys.consensus_key)
+            .collect()
+    }
+
+    fn check_block_is_committed(&self, block: &Block) -> Result<(), failure::Error> {
+        let snapshot = self.snapshot();
+        let block_hash = snapshot.for_core().block_hash_by_height(block.height);
+        ensure!(
+            block_hash == Some(block.object_hash()),
+            "Block at height {} does not belong to the testkit blockchain",
+            block.height
+        );
+        Ok(())
+    }
+
+    /// Returns hashes of the current blockchain state, which can be compared with
+    /// golden values to detect unintended changes of the service data layout.
+    /// See [`StateHashes`] for details.
+    ///
+    /// [`StateHashes`]: struct.StateHashes.html
+    pub fn state_hashes(&self) -> StateHashes {
+        StateHashes::new(self.snapshot().as_ref())
+    }
+
+    /// Returns differences between the `before` state (e.g., a snapshot taken before creating
+    /// a block) and the current blockchain state. See [`StateDiff`] for details.
+    ///
+    /// [`StateDiff`]: struct.StateDiff.html
+    pub fn diff_since(&self, before: &dyn Snapshot) -> StateDiff {
+        StateDiff::new(before, self.snapshot().as_ref())
+    }
+
     /// Returns an actual blockchain configuration.
     pub fn consensus_config(&self) -> ConsensusConfig {
         self.snapshot().for_core().consensus_config()
@@ -634,22 +1262,68 @@ impl TestKit {
         &self.network
     }
 
+    /// Returns precommits for a block conflicting with the committed one at the specified
+    /// height. Such precommits are signed by validators with the
+    /// [`ConflictingPrecommits`] behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum::{helpers::{Height, ValidatorId}, merkledb::ObjectHash};
+    /// # use exonum_testkit::{ByzantineBehavior, TestKitBuilder};
+    /// let mut testkit = TestKitBuilder::validator().with_validators(4).create();
+    /// testkit
+    ///     .network_mut()
+    ///     .make_byzantine(ValidatorId(3), ByzantineBehavior::ConflictingPrecommits);
+    /// let block = testkit.create_block();
+    ///
+    /// let precommits = testkit.conflicting_precommits(Height(1));
+    /// assert_eq!(precommits.len(), 1);
+    /// assert_ne!(precommits[0].payload().block_hash(), &block.header.object_hash());
+    /// ```
+    ///
+    /// [`ConflictingPrecommits`]: enum.ByzantineBehavior.html#variant.ConflictingPrecommits
+    pub fn conflicting_precommits(&self, height: Height) -> &[Verified<Precommit>] {
+        self.conflicting_precommits
+            .get(&height)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns a facade of the explorer HTTP API over the current blockchain state.
+    /// See [`TestKitExplorer`] for details.
+    ///
+    /// [`TestKitExplorer`]: struct.TestKitExplorer.html
+    pub fn explorer(&self) -> TestKitExplorer {
+        TestKitExplorer::new(self.snapshot())
+    }
+
     /// Returns the mutable reference to test network for manual modifications.
     pub fn network_mut(&mut self) -> &mut TestNetwork {
         &mut self.network
     }
 
-    fn run(mut self, public_api_address: SocketAddr, private_api_address: SocketAddr) {
+    fn run(self, public_api_address: SocketAddr, private_api_address: SocketAddr) {
+        self.run_until(
+            WebServerConfig::new(public_api_address),
+            WebServerConfig::new(private_api_address),
+            future::empty(),
+        );
+    }
+
+    /// Runs the testkit web server until the `shutdown` future resolves.
+    pub(crate) fn run_until(
+        mut self,
+        public_api: WebServerConfig,
+        private_api: WebServerConfig,
+        shutdown: impl Future<Item = (), Error = ()>,
+    ) {
         let events_stream = self.remove_events_stream();
         let endpoints_rx = mem::replace(&mut self.api_notifier_channel.1, mpsc::channel(0).1);
 
         let (api_aggregator, actor_handle) = TestKitActor::spawn(self);
         let mut servers = HashMap::new();
-        servers.insert(ApiAccess::Public, WebServerConfig::new(public_api_address));
-        servers.insert(
-            ApiAccess::Private,
-            WebServerConfig::new(private_api_address),
-        );
+        servers.insert(ApiAccess::Public, public_api);
+        servers.insert(ApiAccess::Private, private_api);
         let api_manager_config = ApiManagerConfig {
             servers,
             api_aggregator,
@@ -663,9 +1337,10 @@ impl TestKit {
         // when they are received. Otherwise, a client would need to call a `poll_events` analogue
         // each time after a transaction is posted.
         let mut core = Core::new().unwrap();
-        core.run(events_stream).unwrap();
+        core.run(events_stream.select(shutdown).map(drop).map_err(drop))
+            .unwrap();
         system_runtime.stop().unwrap();
-        actor_handle.join().unwrap();
+        actor_handle.stop();
     }
 
     /// Extracts the event stream from this testkit, replacing it with `futures::stream::empty()`.
@@ -702,10 +1377,45 @@ impl TestKit {
             network,
             db,
             api_notifier_channel,
+            clock: self.clock,
             #[cfg(feature = "exonum-node")]
             plugins,
         }
     }
+
+    /// Emulates a crash and restart of the node. All in-memory state of the testkit,
+    /// including the runtimes and service instances, is dropped; the testkit is then
+    /// reconstructed from the persisted database with the provided Rust runtime, restoring
+    /// the dispatcher and the services in the same way as a real node does on restart.
+    ///
+    /// The emulated network and the testkit clock are retained, while network faults
+    /// set on the testkit are not.
+    ///
+    /// This method is equivalent to [`stop`] followed by [`StoppedTestKit::resume`].
+    ///
+    /// [`stop`]: #method.stop
+    /// [`StoppedTestKit::resume`]: struct.StoppedTestKit.html#method.resume
+    pub fn restart(self, rust_runtime: RustRuntimeBuilder) -> Self {
+        self.stop().resume(rust_runtime)
+    }
+
+    /// Emulates a crash and restart of the node with the specified runtimes.
+    /// See [`restart`](#method.restart) for details.
+    pub fn restart_with_runtimes(
+        self,
+        rust_runtime: RustRuntimeBuilder,
+        external_runtimes: Vec<RuntimeInstance>,
+    ) -> Self {
+        self.stop()
+            .resume_with_runtimes(rust_runtime, external_runtimes)
+    }
+}
+
+/// State of the emulated network saved in a checkpoint.
+#[derive(Debug)]
+struct Checkpoint {
+    network: TestNetwork,
+    faults: Option<FaultInjector>,
 }
 
 /// Persistent state of an Exonum node allowing to emulate node restart.
@@ -784,6 +1494,7 @@ pub struct StoppedTestKit {
     plugins: Vec<Box<dyn NodePlugin>>,
     network: TestNetwork,
     api_notifier_channel: ApiNotifierChannel,
+    clock: TestKitClock,
 }
 
 impl fmt::Debug for StoppedTestKit {
@@ -840,6 +1551,7 @@ impl StoppedTestKit {
             GenesisConfigBuilder::with_consensus_config(ConsensusConfig::default()).build(),
             runtimes,
             self.api_notifier_channel,
+            self.clock,
         );
         testkit.set_plugins(self.plugins);
         testkit
@@ -853,6 +1565,7 @@ impl StoppedTestKit {
             GenesisConfigBuilder::with_consensus_config(ConsensusConfig::default()).build(),
             runtimes,
             self.api_notifier_channel,
+            self.clock,
         )
     }
 }
@@ -903,6 +1616,90 @@ fn test_multiple_spec_of_validators_in_builder() {
     drop(testkit);
 }
 
+#[test]
+fn test_auditor_verifies_blocks() {
+    let mut testkit = TestKitBuilder::auditor().with_validators(4).create();
+    testkit.create_blocks_until(Height(3));
+    for height in 0..=3 {
+        testkit.verify_block(Height(height)).unwrap();
+    }
+
+    let err = testkit.verify_block(Height(4)).unwrap_err();
+    assert!(err.to_string().contains("is not committed"));
+
+    // Precommits are not accepted from unknown validators.
+    let unknown_keys: Vec<_> = (0..4).map(|_| crypto::gen_keypair().0).collect();
+    testkit.audit_block(Height(3), &unknown_keys).unwrap_err();
+}
+
+#[test]
+fn test_auditor_cannot_author_blocks_or_vote() {
+    use exonum::{blockchain::ProposerId, helpers::Round};
+    use std::panic::{self, AssertUnwindSafe};
+
+    let mut testkit = TestKitBuilder::auditor().with_validators(4).create();
+    let auditor = testkit.us();
+    let auditor_key = auditor.consensus_keypair().0;
+    assert_eq!(auditor.validator_id(), None);
+    assert!(!testkit.validator_consensus_keys().contains(&auditor_key));
+
+    // The auditor is never chosen to propose blocks, and it does not precommit them.
+    for _ in 0..4 {
+        assert_ne!(testkit.leader().consensus_keypair().0, auditor_key);
+        testkit.create_block();
+        let height = testkit.height();
+        let proof = testkit
+            .snapshot()
+            .for_core()
+            .block_and_precommits(height)
+            .unwrap();
+        let proposer = proof.block.get_header::<ProposerId>().unwrap().unwrap();
+        assert!((proposer.0).0 < 4);
+        assert_eq!(proof.precommits.len(), 4);
+        assert!(proof
+            .precommits
+            .iter()
+            .all(|precommit| precommit.author() != auditor_key));
+    }
+
+    // The auditor cannot create precommits as a validator.
+    let block_hash = testkit.snapshot().for_core().last_block().object_hash();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        auditor.create_precommit(testkit.height(), block_hash)
+    }));
+    assert!(result.is_err());
+
+    // A vote forged by the auditor on behalf of a validator is rejected.
+    let height = testkit.height();
+    let mut proof = testkit
+        .snapshot()
+        .for_core()
+        .block_and_precommits(height)
+        .unwrap();
+    let (auditor_pk, auditor_sk) = auditor.consensus_keypair();
+    let forged_precommit = Verified::from_value(
+        Precommit::new(
+            ValidatorId(0),
+            height,
+            Round::first(),
+            crypto::Hash::zero(),
+            block_hash,
+            Utc::now(),
+        ),
+        auditor_pk,
+        &auditor_sk,
+    );
+    proof.precommits[0] = forged_precommit;
+    let err = proof
+        .verify(&testkit.validator_consensus_keys())
+        .unwrap_err();
+    assert!(err.to_string().contains("signed with an unexpected key"));
+
+    // Without the forged vote, the remaining precommits still form a quorum.
+    proof.precommits.remove(0);
+    proof.verify(&testkit.validator_consensus_keys()).unwrap();
+}
+
 #[test]
 fn test_stop() {
     let testkit = TestKitBuilder::validator().with_logger().create();
@@ -22,6 +22,8 @@ use exonum::{
     messages::{Precommit, Verified},
 };
 
+use std::collections::BTreeMap;
+
 // TODO Refactor TestNetwork and TestkitBuilder [ECR-3222]
 
 /// Emulated test network.
@@ -29,6 +31,26 @@ use exonum::{
 pub struct TestNetwork {
     us: TestNode,
     nodes: Vec<TestNode>,
+    /// Behavior of byzantine nodes, keyed by the consensus key of the node.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    byzantine: BTreeMap<PublicKey, ByzantineBehavior>,
+}
+
+/// Malicious behavior of an emulated validator.
+///
+/// Byzantine behavior affects messages signed by the validator on behalf of the emulated
+/// network. If byzantine validators prevent the network from reaching the consensus
+/// (e.g., if more than a third of validators withhold precommits), the testkit panics
+/// on an attempt to create a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByzantineBehavior {
+    /// The validator does not sign precommits for created blocks.
+    WithholdPrecommits,
+    /// Besides the precommit for each created block, the validator signs a precommit
+    /// for a conflicting block at the same height and round. Conflicting precommits
+    /// can be retrieved with `TestKit::conflicting_precommits`.
+    ConflictingPrecommits,
 }
 
 impl TestNetwork {
@@ -79,7 +101,11 @@ impl TestNetwork {
             us
         };
 
-        Self { nodes, us }
+        Self {
+            nodes,
+            us,
+            byzantine: BTreeMap::new(),
+        }
     }
 
     /// Adds a new auditor node to this network.
@@ -161,6 +187,37 @@ impl TestNetwork {
             config.find_validator(|keys| keys.consensus_key == self.us.consensus_keypair().0);
     }
 
+    /// Makes the validator with the given id behave maliciously. The behavior is retained
+    /// if the validator id of the node changes.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if there is no validator with the given id.
+    pub fn make_byzantine(&mut self, id: ValidatorId, behavior: ByzantineBehavior) {
+        let consensus_key = self
+            .consensus_public_key_of(id)
+            .expect("Validator with the given id does not exist");
+        self.byzantine.insert(consensus_key, behavior);
+    }
+
+    /// Makes the node with the given consensus key behave honestly.
+    pub fn make_honest(&mut self, consensus_key: PublicKey) {
+        self.byzantine.remove(&consensus_key);
+    }
+
+    /// Returns the malicious behavior of the node, or `None` if the node is honest.
+    pub fn byzantine_behavior(&self, node: &TestNode) -> Option<ByzantineBehavior> {
+        self.byzantine.get(&node.keys.consensus_pk()).cloned()
+    }
+
+    /// Returns all honest validators in the network.
+    pub fn honest_validators(&self) -> Vec<TestNode> {
+        self.validators()
+            .into_iter()
+            .filter(|node| self.byzantine_behavior(node).is_none())
+            .collect()
+    }
+
     /// Returns service public key of the validator with given id.
     pub fn service_public_key_of(&self, id: ValidatorId) -> Option<PublicKey> {
         self.validators()
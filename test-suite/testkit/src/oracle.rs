@@ -0,0 +1,271 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mock oracle services with data feeds controlled by the test.
+
+use exonum::{
+    crypto::PublicKey,
+    merkledb::{
+        access::{Access, AccessExt},
+        BinaryValue, ObjectHash, ProofEntry, ProofMapIndex,
+    },
+    runtime::{
+        versioning::Version, ArtifactId, CommonError, ExecutionError, MethodId, RuntimeIdentifier,
+    },
+};
+use exonum_rust_runtime::{
+    ArtifactProtobufSpec, CallContext, Service, ServiceDispatcher, ServiceFactory,
+};
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+/// Data feed of a [`MockOracle`], which allows the test to control the value reported
+/// by the oracle. Feeds are cheaply cloneable; all clones refer to the same value.
+///
+/// [`MockOracle`]: struct.MockOracle.html
+#[derive(Debug)]
+pub struct OracleFeed<V> {
+    value: Arc<RwLock<Option<V>>>,
+}
+
+impl<V> Clone for OracleFeed<V> {
+    fn clone(&self) -> Self {
+        Self {
+            value: Arc::clone(&self.value),
+        }
+    }
+}
+
+impl<V: Clone> OracleFeed<V> {
+    /// Returns the current value of the feed.
+    pub fn get(&self) -> Option<V> {
+        self.value.read().unwrap().clone()
+    }
+
+    /// Sets the value reported by the oracle starting from the next created block.
+    pub fn set(&self, value: V) {
+        *self.value.write().unwrap() = Some(value);
+    }
+
+    /// Makes the oracle report no value starting from the next created block,
+    /// emulating missing oracle data.
+    pub fn clear(&self) {
+        *self.value.write().unwrap() = None;
+    }
+}
+
+/// Schema of a [`MockOracle`] containing the value reported by the oracle.
+///
+/// The layout of the schema mirrors the schema of the `exonum-time` oracle: the consolidated
+/// value is stored in the `time` entry, and the values reported by validators are stored
+/// in the `validators_times` map. Thus, a mock oracle with `DateTime<Utc>` values can replace
+/// the time oracle for services reading its data by the index names.
+///
+/// [`MockOracle`]: struct.MockOracle.html
+#[derive(Debug)]
+pub struct MockOracleSchema<T: Access, V>
+where
+    V: BinaryValue + ObjectHash,
+{
+    /// Values reported by the current validators, keyed by the service keys of validators.
+    pub validators_values: ProofMapIndex<T::Base, PublicKey, V>,
+    /// The consolidated value reported by the oracle, or `None` if the oracle data is missing.
+    pub value: ProofEntry<T::Base, V>,
+}
+
+impl<T: Access, V> MockOracleSchema<T, V>
+where
+    V: BinaryValue + ObjectHash,
+{
+    /// Creates the schema from the service data access, e.g., one returned by
+    /// `SnapshotExt::for_service` or `CallContext::data().for_service`.
+    pub fn new(access: T) -> Self {
+        Self {
+            validators_values: access.clone().get_proof_map("validators_times"),
+            value: access.get_proof_entry("time"),
+        }
+    }
+}
+
+/// Service factory of an oracle service which reports values controlled by the test,
+/// such as the current time or exchange rates.
+///
+/// At the beginning of each block, the oracle records the current value of its
+/// [`OracleFeed`] into [`MockOracleSchema`] as the value reported by each validator
+/// and as the consolidated value. Thus, the value set via the feed is visible
+/// to transactions and hooks of other services starting from the next created block.
+/// If the feed is cleared, the values are removed from the schema, which allows to test
+/// how services handle missing oracle data.
+///
+/// The oracle does not have any transactions. Services read the oracle data in the same
+/// way as they would read the data of a real oracle service, via the blockchain data
+/// of the oracle instance.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::runtime::SnapshotExt;
+/// # use exonum_rust_runtime::ServiceFactory;
+/// # use exonum_testkit::{MockOracle, MockOracleSchema, TestKitBuilder};
+/// let oracle = MockOracle::<u64>::new("exchange-rates");
+/// let feed = oracle.feed();
+/// let mut testkit = TestKitBuilder::validator()
+///     .with_artifact(oracle.artifact_id())
+///     .with_instance(oracle.artifact_id().into_default_instance(100, "rates"))
+///     .with_rust_service(oracle)
+///     .create();
+///
+/// feed.set(42);
+/// testkit.create_block();
+/// let snapshot = testkit.snapshot();
+/// let schema = MockOracleSchema::<_, u64>::new(snapshot.for_service("rates").unwrap());
+/// assert_eq!(schema.value.get(), Some(42));
+///
+/// feed.clear();
+/// testkit.create_block();
+/// let snapshot = testkit.snapshot();
+/// let schema = MockOracleSchema::<_, u64>::new(snapshot.for_service("rates").unwrap());
+/// assert_eq!(schema.value.get(), None);
+/// ```
+///
+/// [`OracleFeed`]: struct.OracleFeed.html
+/// [`MockOracleSchema`]: struct.MockOracleSchema.html
+pub struct MockOracle<V> {
+    artifact_name: String,
+    feed: OracleFeed<V>,
+}
+
+impl<V> fmt::Debug for MockOracle<V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("MockOracle")
+            .field("artifact_name", &self.artifact_name)
+            .finish()
+    }
+}
+
+impl<V> MockOracle<V>
+where
+    V: BinaryValue + ObjectHash + Clone + Send + Sync + 'static,
+{
+    /// Creates a factory of the oracle service with the specified artifact name.
+    /// The oracle does not report any value until it is set via the feed.
+    pub fn new(artifact_name: impl Into<String>) -> Self {
+        Self {
+            artifact_name: artifact_name.into(),
+            feed: OracleFeed {
+                value: Arc::new(RwLock::new(None)),
+            },
+        }
+    }
+
+    /// Sets the initial value reported by the oracle.
+    pub fn with_value(self, value: V) -> Self {
+        self.feed.set(value);
+        self
+    }
+
+    /// Returns the data feed of the oracle.
+    pub fn feed(&self) -> OracleFeed<V> {
+        self.feed.clone()
+    }
+}
+
+impl<V> ServiceFactory for MockOracle<V>
+where
+    V: BinaryValue + ObjectHash + Clone + Send + Sync + 'static,
+{
+    fn artifact_id(&self) -> ArtifactId {
+        ArtifactId::new(
+            RuntimeIdentifier::Rust,
+            self.artifact_name.clone(),
+            Version::new(0, 1, 0),
+        )
+        .expect("Invalid artifact name of the mock oracle")
+    }
+
+    fn artifact_protobuf_spec(&self) -> ArtifactProtobufSpec {
+        ArtifactProtobufSpec::default()
+    }
+
+    fn create_instance(&self) -> Box<dyn Service> {
+        Box::new(MockOracleService {
+            feed: self.feed.clone(),
+        })
+    }
+}
+
+struct MockOracleService<V> {
+    feed: OracleFeed<V>,
+}
+
+impl<V> fmt::Debug for MockOracleService<V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("MockOracleService").finish()
+    }
+}
+
+impl<V> MockOracleService<V>
+where
+    V: BinaryValue + ObjectHash + Clone,
+{
+    fn record_value(&self, context: CallContext<'_>) {
+        let validator_keys = context.data().for_core().consensus_config().validator_keys;
+        let mut schema = MockOracleSchema::new(context.service_data());
+        schema.validators_values.clear();
+        if let Some(value) = self.feed.get() {
+            for keys in &validator_keys {
+                schema
+                    .validators_values
+                    .put(&keys.service_key, value.clone());
+            }
+            schema.value.set(value);
+        } else {
+            schema.value.remove();
+        }
+    }
+}
+
+impl<V> ServiceDispatcher for MockOracleService<V>
+where
+    V: Send + Sync,
+{
+    fn call(
+        &self,
+        _interface_name: &str,
+        _method: MethodId,
+        _context: CallContext<'_>,
+        _payload: &[u8],
+    ) -> Result<(), ExecutionError> {
+        Err(CommonError::NoSuchMethod.into())
+    }
+}
+
+impl<V> Service for MockOracleService<V>
+where
+    V: BinaryValue + ObjectHash + Clone + Send + Sync + 'static,
+{
+    fn initialize(&self, context: CallContext<'_>, _params: Vec<u8>) -> Result<(), ExecutionError> {
+        self.record_value(context);
+        Ok(())
+    }
+
+    fn before_transactions(&self, context: CallContext<'_>) -> Result<(), ExecutionError> {
+        self.record_value(context);
+        Ok(())
+    }
+}
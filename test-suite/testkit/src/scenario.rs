@@ -0,0 +1,489 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scenarios driving the testkit, which are loaded from YAML or JSON files.
+//!
+//! A scenario is a list of steps, such as deploying artifacts, starting services,
+//! sending transactions and creating blocks with the expected transaction statuses.
+//! Scenarios allow to add regression tests without writing Rust code; the only
+//! Rust code necessary is a test which registers the service methods used in scenarios
+//! and runs scenario files with [`ScenarioRunner`].
+//!
+//! # Scenario format
+//!
+//! Scenarios can be written either in YAML or in JSON; both formats describe the same
+//! structure. For example, a YAML scenario looks as follows:
+//!
+//! ```yaml
+//! steps:
+//!   - deploy:
+//!       artifact: "0:counter:1.0.0"
+//!   - start_service:
+//!       artifact: "0:counter:1.0.0"
+//!       id: 100
+//!       name: counter
+//!   - transaction:
+//!       name: first
+//!       signer: alice
+//!       service: counter
+//!       method: increment
+//!       arguments: 5
+//!   - create_block:
+//!       expect:
+//!         first: ok
+//! ```
+//!
+//! The same scenario in JSON:
+//!
+//! ```json
+//! {
+//!   "steps": [
+//!     { "deploy": { "artifact": "0:counter:1.0.0" } },
+//!     { "start_service": { "artifact": "0:counter:1.0.0", "id": 100, "name": "counter" } },
+//!     {
+//!       "transaction": {
+//!         "name": "first",
+//!         "signer": "alice",
+//!         "service": "counter",
+//!         "method": "increment",
+//!         "arguments": 5
+//!       }
+//!     },
+//!     { "create_block": { "expect": { "first": "ok" } } }
+//!   ]
+//! }
+//! ```
+//!
+//! The following steps are supported:
+//!
+//! - `deploy`: deploys an artifact, identified by a string like `0:counter:1.0.0`.
+//!   An optional `spec` field contains the hex-encoded deploy specification.
+//! - `start_service`: starts a service from a deployed artifact. An optional `constructor`
+//!   field contains the hex-encoded service configuration.
+//! - `transaction`: signs a call of the service method with the keypair of the `signer`
+//!   and sends it to the transaction pool. Keypairs are generated on the first use
+//!   of the signer name. `arguments` are converted to the method payload according
+//!   to the method registered with [`ScenarioRunner::with_method`]. The optional `name`
+//!   allows to refer to the transaction in the following steps.
+//! - `create_block`: creates a block with all transactions in the pool. `expect` maps
+//!   the transaction names to their expected execution statuses: either `ok`,
+//!   `error` (any error), or the kind of the error, such as `service:0`, `core:3`
+//!   or `unexpected`.
+//!
+//! Deploying artifacts and starting services bypass the supervisor service, similar
+//! to [`TestKit::deploy_artifact`] and [`TestKit::start_service`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use exonum_derive::*;
+//! # use exonum_rust_runtime::{CallContext, Service, ServiceFactory};
+//! # use exonum::runtime::ExecutionError;
+//! use exonum_testkit::{scenario::{Scenario, ScenarioRunner}, TestKitBuilder};
+//!
+//! # #[exonum_interface(auto_ids)]
+//! # pub trait CounterInterface<Ctx> {
+//! #     type Output;
+//! #     fn increment(&self, ctx: Ctx, by: u64) -> Self::Output;
+//! # }
+//! #
+//! # #[derive(Debug, ServiceDispatcher, ServiceFactory)]
+//! # #[service_factory(artifact_name = "counter", artifact_version = "1.0.0")]
+//! # #[service_dispatcher(implements("CounterInterface"))]
+//! # pub struct CounterService;
+//! #
+//! # impl CounterInterface<CallContext<'_>> for CounterService {
+//! #     type Output = Result<(), ExecutionError>;
+//! #     fn increment(&self, _ctx: CallContext<'_>, by: u64) -> Self::Output {
+//! #         if by == 0 {
+//! #             Err(ExecutionError::service(0, "Adding zero"))
+//! #         } else {
+//! #             Ok(())
+//! #         }
+//! #     }
+//! # }
+//! #
+//! # impl Service for CounterService {}
+//! let mut testkit = TestKitBuilder::validator()
+//!     .with_rust_service(CounterService)
+//!     .create();
+//!
+//! let scenario = Scenario::from_yaml(r#"
+//! steps:
+//!   - deploy: { artifact: "0:counter:1.0.0" }
+//!   - start_service: { artifact: "0:counter:1.0.0", id: 100, name: counter }
+//!   - transaction:
+//!       { name: inc, signer: alice, service: counter, method: increment, arguments: 5 }
+//!   - transaction:
+//!       { name: zero, signer: bob, service: counter, method: increment, arguments: 0 }
+//!   - create_block:
+//!       expect: { inc: ok, zero: "service:0" }
+//! "#).unwrap();
+//!
+//! ScenarioRunner::new(&mut testkit)
+//!     .with_method::<u64>("counter", "increment", 0)
+//!     .run(&scenario)
+//!     .unwrap();
+//! ```
+//!
+//! [`ScenarioRunner`]: struct.ScenarioRunner.html
+//! [`ScenarioRunner::with_method`]: struct.ScenarioRunner.html#method.with_method
+//! [`TestKit::deploy_artifact`]: ../struct.TestKit.html#method.deploy_artifact
+//! [`TestKit::start_service`]: ../struct.TestKit.html#method.start_service
+
+use exonum::{
+    blockchain::{config::InstanceInitParams, Blockchain},
+    crypto::{gen_keypair, Hash, PublicKey, SecretKey},
+    merkledb::{BinaryValue, ObjectHash},
+    messages::AnyTx,
+    runtime::{ArtifactId, CallInfo, ExecutionError, InstanceId, MethodId, SnapshotExt},
+};
+use failure::{bail, ensure, format_err};
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+use serde_json::Value;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::Path,
+};
+
+use crate::TestKit;
+
+/// Scenario consisting of steps executed in order. See the [module docs](index.html)
+/// for the description of the scenario format.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Steps of the scenario.
+    pub steps: Vec<Step>,
+}
+
+impl Scenario {
+    /// Parses a scenario in the JSON format.
+    pub fn from_json(contents: &str) -> Result<Self, failure::Error> {
+        serde_json::from_str(contents).map_err(failure::Error::from)
+    }
+
+    /// Parses a scenario in the YAML format.
+    pub fn from_yaml(contents: &str) -> Result<Self, failure::Error> {
+        serde_yaml::from_str(contents).map_err(failure::Error::from)
+    }
+
+    /// Loads a scenario from a file. Files with the `yaml` or `yml` extension are parsed
+    /// as YAML; other files are parsed as JSON.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, failure::Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format_err!("Cannot read scenario {}: {}", path.display(), e))?;
+        let is_yaml = path
+            .extension()
+            .map_or(false, |extension| extension == "yaml" || extension == "yml");
+        let scenario = if is_yaml {
+            Self::from_yaml(&contents)
+        } else {
+            Self::from_json(&contents)
+        };
+        scenario.map_err(|e| format_err!("Cannot parse scenario {}: {}", path.display(), e))
+    }
+}
+
+/// Single step of a scenario.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Deploys an artifact.
+    Deploy {
+        /// Artifact identifier in the string form, e.g., `0:counter:1.0.0`.
+        artifact: String,
+        /// Hex-encoded deploy specification.
+        #[serde(default)]
+        spec: String,
+    },
+    /// Starts a service from a deployed artifact.
+    StartService {
+        /// Artifact identifier in the string form, e.g., `0:counter:1.0.0`.
+        artifact: String,
+        /// Numeric identifier of the service.
+        id: InstanceId,
+        /// Name of the service.
+        name: String,
+        /// Hex-encoded service configuration.
+        #[serde(default)]
+        constructor: String,
+    },
+    /// Sends a transaction into the transaction pool.
+    Transaction(TransactionStep),
+    /// Creates a block with all transactions in the pool and checks
+    /// the execution statuses of transactions.
+    CreateBlock {
+        /// Expected statuses keyed by the transaction names.
+        #[serde(default)]
+        expect: BTreeMap<String, String>,
+    },
+}
+
+/// Transaction sent by a scenario.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionStep {
+    /// Name of the transaction used to refer to it in the following steps.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Name of the signer.
+    pub signer: String,
+    /// Name of the service.
+    pub service: String,
+    /// Name of the service method.
+    pub method: String,
+    /// Method arguments. Arguments are represented as a JSON value regardless
+    /// of the scenario format.
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Service method registered in the scenario runner.
+#[derive(Clone, Copy)]
+struct MethodSpec {
+    id: MethodId,
+    encode: fn(Value) -> Result<Vec<u8>, serde_json::Error>,
+}
+
+fn encode_arguments<A>(arguments: Value) -> Result<Vec<u8>, serde_json::Error>
+where
+    A: DeserializeOwned + BinaryValue,
+{
+    serde_json::from_value::<A>(arguments).map(BinaryValue::into_bytes)
+}
+
+/// Runner of scenarios over a testkit instance.
+///
+/// The runner keeps the keypairs of signers and the hashes of named transactions between
+/// runs, so that a scenario may be split into several files or be combined with
+/// assertions in Rust code.
+pub struct ScenarioRunner<'a> {
+    testkit: &'a mut TestKit,
+    methods: HashMap<(String, String), MethodSpec>,
+    keypairs: HashMap<String, (PublicKey, SecretKey)>,
+    transactions: HashMap<String, Hash>,
+}
+
+impl fmt::Debug for ScenarioRunner<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ScenarioRunner")
+            .field("keypairs", &self.keypairs.keys().collect::<Vec<_>>())
+            .field("transactions", &self.transactions)
+            .finish()
+    }
+}
+
+impl<'a> ScenarioRunner<'a> {
+    /// Creates a runner operating on the specified testkit.
+    pub fn new(testkit: &'a mut TestKit) -> Self {
+        Self {
+            testkit,
+            methods: HashMap::new(),
+            keypairs: HashMap::new(),
+            transactions: HashMap::new(),
+        }
+    }
+
+    /// Registers a method of the service with the specified name. Arguments of the method
+    /// in scenarios are deserialized into the type `A` and then converted
+    /// into the transaction payload with `BinaryValue::into_bytes`.
+    pub fn with_method<A>(
+        mut self,
+        service_name: &str,
+        method_name: &str,
+        method_id: MethodId,
+    ) -> Self
+    where
+        A: DeserializeOwned + BinaryValue,
+    {
+        let spec = MethodSpec {
+            id: method_id,
+            encode: encode_arguments::<A>,
+        };
+        self.methods
+            .insert((service_name.to_owned(), method_name.to_owned()), spec);
+        self
+    }
+
+    /// Returns the keypair of the signer with the specified name, if the signer
+    /// was used in the executed scenarios.
+    pub fn keypair(&self, signer: &str) -> Option<(PublicKey, SecretKey)> {
+        self.keypairs.get(signer).cloned()
+    }
+
+    /// Returns the hash of the named transaction sent by the executed scenarios.
+    pub fn transaction_hash(&self, name: &str) -> Option<Hash> {
+        self.transactions.get(name).cloned()
+    }
+
+    /// Loads the scenario from a YAML or JSON file and runs it. The format is determined
+    /// by the file extension, as described in [`Scenario::from_file`].
+    ///
+    /// [`Scenario::from_file`]: struct.Scenario.html#method.from_file
+    pub fn run_file(&mut self, path: impl AsRef<Path>) -> Result<(), failure::Error> {
+        let scenario = Scenario::from_file(path)?;
+        self.run(&scenario)
+    }
+
+    /// Runs the scenario. Execution stops on the first failed step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the failed step, e.g., if a transaction status differs
+    /// from the expected one or a service method is not registered in the runner.
+    pub fn run(&mut self, scenario: &Scenario) -> Result<(), failure::Error> {
+        for (i, step) in scenario.steps.iter().enumerate() {
+            self.run_step(step)
+                .map_err(|e| format_err!("Step #{} failed: {}", i + 1, e))?;
+        }
+        Ok(())
+    }
+
+    fn run_step(&mut self, step: &Step) -> Result<(), failure::Error> {
+        match step {
+            Step::Deploy { artifact, spec } => {
+                let artifact = parse_artifact(artifact)?;
+                let spec = decode_hex(spec)?;
+                self.testkit
+                    .deploy_artifact(artifact, spec)
+                    .map_err(execution_error)?;
+            }
+
+            Step::StartService {
+                artifact,
+                id,
+                name,
+                constructor,
+            } => {
+                let artifact = parse_artifact(artifact)?;
+                let constructor = decode_hex(constructor)?;
+                let params = InstanceInitParams::new(*id, name.as_str(), artifact, constructor);
+                self.testkit
+                    .start_service(params)
+                    .map_err(execution_error)?;
+            }
+
+            Step::Transaction(transaction) => self.send_transaction(transaction)?,
+            Step::CreateBlock { expect } => self.create_block(expect)?,
+        }
+        Ok(())
+    }
+
+    fn send_transaction(&mut self, step: &TransactionStep) -> Result<(), failure::Error> {
+        let key = (step.service.clone(), step.method.clone());
+        let method = self.methods.get(&key).ok_or_else(|| {
+            format_err!(
+                "Method `{}` of service `{}` is not registered in the scenario runner",
+                step.method,
+                step.service
+            )
+        })?;
+        let arguments = (method.encode)(step.arguments.clone())
+            .map_err(|e| format_err!("Invalid arguments of `{}`: {}", step.method, e))?;
+
+        let snapshot = self.testkit.snapshot();
+        let instance = snapshot
+            .for_dispatcher()
+            .get_instance(step.service.as_str())
+            .ok_or_else(|| format_err!("Service `{}` does not exist", step.service))?;
+
+        let (public_key, secret_key) = self
+            .keypairs
+            .entry(step.signer.clone())
+            .or_insert_with(gen_keypair);
+        let transaction = AnyTx {
+            call_info: CallInfo::new(instance.spec.id, method.id),
+            arguments,
+            valid_until: None,
+        }
+        .sign(*public_key, secret_key);
+
+        Blockchain::check_tx(&snapshot, &transaction)
+            .map_err(|e| format_err!("Transaction is rejected: {}", e))?;
+        if let Some(name) = &step.name {
+            ensure!(
+                !self.transactions.contains_key(name),
+                "Transaction name `{}` is already used",
+                name
+            );
+            self.transactions
+                .insert(name.clone(), transaction.object_hash());
+        }
+        self.testkit.add_tx(transaction);
+        Ok(())
+    }
+
+    fn create_block(&mut self, expect: &BTreeMap<String, String>) -> Result<(), failure::Error> {
+        let block = self.testkit.create_block();
+        for (name, expected_status) in expect {
+            let tx_hash = self
+                .transactions
+                .get(name)
+                .ok_or_else(|| format_err!("Unknown transaction `{}`", name))?;
+            let transaction = block
+                .transactions
+                .iter()
+                .find(|transaction| transaction.content().object_hash() == *tx_hash)
+                .ok_or_else(|| format_err!("Transaction `{}` is not in the block", name))?;
+            check_status(name, transaction.status(), expected_status)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_status(
+    name: &str,
+    status: Result<(), &ExecutionError>,
+    expected: &str,
+) -> Result<(), failure::Error> {
+    let matches = match (status, expected) {
+        (Ok(()), "ok") => true,
+        (Err(_), "error") => true,
+        (Err(err), kind) => err.kind().to_string() == kind,
+        (Ok(()), _) => false,
+    };
+    if !matches {
+        let actual = match status {
+            Ok(()) => "ok".to_owned(),
+            Err(err) => format!("{} ({})", err.kind(), err),
+        };
+        bail!(
+            "Transaction `{}` has status {}, expected {}",
+            name,
+            actual,
+            expected
+        );
+    }
+    Ok(())
+}
+
+fn parse_artifact(artifact: &str) -> Result<ArtifactId, failure::Error> {
+    artifact
+        .parse()
+        .map_err(|e| format_err!("Invalid artifact `{}`: {}", artifact, e))
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, failure::Error> {
+    hex::decode(value).map_err(|e| format_err!("Invalid hex value `{}`: {}", value, e))
+}
+
+fn execution_error(err: ExecutionError) -> failure::Error {
+    format_err!("{}", err)
+}
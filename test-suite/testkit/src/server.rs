@@ -13,21 +13,43 @@
 // limitations under the License.
 
 use actix::prelude::*;
-use exonum::{blockchain::ConsensusConfig, crypto::Hash, helpers::Height};
-use exonum_api::{self as api, ApiAggregator, ApiBuilder, FutureResult};
+use exonum::{
+    blockchain::{ConsensusConfig, Schema as CoreSchema},
+    crypto::Hash,
+    helpers::{Height, ValidateInput},
+};
+use exonum_api::{self as api, ApiAggregator, ApiBuilder, FutureResult, WebServerConfig};
 use exonum_explorer::{BlockWithTransactions, BlockchainExplorer};
 use futures::{sync::oneshot, Future};
 use serde::{Deserialize, Serialize};
 
-use std::thread::{self, JoinHandle};
+use std::{
+    fmt,
+    net::{SocketAddr, TcpListener},
+    thread::{self, JoinHandle},
+};
 
 use super::TestKit;
 
 #[derive(Debug)]
 pub struct TestKitActor(TestKit);
 
+/// Handle to the thread running the testkit actor.
+pub(crate) struct TestKitActorHandle {
+    system: System,
+    join_handle: JoinHandle<i32>,
+}
+
+impl TestKitActorHandle {
+    /// Stops the `actix` system running the actor and waits for the thread to finish.
+    pub fn stop(self) {
+        self.system.stop();
+        self.join_handle.join().unwrap();
+    }
+}
+
 impl TestKitActor {
-    pub(crate) fn spawn(mut testkit: TestKit) -> (ApiAggregator, JoinHandle<i32>) {
+    pub(crate) fn spawn(mut testkit: TestKit) -> (ApiAggregator, TestKitActorHandle) {
         let mut api_aggregator = testkit.update_aggregator();
 
         // Spawn the testkit actor on the new `actix` system.
@@ -35,13 +57,17 @@ impl TestKitActor {
         let join_handle = thread::spawn(|| {
             let system = System::new("testkit");
             let testkit = Self(testkit).start();
-            actor_tx.send(testkit).unwrap();
+            actor_tx.send((testkit, System::current())).unwrap();
             system.run()
         });
 
-        let testkit = actor_rx.wait().expect("Failed spawning testkit server");
+        let (testkit, system) = actor_rx.wait().expect("Failed spawning testkit server");
         api_aggregator.insert("testkit", Self::api(testkit));
-        (api_aggregator, join_handle)
+        let handle = TestKitActorHandle {
+            system,
+            join_handle,
+        };
+        (api_aggregator, handle)
     }
 
     fn api(addr: Addr<Self>) -> ApiBuilder {
@@ -60,6 +86,10 @@ impl TestKitActor {
         api_scope.endpoint_mut("v1/blocks/create", move |query: CreateBlock| {
             Box::new(addr_.send(query).then(flatten_err)) as FutureResult<_>
         });
+        let addr_ = addr.clone();
+        api_scope.endpoint_mut("v1/network/validators", move |query: SetValidatorCount| {
+            Box::new(addr_.send(query).then(flatten_err)) as FutureResult<_>
+        });
         builder
     }
 }
@@ -167,6 +197,153 @@ impl Handler<RollBack> for TestKitActor {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetValidatorCount {
+    validator_count: u16,
+}
+
+impl Message for SetValidatorCount {
+    type Result = api::Result<TestKitStatus>;
+}
+
+impl Handler<SetValidatorCount> for TestKitActor {
+    type Result = api::Result<TestKitStatus>;
+
+    fn handle(&mut self, msg: SetValidatorCount, _ctx: &mut Self::Context) -> Self::Result {
+        let validator_count = msg.validator_count as usize;
+        let mut config = self.0.consensus_config();
+        // Retained validators keep their keys; missing validators are promoted
+        // from new auditor nodes.
+        config.validator_keys.truncate(validator_count);
+        while config.validator_keys.len() < validator_count {
+            let keys = self.0.network_mut().add_node().public_keys();
+            config.validator_keys.push(keys);
+        }
+        config
+            .validate()
+            .map_err(|e| api::Error::BadRequest(e.to_string()))?;
+
+        // NB: checkpoints must correspond 1-to-1 to blocks, so the configuration change
+        // is committed together with a new block and can be rolled back with it.
+        self.0.checkpoint();
+        let fork = self.0.blockchain.fork();
+        CoreSchema::new(&fork)
+            .consensus_config_entry()
+            .set(config.clone());
+        self.0
+            .blockchain
+            .merge(fork.into_patch())
+            .map_err(|e| api::Error::InternalError(e.into()))?;
+        self.0.network_mut().update_consensus_config(config);
+        self.0.create_block();
+
+        Ok(TestKitStatus {
+            height: self.0.height(),
+            configuration: self.0.consensus_config(),
+        })
+    }
+}
+
+/// Testkit web server running in a background thread. The server is created with
+/// [`TestKitBuilder::spawn_server`] and is stopped when the handle is dropped.
+///
+/// The server listens on free ports of the loopback interface assigned automatically,
+/// so that several servers can run in parallel (e.g., in different tests of the same
+/// test binary) without port collisions. Each server operates on its own temporary database.
+///
+/// [`TestKitBuilder::spawn_server`]: struct.TestKitBuilder.html#method.spawn_server
+pub struct TestKitServer {
+    public_api_address: SocketAddr,
+    private_api_address: SocketAddr,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for TestKitServer {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("TestKitServer")
+            .field("public_api_address", &self.public_api_address)
+            .field("private_api_address", &self.private_api_address)
+            .finish()
+    }
+}
+
+impl TestKitServer {
+    pub(crate) fn spawn(testkit: TestKit) -> Self {
+        // Listeners are bound before spawning the server, so that the ports assigned by the OS
+        // remain reserved for the server; connections are queued until the server starts.
+        let public_api = local_server_config();
+        let private_api = local_server_config();
+        let public_api_address = public_api.listen_address;
+        let private_api_address = private_api.listen_address;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let join_handle = thread::spawn(move || {
+            let shutdown = shutdown_rx.then(|_| Ok(()));
+            testkit.run_until(public_api, private_api, shutdown);
+        });
+
+        Self {
+            public_api_address,
+            private_api_address,
+            shutdown_tx: Some(shutdown_tx),
+            join_handle: Some(join_handle),
+        }
+    }
+
+    /// Returns the address of the public API server.
+    pub fn public_api_address(&self) -> SocketAddr {
+        self.public_api_address
+    }
+
+    /// Returns the address of the private API server.
+    pub fn private_api_address(&self) -> SocketAddr {
+        self.private_api_address
+    }
+
+    /// Returns the root URL of the public API, such as `http://127.0.0.1:34567/api/`.
+    pub fn public_api_url(&self) -> String {
+        format!("http://{}/api/", self.public_api_address)
+    }
+
+    /// Returns the root URL of the private API, such as `http://127.0.0.1:34568/api/`.
+    /// The testkit API is available with the `testkit/` prefix relative to this URL.
+    pub fn private_api_url(&self) -> String {
+        format!("http://{}/api/", self.private_api_address)
+    }
+
+    /// Stops the server and waits until it is shut down.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            // The server thread may have already terminated, in which case
+            // the receiver is dropped and sending fails.
+            shutdown_tx.send(()).ok();
+        }
+        if let Some(join_handle) = self.join_handle.take() {
+            if join_handle.join().is_err() && !thread::panicking() {
+                panic!("Testkit server thread has panicked");
+            }
+        }
+    }
+}
+
+impl Drop for TestKitServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Returns the configuration of a web server listening on a free port of the loopback interface.
+fn local_server_config() -> WebServerConfig {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Cannot find a free port");
+    WebServerConfig::from_listener(listener).expect("Cannot get address of the listener")
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -385,4 +562,56 @@ mod tests {
             api::Error::BadRequest(ref body) if body == "Cannot rollback past genesis block"
         );
     }
+
+    #[test]
+    fn test_set_validator_count() {
+        let api = init_handler(Height(2));
+        let status: TestKitStatus = api.private("api/testkit").get("v1/status").unwrap();
+        let initial_keys = status.configuration.validator_keys;
+
+        let status: TestKitStatus = api
+            .private("api/testkit")
+            .query(&SetValidatorCount { validator_count: 4 })
+            .post("v1/network/validators")
+            .unwrap();
+        assert_eq!(status.height, Height(3));
+        let validator_keys = status.configuration.validator_keys;
+        assert_eq!(validator_keys.len(), 4);
+        assert_eq!(validator_keys[..1], initial_keys[..]);
+
+        let block: BlockWithTransactions = api
+            .private("api/testkit")
+            .query(&CreateBlock { tx_hashes: None })
+            .post("v1/blocks/create")
+            .unwrap();
+        assert_eq!(block.height(), Height(4));
+
+        let status: TestKitStatus = api
+            .private("api/testkit")
+            .query(&SetValidatorCount { validator_count: 2 })
+            .post("v1/network/validators")
+            .unwrap();
+        assert_eq!(status.height, Height(5));
+        // Retained validators keep their keys.
+        assert_eq!(
+            status.configuration.validator_keys,
+            validator_keys[..2].to_vec()
+        );
+
+        // The configuration change is rolled back together with the block.
+        api.private("api/testkit")
+            .query(&Height(4))
+            .post::<BlockWithTransactions>("v1/blocks/rollback")
+            .unwrap();
+        let status: TestKitStatus = api.private("api/testkit").get("v1/status").unwrap();
+        assert_eq!(status.height, Height(3));
+        assert_eq!(status.configuration.validator_keys, validator_keys);
+
+        let err = api
+            .private("api/testkit")
+            .query(&SetValidatorCount { validator_count: 0 })
+            .post::<TestKitStatus>("v1/network/validators")
+            .unwrap_err();
+        assert_matches!(err, api::Error::BadRequest(_));
+    }
 }
@@ -0,0 +1,85 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic time source for the testkit.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use std::fmt;
+
+/// Function notified about changes of the testkit time.
+pub(crate) type TimeObserver = Box<dyn Fn(DateTime<Utc>) + Send + Sync>;
+
+/// Clock controlled by the testkit.
+///
+/// The clock does not tick on its own; its time only changes when it is set explicitly,
+/// which makes tests of time-dependent logic deterministic. Time-dependent services
+/// (e.g., a time oracle with a mock time provider) learn about the changes via observers
+/// registered with `TestKitBuilder::with_time_observer`.
+pub(crate) struct TestKitClock {
+    time: DateTime<Utc>,
+    observers: Vec<TimeObserver>,
+}
+
+impl fmt::Debug for TestKitClock {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("TestKitClock")
+            .field("time", &self.time)
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+impl Default for TestKitClock {
+    /// Initializes the clock with the time set to the Unix epoch start.
+    fn default() -> Self {
+        Self::new(Utc.timestamp(0, 0))
+    }
+}
+
+impl TestKitClock {
+    /// Creates a clock with the specified initial time.
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self {
+            time,
+            observers: vec![],
+        }
+    }
+
+    /// Adds an observer to the clock. The observer is immediately notified about
+    /// the current time of the clock.
+    pub fn add_observer(&mut self, observer: TimeObserver) {
+        observer(self.time);
+        self.observers.push(observer);
+    }
+
+    /// Returns the current time of the clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    /// Sets the time of the clock and notifies the observers.
+    pub fn set(&mut self, time: DateTime<Utc>) {
+        self.time = time;
+        for observer in &self.observers {
+            observer(time);
+        }
+    }
+
+    /// Moves the clock forward by the specified `duration`.
+    pub fn advance(&mut self, duration: Duration) {
+        self.set(self.time + duration);
+    }
+}
@@ -16,8 +16,9 @@
 
 use assert_matches::assert_matches;
 use exonum_api::Error as ApiError;
-use exonum_testkit::{ApiKind, TestKit, TestKitApi};
+use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder, TestKitStatus};
 use pretty_assertions::assert_eq;
+use reqwest::{Method, StatusCode};
 
 use crate::api_service::{ApiService, PingQuery, SERVICE_ID, SERVICE_NAME};
 
@@ -131,3 +132,93 @@ fn moved() {
         .expect_err("Request to the `MovedPermanently` endpoint succeed");
     assert_matches!(pong_error, ApiError::MovedPermanently(_));
 }
+
+/// Checks raw requests with custom methods, headers and bodies.
+#[test]
+fn raw_requests() {
+    let (_testkit, api) = init_testkit();
+
+    let ping = PingQuery { value: 64 };
+    let response = api
+        .public(ApiKind::Service("api-service"))
+        .query(&ping)
+        .header("X-Custom-Header", "value")
+        .bearer_auth("token")
+        .send(Method::GET, "ping-pong-deprecated");
+    response
+        .assert_status(StatusCode::OK)
+        .assert_header("Content-Type", "application/json");
+    assert!(response.header("Warning").is_some());
+    assert_eq!(response.json::<u64>().unwrap(), 64);
+
+    // The body is not a valid JSON.
+    let response = api
+        .public(ApiKind::Service("api-service"))
+        .raw_body("value=64", "text/plain")
+        .send(Method::POST, "ping-pong-deprecated-mut");
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let response = api
+        .public(ApiKind::Service("api-service"))
+        .raw_body(r#"{ "value": 64 }"#, "application/json")
+        .send(Method::POST, "ping-pong-deprecated-mut");
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "64");
+
+    let response = api
+        .public(ApiKind::Service("api-service"))
+        .send(Method::GET, "unknown-endpoint");
+    response.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn parallel_servers() {
+    let servers: Vec<_> = (0..2)
+        .map(|_| {
+            TestKitBuilder::validator()
+                .with_default_rust_service(ApiService)
+                .spawn_server()
+        })
+        .collect();
+    assert_ne!(
+        servers[0].public_api_address(),
+        servers[1].public_api_address()
+    );
+
+    // Create a block on the first server only; the second one should be unaffected.
+    let client = reqwest::Client::new();
+    let url = format!("{}testkit/v1/blocks/create", servers[0].private_api_url());
+    let response = client
+        .post(&url)
+        .json(&serde_json::json!({}))
+        .send()
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let heights: Vec<_> = servers
+        .iter()
+        .map(|server| {
+            let url = format!("{}testkit/v1/status", server.private_api_url());
+            let status: TestKitStatus = client.get(&url).send().unwrap().json().unwrap();
+            status.height.0
+        })
+        .collect();
+    assert_eq!(heights, vec![1, 0]);
+
+    let url = format!(
+        "{}services/api-service/ping-pong",
+        servers[1].public_api_url()
+    );
+    let pong: u64 = client
+        .get(&url)
+        .query(&PingQuery { value: 10 })
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(pong, 10);
+
+    for server in servers {
+        server.stop();
+    }
+}
@@ -117,8 +117,8 @@ impl CounterServiceInterface<CallContext<'_>> for CounterService {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CounterWithProof {
-    counter: Option<u64>,
-    proof: IndexProof,
+    pub counter: Option<u64>,
+    pub proof: IndexProof,
 }
 
 impl CounterWithProof {
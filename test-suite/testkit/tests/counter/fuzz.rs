@@ -0,0 +1,72 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property-based tests of the counter service using the `fuzz` module of the testkit.
+
+use exonum::crypto::gen_keypair;
+use exonum_testkit::{fuzz, TestKit};
+use proptest::{prelude::*, prop_assert};
+
+use crate::{
+    counter::{CounterService, CounterServiceInterface, SERVICE_ID, SERVICE_NAME},
+    get_schema,
+};
+
+fn init_testkit() -> TestKit {
+    TestKit::for_rust_service(CounterService, SERVICE_NAME, SERVICE_ID, ())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn counter_invariants(
+        blocks in fuzz::blocks(
+            fuzz::signed(prop_oneof![
+                fuzz::call(SERVICE_ID, 0, 0_u64..1_000),
+                fuzz::call(SERVICE_ID, 1, Just(())),
+                fuzz::malformed_call(SERVICE_ID, 0..4),
+                // Calls to a non-existing service are skipped.
+                fuzz::malformed_call(SERVICE_ID + 1, 0..2),
+            ]),
+            1..5,
+            0..8,
+        )
+    ) {
+        let mut testkit = init_testkit();
+        let mut height = testkit.height();
+        fuzz::check_blocks(&mut testkit, blocks, |snapshot| {
+            let counter = get_schema(snapshot).counter.get().unwrap_or(0);
+            prop_assert!(counter < 1_000 * 5 * 8);
+            height = height.next();
+            Ok(())
+        })?;
+        prop_assert!(testkit.height() == height);
+    }
+}
+
+#[test]
+fn panicking_transaction_is_reported() {
+    let keypair = gen_keypair();
+    let blocks = vec![
+        vec![keypair.increment(SERVICE_ID, u64::max_value())],
+        vec![keypair.increment(SERVICE_ID, 1)],
+    ];
+
+    let mut testkit = init_testkit();
+    let err = fuzz::check_blocks(&mut testkit, blocks, |_| Ok(())).unwrap_err();
+    assert!(err.to_string().contains("has panicked"));
+    let snapshot = testkit.snapshot();
+    assert_eq!(get_schema(&snapshot).counter.get(), Some(u64::max_value()));
+}
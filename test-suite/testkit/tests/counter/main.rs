@@ -12,26 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use assert_matches::assert_matches;
 use exonum::{
-    blockchain::{CallInBlock, ValidatorKeys},
-    crypto::{gen_keypair, Hash},
-    helpers::Height,
+    blockchain::{Blockchain, CallInBlock, ValidatorKeys},
+    crypto::{gen_keypair, Hash, PublicKey},
+    helpers::{Height, ValidatorId},
     messages::{AnyTx, Verified},
-    runtime::SnapshotExt,
+    runtime::{ArtifactStatus, CommonError, CoreError, ErrorMatch, InstanceStatus, SnapshotExt},
+};
+use exonum_api::Error as ApiError;
+use exonum_explorer::{
+    api::{BlockQuery, BlocksQuery, CallStatusQuery, TransactionQuery, TransactionResponse},
+    BlockchainExplorer,
+};
+use exonum_merkledb::{
+    access::{Access, AccessExt},
+    HashTag, ObjectHash, Snapshot,
+};
+use exonum_rust_runtime::{DefaultInstance, RustRuntime, ServiceFactory};
+use exonum_testkit::{
+    scenario::{Scenario, ScenarioRunner},
+    ApiKind, BenchOptions, ByzantineBehavior, EntryChanges, IndexChange, MockOracle,
+    MockOracleSchema, NetworkFaults, TestKit, TestKitApi, TestKitBuilder, TestNode,
 };
-use exonum_explorer::{api::TransactionResponse, BlockchainExplorer};
-use exonum_merkledb::{access::Access, HashTag, ObjectHash, Snapshot};
-use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder, TestNode};
 use pretty_assertions::assert_eq;
 
-use std::collections::HashSet;
+use std::{collections::HashSet, env, fs, panic, path::PathBuf, process};
 
 use crate::counter::{
-    CounterSchema, CounterService, CounterServiceInterface, CounterWithProof, SERVICE_ID,
+    CounterSchema, CounterService, CounterServiceInterface, CounterWithProof, Error, SERVICE_ID,
     SERVICE_NAME,
 };
 
 mod counter;
+#[cfg(feature = "fuzz")]
+mod fuzz;
 
 fn init_testkit() -> (TestKit, TestKitApi) {
     let mut testkit = TestKit::for_rust_service(CounterService, SERVICE_NAME, SERVICE_ID, ());
@@ -220,6 +235,161 @@ fn test_inc_count_with_manual_tx_control() {
     assert_eq!(counter, 8);
 }
 
+#[test]
+fn test_pool_inspection_and_ordering() {
+    let (mut testkit, _) = init_testkit();
+    let inc_tx = gen_inc_tx(5);
+    let reset_tx = gen_keypair().reset(SERVICE_ID, ());
+    testkit.add_tx(inc_tx.clone());
+    testkit.add_tx(reset_tx.clone());
+
+    let pool: HashSet<_> = testkit
+        .pool_transactions()
+        .iter()
+        .map(ObjectHash::object_hash)
+        .collect();
+    let expected_pool: HashSet<_> = vec![inc_tx.object_hash(), reset_tx.object_hash()]
+        .into_iter()
+        .collect();
+    assert_eq!(pool, expected_pool);
+
+    // The outcome depends on the order of transactions in the block.
+    testkit.checkpoint();
+    let block =
+        testkit.create_block_with_tx_hashes(&[inc_tx.object_hash(), reset_tx.object_hash()]);
+    assert_eq!(block[0].content().object_hash(), inc_tx.object_hash());
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(0));
+    testkit.rollback();
+
+    testkit.checkpoint();
+    testkit.create_block_with_tx_hashes(&[reset_tx.object_hash(), inc_tx.object_hash()]);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(5));
+    testkit.rollback();
+
+    // Removed transactions do not get into blocks.
+    let removed_tx = testkit.remove_tx(&reset_tx.object_hash()).unwrap();
+    assert_eq!(removed_tx.object_hash(), reset_tx.object_hash());
+    assert!(!testkit.is_tx_in_pool(&reset_tx.object_hash()));
+    assert!(testkit.remove_tx(&reset_tx.object_hash()).is_none());
+    let pool = testkit.pool_transactions();
+    assert_eq!(pool.len(), 1);
+    assert_eq!(pool[0].object_hash(), inc_tx.object_hash());
+
+    let block = testkit.create_block();
+    assert_eq!(block.len(), 1);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(5));
+
+    // A removed transaction can be added to the pool again.
+    testkit.add_tx(reset_tx.clone());
+    assert!(testkit.is_tx_in_pool(&reset_tx.object_hash()));
+}
+
+#[test]
+#[should_panic(expected = "is not in the pool")]
+fn test_create_block_with_unknown_tx_hash() {
+    let (mut testkit, _) = init_testkit();
+    testkit.create_block_with_tx_hashes(&[gen_inc_tx(1).object_hash()]);
+}
+
+fn scenario_runner(testkit: &mut TestKit) -> ScenarioRunner<'_> {
+    ScenarioRunner::new(testkit)
+        .with_method::<u64>(SERVICE_NAME, "increment", 0)
+        .with_method::<()>(SERVICE_NAME, "reset", 1)
+}
+
+fn check_scenario_from_file(file_name: &str) {
+    let (mut testkit, _) = init_testkit();
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/counter/scenarios")
+        .join(file_name);
+
+    let mut runner = scenario_runner(&mut testkit);
+    runner.run_file(&path).unwrap();
+    let reset_hash = runner.transaction_hash("reset").unwrap();
+    let alice_key = runner.keypair("alice").unwrap().0;
+
+    assert_eq!(testkit.height(), Height(3));
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(3));
+    let explorer = testkit.explorer();
+    let tx_info = explorer.inner().transaction(&reset_hash).unwrap();
+    assert_eq!(tx_info.content().author(), alice_key);
+}
+
+#[test]
+fn test_scenario_from_json_file() {
+    check_scenario_from_file("increment_and_reset.json");
+}
+
+#[test]
+fn test_scenario_from_yaml_file() {
+    check_scenario_from_file("increment_and_reset.yaml");
+}
+
+#[test]
+fn test_scenario_with_wrong_expectation() {
+    let (mut testkit, _) = init_testkit();
+    let scenario: Scenario = serde_json::from_value(serde_json::json!({
+        "steps": [
+            {
+                "transaction": {
+                    "name": "inc",
+                    "signer": "alice",
+                    "service": SERVICE_NAME,
+                    "method": "increment",
+                    "arguments": 0,
+                }
+            },
+            { "create_block": { "expect": { "inc": "ok" } } },
+        ]
+    }))
+    .unwrap();
+
+    let err = scenario_runner(&mut testkit).run(&scenario).unwrap_err();
+    let message = err.to_string();
+    assert!(message.starts_with("Step #2 failed"), "{}", message);
+    assert!(message.contains("has status service:0"), "{}", message);
+
+    // Methods not registered in the runner are reported.
+    let scenario: Scenario = serde_json::from_value(serde_json::json!({
+        "steps": [{
+            "transaction": {
+                "signer": "alice",
+                "service": SERVICE_NAME,
+                "method": "decrement",
+            }
+        }]
+    }))
+    .unwrap();
+    let err = scenario_runner(&mut testkit).run(&scenario).unwrap_err();
+    assert!(err.to_string().contains("is not registered"));
+}
+
+#[test]
+fn test_state_diff() {
+    let (mut testkit, _) = init_testkit();
+    testkit.create_block_with_transaction(gen_inc_tx(5));
+
+    let before = testkit.snapshot();
+    testkit.create_block_with_transaction(gen_inc_tx(3));
+    let diff = testkit.diff_since(before.as_ref());
+    let counter_diff = diff.indexes["counter.counter"];
+    assert_eq!(counter_diff.change, IndexChange::Modified);
+    let expected_changes = EntryChanges {
+        added: 0,
+        modified: 1,
+        removed: 0,
+    };
+    assert_eq!(counter_diff.entries, expected_changes);
+    assert_eq!(diff.namespaces()["counter"], expected_changes);
+    assert!(diff
+        .to_string()
+        .contains("counter.counter (Modified): +0 ~1 -0"));
+
+    // Snapshots of the same state do not differ.
+    let snapshot = testkit.snapshot();
+    assert!(testkit.diff_since(snapshot.as_ref()).is_empty());
+}
+
 #[test]
 fn test_private_api() {
     let (mut testkit, api) = init_testkit();
@@ -279,6 +449,35 @@ fn counter_proof_with_mauled_value() {
     counter.verify(&get_validator_keys(&testkit));
 }
 
+#[test]
+fn test_verify_proofs() {
+    let (mut testkit, api) = init_testkit();
+    inc_count(&api, 5);
+    testkit.create_block();
+
+    let mut counter: CounterWithProof = api
+        .public(ApiKind::Service("counter"))
+        .get("count-with-proof")
+        .unwrap();
+    testkit
+        .verify_block_proof(&counter.proof.block_proof)
+        .unwrap();
+    let index_hash = testkit
+        .verify_index_proof(&counter.proof, "counter.counter")
+        .unwrap();
+    assert_eq!(index_hash, 5_u64.object_hash());
+
+    let err = testkit
+        .verify_index_proof(&counter.proof, "counter.other")
+        .unwrap_err();
+    assert!(err.to_string().contains("expected `counter.other`"));
+
+    counter.remove_precommits();
+    assert!(testkit
+        .verify_block_proof(&counter.proof.block_proof)
+        .is_err());
+}
+
 #[test]
 fn test_duplicate_tx() {
     let (mut testkit, api) = init_testkit();
@@ -295,6 +494,21 @@ fn test_duplicate_tx() {
     assert_eq!(counter, 5);
 }
 
+#[test]
+fn test_transaction_status_matching() {
+    let (mut testkit, _) = init_testkit();
+    let block = testkit.create_block_with_transactions(vec![gen_inc_tx(0), gen_inc_tx(5)]);
+
+    let expected_err = ErrorMatch::from_fail(&Error::AddingZero).for_service(SERVICE_ID);
+    assert_eq!(block[0].status(), expected_err);
+    assert_ne!(block[0].status(), expected_err.for_service(SERVICE_ID + 1));
+    assert_ne!(
+        block[1].status(),
+        ErrorMatch::from_fail(&Error::AddingZero).for_service(SERVICE_ID)
+    );
+    block[1].status().unwrap();
+}
+
 #[test]
 fn test_explorer_with_after_transactions_error() {
     let (mut testkit, _) = init_testkit();
@@ -305,9 +519,10 @@ fn test_explorer_with_after_transactions_error() {
     let block = testkit.create_block_with_transactions(vec![tx1, tx2]);
     let errors = block.error_map();
     assert_eq!(errors.len(), 1);
-    assert!(errors[&CallInBlock::after_transactions(SERVICE_ID)]
-        .description()
-        .contains("What's the question?"));
+    assert_eq!(
+        *errors[&CallInBlock::after_transactions(SERVICE_ID)],
+        ErrorMatch::from_fail(&Error::AnswerToTheUltimateQuestion).for_service(SERVICE_ID)
+    );
     assert_ne!(block.header.error_hash, HashTag::empty_map_hash());
 
     let tx3 = keypair.increment(SERVICE_ID, 1);
@@ -382,3 +597,497 @@ fn test_explorer_single_block() {
 
     assert!(validators.len() >= testkit.majority_count());
 }
+
+#[test]
+fn test_explorer_facade() {
+    let (mut testkit, _) = init_testkit();
+    let tx = gen_inc_tx(21);
+    let failed_tx = gen_inc_tx(0);
+    testkit.create_block_with_transactions(vec![tx.clone(), failed_tx.clone()]);
+    testkit.create_blocks_until(Height(3));
+    let pending_tx = gen_inc_tx(1);
+    testkit.add_tx(pending_tx.clone());
+
+    let explorer = testkit.explorer();
+    let query = BlocksQuery {
+        count: 10,
+        skip_empty_blocks: true,
+        ..BlocksQuery::default()
+    };
+    let range = explorer.blocks(query).unwrap();
+    assert_eq!(range.range, Height(0)..Height(4));
+    assert_eq!(range.blocks.len(), 1);
+    assert_eq!(range.blocks[0].block.height, Height(1));
+
+    let query = BlocksQuery {
+        count: 1,
+        latest: Some(Height(4)),
+        ..BlocksQuery::default()
+    };
+    assert_matches!(explorer.blocks(query), Err(ApiError::NotFound(_)));
+
+    let block = explorer.block(BlockQuery::new(Height(1))).unwrap();
+    let txs = block.txs.unwrap();
+    assert_eq!(txs.len(), 2);
+    assert_eq!(txs[0].tx_hash, tx.object_hash());
+
+    let info = explorer
+        .transaction_info(TransactionQuery::new(tx.object_hash()))
+        .unwrap();
+    let committed = info.as_committed().unwrap();
+    assert_eq!(committed.location().block_height(), Height(1));
+    committed.status().unwrap();
+    let info = explorer
+        .transaction_info(TransactionQuery::new(pending_tx.object_hash()))
+        .unwrap();
+    assert!(info.is_in_pool());
+    let unknown_hash = gen_inc_tx(2).object_hash();
+    assert_matches!(
+        explorer.transaction_info(TransactionQuery::new(unknown_hash)),
+        Err(ApiError::NotFound(_))
+    );
+
+    let response = explorer
+        .transaction_status(TransactionQuery::new(failed_tx.object_hash()))
+        .unwrap();
+    assert_eq!(
+        response.status.0,
+        ErrorMatch::from_fail(&Error::AddingZero).for_service(SERVICE_ID)
+    );
+    assert_matches!(
+        explorer.transaction_status(TransactionQuery::new(pending_tx.object_hash())),
+        Err(ApiError::NotFound(_))
+    );
+
+    let query = CallStatusQuery {
+        height: Height(1),
+        service_id: SERVICE_ID,
+    };
+    explorer
+        .before_transactions_status(query.clone())
+        .unwrap()
+        .status
+        .0
+        .unwrap();
+    explorer
+        .after_transactions_status(query)
+        .unwrap()
+        .status
+        .0
+        .unwrap();
+}
+
+#[test]
+fn test_network_faults_loss() {
+    let (mut testkit, api) = init_testkit();
+    testkit.set_network_faults(Some(NetworkFaults::new(0).with_loss(1.0)));
+
+    let tx_hash = inc_count(&api, 5);
+    testkit.create_block();
+    assert!(!testkit.is_tx_in_pool(&tx_hash));
+    let snapshot = testkit.snapshot();
+    assert_eq!(get_schema(&snapshot).counter.get(), None);
+
+    // After the network is healed, transactions are delivered again.
+    testkit.set_network_faults(None);
+    inc_count(&api, 3);
+    testkit.create_block();
+    let snapshot = testkit.snapshot();
+    assert_eq!(get_schema(&snapshot).counter.get(), Some(3));
+}
+
+#[test]
+fn test_network_faults_apply_to_pool() {
+    let (mut testkit, _) = init_testkit();
+    testkit.set_network_faults(Some(NetworkFaults::new(0).with_loss(1.0)));
+
+    let tx = gen_inc_tx(5);
+    testkit.add_tx(tx.clone());
+    assert!(!testkit.is_tx_in_pool(&tx.object_hash()));
+    testkit.create_block();
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), None);
+
+    // Transactions explicitly included into a block are not affected.
+    testkit.create_block_with_transaction(tx);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(5));
+}
+
+#[test]
+fn test_network_faults_delay() {
+    let faults = NetworkFaults::new(42)
+        .with_duplication(0.5)
+        .with_max_delay(3);
+    let mut testkit = TestKitBuilder::validator()
+        .with_default_rust_service(CounterService)
+        .with_network_faults(faults)
+        .create();
+    let api = testkit.api();
+
+    for by in 1..=10 {
+        inc_count(&api, by);
+    }
+    // Every transaction is delivered after at most 3 blocks, and duplicates are ignored.
+    testkit.create_blocks_until(Height(4));
+    let snapshot = testkit.snapshot();
+    assert_eq!(get_schema(&snapshot).counter.get(), Some(55));
+    assert_eq!(snapshot.for_core().transactions_pool_len(), 0);
+}
+
+#[test]
+fn test_create_blocks_until_condition() {
+    let faults = NetworkFaults::new(7).with_max_delay(3);
+    let mut testkit = TestKitBuilder::validator()
+        .with_default_rust_service(CounterService)
+        .with_network_faults(faults)
+        .create();
+    let api = testkit.api();
+
+    for by in 1..=10 {
+        inc_count(&api, by);
+    }
+    let height = testkit.create_blocks_until_condition(Height(10), |snapshot| {
+        get_schema(snapshot).counter.get() == Some(55)
+    });
+    assert_eq!(height, testkit.height());
+    assert!(height <= Height(4));
+
+    // No blocks are created if the condition is already satisfied.
+    let same_height = testkit.create_blocks_until_condition(Height(10), |_| true);
+    assert_eq!(same_height, height);
+}
+
+#[test]
+#[should_panic(expected = "Condition is not satisfied at height 3")]
+fn test_create_blocks_until_unsatisfiable_condition() {
+    let (mut testkit, _) = init_testkit();
+    testkit.create_blocks_until_condition(Height(3), |_| false);
+}
+
+#[test]
+fn test_rollback_restores_network_state() {
+    let (mut testkit, _) = init_testkit();
+    let tx = gen_inc_tx(5);
+    let other_tx = gen_inc_tx(3);
+
+    testkit.checkpoint();
+    testkit.set_network_faults(Some(NetworkFaults::new(0).with_loss(1.0)));
+    testkit.create_block_with_transactions(vec![tx.clone(), other_tx.clone()]);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(8));
+    testkit.rollback();
+
+    // Verify that neither the block nor the network faults are retained.
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), None);
+    assert_eq!(testkit.height(), Height(0));
+    testkit.add_tx(other_tx.clone());
+    assert!(testkit.is_tx_in_pool(&other_tx.object_hash()));
+
+    testkit.checkpoint();
+    testkit.create_block_with_transaction(tx);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(5));
+    testkit.rollback();
+    assert!(testkit.is_tx_in_pool(&other_tx.object_hash()));
+}
+
+#[test]
+fn test_byzantine_validators() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_default_rust_service(CounterService)
+        .create();
+    testkit
+        .network_mut()
+        .make_byzantine(ValidatorId(2), ByzantineBehavior::WithholdPrecommits);
+    testkit
+        .network_mut()
+        .make_byzantine(ValidatorId(3), ByzantineBehavior::ConflictingPrecommits);
+
+    let block = testkit.create_block_with_transaction(gen_inc_tx(5));
+    assert_eq!(block.precommits.len(), 3);
+    let conflicting_precommits = testkit.conflicting_precommits(Height(1));
+    assert_eq!(conflicting_precommits.len(), 1);
+    let precommit = &conflicting_precommits[0];
+    assert_eq!(precommit.payload().validator(), ValidatorId(3));
+    assert_eq!(precommit.payload().height(), Height(1));
+    assert_ne!(
+        *precommit.payload().block_hash(),
+        block.header.object_hash()
+    );
+
+    // Conflicting precommits are rolled back together with blocks.
+    testkit.checkpoint();
+    testkit.create_block();
+    assert_eq!(testkit.conflicting_precommits(Height(2)).len(), 1);
+    testkit.rollback();
+    assert!(testkit.conflicting_precommits(Height(2)).is_empty());
+
+    let consensus_key = testkit
+        .validator(ValidatorId(3))
+        .public_keys()
+        .consensus_key;
+    testkit.network_mut().make_honest(consensus_key);
+    let block = testkit.create_block();
+    assert_eq!(block.precommits.len(), 3);
+    assert!(testkit.conflicting_precommits(Height(2)).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "byzantine validators withhold too many precommits")]
+fn test_byzantine_validators_halting_network() {
+    let mut testkit = TestKitBuilder::validator().with_validators(4).create();
+    for i in 2..4 {
+        testkit
+            .network_mut()
+            .make_byzantine(ValidatorId(i), ByzantineBehavior::WithholdPrecommits);
+    }
+    testkit.create_block();
+}
+
+#[test]
+fn test_bench_block() {
+    let (mut testkit, _) = init_testkit();
+    let transactions: Vec<_> = (1..=10).map(gen_inc_tx).collect();
+
+    let options = BenchOptions::new(3).with_warmup(1);
+    let benchmark = testkit.bench_block_with_options(transactions.clone(), options);
+    assert_eq!(benchmark.transactions, 10);
+    assert_eq!(benchmark.iterations, 3);
+    for stats in &[benchmark.execution, benchmark.commit] {
+        assert!(stats.min <= stats.median && stats.median <= stats.max);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+    }
+
+    // The benchmark does not change the blockchain state.
+    assert_eq!(testkit.height(), Height(0));
+    let snapshot = testkit.snapshot();
+    assert_eq!(get_schema(&snapshot).counter.get(), None);
+    assert_eq!(snapshot.for_core().transactions_pool_len(), 0);
+
+    // Transactions can be committed after the benchmark.
+    testkit.create_block_with_transactions(transactions);
+    let snapshot = testkit.snapshot();
+    assert_eq!(get_schema(&snapshot).counter.get(), Some(55));
+}
+
+#[test]
+fn test_nested_checkpoints() {
+    let (mut testkit, _) = init_testkit();
+
+    testkit.checkpoint();
+    testkit.create_block_with_transaction(gen_inc_tx(1));
+    testkit.checkpoint();
+    testkit.create_block_with_transaction(gen_inc_tx(2));
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(3));
+
+    testkit.rollback();
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(1));
+    assert_eq!(testkit.height(), Height(1));
+    testkit.rollback();
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), None);
+    assert_eq!(testkit.height(), Height(0));
+}
+
+#[test]
+fn test_stop_and_restart_service() {
+    let (mut testkit, _) = init_testkit();
+    testkit.create_block_with_transaction(gen_inc_tx(5));
+
+    let block = testkit.stop_service(SERVICE_ID).unwrap();
+    assert!(block.transactions.is_empty());
+    let snapshot = testkit.snapshot();
+    let state = snapshot.for_dispatcher().get_instance(SERVICE_ID).unwrap();
+    assert_eq!(state.status, Some(InstanceStatus::Stopped));
+    let err = Blockchain::check_tx(&snapshot, &gen_inc_tx(1)).unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::ServiceNotActive));
+
+    // The service cannot be stopped twice.
+    let err = testkit.stop_service(SERVICE_ID).unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::ServiceNotActive));
+    assert_eq!(testkit.height(), Height(2));
+
+    testkit.restart_service(SERVICE_ID, ()).unwrap();
+    let snapshot = testkit.snapshot();
+    let state = snapshot.for_dispatcher().get_instance(SERVICE_ID).unwrap();
+    assert_eq!(state.status, Some(InstanceStatus::Active));
+
+    // The service data is retained.
+    testkit.create_block_with_transaction(gen_inc_tx(3));
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(8));
+    let err = testkit.restart_service(SERVICE_ID, ()).unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::ServiceNotStopped));
+}
+
+#[test]
+fn test_deploy_artifact_and_start_service() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_rust_service(CounterService)
+        .create();
+    let artifact = CounterService.artifact_id();
+
+    let err = testkit
+        .start_service(CounterService.default_instance())
+        .unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::ArtifactNotDeployed));
+    assert_eq!(testkit.height(), Height(0));
+
+    testkit.deploy_artifact(artifact.clone(), ()).unwrap();
+    let snapshot = testkit.snapshot();
+    let state = snapshot.for_dispatcher().get_artifact(&artifact).unwrap();
+    assert_eq!(state.status, ArtifactStatus::Active);
+    let err = testkit.deploy_artifact(artifact, ()).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ArtifactAlreadyDeployed)
+    );
+
+    testkit
+        .start_service(CounterService.default_instance())
+        .unwrap();
+    testkit.create_block_with_transaction(gen_inc_tx(5));
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(5));
+
+    let err = testkit
+        .start_service(CounterService.default_instance())
+        .unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::ServiceIdExists));
+    assert_eq!(testkit.height(), Height(3));
+}
+
+#[test]
+fn test_restart() {
+    let (mut testkit, _) = init_testkit();
+    testkit.create_block_with_transaction(gen_inc_tx(5));
+    testkit.stop_service(SERVICE_ID).unwrap();
+    let tx = gen_inc_tx(3);
+
+    let us = testkit.us();
+    let mut testkit = testkit.restart(RustRuntime::builder().with_factory(CounterService));
+    assert_eq!(testkit.height(), Height(2));
+    assert_eq!(testkit.us(), us);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(5));
+
+    // The dispatcher restores the status of the stopped service.
+    let snapshot = testkit.snapshot();
+    let state = snapshot.for_dispatcher().get_instance(SERVICE_ID).unwrap();
+    assert_eq!(state.status, Some(InstanceStatus::Stopped));
+    let err = Blockchain::check_tx(&snapshot, &tx).unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::ServiceNotActive));
+
+    // The service is operational after it is resumed.
+    testkit.restart_service(SERVICE_ID, ()).unwrap();
+    testkit.create_block_with_transaction(tx);
+    assert_eq!(get_schema(&testkit.snapshot()).counter.get(), Some(8));
+}
+
+fn golden_scenario(increments: &[u64]) -> TestKit {
+    let (mut testkit, _) = init_testkit();
+    let keys = gen_keypair();
+    let txs = increments
+        .iter()
+        .map(|&by| keys.increment(SERVICE_ID, by))
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(txs);
+    testkit
+}
+
+fn golden_path(test_name: &str) -> PathBuf {
+    env::temp_dir().join(format!("{}-{}.json", test_name, process::id()))
+}
+
+#[test]
+fn test_golden_state_hashes() {
+    let path = golden_path("golden_state_hashes");
+    let index_name = format!("{}.counter", SERVICE_NAME);
+
+    let hashes = golden_scenario(&[1, 5]).state_hashes();
+    assert!(hashes.services[SERVICE_NAME]
+        .indexes
+        .contains_key(&index_name));
+    // A missing golden file is not created implicitly.
+    let res = panic::catch_unwind(|| hashes.assert_golden(&path));
+    let err = res.unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.contains("does not exist"));
+    assert!(!path.exists());
+
+    hashes.write_golden(&path);
+    hashes.assert_golden(&path);
+
+    // Another testkit with the same scenario has the same service state, although
+    // the aggregated state hashes differ because of the random validator keys.
+    let other_hashes = golden_scenario(&[1, 5]).state_hashes();
+    assert_ne!(other_hashes.state_hash, hashes.state_hash);
+    other_hashes.assert_golden(&path);
+
+    // A different scenario leads to the mismatch.
+    let other_hashes = golden_scenario(&[2, 5]).state_hashes();
+    let res = panic::catch_unwind(|| other_hashes.assert_golden(&path));
+    fs::remove_file(&path).unwrap();
+    let err = res.unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.contains(&format!("index `{}`: expected", index_name)));
+}
+
+#[test]
+fn test_mock_oracle() {
+    const ORACLE_ID: u32 = SERVICE_ID + 1;
+    const ORACLE_NAME: &str = "oracle";
+
+    let oracle = MockOracle::<u64>::new("mock-oracle").with_value(1);
+    let feed = oracle.feed();
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(2)
+        .with_default_rust_service(CounterService)
+        .with_artifact(oracle.artifact_id())
+        .with_instance(
+            oracle
+                .artifact_id()
+                .into_default_instance(ORACLE_ID, ORACLE_NAME),
+        )
+        .with_rust_service(oracle)
+        .create();
+
+    let oracle_value = |testkit: &TestKit| {
+        let snapshot = testkit.snapshot();
+        let schema = MockOracleSchema::<_, u64>::new(snapshot.for_service(ORACLE_NAME).unwrap());
+        schema.value.get()
+    };
+    // The initial value is recorded when the oracle is initialized.
+    assert_eq!(oracle_value(&testkit), Some(1));
+
+    feed.set(5);
+    assert_eq!(oracle_value(&testkit), Some(1));
+    testkit.create_block();
+    assert_eq!(oracle_value(&testkit), Some(5));
+    testkit.create_block();
+    assert_eq!(oracle_value(&testkit), Some(5));
+
+    // The value is reported on behalf of each validator, and the schema layout
+    // mirrors the one of the `exonum-time` oracle.
+    let snapshot = testkit.snapshot();
+    let access = snapshot.for_service(ORACLE_NAME).unwrap();
+    let schema = MockOracleSchema::<_, u64>::new(access.clone());
+    for validator in testkit.network().validators() {
+        let key = validator.public_keys().service_key;
+        assert_eq!(schema.validators_values.get(&key), Some(5));
+    }
+    assert_eq!(
+        access.clone().get_proof_entry::<_, u64>("time").get(),
+        Some(5)
+    );
+    let validators_times = access.get_proof_map::<_, PublicKey, u64>("validators_times");
+    assert_eq!(validators_times.values().count(), 2);
+
+    feed.clear();
+    testkit.create_block();
+    assert_eq!(oracle_value(&testkit), None);
+    let snapshot = testkit.snapshot();
+    let schema = MockOracleSchema::<_, u64>::new(snapshot.for_service(ORACLE_NAME).unwrap());
+    assert_eq!(schema.validators_values.values().count(), 0);
+
+    // The oracle does not have transactions.
+    let keypair = gen_keypair();
+    let tx = keypair.increment(ORACLE_ID, 1);
+    let block = testkit.create_block_with_transaction(tx);
+    let expected_err = ErrorMatch::from_fail(&CommonError::NoSuchMethod).for_service(ORACLE_ID);
+    assert_eq!(block[0].status(), expected_err);
+}
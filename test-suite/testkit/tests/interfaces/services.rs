@@ -168,6 +168,7 @@ impl AnyCall {
             inner: AnyTx {
                 call_info,
                 arguments: arguments.into_bytes(),
+                valid_until: None,
             },
             fallthrough_auth: false,
             interface_name: String::default(),